@@ -0,0 +1,57 @@
+/* C API of the rmcp client. Strings are NUL-terminated UTF-8; structured
+ * values are JSON. Functions returning a pointer return NULL on failure,
+ * including a panic inside the library, with a message available from
+ * rmcp_last_error(). */
+#ifndef RMCP_H
+#define RMCP_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct RmcpClient RmcpClient;
+
+/* Called with the method and JSON params of each server notification. Calls
+ * come one at a time, in order, from a thread dedicated to the client; the
+ * callback may call into the library, but must not free its own client. */
+typedef void (*RmcpNotificationCallback)(const char *method,
+                                         const char *params_json,
+                                         void *user_data);
+
+/* Message of the last error on this thread, or NULL. Valid until the next
+ * call into the library on this thread; do not free. */
+const char *rmcp_last_error(void);
+
+/* Release a string returned by the library. */
+void rmcp_string_free(char *string);
+
+/* Start `program` with `args_json` (a JSON array of strings, or NULL) and
+ * connect to it over stdio. `callback` may be NULL. */
+RmcpClient *rmcp_client_connect_command(const char *program,
+                                        const char *args_json,
+                                        RmcpNotificationCallback callback,
+                                        void *user_data);
+
+/* Connect to a streamable HTTP server. `callback` may be NULL. */
+RmcpClient *rmcp_client_connect_http(const char *url,
+                                     RmcpNotificationCallback callback,
+                                     void *user_data);
+
+/* The server's tools as a JSON array; free with rmcp_string_free. */
+char *rmcp_client_list_tools(const RmcpClient *client);
+
+/* Call a tool with `arguments_json` (a JSON object, or NULL). Returns the
+ * call result as JSON; free with rmcp_string_free. */
+char *rmcp_client_call_tool(const RmcpClient *client,
+                            const char *name,
+                            const char *arguments_json);
+
+/* Close the session and free the client. Returns once the notification
+ * callback has been called for the last time. */
+void rmcp_client_free(RmcpClient *client);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RMCP_H */
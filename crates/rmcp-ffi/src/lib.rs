@@ -0,0 +1,527 @@
+//! C ABI for the rmcp client, so that hosts written in C, C++ or Swift can
+//! embed it. See `include/rmcp.h` for the declarations.
+//!
+//! Everything crosses the boundary as NUL-terminated UTF-8 strings, JSON
+//! for structured values. Functions that can fail return `NULL` and leave a
+//! message for [`rmcp_last_error`], which is also how a panic inside the
+//! library is reported. Strings returned by the library are owned by the
+//! caller and released with [`rmcp_string_free`].
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString, c_char, c_void},
+    panic::{AssertUnwindSafe, catch_unwind},
+    ptr,
+    sync::mpsc,
+    thread::JoinHandle,
+};
+
+use rmcp::{
+    RoleClient, Service, ServiceError,
+    blocking::Client,
+    model::{
+        CallToolRequestParams, ClientInfo, ClientResult, ErrorData, JsonObject, ServerNotification,
+        ServerRequest,
+    },
+    service::{NotificationContext, RequestContext},
+    transport::{IntoTransport, StreamableHttpClientTransport, TokioChildProcess},
+};
+
+/// Called with the method and the JSON params of each notification sent by
+/// the server.
+///
+/// Calls come one at a time, in order, from a thread dedicated to the
+/// client, so the callback may call into the library, except to free its
+/// own client.
+pub type RmcpNotificationCallback = Option<
+    unsafe extern "C" fn(method: *const c_char, params_json: *const c_char, user_data: *mut c_void),
+>;
+
+/// A connected client, opaque to C.
+pub struct RmcpClient {
+    client: Client<Notifier>,
+    dispatcher: Option<JoinHandle<()>>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(error: impl std::fmt::Display) {
+    let message =
+        CString::new(error.to_string().replace('\0', " ")).expect("nul bytes were replaced");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// The message of the last error on this thread, or `NULL`.
+///
+/// The pointer stays valid until the next call into the library on this
+/// thread; it must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn rmcp_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Release a string returned by the library. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `string` must come from this library and not have been freed already.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rmcp_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+/// Reads `string` as UTF-8, `None` for `NULL`.
+///
+/// # Safety
+///
+/// `string` must be `NULL` or a valid NUL-terminated string.
+unsafe fn read_str<'a>(string: *const c_char, name: &str) -> Result<Option<&'a str>, String> {
+    if string.is_null() {
+        return Ok(None);
+    }
+    unsafe { CStr::from_ptr(string) }
+        .to_str()
+        .map(Some)
+        .map_err(|error| format!("{name} is not valid UTF-8: {error}"))
+}
+
+unsafe fn required_str<'a>(string: *const c_char, name: &str) -> Result<&'a str, String> {
+    unsafe { read_str(string, name) }?.ok_or_else(|| format!("{name} is NULL"))
+}
+
+fn into_c_string(value: impl serde::Serialize) -> Result<*mut c_char, String> {
+    let json = serde_json::to_string(&value).map_err(|error| error.to_string())?;
+    CString::new(json)
+        .map(CString::into_raw)
+        .map_err(|error| error.to_string())
+}
+
+/// Runs `f`, turning its error or panic into the last error and a `NULL`
+/// result, since unwinding into C is undefined behavior.
+fn ffi_call<T>(f: impl FnOnce() -> Result<*mut T, String>) -> *mut T {
+    clear_last_error();
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(error)) => {
+            set_last_error(error);
+            ptr::null_mut()
+        }
+        Err(panic) => {
+            set_last_error(format_args!("panic in rmcp: {}", panic_message(&*panic)));
+            ptr::null_mut()
+        }
+    }
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Forwards server notifications to the thread calling the C callback, so
+/// the callback never runs on the client's runtime.
+struct Notifier {
+    notifications: Option<mpsc::Sender<(CString, CString)>>,
+}
+
+struct UserData(*mut c_void);
+
+impl UserData {
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+// SAFETY: the caller of the connect functions promises that the callback may
+// be called with `user_data` from any thread.
+unsafe impl Send for UserData {}
+
+/// Start the thread calling `callback` for each notification, if there is a
+/// callback.
+fn spawn_dispatcher(
+    callback: RmcpNotificationCallback,
+    user_data: *mut c_void,
+) -> Result<(Notifier, Option<JoinHandle<()>>), String> {
+    let Some(callback) = callback else {
+        return Ok((
+            Notifier {
+                notifications: None,
+            },
+            None,
+        ));
+    };
+    let user_data = UserData(user_data);
+    let (sender, receiver) = mpsc::channel::<(CString, CString)>();
+    let dispatcher = std::thread::Builder::new()
+        .name("rmcp-notifications".into())
+        .spawn(move || {
+            // ends once the client, and with it the sender, is dropped
+            for (method, params) in receiver {
+                // SAFETY: the callback was registered with this user data
+                unsafe { callback(method.as_ptr(), params.as_ptr(), user_data.get()) };
+            }
+        })
+        .map_err(|error| error.to_string())?;
+    Ok((
+        Notifier {
+            notifications: Some(sender),
+        },
+        Some(dispatcher),
+    ))
+}
+
+impl Service<RoleClient> for Notifier {
+    async fn handle_request(
+        &self,
+        request: ServerRequest,
+        context: RequestContext<RoleClient>,
+    ) -> Result<ClientResult, ErrorData> {
+        ().handle_request(request, context).await
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ServerNotification,
+        context: NotificationContext<RoleClient>,
+    ) -> Result<(), ErrorData> {
+        let Some(notifications) = &self.notifications else {
+            return Ok(());
+        };
+        let mut notification = serde_json::to_value(notification)
+            .map_err(|error| ErrorData::internal_error(error.to_string(), None))?;
+        let method = notification["method"]
+            .as_str()
+            .unwrap_or_default()
+            .to_owned();
+        let mut params = notification["params"].take();
+        // the service moved `_meta` into the context
+        if let Some(params) = params.as_object_mut() {
+            params.remove("_meta");
+            if !context.meta.is_empty() {
+                params.insert("_meta".to_owned(), context.meta.0.into());
+            }
+        }
+        if let (Ok(method), Ok(params)) = (CString::new(method), CString::new(params.to_string())) {
+            // the dispatcher only stops once this sender is gone
+            let _ = notifications.send((method, params));
+        }
+        Ok(())
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo::default()
+    }
+}
+
+fn connect<F, T, Err, E, A>(
+    transport: F,
+    callback: RmcpNotificationCallback,
+    user_data: *mut c_void,
+) -> *mut RmcpClient
+where
+    F: FnOnce() -> Result<T, Err>,
+    T: IntoTransport<RoleClient, E, A>,
+    Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    ffi_call(|| {
+        let (notifier, dispatcher) = spawn_dispatcher(callback, user_data)?;
+        let client =
+            Client::try_connect_with(notifier, transport).map_err(|error| error.to_string())?;
+        Ok(Box::into_raw(Box::new(RmcpClient { client, dispatcher })))
+    })
+}
+
+/// Start `program` and connect to it over stdio.
+///
+/// `args_json` is a JSON array of strings, or `NULL` for no arguments.
+/// `callback` may be `NULL`; otherwise it is called with `user_data` for
+/// each notification, see [`RmcpNotificationCallback`], until the client
+/// is freed. Returns `NULL` on failure.
+///
+/// # Safety
+///
+/// String arguments must be `NULL` or valid NUL-terminated strings, and
+/// `user_data` must be usable from any thread.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rmcp_client_connect_command(
+    program: *const c_char,
+    args_json: *const c_char,
+    callback: RmcpNotificationCallback,
+    user_data: *mut c_void,
+) -> *mut RmcpClient {
+    let command = (|| {
+        let program = unsafe { required_str(program, "program") }?;
+        let args: Vec<String> = match unsafe { read_str(args_json, "args_json") }? {
+            Some(args) => serde_json::from_str(args)
+                .map_err(|error| format!("args_json is not an array of strings: {error}"))?,
+            None => Vec::new(),
+        };
+        let mut command = tokio::process::Command::new(program);
+        command.args(args);
+        Ok::<_, String>(command)
+    })();
+    match command {
+        Ok(command) => connect(|| TokioChildProcess::new(command), callback, user_data),
+        Err(error) => ffi_call(|| Err(error)),
+    }
+}
+
+/// Connect to a streamable HTTP server at `url`.
+///
+/// See [`rmcp_client_connect_command`] for `callback` and `user_data`.
+/// Returns `NULL` on failure.
+///
+/// # Safety
+///
+/// `url` must be a valid NUL-terminated string, and `user_data` must be
+/// usable from any thread.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rmcp_client_connect_http(
+    url: *const c_char,
+    callback: RmcpNotificationCallback,
+    user_data: *mut c_void,
+) -> *mut RmcpClient {
+    match unsafe { required_str(url, "url") } {
+        Ok(url) => {
+            let url = url.to_owned();
+            connect(
+                || Ok::<_, std::convert::Infallible>(StreamableHttpClientTransport::from_uri(url)),
+                callback,
+                user_data,
+            )
+        }
+        Err(error) => ffi_call(|| Err(error)),
+    }
+}
+
+/// The tools of the server, as a JSON array, or `NULL` on failure.
+///
+/// # Safety
+///
+/// `client` must be a live client returned by a connect function.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rmcp_client_list_tools(client: *const RmcpClient) -> *mut c_char {
+    ffi_call(|| {
+        let client = unsafe { client.as_ref() }.ok_or("client is NULL")?;
+        let tools = client
+            .client
+            .list_all_tools()
+            .map_err(|error: ServiceError| error.to_string())?;
+        into_c_string(tools)
+    })
+}
+
+/// Call the tool `name` with `arguments_json`, a JSON object or `NULL`.
+///
+/// Returns the JSON of the call result, which may be a tool error with
+/// `isError` set, or `NULL` if the call itself failed.
+///
+/// # Safety
+///
+/// `client` must be a live client returned by a connect function, and
+/// string arguments `NULL` or valid NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rmcp_client_call_tool(
+    client: *const RmcpClient,
+    name: *const c_char,
+    arguments_json: *const c_char,
+) -> *mut c_char {
+    ffi_call(|| {
+        let client = unsafe { client.as_ref() }.ok_or("client is NULL")?;
+        let name = unsafe { required_str(name, "name") }?;
+        let arguments = match unsafe { read_str(arguments_json, "arguments_json") }? {
+            Some(arguments) => Some(
+                serde_json::from_str::<JsonObject>(arguments)
+                    .map_err(|error| format!("arguments_json is not an object: {error}"))?,
+            ),
+            None => None,
+        };
+        let result = client
+            .client
+            .call_tool(CallToolRequestParams {
+                meta: None,
+                name: name.to_owned().into(),
+                arguments,
+                task: None,
+            })
+            .map_err(|error| error.to_string())?;
+        into_c_string(result)
+    })
+}
+
+/// Close the session and free the client. `NULL` is ignored.
+///
+/// Returns once the notification callback has been called for the last
+/// time.
+///
+/// # Safety
+///
+/// `client` must come from a connect function and not have been freed
+/// already, and this must not be called from its notification callback.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rmcp_client_free(client: *mut RmcpClient) {
+    if client.is_null() {
+        return;
+    }
+    let RmcpClient { client, dispatcher } = *unsafe { Box::from_raw(client) };
+    ffi_call(|| {
+        drop(client);
+        if let Some(dispatcher) = dispatcher {
+            let _ = dispatcher.join();
+        }
+        Ok(ptr::null_mut::<()>())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use rmcp::{
+        handler::server::{builder::Server, wrapper::Parameters},
+        model::{LoggingLevel, LoggingMessageNotificationParam},
+    };
+
+    use super::*;
+
+    /// Add two numbers.
+    #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+    struct AddArgs {
+        a: i64,
+        b: i64,
+    }
+
+    type Received = Mutex<Vec<(String, String)>>;
+
+    unsafe extern "C" fn record(
+        method: *const c_char,
+        params_json: *const c_char,
+        user_data: *mut c_void,
+    ) {
+        let received = unsafe { &*(user_data as *const Received) };
+        let method = unsafe { CStr::from_ptr(method) }.to_str().unwrap();
+        let params = unsafe { CStr::from_ptr(params_json) }.to_str().unwrap();
+        received
+            .lock()
+            .unwrap()
+            .push((method.to_owned(), params.to_owned()));
+    }
+
+    fn take_string(string: *mut c_char) -> String {
+        assert!(!string.is_null(), "{:?}", last_error());
+        let value = unsafe { CStr::from_ptr(string) }
+            .to_str()
+            .unwrap()
+            .to_owned();
+        unsafe { rmcp_string_free(string) };
+        value
+    }
+
+    fn last_error() -> Option<String> {
+        let error = rmcp_last_error();
+        (!error.is_null()).then(|| {
+            unsafe { CStr::from_ptr(error) }
+                .to_str()
+                .unwrap()
+                .to_owned()
+        })
+    }
+
+    #[test]
+    fn test_list_and_call_tools() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (server_transport, client_transport) = tokio::io::duplex(4096);
+        let server = runtime.spawn(async move {
+            Server::builder()
+                .tool("add", |Parameters(args): Parameters<AddArgs>| async move {
+                    (args.a + args.b).to_string()
+                })
+                .serve(server_transport)
+                .await
+        });
+        let received = Arc::new(Received::default());
+        let client = connect(
+            || Ok::<_, std::convert::Infallible>(client_transport),
+            Some(record),
+            Arc::as_ptr(&received) as *mut c_void,
+        );
+        assert!(!client.is_null(), "{:?}", last_error());
+        let server = runtime.block_on(server).unwrap().unwrap();
+
+        let tools: serde_json::Value =
+            serde_json::from_str(&take_string(unsafe { rmcp_client_list_tools(client) })).unwrap();
+        assert_eq!(tools[0]["name"], "add");
+
+        let result = take_string(unsafe {
+            rmcp_client_call_tool(client, c"add".as_ptr(), cr#"{"a": 2, "b": 3}"#.as_ptr())
+        });
+        let result: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(result["content"][0]["text"], "5");
+
+        let failed = unsafe { rmcp_client_call_tool(client, c"add".as_ptr(), c"[1, 2]".as_ptr()) };
+        assert!(failed.is_null());
+        assert!(last_error().unwrap().contains("arguments_json"));
+
+        runtime
+            .block_on(
+                server.notify_logging_message(LoggingMessageNotificationParam {
+                    level: LoggingLevel::Info,
+                    logger: None,
+                    data: "indexed".into(),
+                }),
+            )
+            .unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while received.lock().unwrap().is_empty() && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(
+            received.lock().unwrap().as_slice(),
+            [(
+                "notifications/message".to_owned(),
+                r#"{"data":"indexed","level":"info"}"#.to_owned()
+            )]
+        );
+
+        // the blocking client panics when called from async code
+        let tools = runtime.block_on(async { unsafe { rmcp_client_list_tools(client) } });
+        assert!(tools.is_null());
+        assert!(last_error().unwrap().starts_with("panic in rmcp: "));
+
+        unsafe { rmcp_client_free(client) };
+    }
+
+    #[test]
+    fn test_connect_failure_sets_last_error() {
+        let client = unsafe {
+            rmcp_client_connect_command(
+                c"/nonexistent/mcp-server".as_ptr(),
+                ptr::null(),
+                None,
+                ptr::null_mut(),
+            )
+        };
+        assert!(client.is_null());
+        assert!(last_error().is_some());
+
+        let client =
+            unsafe { rmcp_client_connect_command(ptr::null(), ptr::null(), None, ptr::null_mut()) };
+        assert!(client.is_null());
+        assert_eq!(last_error().as_deref(), Some("program is NULL"));
+    }
+}
@@ -21,8 +21,13 @@ mod tool_router;
 /// | :-                | :-                         | :-    |
 /// | `name`            | `String`                   | The name of the tool. If not provided, it defaults to the function name. |
 /// | `description`     | `String`                   | A description of the tool. The document of this function will be used. |
-/// | `input_schema`    | `Expr`                     | A JSON Schema object defining the expected parameters for the tool. If not provide, if will use the json schema of its argument with type `Parameters<T>` |
+/// | `input_schema`    | `Expr`                     | A JSON Schema object defining the expected parameters for the tool, or the path of a function returning one. If not provide, if will use the json schema of its argument with type `Parameters<T>`. A handwritten schema is checked against `T` in debug builds, see `check_input_schema` |
 /// | `annotations`     | `ToolAnnotationsAttribute` | Additional tool information. Defaults to `None`. |
+/// | `schema_transform` | `Expr`                    | A `Fn(&mut JsonObject)` applied to the input and output schemas of the tool, see `rmcp::handler::server::schema_transform`. |
+/// | `version`         | `String`                   | The version of the tool's contract, stored in `_meta`. |
+/// | `deprecated`      | `ToolDeprecationAttribute` | Marks the tool as deprecated: `deprecated` alone, or `deprecated(message = "...", replacement = "other_tool")`. |
+/// | `task`            | `ToolTaskAttribute`        | Lets the tool run as a task when the caller asks for one: `task` alone, `task(required)` to refuse direct calls, `threshold_ms = ...` to report calls finishing within that time as already completed tasks, and `resumable` to let a stored unfinished task be resumed after a restart. Needs `#[task_handler]`. |
+/// | `blocking`        | `bool`                     | For synchronous tools doing heavy compute or blocking IO: runs the body on tokio's blocking thread pool through `rmcp::handler::server::tool::run_blocking`, so the session keeps serving other messages meanwhile. The server type must be `Clone + Send + 'static`, and the arguments `Send + 'static`. |
 ///
 /// ## Example
 ///
@@ -52,6 +57,7 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> TokenStream {
 /// | :-        | :-            | :-    |
 /// | `router`  | `Ident`       | The name of the router function to be generated. Defaults to `tool_router`. |
 /// | `vis`     | `Visibility`  | The visibility of the generated router function. Defaults to empty. |
+/// | `schema_transform` | `Expr` | A `Fn(&mut JsonObject)` applied to the schemas of every tool in the router, see `ToolRouter::transform_schemas`. |
 ///
 /// ## Example
 ///
@@ -272,6 +278,10 @@ pub fn prompt_handler(attr: TokenStream, input: TokenStream) -> TokenStream {
 /// `self.processor` field holding an `Arc<Mutex<OperationProcessor>>`, but it can be customized
 /// via `#[task_handler(processor = ...)]`. Because the macro captures `self` inside spawned
 /// futures, the handler type must implement [`Clone`].
+///
+/// Each task gets a `TaskProgress` in its request extensions, reported to the caller when it
+/// sent a progress token. The generated `on_progress` routes the progress of requests nested in
+/// a task to it.
 #[proc_macro_attribute]
 pub fn task_handler(attr: TokenStream, input: TokenStream) -> TokenStream {
     task_handler::task_handler(attr.into(), input.into())
@@ -77,19 +77,38 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
                 };
                 let task_id = context.id.to_string();
                 let operation_name = request.name.to_string();
+                let execution = self
+                    .get_tool(&operation_name)
+                    .and_then(|tool| tool.execution)
+                    .unwrap_or_default();
+                let threshold = execution.threshold;
                 let future_request = request.clone();
-                let future_context = context.clone();
+                let mut future_context = context.clone();
                 let server = self.clone();
+                let progress = match context.meta.get_progress_token() {
+                    Some(token) => rmcp::task_manager::TaskProgress::notifying(
+                        operation_name.clone(),
+                        context.peer.clone(),
+                        token,
+                    ),
+                    None => rmcp::task_manager::TaskProgress::new(operation_name.clone()),
+                };
+                future_context.extensions.insert(progress.clone());
 
-                let descriptor = OperationDescriptor::new(task_id.clone(), operation_name)
+                let mut descriptor = OperationDescriptor::new(task_id.clone(), operation_name)
                     .with_context(context)
+                    .with_progress(progress.clone())
                     .with_client_request(rmcp::model::ClientRequest::CallToolRequest(
                         rmcp::model::Request::new(request),
                     ));
+                if execution.resumable {
+                    descriptor = descriptor.with_resumable();
+                }
 
                 let task_result_id = task_id.clone();
                 let future = Box::pin(async move {
                     let result = server.call_tool(future_request, future_context).await;
+                    progress.complete();
                     Ok(
                         Box::new(ToolCallTaskResult::new(task_result_id, result))
                             as Box<dyn OperationResultTransport>,
@@ -105,11 +124,28 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
                         None,
                     ))?;
 
+                // a tool finishing within its threshold is reported as done right away
+                let finished = match threshold {
+                    Some(threshold) => {
+                        rmcp::task_manager::OperationProcessor::wait_for_completion(
+                            &(#processor),
+                            &task_id,
+                            threshold,
+                        )
+                        .await
+                    }
+                    None => None,
+                };
+                let (status, status_message) = match finished {
+                    Some(status) => (status, None),
+                    None => (rmcp::model::TaskStatus::Working, Some("Task accepted".to_string())),
+                };
+
                 let timestamp = current_timestamp();
                 let task = rmcp::model::Task {
                     task_id,
-                    status: rmcp::model::TaskStatus::Working,
-                    status_message: Some("Task accepted".to_string()),
+                    status,
+                    status_message,
                     created_at: timestamp.clone(),
                     last_updated_at: Some(timestamp),
                     ttl: None,
@@ -132,25 +168,12 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
                 use rmcp::task_manager::current_timestamp;
                 let task_id = request.task_id.clone();
                 let mut processor = (#processor).lock().await;
-                processor.collect_completed_results();
+                processor.receive_completed_results();
 
                 // Check completed results first
                 let completed = processor.peek_completed().iter().rev().find(|r| r.descriptor.operation_id == task_id);
                 if let Some(completed_result) = completed {
-                    // Determine Finished vs Failed
-                    let status = match &completed_result.result {
-                        Ok(boxed) => {
-                            if let Some(tool) = boxed.as_any().downcast_ref::<rmcp::task_manager::ToolCallTaskResult>() {
-                                match &tool.result {
-                                    Ok(_) => rmcp::model::TaskStatus::Completed,
-                                    Err(_) => rmcp::model::TaskStatus::Failed,
-                                }
-                            } else {
-                                rmcp::model::TaskStatus::Completed
-                            }
-                        }
-                        Err(_) => rmcp::model::TaskStatus::Failed,
-                    };
+                    let status = completed_result.status();
                     let timestamp = current_timestamp();
                     let task = rmcp::model::Task {
                         task_id,
@@ -180,7 +203,13 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
                     return Ok(rmcp::model::GetTaskInfoResult { task: Some(task) });
                 }
 
-                Ok(rmcp::model::GetTaskInfoResult { task: None })
+                if processor.is_expired(&task_id) {
+                    return Err(McpError::task_expired(task_id));
+                }
+
+                // possibly run by another instance sharing the task store
+                let task = processor.stored_task(&task_id).map(|record| record.task);
+                Ok(rmcp::model::GetTaskInfoResult { task })
             }
         };
         item_impl.items.push(syn::parse2::<ImplItem>(get_info_fn)?);
@@ -200,7 +229,7 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
                     // Scope the lock so we can await outside if needed
                     {
                         let mut processor = (#processor).lock().await;
-                        processor.collect_completed_results();
+                        processor.receive_completed_results();
 
                         if let Some(task_result) = processor.take_completed_result(&task_id) {
                             match task_result.result {
@@ -234,7 +263,32 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
                         // Not completed yet: if not running, return not found
                         let running = processor.list_running();
                         if !running.iter().any(|id| id == &task_id) {
-                            return Err(McpError::resource_not_found(format!("task not found: {}", task_id), None));
+                            if processor.is_expired(&task_id) {
+                                return Err(McpError::task_expired(task_id));
+                            }
+                            // possibly run by another instance sharing the task store
+                            let Some(record) = processor.stored_task(&task_id) else {
+                                return Err(McpError::task_not_found(task_id));
+                            };
+                            match (record.task.status, record.result) {
+                                (rmcp::model::TaskStatus::Completed, Some(value)) => {
+                                    return Ok(rmcp::model::TaskResult {
+                                        content_type: "application/json".to_string(),
+                                        value,
+                                        summary: None,
+                                    });
+                                }
+                                (status, _) if status.is_terminal() => {
+                                    return Err(McpError::internal_error(
+                                        format!(
+                                            "task failed: {}",
+                                            record.task.status_message.unwrap_or_else(|| format!("{status:?}"))
+                                        ),
+                                        None,
+                                    ));
+                                }
+                                _ => {}
+                            }
                         }
                     }
 
@@ -247,6 +301,20 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
             .push(syn::parse2::<ImplItem>(get_result_fn)?);
     }
 
+    if !has_method("on_progress", &item_impl) {
+        let progress_fn = quote! {
+            async fn on_progress(
+                &self,
+                notification: rmcp::model::ProgressNotificationParam,
+                _context: rmcp::service::NotificationContext<rmcp::RoleServer>,
+            ) {
+                // progress of requests nested in a task
+                (#processor).lock().await.route_progress(&notification);
+            }
+        };
+        item_impl.items.push(syn::parse2::<ImplItem>(progress_fn)?);
+    }
+
     if !has_method("cancel_task", &item_impl) {
         let cancel_fn = quote! {
             async fn cancel_task(
@@ -256,7 +324,7 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
             ) -> Result<(), McpError> {
                 let task_id = request.task_id;
                 let mut processor = (#processor).lock().await;
-                processor.collect_completed_results();
+                processor.receive_completed_results();
 
                 if processor.cancel_task(&task_id) {
                     return Ok(());
@@ -267,8 +335,11 @@ pub fn task_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
                 if exists_completed {
                     return Err(McpError::invalid_request(format!("task already completed: {}", task_id), None));
                 }
+                if processor.is_expired(&task_id) {
+                    return Err(McpError::task_expired(task_id));
+                }
 
-                Err(McpError::resource_not_found(format!("task not found: {}", task_id), None))
+                Err(McpError::task_not_found(task_id))
             }
         };
         item_impl.items.push(syn::parse2::<ImplItem>(cancel_fn)?);
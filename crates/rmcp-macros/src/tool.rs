@@ -1,4 +1,4 @@
-use darling::{FromMeta, ast::NestedMeta};
+use darling::{FromMeta, ast::NestedMeta, util::Override};
 use proc_macro2::{Span, TokenStream};
 use quote::{ToTokens, format_ident, quote};
 use syn::{Expr, Ident, ImplItemFn, LitStr, ReturnType, parse_quote};
@@ -83,16 +83,47 @@ pub struct ToolAttribute {
     /// Human readable title of tool
     pub title: Option<String>,
     pub description: Option<String>,
-    /// A JSON Schema object defining the expected parameters for the tool
+    /// A JSON Schema object defining the expected parameters for the tool,
+    /// or the path of a function returning one
     pub input_schema: Option<Expr>,
     /// An optional JSON Schema object defining the structure of the tool's output
     pub output_schema: Option<Expr>,
+    /// An optional `Fn(&mut JsonObject)` applied to the input and output schemas
+    pub schema_transform: Option<Expr>,
     /// Optional additional tool information.
     pub annotations: Option<ToolAnnotationsAttribute>,
     /// Optional icons for the tool
     pub icons: Option<Expr>,
     /// Optional metadata for the tool
     pub meta: Option<Expr>,
+    /// Optional version of the tool's contract
+    pub version: Option<String>,
+    /// Marks the tool as deprecated, optionally with a message and replacement
+    pub deprecated: Option<Override<ToolDeprecationAttribute>>,
+    /// Lets the tool be called as a task, optionally requiring it
+    pub task: Option<Override<ToolTaskAttribute>>,
+    /// Runs the (synchronous) tool without stalling the runtime's worker
+    pub blocking: bool,
+}
+
+#[derive(FromMeta, Default, Debug)]
+#[darling(default)]
+pub struct ToolTaskAttribute {
+    /// The tool must be called as a task
+    pub required: bool,
+    /// How long a task-augmented call waits for the tool before answering
+    pub threshold_ms: Option<u64>,
+    /// An unfinished task may be resumed after a restart
+    pub resumable: bool,
+}
+
+#[derive(FromMeta, Default, Debug)]
+#[darling(default)]
+pub struct ToolDeprecationAttribute {
+    /// Why the tool is deprecated
+    pub message: Option<String>,
+    /// Name of the tool replacing this one
+    pub replacement: Option<String>,
 }
 
 pub struct ResolvedToolAttribute {
@@ -104,6 +135,9 @@ pub struct ResolvedToolAttribute {
     pub annotations: Expr,
     pub icons: Option<Expr>,
     pub meta: Option<Expr>,
+    pub version: Option<String>,
+    pub deprecation: Option<ToolDeprecationAttribute>,
+    pub task: Option<ToolTaskAttribute>,
 }
 
 impl ResolvedToolAttribute {
@@ -117,6 +151,9 @@ impl ResolvedToolAttribute {
             annotations,
             icons,
             meta,
+            version,
+            deprecation,
+            task,
         } = self;
         let description = if let Some(description) = description {
             quote! { Some(#description.into()) }
@@ -143,6 +180,38 @@ impl ResolvedToolAttribute {
         } else {
             quote! { None }
         };
+        let version = version.map(|version| quote! { .with_version(#version) });
+        let deprecation = deprecation.map(
+            |ToolDeprecationAttribute {
+                 message,
+                 replacement,
+             }| {
+                let message = message.map(|message| quote! { .message(#message) });
+                let replacement =
+                    replacement.map(|replacement| quote! { .replacement(#replacement) });
+                quote! {
+                    .with_deprecation(rmcp::model::ToolDeprecation::new() #message #replacement)
+                }
+            },
+        );
+        let task = task.map(
+            |ToolTaskAttribute {
+                 required,
+                 threshold_ms,
+                 resumable,
+             }| {
+                let task_support = if required {
+                    quote! { rmcp::model::TaskSupport::Required }
+                } else {
+                    quote! { rmcp::model::TaskSupport::Optional }
+                };
+                let threshold = threshold_ms.map(|threshold_ms| {
+                    quote! { .with_task_threshold(::std::time::Duration::from_millis(#threshold_ms)) }
+                });
+                let resumable = resumable.then(|| quote! { .with_resumable_task() });
+                quote! { .with_task_support(#task_support) #threshold #resumable }
+            },
+        );
         let doc_comment = format!("Generated tool metadata function for {name}");
         let doc_attr: syn::Attribute = parse_quote!(#[doc = #doc_comment]);
         let tokens = quote! {
@@ -156,8 +225,12 @@ impl ResolvedToolAttribute {
                     output_schema: #output_schema,
                     annotations: #annotations,
                     icons: #icons,
+                    execution: None,
                     meta: #meta,
                 }
+                #version
+                #deprecation
+                #task
             }
         };
         syn::parse2::<ImplItemFn>(tokens)
@@ -218,26 +291,43 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
     let fn_ident = &fn_item.sig.ident;
 
     let tool_attr_fn_ident = format_ident!("{}_tool_attr", fn_ident);
+    let tool_name = attribute.name.unwrap_or_else(|| fn_ident.to_string());
+    // try to find some parameters wrapper in the function
+    let params_ty = crate::common::find_parameters_type_impl(&fn_item);
     let input_schema_expr = if let Some(input_schema) = attribute.input_schema {
-        input_schema
-    } else {
-        // try to find some parameters wrapper in the function
-        let params_ty = crate::common::find_parameters_type_impl(&fn_item);
+        // a bare path names a function building the schema
+        let input_schema = match input_schema {
+            Expr::Path(path) => quote! { #path() },
+            expr => quote! { #expr },
+        };
+        let input_schema = quote! {
+            ::std::convert::Into::<std::sync::Arc<rmcp::model::JsonObject>>::into(#input_schema)
+        };
         if let Some(params_ty) = params_ty {
-            // if found, use the Parameters schema
+            // the schema is handwritten, make sure the parameters still fit it
             syn::parse2::<Expr>(quote! {
-                rmcp::handler::server::common::schema_for_type::<#params_ty>()
+                rmcp::handler::server::common::debug_check_input_schema::<#params_ty>(
+                    #tool_name,
+                    #input_schema,
+                )
             })?
         } else {
-            // if not found, use a default empty JSON schema object
-            // TODO: should be updated according to the new specifications
-            syn::parse2::<Expr>(quote! {
-                std::sync::Arc::new(serde_json::json!({
-                    "type": "object",
-                    "properties": {}
-                }).as_object().unwrap().clone())
-            })?
+            syn::parse2::<Expr>(input_schema)?
         }
+    } else if let Some(params_ty) = params_ty {
+        // if found, use the Parameters schema
+        syn::parse2::<Expr>(quote! {
+            rmcp::handler::server::common::schema_for_type::<#params_ty>()
+        })?
+    } else {
+        // if not found, use a default empty JSON schema object
+        // TODO: should be updated according to the new specifications
+        syn::parse2::<Expr>(quote! {
+            std::sync::Arc::new(serde_json::json!({
+                "type": "object",
+                "properties": {}
+            }).as_object().unwrap().clone())
+        })?
     };
     let annotations_expr = if let Some(annotations) = attribute.annotations {
         let ToolAnnotationsAttribute {
@@ -281,6 +371,24 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
         }
     });
 
+    let (input_schema_expr, output_schema_expr) = match attribute.schema_transform {
+        Some(transform) => {
+            let transform_schema =
+                quote! { rmcp::handler::server::schema_transform::transform_schema };
+            (
+                syn::parse2::<Expr>(quote! { #transform_schema(#input_schema_expr, #transform) })?,
+                output_schema_expr
+                    .map(|output_schema| {
+                        syn::parse2::<Expr>(
+                            quote! { #transform_schema(#output_schema, #transform) },
+                        )
+                    })
+                    .transpose()?,
+            )
+        }
+        None => (input_schema_expr, output_schema_expr),
+    };
+
     let description_expr = if let Some(s) = attribute.description {
         Some(Expr::Lit(syn::ExprLit {
             attrs: Vec::new(),
@@ -290,7 +398,7 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
         fn_item.attrs.iter().try_fold(None, extract_doc_line)?
     };
     let resolved_tool_attr = ResolvedToolAttribute {
-        name: attribute.name.unwrap_or_else(|| fn_ident.to_string()),
+        name: tool_name,
         description: description_expr,
         input_schema: input_schema_expr,
         output_schema: output_schema_expr,
@@ -298,8 +406,22 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
         title: attribute.title,
         icons: attribute.icons,
         meta: attribute.meta,
+        version: attribute.version,
+        deprecation: attribute.deprecated.map(Override::unwrap_or_default),
+        task: attribute.task.map(Override::unwrap_or_default),
     };
     let tool_attr_fn = resolved_tool_attr.into_fn(tool_attr_fn_ident)?;
+    let blocking_fn = if attribute.blocking {
+        if let Some(asyncness) = &fn_item.sig.asyncness {
+            return Err(syn::Error::new_spanned(
+                asyncness,
+                "blocking tools must be synchronous, move the blocking part into a sync fn",
+            ));
+        }
+        Some(into_blocking(&mut fn_item)?)
+    } else {
+        None
+    };
     // modify the the input function
     if fn_item.sig.asyncness.is_some() {
         // 1. remove asyncness from sig
@@ -336,9 +458,54 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
     Ok(quote! {
         #tool_attr_fn
         #fn_item
+        #blocking_fn
     })
 }
 
+/// Turn the sync tool `fn_item` into an async one running its body on the
+/// blocking thread pool, returning the original fn under a hidden name. The
+/// arguments and a clone of `self` are moved to the pool, so they must be
+/// `Send + 'static`.
+fn into_blocking(fn_item: &mut ImplItemFn) -> syn::Result<ImplItemFn> {
+    let mut blocking = fn_item.clone();
+    blocking.sig.ident = format_ident!("__rmcp_blocking_{}", fn_item.sig.ident);
+    blocking.vis = syn::Visibility::Inherited;
+    blocking.attrs.retain(|attr| !attr.path().is_ident("doc"));
+    blocking.attrs.push(parse_quote! { #[doc(hidden)] });
+    let blocking_ident = &blocking.sig.ident;
+
+    let mut args = Vec::new();
+    let mut this = None;
+    for (index, input) in fn_item.sig.inputs.iter_mut().enumerate() {
+        match input {
+            syn::FnArg::Receiver(receiver) => {
+                this = Some(if receiver.reference.is_some() {
+                    quote! { let __rmcp_this = ::std::clone::Clone::clone(self); }
+                } else {
+                    quote! { let __rmcp_this = self; }
+                });
+            }
+            syn::FnArg::Typed(pat_type) => {
+                let arg = format_ident!("__rmcp_arg{}", index);
+                *pat_type.pat = parse_quote! { #arg };
+                args.push(arg);
+            }
+        }
+    }
+    let call = match this {
+        Some(_) => quote! { __rmcp_this.#blocking_ident(#(#args),*) },
+        None => quote! { Self::#blocking_ident(#(#args),*) },
+    };
+    fn_item.sig.asyncness = Some(Default::default());
+    fn_item.block = syn::parse2::<syn::Block>(quote! {
+        {
+            #this
+            rmcp::handler::server::tool::run_blocking(move || #call).await
+        }
+    })?;
+    Ok(blocking)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -358,6 +525,118 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_input_schema_path() -> syn::Result<()> {
+        let attr = quote! {
+            input_schema = schemas::search
+        };
+        let input = quote! {
+            async fn search(&self, Parameters(query): Parameters<Query>) -> String {
+                query.text
+            }
+        };
+        let result = tool(attr, input)?.to_string();
+
+        assert!(result.contains("schemas :: search ()"));
+        assert!(result.contains("debug_check_input_schema :: < Parameters < Query > >"));
+        assert!(!result.contains("schema_for_type"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_transform() -> syn::Result<()> {
+        let attr = quote! {
+            schema_transform = cap_enum(20)
+        };
+        let input = quote! {
+            async fn pick(&self, Parameters(choice): Parameters<Choice>) -> Json<Choice> {
+                Json(choice)
+            }
+        };
+        let result = tool(attr, input)?.to_string();
+
+        assert_eq!(result.matches("transform_schema (").count(), 2);
+        assert_eq!(result.matches("cap_enum (20)").count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocking() -> syn::Result<()> {
+        let attr = quote! { blocking };
+        let input = quote! {
+            fn hash(&self, Parameters(input): Parameters<Input>) -> String {
+                expensive(input)
+            }
+        };
+        let result = tool(attr, input)?.to_string();
+        assert!(result.contains(
+            "let __rmcp_this = :: std :: clone :: Clone :: clone (self) ; \
+             rmcp :: handler :: server :: tool :: run_blocking (move | | __rmcp_this . \
+             __rmcp_blocking_hash (__rmcp_arg1)) . await"
+        ));
+        assert!(result.contains(
+            "fn __rmcp_blocking_hash (& self , Parameters (input) : Parameters < Input >) \
+             -> String { expensive (input) }"
+        ));
+
+        let attr = quote! { blocking };
+        let input = quote! {
+            async fn hash(&self) -> String {
+                String::new()
+            }
+        };
+        assert!(tool(attr, input).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_and_deprecation() -> syn::Result<()> {
+        let attr = quote! {
+            version = "2.0.0",
+            deprecated(replacement = "search_v3")
+        };
+        let input = quote! {
+            fn search(&self) -> String {
+                String::new()
+            }
+        };
+        let result = tool(attr, input)?.to_string();
+        assert!(result.contains(". with_version (\"2.0.0\")"));
+        assert!(result.contains(". replacement (\"search_v3\")"));
+
+        let attr = quote! { deprecated };
+        let input = quote! {
+            fn search(&self) -> String {
+                String::new()
+            }
+        };
+        let result = tool(attr, input)?.to_string();
+        assert!(result.contains("ToolDeprecation :: new ()"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_task() -> syn::Result<()> {
+        let input = quote! {
+            async fn build(&self) -> String {
+                String::new()
+            }
+        };
+        let result = tool(quote! { task }, input.clone())?.to_string();
+        assert!(result.contains(". with_task_support (rmcp :: model :: TaskSupport :: Optional)"));
+        assert!(!result.contains("with_task_threshold"));
+        assert!(!result.contains("with_resumable_task"));
+
+        let attr = quote! { task(required, threshold_ms = 500, resumable) };
+        let result = tool(attr, input)?.to_string();
+        assert!(result.contains("TaskSupport :: Required"));
+        assert!(result.contains(
+            ". with_task_threshold (:: std :: time :: Duration :: from_millis (500u64))"
+        ));
+        assert!(result.contains(". with_resumable_task ()"));
+        Ok(())
+    }
+
     #[test]
     fn test_doc_comment_description() -> syn::Result<()> {
         let attr = quote! {}; // No explicit description
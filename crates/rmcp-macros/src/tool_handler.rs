@@ -56,9 +56,21 @@ pub fn tool_handler(attr: TokenStream, input: TokenStream) -> syn::Result<TokenS
             })
         }
     };
+    let tool_get_fn = quote! {
+        fn get_tool(&self, name: &str) -> Option<rmcp::model::Tool> {
+            #router.get(name).cloned()
+        }
+    };
     let tool_call_fn = syn::parse2::<ImplItem>(tool_call_fn)?;
     let tool_list_fn = syn::parse2::<ImplItem>(tool_list_fn)?;
     item_impl.items.push(tool_call_fn);
     item_impl.items.push(tool_list_fn);
+    let has_get_tool = item_impl.items.iter().any(|item| match item {
+        ImplItem::Fn(func) => func.sig.ident == "get_tool",
+        _ => false,
+    });
+    if !has_get_tool {
+        item_impl.items.push(syn::parse2::<ImplItem>(tool_get_fn)?);
+    }
     Ok(item_impl.into_token_stream())
 }
@@ -9,13 +9,14 @@
 use darling::{FromMeta, ast::NestedMeta};
 use proc_macro2::TokenStream;
 use quote::{ToTokens, format_ident, quote};
-use syn::{Ident, ImplItem, ItemImpl, Visibility};
+use syn::{Expr, Ident, ImplItem, ItemImpl, Visibility};
 
 #[derive(FromMeta)]
 #[darling(default)]
 pub struct ToolRouterAttribute {
     pub router: Ident,
     pub vis: Option<Visibility>,
+    pub schema_transform: Option<Expr>,
 }
 
 impl Default for ToolRouterAttribute {
@@ -23,13 +24,18 @@ impl Default for ToolRouterAttribute {
         Self {
             router: format_ident!("tool_router"),
             vis: None,
+            schema_transform: None,
         }
     }
 }
 
 pub fn tool_router(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
     let attr_args = NestedMeta::parse_meta_list(attr)?;
-    let ToolRouterAttribute { router, vis } = ToolRouterAttribute::from_list(&attr_args)?;
+    let ToolRouterAttribute {
+        router,
+        vis,
+        schema_transform,
+    } = ToolRouterAttribute::from_list(&attr_args)?;
     let mut item_impl = syn::parse2::<ItemImpl>(input.clone())?;
     // find all function marked with `#[rmcp::tool]`
     let tool_attr_fns: Vec<_> = item_impl
@@ -59,6 +65,11 @@ pub fn tool_router(attr: TokenStream, input: TokenStream) -> syn::Result<TokenSt
             .with_route((Self::#tool_attr_fn_ident(), Self::#handler))
         })
     }
+    if let Some(transform) = schema_transform {
+        routers.push(quote! {
+            .transform_schemas(#transform)
+        })
+    }
     let router_fn = syn::parse2::<ImplItem>(quote! {
         #vis fn #router() -> rmcp::handler::server::router::tool::ToolRouter<Self> {
             rmcp::handler::server::router::tool::ToolRouter::<Self>::new()
@@ -79,7 +90,7 @@ mod test {
             vis = "pub(crate)"
         };
         let attr_args = NestedMeta::parse_meta_list(attr)?;
-        let ToolRouterAttribute { router, vis } = ToolRouterAttribute::from_list(&attr_args)?;
+        let ToolRouterAttribute { router, vis, .. } = ToolRouterAttribute::from_list(&attr_args)?;
         println!("router: {}", router);
         if let Some(vis) = vis {
             println!("visibility: {}", vis.to_token_stream());
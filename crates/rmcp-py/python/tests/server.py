@@ -0,0 +1,28 @@
+from rmcp import Server
+
+server = Server("calculator", "1.0.0")
+
+
+@server.tool(
+    input_schema={
+        "type": "object",
+        "properties": {"a": {"type": "integer"}, "b": {"type": "integer"}},
+        "required": ["a", "b"],
+    }
+)
+def add(a, b):
+    """Add two numbers."""
+    return str(a + b)
+
+
+@server.tool()
+def stats(values):
+    return {"count": len(values), "total": sum(values)}
+
+
+@server.tool()
+def fail():
+    raise ValueError("nope")
+
+
+server.serve_stdio()
@@ -0,0 +1,46 @@
+import json
+import pathlib
+import sys
+
+import pytest
+
+from rmcp import Client, RmcpError
+
+SERVER = str(pathlib.Path(__file__).with_name("server.py"))
+
+
+@pytest.fixture
+def client():
+    with Client.connect_command(sys.executable, [SERVER]) as client:
+        yield client
+
+
+def test_list_tools(client):
+    tools = {tool["name"]: tool for tool in client.list_tools()}
+    assert set(tools) == {"add", "stats", "fail"}
+    assert tools["add"]["description"] == "Add two numbers."
+    assert tools["add"]["inputSchema"]["required"] == ["a", "b"]
+    assert client.server_info()["serverInfo"]["name"] == "calculator"
+
+
+def test_call_tool(client):
+    result = client.call_tool("add", {"a": 2, "b": 3})
+    assert result["content"][0]["text"] == "5"
+
+    result = client.call_tool("stats", {"values": [1, 2, 3]})
+    assert json.loads(result["content"][0]["text"]) == {"count": 3, "total": 6}
+
+
+def test_tool_errors(client):
+    result = client.call_tool("fail")
+    assert result["isError"]
+    assert "nope" in result["content"][0]["text"]
+
+    with pytest.raises(RmcpError):
+        client.call_tool("missing")
+
+
+def test_closed_client(client):
+    client.close()
+    with pytest.raises(RmcpError):
+        client.list_tools()
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use pyo3::{prelude::*, types::PyDict};
+use rmcp::{
+    RoleClient, Service, blocking,
+    model::{
+        CallToolRequestParams, ClientInfo, ClientResult, ErrorData, JsonObject, ServerNotification,
+        ServerRequest,
+    },
+    service::{NotificationContext, RequestContext},
+    transport::{IntoTransport, StreamableHttpClientTransport, TokioChildProcess},
+};
+
+use crate::{error, json_dumps, json_loads, to_py};
+
+/// Calls the Python notification callback, if any, with the method and the
+/// params of each server notification.
+struct Notifier {
+    callback: Option<Arc<Py<PyAny>>>,
+}
+
+impl Service<RoleClient> for Notifier {
+    async fn handle_request(
+        &self,
+        request: ServerRequest,
+        context: RequestContext<RoleClient>,
+    ) -> Result<ClientResult, ErrorData> {
+        ().handle_request(request, context).await
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ServerNotification,
+        context: NotificationContext<RoleClient>,
+    ) -> Result<(), ErrorData> {
+        let Some(callback) = self.callback.clone() else {
+            return Ok(());
+        };
+        let mut notification = serde_json::to_value(notification)
+            .map_err(|error| ErrorData::internal_error(error.to_string(), None))?;
+        let method = notification["method"]
+            .as_str()
+            .unwrap_or_default()
+            .to_owned();
+        let mut params = notification["params"].take();
+        // the service moved `_meta` into the context
+        if let Some(params) = params.as_object_mut() {
+            params.remove("_meta");
+            if !context.meta.is_empty() {
+                params.insert("_meta".to_owned(), context.meta.0.into());
+            }
+        }
+        Python::attach(|py| {
+            let result = json_loads(py, &params.to_string())
+                .and_then(|params| callback.call1(py, (method, params)));
+            if let Err(error) = result {
+                error.write_unraisable(py, Some(callback.bind(py)));
+            }
+        });
+        Ok(())
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo::default()
+    }
+}
+
+/// A client connected to an MCP server; calls block until it answers.
+///
+/// ```python
+/// with Client.connect_command("my-server", ["--stdio"]) as client:
+///     print(client.list_tools())
+///     print(client.call_tool("add", {"a": 1, "b": 2}))
+/// ```
+#[pyclass(module = "rmcp")]
+pub struct Client {
+    client: Option<blocking::Client<Notifier>>,
+}
+
+impl Client {
+    fn connect<F, T, Err, E, A>(
+        py: Python<'_>,
+        transport: F,
+        on_notification: Option<Py<PyAny>>,
+    ) -> PyResult<Self>
+    where
+        F: FnOnce() -> Result<T, Err> + Send,
+        T: IntoTransport<RoleClient, E, A>,
+        Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let notifier = Notifier {
+            callback: on_notification.map(Arc::new),
+        };
+        let client = py
+            .detach(|| {
+                blocking::Client::try_connect_with(notifier, transport)
+                    .map_err(|error| error.to_string())
+            })
+            .map_err(error)?;
+        Ok(Self {
+            client: Some(client),
+        })
+    }
+
+    fn client(&self) -> PyResult<&blocking::Client<Notifier>> {
+        self.client
+            .as_ref()
+            .ok_or_else(|| error("client is closed"))
+    }
+}
+
+#[pymethods]
+impl Client {
+    /// Start `program` with `args` and connect to it over stdio.
+    /// `on_notification(method, params)` is called for each notification.
+    #[staticmethod]
+    #[pyo3(signature = (program, args = None, on_notification = None))]
+    fn connect_command(
+        py: Python<'_>,
+        program: String,
+        args: Option<Vec<String>>,
+        on_notification: Option<Py<PyAny>>,
+    ) -> PyResult<Self> {
+        let mut command = tokio::process::Command::new(program);
+        command.args(args.unwrap_or_default());
+        Self::connect(py, || TokioChildProcess::new(command), on_notification)
+    }
+
+    /// Connect to a streamable HTTP server at `url`.
+    #[staticmethod]
+    #[pyo3(signature = (url, on_notification = None))]
+    fn connect_http(
+        py: Python<'_>,
+        url: String,
+        on_notification: Option<Py<PyAny>>,
+    ) -> PyResult<Self> {
+        Self::connect(
+            py,
+            || Ok::<_, std::convert::Infallible>(StreamableHttpClientTransport::from_uri(url)),
+            on_notification,
+        )
+    }
+
+    /// Info of the server, as a dict.
+    fn server_info(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_py(py, self.client()?.peer_info())
+    }
+
+    /// All tools of the server, as a list of dicts.
+    fn list_tools(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let client = self.client()?;
+        let tools = py.detach(|| client.list_all_tools()).map_err(error)?;
+        to_py(py, tools)
+    }
+
+    /// Call the tool `name` and return its result as a dict; a failing tool
+    /// gives a result with `isError` set rather than an exception.
+    #[pyo3(signature = (name, arguments = None))]
+    fn call_tool(
+        &self,
+        py: Python<'_>,
+        name: String,
+        arguments: Option<Bound<'_, PyDict>>,
+    ) -> PyResult<Py<PyAny>> {
+        let client = self.client()?;
+        let arguments = arguments
+            .map(|arguments| {
+                serde_json::from_str::<JsonObject>(&json_dumps(arguments.as_any())?).map_err(error)
+            })
+            .transpose()?;
+        let result = py
+            .detach(|| {
+                client.call_tool(CallToolRequestParams {
+                    meta: None,
+                    name: name.into(),
+                    arguments,
+                    task: None,
+                })
+            })
+            .map_err(error)?;
+        to_py(py, result)
+    }
+
+    /// Close the session. The client can't be used afterwards.
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        if let Some(client) = self.client.take() {
+            py.detach(|| client.close()).map_err(error)?;
+        }
+        Ok(())
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(
+        &mut self,
+        py: Python<'_>,
+        _args: &Bound<'_, pyo3::types::PyTuple>,
+    ) -> PyResult<()> {
+        self.close(py)
+    }
+}
@@ -0,0 +1,48 @@
+//! Python bindings for the rmcp client and a decorator-based server, built
+//! with [maturin](https://www.maturin.rs) into the `rmcp` Python module.
+//!
+//! Values cross the boundary as JSON: tool lists and results come back as
+//! dicts and lists, tool arguments go in as dicts.
+use pyo3::{create_exception, exceptions::PyException, prelude::*};
+
+mod client;
+mod server;
+
+pub use client::Client;
+pub use server::{Server, ToolDecorator};
+
+create_exception!(
+    rmcp,
+    RmcpError,
+    PyException,
+    "Raised when an MCP call fails."
+);
+
+fn error(error: impl std::fmt::Display) -> PyErr {
+    RmcpError::new_err(error.to_string())
+}
+
+fn json_loads(py: Python<'_>, json: &str) -> PyResult<Py<PyAny>> {
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+fn json_dumps(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    value
+        .py()
+        .import("json")?
+        .call_method1("dumps", (value,))?
+        .extract()
+}
+
+fn to_py(py: Python<'_>, value: impl serde::Serialize) -> PyResult<Py<PyAny>> {
+    json_loads(py, &serde_json::to_string(&value).map_err(error)?)
+}
+
+#[pymodule(name = "rmcp")]
+fn rmcp_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Client>()?;
+    m.add_class::<Server>()?;
+    m.add_class::<ToolDecorator>()?;
+    m.add("RmcpError", m.py().get_type::<RmcpError>())?;
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use std::sync::{Arc, RwLock};
+
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyString},
+};
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequestParams, CallToolResult, Content, Implementation, JsonObject,
+        ListToolsResult, PaginatedRequestParams, ServerCapabilities, ServerInfo, Tool,
+    },
+    service::RequestContext,
+    transport::stdio,
+};
+
+use crate::{error, json_dumps, json_loads};
+
+struct PyTool {
+    tool: Tool,
+    function: Py<PyAny>,
+}
+
+type Tools = Arc<RwLock<Vec<Arc<PyTool>>>>;
+
+/// An MCP server whose tools are Python functions.
+///
+/// ```python
+/// server = Server("calculator", "1.0.0")
+///
+/// @server.tool(input_schema={
+///     "type": "object",
+///     "properties": {"a": {"type": "integer"}, "b": {"type": "integer"}},
+/// })
+/// def add(a, b):
+///     """Add two numbers."""
+///     return str(a + b)
+///
+/// server.serve_stdio()
+/// ```
+#[pyclass(module = "rmcp")]
+pub struct Server {
+    info: ServerInfo,
+    tools: Tools,
+}
+
+#[pymethods]
+impl Server {
+    #[new]
+    #[pyo3(signature = (name, version, instructions = None))]
+    fn new(name: String, version: String, instructions: Option<String>) -> Self {
+        Self {
+            info: ServerInfo {
+                server_info: Implementation {
+                    name,
+                    version,
+                    ..Default::default()
+                },
+                instructions,
+                capabilities: ServerCapabilities::builder().enable_tools().build(),
+                ..Default::default()
+            },
+            tools: Default::default(),
+        }
+    }
+
+    /// Decorator registering a function as a tool. The name defaults to the
+    /// function's, the description to its docstring, and the input schema
+    /// to any object. The function is called with the arguments as keyword
+    /// arguments; a string result is returned as text, anything else as
+    /// JSON, and an exception as a tool error.
+    #[pyo3(signature = (name = None, description = None, input_schema = None))]
+    fn tool(
+        &self,
+        name: Option<String>,
+        description: Option<String>,
+        input_schema: Option<Bound<'_, PyDict>>,
+    ) -> PyResult<ToolDecorator> {
+        let input_schema = match input_schema {
+            Some(schema) => {
+                serde_json::from_str::<JsonObject>(&json_dumps(schema.as_any())?).map_err(error)?
+            }
+            None => rmcp::object!({ "type": "object" }),
+        };
+        Ok(ToolDecorator {
+            name,
+            description,
+            input_schema,
+            tools: self.tools.clone(),
+        })
+    }
+
+    /// Serve on stdin/stdout until the client disconnects.
+    fn serve_stdio(&self, py: Python<'_>) -> PyResult<()> {
+        let handler = Handler {
+            info: self.info.clone(),
+            tools: self.tools.clone(),
+        };
+        py.detach(|| {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|error| error.to_string())?;
+            runtime.block_on(async move {
+                let server = handler
+                    .serve(stdio())
+                    .await
+                    .map_err(|error| error.to_string())?;
+                server.waiting().await.map_err(|error| error.to_string())?;
+                Ok::<_, String>(())
+            })
+        })
+        .map_err(error)
+    }
+}
+
+/// Returned by [`Server::tool`], registers the function it decorates.
+#[pyclass(module = "rmcp")]
+pub struct ToolDecorator {
+    name: Option<String>,
+    description: Option<String>,
+    input_schema: JsonObject,
+    tools: Tools,
+}
+
+#[pymethods]
+impl ToolDecorator {
+    fn __call__(&self, function: Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => function.getattr("__name__")?.extract()?,
+        };
+        let description = match &self.description {
+            Some(description) => Some(description.clone()),
+            None => function
+                .getattr("__doc__")?
+                .extract::<Option<String>>()?
+                .map(|doc| doc.trim().to_owned()),
+        };
+        let mut tool = Tool::new(name, "", self.input_schema.clone());
+        tool.description = description.map(Into::into);
+        let mut tools = self.tools.write().expect("tools poisoned");
+        tools.retain(|known| known.tool.name != tool.name);
+        tools.push(Arc::new(PyTool {
+            tool,
+            function: function.clone().unbind(),
+        }));
+        Ok(function.unbind())
+    }
+}
+
+struct Handler {
+    info: ServerInfo,
+    tools: Tools,
+}
+
+impl Handler {
+    fn find(&self, name: &str) -> Option<Arc<PyTool>> {
+        self.tools
+            .read()
+            .expect("tools poisoned")
+            .iter()
+            .find(|tool| tool.tool.name == name)
+            .cloned()
+    }
+}
+
+/// Calls `tool` with `arguments` as keyword arguments.
+fn call(tool: &PyTool, arguments: Option<JsonObject>) -> CallToolResult {
+    Python::attach(|py| {
+        let result = (|| {
+            let kwargs = match arguments {
+                Some(arguments) => Some(
+                    json_loads(py, &serde_json::Value::Object(arguments).to_string())?
+                        .into_bound(py)
+                        .cast_into::<PyDict>()?,
+                ),
+                None => None,
+            };
+            let value = tool.function.bind(py).call((), kwargs.as_ref())?;
+            match value.cast::<PyString>() {
+                Ok(text) => Ok(text.to_string()),
+                Err(_) => json_dumps(&value),
+            }
+        })();
+        match result {
+            Ok(text) => CallToolResult::success(vec![Content::text(text)]),
+            Err(error) => CallToolResult::error(vec![Content::text(error.to_string())]),
+        }
+    })
+}
+
+impl ServerHandler for Handler {
+    fn get_info(&self) -> ServerInfo {
+        self.info.clone()
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        let tools = self.tools.read().expect("tools poisoned");
+        Ok(ListToolsResult {
+            tools: tools.iter().map(|tool| tool.tool.clone()).collect(),
+            ..Default::default()
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tool = self.find(&request.name).ok_or_else(|| {
+            ErrorData::invalid_params(format!("tool not found: {}", request.name), None)
+        })?;
+        // Python code may block, keep it off the runtime thread
+        tokio::task::spawn_blocking(move || call(&tool, request.arguments))
+            .await
+            .map_err(|error| ErrorData::internal_error(error.to_string(), None))
+    }
+}
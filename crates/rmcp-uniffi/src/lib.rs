@@ -0,0 +1,428 @@
+//! [UniFFI](https://mozilla.github.io/uniffi-rs/) bindings of the rmcp
+//! client, so that Kotlin and Swift apps connect to remote MCP servers over
+//! streamable HTTP with this crate's protocol implementation.
+//!
+//! Generate the host sources from the built library with
+//! `cargo run -p rmcp-uniffi --features bindgen --bin uniffi-bindgen -- generate --library <lib> --language kotlin --out-dir <dir>`.
+//!
+//! Calls block until the server answers: make them off the UI thread.
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    RoleClient, Service, blocking,
+    model::{
+        CallToolRequestParams, ClientInfo, ClientResult, ErrorData, Implementation, JsonObject,
+        RawContent, ServerNotification, ServerRequest,
+    },
+    service::{NotificationContext, RequestContext},
+    transport::{
+        StreamableHttpClientTransport, streamable_http_client::StreamableHttpClientTransportConfig,
+    },
+};
+
+uniffi::setup_scaffolding!();
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum McpError {
+    #[error("connection failed: {0}")]
+    Connection(String),
+    #[error("request failed: {0}")]
+    Request(String),
+    #[error("invalid arguments: {0}")]
+    InvalidArguments(String),
+    #[error("client is closed")]
+    Closed,
+}
+
+/// Receives the notifications of the server, on a thread of the library.
+#[uniffi::export(with_foreign)]
+pub trait NotificationListener: Send + Sync {
+    fn on_notification(&self, method: String, params_json: String);
+}
+
+/// How to reach the server and who is calling.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct ConnectOptions {
+    pub url: String,
+    /// Sent as a bearer token.
+    #[uniffi(default = None)]
+    pub auth_token: Option<String>,
+    #[uniffi(default = None)]
+    pub client_name: Option<String>,
+    #[uniffi(default = None)]
+    pub client_version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ServerDetails {
+    pub name: String,
+    pub version: String,
+    pub protocol_version: String,
+    pub instructions: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ToolInfo {
+    pub name: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub input_schema_json: String,
+}
+
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ToolCallResult {
+    pub is_error: bool,
+    /// The text items of the content, in order.
+    pub text: Vec<String>,
+    pub structured_content_json: Option<String>,
+    /// The whole result, for content other than text.
+    pub result_json: String,
+}
+
+struct Notifier {
+    listener: Option<Arc<dyn NotificationListener>>,
+    info: ClientInfo,
+}
+
+impl Service<RoleClient> for Notifier {
+    async fn handle_request(
+        &self,
+        request: ServerRequest,
+        context: RequestContext<RoleClient>,
+    ) -> Result<ClientResult, ErrorData> {
+        ().handle_request(request, context).await
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ServerNotification,
+        context: NotificationContext<RoleClient>,
+    ) -> Result<(), ErrorData> {
+        let Some(listener) = &self.listener else {
+            return Ok(());
+        };
+        let mut notification = serde_json::to_value(notification)
+            .map_err(|error| ErrorData::internal_error(error.to_string(), None))?;
+        let method = notification["method"]
+            .as_str()
+            .unwrap_or_default()
+            .to_owned();
+        let mut params = notification["params"].take();
+        // the service moved `_meta` into the context
+        if let Some(params) = params.as_object_mut() {
+            params.remove("_meta");
+            if !context.meta.is_empty() {
+                params.insert("_meta".to_owned(), context.meta.0.into());
+            }
+        }
+        listener.on_notification(method, params.to_string());
+        Ok(())
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        self.info.clone()
+    }
+}
+
+/// A client connected to an MCP server over streamable HTTP.
+#[derive(uniffi::Object)]
+pub struct McpClient {
+    client: Mutex<Option<Arc<blocking::Client<Notifier>>>>,
+}
+
+impl McpClient {
+    fn client(&self) -> Result<Arc<blocking::Client<Notifier>>, McpError> {
+        self.client
+            .lock()
+            .expect("client poisoned")
+            .clone()
+            .ok_or(McpError::Closed)
+    }
+}
+
+#[uniffi::export]
+impl McpClient {
+    #[uniffi::constructor(default(listener = None))]
+    pub fn connect(
+        options: ConnectOptions,
+        listener: Option<Arc<dyn NotificationListener>>,
+    ) -> Result<Arc<Self>, McpError> {
+        let mut info = ClientInfo::default();
+        if let Some(name) = options.client_name {
+            info.client_info = Implementation {
+                name,
+                version: options.client_version.unwrap_or_default(),
+                ..Default::default()
+            };
+        }
+        let mut config = StreamableHttpClientTransportConfig::with_uri(options.url);
+        if let Some(token) = options.auth_token {
+            config = config.auth_header(token);
+        }
+        let client = blocking::Client::try_connect_with(Notifier { listener, info }, || {
+            Ok::<_, std::convert::Infallible>(StreamableHttpClientTransport::from_config(config))
+        })
+        .map_err(|error| McpError::Connection(error.to_string()))?;
+        Ok(Arc::new(Self {
+            client: Mutex::new(Some(Arc::new(client))),
+        }))
+    }
+
+    pub fn server_details(&self) -> Result<Option<ServerDetails>, McpError> {
+        Ok(self.client()?.peer_info().map(|info| ServerDetails {
+            name: info.server_info.name.clone(),
+            version: info.server_info.version.clone(),
+            protocol_version: info.protocol_version.to_string(),
+            instructions: info.instructions.clone(),
+        }))
+    }
+
+    pub fn list_tools(&self) -> Result<Vec<ToolInfo>, McpError> {
+        let tools = self
+            .client()?
+            .list_all_tools()
+            .map_err(|error| McpError::Request(error.to_string()))?;
+        Ok(tools
+            .into_iter()
+            .map(|tool| ToolInfo {
+                input_schema_json: serde_json::to_string(&tool.input_schema).unwrap_or_default(),
+                name: tool.name.into_owned(),
+                title: tool.title,
+                description: tool.description.map(|description| description.into_owned()),
+            })
+            .collect())
+    }
+
+    /// Call the tool `name` with `arguments_json`, a JSON object.
+    #[uniffi::method(default(arguments_json = None))]
+    pub fn call_tool(
+        &self,
+        name: String,
+        arguments_json: Option<String>,
+    ) -> Result<ToolCallResult, McpError> {
+        let arguments = arguments_json
+            .map(|arguments| {
+                serde_json::from_str::<JsonObject>(&arguments)
+                    .map_err(|error| McpError::InvalidArguments(error.to_string()))
+            })
+            .transpose()?;
+        let result = self
+            .client()?
+            .call_tool(CallToolRequestParams {
+                meta: None,
+                name: name.into(),
+                arguments,
+                task: None,
+            })
+            .map_err(|error| McpError::Request(error.to_string()))?;
+        Ok(ToolCallResult {
+            is_error: result.is_error(),
+            text: result
+                .content
+                .iter()
+                .filter_map(|content| match &content.raw {
+                    RawContent::Text(text) => Some(text.text.clone()),
+                    _ => None,
+                })
+                .collect(),
+            structured_content_json: result
+                .structured_content
+                .as_ref()
+                .map(|value| value.to_string()),
+            result_json: serde_json::to_string(&result).unwrap_or_default(),
+        })
+    }
+
+    /// Close the session; later calls fail with [`McpError::Closed`]. Calls
+    /// still in flight finish first.
+    pub fn close(&self) {
+        let client = self.client.lock().expect("client poisoned").take();
+        if let Some(client) = client.and_then(Arc::into_inner) {
+            let _ = client.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use rmcp::{
+        RoleServer, ServerHandler,
+        model::{
+            CallToolResult, Content, ListToolsResult, LoggingLevel,
+            LoggingMessageNotificationParam, PaginatedRequestParams, ServerCapabilities,
+            ServerInfo, Tool,
+        },
+        transport::streamable_http_server::{
+            StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
+        },
+    };
+    use tokio_util::sync::CancellationToken;
+
+    use super::*;
+
+    /// Echoes its `text` argument, logging it first.
+    struct Echo;
+
+    impl ServerHandler for Echo {
+        fn get_info(&self) -> ServerInfo {
+            ServerInfo {
+                instructions: Some("say something".into()),
+                capabilities: ServerCapabilities::builder()
+                    .enable_tools()
+                    .enable_logging()
+                    .build(),
+                ..Default::default()
+            }
+        }
+
+        async fn list_tools(
+            &self,
+            _request: Option<PaginatedRequestParams>,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ListToolsResult, ErrorData> {
+            Ok(ListToolsResult {
+                tools: vec![Tool::new(
+                    "echo",
+                    "Echo the text",
+                    rmcp::object!({
+                        "type": "object",
+                        "properties": { "text": { "type": "string" } },
+                    }),
+                )],
+                ..Default::default()
+            })
+        }
+
+        async fn call_tool(
+            &self,
+            request: CallToolRequestParams,
+            context: RequestContext<RoleServer>,
+        ) -> Result<CallToolResult, ErrorData> {
+            let text = request
+                .arguments
+                .and_then(|arguments| arguments.get("text")?.as_str().map(str::to_owned))
+                .ok_or_else(|| ErrorData::invalid_params("text is required", None))?;
+            context
+                .peer
+                .notify_logging_message(LoggingMessageNotificationParam {
+                    level: LoggingLevel::Info,
+                    logger: None,
+                    data: text.clone().into(),
+                })
+                .await
+                .map_err(|error| ErrorData::internal_error(error.to_string(), None))?;
+            Ok(CallToolResult::success(vec![Content::text(text)]))
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(String, String)>>);
+
+    impl NotificationListener for Recorder {
+        fn on_notification(&self, method: String, params_json: String) {
+            self.0.lock().unwrap().push((method, params_json));
+        }
+    }
+
+    /// Serves [`Echo`] over streamable HTTP, returns its URL.
+    fn serve(runtime: &tokio::runtime::Runtime, ct: &CancellationToken) -> String {
+        let service: StreamableHttpService<Echo, LocalSessionManager> = StreamableHttpService::new(
+            || Ok(Echo),
+            Default::default(),
+            StreamableHttpServerConfig {
+                stateful_mode: true,
+                sse_keep_alive: None,
+                cancellation_token: ct.child_token(),
+                ..Default::default()
+            },
+        );
+        let router = axum::Router::new().nest_service("/mcp", service);
+        let listener = runtime
+            .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shutdown = ct.clone().cancelled_owned();
+        runtime.spawn(async move {
+            let _ = axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await;
+        });
+        format!("http://{addr}/mcp")
+    }
+
+    #[test]
+    fn test_list_and_call_tools() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let ct = CancellationToken::new();
+        let url = serve(&runtime, &ct);
+
+        let recorder = Arc::new(Recorder::default());
+        let client = McpClient::connect(
+            ConnectOptions {
+                url,
+                auth_token: None,
+                client_name: Some("mobile".into()),
+                client_version: Some("1.0.0".into()),
+            },
+            Some(recorder.clone()),
+        )
+        .unwrap();
+        let details = client.server_details().unwrap().unwrap();
+        assert_eq!(details.instructions.as_deref(), Some("say something"));
+
+        let tools = client.list_tools().unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "echo");
+        assert_eq!(tools[0].description.as_deref(), Some("Echo the text"));
+        let schema: serde_json::Value = serde_json::from_str(&tools[0].input_schema_json).unwrap();
+        assert_eq!(schema["properties"]["text"]["type"], "string");
+
+        let result = client
+            .call_tool("echo".into(), Some(r#"{"text": "hello"}"#.into()))
+            .unwrap();
+        assert!(!result.is_error);
+        assert_eq!(result.text, ["hello"]);
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while recorder.0.lock().unwrap().is_empty() && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(
+            recorder.0.lock().unwrap().as_slice(),
+            [(
+                "notifications/message".to_owned(),
+                r#"{"data":"hello","level":"info"}"#.to_owned()
+            )]
+        );
+
+        assert!(matches!(
+            client.call_tool("echo".into(), Some("[]".into())),
+            Err(McpError::InvalidArguments(_))
+        ));
+        assert!(matches!(
+            client.call_tool("echo".into(), None),
+            Err(McpError::Request(_))
+        ));
+
+        client.close();
+        assert!(matches!(client.list_tools(), Err(McpError::Closed)));
+        ct.cancel();
+    }
+
+    #[test]
+    fn test_connect_failure() {
+        let result = McpClient::connect(
+            ConnectOptions {
+                url: "http://127.0.0.1:1/mcp".into(),
+                auth_token: None,
+                client_name: None,
+                client_version: None,
+            },
+            None,
+        );
+        assert!(matches!(result, Err(McpError::Connection(_))));
+    }
+}
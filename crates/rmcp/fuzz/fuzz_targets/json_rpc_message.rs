@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+
+fn round_trip<T>(data: &[u8])
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    let Ok(message) = serde_json::from_slice::<T>(data) else {
+        return;
+    };
+    // anything we accept must serialize, and must parse back after that
+    let json = serde_json::to_vec(&message).expect("accepted message should serialize");
+    if let Err(e) = serde_json::from_slice::<T>(&json) {
+        panic!("re-parsing {message:?} failed: {e}");
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    round_trip::<ClientJsonRpcMessage>(data);
+    round_trip::<ServerJsonRpcMessage>(data);
+});
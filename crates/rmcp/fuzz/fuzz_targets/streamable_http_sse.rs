@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rmcp::transport::common::client_side_sse::decode_sse_body;
+
+// Feeds a `text/event-stream` response body through the same decoding as the
+// streamable http client: SSE framing, event filtering and JSON-RPC parsing.
+fuzz_target!(|data: &[u8]| {
+    let Some((&chunk_size, body)) = data.split_first() else {
+        return;
+    };
+    // deliver the body in arbitrary pieces so frames get split mid-line
+    let chunk_size = usize::from(chunk_size).max(1);
+    let chunks = body.chunks(chunk_size).map(<[u8]>::to_vec).collect();
+    let _ = decode_sse_body(chunks);
+});
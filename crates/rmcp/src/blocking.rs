@@ -0,0 +1,199 @@
+//! A synchronous client, for CLI tools and build scripts that are not async.
+//!
+//! ```rust,ignore
+//! use rmcp::{blocking::Client, model::CallToolRequestParams, transport::TokioChildProcess};
+//!
+//! let client = Client::try_connect(|| {
+//!     TokioChildProcess::new(tokio::process::Command::new("my-mcp-server"))
+//! })?;
+//! for tool in client.list_all_tools()? {
+//!     println!("{}", tool.name);
+//! }
+//! let result = client.call_tool(CallToolRequestParams {
+//!     meta: None,
+//!     name: "sum".into(),
+//!     arguments: Some(rmcp::object!({ "a": 1, "b": 2 })),
+//!     task: None,
+//! })?;
+//! client.close()?;
+//! ```
+use std::{convert::Infallible, future::Future, thread::JoinHandle};
+
+use tokio::{runtime::Handle, sync::oneshot};
+
+use crate::{
+    RmcpError, RoleClient, Service, ServiceError, ServiceExt,
+    model::{
+        CallToolRequestParams, CallToolResult, GetPromptRequestParams, GetPromptResult, Prompt,
+        ReadResourceRequestParams, ReadResourceResult, Resource, ResourceTemplate, ServerInfo,
+        Tool,
+    },
+    service::{Peer, QuitReason, RunningService},
+    transport::IntoTransport,
+};
+
+/// A client whose calls block until the server answers.
+///
+/// The async client runs on a runtime of its own, driven by a background
+/// thread, so notifications and server requests are handled between calls
+/// too. Methods must not be called from async code: they panic inside a
+/// tokio runtime, use the async client there.
+///
+/// Dropping the client cancels the session; [`close`](Self::close) does the
+/// same and reports how it ended.
+pub struct Client<S: Service<RoleClient> = ()> {
+    service: Option<RunningService<RoleClient, S>>,
+    runtime: Handle,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<S: Service<RoleClient>> std::fmt::Debug for Client<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("server", &self.peer_info())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Connect over the transport made by `transport`, which runs inside the
+    /// client's runtime so it may spawn processes or tasks.
+    #[allow(clippy::result_large_err)]
+    pub fn connect<F, T, E, A>(transport: F) -> Result<Self, RmcpError>
+    where
+        F: FnOnce() -> T,
+        T: IntoTransport<RoleClient, E, A>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self::try_connect(|| Ok::<_, Infallible>(transport()))
+    }
+
+    /// Like [`connect`](Self::connect), for transports whose creation can
+    /// fail, e.g. when spawning a child process.
+    #[allow(clippy::result_large_err)]
+    pub fn try_connect<F, T, Err, E, A>(transport: F) -> Result<Self, RmcpError>
+    where
+        F: FnOnce() -> Result<T, Err>,
+        T: IntoTransport<RoleClient, E, A>,
+        Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self::try_connect_with((), transport)
+    }
+}
+
+impl<S: Service<RoleClient>> Client<S> {
+    /// Connect with `service`, usually a [`ClientHandler`](crate::ClientHandler),
+    /// answering the server's requests and notifications.
+    ///
+    /// # Panics
+    ///
+    /// If the runtime or its thread cannot be created.
+    #[allow(clippy::result_large_err)]
+    pub fn try_connect_with<F, T, Err, E, A>(service: S, transport: F) -> Result<Self, RmcpError>
+    where
+        F: FnOnce() -> Result<T, Err>,
+        T: IntoTransport<RoleClient, E, A>,
+        Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build the blocking client runtime");
+        let handle = runtime.handle().clone();
+        let (shutdown, stop) = oneshot::channel::<()>();
+        let thread = std::thread::Builder::new()
+            .name("rmcp-blocking-client".into())
+            .spawn(move || {
+                let _ = runtime.block_on(stop);
+            })
+            .expect("failed to spawn the blocking client thread");
+        let mut client = Self {
+            service: None,
+            runtime: handle,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        };
+        let transport = {
+            let _guard = client.runtime.enter();
+            transport().map_err(RmcpError::transport_creation::<T>)?
+        };
+        client.service = Some(client.block_on(service.serve(transport))?);
+        Ok(client)
+    }
+
+    /// Run `future` on the client's runtime, for anything not wrapped here.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    pub fn peer(&self) -> &Peer<RoleClient> {
+        self.service().peer()
+    }
+
+    pub fn peer_info(&self) -> Option<&ServerInfo> {
+        self.service.as_ref()?.peer_info()
+    }
+
+    pub fn list_all_tools(&self) -> Result<Vec<Tool>, ServiceError> {
+        self.block_on(self.peer().list_all_tools())
+    }
+
+    pub fn call_tool(&self, params: CallToolRequestParams) -> Result<CallToolResult, ServiceError> {
+        self.block_on(self.peer().call_tool(params))
+    }
+
+    pub fn list_all_resources(&self) -> Result<Vec<Resource>, ServiceError> {
+        self.block_on(self.peer().list_all_resources())
+    }
+
+    pub fn list_all_resource_templates(&self) -> Result<Vec<ResourceTemplate>, ServiceError> {
+        self.block_on(self.peer().list_all_resource_templates())
+    }
+
+    pub fn read_resource(
+        &self,
+        params: ReadResourceRequestParams,
+    ) -> Result<ReadResourceResult, ServiceError> {
+        self.block_on(self.peer().read_resource(params))
+    }
+
+    pub fn list_all_prompts(&self) -> Result<Vec<Prompt>, ServiceError> {
+        self.block_on(self.peer().list_all_prompts())
+    }
+
+    pub fn get_prompt(
+        &self,
+        params: GetPromptRequestParams,
+    ) -> Result<GetPromptResult, ServiceError> {
+        self.block_on(self.peer().get_prompt(params))
+    }
+
+    /// Cancel the session and wait for it to end.
+    pub fn close(mut self) -> Result<QuitReason, tokio::task::JoinError> {
+        let service = self.service.take().expect("client is connected");
+        self.block_on(service.cancel())
+    }
+
+    fn service(&self) -> &RunningService<RoleClient, S> {
+        self.service.as_ref().expect("client is connected")
+    }
+}
+
+impl<S: Service<RoleClient>> Drop for Client<S> {
+    fn drop(&mut self) {
+        if let Some(service) = self.service.take() {
+            if Handle::try_current().is_err() {
+                let _ = self.block_on(service.cancel());
+            }
+        }
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
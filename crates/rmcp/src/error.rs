@@ -1,5 +1,8 @@
-use std::{borrow::Cow, fmt::Display};
+#[cfg(any(feature = "client", feature = "server"))]
+use std::borrow::Cow;
+use std::fmt::Display;
 
+#[cfg(any(feature = "client", feature = "server"))]
 use crate::ServiceError;
 pub use crate::model::ErrorData;
 #[deprecated(
@@ -18,7 +21,134 @@ impl Display for ErrorData {
 
 impl std::error::Error for ErrorData {}
 
+/// Where [`ErrorData::with_source_chain`] keeps the messages of the sources.
+const SOURCE_CHAIN_KEY: &str = "sourceChain";
+
+impl ErrorData {
+    /// An internal error for `error`, keeping the messages of its sources
+    /// in `data.sourceChain` so the root cause survives the protocol
+    /// boundary.
+    ///
+    /// ```rust
+    /// # use rmcp::ErrorData;
+    /// fn load() -> anyhow::Result<String> {
+    ///     # Err(anyhow::anyhow!("permission denied").context("reading config.toml"))
+    /// }
+    /// let error = ErrorData::internal_error_with_chain(load().unwrap_err().as_ref());
+    /// assert_eq!(error.message, "reading config.toml");
+    /// assert_eq!(error.source_chain(), ["permission denied"]);
+    /// ```
+    pub fn internal_error_with_chain(error: &(dyn std::error::Error + 'static)) -> Self {
+        Self::internal_error(error.to_string(), None).with_source_chain(error)
+    }
+
+    /// Keep the messages of the sources of `error`, not `error` itself, in
+    /// `data.sourceChain`. Only messages are sent, no backtraces. Data that
+    /// isn't an object is left as is.
+    pub fn with_source_chain(mut self, error: &(dyn std::error::Error + 'static)) -> Self {
+        let chain = std::iter::successors(error.source(), |source| source.source())
+            .map(|source| serde_json::Value::String(source.to_string()))
+            .collect::<Vec<_>>();
+        if chain.is_empty() {
+            return self;
+        }
+        match self.data.get_or_insert_with(Default::default) {
+            serde_json::Value::Object(data) => {
+                data.insert(SOURCE_CHAIN_KEY.into(), chain.into());
+            }
+            data @ serde_json::Value::Null => {
+                *data = serde_json::json!({ SOURCE_CHAIN_KEY: chain });
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// The messages of the sources recorded by
+    /// [`with_source_chain`](Self::with_source_chain), outermost first.
+    pub fn source_chain(&self) -> Vec<&str> {
+        self.data
+            .as_ref()
+            .and_then(|data| data.get(SOURCE_CHAIN_KEY))
+            .and_then(serde_json::Value::as_array)
+            .map(|chain| chain.iter().filter_map(serde_json::Value::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// This error as a std error whose sources are its
+    /// [`source_chain`](Self::source_chain), for error reporters that print
+    /// causes, like anyhow's `{:#}`.
+    pub fn into_error_chain(self) -> ErrorChain {
+        let source = self
+            .source_chain()
+            .into_iter()
+            .rev()
+            .fold(None, |source, message| {
+                Some(Box::new(RemoteSource {
+                    message: message.to_owned(),
+                    source,
+                }))
+            });
+        ErrorChain {
+            error: self,
+            source,
+        }
+    }
+}
+
+/// An [`ErrorData`] together with the chain of its sources, see
+/// [`ErrorData::into_error_chain`].
+#[derive(Debug, Clone)]
+pub struct ErrorChain {
+    error: ErrorData,
+    source: Option<Box<RemoteSource>>,
+}
+
+impl ErrorChain {
+    pub fn error(&self) -> &ErrorData {
+        &self.error
+    }
+
+    pub fn into_inner(self) -> ErrorData {
+        self.error
+    }
+}
+
+impl Display for ErrorChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.error.code.0, self.error.message)
+    }
+}
+
+impl std::error::Error for ErrorChain {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|source| source as _)
+    }
+}
+
+/// A source of an error on the other side, of which only the message is
+/// known.
+#[derive(Debug, Clone)]
+struct RemoteSource {
+    message: String,
+    source: Option<Box<RemoteSource>>,
+}
+
+impl Display for RemoteSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RemoteSource {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|source| source as _)
+    }
+}
+
 /// This is an unified error type for the errors could be returned by the service.
+#[cfg(any(feature = "client", feature = "server"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "client", feature = "server"))))]
 #[derive(Debug, thiserror::Error)]
 #[allow(clippy::large_enum_variant)]
 pub enum RmcpError {
@@ -48,6 +178,7 @@ pub enum RmcpError {
     TaskError(String),
 }
 
+#[cfg(any(feature = "client", feature = "server"))]
 impl RmcpError {
     pub fn transport_creation<T: 'static>(
         error: impl Into<Box<dyn std::error::Error + Send + Sync>>,
@@ -1,11 +1,19 @@
+pub mod cache;
+#[cfg(feature = "elicitation")]
+pub mod elicitation;
+pub mod mirror;
 pub mod progress;
+pub mod router;
+pub mod sampling;
 use std::sync::Arc;
 
 #[allow(deprecated)] // model::* includes deprecated roots types (MCP 2025-11-25)
 use crate::{
     error::ErrorData as McpError,
     model::*,
-    service::{NotificationContext, RequestContext, RoleClient, Service, ServiceRole},
+    service::{
+        NotificationContext, RequestContext, RoleClient, Service, ServiceConfig, ServiceRole,
+    },
 };
 
 impl<H: ClientHandler> Service<RoleClient> for H {
@@ -25,10 +33,30 @@ impl<H: ClientHandler> Service<RoleClient> for H {
                 .list_roots(context)
                 .await
                 .map(ClientResult::ListRootsResult),
-            ServerRequest::CreateElicitationRequest(request) => self
-                .create_elicitation(request.params, context)
-                .await
-                .map(ClientResult::CreateElicitationResult),
+            ServerRequest::CreateElicitationRequest(request) => {
+                let validates = self
+                    .get_info()
+                    .capabilities
+                    .elicitation
+                    .as_ref()
+                    .is_some_and(ElicitationCapability::validates_schema);
+                if !validates {
+                    return self
+                        .create_elicitation(request.params, context)
+                        .await
+                        .map(ClientResult::CreateElicitationResult);
+                }
+                let request = request.params;
+                let mut result = self
+                    .create_elicitation(request.clone(), context.clone())
+                    .await?;
+                while let Err(errors) = request.validate_response(&result) {
+                    result = self
+                        .revise_elicitation(request.clone(), result, errors, context.clone())
+                        .await?;
+                }
+                Ok(ClientResult::CreateElicitationResult(result))
+            }
             ServerRequest::CustomRequest(request) => self
                 .on_custom_request(request, context)
                 .await
@@ -63,6 +91,9 @@ impl<H: ClientHandler> Service<RoleClient> for H {
             ServerNotification::PromptListChangedNotification(_notification_no_param) => {
                 self.on_prompt_list_changed(context).await
             }
+            ServerNotification::TaskStatusNotification(notification) => {
+                self.on_task_status(notification.params, context).await
+            }
             ServerNotification::CustomNotification(notification) => {
                 self.on_custom_notification(notification, context).await
             }
@@ -73,6 +104,10 @@ impl<H: ClientHandler> Service<RoleClient> for H {
     fn get_info(&self) -> <RoleClient as ServiceRole>::Info {
         self.get_info()
     }
+
+    fn config(&self) -> ServiceConfig {
+        self.config()
+    }
 }
 
 #[allow(unused_variables)]
@@ -136,6 +171,30 @@ pub trait ClientHandler: Sized + Send + Sync + 'static {
         }))
     }
 
+    /// Revise an accepted elicitation response that doesn't match the requested schema.
+    ///
+    /// Only called when the client advertises `form.schemaValidation`, see
+    /// [`ElicitationCapability::validates_schema`]. `errors` has one entry per
+    /// offending field, e.g. to show next to the form when prompting the user
+    /// again. The revised response is checked again before it is sent.
+    ///
+    /// # Default Behavior
+    /// The default implementation answers the server with an invalid params
+    /// error carrying the field errors.
+    fn revise_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        result: CreateElicitationResult,
+        errors: ElicitationValidationError,
+        context: RequestContext<RoleClient>,
+    ) -> impl Future<Output = Result<CreateElicitationResult, McpError>> + Send + '_ {
+        let _ = (request, result, context);
+        std::future::ready(Err(McpError::invalid_params(
+            errors.to_string(),
+            Some(serde_json::json!({ "errors": errors.errors })),
+        )))
+    }
+
     fn on_custom_request(
         &self,
         request: CustomRequest,
@@ -196,6 +255,14 @@ pub trait ClientHandler: Sized + Send + Sync + 'static {
     ) -> impl Future<Output = ()> + Send + '_ {
         std::future::ready(())
     }
+    /// The status of a task created by a task-augmented request changed.
+    fn on_task_status(
+        &self,
+        params: TaskStatusNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        std::future::ready(())
+    }
     fn on_custom_notification(
         &self,
         notification: CustomNotification,
@@ -208,6 +275,12 @@ pub trait ClientHandler: Sized + Send + Sync + 'static {
     fn get_info(&self) -> ClientInfo {
         ClientInfo::default()
     }
+
+    /// How sessions of this client run, e.g. one message at a time for
+    /// reproducible tests, see [`ServiceConfig`].
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig::default()
+    }
 }
 
 /// Do nothing, with default client info.
@@ -253,6 +326,16 @@ macro_rules! impl_client_handler_for_wrapper {
                 (**self).create_elicitation(request, context)
             }
 
+            fn revise_elicitation(
+                &self,
+                request: CreateElicitationRequestParams,
+                result: CreateElicitationResult,
+                errors: ElicitationValidationError,
+                context: RequestContext<RoleClient>,
+            ) -> impl Future<Output = Result<CreateElicitationResult, McpError>> + Send + '_ {
+                (**self).revise_elicitation(request, result, errors, context)
+            }
+
             fn on_custom_request(
                 &self,
                 request: CustomRequest,
@@ -314,6 +397,14 @@ macro_rules! impl_client_handler_for_wrapper {
                 (**self).on_prompt_list_changed(context)
             }
 
+            fn on_task_status(
+                &self,
+                params: TaskStatusNotificationParam,
+                context: NotificationContext<RoleClient>,
+            ) -> impl Future<Output = ()> + Send + '_ {
+                (**self).on_task_status(params, context)
+            }
+
             fn on_custom_notification(
                 &self,
                 notification: CustomNotification,
@@ -325,6 +416,10 @@ macro_rules! impl_client_handler_for_wrapper {
             fn get_info(&self) -> ClientInfo {
                 (**self).get_info()
             }
+
+            fn config(&self) -> ServiceConfig {
+                (**self).config()
+            }
         }
     };
 }
@@ -0,0 +1,283 @@
+//! A cache of `resources/read` results on the client.
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::{
+    RoleClient,
+    model::{
+        ReadResourceRequestParams, ReadResourceResult, ResourceContents,
+        ResourceUpdatedNotificationParam,
+    },
+    service::{Peer, ServiceError},
+};
+
+/// Caches `resources/read` results, so UI layers can show resources without
+/// waiting on the server.
+///
+/// [`read`](Self::read) answers from the cache while the entry is fresh and
+/// reads from the server otherwise; [`get_cached`](Self::get_cached) only
+/// looks at the cache. Entries go stale after the [TTL](Self::with_ttl), if
+/// any, and when [`invalidate`](Self::invalidate)d, e.g. from
+/// [`ClientHandler::on_resource_updated`](crate::ClientHandler::on_resource_updated).
+/// Stale entries with an entity tag are revalidated with a conditional read,
+/// see [`ConditionalReads`](crate::model::ConditionalReads).
+///
+/// The cache holds at most `max_bytes` of contents, evicting the least
+/// recently used entries first. Clones share the same entries.
+#[derive(Debug, Clone)]
+pub struct ResourceCache {
+    max_bytes: usize,
+    ttl: Option<Duration>,
+    entries: Arc<Mutex<CacheEntries>>,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    map: HashMap<String, CacheEntry>,
+    bytes: usize,
+    clock: u64,
+    /// Resources being read from the server.
+    reads: HashMap<String, PendingRead>,
+}
+
+/// Reads of one resource in flight, and how often it was invalidated since
+/// the first of them started.
+#[derive(Debug, Default)]
+struct PendingRead {
+    generation: u64,
+    readers: usize,
+}
+
+/// Counts as a reader of its resource until dropped, even if the read is
+/// abandoned.
+struct ReadGuard<'a> {
+    cache: &'a ResourceCache,
+    uri: &'a str,
+    generation: u64,
+}
+
+impl Drop for ReadGuard<'_> {
+    fn drop(&mut self) {
+        let mut entries = self.cache.entries.lock().expect("resource cache poisoned");
+        if let Some(read) = entries.reads.get_mut(self.uri) {
+            read.readers -= 1;
+            if read.readers == 0 {
+                entries.reads.remove(self.uri);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    result: ReadResourceResult,
+    bytes: usize,
+    fresh_until: Option<Instant>,
+    stale: bool,
+    last_used: u64,
+}
+
+impl CacheEntry {
+    fn is_fresh(&self, now: Instant) -> bool {
+        !self.stale && self.fresh_until.is_none_or(|fresh_until| now < fresh_until)
+    }
+}
+
+impl ResourceCache {
+    pub const DEFAULT_MAX_BYTES: usize = 16 * 1024 * 1024;
+
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            ttl: None,
+            entries: Default::default(),
+        }
+    }
+
+    /// Let entries go stale `ttl` after they were read.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// The cached contents of `uri`, if they are fresh.
+    pub fn get_cached(&self, uri: &str) -> Option<ReadResourceResult> {
+        let mut entries = self.entries.lock().expect("resource cache poisoned");
+        entries.clock += 1;
+        let clock = entries.clock;
+        let entry = entries.map.get_mut(uri)?;
+        if !entry.is_fresh(Instant::now()) {
+            return None;
+        }
+        entry.last_used = clock;
+        Some(entry.result.clone())
+    }
+
+    /// The contents of `uri`, from the cache if they are fresh and from the
+    /// server otherwise.
+    pub async fn read(
+        &self,
+        peer: &Peer<RoleClient>,
+        uri: &str,
+    ) -> Result<ReadResourceResult, ServiceError> {
+        if let Some(result) = self.get_cached(uri) {
+            return Ok(result);
+        }
+        let read = self.start_read(uri);
+        let stale = self.tagged_entry(uri);
+        let result = match stale {
+            Some((etag, cached)) => match peer.read_resource_if_modified(uri, Some(&etag)).await? {
+                Some(result) => result,
+                None => cached,
+            },
+            None => {
+                peer.read_resource(ReadResourceRequestParams::new(uri))
+                    .await?
+            }
+        };
+        self.store(uri.to_owned(), result.clone(), Some(&read));
+        Ok(result)
+    }
+
+    /// Cache `result` as the fresh contents of `uri`. Results larger than the
+    /// whole cache aren't kept.
+    pub fn insert(&self, uri: impl Into<String>, result: ReadResourceResult) {
+        self.store(uri.into(), result, None);
+    }
+
+    fn start_read<'a>(&'a self, uri: &'a str) -> ReadGuard<'a> {
+        let mut entries = self.entries.lock().expect("resource cache poisoned");
+        let read = entries.reads.entry(uri.to_owned()).or_default();
+        read.readers += 1;
+        ReadGuard {
+            cache: self,
+            uri,
+            generation: read.generation,
+        }
+    }
+
+    /// Cache `result` as the contents of `uri`, stale if `uri` was
+    /// invalidated during the `read` that got it: the invalidation may be
+    /// about newer contents.
+    fn store(&self, uri: String, result: ReadResourceResult, read: Option<&ReadGuard<'_>>) {
+        let bytes = result_bytes(&result);
+        let mut entries = self.entries.lock().expect("resource cache poisoned");
+        let stale = read.is_some_and(|read| {
+            entries
+                .reads
+                .get(read.uri)
+                .is_some_and(|pending| pending.generation != read.generation)
+        });
+        if let Some(previous) = entries.map.remove(&uri) {
+            entries.bytes -= previous.bytes;
+        }
+        if bytes > self.max_bytes {
+            return;
+        }
+        while entries.bytes + bytes > self.max_bytes {
+            let oldest = entries
+                .map
+                .iter()
+                .min_by_key(|(_, entry)| (!entry.stale, entry.last_used))
+                .map(|(uri, _)| uri.clone());
+            let Some(oldest) = oldest else { break };
+            if let Some(evicted) = entries.map.remove(&oldest) {
+                entries.bytes -= evicted.bytes;
+            }
+        }
+        entries.clock += 1;
+        let last_used = entries.clock;
+        entries.bytes += bytes;
+        entries.map.insert(
+            uri,
+            CacheEntry {
+                result,
+                bytes,
+                fresh_until: self.ttl.map(|ttl| Instant::now() + ttl),
+                stale,
+                last_used,
+            },
+        );
+    }
+
+    /// Mark the contents of `uri` stale, so the next
+    /// [`read`](Self::read) asks the server. Reads of `uri` already in flight
+    /// cache their result as stale. Returns `false` if it wasn't cached.
+    pub fn invalidate(&self, uri: &str) -> bool {
+        let mut entries = self.entries.lock().expect("resource cache poisoned");
+        if let Some(read) = entries.reads.get_mut(uri) {
+            read.generation += 1;
+        }
+        match entries.map.get_mut(uri) {
+            Some(entry) => {
+                entry.stale = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// [`invalidate`](Self::invalidate) the resource a
+    /// `notifications/resources/updated` is about.
+    pub fn on_resource_updated(&self, params: &ResourceUpdatedNotificationParam) {
+        self.invalidate(&params.uri);
+    }
+
+    pub fn remove(&self, uri: &str) -> Option<ReadResourceResult> {
+        let mut entries = self.entries.lock().expect("resource cache poisoned");
+        let entry = entries.map.remove(uri)?;
+        entries.bytes -= entry.bytes;
+        Some(entry.result)
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().expect("resource cache poisoned");
+        entries.map.clear();
+        entries.bytes = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("resource cache poisoned")
+            .map
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Size of the cached contents, in bytes.
+    pub fn size_bytes(&self) -> usize {
+        self.entries.lock().expect("resource cache poisoned").bytes
+    }
+
+    fn tagged_entry(&self, uri: &str) -> Option<(String, ReadResourceResult)> {
+        let entries = self.entries.lock().expect("resource cache poisoned");
+        let entry = entries.map.get(uri)?;
+        Some((entry.result.etag()?.to_owned(), entry.result.clone()))
+    }
+}
+
+impl Default for ResourceCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_BYTES)
+    }
+}
+
+fn result_bytes(result: &ReadResourceResult) -> usize {
+    result
+        .contents
+        .iter()
+        .map(|contents| match contents {
+            ResourceContents::TextResourceContents { text, .. } => text.len(),
+            ResourceContents::BlobResourceContents { blob, .. } => blob.len(),
+        })
+        .sum()
+}
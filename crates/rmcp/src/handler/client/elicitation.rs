@@ -0,0 +1,572 @@
+//! Ready-made elicitation handlers, so simple clients can answer
+//! `elicitation/create` without rendering forms themselves.
+//!
+//! [`ConsoleElicitationHandler`] prompts for each field on a terminal, and
+//! [`CallbackElicitationHandler`] hands the request to a GUI host. Both are
+//! [`ClientHandler`]s advertising form and URL elicitation with schema
+//! validation; to combine them with other handlers, call their
+//! `create_elicitation` and `revise_elicitation` from your own.
+use std::{borrow::Cow, sync::Arc};
+
+use futures::{FutureExt, future::BoxFuture};
+use serde_json::{Map, Number, Value};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader, Stdin, Stdout},
+    sync::Mutex,
+};
+
+use crate::{
+    error::ErrorData as McpError,
+    handler::client::ClientHandler,
+    model::*,
+    service::{RequestContext, RoleClient},
+};
+
+fn elicitation_client_info() -> ClientInfo {
+    ClientInfo {
+        capabilities: ClientCapabilities::builder()
+            .enable_elicitation_both()
+            .enable_elicitation_form_validation()
+            .build(),
+        ..Default::default()
+    }
+}
+
+/// A property of a requested form, as presented to the user.
+#[derive(Debug, Clone, Copy)]
+pub struct ElicitationField<'a> {
+    pub name: &'a str,
+    pub schema: &'a PrimitiveSchema,
+    pub required: bool,
+}
+
+/// One of the values an enum field accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElicitationOption {
+    pub value: String,
+    pub title: Option<String>,
+}
+
+impl<'a> ElicitationField<'a> {
+    /// The fields of `schema`, in property order.
+    pub fn all(schema: &'a ElicitationSchema) -> Vec<ElicitationField<'a>> {
+        schema
+            .properties
+            .iter()
+            .map(|(name, property)| ElicitationField {
+                name,
+                schema: property,
+                required: schema.required.iter().flatten().any(|field| field == name),
+            })
+            .collect()
+    }
+
+    /// The title of the field, or its name.
+    pub fn label(&self) -> &'a str {
+        self.title_and_description().0.unwrap_or(self.name)
+    }
+
+    pub fn description(&self) -> Option<&'a str> {
+        self.title_and_description().1
+    }
+
+    fn title_and_description(&self) -> (Option<&'a str>, Option<&'a str>) {
+        let (title, description) = match self.schema {
+            PrimitiveSchema::String(schema) => (&schema.title, &schema.description),
+            PrimitiveSchema::Number(schema) => (&schema.title, &schema.description),
+            PrimitiveSchema::Integer(schema) => (&schema.title, &schema.description),
+            PrimitiveSchema::Boolean(schema) => (&schema.title, &schema.description),
+            PrimitiveSchema::Enum(EnumSchema::Single(SingleSelectEnumSchema::Untitled(schema))) => {
+                (&schema.title, &schema.description)
+            }
+            PrimitiveSchema::Enum(EnumSchema::Single(SingleSelectEnumSchema::Titled(schema))) => {
+                (&schema.title, &schema.description)
+            }
+            PrimitiveSchema::Enum(EnumSchema::Multi(MultiSelectEnumSchema::Untitled(schema))) => {
+                (&schema.title, &schema.description)
+            }
+            PrimitiveSchema::Enum(EnumSchema::Multi(MultiSelectEnumSchema::Titled(schema))) => {
+                (&schema.title, &schema.description)
+            }
+            PrimitiveSchema::Enum(EnumSchema::Legacy(schema)) => {
+                (&schema.title, &schema.description)
+            }
+        };
+        (title.as_deref(), description.as_deref())
+    }
+
+    /// The value the schema suggests, if any.
+    pub fn default_value(&self) -> Option<Value> {
+        match self.schema {
+            PrimitiveSchema::Boolean(schema) => schema.default.map(Value::Bool),
+            PrimitiveSchema::Enum(EnumSchema::Single(SingleSelectEnumSchema::Untitled(schema))) => {
+                schema.default.clone().map(Value::String)
+            }
+            PrimitiveSchema::Enum(EnumSchema::Single(SingleSelectEnumSchema::Titled(schema))) => {
+                schema.default.clone().map(Value::String)
+            }
+            PrimitiveSchema::Enum(EnumSchema::Multi(MultiSelectEnumSchema::Untitled(schema))) => {
+                schema.default.clone().map(Value::from)
+            }
+            PrimitiveSchema::Enum(EnumSchema::Multi(MultiSelectEnumSchema::Titled(schema))) => {
+                schema.default.clone().map(Value::from)
+            }
+            _ => None,
+        }
+    }
+
+    /// The accepted values of an enum field, and whether several may be
+    /// selected.
+    pub fn options(&self) -> Option<(Vec<ElicitationOption>, bool)> {
+        let untitled = |values: &[String]| {
+            values
+                .iter()
+                .map(|value| ElicitationOption {
+                    value: value.clone(),
+                    title: None,
+                })
+                .collect()
+        };
+        let titled = |options: &[ConstTitle]| {
+            options
+                .iter()
+                .map(|option| ElicitationOption {
+                    value: option.const_.clone(),
+                    title: Some(option.title.clone()),
+                })
+                .collect()
+        };
+        let PrimitiveSchema::Enum(schema) = self.schema else {
+            return None;
+        };
+        Some(match schema {
+            EnumSchema::Single(SingleSelectEnumSchema::Untitled(schema)) => {
+                (untitled(&schema.enum_), false)
+            }
+            EnumSchema::Single(SingleSelectEnumSchema::Titled(schema)) => {
+                (titled(&schema.one_of), false)
+            }
+            EnumSchema::Multi(MultiSelectEnumSchema::Untitled(schema)) => {
+                (untitled(&schema.items.enum_), true)
+            }
+            EnumSchema::Multi(MultiSelectEnumSchema::Titled(schema)) => {
+                (titled(&schema.items.any_of), true)
+            }
+            EnumSchema::Legacy(schema) => (
+                schema
+                    .enum_
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| ElicitationOption {
+                        value: value.clone(),
+                        title: schema
+                            .enum_names
+                            .as_ref()
+                            .and_then(|names| names.get(index).cloned()),
+                    })
+                    .collect(),
+                false,
+            ),
+        })
+    }
+
+    /// Turn what the user typed into a value for this field, then check it.
+    ///
+    /// Enum options may be given by value or by their position, counting from
+    /// 1, and separated by commas when several may be selected.
+    pub fn parse(&self, input: &str) -> Result<Value, String> {
+        let input = input.trim();
+        let value = match self.schema {
+            PrimitiveSchema::String(_) => Value::String(input.to_owned()),
+            PrimitiveSchema::Number(_) => input
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .map(Value::Number)
+                .ok_or("expected a number")?,
+            PrimitiveSchema::Integer(_) => Value::from(
+                input
+                    .parse::<i64>()
+                    .map_err(|_| "expected an integer".to_owned())?,
+            ),
+            PrimitiveSchema::Boolean(_) => match input.to_ascii_lowercase().as_str() {
+                "y" | "yes" | "true" => Value::Bool(true),
+                "n" | "no" | "false" => Value::Bool(false),
+                _ => return Err("expected yes or no".to_owned()),
+            },
+            PrimitiveSchema::Enum(_) => {
+                let (options, multi) = self.options().unwrap_or_default();
+                let pick = |input: &str| {
+                    let input = input.trim();
+                    input
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|position| options.get(position.checked_sub(1)?))
+                        .map(|option| option.value.as_str())
+                        .unwrap_or(input)
+                        .to_owned()
+                };
+                if multi {
+                    Value::from(
+                        input
+                            .split(',')
+                            .filter(|item| !item.trim().is_empty())
+                            .map(pick)
+                            .collect::<Vec<_>>(),
+                    )
+                } else {
+                    Value::String(pick(input))
+                }
+            }
+        };
+        self.schema.validate(&value)?;
+        Ok(value)
+    }
+}
+
+/// Prompts for elicitation responses on a terminal
+///
+/// Each field is asked for in turn, with its options, default and the reason
+/// a previous answer was rejected. An empty answer keeps the default, the end
+/// of input cancels.
+///
+/// ```rust,ignore
+/// let client = ConsoleElicitationHandler::stdio().serve(transport).await?;
+/// ```
+pub struct ConsoleElicitationHandler<R = BufReader<Stdin>, W = Stdout> {
+    console: Arc<Mutex<(R, W)>>,
+}
+
+impl<R, W> Clone for ConsoleElicitationHandler<R, W> {
+    fn clone(&self) -> Self {
+        Self {
+            console: self.console.clone(),
+        }
+    }
+}
+
+impl ConsoleElicitationHandler {
+    /// Prompt on the standard input and output.
+    ///
+    /// Only usable when the transport doesn't run over stdio itself.
+    pub fn stdio() -> Self {
+        Self::new(BufReader::new(tokio::io::stdin()), tokio::io::stdout())
+    }
+}
+
+enum Answer {
+    Yes,
+    No,
+    Cancel,
+}
+
+impl<R, W> ConsoleElicitationHandler<R, W>
+where
+    R: AsyncBufRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            console: Arc::new(Mutex::new((reader, writer))),
+        }
+    }
+
+    /// Ask the user for a response to `request`. When revising, `previous` is
+    /// the rejected response, whose values are offered again, and `errors`
+    /// what was wrong with it.
+    pub async fn prompt(
+        &self,
+        request: &CreateElicitationRequestParams,
+        previous: Option<&CreateElicitationResult>,
+        errors: Option<&ElicitationValidationError>,
+    ) -> std::io::Result<CreateElicitationResult> {
+        let mut console = self.console.lock().await;
+        let (reader, writer) = &mut *console;
+        let mut console = Console { reader, writer };
+
+        console.line(&request.message).await?;
+        for error in errors.iter().flat_map(|errors| &errors.errors) {
+            console.line(&format!("  ! {error}")).await?;
+        }
+        if request.is_url_mode() {
+            if let Some(url) = &request.url {
+                console.line(&format!("Open {url}")).await?;
+            }
+            return Ok(match console.confirm("Done?").await? {
+                Answer::Yes => CreateElicitationResult {
+                    action: ElicitationAction::Accept,
+                    content: None,
+                },
+                Answer::No => CreateElicitationResult::decline(),
+                Answer::Cancel => CreateElicitationResult::cancel(),
+            });
+        }
+        match console.confirm("Respond?").await? {
+            Answer::Yes => {}
+            Answer::No => return Ok(CreateElicitationResult::decline()),
+            Answer::Cancel => return Ok(CreateElicitationResult::cancel()),
+        }
+
+        let previous = previous
+            .and_then(|previous| previous.content.as_ref())
+            .and_then(Value::as_object);
+        let mut content = Map::new();
+        let fields = request
+            .requested_schema
+            .as_ref()
+            .map(ElicitationField::all)
+            .unwrap_or_default();
+        for field in fields {
+            let default = previous
+                .and_then(|previous| previous.get(field.name).cloned())
+                .or_else(|| field.default_value());
+            match console.field(&field, default).await? {
+                Some(Some(value)) => {
+                    content.insert(field.name.to_owned(), value);
+                }
+                Some(None) => {}
+                None => return Ok(CreateElicitationResult::cancel()),
+            }
+        }
+        Ok(CreateElicitationResult::accept(Value::Object(content)))
+    }
+}
+
+struct Console<'a, R, W> {
+    reader: &'a mut R,
+    writer: &'a mut W,
+}
+
+impl<R, W> Console<'_, R, W>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    async fn line(&mut self, line: &str) -> std::io::Result<()> {
+        self.writer.write_all(line.as_bytes()).await?;
+        self.writer.write_all(b"\n").await?;
+        self.writer.flush().await
+    }
+
+    /// Ask `question`, returning the trimmed answer, or `None` at the end of input.
+    async fn ask(&mut self, question: &str) -> std::io::Result<Option<String>> {
+        self.writer.write_all(question.as_bytes()).await?;
+        self.writer.flush().await?;
+        let mut answer = String::new();
+        if self.reader.read_line(&mut answer).await? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(answer.trim().to_owned()))
+    }
+
+    async fn confirm(&mut self, question: &str) -> std::io::Result<Answer> {
+        loop {
+            let Some(answer) = self.ask(&format!("{question} [Y/n/c] ")).await? else {
+                return Ok(Answer::Cancel);
+            };
+            match answer.to_ascii_lowercase().as_str() {
+                "" | "y" | "yes" => return Ok(Answer::Yes),
+                "n" | "no" => return Ok(Answer::No),
+                "c" | "cancel" => return Ok(Answer::Cancel),
+                _ => self.line("  ! answer y, n or c").await?,
+            }
+        }
+    }
+
+    /// Ask for a field until the answer is valid: `Some(None)` when left
+    /// empty, `None` at the end of input.
+    async fn field(
+        &mut self,
+        field: &ElicitationField<'_>,
+        default: Option<Value>,
+    ) -> std::io::Result<Option<Option<Value>>> {
+        let mut question = field.label().to_owned();
+        if field.required {
+            question.push('*');
+        }
+        if let Some(description) = field.description() {
+            question.push_str(&format!(" ({description})"));
+        }
+        match field.options() {
+            Some((options, multi)) => {
+                self.line(&question).await?;
+                for (position, option) in options.iter().enumerate() {
+                    let title = option.title.as_deref().unwrap_or(&option.value);
+                    self.line(&format!("  {}) {title}", position + 1)).await?;
+                }
+                question = if multi {
+                    "Choose, separated by commas".to_owned()
+                } else {
+                    "Choose".to_owned()
+                };
+            }
+            None if matches!(field.schema, PrimitiveSchema::Boolean(_)) => {
+                question.push_str(" [y/n]");
+            }
+            None => {}
+        }
+        if let Some(default) = &default {
+            let default: Cow<'_, str> = match default {
+                Value::String(string) => string.into(),
+                Value::Array(items) => items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+                    .into(),
+                other => other.to_string().into(),
+            };
+            question.push_str(&format!(" [{default}]"));
+        }
+        question.push_str(": ");
+
+        loop {
+            let Some(answer) = self.ask(&question).await? else {
+                return Ok(None);
+            };
+            if answer.is_empty() {
+                if default.is_some() || !field.required {
+                    return Ok(Some(default));
+                }
+                self.line("  ! is required").await?;
+                continue;
+            }
+            match field.parse(&answer) {
+                Ok(value) => return Ok(Some(Some(value))),
+                Err(error) => self.line(&format!("  ! {error}")).await?,
+            }
+        }
+    }
+}
+
+impl<R, W> ClientHandler for ConsoleElicitationHandler<R, W>
+where
+    R: AsyncBufRead + Unpin + Send + Sync + 'static,
+    W: AsyncWrite + Unpin + Send + Sync + 'static,
+{
+    async fn create_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        self.prompt(&request, None, None)
+            .await
+            .map_err(|error| McpError::internal_error(format!("failed to prompt: {error}"), None))
+    }
+
+    async fn revise_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        result: CreateElicitationResult,
+        errors: ElicitationValidationError,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        self.prompt(&request, Some(&result), Some(&errors))
+            .await
+            .map_err(|error| McpError::internal_error(format!("failed to prompt: {error}"), None))
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        elicitation_client_info()
+    }
+}
+
+/// An elicitation for a GUI host to render, see [`CallbackElicitationHandler`].
+#[derive(Debug, Clone)]
+pub struct ElicitationPrompt {
+    pub request: CreateElicitationRequestParams,
+    /// The rejected response, when asking again
+    pub previous: Option<CreateElicitationResult>,
+    /// What was wrong with the rejected response, to show next to its fields
+    pub errors: Option<ElicitationValidationError>,
+}
+
+impl ElicitationPrompt {
+    /// The fields of the requested form, empty in URL mode.
+    pub fn fields(&self) -> Vec<ElicitationField<'_>> {
+        self.request
+            .requested_schema
+            .as_ref()
+            .map(ElicitationField::all)
+            .unwrap_or_default()
+    }
+}
+
+type ElicitationCallback =
+    Arc<dyn Fn(ElicitationPrompt) -> BoxFuture<'static, CreateElicitationResult> + Send + Sync>;
+
+/// Hands elicitation requests to a GUI host
+///
+/// The callback renders the [`ElicitationPrompt`], e.g. in a dialog, and
+/// resolves with the user's response. Responses that don't match the schema
+/// come back to it with their errors.
+///
+/// ```rust,ignore
+/// let handler = CallbackElicitationHandler::new(move |prompt| {
+///     let window = window.clone();
+///     async move { window.show_form(prompt).await }
+/// });
+/// ```
+#[derive(Clone)]
+pub struct CallbackElicitationHandler {
+    callback: ElicitationCallback,
+}
+
+impl std::fmt::Debug for CallbackElicitationHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackElicitationHandler")
+            .finish_non_exhaustive()
+    }
+}
+
+impl CallbackElicitationHandler {
+    pub fn new<F, Fut>(callback: F) -> Self
+    where
+        F: Fn(ElicitationPrompt) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = CreateElicitationResult> + Send + 'static,
+    {
+        Self {
+            callback: Arc::new(move |prompt| callback(prompt).boxed()),
+        }
+    }
+
+    pub async fn prompt(&self, prompt: ElicitationPrompt) -> CreateElicitationResult {
+        (self.callback)(prompt).await
+    }
+}
+
+impl ClientHandler for CallbackElicitationHandler {
+    async fn create_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        Ok(self
+            .prompt(ElicitationPrompt {
+                request,
+                previous: None,
+                errors: None,
+            })
+            .await)
+    }
+
+    async fn revise_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        result: CreateElicitationResult,
+        errors: ElicitationValidationError,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        Ok(self
+            .prompt(ElicitationPrompt {
+                request,
+                previous: Some(result),
+                errors: Some(errors),
+            })
+            .await)
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        elicitation_client_info()
+    }
+}
@@ -0,0 +1,171 @@
+//! A local copy of server resources that follows their updates.
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    RoleClient,
+    model::{
+        ReadResourceRequestParams, ReadResourceResult, ResourceContents, ResourceDelta,
+        ResourceUpdatedNotificationParam, SubscribeRequestParams, UnsubscribeRequestParams,
+    },
+    service::{NotificationContext, Peer, ServiceError},
+};
+
+/// The latest contents of the resources it tracks.
+///
+/// Call [`on_resource_updated`](Self::on_resource_updated) from
+/// [`ClientHandler::on_resource_updated`](crate::ClientHandler::on_resource_updated).
+/// Updates that carry a [`ResourceDelta`] for the version the mirror has are
+/// applied locally; any other update reads the resource again, conditionally
+/// when the mirror knows its entity tag. Advertise
+/// [`ResourceDeltas`](crate::model::ResourceDeltas) to get deltas.
+///
+/// Cloning is cheap and all clones share the same contents.
+#[derive(Debug, Clone, Default)]
+pub struct ResourceMirror {
+    resources: Arc<RwLock<HashMap<String, ReadResourceResult>>>,
+}
+
+impl ResourceMirror {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking `uri`: subscribe to its updates if the server supports
+    /// subscriptions, and read it.
+    pub async fn track(
+        &self,
+        peer: &Peer<RoleClient>,
+        uri: impl Into<String>,
+    ) -> Result<ReadResourceResult, ServiceError> {
+        let uri = uri.into();
+        if supports_subscribe(peer) {
+            peer.subscribe(SubscribeRequestParams {
+                meta: None,
+                uri: uri.clone(),
+            })
+            .await?;
+        }
+        let result = peer
+            .read_resource(ReadResourceRequestParams::new(uri.clone()))
+            .await?;
+        self.store(uri, result.clone());
+        Ok(result)
+    }
+
+    /// Stop tracking `uri`, unsubscribing from its updates.
+    pub async fn untrack(&self, peer: &Peer<RoleClient>, uri: &str) -> Result<(), ServiceError> {
+        let tracked = self
+            .resources
+            .write()
+            .expect("resource mirror poisoned")
+            .remove(uri)
+            .is_some();
+        if tracked && supports_subscribe(peer) {
+            peer.unsubscribe(UnsubscribeRequestParams {
+                meta: None,
+                uri: uri.to_owned(),
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub fn is_tracked(&self, uri: &str) -> bool {
+        self.resources
+            .read()
+            .expect("resource mirror poisoned")
+            .contains_key(uri)
+    }
+
+    /// The latest contents of `uri`, if it's tracked.
+    pub fn get(&self, uri: &str) -> Option<ReadResourceResult> {
+        self.resources
+            .read()
+            .expect("resource mirror poisoned")
+            .get(uri)
+            .cloned()
+    }
+
+    /// The latest text of `uri`, if it's tracked and has text contents.
+    pub fn text(&self, uri: &str) -> Option<String> {
+        self.get(uri)?.text().ok().map(str::to_owned)
+    }
+
+    /// Bring the resource up to date after `notifications/resources/updated`.
+    /// Updates of resources that aren't tracked are ignored.
+    pub async fn on_resource_updated(
+        &self,
+        params: &ResourceUpdatedNotificationParam,
+        context: &NotificationContext<RoleClient>,
+    ) -> Result<(), ServiceError> {
+        let Some(current) = self.get(&params.uri) else {
+            return Ok(());
+        };
+        if let Some(updated) = context
+            .meta
+            .resource_delta()
+            .and_then(|delta| apply_delta(&current, &delta))
+        {
+            self.store(params.uri.clone(), updated);
+            return Ok(());
+        }
+        let read = context
+            .peer
+            .read_resource_if_modified(params.uri.clone(), current.etag())
+            .await?;
+        if let Some(result) = read {
+            self.store(params.uri.clone(), result);
+        }
+        Ok(())
+    }
+
+    fn store(&self, uri: String, result: ReadResourceResult) {
+        self.resources
+            .write()
+            .expect("resource mirror poisoned")
+            .insert(uri, result);
+    }
+}
+
+fn supports_subscribe(peer: &Peer<RoleClient>) -> bool {
+    peer.peer_capabilities()
+        .and_then(|capabilities| capabilities.resources.as_ref())
+        .and_then(|resources| resources.subscribe)
+        .unwrap_or(false)
+}
+
+/// `current` with `delta` applied, if it's a single text entry at the
+/// delta's base version.
+fn apply_delta(current: &ReadResourceResult, delta: &ResourceDelta) -> Option<ReadResourceResult> {
+    let [contents] = current.contents.as_slice() else {
+        return None;
+    };
+    if contents.etag()? != delta.base_etag {
+        return None;
+    }
+    let text = delta.apply(contents.as_text()?)?;
+    let ResourceContents::TextResourceContents {
+        uri,
+        mime_type,
+        meta,
+        size,
+        ..
+    } = contents.clone()
+    else {
+        return None;
+    };
+    let contents = ResourceContents::TextResourceContents {
+        size: size.and_then(|_| u32::try_from(text.len()).ok()),
+        uri,
+        mime_type,
+        text,
+        meta,
+        last_modified: None,
+    };
+    Some(ReadResourceResult {
+        contents: vec![contents.with_etag(delta.etag.clone())],
+    })
+}
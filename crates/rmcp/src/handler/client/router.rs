@@ -0,0 +1,416 @@
+use futures::{FutureExt, future::BoxFuture};
+
+#[allow(deprecated)] // model::* includes deprecated roots types (MCP 2025-11-25)
+use crate::{
+    error::ErrorData as McpError,
+    handler::client::ClientHandler,
+    model::*,
+    service::{NotificationContext, RequestContext, RoleClient},
+};
+
+type RequestFn<P, R> = Box<
+    dyn Fn(P, RequestContext<RoleClient>) -> BoxFuture<'static, Result<R, McpError>> + Send + Sync,
+>;
+type NotificationFn<P> =
+    Box<dyn Fn(P, NotificationContext<RoleClient>) -> BoxFuture<'static, ()> + Send + Sync>;
+
+fn request_fn<P, R, F, Fut>(f: F) -> RequestFn<P, R>
+where
+    F: Fn(P, RequestContext<RoleClient>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<R, McpError>> + Send + 'static,
+{
+    Box::new(move |params, context| f(params, context).boxed())
+}
+
+fn notification_fn<P, F, Fut>(f: F) -> NotificationFn<P>
+where
+    F: Fn(P, NotificationContext<RoleClient>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    Box::new(move |params, context| f(params, context).boxed())
+}
+
+fn list_changed_fn<F, Fut>(f: F) -> NotificationFn<()>
+where
+    F: Fn(NotificationContext<RoleClient>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    Box::new(move |(), context| f(context).boxed())
+}
+
+/// A [`ClientHandler`] made of closures, for clients that only care about
+/// a callback or two.
+///
+/// Everything without a closure is handled by the inner handler, `()` or a
+/// [`ClientInfo`] when there is nothing else to do. Setting a sampling or
+/// elicitation closure also advertises the matching capability, unless the
+/// inner handler's info already does.
+///
+/// ```rust
+/// # use rmcp::{handler::client::router::ClientRouter, model::*};
+/// let client = ClientRouter::new(ClientInfo::default())
+///     .on_create_message(|_params, _context| async move {
+///         Ok(CreateMessageResult {
+///             message: SamplingMessage {
+///                 role: Role::Assistant,
+///                 content: Content::text("hello").into(),
+///             },
+///             model: "my-model".into(),
+///             stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.into()),
+///         })
+///     })
+///     .on_logging_message(|params, _context| async move {
+///         println!("[{:?}] {}", params.level, params.data);
+///     });
+/// ```
+pub struct ClientRouter<H = ()> {
+    inner: H,
+    create_message: Option<RequestFn<CreateMessageRequestParams, CreateMessageResult>>,
+    create_elicitation: Option<RequestFn<CreateElicitationRequestParams, CreateElicitationResult>>,
+    custom_request: Option<RequestFn<CustomRequest, CustomResult>>,
+    cancelled: Option<NotificationFn<CancelledNotificationParam>>,
+    progress: Option<NotificationFn<ProgressNotificationParam>>,
+    logging_message: Option<NotificationFn<LoggingMessageNotificationParam>>,
+    resource_updated: Option<NotificationFn<ResourceUpdatedNotificationParam>>,
+    resource_list_changed: Option<NotificationFn<()>>,
+    tool_list_changed: Option<NotificationFn<()>>,
+    prompt_list_changed: Option<NotificationFn<()>>,
+    task_status: Option<NotificationFn<TaskStatusNotificationParam>>,
+    custom_notification: Option<NotificationFn<CustomNotification>>,
+}
+
+impl<H: std::fmt::Debug> std::fmt::Debug for ClientRouter<H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientRouter")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ClientRouter {
+    fn default() -> Self {
+        Self::new(())
+    }
+}
+
+impl<H: ClientHandler> ClientRouter<H> {
+    pub fn new(inner: H) -> Self {
+        Self {
+            inner,
+            create_message: None,
+            create_elicitation: None,
+            custom_request: None,
+            cancelled: None,
+            progress: None,
+            logging_message: None,
+            resource_updated: None,
+            resource_list_changed: None,
+            tool_list_changed: None,
+            prompt_list_changed: None,
+            task_status: None,
+            custom_notification: None,
+        }
+    }
+
+    pub fn inner(&self) -> &H {
+        &self.inner
+    }
+
+    /// Answer `sampling/createMessage` requests.
+    pub fn on_create_message<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(CreateMessageRequestParams, RequestContext<RoleClient>) -> Fut
+            + Send
+            + Sync
+            + 'static,
+        Fut: Future<Output = Result<CreateMessageResult, McpError>> + Send + 'static,
+    {
+        self.create_message = Some(request_fn(f));
+        self
+    }
+
+    /// Answer `elicitation/create` requests.
+    pub fn on_create_elicitation<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(CreateElicitationRequestParams, RequestContext<RoleClient>) -> Fut
+            + Send
+            + Sync
+            + 'static,
+        Fut: Future<Output = Result<CreateElicitationResult, McpError>> + Send + 'static,
+    {
+        self.create_elicitation = Some(request_fn(f));
+        self
+    }
+
+    pub fn on_custom_request<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(CustomRequest, RequestContext<RoleClient>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<CustomResult, McpError>> + Send + 'static,
+    {
+        self.custom_request = Some(request_fn(f));
+        self
+    }
+
+    pub fn on_cancelled<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(CancelledNotificationParam, NotificationContext<RoleClient>) -> Fut
+            + Send
+            + Sync
+            + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.cancelled = Some(notification_fn(f));
+        self
+    }
+
+    pub fn on_progress<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(ProgressNotificationParam, NotificationContext<RoleClient>) -> Fut
+            + Send
+            + Sync
+            + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.progress = Some(notification_fn(f));
+        self
+    }
+
+    pub fn on_logging_message<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(LoggingMessageNotificationParam, NotificationContext<RoleClient>) -> Fut
+            + Send
+            + Sync
+            + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.logging_message = Some(notification_fn(f));
+        self
+    }
+
+    pub fn on_resource_updated<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(ResourceUpdatedNotificationParam, NotificationContext<RoleClient>) -> Fut
+            + Send
+            + Sync
+            + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.resource_updated = Some(notification_fn(f));
+        self
+    }
+
+    pub fn on_resource_list_changed<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(NotificationContext<RoleClient>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.resource_list_changed = Some(list_changed_fn(f));
+        self
+    }
+
+    pub fn on_tool_list_changed<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(NotificationContext<RoleClient>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.tool_list_changed = Some(list_changed_fn(f));
+        self
+    }
+
+    pub fn on_prompt_list_changed<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(NotificationContext<RoleClient>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.prompt_list_changed = Some(list_changed_fn(f));
+        self
+    }
+
+    pub fn on_task_status<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(TaskStatusNotificationParam, NotificationContext<RoleClient>) -> Fut
+            + Send
+            + Sync
+            + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.task_status = Some(notification_fn(f));
+        self
+    }
+
+    pub fn on_custom_notification<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(CustomNotification, NotificationContext<RoleClient>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.custom_notification = Some(notification_fn(f));
+        self
+    }
+}
+
+impl<H: ClientHandler> ClientHandler for ClientRouter<H> {
+    fn ping(
+        &self,
+        context: RequestContext<RoleClient>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        self.inner.ping(context)
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParams,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, McpError> {
+        match &self.create_message {
+            Some(f) => f(params, context).await,
+            None => self.inner.create_message(params, context).await,
+        }
+    }
+
+    #[allow(deprecated)]
+    fn list_roots(
+        &self,
+        context: RequestContext<RoleClient>,
+    ) -> impl Future<Output = Result<ListRootsResult, McpError>> + Send + '_ {
+        self.inner.list_roots(context)
+    }
+
+    async fn create_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        match &self.create_elicitation {
+            Some(f) => f(request, context).await,
+            None => self.inner.create_elicitation(request, context).await,
+        }
+    }
+
+    fn revise_elicitation(
+        &self,
+        request: CreateElicitationRequestParams,
+        result: CreateElicitationResult,
+        errors: ElicitationValidationError,
+        context: RequestContext<RoleClient>,
+    ) -> impl Future<Output = Result<CreateElicitationResult, McpError>> + Send + '_ {
+        self.inner
+            .revise_elicitation(request, result, errors, context)
+    }
+
+    async fn on_custom_request(
+        &self,
+        request: CustomRequest,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CustomResult, McpError> {
+        match &self.custom_request {
+            Some(f) => f(request, context).await,
+            None => self.inner.on_custom_request(request, context).await,
+        }
+    }
+
+    async fn on_cancelled(
+        &self,
+        params: CancelledNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) {
+        match &self.cancelled {
+            Some(f) => f(params, context).await,
+            None => self.inner.on_cancelled(params, context).await,
+        }
+    }
+
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) {
+        match &self.progress {
+            Some(f) => f(params, context).await,
+            None => self.inner.on_progress(params, context).await,
+        }
+    }
+
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) {
+        match &self.logging_message {
+            Some(f) => f(params, context).await,
+            None => self.inner.on_logging_message(params, context).await,
+        }
+    }
+
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) {
+        match &self.resource_updated {
+            Some(f) => f(params, context).await,
+            None => self.inner.on_resource_updated(params, context).await,
+        }
+    }
+
+    async fn on_resource_list_changed(&self, context: NotificationContext<RoleClient>) {
+        match &self.resource_list_changed {
+            Some(f) => f((), context).await,
+            None => self.inner.on_resource_list_changed(context).await,
+        }
+    }
+
+    async fn on_tool_list_changed(&self, context: NotificationContext<RoleClient>) {
+        match &self.tool_list_changed {
+            Some(f) => f((), context).await,
+            None => self.inner.on_tool_list_changed(context).await,
+        }
+    }
+
+    async fn on_prompt_list_changed(&self, context: NotificationContext<RoleClient>) {
+        match &self.prompt_list_changed {
+            Some(f) => f((), context).await,
+            None => self.inner.on_prompt_list_changed(context).await,
+        }
+    }
+
+    async fn on_task_status(
+        &self,
+        params: TaskStatusNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) {
+        match &self.task_status {
+            Some(f) => f(params, context).await,
+            None => self.inner.on_task_status(params, context).await,
+        }
+    }
+
+    async fn on_custom_notification(
+        &self,
+        notification: CustomNotification,
+        context: NotificationContext<RoleClient>,
+    ) {
+        match &self.custom_notification {
+            Some(f) => f(notification, context).await,
+            None => {
+                self.inner
+                    .on_custom_notification(notification, context)
+                    .await
+            }
+        }
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let mut info = self.inner.get_info();
+        if self.create_message.is_some() && info.capabilities.sampling.is_none() {
+            info.capabilities.sampling = Some(JsonObject::default());
+        }
+        if self.create_elicitation.is_some() && info.capabilities.elicitation.is_none() {
+            info.capabilities.elicitation = Some(ElicitationCapability::default());
+        }
+        info
+    }
+
+    fn config(&self) -> crate::service::ServiceConfig {
+        self.inner.config()
+    }
+}
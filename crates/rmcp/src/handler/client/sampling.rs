@@ -0,0 +1,355 @@
+//! Client side of sampling: `includeContext` and streamed answers.
+use std::sync::{Arc, RwLock};
+
+#[cfg(feature = "tokens")]
+use crate::tokens::Tokenizer;
+use crate::{
+    RoleClient,
+    model::{
+        ClientNotification, ContextInclusion, CreateMessageRequestParams, Meta,
+        ProgressNotification, ProgressNotificationParam, ProgressToken, ReadResourceRequestParams,
+        Resource, Role, SamplingChunk,
+    },
+    service::{Peer, RequestContext, ServiceError},
+};
+
+/// Sends the chunks of a streamed `sampling/createMessage` answer, see
+/// [`SamplingStreaming`](crate::model::SamplingStreaming).
+///
+/// Create one in [`ClientHandler::create_message`](crate::ClientHandler::create_message),
+/// [`send`](Self::send) the text as the model generates it, then return the
+/// whole message as usual.
+#[derive(Debug, Clone)]
+pub struct SamplingChunkSender {
+    peer: Peer<RoleClient>,
+    progress_token: ProgressToken,
+    next_index: u64,
+}
+
+impl SamplingChunkSender {
+    /// A sender for the request of `context`, or `None` if the server didn't
+    /// ask for chunks.
+    pub fn from_context(context: &RequestContext<RoleClient>) -> Option<Self> {
+        if !context.meta.is_stream_requested() {
+            return None;
+        }
+        Some(Self {
+            peer: context.peer.clone(),
+            progress_token: context.meta.get_progress_token()?,
+            next_index: 0,
+        })
+    }
+
+    /// Send the next piece of the completion.
+    pub async fn send(&mut self, text: impl Into<String>) -> Result<(), ServiceError> {
+        let chunk = SamplingChunk {
+            index: self.next_index,
+            text: text.into(),
+        };
+        self.next_index += 1;
+        let mut notification = ProgressNotification::new(ProgressNotificationParam {
+            progress_token: self.progress_token.clone(),
+            progress: self.next_index as f64,
+            total: None,
+            message: None,
+        });
+        let mut meta = Meta::new();
+        meta.set_sampling_chunk(&chunk);
+        notification.extensions.insert(meta);
+        self.peer
+            .send_notification(ClientNotification::ProgressNotification(notification))
+            .await
+    }
+
+    /// How many chunks were sent.
+    pub fn sent(&self) -> u64 {
+        self.next_index
+    }
+}
+
+type Servers = Arc<RwLock<Vec<(String, Peer<RoleClient>)>>>;
+
+/// The servers a client is connected to, by name, whose context sampling
+/// requests can include.
+///
+/// Cloning is cheap and all clones share the same servers.
+#[derive(Debug, Clone, Default)]
+pub struct ContextServers {
+    servers: Servers,
+}
+
+impl ContextServers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the server `peer` talks to, replacing any server named `name`.
+    pub fn insert(&self, name: impl Into<String>, peer: Peer<RoleClient>) {
+        let name = name.into();
+        let mut servers = self.servers.write().expect("context servers poisoned");
+        servers.retain(|(existing, _)| *existing != name);
+        servers.push((name, peer));
+    }
+
+    pub fn remove(&self, name: &str) -> Option<Peer<RoleClient>> {
+        let mut servers = self.servers.write().expect("context servers poisoned");
+        let index = servers.iter().position(|(existing, _)| existing == name)?;
+        Some(servers.remove(index).1)
+    }
+
+    /// The servers, in the order they were added.
+    pub fn list(&self) -> Vec<(String, Peer<RoleClient>)> {
+        self.servers
+            .read()
+            .expect("context servers poisoned")
+            .clone()
+    }
+
+    /// The name `peer`'s server was added with, or the name it gave in its
+    /// `initialize` answer.
+    pub fn name_of(&self, peer: &Peer<RoleClient>) -> String {
+        self.servers
+            .read()
+            .expect("context servers poisoned")
+            .iter()
+            .find(|(_, server)| server.is_same_session(peer))
+            .map(|(name, _)| name.clone())
+            .or_else(|| peer.peer_info().map(|info| info.server_info.name.clone()))
+            .unwrap_or_default()
+    }
+}
+
+/// A piece of context gathered from a server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextItem {
+    /// Where the text comes from, e.g. a resource URI.
+    pub label: String,
+    pub text: String,
+}
+
+impl ContextItem {
+    pub fn new(label: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            text: text.into(),
+        }
+    }
+}
+
+/// What "context" means for a server, when a sampling request asks to
+/// include it. The default, [`ResourceContext`], is the server's resources.
+pub trait ContextSource: Send + Sync + 'static {
+    /// Gather the context of `server`, most relevant first. `max_tokens` is
+    /// the budget still left; items beyond it are dropped anyway.
+    fn gather<'a>(
+        &'a self,
+        server: &'a Peer<RoleClient>,
+        max_tokens: usize,
+    ) -> impl Future<Output = Result<Vec<ContextItem>, ServiceError>> + Send + 'a;
+}
+
+/// The text resources of a server meant for the assistant, highest
+/// [priority](crate::model::Annotations::priority) first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResourceContext;
+
+impl ContextSource for ResourceContext {
+    async fn gather(
+        &self,
+        server: &Peer<RoleClient>,
+        max_tokens: usize,
+    ) -> Result<Vec<ContextItem>, ServiceError> {
+        let supports_resources = server
+            .peer_capabilities()
+            .is_some_and(|capabilities| capabilities.resources.is_some());
+        if !supports_resources {
+            return Ok(Vec::new());
+        }
+        let mut resources = server.list_all_resources().await?;
+        resources.retain(|resource| resource.is_for(Role::Assistant));
+        let priority = |resource: &Resource| {
+            resource
+                .annotations
+                .as_ref()
+                .and_then(|annotations| annotations.priority)
+                .unwrap_or(0.5)
+        };
+        resources.sort_by(|a, b| priority(b).total_cmp(&priority(a)));
+
+        let mut items = Vec::new();
+        let mut tokens = 0;
+        for resource in resources {
+            if tokens >= max_tokens {
+                break;
+            }
+            let result = server
+                .read_resource(ReadResourceRequestParams::new(resource.uri.clone()))
+                .await?;
+            if let Ok(text) = result.text() {
+                tokens += estimate_tokens(text);
+                items.push(ContextItem::new(resource.uri.clone(), text));
+            }
+        }
+        Ok(items)
+    }
+}
+
+/// Handles the `includeContext` of sampling requests: gathers the context of
+/// the servers it asks for from a [`ContextSource`] and adds it to the
+/// system prompt, within a token budget.
+///
+/// `thisServer` means the server making the request; `allServers` means that
+/// one first, then every other server of the [`ContextServers`]. Tokens are
+/// estimated at four bytes each, or counted by the
+/// [tokenizer](Self::with_tokenizer) with the `tokens` feature.
+#[derive(Clone)]
+pub struct ContextPolicy<S = ResourceContext> {
+    servers: ContextServers,
+    source: S,
+    max_tokens: usize,
+    #[cfg(feature = "tokens")]
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+}
+
+impl<S: std::fmt::Debug> std::fmt::Debug for ContextPolicy<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextPolicy")
+            .field("servers", &self.servers)
+            .field("source", &self.source)
+            .field("max_tokens", &self.max_tokens)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ContextPolicy {
+    pub const DEFAULT_MAX_TOKENS: usize = 4096;
+
+    pub fn new(servers: ContextServers) -> Self {
+        Self {
+            servers,
+            source: ResourceContext,
+            max_tokens: Self::DEFAULT_MAX_TOKENS,
+            #[cfg(feature = "tokens")]
+            tokenizer: None,
+        }
+    }
+}
+
+impl<S: ContextSource> ContextPolicy<S> {
+    pub fn with_source<T: ContextSource>(self, source: T) -> ContextPolicy<T> {
+        ContextPolicy {
+            servers: self.servers,
+            source,
+            max_tokens: self.max_tokens,
+            #[cfg(feature = "tokens")]
+            tokenizer: self.tokenizer,
+        }
+    }
+
+    /// Count the tokens of the context with `tokenizer`.
+    #[cfg(feature = "tokens")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokens")))]
+    pub fn with_tokenizer(mut self, tokenizer: impl Tokenizer) -> Self {
+        self.tokenizer = Some(Arc::new(tokenizer));
+        self
+    }
+
+    /// Include at most about `max_tokens` of context in a request.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    pub fn servers(&self) -> &ContextServers {
+        &self.servers
+    }
+
+    /// The context `include` asks for, by server name, for a request of
+    /// `requesting`. Servers that fail to give their context are skipped.
+    pub async fn gather(
+        &self,
+        include: &ContextInclusion,
+        requesting: &Peer<RoleClient>,
+    ) -> Vec<(String, Vec<ContextItem>)> {
+        let mut servers = vec![(self.servers.name_of(requesting), requesting.clone())];
+        if *include == ContextInclusion::AllServers {
+            servers.extend(
+                self.servers
+                    .list()
+                    .into_iter()
+                    .filter(|(_, peer)| !peer.is_same_session(requesting)),
+            );
+        } else if *include != ContextInclusion::ThisServer {
+            return Vec::new();
+        }
+
+        let mut gathered = Vec::new();
+        let mut tokens = 0;
+        for (name, peer) in servers {
+            let left = self.max_tokens.saturating_sub(tokens);
+            if left == 0 {
+                break;
+            }
+            let items = match self.source.gather(&peer, left).await {
+                Ok(items) => items,
+                Err(e) => {
+                    tracing::warn!(server = %name, "Failed to gather sampling context: {e}");
+                    continue;
+                }
+            };
+            let mut kept = Vec::new();
+            for item in items {
+                let cost = self.count_tokens(&item.label) + self.count_tokens(&item.text);
+                if tokens + cost <= self.max_tokens {
+                    tokens += cost;
+                    kept.push(item);
+                }
+            }
+            if !kept.is_empty() {
+                gathered.push((name, kept));
+            }
+        }
+        gathered
+    }
+
+    /// `params` with the context its `includeContext` asks for appended to
+    /// the system prompt.
+    pub async fn apply(
+        &self,
+        mut params: CreateMessageRequestParams,
+        requesting: &Peer<RoleClient>,
+    ) -> CreateMessageRequestParams {
+        let Some(include) = &params.include_context else {
+            return params;
+        };
+        let gathered = self.gather(include, requesting).await;
+        if gathered.is_empty() {
+            return params;
+        }
+        let mut prompt = params.system_prompt.take().unwrap_or_default();
+        for (name, items) in gathered {
+            if !prompt.is_empty() {
+                prompt.push_str("\n\n");
+            }
+            prompt.push_str(&format!("Context from {name}:"));
+            for item in items {
+                prompt.push_str(&format!("\n\n[{}]\n{}", item.label, item.text));
+            }
+        }
+        params.system_prompt = Some(prompt);
+        params
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        #[cfg(feature = "tokens")]
+        if let Some(tokenizer) = &self.tokenizer {
+            return tokenizer.count(text);
+        }
+        estimate_tokens(text)
+    }
+}
+
+/// Rough token count of `text`, about four bytes per token.
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
@@ -0,0 +1,220 @@
+//! Handlers for methods outside the MCP spec, such as `x-myorg/refresh`.
+//!
+//! Register typed handlers on a [`CustomMethodRouter`] and dispatch to it from
+//! `on_custom_request`; the other side calls them with `Peer::call_custom`.
+//! Either side can serve them: a client routes the requests of its server
+//! from `ClientHandler::on_custom_request` the same way. With a
+//! [`CustomMethod`], both ends share the method name and types, and
+//! `Peer::call_method` only sends the request if the peer advertised it.
+//!
+//! ```rust
+//! # use rmcp::{
+//! #     ErrorData, RoleServer, ServerHandler,
+//! #     handler::custom::CustomMethodRouter,
+//! #     model::{CustomRequest, CustomResult, ServerCapabilities, ServerInfo},
+//! #     service::RequestContext,
+//! # };
+//! #[derive(serde::Deserialize)]
+//! struct RefreshParams {
+//!     force: bool,
+//! }
+//!
+//! struct Server {
+//!     custom: CustomMethodRouter<RoleServer>,
+//! }
+//!
+//! impl Server {
+//!     fn new() -> Self {
+//!         let custom = CustomMethodRouter::new().route(
+//!             "x-myorg/refresh",
+//!             |params: RefreshParams, _context| async move { Ok(params.force) },
+//!         );
+//!         Self { custom }
+//!     }
+//! }
+//!
+//! impl ServerHandler for Server {
+//!     async fn on_custom_request(
+//!         &self,
+//!         request: CustomRequest,
+//!         context: RequestContext<RoleServer>,
+//!     ) -> Result<CustomResult, ErrorData> {
+//!         self.custom.handle(request, context).await
+//!     }
+//!
+//!     fn get_info(&self) -> ServerInfo {
+//!         ServerInfo {
+//!             capabilities: ServerCapabilities::builder()
+//!                 .enable_experimental_with(self.custom.experimental_capabilities())
+//!                 .build(),
+//!             ..Default::default()
+//!         }
+//!     }
+//! }
+//! ```
+use std::{collections::BTreeMap, sync::Arc};
+
+use futures::future::BoxFuture;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{
+    ErrorData,
+    model::{CustomRequest, CustomResult, ErrorCode, ExperimentalCapabilities},
+    service::{RequestContext, ServiceRole},
+};
+
+/// A method outside the MCP spec with typed params and result, served with
+/// [`CustomMethodRouter::route_method`] and called with `Peer::call_method`.
+///
+/// ```rust
+/// # use rmcp::handler::custom::CustomMethod;
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct RefreshParams {
+///     force: bool,
+/// }
+///
+/// struct Refresh;
+///
+/// impl CustomMethod for Refresh {
+///     const METHOD: &'static str = "x-myorg/refresh";
+///     type Params = RefreshParams;
+///     type Result = bool;
+/// }
+/// ```
+pub trait CustomMethod {
+    /// The JSON-RPC method, e.g. `"x-myorg/refresh"`. Also the key it is
+    /// advertised under in `capabilities.experimental`.
+    const METHOD: &'static str;
+    type Params: Serialize + DeserializeOwned + Send;
+    type Result: Serialize + DeserializeOwned;
+}
+
+type DynCustomHandler<R> = Arc<
+    dyn Fn(CustomRequest, RequestContext<R>) -> BoxFuture<'static, Result<CustomResult, ErrorData>>
+        + Send
+        + Sync,
+>;
+
+/// Dispatches custom requests to typed handlers by method name.
+pub struct CustomMethodRouter<R: ServiceRole> {
+    routes: BTreeMap<String, DynCustomHandler<R>>,
+}
+
+impl<R: ServiceRole> CustomMethodRouter<R> {
+    pub fn new() -> Self {
+        Self {
+            routes: BTreeMap::new(),
+        }
+    }
+
+    /// Handle `method` with `handler`.
+    ///
+    /// The request params are deserialized into `P` (missing or empty params
+    /// also read as `null`, so `()` and `Option<_>` work for methods without
+    /// any), and the handler's output is serialized as the result.
+    pub fn route<P, T, F, Fut>(mut self, method: impl Into<String>, handler: F) -> Self
+    where
+        P: DeserializeOwned,
+        T: Serialize,
+        F: Fn(P, RequestContext<R>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, ErrorData>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        let route: DynCustomHandler<R> = Arc::new(move |request, context| {
+            let handler = handler.clone();
+            Box::pin(async move {
+                let params = parse_params::<P>(request.params).map_err(|error| {
+                    ErrorData::invalid_params(
+                        format!("invalid params for {}: {error}", request.method),
+                        None,
+                    )
+                })?;
+                let result = handler(params, context).await?;
+                let result = serde_json::to_value(result).map_err(|error| {
+                    ErrorData::internal_error(
+                        format!("failed to serialize result of {}: {error}", request.method),
+                        None,
+                    )
+                })?;
+                Ok(CustomResult::new(result))
+            })
+        });
+        self.routes.insert(method.into(), route);
+        self
+    }
+
+    /// Handle [`M::METHOD`](CustomMethod::METHOD) with `handler`, as with
+    /// [`route`](Self::route).
+    pub fn route_method<M, F, Fut>(self, handler: F) -> Self
+    where
+        M: CustomMethod,
+        F: Fn(M::Params, RequestContext<R>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<M::Result, ErrorData>> + Send + 'static,
+    {
+        self.route(M::METHOD, handler)
+    }
+
+    pub fn has_route(&self, method: &str) -> bool {
+        self.routes.contains_key(method)
+    }
+
+    pub fn methods(&self) -> impl Iterator<Item = &str> {
+        self.routes.keys().map(String::as_str)
+    }
+
+    /// Run the handler registered for `request.method`, or fail with
+    /// [`ErrorCode::METHOD_NOT_FOUND`].
+    pub async fn handle(
+        &self,
+        request: CustomRequest,
+        context: RequestContext<R>,
+    ) -> Result<CustomResult, ErrorData> {
+        let Some(route) = self.routes.get(&request.method) else {
+            return Err(ErrorData::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                request.method,
+                None,
+            ));
+        };
+        route(request, context).await
+    }
+
+    /// One empty entry per registered method, to advertise them under
+    /// `capabilities.experimental`.
+    pub fn experimental_capabilities(&self) -> ExperimentalCapabilities {
+        self.methods()
+            .map(|method| (method.to_owned(), Default::default()))
+            .collect()
+    }
+}
+
+fn parse_params<P: DeserializeOwned>(params: Option<Value>) -> Result<P, serde_json::Error> {
+    match params {
+        Some(Value::Object(object)) if object.is_empty() => serde_json::from_value(Value::Null)
+            .or_else(|_| serde_json::from_value(Value::Object(object))),
+        params => serde_json::from_value(params.unwrap_or_default()),
+    }
+}
+
+impl<R: ServiceRole> Default for CustomMethodRouter<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: ServiceRole> Clone for CustomMethodRouter<R> {
+    fn clone(&self) -> Self {
+        Self {
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+impl<R: ServiceRole> std::fmt::Debug for CustomMethodRouter<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomMethodRouter")
+            .field("methods", &self.routes.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
@@ -4,13 +4,19 @@ use std::sync::Arc;
 use crate::{
     error::ErrorData as McpError,
     model::*,
-    service::{NotificationContext, RequestContext, RoleServer, Service, ServiceRole},
+    service::{
+        NotificationContext, RequestContext, RoleServer, Service, ServiceConfig, ServiceRole,
+    },
 };
 
+pub mod builder;
 pub mod common;
 pub mod prompt;
+pub mod provider;
 mod resource;
 pub mod router;
+pub mod sampling;
+pub mod schema_transform;
 pub mod tool;
 pub mod tool_name_validation;
 pub mod wrapper;
@@ -66,11 +72,22 @@ impl<H: ServerHandler> Service<RoleServer> for H {
                 .await
                 .map(ServerResult::empty),
             ClientRequest::CallToolRequest(request) => {
-                if request.params.task.is_some() {
+                // tools unknown to `get_tool` keep running as tasks whenever asked to
+                let task_support = self
+                    .get_tool(&request.params.name)
+                    .map(|tool| tool.task_support());
+                if request.params.task.is_some() && task_support != Some(TaskSupport::Forbidden) {
                     tracing::info!("Enqueueing task for tool call: {}", request.params.name);
                     self.enqueue_task(request.params, context.clone())
                         .await
                         .map(ServerResult::CreateTaskResult)
+                } else if request.params.task.is_none()
+                    && task_support == Some(TaskSupport::Required)
+                {
+                    Err(McpError::invalid_request(
+                        format!("tool {} must be called as a task", request.params.name),
+                        None,
+                    ))
                 } else {
                     self.call_tool(request.params, context)
                         .await
@@ -136,6 +153,10 @@ impl<H: ServerHandler> Service<RoleServer> for H {
     fn get_info(&self) -> <RoleServer as ServiceRole>::Info {
         self.get_info()
     }
+
+    fn config(&self) -> ServiceConfig {
+        self.config()
+    }
 }
 
 #[allow(unused_variables)]
@@ -165,7 +186,11 @@ pub trait ServerHandler: Sized + Send + Sync + 'static {
         if context.peer.peer_info().is_none() {
             context.peer.set_peer_info(request);
         }
-        std::future::ready(Ok(self.get_info()))
+        let instructions = self.instructions(&context);
+        std::future::ready(Ok(InitializeResult {
+            instructions,
+            ..self.get_info()
+        }))
     }
     fn complete(
         &self,
@@ -246,6 +271,11 @@ pub trait ServerHandler: Sized + Send + Sync + 'static {
     ) -> impl Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
         std::future::ready(Ok(ListToolsResult::default()))
     }
+    /// The tool named `name`, telling whether a call to it may run as a task.
+    /// `#[tool_handler]` looks it up in the tool router.
+    fn get_tool(&self, name: &str) -> Option<Tool> {
+        None
+    }
     fn on_custom_request(
         &self,
         request: CustomRequest,
@@ -320,6 +350,21 @@ pub trait ServerHandler: Sized + Send + Sync + 'static {
         ServerInfo::default()
     }
 
+    /// Instructions of the session, sent by [`initialize`](Self::initialize)
+    /// instead of those of [`get_info`](Self::get_info). Override it to
+    /// tailor them to each session, e.g. with the name of the authenticated
+    /// user from `context.extensions` or of the client from
+    /// `context.peer.peer_info()`.
+    fn instructions(&self, context: &RequestContext<RoleServer>) -> Option<String> {
+        self.get_info().instructions
+    }
+
+    /// How sessions of this server run, e.g. their timeouts or whether a
+    /// panicking handler ends them, see [`ServiceConfig`].
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig::default()
+    }
+
     fn list_tasks(
         &self,
         request: Option<PaginatedRequestParams>,
@@ -470,6 +515,10 @@ macro_rules! impl_server_handler_for_wrapper {
                 (**self).list_tools(request, context)
             }
 
+            fn get_tool(&self, name: &str) -> Option<Tool> {
+                (**self).get_tool(name)
+            }
+
             fn on_custom_request(
                 &self,
                 request: CustomRequest,
@@ -528,6 +577,14 @@ macro_rules! impl_server_handler_for_wrapper {
                 (**self).get_info()
             }
 
+            fn instructions(&self, context: &RequestContext<RoleServer>) -> Option<String> {
+                (**self).instructions(context)
+            }
+
+            fn config(&self) -> ServiceConfig {
+                (**self).config()
+            }
+
             fn list_tasks(
                 &self,
                 request: Option<PaginatedRequestParams>,
@@ -0,0 +1,148 @@
+use std::borrow::Cow;
+
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+
+use super::{
+    ServerHandler,
+    common::schema_for_type,
+    router::{
+        Router,
+        tool::{IntoToolRoute, ToolRoute},
+    },
+    tool::{AsyncAdapter, IntoCallToolResult},
+    wrapper::Parameters,
+};
+use crate::{
+    RoleServer,
+    model::{Implementation, ServerInfo, Tool},
+    service::{RunningService, ServerInitializeError, ServiceExt},
+    transport::IntoTransport,
+};
+
+/// A server made of closures, see [`ServerBuilder`].
+pub type Server = Router<ServerInfo>;
+
+/// Serves its own info and nothing else; the base of a [`Server`].
+impl ServerHandler for ServerInfo {
+    fn get_info(&self) -> ServerInfo {
+        self.clone()
+    }
+}
+
+impl Server {
+    pub fn builder() -> ServerBuilder {
+        ServerBuilder::default()
+    }
+}
+
+/// Builds a [`Server`] from closures, for small scripts and tests that don't
+/// need a handler struct and the tool macros.
+///
+/// The input schema of a tool added with [`tool`](Self::tool) is generated
+/// from the closure's parameter type, and the doc comment of that type
+/// becomes the tool description.
+///
+/// ```rust,no_run
+/// # use rmcp::{handler::server::{builder::Server, wrapper::Parameters}, schemars};
+/// /// Add two numbers.
+/// #[derive(serde::Deserialize, schemars::JsonSchema)]
+/// struct AddArgs {
+///     a: i64,
+///     b: i64,
+/// }
+///
+/// # async fn run(transport: tokio::io::DuplexStream) -> anyhow::Result<()> {
+/// let server = Server::builder()
+///     .name("calculator", "1.0.0")
+///     .tool("add", |Parameters(args): Parameters<AddArgs>| async move {
+///         (args.a + args.b).to_string()
+///     })
+///     .serve(transport)
+///     .await?;
+/// server.waiting().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ServerBuilder {
+    info: ServerInfo,
+    tools: Vec<ToolRoute<ServerInfo>>,
+}
+
+impl ServerBuilder {
+    pub fn name(mut self, name: impl Into<String>, version: impl Into<String>) -> Self {
+        self.info.server_info = Implementation {
+            name: name.into(),
+            version: version.into(),
+            ..Default::default()
+        };
+        self
+    }
+
+    pub fn instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.info.instructions = Some(instructions.into());
+        self
+    }
+
+    /// Start from `info` rather than the default server info; the tools
+    /// capability is still enabled on build.
+    pub fn info(mut self, info: ServerInfo) -> Self {
+        self.info = info;
+        self
+    }
+
+    /// Add a tool taking its arguments as `P`.
+    pub fn tool<P, F, Fut, R>(mut self, name: impl Into<Cow<'static, str>>, call: F) -> Self
+    where
+        P: DeserializeOwned + JsonSchema + 'static,
+        F: Fn(Parameters<P>) -> Fut + Send + Sync + Clone + 'static,
+        Fut: Future<Output = R> + Send + 'static,
+        R: IntoCallToolResult + Send + 'static,
+    {
+        let input_schema = schema_for_type::<P>();
+        let description = input_schema
+            .get("description")
+            .and_then(|description| description.as_str())
+            .map(|description| Cow::Owned(description.to_owned()));
+        let attr = Tool {
+            description,
+            ..Tool::new(name, "", input_schema)
+        };
+        self.tools
+            .push(ToolRoute::new::<F, AsyncAdapter<(Parameters<P>,), Fut, R>>(
+                attr, call,
+            ));
+        self
+    }
+
+    /// Add a tool with full control over its attributes, e.g. a handler
+    /// named with [`CallToolHandlerExt`](super::router::tool::CallToolHandlerExt).
+    pub fn route<R, A>(mut self, route: R) -> Self
+    where
+        R: IntoToolRoute<ServerInfo, A>,
+    {
+        self.tools.push(route.into_tool_route());
+        self
+    }
+
+    pub fn build(mut self) -> Server {
+        self.info
+            .capabilities
+            .tools
+            .get_or_insert_with(Default::default);
+        Router::new(self.info).with_tools(self.tools)
+    }
+
+    /// Build the server and serve it on `transport`.
+    pub async fn serve<T, E, A>(
+        self,
+        transport: T,
+    ) -> Result<RunningService<RoleServer, Server>, ServerInitializeError>
+    where
+        T: IntoTransport<RoleServer, E, A>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.build().serve(transport).await
+    }
+}
@@ -2,11 +2,9 @@
 
 use std::{any::TypeId, collections::HashMap, sync::Arc};
 
-use schemars::JsonSchema;
+use schemars::{JsonSchema, generate::SchemaSettings};
 
-use crate::{
-    RoleServer, model::JsonObject, schemars::generate::SchemaSettings, service::RequestContext,
-};
+use crate::{RoleServer, model::JsonObject, service::RequestContext};
 
 /// Generates a JSON schema for a type
 pub fn schema_for_type<T: JsonSchema + std::any::Any>() -> Arc<JsonObject> {
@@ -89,6 +87,174 @@ pub fn schema_for_output<T: JsonSchema + std::any::Any>() -> Result<Arc<JsonObje
     })
 }
 
+/// A handwritten input schema that the parameter type can't deserialize
+/// from, see [`check_input_schema`].
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("parameters do not deserialize from {instance} allowed by the input schema: {error}")]
+pub struct InputSchemaMismatch {
+    /// The sample instance of the schema that was rejected.
+    pub instance: serde_json::Value,
+    pub error: String,
+}
+
+/// Check that `T` deserializes from instances of a handwritten input schema.
+///
+/// Two instances are derived from the schema, one with only the required
+/// properties and one with all of them, filled from `const`, `default`,
+/// `examples` or `enum` where present and with placeholder values of the
+/// declared type otherwise. `#[tool(input_schema = ...)]` runs this as a debug
+/// assertion; call it from a test to check schemas of tools built by hand.
+pub fn check_input_schema<T: serde::de::DeserializeOwned>(
+    schema: &JsonObject,
+) -> Result<(), InputSchemaMismatch> {
+    for all_properties in [false, true] {
+        let sampler = SchemaSampler {
+            root: schema,
+            all_properties,
+        };
+        let instance = sampler.sample(schema, 0);
+        if let Err(error) = serde_json::from_value::<T>(instance.clone()) {
+            return Err(InputSchemaMismatch {
+                instance,
+                error: error.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[doc(hidden)]
+pub fn debug_check_input_schema<T: serde::de::DeserializeOwned>(
+    tool: &str,
+    schema: Arc<JsonObject>,
+) -> Arc<JsonObject> {
+    if cfg!(debug_assertions) {
+        if let Err(error) = check_input_schema::<T>(&schema) {
+            panic!("invalid input schema for tool `{tool}`: {error}");
+        }
+    }
+    schema
+}
+
+struct SchemaSampler<'a> {
+    root: &'a JsonObject,
+    all_properties: bool,
+}
+
+impl SchemaSampler<'_> {
+    const MAX_DEPTH: usize = 16;
+
+    fn sample(&self, schema: &JsonObject, depth: usize) -> serde_json::Value {
+        use serde_json::{Value, json};
+        if depth > Self::MAX_DEPTH {
+            return Value::Null;
+        }
+        if let Some(value) = schema
+            .get("const")
+            .or_else(|| schema.get("default"))
+            .or_else(|| schema.get("examples").and_then(|v| v.get(0)))
+            .or_else(|| schema.get("enum").and_then(|v| v.get(0)))
+        {
+            return value.clone();
+        }
+        if let Some(target) = schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|reference| reference.strip_prefix('#'))
+            .and_then(|pointer| Value::Object(self.root.clone()).pointer(pointer).cloned())
+        {
+            return match target {
+                Value::Object(target) => self.sample(&target, depth + 1),
+                _ => Value::Null,
+            };
+        }
+        for combinator in ["allOf", "anyOf", "oneOf"] {
+            if let Some(variant) =
+                schema
+                    .get(combinator)
+                    .and_then(Value::as_array)
+                    .and_then(|variants| {
+                        variants
+                            .iter()
+                            .filter_map(Value::as_object)
+                            .find(|variant| variant.get("type") != Some(&json!("null")))
+                    })
+            {
+                return self.sample(variant, depth + 1);
+            }
+        }
+        let ty = match schema.get("type") {
+            Some(Value::String(ty)) => ty.as_str(),
+            Some(Value::Array(types)) => types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|ty| *ty != "null")
+                .unwrap_or("null"),
+            _ if schema.contains_key("properties") => "object",
+            _ => "null",
+        };
+        match ty {
+            "object" => {
+                let required: Vec<&str> = schema
+                    .get("required")
+                    .and_then(Value::as_array)
+                    .map(|required| required.iter().filter_map(Value::as_str).collect())
+                    .unwrap_or_default();
+                let mut object = JsonObject::new();
+                for (name, property) in schema
+                    .get("properties")
+                    .and_then(Value::as_object)
+                    .into_iter()
+                    .flatten()
+                {
+                    if self.all_properties || required.contains(&name.as_str()) {
+                        let value = match property {
+                            Value::Object(property) => self.sample(property, depth + 1),
+                            _ => Value::Null,
+                        };
+                        object.insert(name.clone(), value);
+                    }
+                }
+                Value::Object(object)
+            }
+            "array" => {
+                let min_items = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+                let len = if self.all_properties {
+                    min_items.max(1)
+                } else {
+                    min_items
+                };
+                let item = match schema.get("items") {
+                    Some(Value::Object(items)) => self.sample(items, depth + 1),
+                    _ => Value::Null,
+                };
+                Value::Array(vec![item; len as usize])
+            }
+            "string" => {
+                let sample = match schema.get("format").and_then(Value::as_str) {
+                    Some("date-time") => "1970-01-01T00:00:00Z",
+                    Some("date") => "1970-01-01",
+                    Some("time") => "00:00:00",
+                    Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                    Some("email") => "user@example.com",
+                    Some("uri" | "url") => "https://example.com/",
+                    _ => "",
+                };
+                let min_length = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0);
+                let mut sample = sample.to_owned();
+                while (sample.chars().count() as u64) < min_length {
+                    sample.push('a');
+                }
+                Value::String(sample)
+            }
+            "integer" => json!(schema.get("minimum").and_then(Value::as_i64).unwrap_or(0)),
+            "number" => json!(schema.get("minimum").and_then(Value::as_f64).unwrap_or(0.0)),
+            "boolean" => Value::Bool(false),
+            _ => Value::Null,
+        }
+    }
+}
+
 /// Trait for extracting parts from a context, unifying tool and prompt extraction
 pub trait FromContextPart<C>: Sized {
     fn from_context_part(context: &mut C) -> Result<Self, crate::ErrorData>;
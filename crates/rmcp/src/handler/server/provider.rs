@@ -0,0 +1,607 @@
+//! Prompts and resources that can change while sessions are open.
+//!
+//! A [`PromptProvider`] or [`ResourceProvider`] is meant to be shared by every
+//! session of a server: clone it into each handler and answer the list, get
+//! and read requests from it. Adding or removing an entry sends a
+//! `list_changed` notification to every open session that listed the
+//! provider, so advertise it with [`PromptProvider::capability`] or
+//! [`ResourceProvider::capability`].
+//!
+//! Resource templates can suggest values for their variables through
+//! [`ResourceTemplateProvider::complete`], served by
+//! [`ResourceProvider::complete`].
+//!
+//! ```rust
+//! # use rmcp::{
+//! #     ErrorData, RoleServer, ServerHandler,
+//! #     handler::server::provider::PromptProvider,
+//! #     model::*,
+//! #     service::RequestContext,
+//! # };
+//! #[derive(Clone)]
+//! struct Server {
+//!     prompts: PromptProvider,
+//! }
+//!
+//! impl ServerHandler for Server {
+//!     async fn list_prompts(
+//!         &self,
+//!         _request: Option<PaginatedRequestParams>,
+//!         context: RequestContext<RoleServer>,
+//!     ) -> Result<ListPromptsResult, ErrorData> {
+//!         Ok(self.prompts.list(&context))
+//!     }
+//!
+//!     async fn get_prompt(
+//!         &self,
+//!         request: GetPromptRequestParams,
+//!         _context: RequestContext<RoleServer>,
+//!     ) -> Result<GetPromptResult, ErrorData> {
+//!         self.prompts.get(request).await
+//!     }
+//!
+//!     fn get_info(&self) -> ServerInfo {
+//!         ServerInfo {
+//!             capabilities: ServerCapabilities {
+//!                 prompts: Some(self.prompts.capability()),
+//!                 ..Default::default()
+//!             },
+//!             ..Default::default()
+//!         }
+//!     }
+//! }
+//!
+//! # fn demo(server: &Server) {
+//! // every session that listed the prompts hears about this one
+//! server.prompts.insert(Prompt::new("greet", None::<String>, None), |_arguments| async {
+//!     Ok(GetPromptResult {
+//!         description: None,
+//!         messages: vec![PromptMessage::new_text(PromptMessageRole::User, "hello")],
+//!     })
+//! });
+//! # }
+//! ```
+#[cfg(feature = "mime-sniffing")]
+use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+#[cfg(feature = "mime-sniffing")]
+use chrono::{DateTime, Utc};
+
+use futures::future::{BoxFuture, FutureExt};
+
+#[cfg(feature = "mime-sniffing")]
+use crate::model::{
+    AnnotateAble, Annotations, RawResource, ResourceContents, sniff::mime_from_extension,
+};
+use crate::{
+    ErrorData, RoleServer,
+    model::{
+        ArgumentInfo, CompleteRequestParams, CompleteResult, CompletionContext, CompletionInfo,
+        GetPromptRequestParams, GetPromptResult, JsonObject, ListPromptsResult,
+        ListResourceTemplatesResult, ListResourcesResult, Prompt, PromptsCapability,
+        ReadResourceRequestParams, ReadResourceResult, Reference, Resource, ResourceTemplate,
+        ResourcesCapability,
+    },
+    service::{NotificationCoalescer, Peer, RequestContext, WeakPeer},
+};
+
+/// `list_changed` notifications closer together than this are merged, so
+/// that loading many entries in a row doesn't flood the sessions.
+const LIST_CHANGED_WINDOW: Duration = Duration::from_millis(100);
+
+/// The sessions to tell when a provider's list changes.
+#[derive(Default)]
+struct Subscribers {
+    sessions: Mutex<Vec<(WeakPeer<RoleServer>, NotificationCoalescer)>>,
+}
+
+impl Subscribers {
+    fn subscribe(&self, peer: &Peer<RoleServer>) {
+        let mut sessions = self.sessions.lock().expect("subscribers poisoned");
+        sessions.retain(|(session, _)| !session.is_closed());
+        let known = sessions.iter().any(|(session, _)| {
+            session
+                .upgrade()
+                .is_some_and(|session| session.is_same_session(peer))
+        });
+        if !known {
+            let coalescer = NotificationCoalescer::new(peer.clone(), LIST_CHANGED_WINDOW);
+            sessions.push((peer.downgrade(), coalescer));
+        }
+    }
+
+    fn each(&self, notify: impl Fn(&NotificationCoalescer)) {
+        let mut sessions = self.sessions.lock().expect("subscribers poisoned");
+        sessions.retain(|(session, _)| !session.is_closed());
+        for (_, coalescer) in sessions.iter() {
+            notify(coalescer);
+        }
+    }
+}
+
+type DynPromptGetter = Arc<
+    dyn Fn(Option<JsonObject>) -> BoxFuture<'static, Result<GetPromptResult, ErrorData>>
+        + Send
+        + Sync,
+>;
+
+type DynResourceReader =
+    Arc<dyn Fn() -> BoxFuture<'static, Result<ReadResourceResult, ErrorData>> + Send + Sync>;
+
+struct ProvidedPrompt {
+    prompt: Prompt,
+    get: DynPromptGetter,
+}
+
+#[derive(Default)]
+struct PromptProviderInner {
+    prompts: RwLock<BTreeMap<String, ProvidedPrompt>>,
+    subscribers: Subscribers,
+}
+
+/// Prompts shared by the sessions of a server, see the
+/// [module docs](self).
+///
+/// Cloning is cheap and all clones share the same prompts.
+#[derive(Clone, Default)]
+pub struct PromptProvider {
+    inner: Arc<PromptProviderInner>,
+}
+
+impl PromptProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What to advertise under `capabilities.prompts`.
+    pub fn capability(&self) -> PromptsCapability {
+        PromptsCapability {
+            list_changed: Some(true),
+        }
+    }
+
+    /// Add `prompt`, or replace the one with the same name. `get` renders
+    /// it from the arguments of a `prompts/get` request.
+    pub fn insert<F, Fut>(&self, prompt: Prompt, get: F)
+    where
+        F: Fn(Option<JsonObject>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<GetPromptResult, ErrorData>> + Send + 'static,
+    {
+        let get: DynPromptGetter = Arc::new(move |arguments| Box::pin(get(arguments)));
+        self.inner
+            .prompts
+            .write()
+            .expect("prompt provider poisoned")
+            .insert(prompt.name.clone(), ProvidedPrompt { prompt, get });
+        self.list_changed();
+    }
+
+    /// Remove the prompt called `name`. Returns `false` if there was none.
+    pub fn remove(&self, name: &str) -> bool {
+        let removed = self
+            .inner
+            .prompts
+            .write()
+            .expect("prompt provider poisoned")
+            .remove(name)
+            .is_some();
+        if removed {
+            self.list_changed();
+        }
+        removed
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.inner
+            .prompts
+            .read()
+            .expect("prompt provider poisoned")
+            .contains_key(name)
+    }
+
+    /// All prompts, for `prompts/list`. The session asking is told about
+    /// later changes.
+    pub fn list(&self, context: &RequestContext<RoleServer>) -> ListPromptsResult {
+        self.inner.subscribers.subscribe(&context.peer);
+        let prompts = self
+            .inner
+            .prompts
+            .read()
+            .expect("prompt provider poisoned")
+            .values()
+            .map(|provided| provided.prompt.clone())
+            .collect();
+        ListPromptsResult::with_all_items(prompts)
+    }
+
+    /// Render the prompt named in `request`.
+    pub async fn get(&self, request: GetPromptRequestParams) -> Result<GetPromptResult, ErrorData> {
+        let get = self
+            .inner
+            .prompts
+            .read()
+            .expect("prompt provider poisoned")
+            .get(&request.name)
+            .map(|provided| provided.get.clone())
+            .ok_or_else(|| {
+                ErrorData::invalid_params(format!("prompt '{}' not found", request.name), None)
+            })?;
+        get(request.arguments).await
+    }
+
+    fn list_changed(&self) {
+        self.inner
+            .subscribers
+            .each(NotificationCoalescer::prompt_list_changed);
+    }
+}
+
+impl std::fmt::Debug for PromptProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let prompts = self.inner.prompts.read().expect("prompt provider poisoned");
+        f.debug_struct("PromptProvider")
+            .field("prompts", &prompts.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// A resource template served by a [`ResourceProvider`].
+///
+/// A bare [`ResourceTemplate`] is one without completions. Implement this to
+/// suggest values for the template's variables, e.g. branch names for the
+/// `ref` of `repo://{owner}/{repo}/refs/{ref}`:
+///
+/// ```rust
+/// # use futures::future::{BoxFuture, FutureExt};
+/// # use rmcp::{
+/// #     ErrorData,
+/// #     handler::server::provider::ResourceTemplateProvider,
+/// #     model::{AnnotateAble, ArgumentInfo, CompletionContext, RawResourceTemplate, ResourceTemplate},
+/// # };
+/// struct Refs;
+///
+/// impl ResourceTemplateProvider for Refs {
+///     fn template(&self) -> ResourceTemplate {
+///         RawResourceTemplate {
+///             uri_template: "repo://{owner}/{repo}/refs/{ref}".into(),
+///             name: "refs".into(),
+///             title: None,
+///             description: None,
+///             mime_type: None,
+///             icons: None,
+///         }
+///         .no_annotation()
+///     }
+///
+///     fn complete(
+///         &self,
+///         argument: ArgumentInfo,
+///         _context: Option<CompletionContext>,
+///     ) -> BoxFuture<'_, Result<Vec<String>, ErrorData>> {
+///         let branches = ["main", "release", "review"];
+///         let values = match argument.name.as_str() {
+///             "ref" => branches
+///                 .into_iter()
+///                 .filter(|branch| branch.starts_with(&argument.value))
+///                 .map(String::from)
+///                 .collect(),
+///             _ => Vec::new(),
+///         };
+///         std::future::ready(Ok(values)).boxed()
+///     }
+/// }
+/// ```
+pub trait ResourceTemplateProvider: Send + Sync + 'static {
+    fn template(&self) -> ResourceTemplate;
+
+    /// Values for the template variable `argument.name` that match what was
+    /// typed so far, `argument.value`. `context` holds the variables that
+    /// are already filled in. None by default.
+    fn complete(
+        &self,
+        argument: ArgumentInfo,
+        context: Option<CompletionContext>,
+    ) -> BoxFuture<'_, Result<Vec<String>, ErrorData>> {
+        let _ = (argument, context);
+        std::future::ready(Ok(Vec::new())).boxed()
+    }
+}
+
+impl ResourceTemplateProvider for ResourceTemplate {
+    fn template(&self) -> ResourceTemplate {
+        self.clone()
+    }
+}
+
+struct ProvidedResource {
+    resource: Resource,
+    read: DynResourceReader,
+}
+
+#[derive(Default)]
+struct ResourceProviderInner {
+    resources: RwLock<BTreeMap<String, ProvidedResource>>,
+    templates: RwLock<BTreeMap<String, Arc<dyn ResourceTemplateProvider>>>,
+    subscribers: Subscribers,
+}
+
+/// Resources and resource templates shared by the sessions of a server, see
+/// the [module docs](self).
+///
+/// Cloning is cheap and all clones share the same resources.
+#[derive(Clone, Default)]
+pub struct ResourceProvider {
+    inner: Arc<ResourceProviderInner>,
+}
+
+impl ResourceProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What to advertise under `capabilities.resources`.
+    pub fn capability(&self) -> ResourcesCapability {
+        ResourcesCapability {
+            list_changed: Some(true),
+            ..Default::default()
+        }
+    }
+
+    /// Add `resource`, or replace the one with the same URI. `read` produces
+    /// its contents for `resources/read`.
+    pub fn insert<F, Fut>(&self, resource: Resource, read: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<ReadResourceResult, ErrorData>> + Send + 'static,
+    {
+        let read: DynResourceReader = Arc::new(move || Box::pin(read()));
+        self.inner
+            .resources
+            .write()
+            .expect("resource provider poisoned")
+            .insert(resource.uri.clone(), ProvidedResource { resource, read });
+        self.list_changed();
+    }
+
+    /// Serve the file at `path` under its `file://` URI, and return the
+    /// resource that was listed.
+    ///
+    /// The listing carries the size and modification time the file had when
+    /// it was inserted. Every read looks at the file again, so its contents
+    /// report the current [`size`](crate::model::ResourceContents::size) and
+    /// [`last_modified`](crate::model::ResourceContents::last_modified).
+    #[cfg(feature = "mime-sniffing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mime-sniffing")))]
+    pub fn insert_file(&self, path: impl AsRef<Path>) -> std::io::Result<Resource> {
+        let path = std::path::absolute(path)?;
+        let uri = crate::uri::file_uri(&path)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidInput, error))?;
+        let (size, last_modified) = file_metadata(&path)?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| uri.clone());
+        let mut raw = RawResource::new(uri.clone(), name);
+        raw.size = size;
+        raw.mime_type = mime_from_extension(&uri).map(str::to_owned);
+        let resource = raw.optional_annotate(
+            last_modified
+                .map(|last_modified| Annotations::default().with_last_modified(last_modified)),
+        );
+        self.insert(resource.clone(), move || {
+            read_file(uri.clone(), path.clone())
+        });
+        Ok(resource)
+    }
+
+    /// Remove the resource at `uri`. Returns `false` if there was none.
+    pub fn remove(&self, uri: &str) -> bool {
+        let removed = self
+            .inner
+            .resources
+            .write()
+            .expect("resource provider poisoned")
+            .remove(uri)
+            .is_some();
+        if removed {
+            self.list_changed();
+        }
+        removed
+    }
+
+    pub fn contains(&self, uri: &str) -> bool {
+        self.inner
+            .resources
+            .read()
+            .expect("resource provider poisoned")
+            .contains_key(uri)
+    }
+
+    /// Add `template`, or replace the one with the same URI template.
+    pub fn insert_template(&self, template: impl ResourceTemplateProvider) {
+        let uri_template = template.template().uri_template.clone();
+        self.inner
+            .templates
+            .write()
+            .expect("resource provider poisoned")
+            .insert(uri_template, Arc::new(template));
+        self.list_changed();
+    }
+
+    /// Remove the template for `uri_template`. Returns `false` if there was
+    /// none.
+    pub fn remove_template(&self, uri_template: &str) -> bool {
+        let removed = self
+            .inner
+            .templates
+            .write()
+            .expect("resource provider poisoned")
+            .remove(uri_template)
+            .is_some();
+        if removed {
+            self.list_changed();
+        }
+        removed
+    }
+
+    /// All resources, for `resources/list`. The session asking is told about
+    /// later changes.
+    pub fn list(&self, context: &RequestContext<RoleServer>) -> ListResourcesResult {
+        self.inner.subscribers.subscribe(&context.peer);
+        let resources = self
+            .inner
+            .resources
+            .read()
+            .expect("resource provider poisoned")
+            .values()
+            .map(|provided| provided.resource.clone())
+            .collect();
+        ListResourcesResult::with_all_items(resources)
+    }
+
+    /// All templates, for `resources/templates/list`. The session asking is
+    /// told about later changes.
+    pub fn list_templates(
+        &self,
+        context: &RequestContext<RoleServer>,
+    ) -> ListResourceTemplatesResult {
+        self.inner.subscribers.subscribe(&context.peer);
+        let templates = self
+            .inner
+            .templates
+            .read()
+            .expect("resource provider poisoned")
+            .values()
+            .map(|template| template.template())
+            .collect();
+        ListResourceTemplatesResult::with_all_items(templates)
+    }
+
+    /// Answer `completion/complete` for a `ref/resource` naming one of the
+    /// templates, with what its [`complete`](ResourceTemplateProvider::complete)
+    /// suggests. Anything else completes to nothing.
+    ///
+    /// Only the first [`CompletionInfo::MAX_VALUES`] suggestions are sent,
+    /// with `hasMore` set if there were more.
+    pub async fn complete(
+        &self,
+        request: CompleteRequestParams,
+    ) -> Result<CompleteResult, ErrorData> {
+        let template = match &request.r#ref {
+            Reference::Resource(reference) => self
+                .inner
+                .templates
+                .read()
+                .expect("resource provider poisoned")
+                .get(&reference.uri)
+                .cloned(),
+            Reference::Prompt(_) => None,
+        };
+        let mut values = match template {
+            Some(template) => template.complete(request.argument, request.context).await?,
+            None => Vec::new(),
+        };
+        let total = values.len();
+        values.truncate(CompletionInfo::MAX_VALUES);
+        let completion = CompletionInfo::with_pagination(
+            values,
+            u32::try_from(total).ok(),
+            total > CompletionInfo::MAX_VALUES,
+        )
+        .map_err(|error| ErrorData::internal_error(error, None))?;
+        Ok(CompleteResult { completion })
+    }
+
+    /// Read the resource at `request.uri`.
+    ///
+    /// Reads are [conditional](ReadResourceResult::conditional): a client
+    /// passing the entity tag it already has gets a "not modified" answer
+    /// while the contents stay the same. Advertise
+    /// [`ConditionalReads`](crate::model::ConditionalReads) to let clients
+    /// know.
+    pub async fn read(
+        &self,
+        request: ReadResourceRequestParams,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let read = self
+            .inner
+            .resources
+            .read()
+            .expect("resource provider poisoned")
+            .get(&request.uri)
+            .map(|provided| provided.read.clone())
+            .ok_or_else(|| {
+                ErrorData::resource_not_found(format!("resource '{}' not found", request.uri), None)
+            })?;
+        Ok(read()
+            .await?
+            .conditional(request.uri, context.meta.if_none_match()))
+    }
+
+    fn list_changed(&self) {
+        self.inner
+            .subscribers
+            .each(NotificationCoalescer::resource_list_changed);
+    }
+}
+
+#[cfg(feature = "mime-sniffing")]
+fn file_metadata(path: &Path) -> std::io::Result<(Option<u32>, Option<DateTime<Utc>>)> {
+    let metadata = std::fs::metadata(path)?;
+    if !metadata.is_file() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} is not a file", path.display()),
+        ));
+    }
+    Ok((
+        u32::try_from(metadata.len()).ok(),
+        metadata.modified().ok().map(DateTime::from),
+    ))
+}
+
+#[cfg(feature = "mime-sniffing")]
+async fn read_file(uri: String, path: PathBuf) -> Result<ReadResourceResult, ErrorData> {
+    let read = tokio::task::spawn_blocking(move || {
+        let (size, last_modified) = file_metadata(&path)?;
+        let bytes = std::fs::read(&path)?;
+        std::io::Result::Ok(
+            ResourceContents::from_bytes(uri, &bytes).with_metadata(size, last_modified),
+        )
+    });
+    match read.await {
+        Ok(Ok(contents)) => Ok(ReadResourceResult {
+            contents: vec![contents],
+        }),
+        Ok(Err(error)) if error.kind() == std::io::ErrorKind::NotFound => {
+            Err(ErrorData::resource_not_found(error.to_string(), None))
+        }
+        Ok(Err(error)) => Err(ErrorData::internal_error(error.to_string(), None)),
+        Err(error) => Err(ErrorData::internal_error(error.to_string(), None)),
+    }
+}
+
+impl std::fmt::Debug for ResourceProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let resources = self
+            .inner
+            .resources
+            .read()
+            .expect("resource provider poisoned");
+        let templates = self
+            .inner
+            .templates
+            .read()
+            .expect("resource provider poisoned");
+        f.debug_struct("ResourceProvider")
+            .field("resources", &resources.keys().collect::<Vec<_>>())
+            .field("templates", &templates.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
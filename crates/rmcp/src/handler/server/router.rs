@@ -135,4 +135,8 @@ where
     fn get_info(&self) -> <RoleServer as crate::service::ServiceRole>::Info {
         ServerHandler::get_info(&self.service)
     }
+
+    fn config(&self) -> crate::service::ServiceConfig {
+        ServerHandler::config(&self.service)
+    }
 }
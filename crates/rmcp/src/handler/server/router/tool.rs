@@ -5,10 +5,11 @@ use schemars::JsonSchema;
 
 use crate::{
     handler::server::{
+        schema_transform::transform_schema,
         tool::{CallToolHandler, DynCallToolHandler, ToolCallContext, schema_for_type},
         tool_name_validation::validate_and_warn_tool_name,
     },
-    model::{CallToolResult, Tool, ToolAnnotations},
+    model::{CallToolResult, JsonObject, Tool, ToolAnnotations},
 };
 
 pub struct ToolRoute<S> {
@@ -251,9 +252,28 @@ where
         Ok(result)
     }
 
+    pub fn get(&self, name: &str) -> Option<&crate::model::Tool> {
+        self.map.get(name).map(|item| &item.attr)
+    }
+
     pub fn list_all(&self) -> Vec<crate::model::Tool> {
         self.map.values().map(|item| item.attr.clone()).collect()
     }
+
+    /// Apply a [schema transform](crate::handler::server::schema_transform)
+    /// to the input and output schemas of every tool.
+    pub fn transform_schemas(mut self, transform: impl Fn(&mut JsonObject)) -> Self {
+        for route in self.map.values_mut() {
+            let attr = &mut route.attr;
+            attr.input_schema =
+                transform_schema(std::mem::take(&mut attr.input_schema), &transform);
+            attr.output_schema = attr
+                .output_schema
+                .take()
+                .map(|schema| transform_schema(schema, &transform));
+        }
+        self
+    }
 }
 
 impl<S> std::ops::Add<ToolRouter<S>> for ToolRouter<S>
@@ -0,0 +1,164 @@
+//! Server side of sampling extensions.
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures::{FutureExt, Stream, future::BoxFuture};
+use tokio::sync::{RwLock, mpsc};
+
+use crate::{
+    RoleServer,
+    model::{
+        ClientResult, CreateMessageRequest, CreateMessageRequestParams, CreateMessageResult,
+        ExperimentalCapability, Meta, ProgressNotificationParam, ProgressToken, SamplingChunk,
+        SamplingStreaming, ServerRequest,
+    },
+    service::{NotificationContext, Peer, PeerRequestOptions, ServiceError},
+};
+
+type Streams = Arc<RwLock<HashMap<ProgressToken, mpsc::Sender<SamplingChunk>>>>;
+
+/// Streamed `sampling/createMessage` requests of a server, see
+/// [`SamplingStreaming`].
+///
+/// Call [`on_progress`](Self::on_progress) from
+/// [`ServerHandler::on_progress`](crate::ServerHandler::on_progress) so the
+/// chunks reach their [`SamplingStream`]. Cloning is cheap and all clones
+/// share the same streams.
+#[derive(Debug, Clone, Default)]
+pub struct SamplingStreams {
+    streams: Streams,
+}
+
+impl SamplingStreams {
+    const CHANNEL_SIZE: usize = 16;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send `sampling/createMessage`, asking for chunks if the client
+    /// advertises [`SamplingStreaming`]. Clients that don't just answer, and
+    /// the stream ends without chunks.
+    pub async fn create_message(
+        &self,
+        peer: &Peer<RoleServer>,
+        params: CreateMessageRequestParams,
+    ) -> Result<SamplingStream, ServiceError> {
+        let mut options = PeerRequestOptions::no_options();
+        let streaming = peer.peer_supports_experimental(SamplingStreaming::KEY);
+        if streaming {
+            let mut meta = Meta::new();
+            meta.set_stream_requested();
+            options.meta = Some(meta);
+        }
+        let request = ServerRequest::CreateMessageRequest(CreateMessageRequest::new(params));
+        let (sender, receiver) = mpsc::channel(Self::CHANNEL_SIZE);
+        // register before the client can answer, chunks for unknown tokens are
+        // dropped
+        let mut streams = self.streams.write().await;
+        let handle = peer.send_request_with_option(request, options).await?;
+        let progress_token = handle.progress_token.clone();
+        if streaming {
+            streams.insert(progress_token.clone(), sender);
+        }
+        drop(streams);
+        let response = async move {
+            match handle.await_response().await? {
+                ClientResult::CreateMessageResult(result) => Ok(*result),
+                _ => Err(ServiceError::UnexpectedResponse),
+            }
+        }
+        .boxed();
+        Ok(SamplingStream {
+            progress_token,
+            chunks: receiver,
+            response,
+            result: None,
+            streams: self.streams.clone(),
+        })
+    }
+
+    /// Hand the chunk a progress notification carries to its stream. Returns
+    /// `false` if the notification isn't a chunk of one of the streams.
+    pub async fn on_progress(
+        &self,
+        params: &ProgressNotificationParam,
+        context: &NotificationContext<RoleServer>,
+    ) -> bool {
+        let Some(chunk) = context.meta.sampling_chunk() else {
+            return false;
+        };
+        let Some(sender) = self
+            .streams
+            .read()
+            .await
+            .get(&params.progress_token)
+            .cloned()
+        else {
+            return false;
+        };
+        if let Err(e) = sender.send(chunk).await {
+            tracing::debug!("Dropped sampling chunk of a closed stream: {e}");
+        }
+        true
+    }
+}
+
+/// The chunks of a streamed `sampling/createMessage` answer, then its
+/// [`result`](Self::result).
+///
+/// The stream yields the chunks that arrive before the final result and ends
+/// with it. The result holds the whole message, chunks or not.
+pub struct SamplingStream {
+    progress_token: ProgressToken,
+    chunks: mpsc::Receiver<SamplingChunk>,
+    response: BoxFuture<'static, Result<CreateMessageResult, ServiceError>>,
+    result: Option<Result<CreateMessageResult, ServiceError>>,
+    streams: Streams,
+}
+
+impl SamplingStream {
+    pub fn progress_token(&self) -> &ProgressToken {
+        &self.progress_token
+    }
+
+    /// Wait for the final result, skipping the chunks still to come.
+    pub async fn result(mut self) -> Result<CreateMessageResult, ServiceError> {
+        match self.result.take() {
+            Some(result) => result,
+            None => self.response.as_mut().await,
+        }
+    }
+}
+
+impl Stream for SamplingStream {
+    type Item = SamplingChunk;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Poll::Ready(Some(chunk)) = self.chunks.poll_recv(cx) {
+            return Poll::Ready(Some(chunk));
+        }
+        if self.result.is_none() {
+            match self.response.poll_unpin(cx) {
+                Poll::Ready(result) => self.result = Some(result),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Poll::Ready(self.chunks.try_recv().ok())
+    }
+}
+
+impl Drop for SamplingStream {
+    fn drop(&mut self) {
+        let token = self.progress_token.clone();
+        self.chunks.close();
+        let streams = self.streams.clone();
+        tokio::spawn(async move {
+            streams.write_owned().await.remove(&token);
+        });
+    }
+}
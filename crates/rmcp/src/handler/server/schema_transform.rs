@@ -0,0 +1,252 @@
+//! Post-processing of tool schemas.
+//!
+//! Some LLM providers reject parts of what schemars generates, such as `$ref`
+//! pointers, `format` keywords or very long enums. A schema transform is any
+//! `Fn(&mut JsonObject)`; apply one to a single tool with
+//! `#[tool(schema_transform = ...)]`, or to every tool of a router with
+//! `#[tool_router(schema_transform = ...)]` or [`ToolRouter::transform_schemas`].
+//!
+//! ```rust
+//! # use rmcp::{handler::server::schema_transform::{cap_enum, inline_refs, strip_formats}, model::JsonObject};
+//! fn for_strict_providers(schema: &mut JsonObject) {
+//!     inline_refs(schema);
+//!     strip_formats(schema);
+//!     cap_enum(50)(schema);
+//! }
+//! ```
+//!
+//! [`ToolRouter::transform_schemas`]: super::router::tool::ToolRouter::transform_schemas
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::model::JsonObject;
+
+/// Run `transform` on a shared schema, cloning it only if it is shared.
+pub fn transform_schema(
+    mut schema: Arc<JsonObject>,
+    transform: impl FnOnce(&mut JsonObject),
+) -> Arc<JsonObject> {
+    transform(Arc::make_mut(&mut schema));
+    schema
+}
+
+/// Call `f` on `schema` and then on each of its subschemas, depth first.
+pub fn visit_schemas(schema: &mut JsonObject, f: &mut impl FnMut(&mut JsonObject)) {
+    f(schema);
+    for_each_subschema(schema, &mut |subschema| visit_schemas(subschema, f));
+}
+
+/// Call `f` on the direct subschemas of `schema`, skipping `$defs` when
+/// `include_defs` is false.
+fn for_each_subschema_with(
+    schema: &mut JsonObject,
+    include_defs: bool,
+    f: &mut impl FnMut(&mut JsonObject),
+) {
+    for (key, value) in schema.iter_mut() {
+        match key.as_str() {
+            "$defs" | "definitions" if !include_defs => {}
+            "properties" | "patternProperties" | "dependentSchemas" | "$defs" | "definitions" => {
+                if let Value::Object(map) = value {
+                    map.values_mut()
+                        .filter_map(Value::as_object_mut)
+                        .for_each(&mut *f);
+                }
+            }
+            "allOf" | "anyOf" | "oneOf" | "prefixItems" | "items" => match value {
+                Value::Array(values) => values
+                    .iter_mut()
+                    .filter_map(Value::as_object_mut)
+                    .for_each(&mut *f),
+                Value::Object(subschema) => f(subschema),
+                _ => {}
+            },
+            "additionalProperties"
+            | "additionalItems"
+            | "unevaluatedProperties"
+            | "unevaluatedItems"
+            | "not"
+            | "contains"
+            | "propertyNames"
+            | "if"
+            | "then"
+            | "else" => {
+                if let Value::Object(subschema) = value {
+                    f(subschema)
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn for_each_subschema(schema: &mut JsonObject, f: &mut impl FnMut(&mut JsonObject)) {
+    for_each_subschema_with(schema, true, f)
+}
+
+/// Replace local `$ref` pointers with the schema they point to, and drop
+/// `$defs` and `definitions` once nothing refers to them.
+///
+/// Recursive references can't be inlined; they are left in place and the
+/// definitions are kept for them.
+pub fn inline_refs(schema: &mut JsonObject) {
+    let root = Value::Object(schema.clone());
+    let mut unresolved = false;
+    inline_refs_in(schema, &root, &mut vec!["#".to_owned()], &mut unresolved);
+    if !unresolved {
+        schema.remove("$defs");
+        schema.remove("definitions");
+    }
+}
+
+fn inline_refs_in(
+    schema: &mut JsonObject,
+    root: &Value,
+    stack: &mut Vec<String>,
+    unresolved: &mut bool,
+) {
+    if let Some(Value::String(reference)) = schema.get("$ref") {
+        let reference = reference.clone();
+        let target = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+            .and_then(Value::as_object);
+        match target {
+            Some(target) if !stack.contains(&reference) => {
+                schema.remove("$ref");
+                for (key, value) in target {
+                    if !schema.contains_key(key) {
+                        schema.insert(key.clone(), value.clone());
+                    }
+                }
+                stack.push(reference);
+                inline_refs_in(schema, root, stack, unresolved);
+                stack.pop();
+                return;
+            }
+            _ => *unresolved = true,
+        }
+    }
+    for_each_subschema_with(schema, false, &mut |subschema| {
+        inline_refs_in(subschema, root, stack, unresolved)
+    });
+}
+
+/// Remove every `format` keyword.
+pub fn strip_formats(schema: &mut JsonObject) {
+    visit_schemas(schema, &mut |schema| {
+        schema.remove("format");
+    });
+}
+
+/// Drop `enum` keywords listing more than `max` values, leaving the rest of
+/// the subschema, usually its `type`, in place.
+///
+/// The schema gets looser rather than wrong: every value it accepted is
+/// still accepted.
+pub fn cap_enum(max: usize) -> impl Fn(&mut JsonObject) + Clone + Send + Sync + 'static {
+    move |schema| {
+        visit_schemas(schema, &mut |schema| {
+            if schema
+                .get("enum")
+                .and_then(Value::as_array)
+                .is_some_and(|values| values.len() > max)
+            {
+                schema.remove("enum");
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::object;
+
+    #[test]
+    fn test_inline_refs() {
+        let mut schema = object(json!({
+            "type": "object",
+            "properties": {
+                "point": { "$ref": "#/$defs/Point", "description": "where" },
+                "points": { "type": "array", "items": { "$ref": "#/$defs/Point" } },
+            },
+            "$defs": {
+                "Point": {
+                    "type": "object",
+                    "properties": { "x": { "$ref": "#/$defs/Coordinate" } },
+                },
+                "Coordinate": { "type": "number" },
+            },
+        }));
+        inline_refs(&mut schema);
+        let point = json!({
+            "type": "object",
+            "properties": { "x": { "type": "number" } },
+        });
+        assert_eq!(
+            Value::Object(schema),
+            json!({
+                "type": "object",
+                "properties": {
+                    "point": {
+                        "description": "where",
+                        "type": "object",
+                        "properties": { "x": { "type": "number" } },
+                    },
+                    "points": { "type": "array", "items": point },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_inline_refs_keeps_recursive_definitions() {
+        let mut schema = object(json!({
+            "type": "object",
+            "properties": { "root": { "$ref": "#/$defs/Node" } },
+            "$defs": {
+                "Node": {
+                    "type": "object",
+                    "properties": {
+                        "children": { "type": "array", "items": { "$ref": "#/$defs/Node" } },
+                    },
+                },
+            },
+        }));
+        inline_refs(&mut schema);
+        assert!(schema.contains_key("$defs"));
+        assert_eq!(
+            schema["properties"]["root"]["properties"]["children"]["items"],
+            json!({ "$ref": "#/$defs/Node" })
+        );
+    }
+
+    #[test]
+    fn test_strip_formats_and_cap_enum() {
+        let mut schema = object(json!({
+            "type": "object",
+            "properties": {
+                "format": { "type": "string", "enum": ["json", "yaml", "toml"] },
+                "at": { "type": "string", "format": "date-time" },
+                "level": { "type": "string", "enum": ["low", "high"] },
+            },
+        }));
+        strip_formats(&mut schema);
+        cap_enum(2)(&mut schema);
+        assert_eq!(
+            Value::Object(schema),
+            json!({
+                "type": "object",
+                "properties": {
+                    "format": { "type": "string" },
+                    "at": { "type": "string" },
+                    "level": { "type": "string", "enum": ["low", "high"] },
+                },
+            })
+        );
+    }
+}
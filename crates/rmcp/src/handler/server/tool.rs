@@ -9,7 +9,10 @@ use serde::de::DeserializeOwned;
 
 use super::common::{AsRequestContext, FromContextPart};
 pub use super::{
-    common::{Extension, RequestId, schema_for_output, schema_for_type},
+    common::{
+        Extension, InputSchemaMismatch, RequestId, check_input_schema, schema_for_output,
+        schema_for_type,
+    },
     router::tool::{ToolRoute, ToolRouter},
 };
 use crate::{
@@ -28,6 +31,36 @@ pub fn parse_json_object<T: DeserializeOwned>(input: JsonObject) -> Result<T, cr
         )
     })
 }
+
+/// Run a CPU-heavy or blocking tool, as `#[tool(blocking)]` does.
+///
+/// `f` runs on tokio's blocking thread pool (see
+/// [`tokio::task::spawn_blocking`]), so the runtime's workers keep serving
+/// the session, e.g. answering pings, meanwhile. A panic in `f` resumes in
+/// the caller. On wasm `f` just runs in place.
+#[cfg(not(target_family = "wasm"))]
+pub async fn run_blocking<R>(f: impl FnOnce() -> R + Send + 'static) -> R
+where
+    R: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(error) => match error.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(error) => panic!("blocking tool did not finish: {error}"),
+        },
+    }
+}
+
+/// Run a CPU-heavy or blocking tool, as `#[tool(blocking)]` does.
+#[cfg(target_family = "wasm")]
+pub async fn run_blocking<R>(f: impl FnOnce() -> R + Send + 'static) -> R
+where
+    R: Send + 'static,
+{
+    f()
+}
+
 pub struct ToolCallContext<'s, S> {
     pub request_context: RequestContext<RoleServer>,
     pub service: &'s S,
@@ -92,6 +125,42 @@ impl<T: IntoContents, E: IntoContents> IntoCallToolResult for Result<T, E> {
     }
 }
 
+/// The status half of a `(status, contents)` tool return value.
+///
+/// ```rust
+/// # use rmcp::handler::server::tool::IntoCallToolResult;
+/// let result = (404u16, "no such user".to_string()).into_call_tool_result().unwrap();
+/// assert_eq!(result.is_error, Some(true));
+/// ```
+pub trait ToolStatus {
+    fn is_error(&self) -> bool;
+}
+
+/// `true` means success.
+impl ToolStatus for bool {
+    fn is_error(&self) -> bool {
+        !*self
+    }
+}
+
+/// An HTTP-style status code, where 400 and above are errors.
+impl ToolStatus for u16 {
+    fn is_error(&self) -> bool {
+        *self >= 400
+    }
+}
+
+impl<S: ToolStatus, T: IntoContents> IntoCallToolResult for (S, T) {
+    fn into_call_tool_result(self) -> Result<CallToolResult, crate::ErrorData> {
+        let (status, contents) = self;
+        if status.is_error() {
+            Ok(CallToolResult::error(contents.into_contents()))
+        } else {
+            Ok(CallToolResult::success(contents.into_contents()))
+        }
+    }
+}
+
 impl<T: IntoCallToolResult> IntoCallToolResult for Result<T, crate::ErrorData> {
     fn into_call_tool_result(self) -> Result<CallToolResult, crate::ErrorData> {
         match self {
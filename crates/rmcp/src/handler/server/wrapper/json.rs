@@ -1,11 +1,11 @@
 use std::borrow::Cow;
 
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Serialize, de::DeserializeOwned};
 
 use crate::{
     handler::server::tool::IntoCallToolResult,
-    model::{CallToolResult, IntoContents},
+    model::{CallToolResult, IntoContents, ToolResultError},
 };
 
 /// Json wrapper for structured output
@@ -52,3 +52,12 @@ impl<T: Serialize + JsonSchema + 'static, E: IntoContents> IntoCallToolResult
         }
     }
 }
+
+/// Read a typed value back from a tool result, see [`CallToolResult::json`].
+impl<T: DeserializeOwned> TryFrom<CallToolResult> for Json<T> {
+    type Error = ToolResultError;
+
+    fn try_from(result: CallToolResult) -> Result<Self, Self::Error> {
+        result.json().map(Json)
+    }
+}
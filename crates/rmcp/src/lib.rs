@@ -141,8 +141,11 @@
 //! }
 //! ```
 mod error;
+#[cfg(any(feature = "client", feature = "server"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "client", feature = "server"))))]
+pub use error::RmcpError;
 #[allow(deprecated)]
-pub use error::{Error, ErrorData, RmcpError};
+pub use error::{Error, ErrorChain, ErrorData};
 
 /// Basic data types in MCP specification
 pub mod model;
@@ -168,9 +171,20 @@ pub use service::{RoleClient, serve_client};
 #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
 pub use service::{RoleServer, serve_server};
 
+#[cfg(feature = "client")]
+#[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+pub mod blocking;
 pub mod handler;
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
 pub mod task_manager;
+#[cfg(feature = "tokens")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokens")))]
+pub mod tokens;
+#[cfg(any(feature = "client", feature = "server"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "client", feature = "server"))))]
 pub mod transport;
+pub mod uri;
 
 // re-export
 #[cfg(all(feature = "macros", feature = "server"))]
@@ -4,9 +4,15 @@ mod capabilities;
 mod content;
 mod elicitation_schema;
 mod extension;
+#[cfg(feature = "schemars")]
+#[cfg_attr(docsrs, doc(cfg(feature = "schemars")))]
+pub mod json_schema;
+pub mod lenient;
 mod meta;
+pub mod number;
 mod prompt;
 mod resource;
+mod sampling;
 mod serde_impl;
 mod task;
 mod tool;
@@ -18,6 +24,7 @@ pub use extension::*;
 pub use meta::*;
 pub use prompt::*;
 pub use resource::*;
+pub use sampling::*;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::Value;
 pub use task::*;
@@ -58,7 +65,10 @@ macro_rules! object {
 /// without returning any specific data.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Copy, Eq)]
 #[serde(deny_unknown_fields)]
-#[cfg_attr(feature = "server", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    any(feature = "server", feature = "schemars"),
+    derive(schemars::JsonSchema)
+)]
 pub struct EmptyObject {}
 
 pub trait ConstString: Default {
@@ -450,12 +460,46 @@ pub struct JsonRpcNotification<N = Notification> {
 pub struct ErrorCode(pub i32);
 
 impl ErrorCode {
+    /// The peer went away before a response was produced.
+    ///
+    /// Not defined by the spec, but shared with the other official SDKs.
+    pub const CONNECTION_CLOSED: Self = Self(-32000);
+    /// The request did not complete in time.
+    ///
+    /// Not defined by the spec, but shared with the other official SDKs.
+    pub const REQUEST_TIMEOUT: Self = Self(-32001);
+    /// A `resources/read` (or similar) referenced a URI the server does not know.
     pub const RESOURCE_NOT_FOUND: Self = Self(-32002);
+    /// The server is shedding load and refused the request without handling
+    /// it. `data.retryAfterMs` hints when to try again.
+    pub const SERVER_OVERLOADED: Self = Self(-32003);
+    /// The server needs the user to complete one or more URL-mode elicitations
+    /// before it can handle the request. `data.elicitations` lists them.
+    pub const URL_ELICITATION_REQUIRED: Self = Self(-32042);
+    /// The message is valid JSON but not a valid request object.
     pub const INVALID_REQUEST: Self = Self(-32600);
+    /// The method does not exist, or the matching capability was not negotiated.
     pub const METHOD_NOT_FOUND: Self = Self(-32601);
+    /// The params don't match what the method expects. Also used for unknown
+    /// tools, prompts and task ids.
     pub const INVALID_PARAMS: Self = Self(-32602);
+    /// Something went wrong on the receiving side while handling a valid request.
     pub const INTERNAL_ERROR: Self = Self(-32603);
+    /// The message could not be parsed as JSON.
     pub const PARSE_ERROR: Self = Self(-32700);
+
+    /// Whether a request that failed with this code may succeed if sent again
+    /// unchanged.
+    ///
+    /// Only failures caused by the connection, by timing or by load are
+    /// considered transient; everything else reflects the request itself and
+    /// will fail the same way on retry.
+    pub const fn is_retryable(&self) -> bool {
+        matches!(
+            *self,
+            Self::CONNECTION_CLOSED | Self::REQUEST_TIMEOUT | Self::SERVER_OVERLOADED
+        )
+    }
 }
 
 /// Error information for JSON-RPC error responses.
@@ -473,7 +517,11 @@ pub struct ErrorData {
 
     /// Additional information about the error. The value of this member is defined by the
     /// sender (e.g. detailed error information, nested errors etc.).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "serde_impl::nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub data: Option<Value>,
 }
 
@@ -507,6 +555,51 @@ impl ErrorData {
     pub fn internal_error(message: impl Into<Cow<'static, str>>, data: Option<Value>) -> Self {
         Self::new(ErrorCode::INTERNAL_ERROR, message, data)
     }
+    pub fn connection_closed(message: impl Into<Cow<'static, str>>, data: Option<Value>) -> Self {
+        Self::new(ErrorCode::CONNECTION_CLOSED, message, data)
+    }
+    pub fn request_timeout(message: impl Into<Cow<'static, str>>, data: Option<Value>) -> Self {
+        Self::new(ErrorCode::REQUEST_TIMEOUT, message, data)
+    }
+    pub fn server_overloaded(message: impl Into<Cow<'static, str>>, data: Option<Value>) -> Self {
+        Self::new(ErrorCode::SERVER_OVERLOADED, message, data)
+    }
+    /// Ask the client to complete the given URL-mode elicitations first.
+    ///
+    /// `elicitations` are the params of the `elicitation/create` requests the
+    /// client should perform, as required by the spec.
+    pub fn url_elicitation_required(
+        message: impl Into<Cow<'static, str>>,
+        elicitations: Vec<Value>,
+    ) -> Self {
+        Self::new(
+            ErrorCode::URL_ELICITATION_REQUIRED,
+            message,
+            Some(serde_json::json!({ "elicitations": elicitations })),
+        )
+    }
+    /// The task id given to a `tasks/*` request is unknown or has expired.
+    pub fn task_not_found(task_id: impl Into<String>) -> Self {
+        let task_id = task_id.into();
+        Self::new(
+            ErrorCode::INVALID_PARAMS,
+            format!("task not found: {task_id}"),
+            Some(serde_json::json!({ "taskId": task_id })),
+        )
+    }
+    /// The result of the task was dropped by the server's retention policy.
+    pub fn task_expired(task_id: impl Into<String>) -> Self {
+        let task_id = task_id.into();
+        Self::new(
+            ErrorCode::INVALID_PARAMS,
+            format!("task result expired: {task_id}"),
+            Some(serde_json::json!({ "taskId": task_id, "expired": true })),
+        )
+    }
+    /// See [`ErrorCode::is_retryable`].
+    pub fn is_retryable(&self) -> bool {
+        self.code.is_retryable()
+    }
 }
 
 /// Represents any JSON-RPC message that can be sent or received.
@@ -793,6 +886,47 @@ impl Default for ServerInfo {
     }
 }
 
+impl InitializeResult {
+    /// Maximum length of [`instructions`](Self::instructions) in bytes. The
+    /// specification sets no limit, but clients usually put them in the
+    /// model's context, so a server shouldn't spend it all there.
+    pub const MAX_INSTRUCTIONS_LEN: usize = 16 * 1024;
+
+    pub fn with_server_info(self, server_info: Implementation) -> Self {
+        Self {
+            server_info,
+            ..self
+        }
+    }
+
+    pub fn with_capabilities(self, capabilities: ServerCapabilities) -> Self {
+        Self {
+            capabilities,
+            ..self
+        }
+    }
+
+    pub fn with_instructions(self, instructions: impl Into<String>) -> Self {
+        Self {
+            instructions: Some(instructions.into()),
+            ..self
+        }
+    }
+
+    /// Validate that the instructions are no longer than
+    /// [`MAX_INSTRUCTIONS_LEN`](Self::MAX_INSTRUCTIONS_LEN).
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.instructions {
+            Some(instructions) if instructions.len() > Self::MAX_INSTRUCTIONS_LEN => Err(format!(
+                "Instructions too long: {} bytes (max: {})",
+                instructions.len(),
+                Self::MAX_INSTRUCTIONS_LEN
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
 #[allow(clippy::derivable_impls)]
 impl Default for ClientInfo {
     fn default() -> Self {
@@ -995,6 +1129,45 @@ pub struct ReadResourceResult {
     pub contents: Vec<ResourceContents>,
 }
 
+impl ReadResourceResult {
+    /// The first text entry.
+    pub fn text(&self) -> Result<&str, ResourceContentsError> {
+        self.non_empty()?
+            .iter()
+            .find_map(ResourceContents::as_text)
+            .ok_or(ResourceContentsError::NotText)
+    }
+
+    /// The raw bytes of the first entry, see [`ResourceContents::to_bytes`].
+    #[cfg(feature = "base64")]
+    pub fn bytes(&self) -> Result<Vec<u8>, ResourceContentsError> {
+        self.non_empty()?[0].to_bytes()
+    }
+
+    /// Parse the first entry with a JSON mime type, or the first entry if
+    /// there is none, as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, ResourceContentsError> {
+        let contents = self.non_empty()?;
+        contents
+            .iter()
+            .find(|contents| {
+                contents.mime_type().is_some_and(|mime| {
+                    mime.split(';').next().unwrap_or_default().ends_with("json")
+                })
+            })
+            .unwrap_or(&contents[0])
+            .json()
+    }
+
+    fn non_empty(&self) -> Result<&[ResourceContents], ResourceContentsError> {
+        if self.contents.is_empty() {
+            Err(ResourceContentsError::Empty)
+        } else {
+            Ok(&self.contents)
+        }
+    }
+}
+
 /// Request to read a specific resource
 pub type ReadResourceRequest = Request<ReadResourceRequestMethod, ReadResourceRequestParams>;
 
@@ -1202,7 +1375,7 @@ pub type CreateMessageRequest = Request<CreateMessageRequestMethod, CreateMessag
 ///
 /// Used in sampling and chat contexts to distinguish between different
 /// types of message senders in the conversation flow.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum Role {
@@ -1222,8 +1395,9 @@ pub enum Role {
 pub struct SamplingMessage {
     /// The role of the message sender (User or Assistant)
     pub role: Role,
-    /// The actual content of the message (text, image, etc.)
-    pub content: Content,
+    /// The content of the message: one block or several (text, image,
+    /// tool use, etc.)
+    pub content: SamplingMessageContent,
 }
 
 /// Specifies how much context should be included in sampling requests.
@@ -1282,7 +1456,11 @@ pub struct CreateMessageRequestParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stop_sequences: Option<Vec<String>>,
     /// Additional metadata for the request
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "serde_impl::nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub metadata: Option<Value>,
 }
 
@@ -1789,6 +1967,24 @@ impl CreateElicitationRequestParams {
     pub fn is_url_mode(&self) -> bool {
         self.mode == ElicitationMode::Url
     }
+
+    /// Check an accepted form response against the requested schema. Other
+    /// responses have nothing to check.
+    pub fn validate_response(
+        &self,
+        result: &CreateElicitationResult,
+    ) -> Result<(), ElicitationValidationError> {
+        let Some(schema) = &self.requested_schema else {
+            return Ok(());
+        };
+        if !self.is_form_mode() || result.action != ElicitationAction::Accept {
+            return Ok(());
+        }
+        match &result.content {
+            Some(content) => schema.validate(content),
+            None => schema.validate(&Value::Object(Default::default())),
+        }
+    }
 }
 
 impl RequestParamsMeta for CreateElicitationRequestParams {
@@ -1823,6 +2019,112 @@ pub struct CreateElicitationResult {
     pub content: Option<Value>,
 }
 
+impl CreateElicitationResult {
+    pub fn accept(content: Value) -> Self {
+        Self {
+            action: ElicitationAction::Accept,
+            content: Some(content),
+        }
+    }
+
+    pub fn decline() -> Self {
+        Self {
+            action: ElicitationAction::Decline,
+            content: None,
+        }
+    }
+
+    pub fn cancel() -> Self {
+        Self {
+            action: ElicitationAction::Cancel,
+            content: None,
+        }
+    }
+
+    /// The user's decision, with the accepted content deserialized as `T`.
+    ///
+    /// Accepting without content, as in URL mode, deserializes `null`, so
+    /// use `ElicitationOutcome<()>` there.
+    pub fn outcome<T: serde::de::DeserializeOwned>(
+        &self,
+    ) -> Result<ElicitationOutcome<T>, serde_json::Error> {
+        Ok(match self.action {
+            ElicitationAction::Accept => ElicitationOutcome::Accept(serde_json::from_value(
+                self.content.clone().unwrap_or_default(),
+            )?),
+            ElicitationAction::Decline => ElicitationOutcome::Decline,
+            ElicitationAction::Cancel => ElicitationOutcome::Cancel,
+        })
+    }
+}
+
+/// What the user did with an elicitation, with the data they provided
+///
+/// Servers get one from [`CreateElicitationResult::outcome`] (or
+/// `Peer::elicit_outcome`), and clients turn theirs into a response with
+/// [`into_result`](Self::into_result), so neither side has to match the
+/// action against the content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElicitationOutcome<T = Value> {
+    /// The user provided the requested data
+    Accept(T),
+    /// The user refused to provide it, the operation may go on without it
+    Decline,
+    /// The user dismissed the request, or it timed out
+    Cancel,
+}
+
+impl<T> ElicitationOutcome<T> {
+    pub fn action(&self) -> ElicitationAction {
+        match self {
+            ElicitationOutcome::Accept(_) => ElicitationAction::Accept,
+            ElicitationOutcome::Decline => ElicitationAction::Decline,
+            ElicitationOutcome::Cancel => ElicitationAction::Cancel,
+        }
+    }
+
+    /// The accepted data, if any.
+    pub fn accepted(self) -> Option<T> {
+        match self {
+            ElicitationOutcome::Accept(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ElicitationOutcome<U> {
+        match self {
+            ElicitationOutcome::Accept(value) => ElicitationOutcome::Accept(f(value)),
+            ElicitationOutcome::Decline => ElicitationOutcome::Decline,
+            ElicitationOutcome::Cancel => ElicitationOutcome::Cancel,
+        }
+    }
+}
+
+impl<T: Serialize> ElicitationOutcome<T> {
+    /// The response to send back. Accepted data serializing to `null`, e.g.
+    /// `()`, is sent without content.
+    pub fn into_result(self) -> Result<CreateElicitationResult, serde_json::Error> {
+        Ok(match self {
+            ElicitationOutcome::Accept(value) => CreateElicitationResult {
+                action: ElicitationAction::Accept,
+                content: Some(serde_json::to_value(value)?).filter(|content| !content.is_null()),
+            },
+            ElicitationOutcome::Decline => CreateElicitationResult::decline(),
+            ElicitationOutcome::Cancel => CreateElicitationResult::cancel(),
+        })
+    }
+}
+
+impl From<ElicitationOutcome<Value>> for CreateElicitationResult {
+    fn from(outcome: ElicitationOutcome<Value>) -> Self {
+        match outcome {
+            ElicitationOutcome::Accept(content) => CreateElicitationResult::accept(content),
+            ElicitationOutcome::Decline => CreateElicitationResult::decline(),
+            ElicitationOutcome::Cancel => CreateElicitationResult::cancel(),
+        }
+    }
+}
+
 /// Request type for creating an elicitation to gather user input
 pub type CreateElicitationRequest =
     Request<ElicitationCreateRequestMethod, CreateElicitationRequestParams>;
@@ -1962,6 +2264,55 @@ impl CallToolResult {
         }
         serde_json::from_value(serde_json::Value::Null)
     }
+
+    /// Whether the tool reported a failure. An absent flag means success.
+    pub fn is_error(&self) -> bool {
+        self.is_error.unwrap_or(false)
+    }
+
+    /// The first text content block.
+    pub fn text(&self) -> Option<&str> {
+        self.content
+            .iter()
+            .find_map(|content| content.as_text().map(|text| text.text.as_str()))
+    }
+
+    /// The content blocks meant for `role`, i.e. those whose audience
+    /// includes it or that have no audience at all.
+    ///
+    /// Clients typically show `content_for(Role::User)` to the user and feed
+    /// `content_for(Role::Assistant)` back to the model.
+    pub fn content_for(&self, role: Role) -> impl Iterator<Item = &Content> {
+        self.content
+            .iter()
+            .filter(move |content| content.is_for(role))
+    }
+
+    /// Parse the result into `T`, from the structured content if there is
+    /// any, or else from the first text block.
+    ///
+    /// Unlike [`into_typed`](Self::into_typed), results flagged as errors are
+    /// turned into [`ToolResultError::ToolError`].
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, ToolResultError> {
+        if self.is_error() {
+            return Err(ToolResultError::ToolError(Box::new(self.clone())));
+        }
+        match (&self.structured_content, self.text()) {
+            (Some(value), _) => Ok(T::deserialize(value)?),
+            (None, Some(text)) => Ok(serde_json::from_str(text)?),
+            (None, None) => Ok(serde_json::from_value(Value::Null)?),
+        }
+    }
+}
+
+/// Why a [`CallToolResult`] couldn't be turned into a value.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ToolResultError {
+    #[error("tool returned an error: {}", .0.text().unwrap_or("<no text content>"))]
+    ToolError(Box<CallToolResult>),
+    #[error("unexpected tool result: {0}")]
+    Json(#[from] serde_json::Error),
 }
 
 // Custom deserialize implementation to validate mutual exclusivity
@@ -1975,7 +2326,7 @@ impl<'de> Deserialize<'de> for CallToolResult {
         struct CallToolResultHelper {
             #[serde(skip_serializing_if = "Option::is_none")]
             content: Option<Vec<Content>>,
-            #[serde(skip_serializing_if = "Option::is_none")]
+            #[serde(default, deserialize_with = "serde_impl::nullable")]
             structured_content: Option<Value>,
             #[serde(skip_serializing_if = "Option::is_none")]
             is_error: Option<bool>,
@@ -2199,6 +2550,14 @@ pub struct ListTasksResult {
     pub total: Option<u64>,
 }
 
+const_string!(TaskStatusNotificationMethod = "notifications/tasks/status");
+/// The task whose status changed, as it is now.
+pub type TaskStatusNotificationParam = Task;
+/// Notification sent by the receiver of a task-augmented request when the
+/// status of the task changes (SEP-1686)
+pub type TaskStatusNotification =
+    Notification<TaskStatusNotificationMethod, TaskStatusNotificationParam>;
+
 // =============================================================================
 // MESSAGE TYPE UNIONS
 // =============================================================================
@@ -2266,11 +2625,11 @@ ts_union!(
     | UnsubscribeRequest
     | CallToolRequest
     | ListToolsRequest
-    | CustomRequest
     | GetTaskInfoRequest
     | ListTasksRequest
     | GetTaskResultRequest
-    | CancelTaskRequest;
+    | CancelTaskRequest
+    | CustomRequest;
 );
 
 impl ClientRequest {
@@ -2296,6 +2655,20 @@ impl ClientRequest {
             ClientRequest::CancelTaskRequest(r) => r.method.as_str(),
         }
     }
+
+    /// Whether sending this request twice has the same effect as sending it
+    /// once: `ping`, the `*/list` requests and `resources/read`.
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            ClientRequest::PingRequest(_)
+                | ClientRequest::ListPromptsRequest(_)
+                | ClientRequest::ListResourcesRequest(_)
+                | ClientRequest::ListResourceTemplatesRequest(_)
+                | ClientRequest::ReadResourceRequest(_)
+                | ClientRequest::ListToolsRequest(_)
+        )
+    }
 }
 
 #[allow(deprecated)]
@@ -2309,6 +2682,20 @@ ts_union!(
     | CustomNotification;
 );
 
+impl ClientNotification {
+    #[allow(deprecated)]
+    pub fn method(&self) -> &str {
+        match &self {
+            ClientNotification::CancelledNotification(n) => n.method.as_str(),
+            ClientNotification::ProgressNotification(n) => n.method.as_str(),
+            ClientNotification::InitializedNotification(n) => n.method.as_str(),
+            ClientNotification::RootsListChangedNotification(n) => n.method.as_str(),
+            ClientNotification::ElicitationCompleteNotification(n) => n.method.as_str(),
+            ClientNotification::CustomNotification(n) => n.method.as_str(),
+        }
+    }
+}
+
 #[allow(deprecated)]
 ts_union!(
     export type ClientResult =
@@ -2337,6 +2724,19 @@ ts_union!(
     | CustomRequest;
 );
 
+impl ServerRequest {
+    #[allow(deprecated)]
+    pub fn method(&self) -> &str {
+        match &self {
+            ServerRequest::PingRequest(r) => r.method.as_str(),
+            ServerRequest::CreateMessageRequest(r) => r.method.as_str(),
+            ServerRequest::ListRootsRequest(r) => r.method.as_str(),
+            ServerRequest::CreateElicitationRequest(r) => r.method.as_str(),
+            ServerRequest::CustomRequest(r) => r.method.as_str(),
+        }
+    }
+}
+
 ts_union!(
     export type ServerNotification =
     | CancelledNotification
@@ -2346,9 +2746,26 @@ ts_union!(
     | ResourceListChangedNotification
     | ToolListChangedNotification
     | PromptListChangedNotification
+    | TaskStatusNotification
     | CustomNotification;
 );
 
+impl ServerNotification {
+    pub fn method(&self) -> &str {
+        match &self {
+            ServerNotification::CancelledNotification(n) => n.method.as_str(),
+            ServerNotification::ProgressNotification(n) => n.method.as_str(),
+            ServerNotification::LoggingMessageNotification(n) => n.method.as_str(),
+            ServerNotification::ResourceUpdatedNotification(n) => n.method.as_str(),
+            ServerNotification::ResourceListChangedNotification(n) => n.method.as_str(),
+            ServerNotification::ToolListChangedNotification(n) => n.method.as_str(),
+            ServerNotification::PromptListChangedNotification(n) => n.method.as_str(),
+            ServerNotification::TaskStatusNotification(n) => n.method.as_str(),
+            ServerNotification::CustomNotification(n) => n.method.as_str(),
+        }
+    }
+}
+
 ts_union!(
     export type ServerResult =
     | InitializeResult
@@ -2362,11 +2779,12 @@ ts_union!(
     | ListToolsResult
     | CreateElicitationResult
     | EmptyResult
-    | CustomResult
     | CreateTaskResult
     | ListTasksResult
-    | GetTaskInfoResult
     | TaskResult
+    // every field is optional, so it would match any object
+    | CustomResult
+    | GetTaskInfoResult
     ;
 );
 
@@ -2854,4 +3272,37 @@ mod tests {
         assert_eq!(json["serverInfo"]["icons"][0]["sizes"][0], "48x48");
         assert_eq!(json["serverInfo"]["websiteUrl"], "https://docs.example.com");
     }
+
+    #[test]
+    fn test_error_codes() {
+        assert!(ErrorCode::CONNECTION_CLOSED.is_retryable());
+        assert!(ErrorData::request_timeout("timed out", None).is_retryable());
+        assert!(ErrorData::server_overloaded("busy", None).is_retryable());
+        assert!(!ErrorCode::INVALID_PARAMS.is_retryable());
+        assert!(!ErrorCode::URL_ELICITATION_REQUIRED.is_retryable());
+
+        let error = ErrorData::url_elicitation_required(
+            "authorization required",
+            vec![json!({
+                "mode": "url",
+                "elicitationId": "e1",
+                "url": "https://example.com/auth",
+                "message": "Sign in"
+            })],
+        );
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], -32042);
+        assert_eq!(json["data"]["elicitations"][0]["elicitationId"], "e1");
+
+        let error = ErrorData::task_not_found("t-1");
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(error.data, Some(json!({ "taskId": "t-1" })));
+
+        let error = ErrorData::task_expired("t-1");
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(
+            error.data,
+            Some(json!({ "taskId": "t-1", "expired": true }))
+        );
+    }
 }
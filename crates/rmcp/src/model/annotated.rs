@@ -34,6 +34,40 @@ impl Annotations {
             audience: None,
         }
     }
+
+    /// Annotations that only target the given audience.
+    pub fn for_audience(audience: impl IntoIterator<Item = Role>) -> Self {
+        Annotations::default().with_audience(audience)
+    }
+
+    pub fn with_audience(mut self, audience: impl IntoIterator<Item = Role>) -> Self {
+        self.audience = Some(audience.into_iter().collect());
+        self
+    }
+
+    /// # Panics
+    /// If `priority` is outside of `0.0..=1.0`.
+    pub fn with_priority(mut self, priority: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&priority),
+            "Priority {priority} must be between 0.0 and 1.0"
+        );
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn with_last_modified(mut self, last_modified: DateTime<Utc>) -> Self {
+        self.last_modified = Some(last_modified);
+        self
+    }
+
+    /// Whether the annotated item is meant for `role`. Without an audience
+    /// the item is meant for everyone.
+    pub fn is_for(&self, role: Role) -> bool {
+        self.audience
+            .as_ref()
+            .is_none_or(|audience| audience.contains(&role))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -74,6 +108,10 @@ impl<T: AnnotateAble> Annotated<T> {
     pub fn timestamp(&self) -> Option<DateTime<Utc>> {
         self.annotations.as_ref().and_then(|a| a.last_modified)
     }
+    /// Whether this is meant for `role`, see [`Annotations::is_for`].
+    pub fn is_for(&self, role: Role) -> bool {
+        self.annotations.as_ref().is_none_or(|a| a.is_for(role))
+    }
     pub fn with_audience(self, audience: Vec<Role>) -> Annotated<T>
     where
         Self: Sized,
@@ -222,3 +260,54 @@ pub trait AnnotateAble: sealed::Sealed {
         self.with_timestamp(Utc::now())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audience_filtering() {
+        let user_only = RawTextContent {
+            text: "for you".into(),
+            meta: None,
+        }
+        .annotate(Annotations::for_audience([Role::User]).with_priority(0.5));
+        assert!(user_only.is_for(Role::User));
+        assert!(!user_only.is_for(Role::Assistant));
+        assert_eq!(user_only.priority(), Some(0.5));
+
+        let everyone = RawTextContent {
+            text: "for everyone".into(),
+            meta: None,
+        }
+        .no_annotation();
+        assert!(everyone.is_for(Role::User));
+        assert!(everyone.is_for(Role::Assistant));
+        assert!(Annotations::default().is_for(Role::Assistant));
+    }
+
+    #[test]
+    fn builder_serializes_spec_fields() {
+        let timestamp = DateTime::parse_from_rfc3339("2025-01-12T15:00:58Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let annotations = Annotations::default()
+            .with_audience([Role::User, Role::Assistant])
+            .with_priority(1.0)
+            .with_last_modified(timestamp);
+        assert_eq!(
+            serde_json::to_value(&annotations).unwrap(),
+            serde_json::json!({
+                "audience": ["user", "assistant"],
+                "priority": 1.0,
+                "lastModified": "2025-01-12T15:00:58Z",
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn priority_out_of_range() {
+        let _ = Annotations::default().with_priority(1.5);
+    }
+}
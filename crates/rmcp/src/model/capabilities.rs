@@ -1,11 +1,163 @@
 use std::{collections::BTreeMap, marker::PhantomData};
 
 use pastey::paste;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 use super::JsonObject;
 pub type ExperimentalCapabilities = BTreeMap<String, JsonObject>;
 
+/// A non-standard capability advertised under `experimental`.
+///
+/// The implementing type describes what the capability object looks like, so
+/// both sides can read and write it through
+/// [`ExperimentalCapabilitiesExt`] instead of poking at raw JSON.
+///
+/// ```rust
+/// # use rmcp::model::{ExperimentalCapabilities, ExperimentalCapabilitiesExt, ExperimentalCapability};
+/// #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+/// struct Refresh {
+///     interval_secs: u64,
+/// }
+///
+/// impl ExperimentalCapability for Refresh {
+///     const KEY: &'static str = "x-myorg/refresh";
+/// }
+///
+/// let mut experimental = ExperimentalCapabilities::new();
+/// experimental.insert_typed(&Refresh { interval_secs: 30 })?;
+/// assert_eq!(
+///     experimental.get_typed::<Refresh>()?,
+///     Some(Refresh { interval_secs: 30 })
+/// );
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+pub trait ExperimentalCapability: Serialize + DeserializeOwned {
+    /// The key under `experimental`, e.g. `"x-myorg/refresh"`.
+    const KEY: &'static str;
+}
+
+/// Typed access to [`ExperimentalCapabilities`].
+pub trait ExperimentalCapabilitiesExt {
+    /// Advertise `capability`, replacing any previous value under its key.
+    ///
+    /// Fails if `T` doesn't serialize to a JSON object.
+    fn insert_typed<T: ExperimentalCapability>(
+        &mut self,
+        capability: &T,
+    ) -> Result<(), serde_json::Error>;
+    /// Read the capability advertised under `T::KEY`, if any.
+    fn get_typed<T: ExperimentalCapability>(&self) -> Result<Option<T>, serde_json::Error>;
+    fn contains_typed<T: ExperimentalCapability>(&self) -> bool;
+    fn remove_typed<T: ExperimentalCapability>(&mut self) -> Option<JsonObject>;
+    /// Advertise `key` as supported in each of `versions`, as
+    /// `{"versions": [...]}`, keeping any other field already under `key`.
+    fn insert_versioned<V: Into<String>>(
+        &mut self,
+        key: impl Into<String>,
+        versions: impl IntoIterator<Item = V>,
+    );
+    /// The versions advertised under `key`; empty if there are none.
+    fn versions(&self, key: &str) -> Vec<&str>;
+    /// For each versioned capability both sides advertise, the highest
+    /// version both support. Versions compare by their dot-separated
+    /// numeric parts, e.g. `"1.10"` is above `"1.9"`.
+    fn negotiate_versions(&self, peer: &ExperimentalCapabilities) -> BTreeMap<String, String>;
+}
+
+/// Key of the version list of a versioned experimental capability.
+const VERSIONS_KEY: &str = "versions";
+
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+    loop {
+        let ordering = match (a_parts.next(), b_parts.next()) {
+            (None, None) => return std::cmp::Ordering::Equal,
+            (None, Some(_)) => return std::cmp::Ordering::Less,
+            (Some(_), None) => return std::cmp::Ordering::Greater,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.cmp(b),
+            },
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+impl ExperimentalCapabilitiesExt for ExperimentalCapabilities {
+    fn insert_typed<T: ExperimentalCapability>(
+        &mut self,
+        capability: &T,
+    ) -> Result<(), serde_json::Error> {
+        match serde_json::to_value(capability)? {
+            serde_json::Value::Object(object) => {
+                self.insert(T::KEY.to_owned(), object);
+                Ok(())
+            }
+            _ => Err(serde::ser::Error::custom(format!(
+                "experimental capability {} must serialize to an object",
+                T::KEY
+            ))),
+        }
+    }
+
+    fn get_typed<T: ExperimentalCapability>(&self) -> Result<Option<T>, serde_json::Error> {
+        self.get(T::KEY)
+            .map(|object| serde_json::from_value(serde_json::Value::Object(object.clone())))
+            .transpose()
+    }
+
+    fn contains_typed<T: ExperimentalCapability>(&self) -> bool {
+        self.contains_key(T::KEY)
+    }
+
+    fn remove_typed<T: ExperimentalCapability>(&mut self) -> Option<JsonObject> {
+        self.remove(T::KEY)
+    }
+
+    fn insert_versioned<V: Into<String>>(
+        &mut self,
+        key: impl Into<String>,
+        versions: impl IntoIterator<Item = V>,
+    ) {
+        let versions = versions
+            .into_iter()
+            .map(|version| serde_json::Value::String(version.into()))
+            .collect();
+        self.entry(key.into())
+            .or_default()
+            .insert(VERSIONS_KEY.to_owned(), serde_json::Value::Array(versions));
+    }
+
+    fn versions(&self, key: &str) -> Vec<&str> {
+        self.get(key)
+            .and_then(|object| object.get(VERSIONS_KEY)?.as_array())
+            .map(|versions| {
+                versions
+                    .iter()
+                    .filter_map(serde_json::Value::as_str)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn negotiate_versions(&self, peer: &ExperimentalCapabilities) -> BTreeMap<String, String> {
+        self.keys()
+            .filter_map(|key| {
+                let theirs = peer.versions(key);
+                let version = self
+                    .versions(key)
+                    .into_iter()
+                    .filter(|version| theirs.contains(version))
+                    .max_by(|a, b| compare_versions(a, b))?;
+                Some((key.clone(), version.to_owned()))
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -150,6 +302,17 @@ impl ElicitationCapability {
     pub fn supports_url(&self) -> bool {
         self.url.is_some()
     }
+
+    /// Check if form responses are validated against the requested schema,
+    /// through `form.schemaValidation` or the deprecated top-level flag.
+    #[allow(deprecated)]
+    pub fn validates_schema(&self) -> bool {
+        self.form
+            .as_ref()
+            .and_then(|form| form.schema_validation)
+            .or(self.schema_validation)
+            .unwrap_or(false)
+    }
 }
 
 ///
@@ -214,6 +377,28 @@ pub struct ServerCapabilities {
     pub tasks: Option<TasksCapability>,
 }
 
+impl ClientCapabilities {
+    /// See [`ExperimentalCapabilitiesExt::get_typed`].
+    pub fn experimental_typed<T: ExperimentalCapability>(
+        &self,
+    ) -> Result<Option<T>, serde_json::Error> {
+        self.experimental
+            .as_ref()
+            .map_or(Ok(None), ExperimentalCapabilitiesExt::get_typed)
+    }
+}
+
+impl ServerCapabilities {
+    /// See [`ExperimentalCapabilitiesExt::get_typed`].
+    pub fn experimental_typed<T: ExperimentalCapability>(
+        &self,
+    ) -> Result<Option<T>, serde_json::Error> {
+        self.experimental
+            .as_ref()
+            .map_or(Ok(None), ExperimentalCapabilitiesExt::get_typed)
+    }
+}
+
 macro_rules! builder {
     ($Target: ident {$($f: ident: $T: ty),* $(,)?}) => {
         paste! {
@@ -408,6 +593,17 @@ impl<const E: bool, const R: bool, const S: bool, const TASKS: bool>
         self
     }
 
+    /// Validate form responses against the requested schema before sending
+    /// them, advertised as `form.schemaValidation`.
+    pub fn enable_elicitation_form_validation(mut self) -> Self {
+        if let Some(c) = self.elicitation.as_mut() {
+            c.form
+                .get_or_insert_with(Default::default)
+                .schema_validation = Some(true);
+        }
+        self
+    }
+
     /// DEPRECATED: Use form.schema_validation instead.
     /// Enable JSON Schema validation for elicitation responses.
     #[deprecated(since = "0.14.0", note = "Use form capability instead")]
@@ -498,4 +694,96 @@ mod test {
             })
         );
     }
+
+    #[test]
+    fn test_typed_experimental() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Refresh {
+            interval_secs: u64,
+        }
+        impl ExperimentalCapability for Refresh {
+            const KEY: &'static str = "x-test/refresh";
+        }
+        #[derive(Serialize, Deserialize)]
+        struct NotAnObject(u64);
+        impl ExperimentalCapability for NotAnObject {
+            const KEY: &'static str = "x-test/scalar";
+        }
+
+        let mut experimental = ExperimentalCapabilities::new();
+        experimental
+            .insert_typed(&Refresh { interval_secs: 30 })
+            .unwrap();
+        assert!(experimental.insert_typed(&NotAnObject(1)).is_err());
+        assert!(!experimental.contains_typed::<NotAnObject>());
+
+        let caps = ServerCapabilities::builder()
+            .enable_experimental_with(experimental)
+            .build();
+        let json = serde_json::to_value(&caps).unwrap();
+        assert_eq!(
+            json["experimental"]["x-test/refresh"],
+            serde_json::json!({ "intervalSecs": 30 })
+        );
+        let caps: ServerCapabilities = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            caps.experimental_typed::<Refresh>().unwrap(),
+            Some(Refresh { interval_secs: 30 })
+        );
+        assert_eq!(
+            ClientCapabilities::default()
+                .experimental_typed::<Refresh>()
+                .unwrap(),
+            None
+        );
+
+        let mut malformed = ExperimentalCapabilities::new();
+        malformed.insert(
+            Refresh::KEY.to_owned(),
+            serde_json::json!({ "intervalSecs": "soon" })
+                .as_object()
+                .unwrap()
+                .clone(),
+        );
+        assert!(malformed.get_typed::<Refresh>().is_err());
+        assert!(malformed.remove_typed::<Refresh>().is_some());
+        assert!(malformed.is_empty());
+    }
+
+    #[test]
+    fn test_negotiate_versions() {
+        let mut ours = ExperimentalCapabilities::new();
+        ours.insert_versioned("x-test/feed", ["1.9", "1.10", "2.0"]);
+        ours.insert_versioned("x-test/sync", ["1"]);
+        ours.insert_versioned("x-test/only-ours", ["1"]);
+        ours.insert("x-test/unversioned".to_owned(), JsonObject::new());
+        let mut theirs = ExperimentalCapabilities::new();
+        theirs.insert_versioned("x-test/feed", ["1.10", "1.9", "3.0"]);
+        theirs.insert_versioned("x-test/sync", ["2"]);
+        theirs.insert("x-test/unversioned".to_owned(), JsonObject::new());
+
+        assert_eq!(ours.versions("x-test/feed"), ["1.9", "1.10", "2.0"]);
+        assert!(ours.versions("x-test/unversioned").is_empty());
+        let negotiated = ours.negotiate_versions(&theirs);
+        assert_eq!(
+            negotiated,
+            BTreeMap::from([("x-test/feed".to_owned(), "1.10".to_owned())])
+        );
+        assert_eq!(theirs.negotiate_versions(&ours), negotiated);
+
+        let mut refresh = ExperimentalCapabilities::new();
+        refresh.insert(
+            "x-test/refresh".to_owned(),
+            serde_json::json!({ "intervalSecs": 30 })
+                .as_object()
+                .unwrap()
+                .clone(),
+        );
+        refresh.insert_versioned("x-test/refresh", ["1"]);
+        assert_eq!(
+            serde_json::to_value(&refresh).unwrap(),
+            serde_json::json!({ "x-test/refresh": { "intervalSecs": 30, "versions": ["1"] } })
+        );
+    }
 }
@@ -6,6 +6,18 @@ use serde_json::json;
 
 use super::{AnnotateAble, Annotated, resource::ResourceContents};
 
+pub mod fmt;
+mod preferences;
+mod table;
+pub use preferences::{ContentPreferences, Representations};
+pub use table::{Table, TableFormat};
+#[cfg(feature = "image")]
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+mod image;
+#[cfg(feature = "image")]
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+pub use image::ImageContentError;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -115,6 +127,8 @@ impl RawContent {
                 mime_type: Some("text".to_string()),
                 text: content.into(),
                 meta: None,
+                size: None,
+                last_modified: None,
             },
         })
     }
@@ -209,6 +223,85 @@ impl IntoContents for () {
     }
 }
 
+impl IntoContents for serde_json::Value {
+    fn into_contents(self) -> Vec<Content> {
+        vec![Content::text(self.to_string())]
+    }
+}
+
+/// Binary data with a mime type.
+///
+/// Images and audio become [`ImageContent`] and [`AudioContent`], anything
+/// else an embedded blob resource.
+#[cfg(feature = "base64")]
+#[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blob {
+    pub data: Vec<u8>,
+    pub mime_type: String,
+    /// URI of the embedded resource, [`Blob::DEFAULT_URI`] if not set.
+    pub uri: Option<String>,
+}
+
+#[cfg(feature = "base64")]
+impl Blob {
+    pub const DEFAULT_URI: &str = "blob:unnamed";
+
+    pub fn new(data: impl Into<Vec<u8>>, mime_type: impl Into<String>) -> Self {
+        Self {
+            data: data.into(),
+            mime_type: mime_type.into(),
+            uri: None,
+        }
+    }
+
+    pub fn with_uri(mut self, uri: impl Into<String>) -> Self {
+        self.uri = Some(uri.into());
+        self
+    }
+}
+
+#[cfg(feature = "base64")]
+impl IntoContents for Blob {
+    fn into_contents(self) -> Vec<Content> {
+        use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+        let data = BASE64_STANDARD.encode(&self.data);
+        let content = if self.mime_type.starts_with("image/") {
+            RawContent::image(data, self.mime_type)
+        } else if self.mime_type.starts_with("audio/") {
+            RawContent::Audio(RawAudioContent {
+                data,
+                mime_type: self.mime_type,
+            })
+        } else {
+            RawContent::resource(ResourceContents::BlobResourceContents {
+                uri: self.uri.unwrap_or_else(|| Self::DEFAULT_URI.to_owned()),
+                mime_type: Some(self.mime_type),
+                blob: data,
+                meta: None,
+                size: None,
+                last_modified: None,
+            })
+        };
+        vec![content.no_annotation()]
+    }
+}
+
+/// Raw bytes, as a [`Blob`]. With the `mime-sniffing` feature the mime type
+/// is detected from the content, otherwise it is
+/// `application/octet-stream`.
+#[cfg(feature = "base64")]
+#[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+impl IntoContents for Vec<u8> {
+    fn into_contents(self) -> Vec<Content> {
+        #[cfg(feature = "mime-sniffing")]
+        let mime_type = super::sniff::mime_from_magic(&self).unwrap_or("application/octet-stream");
+        #[cfg(not(feature = "mime-sniffing"))]
+        let mime_type = "application/octet-stream";
+        Blob::new(self, mime_type).into_contents()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json;
@@ -0,0 +1,303 @@
+//! Markdown helpers for tool output.
+//!
+//! Tables, fenced code blocks and collapsible sections are the shapes LLM
+//! clients read best. Every helper returns [`Markdown`], which a tool can
+//! return as is or combine with [`Markdown::then`].
+//!
+//! ```rust
+//! # use rmcp::model::fmt;
+//! #[derive(serde::Serialize)]
+//! struct Check {
+//!     name: &'static str,
+//!     passed: bool,
+//! }
+//!
+//! let report = fmt::table([Check { name: "lint", passed: true }])
+//!     .then(fmt::details("log", fmt::code_block("text", "all good")));
+//! assert_eq!(
+//!     report.to_string(),
+//!     "| name | passed |\n| --- | --- |\n| lint | true |\n\n\
+//!      <details>\n<summary>log</summary>\n\n```text\nall good\n```\n\n</details>\n"
+//! );
+//! ```
+use serde::{
+    Serialize, Serializer,
+    ser::{self, Impossible},
+};
+use serde_json::Value;
+
+use super::{Content, IntoContents};
+
+/// Markdown text, returned by the helpers in this module.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Markdown(pub String);
+
+impl Markdown {
+    /// Append another block, separated by a blank line.
+    pub fn then(mut self, next: impl std::fmt::Display) -> Self {
+        if !self.0.is_empty() {
+            if !self.0.ends_with('\n') {
+                self.0.push('\n');
+            }
+            self.0.push('\n');
+        }
+        self.0.push_str(&next.to_string());
+        self
+    }
+}
+
+impl std::fmt::Display for Markdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Markdown> for String {
+    fn from(markdown: Markdown) -> Self {
+        markdown.0
+    }
+}
+
+impl IntoContents for Markdown {
+    fn into_contents(self) -> Vec<Content> {
+        vec![Content::text(self.0)]
+    }
+}
+
+/// A Markdown table with a row per item. Structs get one column per field,
+/// in declaration order; other values a single `value` column.
+pub fn table<T: Serialize>(rows: impl IntoIterator<Item = T>) -> Markdown {
+    Markdown(markdown_table(rows).unwrap_or_else(render_failed))
+}
+
+/// A fenced code block. The fence is made longer than any run of backticks
+/// inside `code`, so it can't be closed early.
+pub fn code_block(language: &str, code: &str) -> Markdown {
+    let longest_run = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    let newline = if code.ends_with('\n') { "" } else { "\n" };
+    Markdown(format!("{fence}{language}\n{code}{newline}{fence}\n"))
+}
+
+/// A section that is collapsed behind `summary` until the reader opens it.
+pub fn details(summary: &str, body: impl std::fmt::Display) -> Markdown {
+    let summary = summary
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    let body = body.to_string();
+    let body = body.trim_end_matches('\n');
+    Markdown(format!(
+        "<details>\n<summary>{summary}</summary>\n\n{body}\n\n</details>\n"
+    ))
+}
+
+pub(crate) fn render_failed(error: serde_json::Error) -> String {
+    tracing::warn!(%error, "failed to render content");
+    format!("failed to render content: {error}")
+}
+
+pub(crate) fn markdown_table<T: Serialize>(
+    rows: impl IntoIterator<Item = T>,
+) -> Result<String, serde_json::Error> {
+    let mut columns: Vec<String> = Vec::new();
+    let mut cells = Vec::new();
+    for row in rows {
+        let fields = match serde_json::to_value(&row)? {
+            Value::Object(mut object) => {
+                let order = row.serialize(FieldOrder).unwrap_or_default();
+                let mut fields: Vec<(String, Value)> = order
+                    .into_iter()
+                    .filter_map(|key| object.remove_entry(key))
+                    .collect();
+                fields.extend(object);
+                fields
+            }
+            value => vec![("value".to_owned(), value)],
+        };
+        for (key, _) in &fields {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+        cells.push(fields);
+    }
+
+    let mut table = line(columns.iter().map(|column| escape_cell(column)));
+    table.push_str(&line(columns.iter().map(|_| "---".to_owned())));
+    for fields in cells {
+        table.push_str(&line(columns.iter().map(|column| {
+            fields
+                .iter()
+                .find(|(key, _)| key == column)
+                .map(|(_, value)| cell(value))
+                .unwrap_or_default()
+        })));
+    }
+    Ok(table)
+}
+
+fn line(cells: impl Iterator<Item = String>) -> String {
+    format!("| {} |\n", cells.collect::<Vec<_>>().join(" | "))
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => escape_cell(text),
+        value => escape_cell(&value.to_string()),
+    }
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// Collects the field names of a struct in declaration order, since
+/// `serde_json` objects sort their keys.
+struct FieldOrder;
+
+type Fields = Vec<&'static str>;
+
+fn not_a_struct() -> serde_json::Error {
+    ser::Error::custom("not a struct")
+}
+
+macro_rules! not_a_struct {
+    ($($method:ident($($ty:ty),*);)*) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<Fields, serde_json::Error> {
+                Err(not_a_struct())
+            }
+        )*
+    };
+}
+
+impl Serializer for FieldOrder {
+    type Ok = Fields;
+    type Error = serde_json::Error;
+    type SerializeSeq = Impossible<Fields, serde_json::Error>;
+    type SerializeTuple = Impossible<Fields, serde_json::Error>;
+    type SerializeTupleStruct = Impossible<Fields, serde_json::Error>;
+    type SerializeTupleVariant = Impossible<Fields, serde_json::Error>;
+    type SerializeMap = Impossible<Fields, serde_json::Error>;
+    type SerializeStruct = FieldOrderStruct;
+    type SerializeStructVariant = Impossible<Fields, serde_json::Error>;
+
+    not_a_struct! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Fields, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Fields, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Fields, Self::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(FieldOrderStruct(Vec::with_capacity(len)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(not_a_struct())
+    }
+}
+
+struct FieldOrderStruct(Fields);
+
+impl ser::SerializeStruct for FieldOrderStruct {
+    type Ok = Fields;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        _value: &T,
+    ) -> Result<(), Self::Error> {
+        self.0.push(key);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Fields, Self::Error> {
+        Ok(self.0)
+    }
+}
@@ -0,0 +1,115 @@
+//! Helpers for building and shrinking [`ImageContent`](super::ImageContent).
+use std::{io::Cursor, path::Path};
+
+use ::image::{DynamicImage, ImageFormat, ImageReader, imageops::FilterType};
+use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+use thiserror::Error;
+
+use super::{AnnotateAble, Content, RawContent, RawImageContent};
+
+#[derive(Debug, Error)]
+pub enum ImageContentError {
+    #[error("failed to read image: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid image: {0}")]
+    Image(#[from] ::image::ImageError),
+    #[error("invalid base64 image data: {0}")]
+    Base64(#[from] base64::DecodeError),
+}
+
+impl RawImageContent {
+    /// Wrap encoded image bytes, detecting the mime type from their content.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageContentError> {
+        let format = ::image::guess_format(bytes)?;
+        Ok(Self {
+            data: BASE64_STANDARD.encode(bytes),
+            mime_type: format.to_mime_type().to_owned(),
+            meta: None,
+        })
+    }
+
+    /// Read an image file as is.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ImageContentError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Encode `image` as PNG.
+    pub fn from_dynamic_image(image: &DynamicImage) -> Result<Self, ImageContentError> {
+        encode(image, ImageFormat::Png)
+    }
+
+    /// The raw image bytes.
+    pub fn decode_data(&self) -> Result<Vec<u8>, ImageContentError> {
+        Ok(BASE64_STANDARD.decode(&self.data)?)
+    }
+
+    /// Size of the raw image bytes, computed from the base64 data.
+    pub fn byte_len(&self) -> usize {
+        let padding = self.data.bytes().rev().take_while(|b| *b == b'=').count();
+        (self.data.len() / 4 * 3).saturating_sub(padding)
+    }
+
+    /// Width and height in pixels, read from the image header.
+    pub fn dimensions(&self) -> Result<(u32, u32), ImageContentError> {
+        dimensions(&self.decode_data()?)
+    }
+
+    /// Shrink the image so neither side exceeds `max_dimension` pixels,
+    /// keeping its aspect ratio.
+    ///
+    /// JPEG images stay JPEG, anything else is re-encoded as PNG. Images that
+    /// already fit are returned unchanged.
+    pub fn downscale(&self, max_dimension: u32) -> Result<Self, ImageContentError> {
+        let bytes = self.decode_data()?;
+        let (width, height) = dimensions(&bytes)?;
+        if width <= max_dimension && height <= max_dimension {
+            return Ok(self.clone());
+        }
+        let reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+        let format = match reader.format() {
+            Some(ImageFormat::Jpeg) => ImageFormat::Jpeg,
+            _ => ImageFormat::Png,
+        };
+        let image = reader
+            .decode()?
+            .resize(max_dimension, max_dimension, FilterType::Triangle);
+        Ok(Self {
+            meta: self.meta.clone(),
+            ..encode(&image, format)?
+        })
+    }
+}
+
+fn dimensions(bytes: &[u8]) -> Result<(u32, u32), ImageContentError> {
+    Ok(ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_dimensions()?)
+}
+
+fn encode(image: &DynamicImage, format: ImageFormat) -> Result<RawImageContent, ImageContentError> {
+    let mut bytes = Cursor::new(Vec::new());
+    match format {
+        // JPEG has no alpha channel
+        ImageFormat::Jpeg => {
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_to(&mut bytes, format)?
+        }
+        _ => image.write_to(&mut bytes, format)?,
+    }
+    Ok(RawImageContent {
+        data: BASE64_STANDARD.encode(bytes.into_inner()),
+        mime_type: format.to_mime_type().to_owned(),
+        meta: None,
+    })
+}
+
+impl Content {
+    /// Image content read from a file, see [`RawImageContent::from_path`].
+    pub fn image_from_path(path: impl AsRef<Path>) -> Result<Self, ImageContentError> {
+        Ok(RawContent::Image(RawImageContent::from_path(path)?).no_annotation())
+    }
+
+    /// PNG image content, see [`RawImageContent::from_dynamic_image`].
+    pub fn image_from_dynamic_image(image: &DynamicImage) -> Result<Self, ImageContentError> {
+        Ok(RawContent::Image(RawImageContent::from_dynamic_image(image)?).no_annotation())
+    }
+}
@@ -0,0 +1,149 @@
+//! Preferred content types of tool results, an experimental extension.
+//!
+//! A client advertising [`ContentPreferences`] lists the media types it
+//! renders, most preferred first: `text/markdown` before `text/plain` if it
+//! prefers Markdown, `image/*` if it can show images. A tool that can answer
+//! in several forms offers them as [`Representations`] and returns the one
+//! the client prefers. Without preferences, or without a match, the first
+//! representation offered goes out, so clients that don't know the extension
+//! see no change.
+//!
+//! ```rust
+//! # use rmcp::model::{Content, ContentPreferences, IntoContents, Representations};
+//! let preferences = ContentPreferences::new(["text/markdown", "text/plain"]);
+//! let representations = Representations::new()
+//!     .with("text/plain", "build passed".to_owned())
+//!     .with("text/markdown", "**build passed**".to_owned());
+//! let contents = representations.clone().pick(Some(&preferences));
+//! assert_eq!(contents[0].as_text().unwrap().text, "**build passed**");
+//! // the fallback
+//! let contents = representations.into_contents();
+//! assert_eq!(contents[0].as_text().unwrap().text, "build passed");
+//! ```
+use serde::{Deserialize, Serialize};
+
+use super::{Content, IntoContents};
+use crate::model::ExperimentalCapability;
+
+/// Advertised by clients that prefer some content types in tool results.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentPreferences {
+    /// Media types, most preferred first, with `type/*` and `*/*` wildcards.
+    #[serde(default)]
+    pub accept: Vec<String>,
+}
+
+impl ExperimentalCapability for ContentPreferences {
+    const KEY: &'static str = "x-rmcp/content-preferences";
+}
+
+impl ContentPreferences {
+    pub fn new<S: Into<String>>(accept: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            accept: accept.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Position of the first preference matching `mime_type`, lower is
+    /// better.
+    pub fn rank(&self, mime_type: &str) -> Option<usize> {
+        self.accept
+            .iter()
+            .position(|pattern| media_type_matches(pattern, mime_type))
+    }
+
+    pub fn accepts(&self, mime_type: &str) -> bool {
+        self.rank(mime_type).is_some()
+    }
+
+    /// The offered media type the client prefers, the first offered on a
+    /// tie, `None` if it accepts none of them.
+    pub fn choose<'a>(&self, offered: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+        offered
+            .into_iter()
+            .filter_map(|mime_type| Some((self.rank(mime_type)?, mime_type)))
+            .min_by_key(|(rank, _)| *rank)
+            .map(|(_, mime_type)| mime_type)
+    }
+}
+
+/// Whether `pattern`, maybe a wildcard, covers `mime_type`, ignoring case
+/// and parameters.
+fn media_type_matches(pattern: &str, mime_type: &str) -> bool {
+    let essence = |media_type: &str| {
+        let media_type = media_type.split(';').next().unwrap_or_default().trim();
+        let (kind, subtype) = media_type.split_once('/').unwrap_or((media_type, ""));
+        (kind.to_ascii_lowercase(), subtype.to_ascii_lowercase())
+    };
+    let (pattern_kind, pattern_subtype) = essence(pattern);
+    let (kind, subtype) = essence(mime_type);
+    (pattern_kind == "*" || pattern_kind == kind)
+        && (pattern_subtype == "*" || pattern_subtype == subtype)
+}
+
+/// The same tool result in several media types, for the client to get the
+/// one it prefers, see [`ContentPreferences`].
+#[derive(Debug, Clone, Default)]
+pub struct Representations {
+    offers: Vec<(String, Vec<Content>)>,
+}
+
+impl Representations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Offer `contents` as `mime_type`. The first offer is the fallback.
+    pub fn with(mut self, mime_type: impl Into<String>, contents: impl IntoContents) -> Self {
+        self.offers
+            .push((mime_type.into(), contents.into_contents()));
+        self
+    }
+
+    /// The contents of the representation `preferences` choose, or of the
+    /// first one.
+    pub fn pick(mut self, preferences: Option<&ContentPreferences>) -> Vec<Content> {
+        let chosen = preferences
+            .and_then(|preferences| {
+                preferences.choose(self.offers.iter().map(|(mime_type, _)| mime_type.as_str()))
+            })
+            .and_then(|chosen| {
+                self.offers
+                    .iter()
+                    .position(|(mime_type, _)| mime_type == chosen)
+            })
+            .unwrap_or(0);
+        if self.offers.is_empty() {
+            return Vec::new();
+        }
+        self.offers.swap_remove(chosen).1
+    }
+}
+
+/// The fallback, the first representation.
+impl IntoContents for Representations {
+    fn into_contents(self) -> Vec<Content> {
+        self.pick(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose() {
+        let preferences = ContentPreferences::new(["text/markdown", "image/*", "text/plain"]);
+        assert_eq!(
+            preferences.choose(["text/plain", "image/png"]),
+            Some("image/png")
+        );
+        assert_eq!(
+            preferences.choose(["text/plain", "text/Markdown; charset=utf-8"]),
+            Some("text/Markdown; charset=utf-8")
+        );
+        assert_eq!(preferences.choose(["application/json"]), None);
+        assert!(ContentPreferences::new(["*/*"]).accepts("application/json"));
+        assert_eq!(Representations::new().pick(Some(&preferences)), Vec::new());
+    }
+}
@@ -0,0 +1,64 @@
+//! Tabular tool output, see [`Table`].
+use serde::Serialize;
+
+use super::{
+    Content, IntoContents,
+    fmt::{markdown_table, render_failed},
+};
+
+/// How a [`Table`] is rendered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TableFormat {
+    /// A Markdown table with one column per field, in declaration order.
+    #[default]
+    Markdown,
+    /// The rows as a JSON array.
+    Json,
+}
+
+/// Rows of serializable values, returned from a tool as a single text
+/// content block.
+///
+/// ```rust
+/// # use rmcp::model::{IntoContents, Table};
+/// #[derive(serde::Serialize)]
+/// struct Build {
+///     branch: &'static str,
+///     passed: bool,
+/// }
+///
+/// let contents = Table::new(vec![Build { branch: "main", passed: true }]).into_contents();
+/// assert_eq!(
+///     contents[0].as_text().unwrap().text,
+///     "| branch | passed |\n| --- | --- |\n| main | true |\n"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Table<T> {
+    pub rows: Vec<T>,
+    pub format: TableFormat,
+}
+
+impl<T> Table<T> {
+    pub fn new(rows: Vec<T>) -> Self {
+        Self {
+            rows,
+            format: TableFormat::default(),
+        }
+    }
+
+    pub fn with_format(mut self, format: TableFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl<T: Serialize> IntoContents for Table<T> {
+    fn into_contents(self) -> Vec<Content> {
+        let rendered = match self.format {
+            TableFormat::Markdown => markdown_table(&self.rows),
+            TableFormat::Json => serde_json::to_string(&self.rows),
+        };
+        vec![Content::text(rendered.unwrap_or_else(render_failed))]
+    }
+}
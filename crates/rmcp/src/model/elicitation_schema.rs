@@ -1047,7 +1047,7 @@ impl ElicitationSchema {
     where
         T: schemars::JsonSchema,
     {
-        use crate::schemars::generate::SchemaSettings;
+        use schemars::generate::SchemaSettings;
 
         let mut settings = SchemaSettings::draft07();
         settings.transforms = vec![Box::new(schemars::transform::AddNullable::default())];
@@ -1490,6 +1490,231 @@ impl ElicitationSchemaBuilder {
     }
 }
 
+// =============================================================================
+// RESPONSE VALIDATION
+// =============================================================================
+
+/// A field of an elicitation response that doesn't match the requested schema.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitationFieldError {
+    /// The property name, empty when the response as a whole is wrong
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ElicitationFieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.field.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.field, self.message)
+        }
+    }
+}
+
+/// Errors found validating an elicitation response, one per offending field.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("elicitation response doesn't match the requested schema: {}", display_field_errors(.errors))]
+pub struct ElicitationValidationError {
+    pub errors: Vec<ElicitationFieldError>,
+}
+
+fn display_field_errors(errors: &[ElicitationFieldError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl ElicitationSchema {
+    /// Check a response's content against this schema.
+    ///
+    /// Unknown properties are allowed, as in JSON Schema. All offending
+    /// fields are reported, not only the first one.
+    pub fn validate(&self, content: &serde_json::Value) -> Result<(), ElicitationValidationError> {
+        let Some(object) = content.as_object() else {
+            return Err(ElicitationValidationError {
+                errors: vec![ElicitationFieldError {
+                    field: String::new(),
+                    message: "expected an object".to_owned(),
+                }],
+            });
+        };
+        let mut errors = Vec::new();
+        for field in self.required.iter().flatten() {
+            if !object.contains_key(field) {
+                errors.push(ElicitationFieldError {
+                    field: field.clone(),
+                    message: "is required".to_owned(),
+                });
+            }
+        }
+        for (field, schema) in &self.properties {
+            let Some(value) = object.get(field) else {
+                continue;
+            };
+            if let Err(message) = schema.validate(value) {
+                errors.push(ElicitationFieldError {
+                    field: field.clone(),
+                    message,
+                });
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ElicitationValidationError { errors })
+        }
+    }
+}
+
+impl PrimitiveSchema {
+    /// Check a single property value, describing what is wrong with it.
+    pub fn validate(&self, value: &serde_json::Value) -> Result<(), String> {
+        match self {
+            PrimitiveSchema::Enum(schema) => schema.validate(value),
+            PrimitiveSchema::String(schema) => schema.validate(value),
+            PrimitiveSchema::Number(schema) => {
+                let number = value.as_f64().ok_or("expected a number")?;
+                check_range(number, schema.minimum, schema.maximum)
+            }
+            PrimitiveSchema::Integer(schema) => {
+                let integer = value.as_i64().ok_or("expected an integer")?;
+                check_range(integer, schema.minimum, schema.maximum)
+            }
+            PrimitiveSchema::Boolean(_) => value
+                .is_boolean()
+                .then_some(())
+                .ok_or_else(|| "expected a boolean".to_owned()),
+        }
+    }
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(
+    value: T,
+    minimum: Option<T>,
+    maximum: Option<T>,
+) -> Result<(), String> {
+    if let Some(minimum) = minimum.filter(|minimum| value < *minimum) {
+        return Err(format!("must be at least {minimum}"));
+    }
+    if let Some(maximum) = maximum.filter(|maximum| value > *maximum) {
+        return Err(format!("must be at most {maximum}"));
+    }
+    Ok(())
+}
+
+impl StringSchema {
+    fn validate(&self, value: &serde_json::Value) -> Result<(), String> {
+        let string = value.as_str().ok_or("expected a string")?;
+        let length = string.chars().count() as u32;
+        if let Some(min_length) = self.min_length.filter(|min_length| length < *min_length) {
+            return Err(format!("must be at least {min_length} characters long"));
+        }
+        if let Some(max_length) = self.max_length.filter(|max_length| length > *max_length) {
+            return Err(format!("must be at most {max_length} characters long"));
+        }
+        match self.format {
+            Some(StringFormat::Email) if !is_email(string) => {
+                Err("must be an email address".to_owned())
+            }
+            Some(StringFormat::Uri) if !is_uri(string) => Err("must be a URI".to_owned()),
+            Some(StringFormat::Date)
+                if chrono::NaiveDate::parse_from_str(string, "%Y-%m-%d").is_err() =>
+            {
+                Err("must be a date (YYYY-MM-DD)".to_owned())
+            }
+            Some(StringFormat::DateTime)
+                if chrono::DateTime::parse_from_rfc3339(string).is_err() =>
+            {
+                Err("must be a date-time (RFC 3339)".to_owned())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A loose check, the kind form inputs do: something@domain without spaces.
+fn is_email(string: &str) -> bool {
+    match string.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.is_empty()
+                && !domain.contains('@')
+                && !string.contains(char::is_whitespace)
+                && domain.split('.').all(|label| !label.is_empty())
+        }
+        None => false,
+    }
+}
+
+/// An RFC 3986 scheme followed by `:` and no whitespace.
+fn is_uri(string: &str) -> bool {
+    let Some((scheme, _)) = string.split_once(':') else {
+        return false;
+    };
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !string.contains(char::is_whitespace)
+}
+
+impl EnumSchema {
+    fn validate(&self, value: &serde_json::Value) -> Result<(), String> {
+        let (options, min_items, max_items) = match self {
+            EnumSchema::Single(SingleSelectEnumSchema::Untitled(schema)) => {
+                return check_option(value, schema.enum_.iter());
+            }
+            EnumSchema::Single(SingleSelectEnumSchema::Titled(schema)) => {
+                return check_option(value, schema.one_of.iter().map(|option| &option.const_));
+            }
+            EnumSchema::Legacy(schema) => return check_option(value, schema.enum_.iter()),
+            EnumSchema::Multi(MultiSelectEnumSchema::Untitled(schema)) => (
+                schema.items.enum_.iter().collect::<Vec<_>>(),
+                schema.min_items,
+                schema.max_items,
+            ),
+            EnumSchema::Multi(MultiSelectEnumSchema::Titled(schema)) => (
+                schema
+                    .items
+                    .any_of
+                    .iter()
+                    .map(|option| &option.const_)
+                    .collect(),
+                schema.min_items,
+                schema.max_items,
+            ),
+        };
+        let selected = value.as_array().ok_or("expected an array")?;
+        for item in selected {
+            check_option(item, options.iter().copied())?;
+        }
+        let count = selected.len() as u64;
+        if let Some(min_items) = min_items.filter(|min_items| count < *min_items) {
+            return Err(format!("must have at least {min_items} selected"));
+        }
+        if let Some(max_items) = max_items.filter(|max_items| count > *max_items) {
+            return Err(format!("must have at most {max_items} selected"));
+        }
+        Ok(())
+    }
+}
+
+fn check_option<'a>(
+    value: &serde_json::Value,
+    mut options: impl Iterator<Item = &'a String>,
+) -> Result<(), String> {
+    let selected = value.as_str().ok_or("expected a string")?;
+    if options.any(|option| option == selected) {
+        Ok(())
+    } else {
+        Err(format!("{selected:?} is not one of the allowed options"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::anyhow;
@@ -2007,4 +2232,83 @@ mod tests {
             Ok(())
         }
     }
+
+    #[test]
+    fn test_validate_response() {
+        let schema = ElicitationSchema::builder()
+            .required_email("email")
+            .required_integer("age", 0, 150)
+            .optional_string_with("website", |s| s.format(StringFormat::Uri))
+            .optional_string_with("birthday", |s| s.format(StringFormat::Date))
+            .optional_string_with("nickname", |s| s.length(2, 4))
+            .optional_bool("newsletter", false)
+            .optional_enum_schema(
+                "country",
+                EnumSchema::builder(vec!["US".into(), "UK".into()]).build(),
+            )
+            .optional_enum_schema(
+                "colors",
+                EnumSchema::builder(vec!["Red".into(), "Green".into()])
+                    .multiselect()
+                    .min_items(1)
+                    .unwrap()
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
+        let valid = json!({
+            "email": "ada@example.com",
+            "age": 36,
+            "website": "https://example.com",
+            "birthday": "1815-12-10",
+            "nickname": "ada",
+            "newsletter": true,
+            "country": "UK",
+            "colors": ["Red", "Green"],
+            "extra": "allowed",
+        });
+        assert_eq!(schema.validate(&valid), Ok(()));
+
+        let invalid = json!({
+            "email": "ada@",
+            "age": 36.5,
+            "website": "example.com",
+            "birthday": "10/12/1815",
+            "nickname": "a",
+            "newsletter": "yes",
+            "country": "FR",
+            "colors": [],
+        });
+        let errors = schema.validate(&invalid).unwrap_err().errors;
+        let fields = errors
+            .iter()
+            .map(|error| error.field.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                "age",
+                "birthday",
+                "colors",
+                "country",
+                "email",
+                "newsletter",
+                "nickname",
+                "website"
+            ]
+        );
+        assert_eq!(errors[2].message, "must have at least 1 selected");
+        assert_eq!(
+            errors[3].message,
+            "\"FR\" is not one of the allowed options"
+        );
+
+        let errors = schema.validate(&json!({ "age": 151 })).unwrap_err();
+        assert_eq!(
+            errors.to_string(),
+            "elicitation response doesn't match the requested schema: email: is required; age: must be at most 150"
+        );
+        assert!(schema.validate(&json!([])).is_err());
+    }
 }
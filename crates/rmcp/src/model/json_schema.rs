@@ -0,0 +1,40 @@
+//! JSON Schema export for the protocol messages.
+//!
+//! The schemas are generated from the Rust definitions (draft-07), so tooling
+//! in other languages can derive its types and validators from the same source
+//! of truth. Up-to-date copies are kept in `tests/test_message_schema/`.
+use schemars::{JsonSchema, Schema, generate::SchemaSettings};
+
+use super::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+
+/// Any message that can travel over an MCP connection, in either direction.
+#[derive(JsonSchema)]
+#[schemars(rename = "JsonRpcMessage")]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum AnyJsonRpcMessage {
+    Client(ClientJsonRpcMessage),
+    Server(ServerJsonRpcMessage),
+}
+
+fn root_schema_for<T: JsonSchema>() -> Schema {
+    SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+/// JSON Schema of every message a client may send to a server.
+pub fn client_message_schema() -> Schema {
+    root_schema_for::<ClientJsonRpcMessage>()
+}
+
+/// JSON Schema of every message a server may send to a client.
+pub fn server_message_schema() -> Schema {
+    root_schema_for::<ServerJsonRpcMessage>()
+}
+
+/// JSON Schema covering both directions, with all protocol types collected
+/// under `definitions`.
+pub fn protocol_schema() -> Schema {
+    root_schema_for::<AnyJsonRpcMessage>()
+}
@@ -0,0 +1,208 @@
+//! Forgiving deserialization of dates and times in tool arguments.
+//!
+//! Models rarely produce exactly RFC 3339. The `#[serde(with = ...)]` modules
+//! here accept, besides RFC 3339:
+//!
+//! - RFC 2822 (`Tue, 1 Jul 2025 10:52:37 +0200`);
+//! - date and time without an offset, separated by `T` or a space, taken as
+//!   UTC (`2025-07-01 08:52:37`);
+//! - a bare date, taken as midnight UTC (`2025-07-01`);
+//! - a Unix timestamp in seconds, as a JSON number only, so that a string
+//!   like `"2025"` is rejected rather than read as a moment in 1970.
+//!
+//! Values are always written back as RFC 3339 in UTC. The JSON schema should
+//! still say `{"type": "string", "format": "date-time"}`, so models keep being
+//! asked for the canonical form. schemars reads `#[serde(with = ...)]` as a
+//! type, so on parameter structs either use `deserialize_with` for
+//! `chrono::DateTime` fields, which keeps chrono's own schema, or point
+//! schemars at [`DateTimeSchema`], as needed for `time::OffsetDateTime`
+//! (feature `time`).
+//!
+//! `uuid::Uuid` needs no help: with the `uuid` feature it is (de)serializable,
+//! its schema carries `format: uuid`, and it already parses the hyphenated,
+//! simple, braced and URN forms.
+//!
+//! ```rust
+//! # use chrono::{DateTime, Utc};
+//! # use rmcp::model::lenient;
+//! #[derive(serde::Deserialize)]
+//! struct Reminder {
+//!     #[serde(deserialize_with = "lenient::date_time::deserialize")]
+//!     at: DateTime<Utc>,
+//!     #[serde(default, deserialize_with = "lenient::date_time::option::deserialize")]
+//!     until: Option<DateTime<Utc>>,
+//! }
+//!
+//! let reminder: Reminder = serde_json::from_str(r#"{"at": "2025-07-01 08:30"}"#).unwrap();
+//! assert_eq!(reminder.at.to_rfc3339(), "2025-07-01T08:30:00+00:00");
+//! assert!(reminder.until.is_none());
+//! ```
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+const NAIVE_DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Parse a date and time in any of the textual forms listed in the
+/// [module docs](self).
+pub fn parse_date_time(input: &str) -> Option<DateTime<Utc>> {
+    let input = input.trim();
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(input)
+        .or_else(|_| DateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S%.f%:z"))
+        .or_else(|_| DateTime::parse_from_rfc2822(input))
+    {
+        return Some(date_time.to_utc());
+    }
+    if let Some(date_time) = NAIVE_DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+    {
+        return Some(date_time.and_utc());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return date
+            .and_hms_opt(0, 0, 0)
+            .map(|date_time| date_time.and_utc());
+    }
+    None
+}
+
+fn from_timestamp(seconds: f64) -> Option<DateTime<Utc>> {
+    if !seconds.is_finite() {
+        return None;
+    }
+    let whole = seconds.floor();
+    let nanos = ((seconds - whole) * 1e9).round() as u32;
+    DateTime::from_timestamp(whole as i64, nanos.min(999_999_999))
+}
+
+struct DateTimeVisitor;
+
+impl serde::de::Visitor<'_> for DateTimeVisitor {
+    type Value = DateTime<Utc>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a date and time, a date or a Unix timestamp")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        parse_date_time(value)
+            .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(value), &self))
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        DateTime::from_timestamp(value, 0)
+            .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        i64::try_from(value)
+            .ok()
+            .and_then(|value| DateTime::from_timestamp(value, 0))
+            .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Unsigned(value), &self))
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        from_timestamp(value)
+            .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Float(value), &self))
+    }
+}
+
+/// `#[serde(with = ...)]` for `chrono::DateTime<Utc>`.
+pub mod date_time {
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        deserializer.deserialize_any(super::DateTimeVisitor)
+    }
+
+    /// The same for `Option<DateTime<Utc>>`; combine with `#[serde(default)]`.
+    pub mod option {
+        use chrono::{DateTime, Utc};
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &Option<DateTime<Utc>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => super::serialize(value, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<DateTime<Utc>>, D::Error> {
+            #[derive(Deserialize)]
+            struct Lenient(#[serde(with = "super")] DateTime<Utc>);
+            Ok(Option::<Lenient>::deserialize(deserializer)?.map(|Lenient(value)| value))
+        }
+    }
+}
+
+/// `#[serde(with = ...)]` for `time::OffsetDateTime`. The offset of the input
+/// is not kept, values are converted to UTC.
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+pub mod offset_date_time {
+    use chrono::DateTime;
+    use serde::{Deserializer, Serializer, de::Error as _, ser::Error as _};
+    use time::OffsetDateTime;
+
+    pub fn serialize<S: Serializer>(
+        value: &OffsetDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let value = DateTime::from_timestamp(value.unix_timestamp(), value.nanosecond())
+            .ok_or_else(|| S::Error::custom("date out of range"))?;
+        super::date_time::serialize(&value, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<OffsetDateTime, D::Error> {
+        let value = super::date_time::deserialize(deserializer)?;
+        value
+            .timestamp_nanos_opt()
+            .and_then(|nanos| OffsetDateTime::from_unix_timestamp_nanos(nanos.into()).ok())
+            .ok_or_else(|| D::Error::custom("date out of range"))
+    }
+}
+
+/// Schema of an RFC 3339 date and time, for fields whose type has no
+/// `JsonSchema` impl: `#[schemars(with = "lenient::DateTimeSchema")]`.
+#[cfg(any(feature = "server", feature = "schemars"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "server", feature = "schemars"))))]
+pub struct DateTimeSchema;
+
+#[cfg(any(feature = "server", feature = "schemars"))]
+impl schemars::JsonSchema for DateTimeSchema {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "DateTime".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "format": "date-time",
+        })
+    }
+}
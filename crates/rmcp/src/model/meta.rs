@@ -189,6 +189,7 @@ variant_extension! {
         ResourceListChangedNotification
         ToolListChangedNotification
         PromptListChangedNotification
+        TaskStatusNotification
         CustomNotification
     }
 }
@@ -197,6 +198,11 @@ variant_extension! {
 #[serde(transparent)]
 pub struct Meta(pub JsonObject);
 const PROGRESS_TOKEN_FIELD: &str = "progressToken";
+/// Remaining time budget of a request, in milliseconds.
+const BUDGET_FIELD: &str = "rmcp/budgetMs";
+/// `_meta` key of the BCP 47 language tag a client wants its answers in,
+/// on `initialize` for the whole session or on a single request.
+pub const LOCALE_META_KEY: &str = "x-rmcp/locale";
 impl Meta {
     pub fn new() -> Self {
         Self(JsonObject::new())
@@ -246,6 +252,32 @@ impl Meta {
         };
     }
 
+    /// How much time the sender is still willing to wait for this request.
+    ///
+    /// The budget is relative rather than a wall-clock deadline, so it keeps
+    /// working when the two peers' clocks disagree.
+    pub fn get_budget(&self) -> Option<std::time::Duration> {
+        self.0
+            .get(BUDGET_FIELD)
+            .and_then(Value::as_u64)
+            .map(std::time::Duration::from_millis)
+    }
+
+    pub fn set_budget(&mut self, budget: std::time::Duration) {
+        let millis = u64::try_from(budget.as_millis()).unwrap_or(u64::MAX);
+        self.0.insert(BUDGET_FIELD.to_string(), Value::from(millis));
+    }
+
+    /// The language tag of [`LOCALE_META_KEY`], e.g. `"fr-CA"`.
+    pub fn locale(&self) -> Option<&str> {
+        self.0.get(LOCALE_META_KEY)?.as_str()
+    }
+
+    pub fn set_locale(&mut self, locale: impl Into<String>) {
+        self.0
+            .insert(LOCALE_META_KEY.to_owned(), Value::String(locale.into()));
+    }
+
     pub fn extend(&mut self, other: Meta) {
         for (k, v) in other.0.into_iter() {
             self.0.insert(k, v);
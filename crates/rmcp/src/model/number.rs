@@ -0,0 +1,219 @@
+//! Keeping numbers intact across peers.
+//!
+//! JSON itself has no limits on numbers, but JavaScript peers parse them as
+//! `f64` and silently round integers beyond [`MAX_SAFE_INTEGER`], and
+//! `serde_json` turns `NaN` and infinities into `null`. The helpers here make
+//! that loss explicit:
+//!
+//! - [`NumberPolicy`] checks or rewrites a JSON value before it is sent, for
+//!   example the structured content of a tool result;
+//! - [`as_string`] and [`non_finite_as_string`] are `#[serde(with = ...)]`
+//!   modules for fields that may hold such numbers, which also accept the
+//!   string form when reading tool arguments.
+//!
+//! ```rust
+//! # use rmcp::model::number;
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Transfer {
+//!     #[serde(with = "number::as_string")]
+//!     amount: u64,
+//!     #[serde(with = "number::non_finite_as_string")]
+//!     rate: f64,
+//! }
+//!
+//! let transfer: Transfer =
+//!     serde_json::from_str(r#"{"amount": "18446744073709551615", "rate": "Infinity"}"#).unwrap();
+//! assert_eq!(transfer.amount, u64::MAX);
+//! assert!(transfer.rate.is_infinite());
+//! ```
+use serde_json::{Number, Value};
+use thiserror::Error;
+
+use super::JsonObject;
+
+/// Largest integer an IEEE 754 double represents exactly, `2^53 - 1`.
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Whether every peer can read `number` without losing precision.
+pub fn is_safe_number(number: &Number) -> bool {
+    if let Some(n) = number.as_u64() {
+        n <= MAX_SAFE_INTEGER
+    } else if let Some(n) = number.as_i64() {
+        n.unsigned_abs() <= MAX_SAFE_INTEGER
+    } else {
+        true
+    }
+}
+
+/// What to do with integers outside the safe range.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberPolicy {
+    /// Send them unchanged.
+    #[default]
+    Allow,
+    /// Replace them with their decimal string.
+    EncodeAsString,
+    /// Fail with an [`UnsafeNumberError`].
+    Reject,
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+#[error("integer {value} at {path:?} is outside the range JSON peers can represent exactly")]
+pub struct UnsafeNumberError {
+    /// JSON pointer to the offending number.
+    pub path: String,
+    pub value: String,
+}
+
+impl NumberPolicy {
+    /// Apply the policy to every number in `value`.
+    pub fn apply(&self, value: &mut Value) -> Result<(), UnsafeNumberError> {
+        if *self == NumberPolicy::Allow {
+            return Ok(());
+        }
+        self.apply_at(value, &mut String::new())
+    }
+
+    fn apply_at(&self, value: &mut Value, path: &mut String) -> Result<(), UnsafeNumberError> {
+        match value {
+            Value::Number(number) if !is_safe_number(number) => match self {
+                NumberPolicy::Allow => {}
+                NumberPolicy::EncodeAsString => *value = Value::String(number.to_string()),
+                NumberPolicy::Reject => {
+                    return Err(UnsafeNumberError {
+                        path: path.clone(),
+                        value: number.to_string(),
+                    });
+                }
+            },
+            Value::Array(values) => {
+                for (index, value) in values.iter_mut().enumerate() {
+                    let len = path.len();
+                    path.push_str(&format!("/{index}"));
+                    self.apply_at(value, path)?;
+                    path.truncate(len);
+                }
+            }
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    let len = path.len();
+                    path.push('/');
+                    path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                    self.apply_at(value, path)?;
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Widen `schema` so every place that accepts an integer or number also
+/// accepts a string, matching values rewritten by
+/// [`NumberPolicy::EncodeAsString`].
+pub fn allow_strings_for_numbers(schema: &mut JsonObject) {
+    match schema.get_mut("type") {
+        Some(Value::String(ty)) if ty == "integer" || ty == "number" => {
+            let ty = Value::String(std::mem::take(ty));
+            schema.insert("type".into(), Value::Array(vec![ty, "string".into()]));
+        }
+        Some(Value::Array(types))
+            if types.iter().any(|ty| ty == "integer" || ty == "number")
+                && !types.iter().any(|ty| ty == "string") =>
+        {
+            types.push("string".into())
+        }
+        _ => {}
+    }
+    for (key, value) in schema.iter_mut() {
+        // these hold instances, not schemas
+        if matches!(key.as_str(), "const" | "default" | "enum" | "examples") {
+            continue;
+        }
+        match value {
+            Value::Object(schema) => allow_strings_for_numbers(schema),
+            Value::Array(values) => {
+                for value in values {
+                    if let Value::Object(schema) = value {
+                        allow_strings_for_numbers(schema);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Serialize a number as its decimal string; deserialize from either a
+/// string or a JSON number.
+///
+/// Meant for `u64`, `i64`, `u128` and `i128` fields that may exceed
+/// [`MAX_SAFE_INTEGER`]. `serde_json` can't represent 128-bit integers as
+/// numbers at all, so those only round-trip through this module.
+pub mod as_string {
+    use std::{fmt::Display, str::FromStr};
+
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        match StringOrNumber::deserialize(deserializer)? {
+            StringOrNumber::String(text) => text.trim().parse().map_err(D::Error::custom),
+            StringOrNumber::Number(number) => number.to_string().parse().map_err(D::Error::custom),
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(serde_json::Number),
+    }
+}
+
+/// Serialize `NaN` and infinities as `"NaN"`, `"Infinity"` and `"-Infinity"`
+/// instead of letting them become `null`; finite values stay numbers.
+/// Deserializes from both forms.
+pub mod non_finite_as_string {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        match *value {
+            value if value.is_nan() => serializer.serialize_str("NaN"),
+            f64::INFINITY => serializer.serialize_str("Infinity"),
+            f64::NEG_INFINITY => serializer.serialize_str("-Infinity"),
+            value => serializer.serialize_f64(value),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        match FloatOrString::deserialize(deserializer)? {
+            FloatOrString::Float(value) => Ok(value),
+            FloatOrString::String(text) => match text.as_str() {
+                "NaN" => Ok(f64::NAN),
+                "Infinity" | "+Infinity" => Ok(f64::INFINITY),
+                "-Infinity" => Ok(f64::NEG_INFINITY),
+                text => text.parse().map_err(D::Error::custom),
+            },
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum FloatOrString {
+        Float(f64),
+        String(String),
+    }
+}
@@ -1,8 +1,11 @@
+#[cfg(feature = "base64")]
 use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "base64")]
+use super::RawImageContent;
 use super::{
-    AnnotateAble, Annotations, Icon, Meta, RawEmbeddedResource, RawImageContent,
+    AnnotateAble, Annotations, Icon, Meta, RawEmbeddedResource,
     content::{EmbeddedResource, ImageContent},
     resource::ResourceContents,
 };
@@ -168,12 +171,16 @@ impl PromptMessage {
                 mime_type,
                 text: t,
                 meta: resource_content_meta,
+                size: None,
+                last_modified: None,
             },
             None => ResourceContents::BlobResourceContents {
                 uri,
                 mime_type,
                 blob: String::new(),
                 meta: resource_content_meta,
+                size: None,
+                last_modified: None,
             },
         };
         Self {
@@ -1,7 +1,17 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::{Annotated, Icon, Meta};
 
+#[cfg(feature = "mime-sniffing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mime-sniffing")))]
+pub mod sniff;
+
+mod conditional;
+mod delta;
+pub use conditional::*;
+pub use delta::*;
+
 /// Represents a resource in the extension with metadata
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -67,6 +77,12 @@ pub enum ResourceContents {
         text: String,
         #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
         meta: Option<Meta>,
+        /// Size of the raw contents in bytes, if known.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        size: Option<u32>,
+        /// When the contents last changed, if known.
+        #[serde(rename = "lastModified", skip_serializing_if = "Option::is_none")]
+        last_modified: Option<DateTime<Utc>>,
     },
     #[serde(rename_all = "camelCase")]
     BlobResourceContents {
@@ -76,9 +92,30 @@ pub enum ResourceContents {
         blob: String,
         #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
         meta: Option<Meta>,
+        /// Size of the raw contents in bytes, if known.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        size: Option<u32>,
+        /// When the contents last changed, if known.
+        #[serde(rename = "lastModified", skip_serializing_if = "Option::is_none")]
+        last_modified: Option<DateTime<Utc>>,
     },
 }
 
+/// Why resource contents couldn't be read the way they were asked for.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ResourceContentsError {
+    #[error("resource has no contents")]
+    Empty,
+    #[error("resource has no text contents")]
+    NotText,
+    #[cfg(feature = "base64")]
+    #[error("invalid base64 blob: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("invalid JSON contents: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
 impl ResourceContents {
     pub fn text(text: impl Into<String>, uri: impl Into<String>) -> Self {
         Self::TextResourceContents {
@@ -86,6 +123,92 @@ impl ResourceContents {
             mime_type: Some("text".into()),
             text: text.into(),
             meta: None,
+            size: None,
+            last_modified: None,
+        }
+    }
+
+    pub fn uri(&self) -> &str {
+        match self {
+            Self::TextResourceContents { uri, .. } | Self::BlobResourceContents { uri, .. } => uri,
+        }
+    }
+
+    pub fn mime_type(&self) -> Option<&str> {
+        match self {
+            Self::TextResourceContents { mime_type, .. }
+            | Self::BlobResourceContents { mime_type, .. } => mime_type.as_deref(),
+        }
+    }
+
+    /// Size of the raw contents in bytes, if the server reported it.
+    pub fn size(&self) -> Option<u32> {
+        match self {
+            Self::TextResourceContents { size, .. } | Self::BlobResourceContents { size, .. } => {
+                *size
+            }
+        }
+    }
+
+    /// When the contents last changed, if the server reported it.
+    pub fn last_modified(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::TextResourceContents { last_modified, .. }
+            | Self::BlobResourceContents { last_modified, .. } => *last_modified,
+        }
+    }
+
+    /// Set the [`size`](Self::size) and [`last_modified`](Self::last_modified)
+    /// metadata.
+    pub fn with_metadata(
+        mut self,
+        new_size: Option<u32>,
+        new_last_modified: Option<DateTime<Utc>>,
+    ) -> Self {
+        match &mut self {
+            Self::TextResourceContents {
+                size,
+                last_modified,
+                ..
+            }
+            | Self::BlobResourceContents {
+                size,
+                last_modified,
+                ..
+            } => {
+                *size = new_size;
+                *last_modified = new_last_modified;
+            }
+        }
+        self
+    }
+
+    /// The text, if these are text contents.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::TextResourceContents { text, .. } => Some(text),
+            Self::BlobResourceContents { .. } => None,
+        }
+    }
+
+    /// The raw bytes: the decoded blob, or the UTF-8 encoded text.
+    #[cfg(feature = "base64")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ResourceContentsError> {
+        use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+        match self {
+            Self::TextResourceContents { text, .. } => Ok(text.clone().into_bytes()),
+            Self::BlobResourceContents { blob, .. } => Ok(BASE64_STANDARD.decode(blob)?),
+        }
+    }
+
+    /// Parse the contents as JSON.
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, ResourceContentsError> {
+        match self {
+            Self::TextResourceContents { text, .. } => Ok(serde_json::from_str(text)?),
+            #[cfg(feature = "base64")]
+            Self::BlobResourceContents { .. } => Ok(serde_json::from_slice(&self.to_bytes()?)?),
+            #[cfg(not(feature = "base64"))]
+            Self::BlobResourceContents { .. } => Err(ResourceContentsError::NotText),
         }
     }
 }
@@ -104,6 +227,26 @@ impl RawResource {
             meta: None,
         }
     }
+
+    pub fn with_size(mut self, size: u32) -> Self {
+        self.size = Some(size);
+        self
+    }
+}
+
+impl Resource {
+    /// When the resource last changed, from its `lastModified` annotation.
+    pub fn last_modified(&self) -> Option<DateTime<Utc>> {
+        self.timestamp()
+    }
+
+    /// Whether the resource may have changed since `since`, e.g. when a
+    /// copy was downloaded. Resources without a modification time always
+    /// may have.
+    pub fn is_modified_since(&self, since: DateTime<Utc>) -> bool {
+        self.last_modified()
+            .is_none_or(|last_modified| last_modified > since)
+    }
 }
 
 #[cfg(test)]
@@ -140,6 +283,8 @@ mod tests {
             mime_type: Some("text/plain".to_string()),
             text: "Hello world".to_string(),
             meta: None,
+            size: None,
+            last_modified: None,
         };
 
         let json = serde_json::to_string(&text_contents).unwrap();
@@ -150,6 +295,85 @@ mod tests {
         assert!(!json.contains("mime_type"));
     }
 
+    #[test]
+    fn test_resource_contents_metadata() {
+        let last_modified = DateTime::parse_from_rfc3339("2025-01-12T15:00:58Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let contents = ResourceContents::text("Hello world", "file:///test.txt")
+            .with_metadata(Some(11), Some(last_modified));
+        let json = serde_json::to_value(&contents).unwrap();
+        assert_eq!(json["size"], 11);
+        assert_eq!(json["lastModified"], "2025-01-12T15:00:58Z");
+
+        let parsed: ResourceContents = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.size(), Some(11));
+        assert_eq!(parsed.last_modified(), Some(last_modified));
+
+        let plain = ResourceContents::text("Hello world", "file:///test.txt");
+        let json = serde_json::to_value(&plain).unwrap();
+        assert!(json.get("size").is_none());
+        assert!(json.get("lastModified").is_none());
+    }
+
+    #[test]
+    fn test_resource_modified_since() {
+        use crate::model::AnnotateAble;
+
+        let downloaded = DateTime::parse_from_rfc3339("2025-01-12T15:00:58Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let unknown = RawResource::new("file:///a.txt", "a.txt").no_annotation();
+        assert!(unknown.is_modified_since(downloaded));
+
+        let older = RawResource::new("file:///a.txt", "a.txt").with_timestamp(downloaded);
+        assert!(!older.is_modified_since(downloaded));
+        assert!(older.is_modified_since(downloaded - chrono::Duration::seconds(1)));
+    }
+
+    #[test]
+    fn test_read_resource_result_accessors() {
+        use crate::model::ReadResourceResult;
+
+        let result = ReadResourceResult {
+            contents: vec![
+                ResourceContents::BlobResourceContents {
+                    uri: "mem://config".to_string(),
+                    mime_type: Some("application/octet-stream".to_string()),
+                    blob: "AAEC".to_string(),
+                    meta: None,
+                    size: None,
+                    last_modified: None,
+                },
+                ResourceContents::TextResourceContents {
+                    uri: "mem://config".to_string(),
+                    mime_type: Some("application/json; charset=utf-8".to_string()),
+                    text: r#"{"retries": 3}"#.to_string(),
+                    meta: None,
+                    size: None,
+                    last_modified: None,
+                },
+            ],
+        };
+        assert_eq!(result.text().unwrap(), r#"{"retries": 3}"#);
+        let config: serde_json::Value = result.json().unwrap();
+        assert_eq!(config["retries"], 3);
+        #[cfg(feature = "base64")]
+        assert_eq!(result.bytes().unwrap(), vec![0, 1, 2]);
+
+        let blob_only = ReadResourceResult {
+            contents: result.contents[..1].to_vec(),
+        };
+        assert!(matches!(
+            blob_only.text(),
+            Err(ResourceContentsError::NotText)
+        ));
+        assert!(blob_only.json::<serde_json::Value>().is_err());
+
+        let empty = ReadResourceResult { contents: vec![] };
+        assert!(matches!(empty.text(), Err(ResourceContentsError::Empty)));
+    }
+
     #[test]
     fn test_resource_template_with_icons() {
         let resource_template = RawResourceTemplate {
@@ -0,0 +1,222 @@
+//! Conditional `resources/read`, an experimental extension.
+//!
+//! A server advertising [`ConditionalReads`] tags the contents it returns with
+//! an entity tag under [`ETAG_META_KEY`]. A client polling the resource sends
+//! the last tag back in the request `_meta`, see [`Meta::set_if_none_match`],
+//! and while the resource hasn't changed the server answers with
+//! [`ReadResourceResult::not_modified`] instead of the whole contents.
+//!
+//! Everything travels in `_meta`, so peers that don't know the extension
+//! ignore it and keep exchanging the full contents.
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::ResourceContents;
+use crate::model::{ExperimentalCapability, Meta, ReadResourceRequestParams, ReadResourceResult};
+
+/// `_meta` key of the entity tag of resource contents.
+pub const ETAG_META_KEY: &str = "x-rmcp/etag";
+/// `_meta` key of the entity tag a client already has, on `resources/read`.
+pub const IF_NONE_MATCH_META_KEY: &str = "x-rmcp/ifNoneMatch";
+/// `_meta` key marking contents that stand for "unchanged since the tag".
+pub const NOT_MODIFIED_META_KEY: &str = "x-rmcp/notModified";
+
+/// Advertised by servers that answer conditional reads.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConditionalReads {}
+
+impl ExperimentalCapability for ConditionalReads {
+    const KEY: &'static str = "x-rmcp/conditional-reads";
+}
+
+impl ResourceContents {
+    pub fn meta(&self) -> Option<&Meta> {
+        match self {
+            Self::TextResourceContents { meta, .. } | Self::BlobResourceContents { meta, .. } => {
+                meta.as_ref()
+            }
+        }
+    }
+
+    pub fn meta_mut(&mut self) -> &mut Option<Meta> {
+        match self {
+            Self::TextResourceContents { meta, .. } | Self::BlobResourceContents { meta, .. } => {
+                meta
+            }
+        }
+    }
+
+    /// The entity tag of this version of the contents, if the server sent
+    /// one.
+    pub fn etag(&self) -> Option<&str> {
+        self.meta()?.get(ETAG_META_KEY)?.as_str()
+    }
+
+    pub fn with_etag(mut self, etag: impl Into<String>) -> Self {
+        self.meta_mut()
+            .get_or_insert_with(Meta::new)
+            .insert(ETAG_META_KEY.to_owned(), Value::String(etag.into()));
+        self
+    }
+
+    fn is_not_modified(&self) -> bool {
+        self.meta()
+            .and_then(|meta| meta.get(NOT_MODIFIED_META_KEY))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+}
+
+impl ReadResourceRequestParams {
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            meta: None,
+            uri: uri.into(),
+        }
+    }
+}
+
+impl Meta {
+    /// The entity tag the client already has, on `resources/read`.
+    pub fn if_none_match(&self) -> Option<&str> {
+        self.get(IF_NONE_MATCH_META_KEY)?.as_str()
+    }
+
+    /// Only ask for the contents if they no longer have the entity tag
+    /// `etag`.
+    pub fn set_if_none_match(&mut self, etag: impl Into<String>) {
+        self.insert(
+            IF_NONE_MATCH_META_KEY.to_owned(),
+            Value::String(etag.into()),
+        );
+    }
+}
+
+impl ReadResourceResult {
+    /// The answer to a conditional read of `uri` whose contents still have
+    /// the entity tag `etag`.
+    pub fn not_modified(uri: impl Into<String>, etag: impl Into<String>) -> Self {
+        let mut meta = Meta::new();
+        meta.insert(NOT_MODIFIED_META_KEY.to_owned(), Value::Bool(true));
+        let contents = ResourceContents::TextResourceContents {
+            uri: uri.into(),
+            mime_type: None,
+            text: String::new(),
+            meta: Some(meta),
+            size: None,
+            last_modified: None,
+        };
+        Self {
+            contents: vec![contents.with_etag(etag)],
+        }
+    }
+
+    /// Whether the server answered that the resource is unchanged, so the
+    /// copy the client has is still current.
+    pub fn is_not_modified(&self) -> bool {
+        matches!(self.contents.as_slice(), [contents] if contents.is_not_modified())
+    }
+
+    /// The entity tag of the first entry.
+    pub fn etag(&self) -> Option<&str> {
+        self.contents.first()?.etag()
+    }
+
+    /// Answer a read of `uri` conditionally, given the
+    /// [`if_none_match`](Meta::if_none_match) tag of the request.
+    ///
+    /// Entries without an entity tag are tagged with a hash of the contents,
+    /// which stays the same for as long as the server runs. If the tag is
+    /// the one the client already has, this becomes
+    /// [`not_modified`](Self::not_modified).
+    pub fn conditional(self, uri: impl Into<String>, if_none_match: Option<&str>) -> Self {
+        let etag = match self.etag() {
+            Some(etag) => etag.to_owned(),
+            None => content_etag(&self.contents),
+        };
+        if if_none_match == Some(etag.as_str()) {
+            return Self::not_modified(uri, etag);
+        }
+        let contents = self
+            .contents
+            .into_iter()
+            .map(|contents| match contents.etag() {
+                Some(_) => contents,
+                None => contents.with_etag(etag.clone()),
+            })
+            .collect();
+        Self { contents }
+    }
+}
+
+fn content_etag(contents: &[ResourceContents]) -> String {
+    let mut hasher = DefaultHasher::new();
+    for contents in contents {
+        contents.uri().hash(&mut hasher);
+        contents.mime_type().hash(&mut hasher);
+        match contents {
+            ResourceContents::TextResourceContents { text, .. } => text.hash(&mut hasher),
+            ResourceContents::BlobResourceContents { blob, .. } => blob.hash(&mut hasher),
+        }
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conditional_read_wire_format() {
+        let mut meta = Meta::new();
+        meta.set_if_none_match("v1");
+        assert_eq!(
+            serde_json::to_value(&meta).unwrap(),
+            serde_json::json!({ "x-rmcp/ifNoneMatch": "v1" })
+        );
+        assert_eq!(meta.if_none_match(), Some("v1"));
+
+        let not_modified = ReadResourceResult::not_modified("mem://log", "v1");
+        let json = serde_json::to_value(&not_modified).unwrap();
+        assert_eq!(json["contents"][0]["_meta"]["x-rmcp/notModified"], true);
+        assert_eq!(json["contents"][0]["_meta"]["x-rmcp/etag"], "v1");
+        let parsed: ReadResourceResult = serde_json::from_value(json).unwrap();
+        assert!(parsed.is_not_modified());
+        assert_eq!(parsed.etag(), Some("v1"));
+    }
+
+    #[test]
+    fn conditional_tags_and_matches() {
+        let read = || ReadResourceResult {
+            contents: vec![ResourceContents::text("line 1", "mem://log")],
+        };
+        let first = read().conditional("mem://log", None);
+        assert!(!first.is_not_modified());
+        let etag = first.etag().expect("tagged").to_owned();
+
+        assert!(
+            read()
+                .conditional("mem://log", Some(&etag))
+                .is_not_modified()
+        );
+
+        let changed = ReadResourceResult {
+            contents: vec![ResourceContents::text("line 1\nline 2", "mem://log")],
+        }
+        .conditional("mem://log", Some(&etag));
+        assert!(!changed.is_not_modified());
+        assert_ne!(changed.etag(), Some(etag.as_str()));
+
+        // a tag chosen by the server wins over the hash
+        let versioned = ReadResourceResult {
+            contents: vec![ResourceContents::text("line 1", "mem://log").with_etag("7")],
+        };
+        assert!(
+            versioned
+                .conditional("mem://log", Some("7"))
+                .is_not_modified()
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Resource updates that carry the change, an experimental extension.
+//!
+//! A client advertising [`ResourceDeltas`] lets the server attach a
+//! [`ResourceDelta`] to `notifications/resources/updated`, under
+//! [`DELTA_META_KEY`] in the notification `_meta`. The delta turns the text
+//! tagged with its `base_etag` (see the conditional reads extension,
+//! [`ConditionalReads`](super::ConditionalReads)) into the text tagged with
+//! its `etag`, so a client mirroring the resource doesn't have to read it
+//! again. A client without the base version reads the resource as usual.
+use serde::{Deserialize, Serialize};
+
+use crate::model::{ExperimentalCapability, Meta};
+
+/// `_meta` key of the [`ResourceDelta`] of a resource update.
+pub const DELTA_META_KEY: &str = "x-rmcp/delta";
+
+/// Advertised by clients that apply resource deltas.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceDeltas {}
+
+impl ExperimentalCapability for ResourceDeltas {
+    const KEY: &'static str = "x-rmcp/resource-deltas";
+}
+
+/// Replace `delete` bytes at byte offset `start` with `insert`.
+///
+/// Offsets count UTF-8 bytes of the text as it is after the previous edits
+/// of the same delta.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    pub start: usize,
+    #[serde(default)]
+    pub delete: usize,
+    #[serde(default)]
+    pub insert: String,
+}
+
+impl TextEdit {
+    /// Apply the edit, or `None` if it doesn't fit `text`.
+    pub fn apply(&self, text: &mut String) -> Option<()> {
+        let end = self.start.checked_add(self.delete)?;
+        if end > text.len() || !text.is_char_boundary(self.start) || !text.is_char_boundary(end) {
+            return None;
+        }
+        text.replace_range(self.start..end, &self.insert);
+        Some(())
+    }
+}
+
+/// How the text of a resource changed from one version to the next.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceDelta {
+    /// Entity tag of the version the edits apply to.
+    pub base_etag: String,
+    /// Entity tag of the version after the edits.
+    pub etag: String,
+    pub edits: Vec<TextEdit>,
+}
+
+impl ResourceDelta {
+    /// The delta from `old` to `new`, as a single edit of the part between
+    /// their common prefix and suffix.
+    pub fn between(
+        base_etag: impl Into<String>,
+        old: &str,
+        etag: impl Into<String>,
+        new: &str,
+    ) -> Self {
+        let prefix = common_len(old.chars(), new.chars());
+        let suffix = common_len(old[prefix..].chars().rev(), new[prefix..].chars().rev());
+        let edits = if old == new {
+            Vec::new()
+        } else {
+            vec![TextEdit {
+                start: prefix,
+                delete: old.len() - prefix - suffix,
+                insert: new[prefix..new.len() - suffix].to_owned(),
+            }]
+        };
+        Self {
+            base_etag: base_etag.into(),
+            etag: etag.into(),
+            edits,
+        }
+    }
+
+    /// Apply the edits to `text`, or `None` if one of them doesn't fit.
+    pub fn apply(&self, text: &str) -> Option<String> {
+        let mut text = text.to_owned();
+        for edit in &self.edits {
+            edit.apply(&mut text)?;
+        }
+        Some(text)
+    }
+}
+
+/// Length in bytes of the run of characters both iterators start with.
+fn common_len(a: impl Iterator<Item = char>, b: impl Iterator<Item = char>) -> usize {
+    a.zip(b)
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum()
+}
+
+impl Meta {
+    /// The delta attached to a resource update.
+    pub fn resource_delta(&self) -> Option<ResourceDelta> {
+        serde_json::from_value(self.get(DELTA_META_KEY)?.clone()).ok()
+    }
+
+    pub fn set_resource_delta(&mut self, delta: &ResourceDelta) {
+        self.insert(
+            DELTA_META_KEY.to_owned(),
+            serde_json::to_value(delta).expect("resource delta serializes"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delta_between_versions() {
+        let cases = [
+            ("line 1\n", "line 1\nline 2\n"),
+            ("hello world", "hello, world"),
+            ("héllo", "hallo"),
+            ("abc", "abc"),
+            ("", "new"),
+            ("old", ""),
+            ("aaa", "aaaa"),
+        ];
+        for (old, new) in cases {
+            let delta = ResourceDelta::between("v1", old, "v2", new);
+            assert_eq!(delta.apply(old).as_deref(), Some(new), "{old:?} -> {new:?}");
+        }
+        assert!(
+            ResourceDelta::between("v1", "same", "v2", "same")
+                .edits
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn edits_out_of_range_fail() {
+        let edit = |start, delete| TextEdit {
+            start,
+            delete,
+            insert: String::new(),
+        };
+        assert_eq!(edit(10, 0).apply(&mut "short".to_owned()), None);
+        assert_eq!(edit(2, 10).apply(&mut "short".to_owned()), None);
+        // inside the two bytes of 'é'
+        assert_eq!(edit(2, 0).apply(&mut "héllo".to_owned()), None);
+    }
+
+    #[test]
+    fn delta_in_meta() {
+        let delta = ResourceDelta::between("v1", "a", "v2", "ab");
+        let mut meta = Meta::new();
+        meta.set_resource_delta(&delta);
+        assert_eq!(
+            serde_json::to_value(&meta).unwrap(),
+            serde_json::json!({
+                "x-rmcp/delta": {
+                    "baseEtag": "v1",
+                    "etag": "v2",
+                    "edits": [{ "start": 1, "delete": 0, "insert": "b" }],
+                }
+            })
+        );
+        assert_eq!(meta.resource_delta(), Some(delta));
+    }
+}
@@ -0,0 +1,174 @@
+//! Mime type and charset detection for [`ResourceContents`].
+use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+use encoding_rs::Encoding;
+
+use super::ResourceContents;
+
+/// Fallback for content that is neither recognizable nor text.
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+const MAGIC: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xFF\xD8\xFF", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"\x1F\x8B", "application/gzip"),
+    (0, b"\0asm", "application/wasm"),
+    (0, b"\x7FELF", "application/x-executable"),
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"fLaC", "audio/flac"),
+    (4, b"ftyp", "video/mp4"),
+];
+
+/// Mime type of a well-known binary format, recognized by its leading bytes.
+pub fn mime_from_magic(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"RIFF") {
+        match bytes.get(8..12) {
+            Some(b"WEBP") => return Some("image/webp"),
+            Some(b"WAVE") => return Some("audio/wav"),
+            _ => {}
+        }
+    }
+    MAGIC
+        .iter()
+        .find_map(|(offset, magic, mime)| bytes.get(*offset..)?.starts_with(magic).then_some(*mime))
+}
+
+/// Mime type registered for the extension of `path`, which may also be a
+/// URI.
+pub fn mime_from_extension(path: &str) -> Option<&'static str> {
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    mime_guess::from_path(path).first_raw()
+}
+
+/// Whether content of this mime type is text.
+pub fn is_text_mime(mime_type: &str) -> bool {
+    let essence = mime_type.split(';').next().unwrap_or_default().trim();
+    essence.starts_with("text/")
+        || essence.ends_with("+json")
+        || essence.ends_with("+xml")
+        || matches!(
+            essence,
+            "application/json"
+                | "application/xml"
+                | "application/javascript"
+                | "application/ecmascript"
+                | "application/toml"
+                | "application/yaml"
+                | "application/x-yaml"
+                | "application/x-sh"
+                | "application/sql"
+                | "application/graphql"
+        )
+}
+
+/// Best guess for the mime type of `bytes` read from `path`.
+///
+/// Magic bytes win over the extension, since they can't lie about binary
+/// content. Unknown content is `text/plain` if it looks like text and
+/// [`OCTET_STREAM`] otherwise.
+pub fn sniff_mime_type(path: &str, bytes: &[u8]) -> &'static str {
+    mime_from_magic(bytes)
+        .or_else(|| mime_from_extension(path))
+        .unwrap_or_else(|| {
+            if looks_like_text(bytes) {
+                "text/plain"
+            } else {
+                OCTET_STREAM
+            }
+        })
+}
+
+fn looks_like_text(bytes: &[u8]) -> bool {
+    Encoding::for_bom(bytes).is_some() || !bytes.iter().take(8192).any(|b| *b == 0)
+}
+
+/// Text decoded by [`decode_text`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText {
+    pub text: String,
+    /// Name of the charset the bytes were decoded from, e.g. `"UTF-8"`.
+    pub encoding: &'static str,
+    /// Whether some bytes were invalid and replaced with U+FFFD.
+    pub had_errors: bool,
+}
+
+/// Decode text of any charset.
+///
+/// A byte order mark wins, then the `charset` parameter of `mime_type` if
+/// there is one. Otherwise the bytes are decoded as UTF-8 when valid, and the
+/// charset is detected from the content when not.
+pub fn decode_text(bytes: &[u8], mime_type: Option<&str>) -> DecodedText {
+    let (encoding, bom_len) = Encoding::for_bom(bytes)
+        .or_else(|| {
+            let charset = mime_type.and_then(charset_param)?;
+            Some((Encoding::for_label(charset.as_bytes())?, 0))
+        })
+        .unwrap_or_else(|| {
+            if std::str::from_utf8(bytes).is_ok() {
+                (encoding_rs::UTF_8, 0)
+            } else {
+                let mut detector = chardetng::EncodingDetector::new();
+                detector.feed(bytes, true);
+                (detector.guess(None, true), 0)
+            }
+        });
+    let (text, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+    DecodedText {
+        text: text.into_owned(),
+        encoding: encoding.name(),
+        had_errors,
+    }
+}
+
+fn charset_param(mime_type: &str) -> Option<&str> {
+    mime_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+impl ResourceContents {
+    /// Contents of a resource read from raw bytes.
+    ///
+    /// The mime type is sniffed with [`sniff_mime_type`], using `uri` for the
+    /// extension. Text is decoded with [`decode_text`] into
+    /// [`TextResourceContents`](Self::TextResourceContents), anything else
+    /// becomes base64 [`BlobResourceContents`](Self::BlobResourceContents).
+    pub fn from_bytes(uri: impl Into<String>, bytes: &[u8]) -> Self {
+        let uri = uri.into();
+        let mime_type = sniff_mime_type(&uri, bytes);
+        if is_text_mime(mime_type) {
+            Self::TextResourceContents {
+                uri,
+                mime_type: Some(mime_type.to_owned()),
+                text: decode_text(bytes, None).text,
+                meta: None,
+                size: None,
+                last_modified: None,
+            }
+        } else {
+            Self::BlobResourceContents {
+                uri,
+                mime_type: Some(mime_type.to_owned()),
+                blob: BASE64_STANDARD.encode(bytes),
+                meta: None,
+                size: None,
+                last_modified: None,
+            }
+        }
+    }
+
+    /// Read a file with [`from_bytes`](Self::from_bytes), served under `uri`.
+    pub fn from_path(
+        uri: impl Into<String>,
+        path: impl AsRef<std::path::Path>,
+    ) -> std::io::Result<Self> {
+        Ok(Self::from_bytes(uri, &std::fs::read(path)?))
+    }
+}
@@ -0,0 +1,362 @@
+//! Content of sampling messages.
+//!
+//! A [`SamplingMessage`](super::SamplingMessage) carries one block or a list
+//! of them: text, images and audio like any [`Content`], plus the
+//! [`ToolUseContent`] and [`ToolResultContent`] blocks of tool-using
+//! conversations. Model providers that only take plain text can be fed
+//! [`SamplingMessageContent::to_plain_text`] instead of rejecting the request.
+use serde::{Deserialize, Serialize};
+
+use super::{
+    CallToolResult, Content, CreateMessageRequestParams, JsonObject, Meta, RawContent,
+    RawTextContent, ResourceContents, Role, SamplingMessage,
+};
+
+mod stream;
+pub use stream::*;
+
+/// The model asking for a tool to be called.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "tool_use", rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ToolUseContent {
+    /// Identifies this call, for the [`ToolResultContent`] answering it.
+    pub id: String,
+    pub name: String,
+    pub input: JsonObject,
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+/// The result of a tool call the model asked for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "tool_result", rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ToolResultContent {
+    /// The [`ToolUseContent::id`] of the call.
+    pub tool_use_id: String,
+    #[serde(default)]
+    pub content: Vec<Content>,
+    #[serde(
+        default,
+        deserialize_with = "super::serde_impl::nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub structured_content: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_error: Option<bool>,
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+impl ToolUseContent {
+    pub fn new(id: impl Into<String>, name: impl Into<String>, input: JsonObject) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            input,
+            meta: None,
+        }
+    }
+}
+
+impl ToolResultContent {
+    /// Answer the call `tool_use_id` with the result of `tools/call`.
+    pub fn new(tool_use_id: impl Into<String>, result: CallToolResult) -> Self {
+        Self {
+            tool_use_id: tool_use_id.into(),
+            content: result.content,
+            structured_content: result.structured_content,
+            is_error: result.is_error,
+            meta: None,
+        }
+    }
+}
+
+/// One block of a sampling message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum SamplingContent {
+    ToolUse(ToolUseContent),
+    ToolResult(ToolResultContent),
+    Content(Content),
+}
+
+impl SamplingContent {
+    pub fn as_content(&self) -> Option<&Content> {
+        match self {
+            Self::Content(content) => Some(content),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&RawTextContent> {
+        self.as_content()?.as_text()
+    }
+
+    pub fn as_tool_use(&self) -> Option<&ToolUseContent> {
+        match self {
+            Self::ToolUse(tool_use) => Some(tool_use),
+            _ => None,
+        }
+    }
+
+    pub fn as_tool_result(&self) -> Option<&ToolResultContent> {
+        match self {
+            Self::ToolResult(tool_result) => Some(tool_result),
+            _ => None,
+        }
+    }
+
+    /// The block as text: text as is, and a short bracketed description of
+    /// anything else.
+    pub fn to_plain_text(&self) -> String {
+        match self {
+            Self::Content(content) => content_to_plain_text(content),
+            Self::ToolUse(tool_use) => format!(
+                "[tool call {} ({}): {}]",
+                tool_use.name,
+                tool_use.id,
+                serde_json::Value::Object(tool_use.input.clone())
+            ),
+            Self::ToolResult(tool_result) => {
+                let mut text = format!("[tool result ({})", tool_result.tool_use_id);
+                if tool_result.is_error == Some(true) {
+                    text.push_str(", failed");
+                }
+                text.push(']');
+                for content in &tool_result.content {
+                    text.push('\n');
+                    text.push_str(&content_to_plain_text(content));
+                }
+                text
+            }
+        }
+    }
+}
+
+fn content_to_plain_text(content: &Content) -> String {
+    match &content.raw {
+        RawContent::Text(text) => text.text.clone(),
+        RawContent::Image(image) => format!("[image: {}]", image.mime_type),
+        RawContent::Audio(audio) => format!("[audio: {}]", audio.mime_type),
+        RawContent::Resource(embedded) => match &embedded.resource {
+            ResourceContents::TextResourceContents { text, .. } => text.clone(),
+            ResourceContents::BlobResourceContents { uri, .. } => format!("[resource: {uri}]"),
+        },
+        RawContent::ResourceLink(link) => format!("[resource: {}]", link.uri),
+    }
+}
+
+impl From<Content> for SamplingContent {
+    fn from(content: Content) -> Self {
+        Self::Content(content)
+    }
+}
+
+impl From<ToolUseContent> for SamplingContent {
+    fn from(tool_use: ToolUseContent) -> Self {
+        Self::ToolUse(tool_use)
+    }
+}
+
+impl From<ToolResultContent> for SamplingContent {
+    fn from(tool_result: ToolResultContent) -> Self {
+        Self::ToolResult(tool_result)
+    }
+}
+
+/// The content of a sampling message: a single block, or several.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[allow(clippy::large_enum_variant)]
+pub enum SamplingMessageContent {
+    Single(SamplingContent),
+    Multiple(Vec<SamplingContent>),
+}
+
+impl SamplingMessageContent {
+    pub fn parts(&self) -> &[SamplingContent] {
+        match self {
+            Self::Single(part) => std::slice::from_ref(part),
+            Self::Multiple(parts) => parts,
+        }
+    }
+
+    pub fn into_parts(self) -> Vec<SamplingContent> {
+        match self {
+            Self::Single(part) => vec![part],
+            Self::Multiple(parts) => parts,
+        }
+    }
+
+    /// Add `part` after the existing ones.
+    pub fn push(&mut self, part: impl Into<SamplingContent>) {
+        let mut parts = std::mem::replace(self, Self::Multiple(Vec::new())).into_parts();
+        parts.push(part.into());
+        *self = Self::Multiple(parts);
+    }
+
+    /// The first text block.
+    pub fn as_text(&self) -> Option<&RawTextContent> {
+        self.parts().iter().find_map(SamplingContent::as_text)
+    }
+
+    /// Whether every block is text, so providers that only take text can
+    /// use the content as is.
+    pub fn is_plain_text(&self) -> bool {
+        self.parts().iter().all(|part| part.as_text().is_some())
+    }
+
+    /// The blocks as text, one after the other, see
+    /// [`SamplingContent::to_plain_text`].
+    pub fn to_plain_text(&self) -> String {
+        self.parts()
+            .iter()
+            .map(SamplingContent::to_plain_text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl<T: Into<SamplingContent>> From<T> for SamplingMessageContent {
+    fn from(part: T) -> Self {
+        Self::Single(part.into())
+    }
+}
+
+impl<T: Into<SamplingContent>> From<Vec<T>> for SamplingMessageContent {
+    fn from(parts: Vec<T>) -> Self {
+        Self::Multiple(parts.into_iter().map(Into::into).collect())
+    }
+}
+
+impl SamplingMessage {
+    pub fn new(role: Role, content: impl Into<SamplingMessageContent>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+        }
+    }
+
+    pub fn user_text(text: impl Into<String>) -> Self {
+        Self::new(Role::User, Content::text(text))
+    }
+
+    pub fn assistant_text(text: impl Into<String>) -> Self {
+        Self::new(Role::Assistant, Content::text(text))
+    }
+
+    /// Add a block after the existing ones.
+    pub fn with_part(mut self, part: impl Into<SamplingContent>) -> Self {
+        self.content.push(part);
+        self
+    }
+
+    /// The message with its content as a single text block, see
+    /// [`SamplingMessageContent::to_plain_text`].
+    pub fn to_plain_text(&self) -> Self {
+        Self::new(self.role, Content::text(self.content.to_plain_text()))
+    }
+}
+
+impl CreateMessageRequestParams {
+    /// Whether every message is plain text.
+    pub fn is_plain_text(&self) -> bool {
+        self.messages
+            .iter()
+            .all(|message| message.content.is_plain_text())
+    }
+
+    /// The request with every message turned into plain text, for model
+    /// providers that only take text. Messages that already are text are
+    /// left as they are.
+    pub fn downgrade_to_text(mut self) -> Self {
+        for message in &mut self.messages {
+            if !message.content.is_plain_text() {
+                *message = message.to_plain_text();
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn single_and_multi_part_wire_format() {
+        let single: SamplingMessage = serde_json::from_value(
+            json!({ "role": "user", "content": { "type": "text", "text": "hi" } }),
+        )
+        .unwrap();
+        assert_eq!(single, SamplingMessage::user_text("hi"));
+
+        let message = SamplingMessage::user_text("what is in this picture?")
+            .with_part(Content::image("aGk=", "image/png"));
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": "what is in this picture?" },
+                    { "type": "image", "data": "aGk=", "mimeType": "image/png" },
+                ],
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<SamplingMessage>(value).unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn tool_blocks_round_trip() {
+        let input = json!({ "city": "Paris" }).as_object().unwrap().clone();
+        let call = SamplingMessage::new(
+            Role::Assistant,
+            ToolUseContent::new("call-1", "weather", input),
+        );
+        let answer = SamplingMessage::new(
+            Role::User,
+            ToolResultContent::new(
+                "call-1",
+                CallToolResult::success(vec![Content::text("sunny")]),
+            ),
+        );
+        let value = serde_json::to_value([&call, &answer]).unwrap();
+        assert_eq!(value[0]["content"]["type"], "tool_use");
+        assert_eq!(value[1]["content"]["type"], "tool_result");
+        assert_eq!(value[1]["content"]["toolUseId"], "call-1");
+        let parsed: Vec<SamplingMessage> = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, [call, answer]);
+        assert!(parsed[0].content.parts()[0].as_tool_use().is_some());
+    }
+
+    #[test]
+    fn downgrade_to_plain_text() {
+        let message = SamplingMessage::user_text("describe")
+            .with_part(Content::image("aGk=", "image/png"))
+            .with_part(ToolResultContent::new(
+                "call-1",
+                CallToolResult::error(vec![Content::text("timeout")]),
+            ));
+        assert!(!message.content.is_plain_text());
+        assert_eq!(
+            message.content.to_plain_text(),
+            "describe\n[image: image/png]\n[tool result (call-1), failed]\ntimeout"
+        );
+        let plain = message.to_plain_text();
+        assert!(plain.content.is_plain_text());
+        assert_eq!(
+            plain.content.as_text().unwrap().text,
+            message.content.to_plain_text()
+        );
+    }
+}
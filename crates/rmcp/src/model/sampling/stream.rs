@@ -0,0 +1,87 @@
+//! Streaming `sampling/createMessage`, an experimental extension.
+//!
+//! A server talking to a client that advertises [`SamplingStreaming`] can ask
+//! for a streamed answer by setting [`STREAM_META_KEY`] in the request
+//! `_meta`. The client then reports the completion as it's generated, with
+//! `notifications/progress` for the request's progress token that carry a
+//! [`SamplingChunk`] under [`SAMPLING_CHUNK_META_KEY`], and answers the
+//! request with the whole message as usual. The final result is
+//! authoritative; the chunks only let the server show the answer early.
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::model::{ExperimentalCapability, Meta};
+
+/// `_meta` key of a `sampling/createMessage` request that asks for chunks.
+pub const STREAM_META_KEY: &str = "x-rmcp/stream";
+/// `_meta` key of the [`SamplingChunk`] in a progress notification.
+pub const SAMPLING_CHUNK_META_KEY: &str = "x-rmcp/samplingChunk";
+
+/// Advertised by clients that stream sampling results.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SamplingStreaming {}
+
+impl ExperimentalCapability for SamplingStreaming {
+    const KEY: &'static str = "x-rmcp/sampling-streaming";
+}
+
+/// A piece of the completion, to append to the text of the previous ones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingChunk {
+    /// Position of the chunk in the stream, starting at 0, so gaps show.
+    pub index: u64,
+    pub text: String,
+}
+
+impl Meta {
+    /// Whether a `sampling/createMessage` request asks for chunks.
+    pub fn is_stream_requested(&self) -> bool {
+        self.get(STREAM_META_KEY)
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    pub fn set_stream_requested(&mut self) {
+        self.insert(STREAM_META_KEY.to_owned(), Value::Bool(true));
+    }
+
+    /// The chunk carried by a progress notification.
+    pub fn sampling_chunk(&self) -> Option<SamplingChunk> {
+        serde_json::from_value(self.get(SAMPLING_CHUNK_META_KEY)?.clone()).ok()
+    }
+
+    pub fn set_sampling_chunk(&mut self, chunk: &SamplingChunk) {
+        self.insert(
+            SAMPLING_CHUNK_META_KEY.to_owned(),
+            serde_json::to_value(chunk).expect("sampling chunk serializes"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_meta_wire_format() {
+        let mut meta = Meta::new();
+        assert!(!meta.is_stream_requested());
+        meta.set_stream_requested();
+        assert!(meta.is_stream_requested());
+
+        let chunk = SamplingChunk {
+            index: 2,
+            text: "Par".to_owned(),
+        };
+        meta.set_sampling_chunk(&chunk);
+        assert_eq!(
+            serde_json::to_value(&meta).unwrap(),
+            serde_json::json!({
+                "x-rmcp/stream": true,
+                "x-rmcp/samplingChunk": { "index": 2, "text": "Par" },
+            })
+        );
+        assert_eq!(meta.sampling_chunk(), Some(chunk));
+    }
+}
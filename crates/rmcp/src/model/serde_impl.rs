@@ -6,6 +6,16 @@ use super::{
     CustomNotification, CustomRequest, Extensions, Meta, Notification, NotificationNoParam,
     Request, RequestNoParam, RequestOptionalParam,
 };
+/// Deserialize an optional value keeping an explicit `null` as
+/// `Some(Value::Null)`, so it isn't confused with an absent field. Use it
+/// together with `#[serde(default)]`.
+pub(crate) fn nullable<'de, D>(deserializer: D) -> Result<Option<serde_json::Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    serde_json::Value::deserialize(deserializer).map(Some)
+}
+
 #[derive(Serialize, Deserialize)]
 struct WithMeta<'a, P> {
     #[serde(skip_serializing_if = "Option::is_none")]
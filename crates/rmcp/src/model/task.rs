@@ -19,6 +19,40 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+impl TaskStatus {
+    /// Whether the task is over: completed, failed or cancelled.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+
+    /// Whether a task may move from this status to `next`.
+    ///
+    /// `working` and `input_required` may move to each other or to any
+    /// terminal status, and stay as they are to update the status message;
+    /// terminal statuses are final.
+    pub fn can_transition_to(&self, next: &TaskStatus) -> bool {
+        match self {
+            Self::Working | Self::InputRequired => matches!(
+                next,
+                Self::Working
+                    | Self::InputRequired
+                    | Self::Completed
+                    | Self::Failed
+                    | Self::Cancelled
+            ),
+            Self::Completed | Self::Failed | Self::Cancelled => false,
+        }
+    }
+}
+
+/// A task status change that [`TaskStatus::can_transition_to`] forbids.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("task can't move from {from:?} to {to:?}")]
+pub struct InvalidTaskTransition {
+    pub from: TaskStatus,
+    pub to: TaskStatus,
+}
+
 /// Final result for a succeeded task (returned from `tasks/result`).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -58,6 +92,28 @@ pub struct Task {
     pub poll_interval: Option<u64>,
 }
 
+impl Task {
+    /// Move the task to `status` with `status_message`, stamping
+    /// [`last_updated_at`](Self::last_updated_at). Fails, leaving the task
+    /// unchanged, if the transition isn't legal.
+    pub fn transition(
+        &mut self,
+        status: TaskStatus,
+        status_message: Option<String>,
+    ) -> Result<(), InvalidTaskTransition> {
+        if !self.status.can_transition_to(&status) {
+            return Err(InvalidTaskTransition {
+                from: self.status.clone(),
+                to: status,
+            });
+        }
+        self.status = status;
+        self.status_message = status_message;
+        self.last_updated_at = Some(chrono::Utc::now().to_rfc3339());
+        Ok(())
+    }
+}
+
 /// Wrapper returned by task-augmented requests (CreateTaskResult in SEP-1686).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -77,3 +133,53 @@ pub struct TaskList {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total: Option<u64>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_transitions() {
+        let mut task = Task {
+            task_id: "task-1".into(),
+            created_at: "2025-11-25T00:00:00Z".into(),
+            ..Default::default()
+        };
+        task.transition(TaskStatus::InputRequired, Some("need a file".into()))
+            .unwrap();
+        assert_eq!(task.status_message.as_deref(), Some("need a file"));
+        assert!(task.last_updated_at.is_some());
+        task.transition(TaskStatus::Working, None).unwrap();
+        task.transition(TaskStatus::Working, Some("halfway".into()))
+            .unwrap();
+        task.transition(TaskStatus::Completed, None).unwrap();
+        assert!(task.status.is_terminal());
+
+        for status in [
+            TaskStatus::Working,
+            TaskStatus::InputRequired,
+            TaskStatus::Failed,
+        ] {
+            assert_eq!(
+                task.transition(status.clone(), None),
+                Err(InvalidTaskTransition {
+                    from: TaskStatus::Completed,
+                    to: status,
+                })
+            );
+        }
+        assert_eq!(task.status, TaskStatus::Completed);
+    }
+
+    #[test]
+    fn test_task_status_serde() {
+        assert_eq!(
+            serde_json::to_value(TaskStatus::InputRequired).unwrap(),
+            "input_required"
+        );
+        assert_eq!(
+            serde_json::from_value::<TaskStatus>("cancelled".into()).unwrap(),
+            TaskStatus::Cancelled
+        );
+    }
+}
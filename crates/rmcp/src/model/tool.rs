@@ -1,5 +1,6 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, sync::Arc, time::Duration};
 
+#[cfg(feature = "server")]
 use schemars::JsonSchema;
 /// Tools represent a routine that a server can execute
 /// Tool calls represent requests from the client to execute one
@@ -32,11 +33,50 @@ pub struct Tool {
     /// Optional list of icons for the tool
     #[serde(skip_serializing_if = "Option::is_none")]
     pub icons: Option<Vec<Icon>>,
+    /// How the tool may be executed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution: Option<ToolExecution>,
     /// Optional additional metadata for this tool
     #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
     pub meta: Option<Meta>,
 }
 
+/// Execution properties of a tool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ToolExecution {
+    /// Whether the tool may be called as a task (SEP-1686). Defaults to forbidden.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_support: Option<TaskSupport>,
+    /// How long a task-augmented call waits for the tool before answering. A
+    /// tool finishing in time is reported as an already completed task.
+    ///
+    /// Only used by the server, never sent to clients.
+    #[serde(skip)]
+    pub threshold: Option<Duration>,
+    /// Whether an unfinished task may be resumed after a restart, see
+    /// [`OperationProcessor::resume_tasks`](crate::task_manager::OperationProcessor::resume_tasks).
+    ///
+    /// Only used by the server, never sent to clients.
+    #[serde(skip)]
+    pub resumable: bool,
+}
+
+/// Whether a tool may be called with task augmentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum TaskSupport {
+    /// The tool is always called directly; task metadata is ignored.
+    #[default]
+    Forbidden,
+    /// The tool runs as a task when the caller asks for one, directly otherwise.
+    Optional,
+    /// The tool must be called as a task.
+    Required,
+}
+
 /// Additional properties describing a Tool to clients.
 ///
 /// NOTE: all properties in ToolAnnotations are **hints**.
@@ -158,6 +198,41 @@ impl ToolAnnotations {
     }
 }
 
+/// `_meta` key holding the version of a tool's contract.
+pub const TOOL_VERSION_META_KEY: &str = "rmcp/version";
+/// `_meta` key marking a tool as deprecated, holding a [`ToolDeprecation`].
+pub const TOOL_DEPRECATION_META_KEY: &str = "rmcp/deprecated";
+
+/// Why a tool is deprecated and what to use instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ToolDeprecation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Name of the tool replacing this one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+impl ToolDeprecation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn message(self, message: impl Into<String>) -> Self {
+        ToolDeprecation {
+            message: Some(message.into()),
+            ..self
+        }
+    }
+    pub fn replacement(self, replacement: impl Into<String>) -> Self {
+        ToolDeprecation {
+            replacement: Some(replacement.into()),
+            ..self
+        }
+    }
+}
+
 impl Tool {
     /// Create a new tool with the given name and description
     pub fn new<N, D, S>(name: N, description: D, input_schema: S) -> Self
@@ -174,6 +249,7 @@ impl Tool {
             output_schema: None,
             annotations: None,
             icons: None,
+            execution: None,
             meta: None,
         }
     }
@@ -190,6 +266,8 @@ impl Tool {
     /// # Panics
     ///
     /// Panics if the generated schema does not have root type "object" as required by MCP specification.
+    #[cfg(feature = "server")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
     pub fn with_output_schema<T: JsonSchema + 'static>(mut self) -> Self {
         let schema = crate::handler::server::tool::schema_for_output::<T>()
             .unwrap_or_else(|e| panic!("Invalid output schema for tool '{}': {}", self.name, e));
@@ -198,6 +276,8 @@ impl Tool {
     }
 
     /// Set the input schema using a type that implements JsonSchema
+    #[cfg(feature = "server")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
     pub fn with_input_schema<T: JsonSchema + 'static>(mut self) -> Self {
         self.input_schema = crate::handler::server::tool::schema_for_type::<T>();
         self
@@ -207,4 +287,90 @@ impl Tool {
     pub fn schema_as_json_value(&self) -> Value {
         Value::Object(self.input_schema.as_ref().clone())
     }
+
+    /// Set the version of the tool's contract, stored under
+    /// [`TOOL_VERSION_META_KEY`] in `_meta`.
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.meta
+            .get_or_insert_with(Meta::new)
+            .insert(TOOL_VERSION_META_KEY.to_owned(), version.into().into());
+        self
+    }
+
+    /// Mark the tool as deprecated, stored under
+    /// [`TOOL_DEPRECATION_META_KEY`] in `_meta`.
+    pub fn with_deprecation(mut self, deprecation: ToolDeprecation) -> Self {
+        let deprecation =
+            serde_json::to_value(deprecation).expect("tool deprecation is always serializable");
+        self.meta
+            .get_or_insert_with(Meta::new)
+            .insert(TOOL_DEPRECATION_META_KEY.to_owned(), deprecation);
+        self
+    }
+
+    /// Let the tool be called as a task.
+    pub fn with_task_support(mut self, task_support: TaskSupport) -> Self {
+        self.execution
+            .get_or_insert_with(Default::default)
+            .task_support = Some(task_support);
+        self
+    }
+
+    /// Wait up to `threshold` for a task-augmented call before answering, see
+    /// [`ToolExecution::threshold`].
+    pub fn with_task_threshold(mut self, threshold: Duration) -> Self {
+        self.execution
+            .get_or_insert_with(Default::default)
+            .threshold = Some(threshold);
+        self
+    }
+
+    /// Let an unfinished task of the tool be resumed, see
+    /// [`ToolExecution::resumable`].
+    pub fn with_resumable_task(mut self) -> Self {
+        self.execution
+            .get_or_insert_with(Default::default)
+            .resumable = true;
+        self
+    }
+
+    pub fn task_support(&self) -> TaskSupport {
+        self.execution
+            .as_ref()
+            .and_then(|execution| execution.task_support)
+            .unwrap_or_default()
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.meta.as_ref()?.get(TOOL_VERSION_META_KEY)?.as_str()
+    }
+
+    /// The deprecation of the tool, if any. A bare `true` under
+    /// [`TOOL_DEPRECATION_META_KEY`] also counts.
+    pub fn deprecation(&self) -> Option<ToolDeprecation> {
+        match self.meta.as_ref()?.get(TOOL_DEPRECATION_META_KEY)? {
+            Value::Bool(true) => Some(ToolDeprecation::default()),
+            value @ Value::Object(_) => serde_json::from_value(value.clone()).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecation().is_some()
+    }
+
+    /// A line telling agents and users that the tool is deprecated, or `None`
+    /// if it isn't.
+    pub fn deprecation_warning(&self) -> Option<String> {
+        let deprecation = self.deprecation()?;
+        let mut warning = format!("tool `{}` is deprecated", self.name);
+        if let Some(message) = &deprecation.message {
+            warning.push_str(": ");
+            warning.push_str(message);
+        }
+        if let Some(replacement) = &deprecation.replacement {
+            warning.push_str(&format!(", use `{replacement}` instead"));
+        }
+        Some(warning)
+    }
 }
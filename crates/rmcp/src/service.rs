@@ -3,10 +3,12 @@ use thiserror::Error;
 
 use crate::{
     error::ErrorData as McpError,
+    handler::custom::CustomMethod,
     model::{
-        CancelledNotification, CancelledNotificationParam, Extensions, GetExtensions, GetMeta,
+        CancelledNotification, CancelledNotificationParam, CustomRequest, ErrorCode,
+        ExperimentalCapabilities, ExperimentalCapabilitiesExt, Extensions, GetExtensions, GetMeta,
         JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, Meta,
-        NumberOrString, ProgressToken, RequestId, ServerJsonRpcMessage,
+        NumberOrString, ProgressToken, ProtocolVersion, RequestId,
     },
     transport::{DynamicTransportError, IntoTransport, Transport},
 };
@@ -22,6 +24,42 @@ mod server;
 #[cfg(feature = "server")]
 #[cfg_attr(docsrs, doc(cfg(feature = "server")))]
 pub use server::*;
+mod notification_queue;
+use notification_queue::NotificationQueue;
+mod retry;
+pub use retry::RetryPolicy;
+mod interceptor;
+pub use interceptor::Interceptor;
+mod expiry;
+pub use expiry::{SessionExpiry, SessionTimeouts};
+mod config;
+pub use config::{ServiceConfig, SessionEndHook};
+mod connection;
+pub use connection::{CloseReason, ConnectionState};
+mod pending;
+pub use pending::PendingRequest;
+use pending::PendingRequests;
+mod memory;
+pub use memory::{MemoryAccount, MemoryKind, MemoryUsage, buffered_bytes};
+mod panic;
+mod sequence;
+use panic::PanicGuard;
+pub use panic::PanicPolicy;
+pub use sequence::DeliveryOrder;
+use sequence::{Sequencer, in_turn};
+mod violations;
+pub use violations::{ProtocolViolations, protocol_violations};
+mod tasks;
+pub use tasks::{TaskCounts, TaskKind, task_counts};
+mod diagnostics;
+#[cfg(feature = "client")]
+mod handshake;
+pub use diagnostics::{Diagnostics, DiagnosticsReport, RttPercentiles};
+#[cfg(feature = "client")]
+pub use handshake::HandshakeDiagnostics;
+mod compatibility;
+pub use compatibility::CompatibilityReport;
+pub(crate) use tasks::{counted as counted_task, spawn as spawn_task};
 #[cfg(feature = "tower")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
 mod tower;
@@ -47,6 +85,20 @@ pub enum ServiceError {
     Timeout { timeout: Duration },
 }
 
+impl ServiceError {
+    /// Whether the same request may succeed if it is sent again.
+    ///
+    /// True for transport send failures, timeouts and MCP errors whose code is
+    /// [retryable](crate::model::ErrorCode::is_retryable).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ServiceError::McpError(error) => error.is_retryable(),
+            ServiceError::TransportSend(_) | ServiceError::Timeout { .. } => true,
+            _ => false,
+        }
+    }
+}
+
 trait TransferObject:
     std::fmt::Debug + Clone + serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static
 {
@@ -81,6 +133,35 @@ pub trait ServiceRole: std::fmt::Debug + Send + Sync + 'static + Copy + Clone {
     const IS_CLIENT: bool;
     type Info: TransferObject;
     type PeerInfo: TransferObject;
+    /// The JSON-RPC method of a request sent by this role.
+    fn request_method(request: &Self::Req) -> &str;
+    /// The JSON-RPC method of a request received by this role.
+    fn peer_request_method(request: &Self::PeerReq) -> &str;
+    /// The JSON-RPC method of a notification received by this role.
+    fn peer_notification_method(notification: &Self::PeerNot) -> &str;
+    /// Whether `request` can safely be sent more than once, which makes it
+    /// eligible for automatic retries under a [`RetryPolicy`].
+    fn is_idempotent(request: &Self::Req) -> bool {
+        let _ = request;
+        false
+    }
+    /// Whether `notification` may be delayed behind other outbound messages,
+    /// and dropped if too many of its kind pile up.
+    fn is_low_priority(notification: &Self::Not) -> bool {
+        let _ = notification;
+        false
+    }
+    /// Sent to the peer right before a session is closed because of
+    /// [`SessionTimeouts`].
+    fn expiry_notification(expiry: SessionExpiry) -> Option<Self::Not> {
+        let _ = expiry;
+        None
+    }
+    /// What the peer advertised under `capabilities.experimental`.
+    fn peer_experimental(info: &Self::PeerInfo) -> Option<&ExperimentalCapabilities> {
+        let _ = info;
+        None
+    }
 }
 
 pub type TxJsonRpcMessage<R> =
@@ -91,6 +172,60 @@ pub type RxJsonRpcMessage<R> = JsonRpcMessage<
     <R as ServiceRole>::PeerNot,
 >;
 
+/// Handles the requests and notifications of a peer
+///
+/// # Tracing
+///
+/// Each request is handled in an `mcp.request` span, and each notification
+/// in an `mcp.notification` span, with these fields so logs of both sides
+/// of a session can be matched:
+///
+/// - `jsonrpc.request.id`: the JSON-RPC id of the request
+/// - `mcp.method.name`: the method of the request or notification
+/// - `mcp.session.id`: the session id, for transports that have one, see
+///   [`Transport::session_id`]
+///
+/// When [`request_timings`](ServiceConfig::request_timings) is on, each
+/// `mcp.request` span gets a child span per stage of the request, so a
+/// subscriber that reports span durations shows where the time went:
+///
+/// - `mcp.request.queue`: from receipt until the handler starts
+/// - `mcp.handler`: the handler itself
+/// - `mcp.response.queue`: until the response is handed to the transport
+/// - `mcp.transport.write`: encoding and writing the response, since
+///   transports encode messages as they write them
+///
+/// # Ordering
+///
+/// By default ([`DeliveryOrder::Concurrent`]) each request and notification
+/// from the peer gets its own task, and each message to the peer is written
+/// by its own task, so the only guarantees are that a request is answered
+/// after it is received and that a message is written before the future
+/// sending it resolves. Under load, a `notifications/resources/updated` can
+/// be handled or written before an earlier one.
+///
+/// With [`DeliveryOrder::Fifo`], notifications from the peer are handled
+/// one after the other, in the order they arrived, and messages to the peer
+/// are written in the order they were handed to the service, e.g. progress
+/// notifications before the response of their request. Progress and logging
+/// notifications are then neither held back behind other messages nor
+/// dropped under pressure. Requests are still handled concurrently.
+///
+/// # Deterministic tests
+///
+/// Handlers normally run concurrently, so the order in which their effects
+/// show up depends on scheduling. With [`sequential`](ServiceConfig::sequential)
+/// on, each handler waits for the ones received before it to finish, and
+/// messages are written as with [`DeliveryOrder::Fifo`], so tests can assert
+/// on the exact order of responses and notifications, logging and progress
+/// included: none of them is dropped.
+/// Pings, responses and cancellations are still processed meanwhile, so a
+/// handler may wait on the peer.
+///
+/// The service only measures time with [`tokio::time`], so its timeouts
+/// follow a paused clock too: under `#[tokio::test(start_paused = true)]`
+/// time only advances when every task is idle, and session timeouts fire
+/// deterministically without the test waiting for them.
 pub trait Service<R: ServiceRole>: Send + Sync + 'static {
     fn handle_request(
         &self,
@@ -103,6 +238,11 @@ pub trait Service<R: ServiceRole>: Send + Sync + 'static {
         context: NotificationContext<R>,
     ) -> impl Future<Output = Result<(), McpError>> + Send + '_;
     fn get_info(&self) -> R::Info;
+    /// How sessions of this service run: timeouts, ordering, panics and
+    /// more, see [`ServiceConfig`]. Asked once per session.
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig::default()
+    }
 }
 
 pub trait ServiceExt<R: ServiceRole>: Service<R> + Sized {
@@ -123,6 +263,53 @@ pub trait ServiceExt<R: ServiceRole>: Service<R> + Sized {
     {
         Self::serve_with_ct(self, transport, Default::default())
     }
+    /// Serve until the peer disconnects or `signal` resolves, whichever comes
+    /// first.
+    ///
+    /// When `signal` fires the session is cancelled and the transport closed
+    /// properly, so the peer never sees half a message. Meant for stdio
+    /// servers, with a future that resolves on SIGINT/SIGTERM:
+    ///
+    /// ```rust,ignore
+    /// let quit_reason = Counter::new()
+    ///     .serve_with_graceful_shutdown(stdio(), async {
+    ///         tokio::signal::ctrl_c().await.ok();
+    ///     })
+    ///     .await?;
+    /// ```
+    fn serve_with_graceful_shutdown<T, E, A, F>(
+        self,
+        transport: T,
+        signal: F,
+    ) -> impl Future<Output = Result<QuitReason, R::InitializeError>> + Send
+    where
+        T: IntoTransport<R, E, A>,
+        E: std::error::Error + Send + Sync + 'static,
+        F: Future<Output = ()> + Send,
+        Self: Sized,
+    {
+        async move {
+            let mut signal = std::pin::pin!(signal);
+            let running = tokio::select! {
+                running = self.serve(transport) => running?,
+                _ = &mut signal => {
+                    tracing::info!("shutdown signal received during initialization");
+                    return Ok(QuitReason::Cancelled);
+                }
+            };
+            let ct = running.cancellation_token();
+            let mut waiting = std::pin::pin!(running.waiting());
+            let result = tokio::select! {
+                result = &mut waiting => result,
+                _ = signal => {
+                    tracing::info!("shutdown signal received");
+                    ct.cancel();
+                    waiting.await
+                }
+            };
+            Ok(result.unwrap_or_else(QuitReason::JoinError))
+        }
+    }
     fn serve_with_ct<T, E, A>(
         self,
         transport: T,
@@ -154,6 +341,10 @@ impl<R: ServiceRole> Service<R> for Box<dyn DynService<R>> {
     fn get_info(&self) -> R::Info {
         DynService::get_info(self.as_ref())
     }
+
+    fn config(&self) -> ServiceConfig {
+        DynService::config(self.as_ref())
+    }
 }
 
 pub trait DynService<R: ServiceRole>: Send + Sync {
@@ -168,6 +359,7 @@ pub trait DynService<R: ServiceRole>: Send + Sync {
         context: NotificationContext<R>,
     ) -> BoxFuture<'_, Result<(), McpError>>;
     fn get_info(&self) -> R::Info;
+    fn config(&self) -> ServiceConfig;
 }
 
 impl<R: ServiceRole, S: Service<R>> DynService<R> for S {
@@ -188,6 +380,9 @@ impl<R: ServiceRole, S: Service<R>> DynService<R> for S {
     fn get_info(&self) -> R::Info {
         self.get_info()
     }
+    fn config(&self) -> ServiceConfig {
+        self.config()
+    }
 }
 
 use std::{
@@ -199,6 +394,11 @@ use std::{
 
 use tokio::sync::mpsc;
 
+/// Where the ids of the requests sent on a session come from, see
+/// [`ServiceConfig::request_id_provider`].
+///
+/// Ids must be unique for as long as their request is pending: a response is
+/// matched to its request by id alone.
 pub trait RequestIdProvider: Send + Sync + 'static {
     fn next_request_id(&self) -> RequestId;
 }
@@ -230,6 +430,50 @@ impl ProgressTokenProvider for AtomicU32Provider {
     }
 }
 
+/// Random UUID strings as request ids, e.g. to correlate requests with
+/// systems that already key their records by UUID.
+#[cfg(feature = "uuid")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UuidRequestIdProvider;
+
+#[cfg(feature = "uuid")]
+impl RequestIdProvider for UuidRequestIdProvider {
+    fn next_request_id(&self) -> RequestId {
+        RequestId::String(uuid::Uuid::new_v4().to_string().into())
+    }
+}
+
+/// String ids made of a prefix and the ids of another provider, like
+/// `"indexer-7"`, so the requests of a subsystem stand out in logs and
+/// traces. See [`Peer::with_id_prefix`].
+pub struct PrefixedRequestIdProvider {
+    prefix: Arc<str>,
+    inner: Arc<dyn RequestIdProvider>,
+}
+
+impl PrefixedRequestIdProvider {
+    pub fn new(prefix: impl Into<Arc<str>>, inner: Arc<dyn RequestIdProvider>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            inner,
+        }
+    }
+}
+
+impl std::fmt::Debug for PrefixedRequestIdProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrefixedRequestIdProvider")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RequestIdProvider for PrefixedRequestIdProvider {
+    fn next_request_id(&self) -> RequestId {
+        RequestId::String(format!("{}{}", self.prefix, self.inner.next_request_id()).into())
+    }
+}
+
 type Responder<T> = tokio::sync::oneshot::Sender<T>;
 
 /// A handle to a remote request
@@ -249,6 +493,15 @@ pub struct RequestHandle<R: ServiceRole> {
 impl<R: ServiceRole> RequestHandle<R> {
     pub const REQUEST_TIMEOUT_REASON: &str = "request timeout";
     pub async fn await_response(self) -> Result<R::PeerResp, ServiceError> {
+        let interceptors = self.peer.interceptors.clone();
+        let mut response = self.await_raw_response().await?;
+        for interceptor in interceptors.iter() {
+            interceptor.on_incoming(&mut response);
+        }
+        Ok(response)
+    }
+
+    async fn await_raw_response(self) -> Result<R::PeerResp, ServiceError> {
         if let Some(timeout) = self.options.timeout {
             let timeout_result = tokio::time::timeout(timeout, async move {
                 self.rx.await.map_err(|_e| ServiceError::TransportClosed)?
@@ -312,9 +565,28 @@ pub(crate) enum PeerSinkMessage<R: ServiceRole> {
 #[derive(Clone)]
 pub struct Peer<R: ServiceRole> {
     tx: mpsc::Sender<PeerSinkMessage<R>>,
+    shared: Arc<PeerShared<R>>,
     request_id_provider: Arc<dyn RequestIdProvider>,
+    retry_policy: Option<Arc<RetryPolicy>>,
+    interceptors: Arc<Vec<Arc<dyn Interceptor<R>>>>,
+}
+
+/// Session state shared by every [`Peer`] and [`WeakPeer`] handle.
+struct PeerShared<R: ServiceRole> {
     progress_token_provider: Arc<dyn ProgressTokenProvider>,
-    info: Arc<tokio::sync::OnceCell<R::PeerInfo>>,
+    info: tokio::sync::OnceCell<R::PeerInfo>,
+    protocol_version: std::sync::OnceLock<ProtocolVersion>,
+    negotiated_experimental: std::sync::OnceLock<std::collections::BTreeMap<String, String>>,
+    compatibility: std::sync::OnceLock<CompatibilityReport>,
+    low_priority: Arc<NotificationQueue<R>>,
+    /// Whether outbound messages keep their order, in which case low-priority
+    /// notifications take the normal path instead of the queue
+    in_order: std::sync::atomic::AtomicBool,
+    pending: Arc<PendingRequests>,
+    memory: MemoryAccount,
+    /// Timeout of elicitations without their own, shared by the whole session
+    #[cfg(feature = "elicitation")]
+    elicitation_timeout: std::sync::RwLock<Option<Duration>>,
 }
 
 impl<R: ServiceRole> std::fmt::Debug for Peer<R> {
@@ -326,8 +598,63 @@ impl<R: ServiceRole> std::fmt::Debug for Peer<R> {
     }
 }
 
+/// A [`Peer`] handle that doesn't keep the message channel open.
+///
+/// Meant for background tasks (file watchers, timers, ...) that push
+/// notifications for as long as the session lives: [`upgrade`](Self::upgrade)
+/// fails once the session is gone, so the task can just stop.
+#[derive(Clone)]
+pub struct WeakPeer<R: ServiceRole> {
+    tx: mpsc::WeakSender<PeerSinkMessage<R>>,
+    shared: Arc<PeerShared<R>>,
+    request_id_provider: Arc<dyn RequestIdProvider>,
+    retry_policy: Option<Arc<RetryPolicy>>,
+    interceptors: Arc<Vec<Arc<dyn Interceptor<R>>>>,
+}
+
+impl<R: ServiceRole> WeakPeer<R> {
+    /// Get a usable [`Peer`] back, unless the session has ended.
+    pub fn upgrade(&self) -> Option<Peer<R>> {
+        let tx = self.tx.upgrade()?;
+        if tx.is_closed() {
+            return None;
+        }
+        Some(Peer {
+            tx,
+            shared: self.shared.clone(),
+            request_id_provider: self.request_id_provider.clone(),
+            retry_policy: self.retry_policy.clone(),
+            interceptors: self.interceptors.clone(),
+        })
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.upgrade().is_none()
+    }
+}
+
+impl<R: ServiceRole> std::fmt::Debug for WeakPeer<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeakPeer")
+            .field("is_closed", &self.is_closed())
+            .field("is_client", &R::IS_CLIENT)
+            .finish()
+    }
+}
+
 type ProxyOutbound<R> = mpsc::Receiver<PeerSinkMessage<R>>;
 
+tokio::task_local! {
+    /// Deadline of the peer request currently being handled, so that requests
+    /// sent while handling it don't outlive it.
+    static REQUEST_DEADLINE: tokio::time::Instant;
+}
+
+/// The point in time after which the sender of a request stops waiting for
+/// it. Carried in the [`RequestContext`] extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestDeadline(pub tokio::time::Instant);
+
 #[derive(Debug, Default)]
 pub struct PeerRequestOptions {
     pub timeout: Option<Duration>,
@@ -342,22 +669,60 @@ impl PeerRequestOptions {
 
 impl<R: ServiceRole> Peer<R> {
     const CLIENT_CHANNEL_BUFFER_SIZE: usize = 1024;
+    const LOW_PRIORITY_QUEUE_CAPACITY: usize = 256;
     pub(crate) fn new(
         request_id_provider: Arc<dyn RequestIdProvider>,
         peer_info: Option<R::PeerInfo>,
+        memory: MemoryAccount,
     ) -> (Peer<R>, ProxyOutbound<R>) {
         let (tx, rx) = mpsc::channel(Self::CLIENT_CHANNEL_BUFFER_SIZE);
         (
             Self {
                 tx,
+                shared: Arc::new(PeerShared {
+                    progress_token_provider: Arc::new(AtomicU32ProgressTokenProvider::default()),
+                    info: tokio::sync::OnceCell::new_with(peer_info),
+                    protocol_version: Default::default(),
+                    negotiated_experimental: Default::default(),
+                    compatibility: Default::default(),
+                    low_priority: Arc::new(NotificationQueue::new(
+                        Self::LOW_PRIORITY_QUEUE_CAPACITY,
+                        memory.clone(),
+                    )),
+                    in_order: Default::default(),
+                    pending: Arc::default(),
+                    memory,
+                    #[cfg(feature = "elicitation")]
+                    elicitation_timeout: Default::default(),
+                }),
                 request_id_provider,
-                progress_token_provider: Arc::new(AtomicU32ProgressTokenProvider::default()),
-                info: Arc::new(tokio::sync::OnceCell::new_with(peer_info)),
+                retry_policy: None,
+                interceptors: Arc::default(),
             },
             rx,
         )
     }
+    /// Send a notification. Low-priority ones (progress and logging) are only
+    /// queued and may be dropped, see
+    /// [`dropped_notifications`](Self::dropped_notifications); the others are
+    /// waited for until they have been handed to the transport.
+    ///
+    /// Queued notifications are sent when nothing more urgent is pending. If
+    /// too many of them queue up, the oldest ones are dropped and counted.
+    /// With [`DeliveryOrder::Fifo`] or [`ServiceConfig::sequential`] nothing
+    /// is queued: every notification is waited for and written in order.
     pub async fn send_notification(&self, notification: R::Not) -> Result<(), ServiceError> {
+        if R::is_low_priority(&notification)
+            && !self
+                .shared
+                .in_order
+                .load(std::sync::atomic::Ordering::Relaxed)
+        {
+            if self.tx.is_closed() {
+                return Err(ServiceError::TransportClosed);
+            }
+            return self.shared.low_priority.push(notification);
+        }
         let (responder, receiver) = tokio::sync::oneshot::channel();
         self.tx
             .send(PeerSinkMessage::Notification {
@@ -368,11 +733,149 @@ impl<R: ServiceRole> Peer<R> {
             .map_err(|_m| ServiceError::TransportClosed)?;
         receiver.await.map_err(|_e| ServiceError::TransportClosed)?
     }
+    /// Set the policy used to retry idempotent requests sent through this
+    /// handle (and its clones).
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(Arc::new(policy));
+        self
+    }
+
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_deref()
+    }
+
+    /// Add an [`Interceptor`] to the requests sent through this handle (and
+    /// its clones) and to their responses.
+    ///
+    /// Interceptors run in the order they were added.
+    pub fn with_interceptor(mut self, interceptor: impl Interceptor<R>) -> Self {
+        Arc::make_mut(&mut self.interceptors).push(Arc::new(interceptor));
+        self
+    }
+
     pub async fn send_request(&self, request: R::Req) -> Result<R::PeerResp, ServiceError> {
-        self.send_request_with_option(request, PeerRequestOptions::no_options())
-            .await?
-            .await_response()
-            .await
+        match &self.retry_policy {
+            Some(policy) if R::is_idempotent(&request) => {
+                self.send_request_retrying(request, policy).await
+            }
+            _ => {
+                self.send_request_with_option(request, PeerRequestOptions::no_options())
+                    .await?
+                    .await_response()
+                    .await
+            }
+        }
+    }
+
+    /// Send a request and retry it on transient failures even if it is not
+    /// known to be idempotent.
+    ///
+    /// Uses the peer's [`RetryPolicy`], or the default one if none is set.
+    pub async fn send_request_with_retry(
+        &self,
+        request: R::Req,
+    ) -> Result<R::PeerResp, ServiceError> {
+        match &self.retry_policy {
+            Some(policy) => self.send_request_retrying(request, policy).await,
+            None => {
+                self.send_request_retrying(request, &RetryPolicy::default())
+                    .await
+            }
+        }
+    }
+
+    async fn send_request_retrying(
+        &self,
+        request: R::Req,
+        policy: &RetryPolicy,
+    ) -> Result<R::PeerResp, ServiceError> {
+        let mut attempt = 0;
+        loop {
+            let result = match self
+                .send_request_with_option(request.clone(), PeerRequestOptions::no_options())
+                .await
+            {
+                Ok(handle) => handle.await_response().await,
+                Err(error) => Err(error),
+            };
+            match result {
+                Err(error) if attempt < policy.max_retries && policy.should_retry(&error) => {
+                    let delay = policy.backoff(attempt);
+                    tracing::debug!(%error, attempt, ?delay, "retrying request");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Call a method outside the MCP spec, such as one served by a
+    /// [`CustomMethodRouter`](crate::handler::custom::CustomMethodRouter).
+    ///
+    /// `params` must serialize to an object, or to `null` to omit them. The
+    /// result is deserialized into `T`.
+    pub async fn call_custom<P, T>(
+        &self,
+        method: impl Into<String>,
+        params: P,
+    ) -> Result<T, ServiceError>
+    where
+        P: serde::Serialize,
+        T: serde::de::DeserializeOwned,
+        R::Req: From<CustomRequest>,
+    {
+        let method = method.into();
+        let params = match serde_json::to_value(params) {
+            Ok(serde_json::Value::Null) => None,
+            Ok(params @ serde_json::Value::Object(_)) => Some(params),
+            Ok(_) => {
+                return Err(ServiceError::McpError(McpError::invalid_params(
+                    format!("params of {method} must serialize to an object"),
+                    None,
+                )));
+            }
+            Err(error) => {
+                return Err(ServiceError::McpError(McpError::invalid_params(
+                    format!("failed to serialize params of {method}: {error}"),
+                    None,
+                )));
+            }
+        };
+        let response = self
+            .send_request(CustomRequest::new(method, params).into())
+            .await?;
+        // small results may come back as another result type, e.g. `{}` as
+        // an empty result, so go through JSON rather than matching on it
+        serde_json::to_value(response)
+            .and_then(serde_json::from_value)
+            .map_err(|error| {
+                tracing::debug!(%error, "unexpected custom response");
+                ServiceError::UnexpectedResponse
+            })
+    }
+
+    /// Call the custom method `M`, if the peer advertised it under
+    /// `capabilities.experimental`, e.g. with
+    /// [`CustomMethodRouter::experimental_capabilities`](crate::handler::custom::CustomMethodRouter::experimental_capabilities).
+    ///
+    /// Works in both directions, so a server can call methods its client
+    /// serves. If the peer didn't advertise `M::METHOD`, nothing is sent and
+    /// this fails with [`ErrorCode::METHOD_NOT_FOUND`], so an extension can
+    /// fall back to standard behavior.
+    pub async fn call_method<M>(&self, params: M::Params) -> Result<M::Result, ServiceError>
+    where
+        M: CustomMethod,
+        R::Req: From<CustomRequest>,
+    {
+        if !self.peer_supports_experimental(M::METHOD) {
+            return Err(ServiceError::McpError(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                format!("peer doesn't advertise {}", M::METHOD),
+                None,
+            )));
+        }
+        self.call_custom(M::METHOD, params).await
     }
 
     pub async fn send_cancellable_request(
@@ -383,28 +886,58 @@ impl<R: ServiceRole> Peer<R> {
         self.send_request_with_option(request, options).await
     }
 
+    /// Send a request and return a handle to its response.
+    ///
+    /// When called while handling a peer request that carries a time budget,
+    /// the new request inherits whatever is left of that budget: its timeout
+    /// is capped to the remaining time. Any timeout is forwarded to the peer
+    /// as the request's own budget.
+    ///
+    /// The handle carries the [`id`](RequestHandle::id) the request was sent
+    /// with, e.g. to correlate it with records in other systems.
     pub async fn send_request_with_option(
         &self,
         mut request: R::Req,
-        options: PeerRequestOptions,
+        mut options: PeerRequestOptions,
     ) -> Result<RequestHandle<R>, ServiceError> {
         let id = self.request_id_provider.next_request_id();
-        let progress_token = self.progress_token_provider.next_progress_token();
+        let progress_token = self.shared.progress_token_provider.next_progress_token();
         request
             .get_meta_mut()
             .set_progress_token(progress_token.clone());
+        if let Ok(deadline) = REQUEST_DEADLINE.try_with(|deadline| *deadline) {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            options.timeout = Some(options.timeout.map_or(remaining, |t| t.min(remaining)));
+        }
+        if let Some(timeout) = options.timeout {
+            request.get_meta_mut().set_budget(timeout);
+        }
         if let Some(meta) = options.meta.clone() {
             request.get_meta_mut().extend(meta);
         }
+        for interceptor in self.interceptors.iter() {
+            interceptor.on_outgoing(&mut request);
+        }
+        self.shared.pending.insert(PendingRequest {
+            id: id.clone(),
+            method: R::request_method(&request).to_owned(),
+            progress_token: progress_token.clone(),
+            sent_at: tokio::time::Instant::now(),
+        });
         let (responder, receiver) = tokio::sync::oneshot::channel();
-        self.tx
+        if self
+            .tx
             .send(PeerSinkMessage::Request {
                 request,
                 id: id.clone(),
                 responder,
             })
             .await
-            .map_err(|_m| ServiceError::TransportClosed)?;
+            .is_err()
+        {
+            self.shared.pending.remove(&id);
+            return Err(ServiceError::TransportClosed);
+        }
         Ok(RequestHandle {
             id,
             rx: receiver,
@@ -413,21 +946,167 @@ impl<R: ServiceRole> Peer<R> {
             peer: self.clone(),
         })
     }
+
+    /// A handle to the same session whose requests get ids starting with
+    /// `prefix`, followed by an id of the session's
+    /// [provider](ServiceConfig::request_id_provider). Hand one to each subsystem
+    /// to tell their requests apart.
+    pub fn with_id_prefix(&self, prefix: impl Into<Arc<str>>) -> Self {
+        Self {
+            request_id_provider: Arc::new(PrefixedRequestIdProvider::new(
+                prefix,
+                self.request_id_provider.clone(),
+            )),
+            ..self.clone()
+        }
+    }
+
+    /// The requests sent on this session that are still waiting for a
+    /// response, oldest first.
+    pub fn pending_requests(&self) -> Vec<PendingRequest> {
+        self.shared.pending.list()
+    }
+
+    /// Where this session records the bytes it buffers. Hand it to the
+    /// buffers the session doesn't own itself, like the task results of an
+    /// `OperationProcessor`.
+    pub fn memory_account(&self) -> &MemoryAccount {
+        &self.shared.memory
+    }
+
+    /// Approximate bytes buffered for this session so far.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.shared.memory.usage()
+    }
+
+    /// How many low-priority notifications of this session were dropped
+    /// because too many of them were waiting to be sent.
+    pub fn dropped_notifications(&self) -> usize {
+        self.shared.low_priority.dropped()
+    }
+
+    /// A latency and throughput benchmark of this session, see
+    /// [`Diagnostics`].
+    pub fn diagnostics(&self) -> Diagnostics<R> {
+        Diagnostics::new(self.clone())
+    }
+
+    /// Cancel the pending request `id`, telling the peer to stop working on
+    /// it. Whoever awaits its response gets [`ServiceError::Cancelled`].
+    ///
+    /// Returns `false` if the request isn't pending anymore.
+    pub async fn cancel_request(
+        &self,
+        id: &RequestId,
+        reason: Option<String>,
+    ) -> Result<bool, ServiceError> {
+        if !self.shared.pending.contains(id) {
+            return Ok(false);
+        }
+        let notification = CancelledNotification {
+            params: CancelledNotificationParam {
+                request_id: id.clone(),
+                reason,
+            },
+            method: crate::model::CancelledNotificationMethod,
+            extensions: Default::default(),
+        };
+        self.send_notification(notification.into()).await?;
+        Ok(true)
+    }
+
     pub fn peer_info(&self) -> Option<&R::PeerInfo> {
-        self.info.get()
+        self.shared.info.get()
     }
 
     pub fn set_peer_info(&self, info: R::PeerInfo) {
-        if self.info.initialized() {
+        if self.shared.info.initialized() {
             tracing::warn!("trying to set peer info, which is already initialized");
         } else {
-            let _ = self.info.set(info);
+            let _ = self.shared.info.set(info);
         }
     }
 
+    /// The protocol version both sides agreed on during initialization.
+    /// `None` for services started without the handshake, e.g. with
+    /// [`serve_directly`].
+    pub fn protocol_version(&self) -> Option<&ProtocolVersion> {
+        self.shared.protocol_version.get()
+    }
+
+    pub(crate) fn set_protocol_version(&self, protocol_version: ProtocolVersion) {
+        let _ = self.shared.protocol_version.set(protocol_version);
+    }
+
+    /// The version of the experimental capability `key` both sides agreed on
+    /// during initialization: the highest one they both listed with
+    /// [`ExperimentalCapabilitiesExt::insert_versioned`]. `None` if either
+    /// side doesn't support `key`, so an extension can fall back to
+    /// standard behavior.
+    ///
+    /// [`ExperimentalCapabilitiesExt::insert_versioned`]: crate::model::ExperimentalCapabilitiesExt::insert_versioned
+    pub fn negotiated_experimental(&self, key: &str) -> Option<&str> {
+        self.shared
+            .negotiated_experimental
+            .get()?
+            .get(key)
+            .map(String::as_str)
+    }
+
+    /// Whether the peer advertised `key` under `capabilities.experimental`
+    /// during initialization, with or without versions.
+    pub fn peer_supports_experimental(&self, key: &str) -> bool {
+        self.peer_info()
+            .and_then(R::peer_experimental)
+            .is_some_and(|experimental| experimental.contains_key(key))
+    }
+
+    pub(crate) fn set_experimental(
+        &self,
+        ours: Option<&ExperimentalCapabilities>,
+        theirs: Option<&ExperimentalCapabilities>,
+    ) {
+        let negotiated = match (ours, theirs) {
+            (Some(ours), Some(theirs)) => ours.negotiate_versions(theirs),
+            _ => Default::default(),
+        };
+        let _ = self.shared.negotiated_experimental.set(negotiated);
+    }
+
+    /// What the local side wanted during initialization and the peer
+    /// lacks. `None` for services started without the handshake.
+    pub fn compatibility_report(&self) -> Option<&CompatibilityReport> {
+        self.shared.compatibility.get()
+    }
+
+    pub(crate) fn set_compatibility_report(&self, report: CompatibilityReport) {
+        let _ = self.shared.compatibility.set(report);
+    }
+
     pub fn is_transport_closed(&self) -> bool {
         self.tx.is_closed()
     }
+
+    /// Resolves once the session has ended and nothing more can be sent.
+    pub async fn closed(&self) {
+        self.tx.closed().await
+    }
+
+    /// Whether `self` and `other` are handles to the same session.
+    pub fn is_same_session(&self, other: &Peer<R>) -> bool {
+        self.tx.same_channel(&other.tx)
+    }
+
+    /// Create a [`WeakPeer`] that doesn't keep the session's channel open.
+    pub fn downgrade(&self) -> WeakPeer<R> {
+        WeakPeer {
+            tx: self.tx.downgrade(),
+            shared: self.shared.clone(),
+            request_id_provider: self.request_id_provider.clone(),
+            retry_policy: self.retry_policy.clone(),
+            interceptors: self.interceptors.clone(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -437,6 +1116,7 @@ pub struct RunningService<R: ServiceRole, S: Service<R>> {
     handle: Option<tokio::task::JoinHandle<QuitReason>>,
     cancellation_token: CancellationToken,
     dg: DropGuard,
+    state: tokio::sync::watch::Receiver<ConnectionState>,
 }
 impl<R: ServiceRole, S: Service<R>> Deref for RunningService<R, S> {
     type Target = Peer<R>;
@@ -460,6 +1140,18 @@ impl<R: ServiceRole, S: Service<R>> RunningService<R, S> {
         RunningServiceCancellationToken(self.cancellation_token.clone())
     }
 
+    /// Follow the state of the connection, e.g. to show it in a UI.
+    ///
+    /// Starts out [`Ready`](ConnectionState::Ready), goes
+    /// [`Reconnecting`](ConnectionState::Reconnecting) and back while a
+    /// transport that reports it, such as the streamable HTTP client with a
+    /// [request queue](crate::transport::streamable_http_client::RequestQueueConfig),
+    /// has lost the peer, and ends [`Closed`](ConnectionState::Closed) once
+    /// the service stops.
+    pub fn state_changes(&self) -> tokio::sync::watch::Receiver<ConnectionState> {
+        self.state.clone()
+    }
+
     /// Returns true if the service has been closed or cancelled.
     #[inline]
     pub fn is_closed(&self) -> bool {
@@ -574,6 +1266,22 @@ pub enum QuitReason {
     Cancelled,
     Closed,
     JoinError(tokio::task::JoinError),
+    /// One of the service's [`SessionTimeouts`] fired.
+    Expired(SessionExpiry),
+    /// The transport stopped and told why, see
+    /// [`Transport::close_reason`](crate::transport::Transport::close_reason).
+    Transport(CloseReason),
+    /// A handler panicked under [`PanicPolicy::CloseSession`], with the
+    /// panic message.
+    Panicked(String),
+}
+
+impl QuitReason {
+    /// The cloneable summary of this reason, e.g. to decide whether to
+    /// [reconnect](CloseReason::should_reconnect).
+    pub fn close_reason(&self) -> CloseReason {
+        CloseReason::from(self)
+    }
 }
 
 /// Request execution context
@@ -588,6 +1296,21 @@ pub struct RequestContext<R: ServiceRole> {
     pub peer: Peer<R>,
 }
 
+impl<R: ServiceRole> RequestContext<R> {
+    /// When the peer stops waiting for this request, if it sent a budget.
+    pub fn deadline(&self) -> Option<tokio::time::Instant> {
+        self.extensions
+            .get::<RequestDeadline>()
+            .map(|deadline| deadline.0)
+    }
+
+    /// Time left before [`deadline`](Self::deadline), saturating at zero.
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.deadline()
+            .map(|deadline| deadline.saturating_duration_since(tokio::time::Instant::now()))
+    }
+}
+
 /// Request execution context
 #[derive(Debug, Clone)]
 pub struct NotificationContext<R: ServiceRole> {
@@ -625,13 +1348,32 @@ where
     T: IntoTransport<R, E, A>,
     E: std::error::Error + Send + Sync + 'static,
 {
-    let (peer, peer_rx) = Peer::new(Arc::new(AtomicU32RequestIdProvider::default()), peer_info);
-    serve_inner(service, transport.into_transport(), peer, peer_rx, ct)
+    let transport = transport.into_transport();
+    let memory = transport.memory_account().unwrap_or_default();
+    let config = service.config();
+    let (peer, peer_rx) = Peer::new(config.request_id_provider.clone(), peer_info, memory);
+    serve_inner(service, config, transport, peer, peer_rx, ct)
+}
+
+/// A response on its way from a request handler to the transport.
+#[derive(Debug)]
+struct OutgoingResponse<R: ServiceRole> {
+    message: TxJsonRpcMessage<R>,
+    timings: Option<ResponseTimings>,
+}
+
+/// The spans still open when a response leaves its handler, if
+/// [`ServiceConfig::request_timings`] is on.
+#[derive(Debug)]
+struct ResponseTimings {
+    request: tracing::Span,
+    queue: tracing::Span,
 }
 
 #[instrument(skip_all)]
 fn serve_inner<R, S, T>(
     service: S,
+    config: ServiceConfig,
     transport: T,
     peer: Peer<R>,
     mut peer_rx: tokio::sync::mpsc::Receiver<PeerSinkMessage<R>>,
@@ -644,7 +1386,7 @@ where
 {
     const SINK_PROXY_BUFFER_SIZE: usize = 64;
     let (sink_proxy_tx, mut sink_proxy_rx) =
-        tokio::sync::mpsc::channel::<TxJsonRpcMessage<R>>(SINK_PROXY_BUFFER_SIZE);
+        tokio::sync::mpsc::channel::<OutgoingResponse<R>>(SINK_PROXY_BUFFER_SIZE);
     let peer_info = peer.peer_info();
     if R::IS_CLIENT {
         tracing::info!(?peer_info, "Service initialized as client");
@@ -663,8 +1405,33 @@ where
     // let mut stream = std::pin::pin!(stream);
     let serve_loop_ct = ct.child_token();
     let peer_return: Peer<R> = peer.clone();
+    let low_priority = peer.shared.low_priority.clone();
+    // progress and logging must not skip ahead of what was sent before them
+    let fifo = config.sequential || config.delivery_order == DeliveryOrder::Fifo;
+    peer.shared
+        .in_order
+        .store(fifo, std::sync::atomic::Ordering::Relaxed);
+    let pending = peer.shared.pending.clone();
     let current_span = tracing::Span::current();
-    let handle = tokio::spawn(async move {
+    let (state_tx, state) = tokio::sync::watch::channel(ConnectionState::Ready);
+    let session_id = transport.session_id();
+    if let Some(mut transport_state) = transport.state_changes() {
+        let state_tx = state_tx.clone();
+        spawn_task(TaskKind::Background, "rmcp::connection_state", async move {
+            while transport_state.changed().await.is_ok() {
+                let next = transport_state.borrow_and_update().clone();
+                state_tx.send_if_modified(|current| {
+                    // closing is final, and only the service itself knows why
+                    if current.is_closed() || next.is_closed() || *current == next {
+                        return false;
+                    }
+                    *current = next;
+                    true
+                });
+            }
+        });
+    }
+    let handle = spawn_task(TaskKind::ServeLoop, "rmcp::serve_loop", async move {
         let mut transport = transport.into_transport();
         let mut batch_messages = VecDeque::<RxJsonRpcMessage<R>>::new();
         let mut send_task_set = tokio::task::JoinSet::<SendTaskResult>::new();
@@ -679,20 +1446,45 @@ where
                 cancellation_param: Option<CancelledNotificationParam>,
                 result: Result<(), DynamicTransportError>,
             },
+            Response {
+                result: Result<(), DynamicTransportError>,
+            },
         }
         #[derive(Debug)]
         enum Event<R: ServiceRole> {
             ProxyMessage(PeerSinkMessage<R>),
             PeerMessage(RxJsonRpcMessage<R>),
-            ToSink(TxJsonRpcMessage<R>),
+            ToSink(OutgoingResponse<R>),
             SendTaskResult(SendTaskResult),
         }
 
+        let timeouts = config.session_timeouts;
+        let request_timings = config.request_timings;
+        let panic_guard = PanicGuard::new(config.panic_policy, serve_loop_ct.clone());
+        let sequential = config.sequential;
+        let mut sequencer = sequential.then(Sequencer::default);
+        let mut notification_sequencer = (fifo && !sequential).then(Sequencer::default);
+        let mut writes = fifo.then(Sequencer::default);
+        let started_at = tokio::time::Instant::now();
+        let mut last_activity = started_at;
+
         let quit_reason = loop {
+            // not idle while handling a request or still writing out its
+            // response
+            let busy = !local_ct_pool.is_empty() || !send_task_set.is_empty();
+            let next_expiry = timeouts.next_expiry(started_at, last_activity, busy);
             let evt = if let Some(m) = batch_messages.pop_front() {
                 Event::PeerMessage(m)
             } else {
+                // biased: responses first, then our own requests and
+                // notifications, and low-priority notifications only when
+                // nothing else is ready.
                 tokio::select! {
+                    biased;
+                    _ = serve_loop_ct.cancelled() => {
+                        tracing::info!("task cancelled");
+                        break panic_guard.quit_reason()
+                    }
                     m = sink_proxy_rx.recv(), if !sink_proxy_rx.is_closed() => {
                         if let Some(m) = m {
                             Event::ToSink(m)
@@ -700,15 +1492,6 @@ where
                             continue
                         }
                     }
-                    m = transport.receive() => {
-                        if let Some(m) = m {
-                            Event::PeerMessage(m)
-                        } else {
-                            // input stream closed
-                            tracing::info!("input stream terminated");
-                            break QuitReason::Closed
-                        }
-                    }
                     m = peer_rx.recv(), if !peer_rx.is_closed() => {
                         if let Some(m) = m {
                             Event::ProxyMessage(m)
@@ -731,17 +1514,34 @@ where
                             }
                         }
                     }
-                    _ = serve_loop_ct.cancelled() => {
-                        tracing::info!("task cancelled");
-                        break QuitReason::Cancelled
+                    m = transport.receive() => {
+                        if let Some(m) = m {
+                            Event::PeerMessage(m)
+                        } else {
+                            // input stream closed
+                            tracing::info!("input stream terminated");
+                            break transport
+                                .close_reason()
+                                .map_or(QuitReason::Closed, QuitReason::Transport)
+                        }
                     }
+                    expiry = expiry::expire_at(next_expiry) => {
+                        tracing::info!(%expiry, "session expired");
+                        serve_loop_ct.cancel();
+                        break QuitReason::Expired(expiry)
+                    }
+                    m = low_priority.recv() => Event::ProxyMessage(m),
                 }
             };
 
             tracing::trace!(?evt, "new event");
+            if matches!(evt, Event::PeerMessage(_)) {
+                last_activity = tokio::time::Instant::now();
+            }
             match evt {
                 Event::SendTaskResult(SendTaskResult::Request { id, result }) => {
                     if let Err(e) = result {
+                        pending.remove(&id);
                         if let Some(responder) = local_responder_pool.remove(&id) {
                             let _ = responder.send(Err(ServiceError::TransportSend(e)));
                         }
@@ -759,6 +1559,7 @@ where
                     };
                     let _ = responder.send(response);
                     if let Some(param) = cancellation_param {
+                        pending.remove(&param.request_id);
                         if let Some(responder) = local_responder_pool.remove(&param.request_id) {
                             tracing::info!(id = %param.request_id, reason = param.reason, "cancelled");
                             let _response_result = responder.send(Err(ServiceError::Cancelled {
@@ -767,8 +1568,13 @@ where
                         }
                     }
                 }
+                Event::SendTaskResult(SendTaskResult::Response { result }) => {
+                    if let Err(error) = result {
+                        tracing::error!(%error, "fail to response message");
+                    }
+                }
                 // response and error
-                Event::ToSink(m) => {
+                Event::ToSink(OutgoingResponse { message: m, timings }) => {
                     if let Some(id) = match &m {
                         JsonRpcMessage::Response(response) => Some(&response.id),
                         JsonRpcMessage::Error(error) => Some(&error.id),
@@ -777,14 +1583,23 @@ where
                         if let Some(ct) = local_ct_pool.remove(id) {
                             ct.cancel();
                         }
-                        let send = transport.send(m);
-                        let current_span = tracing::Span::current();
-                        tokio::spawn(async move {
-                            let send_result = send.await;
-                            if let Err(error) = send_result {
-                                tracing::error!(%error, "fail to response message");
+                        let send = in_turn(writes.as_mut().map(Sequencer::next_turn), transport.send(m));
+                        let span = match timings {
+                            Some(timings) => {
+                                drop(timings.queue);
+                                tracing::info_span!(parent: &timings.request, "mcp.transport.write")
                             }
-                        }.instrument(current_span));
+                            None => tracing::Span::current(),
+                        };
+                        // tracked, so the session isn't idle before the
+                        // response is out
+                        let send = send.map(|result| SendTaskResult::Response {
+                            result: result.map_err(DynamicTransportError::new::<T, R>),
+                        });
+                        send_task_set.spawn(counted_task(
+                            TaskKind::TransportWrite,
+                            send.instrument(span),
+                        ));
                     }
                 }
                 Event::ProxyMessage(PeerSinkMessage::Request {
@@ -793,14 +1608,21 @@ where
                     responder,
                 }) => {
                     local_responder_pool.insert(id.clone(), responder);
-                    let send = transport.send(JsonRpcMessage::request(request, id.clone()));
+                    let send = in_turn(
+                        writes.as_mut().map(Sequencer::next_turn),
+                        transport.send(JsonRpcMessage::request(request, id.clone())),
+                    );
                     {
                         let id = id.clone();
                         let current_span = tracing::Span::current();
-                        send_task_set.spawn(send.map(move |r| SendTaskResult::Request {
+                        let send = send.map(move |r| SendTaskResult::Request {
                             id,
                             result: r.map_err(DynamicTransportError::new::<T, R>),
-                        }).instrument(current_span));
+                        });
+                        send_task_set.spawn(counted_task(
+                            TaskKind::TransportWrite,
+                            send.instrument(current_span),
+                        ));
                     }
                 }
                 Event::ProxyMessage(PeerSinkMessage::Notification {
@@ -816,13 +1638,20 @@ where
                         }
                         Err(notification) => notification,
                     };
-                    let send = transport.send(JsonRpcMessage::notification(notification));
+                    let send = in_turn(
+                        writes.as_mut().map(Sequencer::next_turn),
+                        transport.send(JsonRpcMessage::notification(notification)),
+                    );
                     let current_span = tracing::Span::current();
-                    send_task_set.spawn(send.map(move |result| SendTaskResult::Notification {
+                    let send = send.map(move |result| SendTaskResult::Notification {
                         responder,
                         cancellation_param,
                         result: result.map_err(DynamicTransportError::new::<T, R>),
-                    }).instrument(current_span));
+                    });
+                    send_task_set.spawn(counted_task(
+                        TaskKind::TransportWrite,
+                        send.instrument(current_span),
+                    ));
                 }
                 Event::PeerMessage(JsonRpcMessage::Request(JsonRpcRequest {
                     id,
@@ -830,6 +1659,26 @@ where
                     ..
                 })) => {
                     tracing::debug!(%id, ?request, "received request");
+                    if local_ct_pool.contains_key(&id) {
+                        // answering through the sink would cancel the
+                        // request already using this id
+                        tracing::warn!(%id, "peer reused the id of a request in flight");
+                        violations::record_duplicate_request_id();
+                        let error = McpError::invalid_request(
+                            format!("request id {id} is already in use"),
+                            Some(serde_json::json!({ "id": id })),
+                        );
+                        let send = in_turn(
+                            writes.as_mut().map(Sequencer::next_turn),
+                            transport.send(JsonRpcMessage::error(error, id)),
+                        );
+                        spawn_task(TaskKind::TransportWrite, "rmcp::response", async move {
+                            if let Err(error) = send.await {
+                                tracing::error!(%error, "fail to response message");
+                            }
+                        }.instrument(tracing::Span::current()));
+                        continue;
+                    }
                     {
                         let service = shared_service.clone();
                         let sink = sink_proxy_tx.clone();
@@ -842,6 +1691,12 @@ where
                         // swap meta firstly, otherwise progress token will be lost
                         std::mem::swap(&mut meta, request.get_meta_mut());
                         std::mem::swap(&mut extensions, request.extensions_mut());
+                        let deadline = meta
+                            .get_budget()
+                            .map(|budget| tokio::time::Instant::now() + budget);
+                        if let Some(deadline) = deadline {
+                            extensions.insert(RequestDeadline(deadline));
+                        }
                         let context = RequestContext {
                             ct: context_ct,
                             id: id.clone(),
@@ -849,11 +1704,37 @@ where
                             meta,
                             extensions,
                         };
-                        let current_span = tracing::Span::current();
-                        tokio::spawn(async move {
-                            let result = service
-                                .handle_request(request, context)
-                                .await;
+                        let span = tracing::info_span!(
+                            "mcp.request",
+                            jsonrpc.request.id = %id,
+                            mcp.method.name = R::peer_request_method(&request),
+                            mcp.session.id = session_id.as_deref(),
+                        );
+                        let queue = request_timings
+                            .then(|| tracing::info_span!(parent: &span, "mcp.request.queue"));
+                        let panic_guard = panic_guard.clone();
+                        let mut turn = sequencer.as_mut().map(Sequencer::next_turn);
+                        spawn_task(TaskKind::Handler, "rmcp::request", async move {
+                            if let Some(turn) = &mut turn {
+                                turn.wait().await;
+                            }
+                            drop(queue);
+                            let handle = service.handle_request(request, context);
+                            let handle = async move {
+                                match deadline {
+                                    Some(deadline) => REQUEST_DEADLINE.scope(deadline, handle).await,
+                                    None => handle.await,
+                                }
+                            };
+                            let handle = panic_guard.catch(handle);
+                            let result = if request_timings {
+                                handle.instrument(tracing::info_span!("mcp.handler")).await
+                            } else {
+                                handle.await
+                            };
+                            let result = result.unwrap_or_else(|_| {
+                                Err(McpError::internal_error("request handler panicked", None))
+                            });
                             let response = match result {
                                 Ok(result) => {
                                     tracing::debug!(%id, ?result, "response message");
@@ -864,8 +1745,13 @@ where
                                     JsonRpcMessage::error(error, id)
                                 }
                             };
-                            let _send_result = sink.send(response).await;
-                        }.instrument(current_span));
+                            let timings = request_timings.then(|| ResponseTimings {
+                                request: tracing::Span::current(),
+                                queue: tracing::info_span!("mcp.response.queue"),
+                            });
+                            let _send_result = sink.send(OutgoingResponse { message: response, timings }).await;
+                            drop(turn);
+                        }.instrument(span));
                     }
                 }
                 Event::PeerMessage(JsonRpcMessage::Notification(JsonRpcNotification {
@@ -889,20 +1775,33 @@ where
                         let mut extensions = Extensions::new();
                         let mut meta = Meta::new();
                         // avoid clone
-                        std::mem::swap(&mut extensions, notification.extensions_mut());
+                        // the meta lives in the extensions, so take it out first
                         std::mem::swap(&mut meta, notification.get_meta_mut());
+                        std::mem::swap(&mut extensions, notification.extensions_mut());
                         let context = NotificationContext {
                             peer: peer.clone(),
                             meta,
                             extensions,
                         };
-                        let current_span = tracing::Span::current();
-                        tokio::spawn(async move {
-                            let result = service.handle_notification(notification, context).await;
-                            if let Err(error) = result {
+                        let span = tracing::info_span!(
+                            "mcp.notification",
+                            mcp.method.name = R::peer_notification_method(&notification),
+                            mcp.session.id = session_id.as_deref(),
+                        );
+                        let panic_guard = panic_guard.clone();
+                        let mut turn = sequencer
+                            .as_mut()
+                            .or(notification_sequencer.as_mut())
+                            .map(Sequencer::next_turn);
+                        spawn_task(TaskKind::Handler, "rmcp::notification", async move {
+                            if let Some(turn) = &mut turn {
+                                turn.wait().await;
+                            }
+                            let result = panic_guard.catch(service.handle_notification(notification, context)).await;
+                            if let Ok(Err(error)) = result {
                                 tracing::warn!(%error, "Error sending notification");
                             }
-                        }.instrument(current_span));
+                        }.instrument(span));
                     }
                 }
                 Event::PeerMessage(JsonRpcMessage::Response(JsonRpcResponse {
@@ -910,6 +1809,7 @@ where
                     id,
                     ..
                 })) => {
+                    pending.remove(&id);
                     if let Some(responder) = local_responder_pool.remove(&id) {
                         let response_result = responder.send(Ok(result));
                         if let Err(_error) = response_result {
@@ -918,6 +1818,7 @@ where
                     }
                 }
                 Event::PeerMessage(JsonRpcMessage::Error(JsonRpcError { error, id, .. })) => {
+                    pending.remove(&id);
                     if let Some(responder) = local_responder_pool.remove(&id) {
                         let _response_result = responder.send(Err(ServiceError::McpError(error)));
                         if let Err(_error) = _response_result {
@@ -927,11 +1828,30 @@ where
                 }
             }
         };
+        if let QuitReason::Expired(expiry) = &quit_reason {
+            if let Some(notification) = R::expiry_notification(*expiry) {
+                const EXPIRY_NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(1);
+                let send = transport.send(JsonRpcMessage::notification(notification));
+                let sent = tokio::time::timeout(EXPIRY_NOTIFICATION_TIMEOUT, send).await;
+                if !matches!(sent, Ok(Ok(()))) {
+                    tracing::debug!(%expiry, "failed to notify peer of session expiry");
+                }
+            }
+        }
+        low_priority.close();
+        // the responders are gone with the loop
+        pending.clear();
         let sink_close_result = transport.close().await;
         if let Err(e) = sink_close_result {
             tracing::error!(%e, "fail to close sink");
         }
         tracing::info!(?quit_reason, "serve finished");
+        if let Some(on_session_end) = &config.on_session_end {
+            on_session_end(&quit_reason);
+        }
+        state_tx.send_replace(ConnectionState::Closed {
+            reason: CloseReason::from(&quit_reason),
+        });
         quit_reason
     }.instrument(current_span));
     RunningService {
@@ -940,5 +1860,6 @@ where
         handle: Some(handle),
         cancellation_token: ct.clone(),
         dg: ct.drop_guard(),
+        state,
     }
 }
@@ -7,17 +7,19 @@ use super::*;
 use crate::{
     model::{
         ArgumentInfo, CallToolRequest, CallToolRequestParams, CallToolResult,
-        CancelledNotification, CancelledNotificationParam, ClientInfo, ClientJsonRpcMessage,
-        ClientNotification, ClientRequest, ClientResult, CompleteRequest, CompleteRequestParams,
-        CompleteResult, CompletionContext, CompletionInfo, ElicitationCompleteNotification,
-        ElicitationCompleteNotificationParams, ErrorData, GetPromptRequest,
-        GetPromptRequestParams, GetPromptResult, InitializeRequest, InitializedNotification,
+        CancelledNotification, CancelledNotificationParam, ClientCapabilities, ClientInfo,
+        ClientJsonRpcMessage, ClientNotification, ClientRequest, ClientResult, CompleteRequest,
+        CompleteRequestParams, CompleteResult, CompletionContext, CompletionInfo,
+        ElicitationCompleteNotification, ElicitationCompleteNotificationParams, ErrorData,
+        ExperimentalCapabilities, GetPromptRequest, GetPromptRequestParams, GetPromptResult,
+        Implementation, InitializeRequest, InitializeResult, InitializedNotification, JsonObject,
         JsonRpcResponse, ListPromptsRequest, ListPromptsResult, ListResourceTemplatesRequest,
         ListResourceTemplatesResult, ListResourcesRequest, ListResourcesResult, ListToolsRequest,
         ListToolsResult, PaginatedRequestParams, ProgressNotification, ProgressNotificationParam,
-        ReadResourceRequest, ReadResourceRequestParams, ReadResourceResult, Reference, RequestId,
-        RootsListChangedNotification, ServerInfo, ServerJsonRpcMessage, ServerNotification,
-        ServerRequest, ServerResult, SetLevelRequest, SetLevelRequestParams, SubscribeRequest,
+        ProtocolVersion, ReadResourceRequest, ReadResourceRequestParams, ReadResourceResult,
+        Reference, RequestId, RootsListChangedNotification, SAMPLING_CHUNK_META_KEY,
+        ServerCapabilities, ServerInfo, ServerJsonRpcMessage, ServerNotification, ServerRequest,
+        ServerResult, SetLevelRequest, SetLevelRequestParams, SubscribeRequest,
         SubscribeRequestParams, UnsubscribeRequest, UnsubscribeRequestParams,
     },
     transport::DynamicTransportError,
@@ -37,9 +39,23 @@ pub enum ClientInitializeError {
     #[error("conflict initialized response id: expected {0}, got {1}")]
     ConflictInitResponseId(RequestId, RequestId),
 
+    /// Not returned by the handshake anymore, which returns
+    /// [`Closed`](Self::Closed) with diagnostics instead.
+    #[deprecated(note = "the handshake returns `Closed` instead")]
     #[error("connection closed: {0}")]
     ConnectionClosed(String),
 
+    /// The connection closed before the `initialize` response.
+    #[error("connection closed before the initialize response\n{0}")]
+    Closed(Box<HandshakeDiagnostics>),
+
+    /// No `initialize` response within [`ClientOptions::handshake_timeout`].
+    #[error("no initialize response within {timeout:?}\n{diagnostics}")]
+    Timeout {
+        timeout: Duration,
+        diagnostics: Box<HandshakeDiagnostics>,
+    },
+
     #[error("Send message error {error}, when {context}")]
     TransportError {
         error: DynamicTransportError,
@@ -54,6 +70,14 @@ pub enum ClientInitializeError {
 }
 
 impl ClientInitializeError {
+    /// What went through the transport, for the errors that keep it.
+    pub fn diagnostics(&self) -> Option<&HandshakeDiagnostics> {
+        match self {
+            Self::Closed(diagnostics) | Self::Timeout { diagnostics, .. } => Some(diagnostics),
+            _ => None,
+        }
+    }
+
     pub fn transport<T: Transport<RoleClient> + 'static>(
         error: T::Error,
         context: impl Into<Cow<'static, str>>,
@@ -68,21 +92,26 @@ impl ClientInitializeError {
 /// Helper function to get the next message from the stream
 async fn expect_next_message<T>(
     transport: &mut T,
-    context: &str,
+    diagnostics: &mut HandshakeDiagnostics,
 ) -> Result<ServerJsonRpcMessage, ClientInitializeError>
 where
     T: Transport<RoleClient>,
 {
-    transport
-        .receive()
-        .await
-        .ok_or_else(|| ClientInitializeError::ConnectionClosed(context.to_string()))
+    match transport.receive().await {
+        Some(message) => {
+            diagnostics.record(&message);
+            Ok(message)
+        }
+        None => Err(ClientInitializeError::Closed(
+            diagnostics.snapshot(transport.close_reason()),
+        )),
+    }
 }
 
 /// Helper function to expect a response from the stream
 async fn expect_response<T, S>(
     transport: &mut T,
-    context: &str,
+    diagnostics: &mut HandshakeDiagnostics,
     service: &S,
     peer: Peer<RoleClient>,
 ) -> Result<(ServerResult, RequestId), ClientInitializeError>
@@ -91,7 +120,7 @@ where
     S: Service<RoleClient>,
 {
     loop {
-        let message = expect_next_message(transport, context).await?;
+        let message = expect_next_message(transport, diagnostics).await?;
         match message {
             // Expected message to complete the initialization
             ServerJsonRpcMessage::Response(JsonRpcResponse { id, result, .. }) => {
@@ -154,6 +183,32 @@ impl ServiceRole for RoleClient {
     type PeerInfo = ServerInfo;
     type InitializeError = ClientInitializeError;
     const IS_CLIENT: bool = true;
+    fn request_method(request: &ClientRequest) -> &str {
+        request.method()
+    }
+    fn peer_request_method(request: &ServerRequest) -> &str {
+        request.method()
+    }
+    fn peer_notification_method(notification: &ServerNotification) -> &str {
+        notification.method()
+    }
+    fn is_idempotent(request: &ClientRequest) -> bool {
+        request.is_idempotent()
+    }
+    fn is_low_priority(notification: &ClientNotification) -> bool {
+        // sampling chunks make up a stream, dropping one would garble it
+        matches!(
+            notification,
+            ClientNotification::ProgressNotification(progress)
+                if progress
+                    .extensions
+                    .get::<Meta>()
+                    .is_none_or(|meta| !meta.contains_key(SAMPLING_CHUNK_META_KEY))
+        )
+    }
+    fn peer_experimental(info: &ServerInfo) -> Option<&ExperimentalCapabilities> {
+        info.capabilities.experimental.as_ref()
+    }
 }
 
 pub type ServerSink = Peer<RoleClient>;
@@ -190,13 +245,177 @@ pub async fn serve_client_with_ct<S, T, E, A>(
     transport: T,
     ct: CancellationToken,
 ) -> Result<RunningService<RoleClient, S>, ClientInitializeError>
+where
+    S: Service<RoleClient>,
+    T: IntoTransport<RoleClient, E, A>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    serve_client_with_options_and_ct(service, transport, ClientOptions::default(), ct).await
+}
+
+/// Overrides for the `initialize` request, on top of what
+/// [`Service::get_info`] returns.
+///
+/// ```rust,no_run
+/// # use rmcp::{model::{Implementation, ProtocolVersion, object}, service::{ClientOptions, serve_client_with_options}};
+/// # async fn demo(transport: tokio::io::DuplexStream) -> anyhow::Result<()> {
+/// let options = ClientOptions::default()
+///     .protocol_version(ProtocolVersion::V_2025_03_26)
+///     .client_info(Implementation {
+///         name: "my-host".into(),
+///         version: "1.2.0".into(),
+///         ..Default::default()
+///     })
+///     .experimental("acme/streaming", object(serde_json::json!({ "chunked": true })));
+/// let client = serve_client_with_options((), transport, options).await?;
+/// let server_info = client.initialize_result();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClientOptions {
+    /// Protocol version to ask for instead of the handler's.
+    pub protocol_version: Option<ProtocolVersion>,
+    /// Client implementation to announce instead of the handler's.
+    pub client_info: Option<Implementation>,
+    /// Capabilities to announce instead of the handler's.
+    pub capabilities: Option<ClientCapabilities>,
+    /// Experimental capabilities added to the announced ones, replacing
+    /// those of the same name.
+    pub experimental: ExperimentalCapabilities,
+    /// `_meta` of the request.
+    pub meta: Option<Meta>,
+    /// How long to wait for the `initialize` response once the request is
+    /// sent, without limit by default. Connecting and sending the request
+    /// are not covered.
+    pub handshake_timeout: Option<Duration>,
+    /// Server capabilities to check for, as paths like `resources.subscribe`,
+    /// in [`Peer::compatibility_report`].
+    pub wanted_features: Vec<String>,
+    /// Log a warning after initialize if the
+    /// [`CompatibilityReport`] has something to say.
+    pub log_compatibility: bool,
+    /// Policy for retrying idempotent requests sent through the
+    /// [`RunningService`] and every clone of its peer.
+    pub retry_policy: Option<RetryPolicy>,
+}
+
+impl ClientOptions {
+    pub fn protocol_version(self, protocol_version: ProtocolVersion) -> Self {
+        Self {
+            protocol_version: Some(protocol_version),
+            ..self
+        }
+    }
+
+    pub fn client_info(self, client_info: Implementation) -> Self {
+        Self {
+            client_info: Some(client_info),
+            ..self
+        }
+    }
+
+    pub fn capabilities(self, capabilities: ClientCapabilities) -> Self {
+        Self {
+            capabilities: Some(capabilities),
+            ..self
+        }
+    }
+
+    pub fn experimental(mut self, name: impl Into<String>, payload: JsonObject) -> Self {
+        self.experimental.insert(name.into(), payload);
+        self
+    }
+
+    pub fn meta(self, meta: Meta) -> Self {
+        Self {
+            meta: Some(meta),
+            ..self
+        }
+    }
+
+    /// Ask for answers in `locale`, a BCP 47 language tag, for the whole
+    /// session. It travels in the `_meta` of the request.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.meta.get_or_insert_default().set_locale(locale);
+        self
+    }
+
+    pub fn handshake_timeout(self, timeout: Duration) -> Self {
+        Self {
+            handshake_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    pub fn want_feature(mut self, path: impl Into<String>) -> Self {
+        self.wanted_features.push(path.into());
+        self
+    }
+
+    pub fn log_compatibility(self) -> Self {
+        Self {
+            log_compatibility: true,
+            ..self
+        }
+    }
+
+    pub fn retry_policy(self, policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy: Some(policy),
+            ..self
+        }
+    }
+
+    /// The parameters of the `initialize` request, starting from `info`.
+    /// [`meta`](Self::meta) travels in the request extensions instead.
+    pub fn apply(&self, mut info: ClientInfo) -> ClientInfo {
+        if let Some(protocol_version) = &self.protocol_version {
+            info.protocol_version = protocol_version.clone();
+        }
+        if let Some(client_info) = &self.client_info {
+            info.client_info = client_info.clone();
+        }
+        if let Some(capabilities) = &self.capabilities {
+            info.capabilities = capabilities.clone();
+        }
+        if !self.experimental.is_empty() {
+            info.capabilities
+                .experimental
+                .get_or_insert_default()
+                .extend(self.experimental.clone());
+        }
+        info
+    }
+}
+
+/// [`serve_client`] with a customized `initialize` request.
+pub async fn serve_client_with_options<S, T, E, A>(
+    service: S,
+    transport: T,
+    options: ClientOptions,
+) -> Result<RunningService<RoleClient, S>, ClientInitializeError>
+where
+    S: Service<RoleClient>,
+    T: IntoTransport<RoleClient, E, A>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    serve_client_with_options_and_ct(service, transport, options, Default::default()).await
+}
+
+pub async fn serve_client_with_options_and_ct<S, T, E, A>(
+    service: S,
+    transport: T,
+    options: ClientOptions,
+    ct: CancellationToken,
+) -> Result<RunningService<RoleClient, S>, ClientInitializeError>
 where
     S: Service<RoleClient>,
     T: IntoTransport<RoleClient, E, A>,
     E: std::error::Error + Send + Sync + 'static,
 {
     tokio::select! {
-        result = serve_client_with_ct_inner(service, transport.into_transport(), ct.clone()) => { result }
+        result = serve_client_with_ct_inner(service, transport.into_transport(), options, ct.clone()) => { result }
         _ = ct.cancelled() => {
             Err(ClientInitializeError::Cancelled)
         }
@@ -206,6 +425,7 @@ where
 async fn serve_client_with_ct_inner<S, T>(
     service: S,
     transport: T,
+    options: ClientOptions,
     ct: CancellationToken,
 ) -> Result<RunningService<RoleClient, S>, ClientInitializeError>
 where
@@ -213,35 +433,58 @@ where
     T: Transport<RoleClient> + 'static,
 {
     let mut transport = transport.into_transport();
-    let id_provider = <Arc<AtomicU32RequestIdProvider>>::default();
+    let config = service.config();
+    let id_provider = config.request_id_provider.clone();
 
     // service
     let id = id_provider.next_request_id();
-    let init_request = InitializeRequest {
+    let mut init_request = InitializeRequest {
         method: Default::default(),
-        params: service.get_info(),
+        params: options.apply(service.get_info()),
         extensions: Default::default(),
     };
+    let experimental = init_request.params.capabilities.experimental.clone();
+    let requested_version = init_request.params.protocol_version.clone();
+    if let Some(meta) = options.meta {
+        init_request.extensions.insert(meta);
+    }
+    let request =
+        ClientJsonRpcMessage::request(ClientRequest::InitializeRequest(init_request), id.clone());
+    let mut diagnostics =
+        HandshakeDiagnostics::new(serde_json::to_value(&request).unwrap_or_default());
     transport
-        .send(ClientJsonRpcMessage::request(
-            ClientRequest::InitializeRequest(init_request),
-            id.clone(),
-        ))
+        .send(request)
         .await
         .map_err(|error| ClientInitializeError::TransportError {
             error: DynamicTransportError::new::<T, _>(error),
             context: "send initialize request".into(),
         })?;
 
-    let (peer, peer_rx) = Peer::new(id_provider, None);
+    let (peer, peer_rx) = Peer::new(
+        id_provider,
+        None,
+        transport.memory_account().unwrap_or_default(),
+    );
+    let peer = match options.retry_policy {
+        Some(policy) => peer.with_retry_policy(policy),
+        None => peer,
+    };
 
-    let (response, response_id) = expect_response(
-        &mut transport,
-        "initialize response",
-        &service,
-        peer.clone(),
-    )
-    .await?;
+    let response = expect_response(&mut transport, &mut diagnostics, &service, peer.clone());
+    let (response, response_id) = match options.handshake_timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, response).await {
+            Ok(response) => response?,
+            Err(_) => {
+                let error = ClientInitializeError::Timeout {
+                    timeout,
+                    diagnostics: diagnostics.snapshot(None),
+                };
+                tracing::debug!("{error}");
+                return Err(error);
+            }
+        },
+        None => response.await?,
+    };
 
     if id != response_id {
         return Err(ClientInitializeError::ConflictInitResponseId(
@@ -253,6 +496,20 @@ where
     let ServerResult::InitializeResult(initialize_result) = response else {
         return Err(ClientInitializeError::ExpectedInitResult(Some(response)));
     };
+    peer.set_protocol_version(initialize_result.protocol_version.clone());
+    peer.set_experimental(
+        experimental.as_ref(),
+        initialize_result.capabilities.experimental.as_ref(),
+    );
+    let report = CompatibilityReport::new(
+        requested_version,
+        initialize_result.protocol_version.clone(),
+        &options.wanted_features,
+        experimental.as_ref(),
+        &serde_json::to_value(&initialize_result.capabilities).unwrap_or_default(),
+    );
+    report.log(options.log_compatibility);
+    peer.set_compatibility_report(report);
     peer.set_peer_info(initialize_result);
 
     // send notification
@@ -265,7 +522,7 @@ where
     transport.send(notification).await.map_err(|error| {
         ClientInitializeError::transport::<T>(error, "send initialized notification")
     })?;
-    Ok(serve_inner(service, transport, peer, peer_rx, ct))
+    Ok(serve_inner(service, config, transport, peer, peer_rx, ct))
 }
 
 macro_rules! method {
@@ -377,6 +634,24 @@ impl Peer<RoleClient> {
 }
 
 impl Peer<RoleClient> {
+    /// The server's answer to `initialize`: negotiated protocol version,
+    /// capabilities, implementation and instructions. The same as
+    /// [`peer_info`](Self::peer_info), under a clearer name.
+    pub fn initialize_result(&self) -> Option<&InitializeResult> {
+        self.peer_info()
+    }
+
+    /// What the server declared it supports during initialization.
+    pub fn peer_capabilities(&self) -> Option<&ServerCapabilities> {
+        self.peer_info().map(|info| &info.capabilities)
+    }
+
+    /// The [`instructions`](InitializeResult::instructions) the server gave
+    /// for using it, e.g. to add to the system prompt.
+    pub fn instructions(&self) -> Option<&str> {
+        self.peer_info()?.instructions.as_deref()
+    }
+
     /// A wrapper method for [`Peer<RoleClient>::list_tools`].
     ///
     /// This function will call [`Peer<RoleClient>::list_tools`] multiple times until all tools are listed.
@@ -396,6 +671,20 @@ impl Peer<RoleClient> {
         Ok(tools)
     }
 
+    /// Like [`Peer<RoleClient>::list_all_tools`], but leaves out deprecated
+    /// tools, logging a warning for each one.
+    pub async fn list_current_tools(&self) -> Result<Vec<crate::model::Tool>, ServiceError> {
+        let mut tools = self.list_all_tools().await?;
+        tools.retain(|tool| match tool.deprecation_warning() {
+            Some(warning) => {
+                tracing::warn!("{warning}");
+                false
+            }
+            None => true,
+        });
+        Ok(tools)
+    }
+
     /// A wrapper method for [`Peer<RoleClient>::list_prompts`].
     ///
     /// This function will call [`Peer<RoleClient>::list_prompts`] multiple times until all prompts are listed.
@@ -455,6 +744,39 @@ impl Peer<RoleClient> {
         Ok(resource_templates)
     }
 
+    /// Read `uri` unless the copy tagged `etag` is still current, see
+    /// [`ConditionalReads`](crate::model::ConditionalReads).
+    ///
+    /// Returns `None` if the server answered that it's unchanged. Servers
+    /// without the extension always send the contents.
+    pub async fn read_resource_if_modified(
+        &self,
+        uri: impl Into<String>,
+        etag: Option<&str>,
+    ) -> Result<Option<ReadResourceResult>, ServiceError> {
+        let mut options = PeerRequestOptions::no_options();
+        if let Some(etag) = etag {
+            options
+                .meta
+                .get_or_insert_with(Meta::new)
+                .set_if_none_match(etag);
+        }
+        let request = ClientRequest::ReadResourceRequest(ReadResourceRequest::new(
+            ReadResourceRequestParams::new(uri),
+        ));
+        let result = self
+            .send_request_with_option(request, options)
+            .await?
+            .await_response()
+            .await?;
+        match result {
+            ServerResult::ReadResourceResult(result) => {
+                Ok((!result.is_not_modified()).then_some(result))
+            }
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
     /// Convenient method to get completion suggestions for a prompt argument
     ///
     /// # Arguments
@@ -0,0 +1,169 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::model::{ExperimentalCapabilities, ProtocolVersion};
+
+/// What the local side wanted from the peer during initialization and didn't
+/// get, so integration problems show at connect time rather than at the
+/// first call that fails.
+///
+/// Features are capability paths, e.g. `resources.subscribe` or `tasks`,
+/// and `experimental.<key>` for the experimental capabilities the local
+/// side listed that the peer didn't.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    /// The protocol version the local side asked for or supports.
+    pub requested_version: ProtocolVersion,
+    /// The protocol version of the session.
+    pub negotiated_version: ProtocolVersion,
+    /// Wanted features the peer didn't advertise.
+    pub missing: Vec<String>,
+}
+
+impl CompatibilityReport {
+    /// `peer_capabilities` is the `capabilities` object of the peer, as JSON.
+    pub(crate) fn new(
+        requested_version: ProtocolVersion,
+        negotiated_version: ProtocolVersion,
+        wanted: &[String],
+        ours: Option<&ExperimentalCapabilities>,
+        peer_capabilities: &Value,
+    ) -> Self {
+        let experimental = ours
+            .into_iter()
+            .flat_map(|experimental| experimental.keys())
+            .map(|key| format!("experimental.{key}"));
+        let mut missing = Vec::new();
+        for feature in wanted.iter().cloned().chain(experimental) {
+            if !has_capability(peer_capabilities, &feature) && !missing.contains(&feature) {
+                missing.push(feature);
+            }
+        }
+        Self {
+            requested_version,
+            negotiated_version,
+            missing,
+        }
+    }
+
+    /// Whether the session runs an older protocol version than asked for.
+    pub fn is_downgraded(&self) -> bool {
+        self.negotiated_version < self.requested_version
+    }
+
+    /// Whether the peer has all the local side wanted.
+    pub fn is_complete(&self) -> bool {
+        !self.is_downgraded() && self.missing.is_empty()
+    }
+
+    pub(crate) fn log(&self, warn: bool) {
+        if self.is_complete() {
+            return;
+        }
+        if warn {
+            tracing::warn!("Peer is not fully compatible: {self}");
+        } else {
+            tracing::debug!("Peer is not fully compatible: {self}");
+        }
+    }
+}
+
+/// Whether the dot separated `path` leads to something else than `false`.
+fn has_capability(capabilities: &Value, path: &str) -> bool {
+    let mut value = capabilities;
+    let mut rest = path;
+    while !rest.is_empty() {
+        let Value::Object(object) = value else {
+            return false;
+        };
+        // experimental keys may contain dots
+        if let Some(next) = object.get(rest) {
+            value = next;
+            break;
+        }
+        let (key, tail) = rest.split_once('.').unwrap_or((rest, ""));
+        let Some(next) = object.get(key) else {
+            return false;
+        };
+        value = next;
+        rest = tail;
+    }
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_complete() {
+            return write!(f, "protocol {}, nothing missing", self.negotiated_version);
+        }
+        if self.is_downgraded() {
+            write!(
+                f,
+                "protocol downgraded from {} to {}",
+                self.requested_version, self.negotiated_version
+            )?;
+        } else {
+            write!(f, "protocol {}", self.negotiated_version)?;
+        }
+        if !self.missing.is_empty() {
+            write!(f, ", peer lacks {}", self.missing.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_compatibility_report() {
+        let capabilities = json!({
+            "resources": { "listChanged": true, "subscribe": false },
+            "tools": {},
+            "experimental": { "io.example/stream": {} },
+        });
+        let wanted = [
+            "tools",
+            "resources.subscribe",
+            "resources.listChanged",
+            "tasks",
+        ]
+        .map(String::from);
+        let mut ours = ExperimentalCapabilities::new();
+        ours.insert("io.example/stream".into(), Default::default());
+        ours.insert("io.example/batch".into(), Default::default());
+        let report = CompatibilityReport::new(
+            ProtocolVersion::V_2025_11_25,
+            ProtocolVersion::V_2025_03_26,
+            &wanted,
+            Some(&ours),
+            &capabilities,
+        );
+        assert_eq!(
+            report.missing,
+            [
+                "resources.subscribe",
+                "tasks",
+                "experimental.io.example/batch"
+            ]
+        );
+        assert!(report.is_downgraded());
+        assert_eq!(
+            report.to_string(),
+            "protocol downgraded from 2025-11-25 to 2025-03-26, peer lacks resources.subscribe, tasks, experimental.io.example/batch"
+        );
+
+        let report = CompatibilityReport::new(
+            ProtocolVersion::V_2025_03_26,
+            ProtocolVersion::V_2025_03_26,
+            &wanted[..1],
+            None,
+            &capabilities,
+        );
+        assert!(report.is_complete());
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use super::{
+    AtomicU32RequestIdProvider, DeliveryOrder, PanicPolicy, QuitReason, RequestIdProvider,
+    SessionTimeouts,
+};
+
+/// Called once a session has ended, see [`ServiceConfig::on_session_end`].
+pub type SessionEndHook = Arc<dyn Fn(&QuitReason) + Send + Sync>;
+
+/// How a session of a service runs, see [`Service::config`](super::Service::config).
+///
+/// Asked once per session, so a service may hand each session its own
+/// [`RequestIdProvider`]. Wrapping services forward the config of the
+/// service they wrap.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use rmcp::service::{DeliveryOrder, ServiceConfig, SessionTimeouts};
+/// let config = ServiceConfig {
+///     session_timeouts: SessionTimeouts {
+///         idle_timeout: Some(Duration::from_secs(300)),
+///         ..Default::default()
+///     },
+///     delivery_order: DeliveryOrder::Fifo,
+///     ..Default::default()
+/// }
+/// .on_session_end(|reason| tracing::info!(?reason, "session ended"));
+/// ```
+#[derive(Clone)]
+pub struct ServiceConfig {
+    /// Timeouts after which the session is closed from this side; a server
+    /// first sends the client a `notifications/message` explaining why.
+    /// Unlimited by default.
+    pub session_timeouts: SessionTimeouts,
+    /// Called once the session has ended, for whatever reason, e.g. to
+    /// release per-session state.
+    pub on_session_end: Option<SessionEndHook>,
+    /// Whether to trace the time spent in each stage of a request, see
+    /// [Tracing](super::Service#tracing). Off by default.
+    pub request_timings: bool,
+    /// What to do with the session when a handler panics. Panics are
+    /// isolated by default.
+    pub panic_policy: PanicPolicy,
+    /// Handle one message at a time, in the order they were received, and
+    /// write every message in order without dropping any, see
+    /// [Deterministic tests](super::Service#deterministic-tests). Off by
+    /// default.
+    pub sequential: bool,
+    /// Which messages keep their order, see [Ordering](super::Service#ordering).
+    pub delivery_order: DeliveryOrder,
+    /// Where the ids of the requests sent to the peer come from. Increasing
+    /// integers from 0 by default.
+    pub request_id_provider: Arc<dyn RequestIdProvider>,
+}
+
+impl ServiceConfig {
+    /// Call `hook` once the session has ended.
+    pub fn on_session_end(self, hook: impl Fn(&QuitReason) + Send + Sync + 'static) -> Self {
+        Self {
+            on_session_end: Some(Arc::new(hook)),
+            ..self
+        }
+    }
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            session_timeouts: SessionTimeouts::default(),
+            on_session_end: None,
+            request_timings: false,
+            panic_policy: PanicPolicy::default(),
+            sequential: false,
+            delivery_order: DeliveryOrder::default(),
+            request_id_provider: Arc::new(AtomicU32RequestIdProvider::default()),
+        }
+    }
+}
+
+impl std::fmt::Debug for ServiceConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServiceConfig")
+            .field("session_timeouts", &self.session_timeouts)
+            .field("on_session_end", &self.on_session_end.is_some())
+            .field("request_timings", &self.request_timings)
+            .field("panic_policy", &self.panic_policy)
+            .field("sequential", &self.sequential)
+            .field("delivery_order", &self.delivery_order)
+            .finish_non_exhaustive()
+    }
+}
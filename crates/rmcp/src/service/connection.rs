@@ -0,0 +1,111 @@
+use super::{QuitReason, SessionExpiry};
+
+/// Where a connection to a peer stands, as published by
+/// [`RunningService::state_changes`](super::RunningService::state_changes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Not connected yet. Only reported by transports that connect lazily:
+    /// a [`RunningService`](super::RunningService) exists once the
+    /// handshake is done.
+    Connecting,
+    Ready,
+    /// The transport lost the peer and is trying to get it back; messages
+    /// may be delayed or fail.
+    Reconnecting,
+    /// The service stopped; final.
+    Closed {
+        reason: CloseReason,
+    },
+}
+
+impl ConnectionState {
+    pub fn is_ready(&self) -> bool {
+        matches!(self, ConnectionState::Ready)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        matches!(self, ConnectionState::Closed { .. })
+    }
+}
+
+/// How much of an offending message [`CloseReason::ProtocolError`] keeps.
+const EXCERPT_LEN: usize = 256;
+
+/// Why a service stopped, a cloneable summary of its [`QuitReason`].
+///
+/// Hosts can use [`should_reconnect`](Self::should_reconnect) to decide
+/// whether to connect again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloseReason {
+    /// Cancelled from this side.
+    Cancelled,
+    /// The peer closed the transport: end of stream.
+    Closed,
+    Expired(SessionExpiry),
+    /// The peer sent something that isn't a valid message, and the transport
+    /// gave up on it.
+    ProtocolError {
+        message: String,
+        /// The start of what the peer sent.
+        excerpt: String,
+    },
+    /// The peer no longer accepts our credentials.
+    AuthRevoked(String),
+    /// The service task or its transport failed, with the error message.
+    Failed(String),
+}
+
+impl CloseReason {
+    /// A [`CloseReason::ProtocolError`] keeping the start of `offending`.
+    pub fn protocol_error(message: impl Into<String>, offending: &str) -> Self {
+        let mut end = offending.len().min(EXCERPT_LEN);
+        while !offending.is_char_boundary(end) {
+            end -= 1;
+        }
+        CloseReason::ProtocolError {
+            message: message.into(),
+            excerpt: offending[..end].to_owned(),
+        }
+    }
+
+    /// Whether connecting again may help: not after a cancellation, a protocol
+    /// error or revoked credentials, which would end the same way.
+    pub fn should_reconnect(&self) -> bool {
+        match self {
+            CloseReason::Closed | CloseReason::Expired(_) | CloseReason::Failed(_) => true,
+            CloseReason::Cancelled
+            | CloseReason::ProtocolError { .. }
+            | CloseReason::AuthRevoked(_) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CloseReason::Cancelled => write!(f, "cancelled"),
+            CloseReason::Closed => write!(f, "closed by peer"),
+            CloseReason::Expired(expiry) => write!(f, "expired: {expiry}"),
+            CloseReason::ProtocolError { message, excerpt } => {
+                write!(f, "protocol error: {message}, in {excerpt:?}")
+            }
+            CloseReason::AuthRevoked(message) => write!(f, "auth revoked: {message}"),
+            CloseReason::Failed(message) => write!(f, "failed: {message}"),
+        }
+    }
+}
+
+impl From<&QuitReason> for CloseReason {
+    fn from(reason: &QuitReason) -> Self {
+        match reason {
+            QuitReason::Cancelled => CloseReason::Cancelled,
+            QuitReason::Closed => CloseReason::Closed,
+            QuitReason::JoinError(error) => CloseReason::Failed(error.to_string()),
+            QuitReason::Expired(expiry) => CloseReason::Expired(*expiry),
+            QuitReason::Transport(reason) => reason.clone(),
+            QuitReason::Panicked(message) => {
+                CloseReason::Failed(format!("handler panicked: {message}"))
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use tokio::time::Instant;
+
+use super::{Peer, ServiceError, ServiceRole};
+use crate::model::PingRequest;
+#[cfg(feature = "client")]
+use crate::model::{CallToolRequest, CallToolRequestParams, ClientRequest};
+
+type Probe<R> = Arc<dyn Fn() -> <R as ServiceRole>::Req + Send + Sync>;
+
+/// A benchmark of a session, for support tooling: first the round trip
+/// time of requests sent one at a time, then how many requests per second
+/// the peer answers with several in flight.
+///
+/// The requests are pings unless [`with_probe`](Self::with_probe) or
+/// [`with_echo_tool`](Diagnostics::with_echo_tool) say otherwise. They go
+/// through the session like any other, so they queue behind its traffic.
+///
+/// ```rust,ignore
+/// let report = client.diagnostics().with_samples(50).measure().await?;
+/// println!("p99 {:?}, {:.0} requests/s", report.rtt.p99, report.requests_per_second);
+/// ```
+pub struct Diagnostics<R: ServiceRole> {
+    peer: Peer<R>,
+    probe: Option<Probe<R>>,
+    samples: usize,
+    requests: usize,
+    concurrency: usize,
+}
+
+impl<R: ServiceRole> std::fmt::Debug for Diagnostics<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Diagnostics")
+            .field("samples", &self.samples)
+            .field("requests", &self.requests)
+            .field("concurrency", &self.concurrency)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Round trip times of a [`DiagnosticsReport`], by nearest rank.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RttPercentiles {
+    pub min: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl RttPercentiles {
+    fn new(mut rtts: Vec<Duration>) -> Self {
+        rtts.sort();
+        let rank = |p: f64| {
+            let index = ((p * rtts.len() as f64).ceil() as usize).saturating_sub(1);
+            rtts.get(index).copied().unwrap_or_default()
+        };
+        Self {
+            min: rtts.first().copied().unwrap_or_default(),
+            p50: rank(0.5),
+            p90: rank(0.9),
+            p99: rank(0.99),
+            max: rtts.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagnosticsReport {
+    /// Of requests sent one at a time.
+    pub rtt: RttPercentiles,
+    pub samples: usize,
+    /// Requests answered per second with up to `concurrency` in flight.
+    pub requests_per_second: f64,
+    pub requests: usize,
+    pub concurrency: usize,
+}
+
+impl<R: ServiceRole> Diagnostics<R> {
+    pub(crate) fn new(peer: Peer<R>) -> Self {
+        Self {
+            peer,
+            probe: None,
+            samples: 100,
+            requests: 1000,
+            concurrency: 32,
+        }
+    }
+
+    /// Requests sent one at a time to measure round trips, 100 by default.
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    /// Requests sent to measure throughput, 1000 by default, up to
+    /// `concurrency` at a time, 32 by default.
+    pub fn with_throughput(mut self, requests: usize, concurrency: usize) -> Self {
+        self.requests = requests;
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Send the requests `probe` makes instead of pings. They should be
+    /// cheap for the peer, or the report measures the peer.
+    pub fn with_probe(mut self, probe: impl Fn() -> R::Req + Send + Sync + 'static) -> Self {
+        self.probe = Some(Arc::new(probe));
+        self
+    }
+
+    async fn probe(&self) -> Result<Duration, ServiceError>
+    where
+        R::Req: From<PingRequest>,
+    {
+        let request = match &self.probe {
+            Some(probe) => probe(),
+            None => PingRequest::default().into(),
+        };
+        let start = Instant::now();
+        self.peer.send_request(request).await?;
+        Ok(start.elapsed())
+    }
+
+    /// Run the benchmark, failing with the first request that fails.
+    pub async fn measure(self) -> Result<DiagnosticsReport, ServiceError>
+    where
+        R::Req: From<PingRequest>,
+    {
+        let mut rtts = Vec::with_capacity(self.samples);
+        for _ in 0..self.samples {
+            rtts.push(self.probe().await?);
+        }
+
+        let start = Instant::now();
+        let mut answers = futures::stream::iter((0..self.requests).map(|_| self.probe()))
+            .buffer_unordered(self.concurrency);
+        while let Some(answer) = answers.next().await {
+            answer?;
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+        let requests_per_second = if elapsed > 0.0 {
+            self.requests as f64 / elapsed
+        } else {
+            0.0
+        };
+
+        let report = DiagnosticsReport {
+            rtt: RttPercentiles::new(rtts),
+            samples: self.samples,
+            requests_per_second,
+            requests: self.requests,
+            concurrency: self.concurrency,
+        };
+        tracing::debug!(?report, "Measured the session");
+        Ok(report)
+    }
+}
+
+#[cfg(feature = "client")]
+impl Diagnostics<crate::RoleClient> {
+    /// Call the tool of `params`, e.g. an echo tool, instead of pinging,
+    /// to include the tool dispatch of the server.
+    pub fn with_echo_tool(self, params: CallToolRequestParams) -> Self {
+        self.with_probe(move || {
+            ClientRequest::CallToolRequest(CallToolRequest::new(params.clone()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let rtts = (1..=100).rev().map(Duration::from_millis).collect();
+        let percentiles = RttPercentiles::new(rtts);
+        assert_eq!(
+            percentiles,
+            RttPercentiles {
+                min: Duration::from_millis(1),
+                p50: Duration::from_millis(50),
+                p90: Duration::from_millis(90),
+                p99: Duration::from_millis(99),
+                max: Duration::from_millis(100),
+            }
+        );
+        assert_eq!(RttPercentiles::new(Vec::new()), RttPercentiles::default());
+    }
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// When a service ends its session on its own, see
+/// [`ServiceConfig::session_timeouts`](super::ServiceConfig::session_timeouts).
+///
+/// Useful to reclaim handler resources held by clients that went quiet or
+/// never disconnect, regardless of the transport in use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionTimeouts {
+    /// Close the session after this long without any message from the peer
+    /// while no request is being handled.
+    pub idle_timeout: Option<Duration>,
+    /// Close the session once it has been running this long, whatever it is
+    /// doing.
+    pub max_duration: Option<Duration>,
+}
+
+impl SessionTimeouts {
+    pub fn is_unlimited(&self) -> bool {
+        self.idle_timeout.is_none() && self.max_duration.is_none()
+    }
+
+    /// The earliest instant at which one of the timeouts fires, along with
+    /// which one it is. `busy` suspends the idle timeout.
+    pub(crate) fn next_expiry(
+        &self,
+        started_at: Instant,
+        last_activity: Instant,
+        busy: bool,
+    ) -> Option<(Instant, SessionExpiry)> {
+        let max_duration = self
+            .max_duration
+            .map(|max| (started_at + max, SessionExpiry::MaxDuration(max)));
+        let idle = self
+            .idle_timeout
+            .filter(|_| !busy)
+            .map(|idle| (last_activity + idle, SessionExpiry::IdleTimeout(idle)));
+        match (max_duration, idle) {
+            (Some(a), Some(b)) => Some(if b.0 < a.0 { b } else { a }),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Which of the [`SessionTimeouts`] ended a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionExpiry {
+    IdleTimeout(Duration),
+    MaxDuration(Duration),
+}
+
+impl std::fmt::Display for SessionExpiry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionExpiry::IdleTimeout(idle) => {
+                write!(f, "idle for more than {}ms", idle.as_millis())
+            }
+            SessionExpiry::MaxDuration(max) => {
+                write!(
+                    f,
+                    "maximum session duration of {}ms reached",
+                    max.as_millis()
+                )
+            }
+        }
+    }
+}
+
+/// Resolves when `next` is due; never resolves if it's `None`.
+pub(crate) async fn expire_at(next: Option<(Instant, SessionExpiry)>) -> SessionExpiry {
+    match next {
+        Some((at, expiry)) => {
+            tokio::time::sleep_until(at).await;
+            expiry
+        }
+        None => std::future::pending().await,
+    }
+}
@@ -0,0 +1,133 @@
+use std::{fmt, time::Duration};
+
+use serde_json::Value;
+use tokio::time::Instant;
+
+use super::CloseReason;
+
+/// Bytes of a hex dump line.
+const HEX_DUMP_WIDTH: usize = 16;
+
+/// What went through the transport during a failed `initialize` handshake,
+/// in [`ClientInitializeError`](super::ClientInitializeError)s.
+///
+/// Its `Display` is meant for bug reports: the request sent, the messages
+/// received, why the transport closed, and a hex dump of what the peer sent
+/// that wasn't a message, e.g. a banner a stdio server printed on stdout.
+#[derive(Debug, Clone)]
+pub struct HandshakeDiagnostics {
+    /// The `initialize` request, as JSON.
+    pub sent: Value,
+    /// The messages received before the failure, as JSON.
+    pub received: Vec<Value>,
+    /// Why the transport stopped receiving, if it says.
+    pub close_reason: Option<CloseReason>,
+    /// From sending the request to the failure.
+    pub elapsed: Duration,
+    started: Instant,
+}
+
+impl HandshakeDiagnostics {
+    pub(crate) fn new(sent: Value) -> Self {
+        Self {
+            sent,
+            received: Vec::new(),
+            close_reason: None,
+            elapsed: Duration::ZERO,
+            started: Instant::now(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, message: &impl serde::Serialize) {
+        self.received
+            .push(serde_json::to_value(message).unwrap_or_default());
+    }
+
+    /// A copy for an error, as of now.
+    pub(crate) fn snapshot(&self, close_reason: Option<CloseReason>) -> Box<Self> {
+        Box::new(Self {
+            close_reason,
+            elapsed: self.started.elapsed(),
+            ..self.clone()
+        })
+    }
+
+    /// The start of what the peer sent that wasn't a message, if the
+    /// transport kept it.
+    pub fn invalid_bytes(&self) -> Option<&[u8]> {
+        match &self.close_reason {
+            Some(CloseReason::ProtocolError { excerpt, .. }) => Some(excerpt.as_bytes()),
+            _ => None,
+        }
+    }
+}
+
+/// `offset  hex  |ascii|` lines, like `hexdump -C`.
+fn hex_dump(bytes: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, chunk) in bytes.chunks(HEX_DUMP_WIDTH).enumerate() {
+        let hex = chunk
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        dump.push_str(&format!(
+            "  {:08x}  {hex:<width$}  |{ascii}|\n",
+            line * HEX_DUMP_WIDTH,
+            width = HEX_DUMP_WIDTH * 3 - 1
+        ));
+    }
+    dump
+}
+
+impl fmt::Display for HandshakeDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "handshake diagnostics, after {:?}:", self.elapsed)?;
+        writeln!(f, "  sent: {}", self.sent)?;
+        if self.received.is_empty() {
+            writeln!(f, "  received: nothing")?;
+        }
+        for message in &self.received {
+            writeln!(f, "  received: {message}")?;
+        }
+        match &self.close_reason {
+            Some(reason) => writeln!(f, "  transport: {reason}")?,
+            None => writeln!(f, "  transport: no close reason")?,
+        }
+        if let Some(bytes) = self.invalid_bytes() {
+            writeln!(f, "  first invalid bytes:")?;
+            f.write_str(&hex_dump(bytes))?;
+        }
+        if self.received.is_empty() && self.close_reason.is_none() {
+            writeln!(
+                f,
+                "  hint: the server sent nothing, check that it started and speaks MCP on this transport"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_dump() {
+        assert_eq!(
+            hex_dump(b"Starting server\n\x00v1"),
+            "  00000000  53 74 61 72 74 69 6e 67 20 73 65 72 76 65 72 0a  |Starting server.|\n  \
+             00000010  00 76 31                                         |.v1|\n"
+        );
+    }
+}
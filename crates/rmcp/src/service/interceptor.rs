@@ -0,0 +1,38 @@
+use super::ServiceRole;
+
+/// Hook that can rewrite the requests a [`Peer`](super::Peer) sends and the
+/// responses it gets back.
+///
+/// [`on_outgoing`](Self::on_outgoing) runs right before a request is handed
+/// to the transport, after the progress token, budget and per-call `_meta`
+/// have been set. [`on_incoming`](Self::on_incoming) runs on every successful
+/// response once it has been deserialized. Typical uses are normalizing
+/// arguments, injecting a locale into `_meta` or scrubbing response content.
+///
+/// Interceptors run in the order they were added with
+/// [`Peer::with_interceptor`](super::Peer::with_interceptor), for both
+/// directions.
+///
+/// ```rust
+/// # use rmcp::{model::ClientRequest, service::{Interceptor, RoleClient}};
+/// struct Locale(&'static str);
+///
+/// impl Interceptor<RoleClient> for Locale {
+///     fn on_outgoing(&self, request: &mut ClientRequest) {
+///         use rmcp::model::GetMeta;
+///         request
+///             .get_meta_mut()
+///             .insert("locale".to_owned(), self.0.into());
+///     }
+/// }
+/// ```
+pub trait Interceptor<R: ServiceRole>: Send + Sync + 'static {
+    /// Called with every request before it is sent.
+    fn on_outgoing(&self, request: &mut R::Req) {
+        let _ = request;
+    }
+    /// Called with every successful response after it has been received.
+    fn on_incoming(&self, response: &mut R::PeerResp) {
+        let _ = response;
+    }
+}
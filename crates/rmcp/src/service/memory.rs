@@ -0,0 +1,131 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use serde::Serialize;
+
+/// What buffered bytes are held for, see [`MemoryUsage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MemoryKind {
+    /// Low-priority notifications waiting to be sent.
+    Notifications,
+    /// Server-sent events kept so that clients can resume their streams.
+    Events,
+    /// Completed task results waiting to be fetched.
+    TaskResults,
+}
+
+impl MemoryKind {
+    const ALL: [MemoryKind; 3] = [
+        MemoryKind::Notifications,
+        MemoryKind::Events,
+        MemoryKind::TaskResults,
+    ];
+}
+
+type Gauges = [AtomicUsize; MemoryKind::ALL.len()];
+
+static PROCESS: Gauges = [const { AtomicUsize::new(0) }; MemoryKind::ALL.len()];
+
+/// Approximate bytes buffered by a session, by [`MemoryKind`].
+///
+/// Sizes are those of the JSON the buffered messages serialize to, not what
+/// they take on the heap, which is good enough to find the session that holds
+/// on to the most.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsage {
+    pub notifications: usize,
+    pub events: usize,
+    pub task_results: usize,
+}
+
+impl MemoryUsage {
+    pub fn get(&self, kind: MemoryKind) -> usize {
+        match kind {
+            MemoryKind::Notifications => self.notifications,
+            MemoryKind::Events => self.events,
+            MemoryKind::TaskResults => self.task_results,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        MemoryKind::ALL.iter().map(|kind| self.get(*kind)).sum()
+    }
+
+    fn load(gauges: &Gauges) -> Self {
+        let load = |kind: MemoryKind| gauges[kind as usize].load(Ordering::Relaxed);
+        MemoryUsage {
+            notifications: load(MemoryKind::Notifications),
+            events: load(MemoryKind::Events),
+            task_results: load(MemoryKind::TaskResults),
+        }
+    }
+}
+
+/// The bytes buffered by every session in the process, e.g. to export as
+/// gauges next to [`task_counts`](super::task_counts).
+pub fn buffered_bytes() -> MemoryUsage {
+    MemoryUsage::load(&PROCESS)
+}
+
+/// Where the parts of a session record what they buffer, shared by its
+/// transport and its [`Peer`](super::Peer).
+///
+/// Cloning gives another handle to the same counters. Whatever is still
+/// recorded when the last handle is dropped is taken off
+/// [`buffered_bytes`].
+#[derive(Debug, Clone, Default)]
+pub struct MemoryAccount {
+    gauges: Arc<AccountGauges>,
+}
+
+#[derive(Debug, Default)]
+struct AccountGauges(Gauges);
+
+impl Drop for AccountGauges {
+    fn drop(&mut self) {
+        for (gauge, process) in self.0.iter().zip(&PROCESS) {
+            process.fetch_sub(gauge.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+    }
+}
+
+impl MemoryAccount {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, kind: MemoryKind, bytes: usize) {
+        self.gauges.0[kind as usize].fetch_add(bytes, Ordering::Relaxed);
+        PROCESS[kind as usize].fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn sub(&self, kind: MemoryKind, bytes: usize) {
+        let gauge = &self.gauges.0[kind as usize];
+        // never below zero, even if a caller gets its sizes wrong
+        let previous = gauge
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |held| {
+                Some(held.saturating_sub(bytes))
+            })
+            .unwrap_or_default();
+        PROCESS[kind as usize].fetch_sub(previous.min(bytes), Ordering::Relaxed);
+    }
+
+    /// Replace what is recorded for `kind`, for owners that recompute their
+    /// total rather than track changes.
+    pub fn set(&self, kind: MemoryKind, bytes: usize) {
+        let previous = self.gauges.0[kind as usize].swap(bytes, Ordering::Relaxed);
+        if bytes >= previous {
+            PROCESS[kind as usize].fetch_add(bytes - previous, Ordering::Relaxed);
+        } else {
+            PROCESS[kind as usize].fetch_sub(previous - bytes, Ordering::Relaxed);
+        }
+    }
+
+    pub fn usage(&self) -> MemoryUsage {
+        MemoryUsage::load(&self.gauges.0)
+    }
+}
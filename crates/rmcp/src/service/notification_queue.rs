@@ -0,0 +1,167 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use tokio::sync::Notify;
+
+use super::{MemoryAccount, MemoryKind, PeerSinkMessage, ServiceError, ServiceRole};
+
+/// Bounded queue for notifications that may be dropped under pressure
+/// (see [`ServiceRole::is_low_priority`]).
+///
+/// The serve loop only drains it when nothing else is waiting to be sent, so
+/// a flood of progress or log messages can't hold back responses. When full,
+/// the oldest entry is dropped and counted.
+pub(crate) struct NotificationQueue<R: ServiceRole> {
+    state: Mutex<QueueState<R>>,
+    notify: Notify,
+    capacity: usize,
+    dropped: AtomicUsize,
+    memory: MemoryAccount,
+}
+
+fn size_of<R: ServiceRole>(notification: &R::Not) -> usize {
+    serde_json::to_vec(notification).map_or(0, |json| json.len())
+}
+
+struct QueueState<R: ServiceRole> {
+    queue: VecDeque<R::Not>,
+    closed: bool,
+}
+
+impl<R: ServiceRole> NotificationQueue<R> {
+    pub(crate) fn new(capacity: usize, memory: MemoryAccount) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                queue: VecDeque::with_capacity(capacity),
+                closed: false,
+            }),
+            notify: Notify::new(),
+            capacity,
+            dropped: AtomicUsize::new(0),
+            memory,
+        }
+    }
+
+    pub(crate) fn push(&self, notification: R::Not) -> Result<(), ServiceError> {
+        let size = size_of::<R>(&notification);
+        let dropped = {
+            let mut state = self.state.lock().expect("notification queue poisoned");
+            if state.closed {
+                return Err(ServiceError::TransportClosed);
+            }
+            self.memory.add(MemoryKind::Notifications, size);
+            state.queue.push_back(notification);
+            if state.queue.len() > self.capacity {
+                state.queue.pop_front()
+            } else {
+                None
+            }
+        };
+        if let Some(notification) = dropped {
+            self.memory
+                .sub(MemoryKind::Notifications, size_of::<R>(&notification));
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!(?notification, "notification queue full, dropping oldest");
+        }
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// How many notifications were dropped so far.
+    pub(crate) fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Wait for the next queued notification. Cancel safe.
+    ///
+    /// Nobody waits for the write of a queued notification, so its responder
+    /// goes nowhere.
+    pub(crate) async fn recv(&self) -> PeerSinkMessage<R> {
+        loop {
+            let next = self
+                .state
+                .lock()
+                .expect("notification queue poisoned")
+                .queue
+                .pop_front();
+            if let Some(notification) = next {
+                self.memory
+                    .sub(MemoryKind::Notifications, size_of::<R>(&notification));
+                let (responder, _) = tokio::sync::oneshot::channel();
+                return PeerSinkMessage::Notification {
+                    notification,
+                    responder,
+                };
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// Refuse further notifications and drop the queued ones.
+    pub(crate) fn close(&self) {
+        let mut state = self.state.lock().expect("notification queue poisoned");
+        state.closed = true;
+        for notification in state.queue.drain(..) {
+            self.memory
+                .sub(MemoryKind::Notifications, size_of::<R>(&notification));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::{
+        model::{
+            LoggingLevel, LoggingMessageNotification, LoggingMessageNotificationParam,
+            ServerNotification,
+        },
+        service::RoleServer,
+    };
+
+    fn log(n: u64) -> ServerNotification {
+        LoggingMessageNotification::new(LoggingMessageNotificationParam {
+            level: LoggingLevel::Info,
+            logger: None,
+            data: n.into(),
+        })
+        .into()
+    }
+
+    #[tokio::test]
+    async fn test_drops_oldest_when_full() {
+        let memory = MemoryAccount::new();
+        let queue = NotificationQueue::<RoleServer>::new(2, memory.clone());
+        for n in 0..3 {
+            queue.push(log(n)).unwrap();
+        }
+
+        assert_eq!(queue.dropped(), 1);
+        assert_eq!(
+            memory.usage().notifications,
+            size_of::<RoleServer>(&log(1)) + size_of::<RoleServer>(&log(2))
+        );
+        for expected in 1..3 {
+            let PeerSinkMessage::Notification {
+                notification: ServerNotification::LoggingMessageNotification(n),
+                ..
+            } = queue.recv().await
+            else {
+                panic!("expected a logging notification");
+            };
+            assert_eq!(n.params.data, expected);
+        }
+
+        assert_eq!(memory.usage().notifications, 0);
+        queue.close();
+        assert!(matches!(
+            queue.push(log(3)),
+            Err(ServiceError::TransportClosed)
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    any::Any,
+    panic::AssertUnwindSafe,
+    sync::{Arc, OnceLock},
+};
+
+use futures::FutureExt;
+use tokio_util::sync::CancellationToken;
+
+use super::QuitReason;
+
+/// What happens to a session when one of its handlers panics, see
+/// [`ServiceConfig::panic_policy`](super::ServiceConfig::panic_policy).
+///
+/// Either way the panic is caught, logged as an error event and, for a
+/// request, answered with an internal error, so one faulty tool cannot take
+/// down other sessions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Keep serving the session.
+    #[default]
+    Isolate,
+    /// End the session with [`QuitReason::Panicked`], for handlers whose
+    /// state can't be trusted after a panic.
+    CloseSession,
+}
+
+/// Catches the panics of the handlers of one session.
+#[derive(Debug, Clone)]
+pub(crate) struct PanicGuard {
+    policy: PanicPolicy,
+    session_ct: CancellationToken,
+    panicked: Arc<OnceLock<String>>,
+}
+
+impl PanicGuard {
+    pub(crate) fn new(policy: PanicPolicy, session_ct: CancellationToken) -> Self {
+        Self {
+            policy,
+            session_ct,
+            panicked: Default::default(),
+        }
+    }
+
+    /// Run `handler`, returning the panic message if it panics.
+    pub(crate) async fn catch<T>(&self, handler: impl Future<Output = T>) -> Result<T, String> {
+        let payload = match AssertUnwindSafe(handler).catch_unwind().await {
+            Ok(output) => return Ok(output),
+            Err(payload) => payload,
+        };
+        let message = panic_message(payload.as_ref());
+        tracing::error!(panic = %message, policy = ?self.policy, "handler panicked");
+        if self.policy == PanicPolicy::CloseSession {
+            let _ = self.panicked.set(message.clone());
+            self.session_ct.cancel();
+        }
+        Err(message)
+    }
+
+    /// Why the session was cancelled: [`QuitReason::Panicked`] if a handler
+    /// panicked under [`PanicPolicy::CloseSession`].
+    pub(crate) fn quit_reason(&self) -> QuitReason {
+        self.panicked
+            .get()
+            .map_or(QuitReason::Cancelled, |message| {
+                QuitReason::Panicked(message.clone())
+            })
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<non-string panic payload>".to_owned()
+    }
+}
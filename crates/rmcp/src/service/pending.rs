@@ -0,0 +1,56 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::model::{ProgressToken, RequestId};
+
+/// An outgoing request still waiting for its response, as listed by
+/// [`Peer::pending_requests`](super::Peer::pending_requests).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingRequest {
+    pub id: RequestId,
+    pub method: String,
+    /// The token the peer reports this request's progress with
+    pub progress_token: ProgressToken,
+    pub sent_at: Instant,
+}
+
+impl PendingRequest {
+    /// How long the request has been waiting for its response.
+    pub fn age(&self) -> Duration {
+        self.sent_at.elapsed()
+    }
+}
+
+/// The pending requests of a session, shared by all its peer handles.
+#[derive(Debug, Default)]
+pub(crate) struct PendingRequests(Mutex<HashMap<RequestId, PendingRequest>>);
+
+impl PendingRequests {
+    fn requests(&self) -> std::sync::MutexGuard<'_, HashMap<RequestId, PendingRequest>> {
+        self.0.lock().expect("pending requests poisoned")
+    }
+
+    pub(crate) fn insert(&self, request: PendingRequest) {
+        self.requests().insert(request.id.clone(), request);
+    }
+
+    pub(crate) fn remove(&self, id: &RequestId) {
+        self.requests().remove(id);
+    }
+
+    pub(crate) fn contains(&self, id: &RequestId) -> bool {
+        self.requests().contains_key(id)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.requests().clear();
+    }
+
+    /// Oldest first.
+    pub(crate) fn list(&self) -> Vec<PendingRequest> {
+        let mut requests = self.requests().values().cloned().collect::<Vec<_>>();
+        requests.sort_by_key(|request| request.sent_at);
+        requests
+    }
+}
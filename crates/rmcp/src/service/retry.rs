@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use super::ServiceError;
+use crate::model::ErrorCode;
+
+/// Controls how a [`Peer`](super::Peer) retries requests that failed for a
+/// transient reason.
+///
+/// Only requests that are safe to send twice are retried automatically:
+/// `ping`, the `*/list` requests and `resources/read` on the client side. Any
+/// other request can opt in per call with
+/// [`Peer::send_request_with_retry`](super::Peer::send_request_with_retry).
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use rmcp::{model::ErrorCode, service::RetryPolicy};
+/// let policy = RetryPolicy {
+///     max_retries: 5,
+///     retry_on: vec![ErrorCode::INTERNAL_ERROR],
+///     ..Default::default()
+/// };
+/// assert_eq!(policy.backoff(0), Duration::from_millis(100));
+/// assert_eq!(policy.backoff(10), Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// How many times a request is sent again after the first attempt.
+    pub max_retries: usize,
+    /// Delay before the first retry; doubled for every following one.
+    pub base_backoff: Duration,
+    /// Upper bound for the delay between two attempts.
+    pub max_backoff: Duration,
+    /// Error codes that are retried on top of the ones
+    /// [`ErrorCode::is_retryable`] already accepts.
+    pub retry_on: Vec<ErrorCode>,
+}
+
+impl RetryPolicy {
+    pub const DEFAULT_MAX_RETRIES: usize = 3;
+    pub const DEFAULT_BASE_BACKOFF: Duration = Duration::from_millis(100);
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+    /// Whether `error` is worth another attempt under this policy.
+    pub fn should_retry(&self, error: &ServiceError) -> bool {
+        match error {
+            ServiceError::McpError(error) => {
+                error.is_retryable() || self.retry_on.contains(&error.code)
+            }
+            error => error.is_retryable(),
+        }
+    }
+
+    /// Delay before retry number `attempt` (starting from zero).
+    pub fn backoff(&self, attempt: usize) -> Duration {
+        let factor = 1u32.checked_shl(attempt as u32).unwrap_or(u32::MAX);
+        self.base_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: Self::DEFAULT_MAX_RETRIES,
+            base_backoff: Self::DEFAULT_BASE_BACKOFF,
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+            retry_on: Vec::new(),
+        }
+    }
+}
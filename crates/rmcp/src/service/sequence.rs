@@ -0,0 +1,63 @@
+use tokio::sync::oneshot;
+
+/// Which messages of a session keep their order, see
+/// [`ServiceConfig::delivery_order`](super::ServiceConfig::delivery_order).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeliveryOrder {
+    /// Every message is handled as soon as it arrives and written as soon as
+    /// it is sent, so a notification may overtake an earlier one when
+    /// handlers or writes take different times.
+    #[default]
+    Concurrent,
+    /// Notifications from the peer are handled one at a time, in the order
+    /// they were received, and messages to the peer are written in the
+    /// order they were sent. Requests are still handled concurrently.
+    Fifo,
+}
+
+/// Hands out turns so that handlers run one after the other, in the order
+/// their messages were received, see
+/// [`ServiceConfig::sequential`](super::ServiceConfig::sequential).
+#[derive(Debug, Default)]
+pub(crate) struct Sequencer {
+    previous: Option<oneshot::Receiver<()>>,
+}
+
+impl Sequencer {
+    pub(crate) fn next_turn(&mut self) -> Turn {
+        let (done, next) = oneshot::channel();
+        Turn {
+            previous: self.previous.replace(next),
+            _done: done,
+        }
+    }
+}
+
+/// A place in a [`Sequencer`]'s line, passed on to the next handler when
+/// dropped.
+#[derive(Debug)]
+pub(crate) struct Turn {
+    previous: Option<oneshot::Receiver<()>>,
+    _done: oneshot::Sender<()>,
+}
+
+impl Turn {
+    /// Wait for the handlers received earlier to finish.
+    pub(crate) async fn wait(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            // a dropped sender is the signal
+            let _ = previous.await;
+        }
+    }
+}
+
+/// Run `f` once the earlier turns are done, if there is a `turn` to take.
+pub(crate) async fn in_turn<F: Future>(turn: Option<Turn>, f: F) -> F::Output {
+    let Some(mut turn) = turn else {
+        return f.await;
+    };
+    turn.wait().await;
+    let output = f.await;
+    drop(turn);
+    output
+}
@@ -10,18 +10,39 @@ use crate::model::{
 #[allow(deprecated)] // ListRootsRequest/ListRootsResult are deprecated (MCP 2025-11-25)
 use crate::{
     model::{
-        CancelledNotification, CancelledNotificationParam, ClientInfo, ClientJsonRpcMessage,
-        ClientNotification, ClientRequest, ClientResult, CreateMessageRequest,
-        CreateMessageRequestParams, CreateMessageResult, ErrorData, ListRootsRequest,
-        ListRootsResult, LoggingMessageNotification, LoggingMessageNotificationParam,
-        ProgressNotification, ProgressNotificationParam, PromptListChangedNotification,
-        ProtocolVersion, ResourceListChangedNotification, ResourceUpdatedNotification,
-        ResourceUpdatedNotificationParam, ServerInfo, ServerNotification, ServerRequest,
-        ServerResult, ToolListChangedNotification,
+        CancelledNotification, CancelledNotificationParam, ClientCapabilities, ClientInfo,
+        ClientJsonRpcMessage, ClientNotification, ClientRequest, ClientResult, ContentPreferences,
+        CreateMessageRequest, CreateMessageRequestParams, CreateMessageResult, ErrorData,
+        ExperimentalCapability, ListRootsRequest, ListRootsResult, LoggingLevel,
+        LoggingMessageNotification, LoggingMessageNotificationParam, ProgressNotification,
+        ProgressNotificationParam, PromptListChangedNotification, ProtocolVersion, ResourceDelta,
+        ResourceDeltas, ResourceListChangedNotification, ResourceUpdatedNotification,
+        ResourceUpdatedNotificationParam, ServerInfo, ServerJsonRpcMessage, ServerNotification,
+        ServerRequest, ServerResult, TaskStatusNotification, TaskStatusNotificationParam,
+        ToolListChangedNotification,
     },
     transport::DynamicTransportError,
 };
 
+mod cache;
+pub use cache::*;
+mod coalescer;
+pub use coalescer::*;
+mod dedup;
+pub use dedup::*;
+mod localize;
+pub use localize::*;
+mod overload;
+pub use overload::*;
+mod pipeline;
+pub use pipeline::*;
+mod quota;
+pub use quota::*;
+mod usage;
+pub use usage::*;
+mod variants;
+pub use variants::*;
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct RoleServer;
 
@@ -37,6 +58,35 @@ impl ServiceRole for RoleServer {
 
     type InitializeError = ServerInitializeError;
     const IS_CLIENT: bool = false;
+    fn request_method(request: &ServerRequest) -> &str {
+        request.method()
+    }
+    fn peer_request_method(request: &ClientRequest) -> &str {
+        request.method()
+    }
+    fn peer_notification_method(notification: &ClientNotification) -> &str {
+        notification.method()
+    }
+    fn is_low_priority(notification: &ServerNotification) -> bool {
+        matches!(
+            notification,
+            ServerNotification::ProgressNotification(_)
+                | ServerNotification::LoggingMessageNotification(_)
+        )
+    }
+    fn peer_experimental(info: &ClientInfo) -> Option<&ExperimentalCapabilities> {
+        info.capabilities.experimental.as_ref()
+    }
+    fn expiry_notification(expiry: SessionExpiry) -> Option<ServerNotification> {
+        Some(
+            LoggingMessageNotification::new(LoggingMessageNotificationParam {
+                level: LoggingLevel::Notice,
+                logger: Some("rmcp".into()),
+                data: format!("session closed: {expiry}").into(),
+            })
+            .into(),
+        )
+    }
 }
 
 /// It represents the error that may occur when serving the server.
@@ -186,7 +236,8 @@ where
     T: Transport<RoleServer> + 'static,
 {
     let mut transport = transport.into_transport();
-    let id_provider = <Arc<AtomicU32RequestIdProvider>>::default();
+    let config = service.config();
+    let id_provider = config.request_id_provider.clone();
 
     // Get initialize request
     let (request, id) = expect_request(&mut transport, "initialized request").await?;
@@ -196,7 +247,16 @@ where
             ClientJsonRpcMessage::request(request, id),
         )));
     };
-    let (peer, peer_rx) = Peer::new(id_provider, Some(peer_info.params.clone()));
+    let mut params = peer_info.params.clone();
+    // keep the `_meta` of the request, e.g. the session locale
+    if params.meta.is_none() && !request.get_meta().is_empty() {
+        params.meta = Some(request.get_meta().clone());
+    }
+    let (peer, peer_rx) = Peer::new(
+        id_provider,
+        Some(params),
+        transport.memory_account().unwrap_or_default(),
+    );
     let context = RequestContext {
         ct: ct.child_token(),
         id: id.clone(),
@@ -221,6 +281,16 @@ where
             return Err(ServerInitializeError::InitializeFailed(e));
         }
     };
+    if let Err(message) = init_response.validate() {
+        let error = ErrorData::internal_error(message, None);
+        transport
+            .send(ServerJsonRpcMessage::error(error.clone(), id))
+            .await
+            .map_err(|error| {
+                ServerInitializeError::transport::<T>(error, "sending error response")
+            })?;
+        return Err(ServerInitializeError::InitializeFailed(error));
+    }
     let peer_protocol_version = peer_info.params.protocol_version.clone();
     let protocol_version = match peer_protocol_version
         .partial_cmp(&init_response.protocol_version)
@@ -228,9 +298,23 @@ where
             peer_protocol_version,
         ))? {
         std::cmp::Ordering::Less => peer_info.params.protocol_version.clone(),
-        _ => init_response.protocol_version,
+        _ => init_response.protocol_version.clone(),
     };
-    init_response.protocol_version = protocol_version;
+    let report = CompatibilityReport::new(
+        init_response.protocol_version.clone(),
+        protocol_version.clone(),
+        &[],
+        init_response.capabilities.experimental.as_ref(),
+        &serde_json::to_value(&peer_info.params.capabilities).unwrap_or_default(),
+    );
+    report.log(false);
+    peer.set_compatibility_report(report);
+    init_response.protocol_version = protocol_version.clone();
+    peer.set_protocol_version(protocol_version);
+    peer.set_experimental(
+        init_response.capabilities.experimental.as_ref(),
+        peer_info.params.capabilities.experimental.as_ref(),
+    );
     transport
         .send(ServerJsonRpcMessage::response(
             ServerResult::InitializeResult(init_response),
@@ -255,7 +339,7 @@ where
     };
     let _ = service.handle_notification(notification, context).await;
     // Continue processing service
-    Ok(serve_inner(service, transport, peer, peer_rx, ct))
+    Ok(serve_inner(service, config, transport, peer, peer_rx, ct))
 }
 
 macro_rules! method {
@@ -385,6 +469,11 @@ macro_rules! method {
 }
 
 impl Peer<RoleServer> {
+    /// What the client declared it supports during initialization.
+    pub fn peer_capabilities(&self) -> Option<&ClientCapabilities> {
+        self.peer_info().map(|info| &info.capabilities)
+    }
+
     pub async fn create_message(
         &self,
         params: CreateMessageRequestParams,
@@ -403,10 +492,6 @@ impl Peer<RoleServer> {
     }
     #[allow(deprecated)]
     method!(peer_req list_roots ListRootsRequest() => ListRootsResult);
-    #[cfg(feature = "elicitation")]
-    method!(peer_req create_elicitation CreateElicitationRequest(CreateElicitationRequestParams) => CreateElicitationResult);
-    #[cfg(feature = "elicitation")]
-    method!(peer_req_with_timeout create_elicitation_with_timeout CreateElicitationRequest(CreateElicitationRequestParams) => CreateElicitationResult);
 
     method!(peer_not notify_cancelled CancelledNotification(CancelledNotificationParam));
     method!(peer_not notify_progress ProgressNotification(ProgressNotificationParam));
@@ -415,6 +500,54 @@ impl Peer<RoleServer> {
     method!(peer_not notify_resource_list_changed ResourceListChangedNotification);
     method!(peer_not notify_tool_list_changed ToolListChangedNotification);
     method!(peer_not notify_prompt_list_changed PromptListChangedNotification);
+    method!(peer_not notify_task_status TaskStatusNotification(TaskStatusNotificationParam));
+
+    /// Tell the client that the resource at `uri` changed, attaching `delta`
+    /// if it advertised [`ResourceDeltas`].
+    pub async fn notify_resource_delta(
+        &self,
+        uri: impl Into<String>,
+        delta: &ResourceDelta,
+    ) -> Result<(), ServiceError> {
+        let mut notification =
+            ResourceUpdatedNotification::new(ResourceUpdatedNotificationParam { uri: uri.into() });
+        if self.peer_supports_experimental(ResourceDeltas::KEY) {
+            let mut meta = Meta::new();
+            meta.set_resource_delta(delta);
+            notification.extensions.insert(meta);
+        }
+        self.send_notification(ServerNotification::ResourceUpdatedNotification(
+            notification,
+        ))
+        .await
+    }
+
+    /// The media types the client prefers in tool results, if it advertised
+    /// [`ContentPreferences`]. Pass them to [`Representations::pick`].
+    ///
+    /// [`Representations::pick`]: crate::model::Representations::pick
+    pub fn content_preferences(&self) -> Option<ContentPreferences> {
+        self.peer_info()?
+            .capabilities
+            .experimental_typed()
+            .ok()
+            .flatten()
+    }
+}
+
+impl RequestContext<RoleServer> {
+    /// The language tag the client wants this answer in: the one of the
+    /// request `_meta`, else the one it gave for the session on
+    /// `initialize`. See [`LOCALE_META_KEY`](crate::model::LOCALE_META_KEY).
+    pub fn locale(&self) -> Option<&str> {
+        self.meta.locale().or_else(|| {
+            self.peer
+                .peer_info()?
+                .meta
+                .as_ref()
+                .and_then(|meta| meta.locale())
+        })
+    }
 }
 
 // =============================================================================
@@ -526,6 +659,165 @@ impl Peer<RoleServer> {
         }
     }
 
+    /// Give up on elicitations after `timeout` unless they set their own,
+    /// cancelling them on the client. Applies to every handle on this session.
+    ///
+    /// Users may take a while to answer, so there is no timeout by default.
+    pub fn set_elicitation_timeout(&self, timeout: Option<std::time::Duration>) {
+        *self
+            .shared
+            .elicitation_timeout
+            .write()
+            .expect("elicitation timeout poisoned") = timeout;
+    }
+
+    pub fn elicitation_timeout(&self) -> Option<std::time::Duration> {
+        *self
+            .shared
+            .elicitation_timeout
+            .read()
+            .expect("elicitation timeout poisoned")
+    }
+
+    /// Ask the client to elicit information from the user, giving up after
+    /// the [session's elicitation timeout](Self::set_elicitation_timeout).
+    pub async fn create_elicitation(
+        &self,
+        params: CreateElicitationRequestParams,
+    ) -> Result<CreateElicitationResult, ServiceError> {
+        self.create_elicitation_with_timeout(params, None).await
+    }
+
+    /// Same as [`create_elicitation`](Self::create_elicitation), giving up
+    /// after `timeout` if set.
+    ///
+    /// Once the timeout expires, the client is told to cancel the request and
+    /// `ServiceError::Timeout` is returned.
+    pub async fn create_elicitation_with_timeout(
+        &self,
+        params: CreateElicitationRequestParams,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<CreateElicitationResult, ServiceError> {
+        let request = ServerRequest::CreateElicitationRequest(CreateElicitationRequest {
+            method: Default::default(),
+            params,
+            extensions: Default::default(),
+        });
+        let options = crate::service::PeerRequestOptions {
+            timeout: timeout.or_else(|| self.elicitation_timeout()),
+            meta: None,
+        };
+        let result = self
+            .send_request_with_option(request, options)
+            .await?
+            .await_response()
+            .await?;
+        match result {
+            ClientResult::CreateElicitationResult(result) => Ok(result),
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    /// Request typed data from the user, as an [`ElicitationOutcome`] to
+    /// branch on.
+    ///
+    /// Unlike [`elicit`](Self::elicit), declining and cancelling aren't
+    /// errors. An elicitation that timed out was cancelled on the client, so
+    /// it is reported as [`ElicitationOutcome::Cancel`] too.
+    ///
+    /// [`ElicitationOutcome`]: crate::model::ElicitationOutcome
+    /// [`ElicitationOutcome::Cancel`]: crate::model::ElicitationOutcome::Cancel
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use rmcp::*;
+    /// # use rmcp::model::ElicitationOutcome;
+    /// # use serde::{Deserialize, Serialize};
+    /// # use schemars::JsonSchema;
+    /// #
+    /// #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+    /// struct Confirmation {
+    ///     confirmed: bool,
+    /// }
+    ///
+    /// rmcp::elicit_safe!(Confirmation);
+    ///
+    /// # async fn example(peer: Peer<RoleServer>) -> Result<(), Box<dyn std::error::Error>> {
+    /// match peer.elicit_outcome::<Confirmation>("Delete the branch?").await? {
+    ///     ElicitationOutcome::Accept(Confirmation { confirmed: true }) => { /* delete */ }
+    ///     ElicitationOutcome::Accept(_) | ElicitationOutcome::Decline => { /* keep it */ }
+    ///     ElicitationOutcome::Cancel => { /* stop here */ }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "schemars")]
+    pub async fn elicit_outcome<T>(
+        &self,
+        message: impl Into<String>,
+    ) -> Result<crate::model::ElicitationOutcome<T>, ElicitationError>
+    where
+        T: ElicitationSafe + for<'de> serde::Deserialize<'de>,
+    {
+        self.elicit_outcome_with_timeout(message, None).await
+    }
+
+    /// Same as [`elicit_outcome`](Self::elicit_outcome), giving up after
+    /// `timeout` if set.
+    #[cfg(feature = "schemars")]
+    pub async fn elicit_outcome_with_timeout<T>(
+        &self,
+        message: impl Into<String>,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<crate::model::ElicitationOutcome<T>, ElicitationError>
+    where
+        T: ElicitationSafe + for<'de> serde::Deserialize<'de>,
+    {
+        let response = match self.elicit_response::<T>(message, timeout).await {
+            Err(ElicitationError::Service(ServiceError::Timeout { .. })) => {
+                return Ok(crate::model::ElicitationOutcome::Cancel);
+            }
+            response => response?,
+        };
+        response
+            .outcome()
+            .map_err(|error| ElicitationError::ParseError {
+                error,
+                data: response.content.unwrap_or_default(),
+            })
+    }
+
+    #[cfg(feature = "schemars")]
+    async fn elicit_response<T: ElicitationSafe>(
+        &self,
+        message: impl Into<String>,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<CreateElicitationResult, ElicitationError> {
+        // Check if client supports elicitation capability
+        if !self.supports_elicitation() {
+            return Err(ElicitationError::CapabilityNotSupported);
+        }
+
+        // Generate schema automatically from type
+        let schema = crate::model::ElicitationSchema::from_type::<T>().map_err(|e| {
+            ElicitationError::Service(ServiceError::McpError(crate::ErrorData::invalid_params(
+                format!(
+                    "Invalid schema for type {}: {}",
+                    std::any::type_name::<T>(),
+                    e
+                ),
+                None,
+            )))
+        })?;
+
+        Ok(self
+            .create_elicitation_with_timeout(
+                CreateElicitationRequestParams::form(message, schema),
+                timeout,
+            )
+            .await?)
+    }
+
     /// Request typed data from the user with automatic schema generation.
     ///
     /// This method automatically generates the JSON schema from the Rust type using `schemars`,
@@ -673,29 +965,7 @@ impl Peer<RoleServer> {
     where
         T: ElicitationSafe + for<'de> serde::Deserialize<'de>,
     {
-        // Check if client supports elicitation capability
-        if !self.supports_elicitation() {
-            return Err(ElicitationError::CapabilityNotSupported);
-        }
-
-        // Generate schema automatically from type
-        let schema = crate::model::ElicitationSchema::from_type::<T>().map_err(|e| {
-            ElicitationError::Service(ServiceError::McpError(crate::ErrorData::invalid_params(
-                format!(
-                    "Invalid schema for type {}: {}",
-                    std::any::type_name::<T>(),
-                    e
-                ),
-                None,
-            )))
-        })?;
-
-        let response = self
-            .create_elicitation_with_timeout(
-                CreateElicitationRequestParams::form(message, schema),
-                timeout,
-            )
-            .await?;
+        let response = self.elicit_response::<T>(message, timeout).await?;
 
         match response.action {
             crate::model::ElicitationAction::Accept => {
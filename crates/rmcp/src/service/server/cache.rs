@@ -0,0 +1,337 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use serde_json::Value;
+use tokio::time::Instant;
+
+use crate::{
+    model::{
+        CallToolResult, ClientNotification, ClientRequest, ErrorData, JsonObject, ServerInfo,
+        ServerResult, Tool,
+    },
+    service::{NotificationContext, RequestContext, RoleServer, Service, ServiceConfig},
+};
+
+/// Storage for cached tool results, see [`CacheLayer`].
+///
+/// Backends shared between processes, e.g. on Redis, serialize the result
+/// themselves and should expire entries after `ttl`. Errors are best
+/// reported as misses: the call then simply runs again.
+pub trait CacheBackend: Send + Sync + 'static {
+    fn get(&self, key: &str) -> impl Future<Output = Option<CallToolResult>> + Send;
+    fn set(
+        &self,
+        key: String,
+        result: CallToolResult,
+        ttl: Duration,
+    ) -> impl Future<Output = ()> + Send;
+}
+
+/// In-memory [`CacheBackend`] evicting the least recently used entry once
+/// `capacity` is reached.
+///
+/// Clones share the same entries.
+#[derive(Debug, Clone)]
+pub struct MemoryCache {
+    capacity: usize,
+    entries: Arc<Mutex<MemoryEntries>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryEntries {
+    map: HashMap<String, MemoryEntry>,
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct MemoryEntry {
+    result: CallToolResult,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+impl MemoryCache {
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Default::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("cache poisoned").map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().expect("cache poisoned").map.clear()
+    }
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl CacheBackend for MemoryCache {
+    async fn get(&self, key: &str) -> Option<CallToolResult> {
+        let mut entries = self.entries.lock().expect("cache poisoned");
+        entries.clock += 1;
+        let clock = entries.clock;
+        let entry = entries.map.get_mut(key)?;
+        if entry.expires_at <= Instant::now() {
+            entries.map.remove(key);
+            return None;
+        }
+        entry.last_used = clock;
+        Some(entry.result.clone())
+    }
+
+    async fn set(&self, key: String, result: CallToolResult, ttl: Duration) {
+        let mut entries = self.entries.lock().expect("cache poisoned");
+        let now = Instant::now();
+        if entries.map.len() >= self.capacity && !entries.map.contains_key(&key) {
+            entries.map.retain(|_, entry| entry.expires_at > now);
+        }
+        if entries.map.len() >= self.capacity && !entries.map.contains_key(&key) {
+            let oldest = entries
+                .map
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.map.remove(&oldest);
+            }
+        }
+        entries.clock += 1;
+        let last_used = entries.clock;
+        entries.map.insert(
+            key,
+            MemoryEntry {
+                result,
+                expires_at: now + ttl,
+                last_used,
+            },
+        );
+    }
+}
+
+/// JSON text of `value` with object keys sorted, so equal arguments give
+/// equal cache keys whatever order the client sent them in.
+pub fn canonical_json(value: &Value) -> String {
+    fn sorted(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key.clone(), sorted(value)))
+                        .collect(),
+                )
+            }
+            Value::Array(values) => Value::Array(values.iter().map(sorted).collect()),
+            value => value.clone(),
+        }
+    }
+    sorted(value).to_string()
+}
+
+/// Whether the annotations of `tool` allow caching its results: it is marked
+/// read-only or idempotent.
+pub fn is_cacheable(tool: &Tool) -> bool {
+    tool.annotations.as_ref().is_some_and(|annotations| {
+        annotations.read_only_hint == Some(true) || annotations.idempotent_hint == Some(true)
+    })
+}
+
+type ScopeFn = dyn Fn(&RequestContext<RoleServer>) -> Option<String> + Send + Sync;
+
+/// Caches successful `tools/call` results of idempotent tools, to absorb
+/// agents calling the same tool with the same arguments over and over.
+///
+/// Results are keyed by tool name and canonical arguments. A tool is cached
+/// if it was configured with [`with_tool`](Self::with_tool) or
+/// [`with_tools`](Self::with_tools), or if a `tools/list` response of the
+/// inner service marks it read-only or idempotent. Errors, results flagged
+/// `isError` and task-augmented calls are never cached.
+///
+/// A backend shared between sessions shares results between them too; when
+/// results depend on who is calling, set a [`scope`](Self::with_scope).
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use rmcp::{ServerHandler, service::{CacheLayer, MemoryCache}};
+/// # struct Weather;
+/// # impl ServerHandler for Weather {}
+/// let service = CacheLayer::new(Weather, MemoryCache::default(), Duration::from_secs(30))
+///     .with_tool("forecast", Duration::from_secs(600));
+/// ```
+pub struct CacheLayer<S, B = MemoryCache> {
+    inner: S,
+    backend: B,
+    default_ttl: Duration,
+    tools: RwLock<HashMap<String, Duration>>,
+    scope: Option<Box<ScopeFn>>,
+}
+
+impl<S: std::fmt::Debug, B: std::fmt::Debug> std::fmt::Debug for CacheLayer<S, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CacheLayer")
+            .field("inner", &self.inner)
+            .field("backend", &self.backend)
+            .field("default_ttl", &self.default_ttl)
+            .field("tools", &self.tools)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, B: CacheBackend> CacheLayer<S, B> {
+    /// Cache into `backend`, for `default_ttl` unless a tool was configured
+    /// with its own.
+    pub fn new(inner: S, backend: B, default_ttl: Duration) -> Self {
+        Self {
+            inner,
+            backend,
+            default_ttl,
+            tools: Default::default(),
+            scope: None,
+        }
+    }
+
+    /// Cache results of `tool` for `ttl`, whatever its annotations say.
+    pub fn with_tool(self, tool: impl Into<String>, ttl: Duration) -> Self {
+        self.tools
+            .write()
+            .expect("cache config poisoned")
+            .insert(tool.into(), ttl);
+        self
+    }
+
+    /// Cache the [cacheable](is_cacheable) ones of `tools`, e.g. those of a
+    /// tool router, without waiting for a `tools/list`.
+    pub fn with_tools<'a>(self, tools: impl IntoIterator<Item = &'a Tool>) -> Self {
+        self.learn(tools);
+        self
+    }
+
+    /// Partition the cache, e.g. by user, with a key taken from the request.
+    /// Requests without a key bypass the cache.
+    pub fn with_scope(
+        mut self,
+        scope: impl Fn(&RequestContext<RoleServer>) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.scope = Some(Box::new(scope));
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    fn learn<'a>(&self, tools: impl IntoIterator<Item = &'a Tool>) {
+        let mut known = self.tools.write().expect("cache config poisoned");
+        for tool in tools {
+            if is_cacheable(tool) && !known.contains_key(tool.name.as_ref()) {
+                known.insert(tool.name.to_string(), self.default_ttl);
+            }
+        }
+    }
+
+    fn ttl(&self, tool: &str) -> Option<Duration> {
+        self.tools
+            .read()
+            .expect("cache config poisoned")
+            .get(tool)
+            .copied()
+    }
+
+    fn key(
+        &self,
+        tool: &str,
+        arguments: Option<&JsonObject>,
+        context: &RequestContext<RoleServer>,
+    ) -> Option<String> {
+        let arguments = arguments
+            .map(|arguments| canonical_json(&Value::Object(arguments.clone())))
+            .unwrap_or_else(|| "{}".to_owned());
+        match &self.scope {
+            Some(scope) => Some(format!("{}\0{tool}\0{arguments}", scope(context)?)),
+            None => Some(format!("{tool}\0{arguments}")),
+        }
+    }
+}
+
+impl<S: Service<RoleServer>, B: CacheBackend> Service<RoleServer> for CacheLayer<S, B> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        match &request {
+            ClientRequest::CallToolRequest(call) => {
+                // task-augmented calls expect a task back, not the result
+                let cached = (call.params.task.is_none())
+                    .then(|| self.ttl(&call.params.name))
+                    .flatten()
+                    .and_then(|ttl| {
+                        let key =
+                            self.key(&call.params.name, call.params.arguments.as_ref(), &context)?;
+                        Some((key, ttl))
+                    });
+                let Some((key, ttl)) = cached else {
+                    return self.inner.handle_request(request, context).await;
+                };
+                if let Some(result) = self.backend.get(&key).await {
+                    return Ok(ServerResult::CallToolResult(result));
+                }
+                let response = self.inner.handle_request(request, context).await?;
+                if let ServerResult::CallToolResult(result) = &response {
+                    if !result.is_error() {
+                        self.backend.set(key, result.clone(), ttl).await;
+                    }
+                }
+                Ok(response)
+            }
+            ClientRequest::ListToolsRequest(_) => {
+                let response = self.inner.handle_request(request, context).await?;
+                if let ServerResult::ListToolsResult(list) = &response {
+                    self.learn(&list.tools);
+                }
+                Ok(response)
+            }
+            _ => self.inner.handle_request(request, context).await,
+        }
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+
+    fn config(&self) -> ServiceConfig {
+        self.inner.config()
+    }
+}
@@ -0,0 +1,167 @@
+use std::{collections::HashMap, time::Duration};
+
+use tokio::{sync::mpsc, time::Instant};
+
+use crate::{
+    model::ResourceUpdatedNotificationParam,
+    service::{Peer, RoleServer, TaskKind, WeakPeer, spawn_task},
+};
+
+/// A notification that [`NotificationCoalescer`] can merge.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CoalescedNotification {
+    ToolListChanged,
+    PromptListChanged,
+    ResourceListChanged,
+    /// Merged per URI.
+    ResourceUpdated(String),
+}
+
+/// Rate limits `list_changed` and `resources/updated` notifications.
+///
+/// The first notification of a kind (per URI for resource updates) is sent
+/// right away; any more within `window` are merged into a single one sent when
+/// the window ends. Useful for servers driven by file watchers, which tend to
+/// report the same change many times in a row.
+///
+/// Cloning is cheap and all clones share the same windows. Pending
+/// notifications are flushed once the last clone is dropped. The coalescer
+/// only holds a [`WeakPeer`], so it doesn't keep the session alive and quietly
+/// stops sending once the session has closed.
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use rmcp::service::{NotificationCoalescer, Peer, RoleServer};
+/// # fn demo(peer: Peer<RoleServer>) {
+/// let coalescer = NotificationCoalescer::new(peer, Duration::from_millis(250));
+/// for _ in 0..100 {
+///     coalescer.resource_updated("file:///notes.md");
+/// }
+/// // the client sees at most two updates for notes.md
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NotificationCoalescer {
+    tx: mpsc::UnboundedSender<CoalescedNotification>,
+}
+
+impl NotificationCoalescer {
+    /// Start coalescing notifications sent to `peer`. Must be called within a
+    /// tokio runtime.
+    pub fn new(peer: Peer<RoleServer>, window: Duration) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        spawn_task(
+            TaskKind::Background,
+            "rmcp::notification_coalescer",
+            run(peer.downgrade(), window, rx),
+        );
+        Self { tx }
+    }
+
+    pub fn notify(&self, notification: CoalescedNotification) {
+        // the task only stops once every sender is gone
+        let _ = self.tx.send(notification);
+    }
+
+    pub fn tool_list_changed(&self) {
+        self.notify(CoalescedNotification::ToolListChanged)
+    }
+
+    pub fn prompt_list_changed(&self) {
+        self.notify(CoalescedNotification::PromptListChanged)
+    }
+
+    pub fn resource_list_changed(&self) {
+        self.notify(CoalescedNotification::ResourceListChanged)
+    }
+
+    pub fn resource_updated(&self, uri: impl Into<String>) {
+        self.notify(CoalescedNotification::ResourceUpdated(uri.into()))
+    }
+}
+
+#[derive(Debug)]
+struct Window {
+    ends_at: Instant,
+    pending: bool,
+}
+
+async fn run(
+    peer: WeakPeer<RoleServer>,
+    window: Duration,
+    mut rx: mpsc::UnboundedReceiver<CoalescedNotification>,
+) {
+    let mut windows = HashMap::<CoalescedNotification, Window>::new();
+    loop {
+        let next_flush = windows
+            .values()
+            .filter(|w| w.pending)
+            .map(|w| w.ends_at)
+            .min();
+        tokio::select! {
+            notification = rx.recv() => {
+                let Some(notification) = notification else {
+                    break;
+                };
+                let now = Instant::now();
+                match windows.get_mut(&notification) {
+                    Some(w) if w.ends_at > now => w.pending = true,
+                    _ => {
+                        if !send(&peer, &notification).await {
+                            break;
+                        }
+                        windows.insert(notification, Window {
+                            ends_at: now + window,
+                            pending: false,
+                        });
+                    }
+                }
+            }
+            _ = tokio::time::sleep_until(next_flush.unwrap_or_else(Instant::now)), if next_flush.is_some() => {
+                let now = Instant::now();
+                let due = windows
+                    .iter()
+                    .filter(|(_, w)| w.pending && w.ends_at <= now)
+                    .map(|(n, _)| n.clone())
+                    .collect::<Vec<_>>();
+                for notification in due {
+                    if !send(&peer, &notification).await {
+                        return;
+                    }
+                    windows.insert(notification, Window {
+                        ends_at: Instant::now() + window,
+                        pending: false,
+                    });
+                }
+            }
+        }
+        // forget idle windows so the map doesn't grow with every URI seen
+        let now = Instant::now();
+        windows.retain(|_, w| w.pending || w.ends_at > now);
+    }
+    for (notification, w) in windows {
+        if w.pending && !send(&peer, &notification).await {
+            break;
+        }
+    }
+}
+
+/// Returns `false` once the session is gone.
+async fn send(peer: &WeakPeer<RoleServer>, notification: &CoalescedNotification) -> bool {
+    let Some(peer) = peer.upgrade() else {
+        return false;
+    };
+    let result = match notification {
+        CoalescedNotification::ToolListChanged => peer.notify_tool_list_changed().await,
+        CoalescedNotification::PromptListChanged => peer.notify_prompt_list_changed().await,
+        CoalescedNotification::ResourceListChanged => peer.notify_resource_list_changed().await,
+        CoalescedNotification::ResourceUpdated(uri) => {
+            peer.notify_resource_updated(ResourceUpdatedNotificationParam { uri: uri.clone() })
+                .await
+        }
+    };
+    if let Err(error) = result {
+        tracing::debug!(%error, ?notification, "failed to send coalesced notification");
+    }
+    true
+}
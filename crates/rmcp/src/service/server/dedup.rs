@@ -0,0 +1,196 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use super::canonical_json;
+use crate::{
+    model::{
+        ClientNotification, ClientRequest, ConstString, ErrorData, ListPromptsRequestMethod,
+        ListResourceTemplatesRequestMethod, ListResourcesRequestMethod, ListToolsRequestMethod,
+        ReadResourceRequestMethod, ServerInfo, ServerResult,
+    },
+    service::{NotificationContext, RequestContext, RoleServer, Service, ServiceConfig},
+};
+
+type Response = Result<ServerResult, ErrorData>;
+type InFlight = Arc<Mutex<HashMap<String, Vec<oneshot::Sender<Response>>>>>;
+
+/// Runs identical requests arriving while one is still in flight only once,
+/// handing its response to all of them.
+///
+/// Agents often fire the same `tools/list` or `resources/read` twice in a
+/// row. Requests are identical when they have the same method and the same
+/// params, ignoring key order and `_meta`. Only the methods in
+/// [`DEFAULT_METHODS`](Self::DEFAULT_METHODS) are coalesced unless configured
+/// otherwise; add only methods without side effects, e.g. `tools/call` if
+/// every tool is idempotent.
+///
+/// Wrap each session's service, not a service shared by sessions: requests
+/// of different sessions must not be merged. If the request doing the work
+/// is dropped, the requests waiting for it run on their own.
+///
+/// ```rust
+/// # use rmcp::{ServerHandler, service::RequestDedupService};
+/// # struct Docs;
+/// # impl ServerHandler for Docs {}
+/// let service = RequestDedupService::new(Docs)
+///     .with_method("completion/complete")
+///     .without_method("resources/list");
+/// ```
+#[derive(Debug)]
+pub struct RequestDedupService<S> {
+    inner: S,
+    methods: HashSet<String>,
+    in_flight: InFlight,
+}
+
+impl<S> RequestDedupService<S> {
+    /// Methods coalesced by [`new`](Self::new): the list methods and
+    /// `resources/read`.
+    pub const DEFAULT_METHODS: &[&str] = &[
+        ListToolsRequestMethod::VALUE,
+        ListPromptsRequestMethod::VALUE,
+        ListResourcesRequestMethod::VALUE,
+        ListResourceTemplatesRequestMethod::VALUE,
+        ReadResourceRequestMethod::VALUE,
+    ];
+
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            methods: Self::DEFAULT_METHODS
+                .iter()
+                .map(|method| method.to_string())
+                .collect(),
+            in_flight: Default::default(),
+        }
+    }
+
+    pub fn with_method(mut self, method: impl Into<String>) -> Self {
+        self.methods.insert(method.into());
+        self
+    }
+
+    pub fn without_method(mut self, method: &str) -> Self {
+        self.methods.remove(method);
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Number of distinct requests currently being handled.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().expect("dedup state poisoned").len()
+    }
+
+    fn key(&self, request: &ClientRequest) -> Option<String> {
+        let method = request.method();
+        if !self.methods.contains(method) {
+            return None;
+        }
+        let mut params = serde_json::to_value(request)
+            .ok()?
+            .get_mut("params")
+            .map(Value::take)
+            .unwrap_or_default();
+        if let Value::Object(params) = &mut params {
+            // progress tokens and the like differ between otherwise equal requests
+            params.remove("_meta");
+        }
+        Some(format!("{method}\0{}", canonical_json(&params)))
+    }
+}
+
+/// Removes the in-flight entry of a leading request, waking up its waiters
+/// with the response, or with nothing if the request was dropped.
+struct Leader {
+    in_flight: InFlight,
+    key: Option<String>,
+}
+
+impl Leader {
+    fn finish(mut self, response: &Response) {
+        for waiter in self.take_waiters() {
+            let _ = waiter.send(response.clone());
+        }
+    }
+
+    fn take_waiters(&mut self) -> Vec<oneshot::Sender<Response>> {
+        let Some(key) = self.key.take() else {
+            return Vec::new();
+        };
+        self.in_flight
+            .lock()
+            .expect("dedup state poisoned")
+            .remove(&key)
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        // dropping the senders tells the waiters to run the request themselves
+        self.take_waiters();
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for RequestDedupService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        let Some(key) = self.key(&request) else {
+            return self.inner.handle_request(request, context).await;
+        };
+        let waiting = {
+            let mut in_flight = self.in_flight.lock().expect("dedup state poisoned");
+            match in_flight.get_mut(&key) {
+                Some(waiters) => {
+                    let (tx, rx) = oneshot::channel();
+                    waiters.push(tx);
+                    Some(rx)
+                }
+                None => {
+                    in_flight.insert(key.clone(), Vec::new());
+                    None
+                }
+            }
+        };
+        if let Some(rx) = waiting {
+            return match rx.await {
+                Ok(response) => response,
+                Err(_) => self.inner.handle_request(request, context).await,
+            };
+        }
+        let leader = Leader {
+            in_flight: self.in_flight.clone(),
+            key: Some(key),
+        };
+        let response = self.inner.handle_request(request, context).await;
+        leader.finish(&response);
+        response
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+
+    fn config(&self) -> ServiceConfig {
+        self.inner.config()
+    }
+}
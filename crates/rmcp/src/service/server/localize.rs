@@ -0,0 +1,133 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::{
+    model::{ClientNotification, ClientRequest, ErrorData, ServerInfo, ServerResult, Tool},
+    service::{NotificationContext, RequestContext, RoleServer, Service, ServiceConfig},
+};
+
+/// Translations of tool descriptions, by language tag and tool name.
+pub type ToolDescriptions = HashMap<String, HashMap<String, String>>;
+
+/// Lists tools with their descriptions in the locale of each session, see
+/// [`RequestContext::locale`].
+///
+/// A session asking for `fr-CA` gets the `fr-CA` descriptions, else the
+/// `fr` ones; tools without a translation, and sessions without a locale,
+/// keep the descriptions of the inner service. Tool calls are left alone:
+/// tools localize their outputs with [`RequestContext::locale`] themselves.
+///
+/// ```rust
+/// # use rmcp::{ServerHandler, service::LocalizedToolsService};
+/// # struct Search;
+/// # impl ServerHandler for Search {}
+/// let service = LocalizedToolsService::new(Search)
+///     .with_descriptions("fr", [("search", "Cherche dans la documentation.")])
+///     .with_descriptions("de", [("search", "Durchsucht die Dokumentation.")]);
+/// ```
+pub struct LocalizedToolsService<S> {
+    inner: S,
+    descriptions: ToolDescriptions,
+}
+
+impl<S: std::fmt::Debug> std::fmt::Debug for LocalizedToolsService<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalizedToolsService")
+            .field("inner", &self.inner)
+            .field("locales", &self.descriptions.keys())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> LocalizedToolsService<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            descriptions: HashMap::new(),
+        }
+    }
+
+    /// Descriptions of tools, by tool name, in `locale`, adding to those
+    /// already given.
+    pub fn with_descriptions<T, D>(
+        mut self,
+        locale: impl Into<String>,
+        descriptions: impl IntoIterator<Item = (T, D)>,
+    ) -> Self
+    where
+        T: Into<String>,
+        D: Into<String>,
+    {
+        self.descriptions
+            .entry(locale.into().to_ascii_lowercase())
+            .or_default()
+            .extend(
+                descriptions
+                    .into_iter()
+                    .map(|(tool, description)| (tool.into(), description.into())),
+            );
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The description of `tool` in `locale`, or in a less specific locale.
+    pub fn description(&self, tool: &str, locale: &str) -> Option<&str> {
+        let mut locale = locale.to_ascii_lowercase();
+        loop {
+            if let Some(description) = self
+                .descriptions
+                .get(&locale)
+                .and_then(|descriptions| descriptions.get(tool))
+            {
+                return Some(description);
+            }
+            locale.truncate(locale.rfind('-')?);
+        }
+    }
+
+    fn localize_tools(&self, tools: &mut [Tool], locale: &str) {
+        for tool in tools {
+            if let Some(description) = self.description(&tool.name, locale) {
+                tool.description = Some(Cow::Owned(description.to_owned()));
+            }
+        }
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for LocalizedToolsService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        let ClientRequest::ListToolsRequest(_) = &request else {
+            return self.inner.handle_request(request, context).await;
+        };
+        let response = self.inner.handle_request(request, context.clone()).await?;
+        Ok(match (response, context.locale()) {
+            (ServerResult::ListToolsResult(mut result), Some(locale)) => {
+                self.localize_tools(&mut result.tools, locale);
+                ServerResult::ListToolsResult(result)
+            }
+            (response, _) => response,
+        })
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+
+    fn config(&self) -> ServiceConfig {
+        self.inner.config()
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU8, AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::{
+    model::{ClientNotification, ClientRequest, ErrorData, ServerInfo},
+    service::{NotificationContext, RequestContext, RoleServer, Service, ServiceConfig},
+};
+
+/// How much a request matters when the server is under pressure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RequestPriority {
+    /// Listings and completions, which clients can do without for a while.
+    Low,
+    Normal,
+    /// `initialize`, `ping`, `tools/call` and task cancellation, never shed.
+    Critical,
+}
+
+impl RequestPriority {
+    pub fn of(request: &ClientRequest) -> Self {
+        match request {
+            ClientRequest::ListPromptsRequest(_)
+            | ClientRequest::ListResourcesRequest(_)
+            | ClientRequest::ListResourceTemplatesRequest(_)
+            | ClientRequest::ListToolsRequest(_)
+            | ClientRequest::ListTasksRequest(_)
+            | ClientRequest::CompleteRequest(_) => RequestPriority::Low,
+            ClientRequest::InitializeRequest(_)
+            | ClientRequest::PingRequest(_)
+            | ClientRequest::CallToolRequest(_)
+            | ClientRequest::CancelTaskRequest(_) => RequestPriority::Critical,
+            _ => RequestPriority::Normal,
+        }
+    }
+}
+
+/// How loaded the server is, see [`OverloadController`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Pressure {
+    Normal,
+    /// Over a threshold: low priority requests are shed.
+    Elevated,
+    /// Over twice a threshold: only critical requests are served.
+    Severe,
+}
+
+impl Pressure {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Pressure::Normal => "normal",
+            Pressure::Elevated => "elevated",
+            Pressure::Severe => "severe",
+        }
+    }
+
+    /// Whether requests of `priority` are rejected at this pressure.
+    pub fn sheds(&self, priority: RequestPriority) -> bool {
+        match self {
+            Pressure::Normal => false,
+            Pressure::Elevated => priority == RequestPriority::Low,
+            Pressure::Severe => priority < RequestPriority::Critical,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Pressure::Normal,
+            1 => Pressure::Elevated,
+            _ => Pressure::Severe,
+        }
+    }
+}
+
+impl std::fmt::Display for Pressure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// When an [`OverloadController`] considers the server under pressure.
+/// `None` means the signal is ignored.
+#[derive(Debug, Clone)]
+pub struct OverloadThresholds {
+    /// Requests being handled at once, over every session sharing the
+    /// controller.
+    pub max_in_flight: Option<usize>,
+    /// Average time to handle a request, weighted towards the recent ones.
+    /// While no request finishes, the average halves every `retry_after`,
+    /// so the pressure eases even when slow requests were all shed.
+    pub max_latency: Option<Duration>,
+    /// Sent to shed clients as `data.retryAfterMs`.
+    pub retry_after: Duration,
+}
+
+impl Default for OverloadThresholds {
+    fn default() -> Self {
+        Self {
+            max_in_flight: None,
+            max_latency: None,
+            retry_after: Duration::from_secs(1),
+        }
+    }
+}
+
+type PressureHook = Box<dyn Fn(Pressure) + Send + Sync>;
+
+/// Tracks the load of a server and decides which requests to shed.
+///
+/// Share one controller between the [`OverloadService`]s of all sessions, so
+/// they see the load of the whole server.
+///
+/// ```rust
+/// # use std::{sync::Arc, time::Duration};
+/// # use rmcp::{ServerHandler, service::{OverloadController, OverloadService, OverloadThresholds}};
+/// # struct Counter;
+/// # impl ServerHandler for Counter {}
+/// let controller = Arc::new(
+///     OverloadController::new(OverloadThresholds {
+///         max_in_flight: Some(64),
+///         max_latency: Some(Duration::from_millis(500)),
+///         ..Default::default()
+///     })
+///     .with_pressure_hook(|pressure| tracing::warn!(%pressure, "load changed")),
+/// );
+/// let service = OverloadService::new(Counter, controller.clone());
+/// ```
+pub struct OverloadController {
+    thresholds: OverloadThresholds,
+    in_flight: AtomicUsize,
+    latency_micros: AtomicU64,
+    /// When `latency_micros` was last updated, in microseconds since `epoch`.
+    latency_updated_at: AtomicU64,
+    epoch: Instant,
+    pressure: AtomicU8,
+    shed: AtomicU64,
+    on_pressure: Option<PressureHook>,
+}
+
+impl std::fmt::Debug for OverloadController {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OverloadController")
+            .field("thresholds", &self.thresholds)
+            .field("pressure", &self.pressure())
+            .field("in_flight", &self.in_flight())
+            .field("latency", &self.latency())
+            .finish()
+    }
+}
+
+impl OverloadController {
+    pub fn new(thresholds: OverloadThresholds) -> Self {
+        Self {
+            thresholds,
+            in_flight: AtomicUsize::new(0),
+            latency_micros: AtomicU64::new(0),
+            latency_updated_at: AtomicU64::new(0),
+            epoch: Instant::now(),
+            pressure: AtomicU8::new(Pressure::Normal as u8),
+            shed: AtomicU64::new(0),
+            on_pressure: None,
+        }
+    }
+
+    /// Call `hook` whenever the pressure changes, e.g. to scale out or to
+    /// stop background work.
+    pub fn with_pressure_hook(mut self, hook: impl Fn(Pressure) + Send + Sync + 'static) -> Self {
+        self.on_pressure = Some(Box::new(hook));
+        self
+    }
+
+    pub fn thresholds(&self) -> &OverloadThresholds {
+        &self.thresholds
+    }
+
+    pub fn pressure(&self) -> Pressure {
+        Pressure::from_u8(self.pressure.load(Ordering::Acquire))
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// The recent average time to handle a request.
+    pub fn latency(&self) -> Duration {
+        let average = self.latency_micros.load(Ordering::Relaxed);
+        Duration::from_micros((average as f64 * self.latency_decay(self.now_micros())) as u64)
+    }
+
+    fn now_micros(&self) -> u64 {
+        self.epoch.elapsed().as_micros().min(u64::MAX as u128) as u64
+    }
+
+    /// How much of the latency average is left at `now`, halving every
+    /// `retry_after` since the last update.
+    fn latency_decay(&self, now: u64) -> f64 {
+        let idle = now.saturating_sub(self.latency_updated_at.load(Ordering::Relaxed));
+        let half_life = self.thresholds.retry_after.max(Duration::from_millis(1));
+        0.5f64.powf(idle as f64 / half_life.as_micros() as f64)
+    }
+
+    /// How many requests were rejected so far.
+    pub fn shed_count(&self) -> u64 {
+        self.shed.load(Ordering::Relaxed)
+    }
+
+    fn admit(&self, request: &ClientRequest) -> Result<InFlight<'_>, ErrorData> {
+        // let the latency decay, nothing may have finished for a while
+        self.update();
+        let pressure = self.pressure();
+        if pressure.sheds(RequestPriority::of(request)) {
+            self.shed.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!(method = request.method(), %pressure, "shedding request");
+            let retry_after = self.thresholds.retry_after.as_millis() as u64;
+            return Err(ErrorData::server_overloaded(
+                format!("server overloaded, retry in {retry_after}ms"),
+                Some(serde_json::json!({
+                    "pressure": pressure.as_str(),
+                    "retryAfterMs": retry_after,
+                })),
+            ));
+        }
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        self.update();
+        Ok(InFlight {
+            controller: self,
+            started_at: Instant::now(),
+        })
+    }
+
+    fn finish(&self, elapsed: Duration) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+        let sample = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let now = self.now_micros();
+        let decay = self.latency_decay(now);
+        let _ = self
+            .latency_micros
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |average| {
+                let average = (average as f64 * decay) as u64;
+                // exponentially weighted, each sample counts for an eighth
+                Some(average - average / 8 + sample / 8)
+            });
+        self.latency_updated_at.store(now, Ordering::Relaxed);
+        self.update();
+    }
+
+    fn update(&self) {
+        let mut level = Pressure::Normal;
+        let mut raise = |value: u128, max: u128| {
+            if value > max.saturating_mul(2) {
+                level = Pressure::Severe;
+            } else if value > max && level < Pressure::Elevated {
+                level = Pressure::Elevated;
+            }
+        };
+        if let Some(max) = self.thresholds.max_in_flight {
+            raise(self.in_flight() as u128, max as u128);
+        }
+        if let Some(max) = self.thresholds.max_latency {
+            raise(self.latency().as_micros(), max.as_micros());
+        }
+        let previous = Pressure::from_u8(self.pressure.swap(level as u8, Ordering::AcqRel));
+        if previous != level {
+            tracing::info!(from = %previous, to = %level, "server pressure changed");
+            if let Some(hook) = &self.on_pressure {
+                hook(level);
+            }
+        }
+    }
+}
+
+struct InFlight<'a> {
+    controller: &'a OverloadController,
+    started_at: Instant,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.controller.finish(self.started_at.elapsed());
+    }
+}
+
+/// Sheds requests in front of a server service when its
+/// [`OverloadController`] reports pressure.
+///
+/// Shed requests fail with [`ErrorCode::SERVER_OVERLOADED`](crate::model::ErrorCode::SERVER_OVERLOADED),
+/// which clients treat as retryable. Lower [priorities](RequestPriority) go
+/// first, so `tools/call` keeps its latency while listings wait.
+#[derive(Debug)]
+pub struct OverloadService<S> {
+    inner: S,
+    controller: Arc<OverloadController>,
+}
+
+impl<S> OverloadService<S> {
+    pub fn new(inner: S, controller: Arc<OverloadController>) -> Self {
+        Self { inner, controller }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn controller(&self) -> &Arc<OverloadController> {
+        &self.controller
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for OverloadService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<crate::model::ServerResult, ErrorData> {
+        let _in_flight = self.controller.admit(&request)?;
+        self.inner.handle_request(request, context).await
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+
+    fn config(&self) -> ServiceConfig {
+        self.inner.config()
+    }
+}
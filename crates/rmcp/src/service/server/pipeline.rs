@@ -0,0 +1,647 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    model::{
+        CallToolResult, ClientNotification, ClientRequest, Content, ErrorData, JsonObject, Meta,
+        RawContent, RawTextContent, ReadResourceResult, ResourceContents, ServerInfo, ServerResult,
+        number::{self, NumberPolicy},
+    },
+    service::{NotificationContext, RequestContext, RoleServer, Service, ServiceConfig},
+};
+
+/// `_meta` key set on text content shortened by [`TruncateText`]; the value
+/// is `{"originalLength": <chars>, "strategy": "head" | "tail" | "middle"}`.
+pub const TRUNCATED_META_KEY: &str = "rmcp/truncated";
+
+/// One step of a [`ContentPipeline`].
+///
+/// Closures taking the tool name and the result implement this trait too.
+pub trait ContentTransformer: Send + Sync + 'static {
+    fn transform(&self, tool: &str, result: &mut CallToolResult);
+
+    /// Whether [`transform`](Self::transform) does enough CPU work that
+    /// [`ContentPipelineService`] should run it on the blocking thread pool.
+    fn is_blocking(&self) -> bool {
+        false
+    }
+
+    /// Adjust the `outputSchema` advertised for `tool` so it still describes
+    /// the structured content once [`transform`](Self::transform) has run.
+    fn transform_output_schema(&self, tool: &str, schema: &mut JsonObject) {
+        let _ = (tool, schema);
+    }
+}
+
+impl<F> ContentTransformer for F
+where
+    F: Fn(&str, &mut CallToolResult) + Send + Sync + 'static,
+{
+    fn transform(&self, tool: &str, result: &mut CallToolResult) {
+        self(tool, result)
+    }
+}
+
+/// Chain of [`ContentTransformer`]s run over tool results before they are
+/// sent to the client.
+///
+/// Transformers run in the order they were added. A tool can get its own
+/// pipeline with [`for_tool`](Self::for_tool), which replaces the default
+/// chain for that tool only. Resource reads are left alone unless
+/// [`limit_resources`](Self::limit_resources) sets caps for them.
+///
+/// ```rust
+/// # use rmcp::service::{ContentPipeline, ScrubText, TruncateText};
+/// let pipeline = ContentPipeline::new()
+///     .then(ScrubText::new(|text: &str| text.replace("hunter2", "***")))
+///     .then(TruncateText::new(4_000))
+///     // logs are long, but the client wants all of them
+///     .for_tool("read_logs", ContentPipeline::new());
+/// ```
+#[derive(Clone, Default)]
+pub struct ContentPipeline {
+    transformers: Vec<Arc<dyn ContentTransformer>>,
+    per_tool: HashMap<String, ContentPipeline>,
+    resources: Option<ContentLimits>,
+}
+
+impl std::fmt::Debug for ContentPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContentPipeline")
+            .field("transformers", &self.transformers.len())
+            .field("per_tool", &self.per_tool)
+            .field("resources", &self.resources)
+            .finish()
+    }
+}
+
+impl ContentPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a transformer to the default chain.
+    pub fn then(mut self, transformer: impl ContentTransformer) -> Self {
+        self.transformers.push(Arc::new(transformer));
+        self
+    }
+
+    /// Use `pipeline` instead of the default chain for results of `tool`.
+    pub fn for_tool(mut self, tool: impl Into<String>, pipeline: ContentPipeline) -> Self {
+        self.per_tool.insert(tool.into(), pipeline);
+        self
+    }
+
+    /// Cap the contents of `resources/read` results with `limits`.
+    pub fn limit_resources(mut self, limits: ContentLimits) -> Self {
+        self.resources = Some(limits);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transformers.is_empty()
+            && self.resources.is_none()
+            && self.per_tool.values().all(ContentPipeline::is_empty)
+    }
+
+    /// Whether the chain that applies to `tool` has a
+    /// [blocking](ContentTransformer::is_blocking) transformer.
+    pub fn is_blocking_for(&self, tool: &str) -> bool {
+        let pipeline = self.per_tool.get(tool).unwrap_or(self);
+        pipeline.transformers.iter().any(|t| t.is_blocking())
+    }
+
+    /// Run the chain that applies to `tool` over `result`.
+    pub fn apply(&self, tool: &str, result: &mut CallToolResult) {
+        let pipeline = self.per_tool.get(tool).unwrap_or(self);
+        for transformer in &pipeline.transformers {
+            transformer.transform(tool, result);
+        }
+    }
+
+    /// Run [`ContentTransformer::transform_output_schema`] of the chain that
+    /// applies to `tool` over `schema`.
+    pub fn apply_to_output_schema(&self, tool: &str, schema: &mut JsonObject) {
+        let pipeline = self.per_tool.get(tool).unwrap_or(self);
+        for transformer in &pipeline.transformers {
+            transformer.transform_output_schema(tool, schema);
+        }
+    }
+
+    /// Apply the [resource limits](Self::limit_resources), if any, to `result`.
+    pub fn apply_to_resource(&self, result: &mut ReadResourceResult) {
+        if let Some(limits) = &self.resources {
+            limits.apply_to_resource(result);
+        }
+    }
+}
+
+impl ContentTransformer for ContentPipeline {
+    fn transform(&self, tool: &str, result: &mut CallToolResult) {
+        self.apply(tool, result)
+    }
+
+    fn is_blocking(&self) -> bool {
+        self.transformers.iter().any(|t| t.is_blocking())
+            || self.per_tool.values().any(ContentPipeline::is_blocking)
+    }
+
+    fn transform_output_schema(&self, tool: &str, schema: &mut JsonObject) {
+        self.apply_to_output_schema(tool, schema)
+    }
+}
+
+/// Which part of an oversized text [`TruncateText`] keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TruncationStrategy {
+    /// The beginning, e.g. for documents.
+    #[default]
+    Head,
+    /// The end, e.g. for logs.
+    Tail,
+    /// Both ends, cutting the middle out, e.g. for command output.
+    Middle,
+}
+
+impl TruncationStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Head => "head",
+            Self::Tail => "tail",
+            Self::Middle => "middle",
+        }
+    }
+
+    /// The marker put where the text was cut.
+    pub fn default_marker(self) -> &'static str {
+        match self {
+            Self::Head => "\n[truncated]",
+            Self::Tail => "[truncated]\n",
+            Self::Middle => "\n[truncated]\n",
+        }
+    }
+}
+
+/// Cuts text content longer than `max_chars` characters.
+///
+/// The [`strategy`](TruncationStrategy) says which part of the text is kept.
+/// A shortened block has `marker` where the text was cut and carries
+/// [`TRUNCATED_META_KEY`] in its `_meta`, so clients can tell the text is
+/// incomplete.
+#[derive(Debug, Clone)]
+pub struct TruncateText {
+    pub max_chars: usize,
+    pub marker: String,
+    pub strategy: TruncationStrategy,
+}
+
+impl TruncateText {
+    pub const DEFAULT_MARKER: &str = "\n[truncated]";
+
+    /// Keep the first `max_chars` characters.
+    pub fn new(max_chars: usize) -> Self {
+        Self::with_strategy(max_chars, TruncationStrategy::Head)
+    }
+
+    /// Keep the last `max_chars` characters.
+    pub fn tail(max_chars: usize) -> Self {
+        Self::with_strategy(max_chars, TruncationStrategy::Tail)
+    }
+
+    /// Keep the first and last `max_chars / 2` characters.
+    pub fn middle(max_chars: usize) -> Self {
+        Self::with_strategy(max_chars, TruncationStrategy::Middle)
+    }
+
+    pub fn with_strategy(max_chars: usize, strategy: TruncationStrategy) -> Self {
+        Self {
+            max_chars,
+            marker: strategy.default_marker().to_owned(),
+            strategy,
+        }
+    }
+
+    pub fn with_marker(mut self, marker: impl Into<String>) -> Self {
+        self.marker = marker.into();
+        self
+    }
+
+    /// Cut `text` down to `max_chars` characters plus the marker, and return
+    /// what [`TRUNCATED_META_KEY`] should say about it, or `None` if it's
+    /// short enough already.
+    pub fn truncate(&self, text: &mut String) -> Option<serde_json::Value> {
+        let original_length = text.chars().count();
+        if original_length <= self.max_chars {
+            return None;
+        }
+        let byte_at = |text: &str, chars: usize| {
+            text.char_indices()
+                .nth(chars)
+                .map_or(text.len(), |(index, _)| index)
+        };
+        *text = match self.strategy {
+            TruncationStrategy::Head => {
+                format!("{}{}", &text[..byte_at(text, self.max_chars)], self.marker)
+            }
+            TruncationStrategy::Tail => {
+                let start = byte_at(text, original_length - self.max_chars);
+                format!("{}{}", self.marker, &text[start..])
+            }
+            TruncationStrategy::Middle => {
+                let end = byte_at(text, self.max_chars.div_ceil(2));
+                let start = byte_at(text, original_length - self.max_chars / 2);
+                format!("{}{}{}", &text[..end], self.marker, &text[start..])
+            }
+        };
+        Some(serde_json::json!({
+            "originalLength": original_length,
+            "strategy": self.strategy.as_str(),
+        }))
+    }
+}
+
+impl ContentTransformer for TruncateText {
+    fn transform(&self, _tool: &str, result: &mut CallToolResult) {
+        for content in &mut result.content {
+            let RawContent::Text(text) = &mut content.raw else {
+                continue;
+            };
+            if let Some(truncated) = self.truncate(&mut text.text) {
+                mark_truncated(&mut text.meta, truncated);
+            }
+        }
+    }
+}
+
+fn mark_truncated(meta: &mut Option<Meta>, truncated: serde_json::Value) {
+    meta.get_or_insert_with(Meta::new)
+        .insert(TRUNCATED_META_KEY.to_owned(), truncated);
+}
+
+/// Caps on each kind of content, for tool results and, with
+/// [`ContentPipeline::limit_resources`], resource reads.
+///
+/// Text blocks are cut by [`text`](Self::text), the text of resources by
+/// [`resource_text`](Self::resource_text). Images, audio and binary
+/// resources larger than [`max_media_bytes`](Self::max_media_bytes) are
+/// replaced by a short text saying what was left out. Either way the
+/// replacement carries [`TRUNCATED_META_KEY`] in its `_meta`; for media its
+/// value is `{"originalBytes": <bytes>, "strategy": "omitted"}`.
+///
+/// ```rust
+/// # use rmcp::service::{ContentLimits, TruncateText};
+/// let limits = ContentLimits::new()
+///     .with_text(TruncateText::middle(4_000))
+///     .with_resource_text(TruncateText::new(16_000))
+///     .with_max_media_bytes(1024 * 1024);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ContentLimits {
+    pub text: Option<TruncateText>,
+    pub resource_text: Option<TruncateText>,
+    pub max_media_bytes: Option<usize>,
+}
+
+impl ContentLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_text(mut self, truncate: TruncateText) -> Self {
+        self.text = Some(truncate);
+        self
+    }
+
+    pub fn with_resource_text(mut self, truncate: TruncateText) -> Self {
+        self.resource_text = Some(truncate);
+        self
+    }
+
+    pub fn with_max_media_bytes(mut self, max_media_bytes: usize) -> Self {
+        self.max_media_bytes = Some(max_media_bytes);
+        self
+    }
+
+    /// Apply the caps to the contents of a `resources/read` result.
+    pub fn apply_to_resource(&self, result: &mut ReadResourceResult) {
+        for contents in &mut result.contents {
+            self.limit_resource(contents);
+        }
+    }
+
+    fn limit_resource(&self, contents: &mut ResourceContents) {
+        match contents {
+            ResourceContents::TextResourceContents { text, meta, .. } => {
+                let Some(truncate) = &self.resource_text else {
+                    return;
+                };
+                if let Some(truncated) = truncate.truncate(text) {
+                    mark_truncated(meta, truncated);
+                }
+            }
+            ResourceContents::BlobResourceContents {
+                uri,
+                mime_type,
+                blob,
+                meta,
+                ..
+            } => {
+                let Some((text, truncated)) = self.omit(mime_type.as_deref(), blob) else {
+                    return;
+                };
+                let mut meta = meta.take();
+                mark_truncated(&mut meta, truncated);
+                *contents = ResourceContents::TextResourceContents {
+                    uri: std::mem::take(uri),
+                    mime_type: Some("text/plain".to_owned()),
+                    text,
+                    meta,
+                    size: None,
+                    last_modified: None,
+                };
+            }
+        }
+    }
+
+    /// The note standing for base64 `data` if it's over the cap.
+    fn omit(&self, mime_type: Option<&str>, data: &str) -> Option<(String, serde_json::Value)> {
+        let max_media_bytes = self.max_media_bytes?;
+        let bytes = data.len() / 4 * 3;
+        if bytes <= max_media_bytes {
+            return None;
+        }
+        let mime_type = mime_type.unwrap_or("binary content");
+        Some((
+            format!("[{mime_type} omitted: {bytes} bytes]"),
+            serde_json::json!({ "originalBytes": bytes, "strategy": "omitted" }),
+        ))
+    }
+}
+
+impl ContentTransformer for ContentLimits {
+    fn transform(&self, _tool: &str, result: &mut CallToolResult) {
+        for content in &mut result.content {
+            let omitted = match &mut content.raw {
+                RawContent::Text(text) => {
+                    if let Some(truncated) = self
+                        .text
+                        .as_ref()
+                        .and_then(|truncate| truncate.truncate(&mut text.text))
+                    {
+                        mark_truncated(&mut text.meta, truncated);
+                    }
+                    None
+                }
+                RawContent::Resource(embedded) => {
+                    self.limit_resource(&mut embedded.resource);
+                    None
+                }
+                RawContent::Image(image) => self.omit(Some(&image.mime_type), &image.data),
+                RawContent::Audio(audio) => self.omit(Some(&audio.mime_type), &audio.data),
+                RawContent::ResourceLink(_) => None,
+            };
+            if let Some((text, truncated)) = omitted {
+                let mut meta = None;
+                mark_truncated(&mut meta, truncated);
+                content.raw = RawContent::Text(RawTextContent { text, meta });
+            }
+        }
+    }
+}
+
+/// Rewrites every piece of text in a result, e.g. to mask personal data.
+///
+/// Applies to text content, embedded text resources and the structured
+/// content's string values.
+pub struct ScrubText<F> {
+    scrub: F,
+}
+
+impl<F> ScrubText<F>
+where
+    F: Fn(&str) -> String + Send + Sync + 'static,
+{
+    pub fn new(scrub: F) -> Self {
+        Self { scrub }
+    }
+
+    fn scrub_value(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(text) => *text = (self.scrub)(text),
+            serde_json::Value::Array(values) => {
+                values.iter_mut().for_each(|value| self.scrub_value(value))
+            }
+            serde_json::Value::Object(map) => {
+                map.values_mut().for_each(|value| self.scrub_value(value))
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<F> std::fmt::Debug for ScrubText<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScrubText").finish_non_exhaustive()
+    }
+}
+
+impl<F> ContentTransformer for ScrubText<F>
+where
+    F: Fn(&str) -> String + Send + Sync + 'static,
+{
+    fn transform(&self, _tool: &str, result: &mut CallToolResult) {
+        for content in &mut result.content {
+            match &mut content.raw {
+                RawContent::Text(text) => text.text = (self.scrub)(&text.text),
+                RawContent::Resource(embedded) => {
+                    if let ResourceContents::TextResourceContents { text, .. } =
+                        &mut embedded.resource
+                    {
+                        *text = (self.scrub)(text);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(structured) = &mut result.structured_content {
+            self.scrub_value(structured);
+        }
+    }
+}
+
+/// Shrinks images so neither side exceeds `max_dimension` pixels, see
+/// [`RawImageContent::downscale`](crate::model::RawImageContent::downscale).
+///
+/// Images that can't be decoded are passed on unchanged.
+#[cfg(feature = "image")]
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+#[derive(Debug, Clone, Copy)]
+pub struct DownscaleImages {
+    pub max_dimension: u32,
+}
+
+#[cfg(feature = "image")]
+impl DownscaleImages {
+    pub fn new(max_dimension: u32) -> Self {
+        Self { max_dimension }
+    }
+}
+
+#[cfg(feature = "image")]
+impl ContentTransformer for DownscaleImages {
+    fn transform(&self, tool: &str, result: &mut CallToolResult) {
+        for content in &mut result.content {
+            let RawContent::Image(image) = &mut content.raw else {
+                continue;
+            };
+            match image.downscale(self.max_dimension) {
+                Ok(downscaled) => *image = downscaled,
+                Err(error) => tracing::warn!(%error, tool, "failed to downscale image"),
+            }
+        }
+    }
+
+    fn is_blocking(&self) -> bool {
+        true
+    }
+}
+
+/// Checks the structured content of tool results against a
+/// [`NumberPolicy`].
+///
+/// With [`NumberPolicy::Reject`], a result holding an unsafe integer is
+/// replaced by an error result naming it. With
+/// [`NumberPolicy::EncodeAsString`], text blocks holding the serialized
+/// structured content are rewritten along with it, and the tool's
+/// `outputSchema` is widened to accept strings wherever it accepts numbers.
+impl ContentTransformer for NumberPolicy {
+    fn transform(&self, tool: &str, result: &mut CallToolResult) {
+        let Some(structured) = &mut result.structured_content else {
+            return;
+        };
+        let original = (*self == NumberPolicy::EncodeAsString).then(|| structured.clone());
+        if let Err(error) = self.apply(structured) {
+            tracing::warn!(%error, tool, "tool result rejected by number policy");
+            *result = CallToolResult::error(vec![Content::text(error.to_string())]);
+            return;
+        }
+        let Some(original) = original.filter(|original| original != structured) else {
+            return;
+        };
+        let encoded = structured.to_string();
+        for content in &mut result.content {
+            if let RawContent::Text(text) = &mut content.raw {
+                if serde_json::from_str::<serde_json::Value>(&text.text)
+                    .ok()
+                    .as_ref()
+                    == Some(&original)
+                {
+                    text.text = encoded.clone();
+                }
+            }
+        }
+    }
+
+    fn transform_output_schema(&self, _tool: &str, schema: &mut JsonObject) {
+        if *self == NumberPolicy::EncodeAsString {
+            number::allow_strings_for_numbers(schema);
+        }
+    }
+}
+
+/// Runs a [`ContentPipeline`] over the results of every `tools/call` and
+/// `resources/read` the inner service answers, and over the output schemas
+/// in its `tools/list` results.
+///
+/// ```rust
+/// # use rmcp::{ServerHandler, service::{ContentPipeline, ContentPipelineService, TruncateText}};
+/// # struct Counter;
+/// # impl ServerHandler for Counter {}
+/// let service = ContentPipelineService::new(
+///     Counter,
+///     ContentPipeline::new().then(TruncateText::new(4_000)),
+/// );
+/// ```
+#[derive(Debug)]
+pub struct ContentPipelineService<S> {
+    inner: S,
+    pipeline: ContentPipeline,
+}
+
+impl<S> ContentPipelineService<S> {
+    pub fn new(inner: S, pipeline: ContentPipeline) -> Self {
+        Self { inner, pipeline }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn pipeline(&self) -> &ContentPipeline {
+        &self.pipeline
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for ContentPipelineService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        let tool = match &request {
+            ClientRequest::CallToolRequest(call) => Some(call.params.name.clone()),
+            ClientRequest::ReadResourceRequest(_) | ClientRequest::ListToolsRequest(_) => None,
+            _ => return self.inner.handle_request(request, context).await,
+        };
+        let mut response = self.inner.handle_request(request, context).await?;
+        match (&mut response, tool) {
+            (ServerResult::ListToolsResult(list), None) => {
+                for tool in &mut list.tools {
+                    if let Some(schema) = &mut tool.output_schema {
+                        self.pipeline
+                            .apply_to_output_schema(&tool.name, Arc::make_mut(schema));
+                    }
+                }
+            }
+            (ServerResult::CallToolResult(result), Some(tool))
+                if self.pipeline.is_blocking_for(&tool) =>
+            {
+                let pipeline = self.pipeline.clone();
+                let mut owned = std::mem::replace(result, CallToolResult::success(Vec::new()));
+                let transformed = tokio::task::spawn_blocking(move || {
+                    pipeline.apply(&tool, &mut owned);
+                    owned
+                })
+                .await;
+                match transformed {
+                    Ok(transformed) => *result = transformed,
+                    Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+                    Err(error) => return Err(ErrorData::internal_error(error.to_string(), None)),
+                }
+            }
+            (ServerResult::CallToolResult(result), Some(tool)) => {
+                self.pipeline.apply(&tool, result)
+            }
+            (ServerResult::ReadResourceResult(result), None) => {
+                self.pipeline.apply_to_resource(result)
+            }
+            _ => {}
+        }
+        Ok(response)
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+
+    fn config(&self) -> ServiceConfig {
+        self.inner.config()
+    }
+}
@@ -0,0 +1,422 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::{sync::oneshot, time::Instant};
+
+use crate::{
+    model::{ClientNotification, ClientRequest, ErrorData, ServerInfo},
+    service::{NotificationContext, RequestContext, RoleServer, Service, ServiceConfig},
+};
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+/// Retry hint for requests over the concurrency limit, which has no known
+/// point in time where a slot frees up.
+const CONCURRENCY_RETRY_AFTER: Duration = Duration::from_millis(100);
+
+/// Limits applied to a single session by [`QuotaService`]. `None` means
+/// unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct SessionQuota {
+    pub max_requests_per_minute: Option<u32>,
+    pub max_concurrent_requests: Option<usize>,
+    /// Upper bound for the summed JSON size of every request the client
+    /// sends over the session's lifetime.
+    pub max_content_bytes: Option<u64>,
+}
+
+/// The limit a request ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaKind {
+    RequestsPerMinute,
+    ConcurrentRequests,
+    ContentBytes,
+}
+
+impl QuotaKind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            QuotaKind::RequestsPerMinute => "requestsPerMinute",
+            QuotaKind::ConcurrentRequests => "concurrentRequests",
+            QuotaKind::ContentBytes => "contentBytes",
+        }
+    }
+}
+
+impl std::fmt::Display for QuotaKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Snapshot of what a session has consumed so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuotaUsage {
+    pub requests_last_minute: u32,
+    pub in_flight: usize,
+    pub content_bytes: u64,
+    pub rejected: u64,
+}
+
+/// Enforces a [`SessionQuota`] in front of a server service.
+///
+/// Every session gets its own counters, so on a shared HTTP server wrap the
+/// service inside the factory to keep one busy client from starving the
+/// others. Requests over the rate or concurrency limit fail with the
+/// retryable [`ErrorCode::SERVER_OVERLOADED`] and a `data.retryAfterMs` hint;
+/// once the session has used up its content bytes, which never come back,
+/// requests fail with [`ErrorCode::INVALID_REQUEST`]. Either way `data.quota`
+/// names the limit and `data.limit` its value. `initialize` and `ping` are
+/// never limited.
+///
+/// To also share a server-wide concurrency limit fairly between sessions,
+/// give each of them the same [`FairScheduler`].
+///
+/// ```rust
+/// # use rmcp::{ServerHandler, service::{QuotaService, SessionQuota}};
+/// # struct Counter;
+/// # impl ServerHandler for Counter {}
+/// let service = QuotaService::new(
+///     Counter,
+///     SessionQuota {
+///         max_requests_per_minute: Some(600),
+///         max_concurrent_requests: Some(8),
+///         ..Default::default()
+///     },
+/// );
+/// ```
+///
+/// [`ErrorCode::SERVER_OVERLOADED`]: crate::model::ErrorCode::SERVER_OVERLOADED
+/// [`ErrorCode::INVALID_REQUEST`]: crate::model::ErrorCode::INVALID_REQUEST
+#[derive(Debug)]
+pub struct QuotaService<S> {
+    inner: S,
+    quota: SessionQuota,
+    scheduler: Option<(FairScheduler, u64)>,
+    recent: Mutex<VecDeque<Instant>>,
+    in_flight: AtomicUsize,
+    content_bytes: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl<S> QuotaService<S> {
+    pub fn new(inner: S, quota: SessionQuota) -> Self {
+        Self {
+            inner,
+            quota,
+            scheduler: None,
+            recent: Mutex::new(VecDeque::new()),
+            in_flight: AtomicUsize::new(0),
+            content_bytes: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Wait for a slot of `scheduler` before handling a request.
+    pub fn with_scheduler(mut self, scheduler: FairScheduler) -> Self {
+        let session = scheduler.register();
+        self.scheduler = Some((scheduler, session));
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn quota(&self) -> &SessionQuota {
+        &self.quota
+    }
+
+    pub fn usage(&self) -> QuotaUsage {
+        let mut recent = self.recent.lock().expect("quota state poisoned");
+        expire(&mut recent, Instant::now());
+        QuotaUsage {
+            requests_last_minute: recent.len() as u32,
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            content_bytes: self.content_bytes.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    fn admit(&self, request: &ClientRequest) -> Result<InFlightGuard<'_>, ErrorData> {
+        if let Some(max) = self.quota.max_concurrent_requests {
+            let admitted = self
+                .in_flight
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                    (n < max).then_some(n + 1)
+                })
+                .is_ok();
+            if !admitted {
+                return Err(self.reject(
+                    QuotaKind::ConcurrentRequests,
+                    max as u64,
+                    Some(CONCURRENCY_RETRY_AFTER),
+                ));
+            }
+        } else {
+            self.in_flight.fetch_add(1, Ordering::AcqRel);
+        }
+        // from here on the guard gives the concurrency slot back on rejection
+        let guard = InFlightGuard(&self.in_flight);
+
+        // held until every check passed, so that only admitted requests
+        // count against the rate
+        let now = Instant::now();
+        let mut recent = None;
+        if let Some(max) = self.quota.max_requests_per_minute {
+            let mut window = self.recent.lock().expect("quota state poisoned");
+            expire(&mut window, now);
+            if window.len() >= max as usize {
+                // the window has room again once its oldest request expires
+                let retry_after = window
+                    .front()
+                    .map_or(RATE_WINDOW, |at| RATE_WINDOW.saturating_sub(now - *at));
+                drop(window);
+                return Err(self.reject(
+                    QuotaKind::RequestsPerMinute,
+                    max.into(),
+                    Some(retry_after),
+                ));
+            }
+            recent = Some(window);
+        }
+
+        if let Some(max) = self.quota.max_content_bytes {
+            let size = serde_json::to_vec(request).map_or(0, |json| json.len() as u64);
+            let admitted = self
+                .content_bytes
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| {
+                    used.checked_add(size).filter(|total| *total <= max)
+                })
+                .is_ok();
+            if !admitted {
+                drop(recent);
+                return Err(self.reject(QuotaKind::ContentBytes, max, None));
+            }
+        }
+        if let Some(mut recent) = recent {
+            recent.push_back(now);
+        }
+        Ok(guard)
+    }
+
+    /// The error for a request over the `kind` limit, retryable after
+    /// `retry_after` if given.
+    fn reject(&self, kind: QuotaKind, limit: u64, retry_after: Option<Duration>) -> ErrorData {
+        let rejected = self.rejected.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::warn!(quota = %kind, limit, rejected, "session quota exceeded");
+        let message = format!("session quota exceeded: {kind}");
+        match retry_after {
+            Some(retry_after) => ErrorData::server_overloaded(
+                message,
+                Some(serde_json::json!({
+                    "quota": kind.as_str(),
+                    "limit": limit,
+                    "retryAfterMs": retry_after.as_millis() as u64,
+                })),
+            ),
+            None => ErrorData::invalid_request(
+                message,
+                Some(serde_json::json!({ "quota": kind.as_str(), "limit": limit })),
+            ),
+        }
+    }
+}
+
+fn expire(recent: &mut VecDeque<Instant>, now: Instant) {
+    while recent
+        .front()
+        .is_some_and(|at| now.duration_since(*at) >= RATE_WINDOW)
+    {
+        recent.pop_front();
+    }
+}
+
+struct InFlightGuard<'a>(&'a AtomicUsize);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A concurrency limit shared by the sessions of a server, see
+/// [`QuotaService::with_scheduler`].
+///
+/// When every slot is taken, requests wait for one instead of failing. Free
+/// slots go to the waiting sessions in turn, one request each, so a session
+/// with many requests queued up can't hold back the others.
+///
+/// ```rust
+/// # use rmcp::{ServerHandler, service::{FairScheduler, QuotaService, SessionQuota}};
+/// # struct Counter;
+/// # impl ServerHandler for Counter {}
+/// let scheduler = FairScheduler::new(32);
+/// // in the session factory
+/// let service = QuotaService::new(Counter, SessionQuota::default()).with_scheduler(scheduler.clone());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FairScheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+#[derive(Debug)]
+struct SchedulerInner {
+    max_in_flight: usize,
+    next_session: AtomicU64,
+    state: Mutex<SchedulerState>,
+}
+
+#[derive(Debug, Default)]
+struct SchedulerState {
+    in_flight: usize,
+    waiting: HashMap<u64, VecDeque<oneshot::Sender<()>>>,
+    /// Sessions with waiting requests, the next one to get a slot first.
+    turns: VecDeque<u64>,
+}
+
+impl FairScheduler {
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            inner: Arc::new(SchedulerInner {
+                max_in_flight,
+                next_session: AtomicU64::new(0),
+                state: Mutex::default(),
+            }),
+        }
+    }
+
+    /// Requests currently holding a slot.
+    pub fn in_flight(&self) -> usize {
+        self.state().in_flight
+    }
+
+    /// Requests waiting for a slot.
+    pub fn waiting(&self) -> usize {
+        self.state().waiting.values().map(VecDeque::len).sum()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, SchedulerState> {
+        self.inner.state.lock().expect("scheduler state poisoned")
+    }
+
+    fn register(&self) -> u64 {
+        self.inner.next_session.fetch_add(1, Ordering::Relaxed)
+    }
+
+    async fn acquire(&self, session: u64) -> SchedulerPermit {
+        let rx = {
+            let mut state = self.state();
+            if state.in_flight < self.inner.max_in_flight {
+                state.in_flight += 1;
+                return SchedulerPermit(self.clone());
+            }
+            let (tx, rx) = oneshot::channel();
+            if !state.waiting.contains_key(&session) {
+                state.turns.push_back(session);
+            }
+            state.waiting.entry(session).or_default().push_back(tx);
+            rx
+        };
+        let mut waiter = Waiter {
+            scheduler: self,
+            rx: Some(rx),
+        };
+        if let Some(rx) = waiter.rx.as_mut() {
+            // the sender is only dropped along with the scheduler
+            let _ = rx.await;
+        }
+        waiter.rx = None;
+        SchedulerPermit(self.clone())
+    }
+
+    /// Hand the slot of a finished request to the next session in turn, or
+    /// free it if nobody is waiting.
+    fn release(&self) {
+        let mut state = self.state();
+        while let Some(session) = state.turns.pop_front() {
+            let Some(queue) = state.waiting.get_mut(&session) else {
+                continue;
+            };
+            let waiter = queue.pop_front();
+            if queue.is_empty() {
+                state.waiting.remove(&session);
+            } else {
+                state.turns.push_back(session);
+            }
+            // requests given up on while waiting are skipped
+            if waiter.is_some_and(|waiter| waiter.send(()).is_ok()) {
+                return;
+            }
+        }
+        state.in_flight -= 1;
+    }
+}
+
+/// A request waiting for a slot. Gives the slot back if the request is
+/// dropped right after being granted one.
+struct Waiter<'a> {
+    scheduler: &'a FairScheduler,
+    rx: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        if let Some(mut rx) = self.rx.take() {
+            rx.close();
+            if rx.try_recv().is_ok() {
+                self.scheduler.release();
+            }
+        }
+    }
+}
+
+struct SchedulerPermit(FairScheduler);
+
+impl Drop for SchedulerPermit {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for QuotaService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<crate::model::ServerResult, ErrorData> {
+        if matches!(
+            request,
+            ClientRequest::InitializeRequest(_) | ClientRequest::PingRequest(_)
+        ) {
+            return self.inner.handle_request(request, context).await;
+        }
+        let _guard = self.admit(&request)?;
+        let _permit = match &self.scheduler {
+            Some((scheduler, session)) => Some(scheduler.acquire(*session).await),
+            None => None,
+        };
+        self.inner.handle_request(request, context).await
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+
+    fn config(&self) -> ServiceConfig {
+        self.inner.config()
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    model::{ClientNotification, ClientRequest, ErrorCode, ErrorData, ServerInfo, ServerResult},
+    service::{NotificationContext, RequestContext, RoleServer, Service, ServiceConfig},
+};
+
+/// How one tool was used, as counted by a [`UsageRecorder`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ToolUsage {
+    /// `tools/list` responses that offered the tool.
+    pub listed: u64,
+    /// `tools/call` requests for the tool.
+    pub called: u64,
+    /// Calls rejected with [`ErrorCode::INVALID_PARAMS`], usually because the
+    /// arguments didn't match the input schema.
+    pub invalid_arguments: u64,
+    /// Calls failing with any other protocol error.
+    pub failed: u64,
+    /// Calls answered with a result flagged `isError`.
+    pub tool_errors: u64,
+}
+
+impl ToolUsage {
+    /// Calls per listing; low values point at descriptions models don't pick
+    /// up on.
+    pub fn call_rate(&self) -> f64 {
+        ratio(self.called, self.listed)
+    }
+
+    /// Share of calls with arguments the tool rejected; high values point at
+    /// confusing schemas.
+    pub fn invalid_argument_rate(&self) -> f64 {
+        ratio(self.invalid_arguments, self.called)
+    }
+
+    /// Share of calls that didn't succeed, for whatever reason.
+    pub fn error_rate(&self) -> f64 {
+        ratio(
+            self.invalid_arguments + self.failed + self.tool_errors,
+            self.called,
+        )
+    }
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Snapshot of a [`UsageRecorder`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageSummary {
+    /// Successful `tools/list` responses.
+    pub list_requests: u64,
+    pub tools: BTreeMap<String, ToolUsage>,
+}
+
+/// Counts how tools get listed and called, to find tool descriptions and
+/// schemas that work poorly with models.
+///
+/// Cheap to clone; clones share the counters, so one recorder can collect
+/// the usage of every session of a server. Feed it with [`UsageService`].
+#[derive(Debug, Clone, Default)]
+pub struct UsageRecorder {
+    state: Arc<Mutex<UsageSummary>>,
+}
+
+impl UsageRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn summary(&self) -> UsageSummary {
+        self.state.lock().expect("usage state poisoned").clone()
+    }
+
+    /// Usage of one tool, all zeros if it was never seen.
+    pub fn tool(&self, name: &str) -> ToolUsage {
+        let state = self.state.lock().expect("usage state poisoned");
+        state.tools.get(name).copied().unwrap_or_default()
+    }
+
+    /// Clear all counters, e.g. after exporting a summary.
+    pub fn reset(&self) {
+        *self.state.lock().expect("usage state poisoned") = UsageSummary::default();
+    }
+
+    /// Record a `tools/list` response offering `tools`.
+    pub fn record_listed<'a>(&self, tools: impl IntoIterator<Item = &'a str>) {
+        let mut state = self.state.lock().expect("usage state poisoned");
+        state.list_requests += 1;
+        for tool in tools {
+            entry(&mut state.tools, tool).listed += 1;
+        }
+    }
+
+    /// Record the outcome of a `tools/call`.
+    pub fn record_call(&self, tool: &str, outcome: CallOutcome) {
+        let mut state = self.state.lock().expect("usage state poisoned");
+        let usage = entry(&mut state.tools, tool);
+        usage.called += 1;
+        match outcome {
+            CallOutcome::Success => {}
+            CallOutcome::ToolError => usage.tool_errors += 1,
+            CallOutcome::InvalidArguments => usage.invalid_arguments += 1,
+            CallOutcome::Failed => usage.failed += 1,
+        }
+    }
+}
+
+fn entry<'a>(tools: &'a mut BTreeMap<String, ToolUsage>, name: &str) -> &'a mut ToolUsage {
+    tools.entry(name.to_owned()).or_default()
+}
+
+/// How a `tools/call` ended, see [`UsageRecorder::record_call`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallOutcome {
+    Success,
+    /// The result was flagged `isError`.
+    ToolError,
+    /// The request failed with [`ErrorCode::INVALID_PARAMS`].
+    InvalidArguments,
+    /// The request failed with another error.
+    Failed,
+}
+
+impl CallOutcome {
+    pub fn of(result: &Result<ServerResult, ErrorData>) -> Self {
+        match result {
+            Ok(ServerResult::CallToolResult(result)) if result.is_error() => CallOutcome::ToolError,
+            Ok(_) => CallOutcome::Success,
+            Err(error) if error.code == ErrorCode::INVALID_PARAMS => CallOutcome::InvalidArguments,
+            Err(_) => CallOutcome::Failed,
+        }
+    }
+}
+
+/// Records the `tools/list` and `tools/call` traffic of the inner service
+/// into a [`UsageRecorder`].
+///
+/// ```rust
+/// # use rmcp::{ServerHandler, service::{UsageRecorder, UsageService}};
+/// # struct Counter;
+/// # impl ServerHandler for Counter {}
+/// let recorder = UsageRecorder::new();
+/// let service = UsageService::new(Counter, recorder.clone());
+/// // later, e.g. from an admin endpoint
+/// for (tool, usage) in recorder.summary().tools {
+///     println!("{tool}: {:.0}% invalid arguments", usage.invalid_argument_rate() * 100.0);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct UsageService<S> {
+    inner: S,
+    recorder: UsageRecorder,
+}
+
+impl<S> UsageService<S> {
+    pub fn new(inner: S, recorder: UsageRecorder) -> Self {
+        Self { inner, recorder }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn recorder(&self) -> &UsageRecorder {
+        &self.recorder
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for UsageService<S> {
+    async fn handle_request(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        match &request {
+            ClientRequest::CallToolRequest(call) => {
+                let tool = call.params.name.clone();
+                let result = self.inner.handle_request(request, context).await;
+                self.recorder.record_call(&tool, CallOutcome::of(&result));
+                result
+            }
+            ClientRequest::ListToolsRequest(_) => {
+                let result = self.inner.handle_request(request, context).await;
+                if let Ok(ServerResult::ListToolsResult(list)) = &result {
+                    self.recorder
+                        .record_listed(list.tools.iter().map(|tool| tool.name.as_ref()));
+                }
+                result
+            }
+            _ => self.inner.handle_request(request, context).await,
+        }
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+
+    fn config(&self) -> ServiceConfig {
+        self.inner.config()
+    }
+}
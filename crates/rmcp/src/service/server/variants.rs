@@ -0,0 +1,378 @@
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+use crate::{
+    model::{
+        ClientNotification, ClientRequest, ErrorData, ListToolsRequest, PaginatedRequestParams,
+        ServerInfo, ServerResult, Tool,
+    },
+    service::{NotificationContext, RequestContext, RoleServer, Service, ServiceConfig},
+};
+
+/// One alternative of a tool under experiment, see [`ToolVariantService`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolVariant {
+    /// Identifies the variant in [`SelectedToolVariant`], e.g. `"control"`.
+    pub label: String,
+    /// Name of the tool actually listed and called for this variant. `None`
+    /// keeps the experimented tool itself.
+    pub implementation: Option<String>,
+    /// Description replacing the listed one.
+    pub description: Option<String>,
+    /// Share of sessions getting this variant, relative to the other
+    /// variants of the tool.
+    pub weight: u32,
+}
+
+impl ToolVariant {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            implementation: None,
+            description: None,
+            weight: 1,
+        }
+    }
+
+    pub fn implementation(self, tool: impl Into<String>) -> Self {
+        Self {
+            implementation: Some(tool.into()),
+            ..self
+        }
+    }
+
+    pub fn description(self, description: impl Into<String>) -> Self {
+        Self {
+            description: Some(description.into()),
+            ..self
+        }
+    }
+
+    pub fn weight(self, weight: u32) -> Self {
+        Self { weight, ..self }
+    }
+}
+
+/// The variant chosen for a `tools/call`, inserted into the request
+/// extensions before the inner service handles it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedToolVariant {
+    pub tool: String,
+    pub label: String,
+}
+
+/// Picks which variant of a tool a session gets.
+///
+/// The choice should be stable for a session, since the listed tool and the
+/// called tool must agree. Return an index into `variants`; out of range
+/// indices fall back to the first variant.
+pub trait ToolVariantSelector: Send + Sync + 'static {
+    fn select(
+        &self,
+        tool: &str,
+        variants: &[ToolVariant],
+        context: &RequestContext<RoleServer>,
+    ) -> usize;
+}
+
+impl<F> ToolVariantSelector for F
+where
+    F: Fn(&str, &[ToolVariant], &RequestContext<RoleServer>) -> usize + Send + Sync + 'static,
+{
+    fn select(
+        &self,
+        tool: &str,
+        variants: &[ToolVariant],
+        context: &RequestContext<RoleServer>,
+    ) -> usize {
+        self(tool, variants, context)
+    }
+}
+
+type SessionKeyFn = dyn Fn(&RequestContext<RoleServer>) -> Option<String> + Send + Sync;
+
+/// Spreads sessions over the variants by a stable hash of a session key,
+/// honoring the variant weights. Sessions without a key get the first
+/// variant.
+#[derive(Clone)]
+pub struct SessionHashSelector {
+    key: Arc<SessionKeyFn>,
+}
+
+impl std::fmt::Debug for SessionHashSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionHashSelector")
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionHashSelector {
+    /// Hash the key returned by `key`, e.g. a user id taken from auth claims
+    /// in the request extensions.
+    pub fn new(
+        key: impl Fn(&RequestContext<RoleServer>) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self { key: Arc::new(key) }
+    }
+
+    /// Hash the `Mcp-Session-Id` of streamable HTTP sessions.
+    #[cfg(feature = "transport-streamable-http-server")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "transport-streamable-http-server")))]
+    pub fn by_session_id() -> Self {
+        use crate::transport::common::http_header::HEADER_SESSION_ID;
+        Self::new(|context| {
+            let parts = context.extensions.get::<http::request::Parts>()?;
+            let session_id = parts.headers.get(HEADER_SESSION_ID)?.to_str().ok()?;
+            Some(session_id.to_owned())
+        })
+    }
+}
+
+impl ToolVariantSelector for SessionHashSelector {
+    fn select(
+        &self,
+        tool: &str,
+        variants: &[ToolVariant],
+        context: &RequestContext<RoleServer>,
+    ) -> usize {
+        let Some(key) = (self.key)(context) else {
+            return 0;
+        };
+        let total: u64 = variants
+            .iter()
+            .map(|variant| u64::from(variant.weight))
+            .sum();
+        if total == 0 {
+            return 0;
+        }
+        // the tool name is mixed in so experiments on different tools don't
+        // put the same sessions in the same arm
+        let mut point = fnv1a([key.as_bytes(), b"\0", tool.as_bytes()]) % total;
+        for (index, variant) in variants.iter().enumerate() {
+            match point.checked_sub(u64::from(variant.weight)) {
+                Some(rest) => point = rest,
+                None => return index,
+            }
+        }
+        0
+    }
+}
+
+fn fnv1a<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> u64 {
+    parts
+        .into_iter()
+        .flatten()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Serves alternative descriptions or implementations of tools to different
+/// sessions, to try out tool prompt engineering in production.
+///
+/// For every tool with variants, `tools/list` shows the chosen variant under
+/// the tool's own name: the listing of its implementation tool, if any, with
+/// its description, if any. `tools/call` of the tool goes to the chosen
+/// implementation, with a [`SelectedToolVariant`] in the request extensions.
+/// Implementation tools are hidden from the listing and can't be called
+/// directly.
+///
+/// ```rust
+/// # use rmcp::{ServerHandler, service::{SessionHashSelector, ToolVariant, ToolVariantService}};
+/// # struct Search;
+/// # impl ServerHandler for Search {}
+/// let service = ToolVariantService::new(Search, SessionHashSelector::new(|_| None)).with_variants(
+///     "search",
+///     [
+///         ToolVariant::new("control"),
+///         ToolVariant::new("terse").description("Search the docs. Prefer short queries."),
+///         ToolVariant::new("semantic").implementation("semantic_search").weight(2),
+///     ],
+/// );
+/// ```
+pub struct ToolVariantService<S> {
+    inner: S,
+    selector: Box<dyn ToolVariantSelector>,
+    experiments: HashMap<String, Vec<ToolVariant>>,
+}
+
+impl<S: std::fmt::Debug> std::fmt::Debug for ToolVariantService<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolVariantService")
+            .field("inner", &self.inner)
+            .field("experiments", &self.experiments)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> ToolVariantService<S> {
+    pub fn new(inner: S, selector: impl ToolVariantSelector) -> Self {
+        Self {
+            inner,
+            selector: Box::new(selector),
+            experiments: HashMap::new(),
+        }
+    }
+
+    /// Experiment on `tool` with `variants`. Without any variant the tool is
+    /// left alone.
+    pub fn with_variants(
+        mut self,
+        tool: impl Into<String>,
+        variants: impl IntoIterator<Item = ToolVariant>,
+    ) -> Self {
+        let variants: Vec<_> = variants.into_iter().collect();
+        if !variants.is_empty() {
+            self.experiments.insert(tool.into(), variants);
+        }
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The variant of `tool` the session of `context` gets, if `tool` is
+    /// under experiment.
+    pub fn selected_variant(
+        &self,
+        tool: &str,
+        context: &RequestContext<RoleServer>,
+    ) -> Option<&ToolVariant> {
+        let variants = self.experiments.get(tool)?;
+        let index = self.selector.select(tool, variants, context);
+        variants.get(index).or(variants.first())
+    }
+
+    fn select_tools(
+        &self,
+        tools: Vec<Tool>,
+        all_tools: &[Tool],
+        context: &RequestContext<RoleServer>,
+    ) -> Vec<Tool> {
+        let by_name: HashMap<&str, &Tool> = all_tools
+            .iter()
+            .chain(&tools)
+            .map(|tool| (tool.name.as_ref(), tool))
+            .collect();
+        let mut selected = Vec::with_capacity(tools.len());
+        for tool in &tools {
+            if let Some(variant) = self.selected_variant(&tool.name, context) {
+                let mut listed = variant
+                    .implementation
+                    .as_deref()
+                    .and_then(|name| by_name.get(name))
+                    .copied()
+                    .unwrap_or(tool)
+                    .clone();
+                listed.name = tool.name.clone();
+                if let Some(description) = &variant.description {
+                    listed.description = Some(Cow::Owned(description.clone()));
+                }
+                selected.push(listed);
+            } else if !self.is_implementation(&tool.name) {
+                selected.push(tool.clone());
+            }
+        }
+        selected
+    }
+
+    /// Whether `tools` lacks an implementation tool selected for one of its
+    /// tools, which happens when the inner service paginates its listing.
+    fn misses_implementation(&self, tools: &[Tool], context: &RequestContext<RoleServer>) -> bool {
+        tools.iter().any(|tool| {
+            self.selected_variant(&tool.name, context)
+                .and_then(|variant| variant.implementation.as_deref())
+                .is_some_and(|name| !tools.iter().any(|tool| tool.name == name))
+        })
+    }
+
+    fn is_implementation(&self, name: &str) -> bool {
+        self.experiments
+            .values()
+            .flatten()
+            .any(|variant| variant.implementation.as_deref() == Some(name))
+    }
+}
+
+impl<S: Service<RoleServer>> ToolVariantService<S> {
+    /// Every tool of the inner service, following its pagination.
+    async fn all_tools(
+        &self,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<Vec<Tool>, ErrorData> {
+        let mut tools = Vec::new();
+        let mut cursor = None;
+        loop {
+            let request = ClientRequest::ListToolsRequest(ListToolsRequest::with_param(
+                PaginatedRequestParams { meta: None, cursor },
+            ));
+            match self.inner.handle_request(request, context.clone()).await? {
+                ServerResult::ListToolsResult(result) => {
+                    tools.extend(result.tools);
+                    cursor = result.next_cursor;
+                }
+                _ => cursor = None,
+            }
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+}
+
+impl<S: Service<RoleServer>> Service<RoleServer> for ToolVariantService<S> {
+    async fn handle_request(
+        &self,
+        mut request: ClientRequest,
+        mut context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, ErrorData> {
+        match &mut request {
+            ClientRequest::CallToolRequest(call) => {
+                if let Some(variant) = self.selected_variant(&call.params.name, &context) {
+                    context.extensions.insert(SelectedToolVariant {
+                        tool: call.params.name.to_string(),
+                        label: variant.label.clone(),
+                    });
+                    if let Some(implementation) = &variant.implementation {
+                        call.params.name = implementation.clone().into();
+                    }
+                } else if self.is_implementation(&call.params.name) {
+                    return Err(ErrorData::invalid_params("tool not found", None));
+                }
+                self.inner.handle_request(request, context).await
+            }
+            ClientRequest::ListToolsRequest(_) => {
+                let response = self.inner.handle_request(request, context.clone()).await?;
+                Ok(match response {
+                    ServerResult::ListToolsResult(mut result) => {
+                        let all_tools = if self.misses_implementation(&result.tools, &context) {
+                            self.all_tools(&context).await?
+                        } else {
+                            Vec::new()
+                        };
+                        result.tools = self.select_tools(result.tools, &all_tools, &context);
+                        ServerResult::ListToolsResult(result)
+                    }
+                    response => response,
+                })
+            }
+            _ => self.inner.handle_request(request, context).await,
+        }
+    }
+
+    async fn handle_notification(
+        &self,
+        notification: ClientNotification,
+        context: NotificationContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.inner.handle_notification(notification, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        self.inner.get_info()
+    }
+
+    fn config(&self) -> ServiceConfig {
+        self.inner.config()
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    future::Future,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use tokio::task::JoinHandle;
+
+/// What a task spawned by rmcp is for, see [`task_counts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TaskKind {
+    /// The loop of a running service, reading its transport and dispatching
+    /// messages. One per session.
+    ServeLoop,
+    /// A transport worker, e.g. a streamable HTTP client or server session.
+    TransportWorker,
+    /// A request or notification handler.
+    Handler,
+    /// A message being written to a transport.
+    TransportWrite,
+    /// Periodic or per-session chores: connection state forwarding, task
+    /// result sweeping, progress and notification senders.
+    Background,
+}
+
+impl TaskKind {
+    const ALL: [TaskKind; 5] = [
+        TaskKind::ServeLoop,
+        TaskKind::TransportWorker,
+        TaskKind::Handler,
+        TaskKind::TransportWrite,
+        TaskKind::Background,
+    ];
+
+    fn live(self) -> &'static AtomicUsize {
+        static LIVE: [AtomicUsize; TaskKind::ALL.len()] =
+            [const { AtomicUsize::new(0) }; TaskKind::ALL.len()];
+        &LIVE[self as usize]
+    }
+}
+
+/// The number of tasks rmcp has spawned that haven't finished yet, by
+/// [`TaskKind`]. Counts that keep growing point to sessions that never
+/// close.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TaskCounts {
+    pub serve_loops: usize,
+    pub transport_workers: usize,
+    pub handlers: usize,
+    pub transport_writes: usize,
+    pub background: usize,
+}
+
+impl TaskCounts {
+    pub fn get(&self, kind: TaskKind) -> usize {
+        match kind {
+            TaskKind::ServeLoop => self.serve_loops,
+            TaskKind::TransportWorker => self.transport_workers,
+            TaskKind::Handler => self.handlers,
+            TaskKind::TransportWrite => self.transport_writes,
+            TaskKind::Background => self.background,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        TaskKind::ALL.iter().map(|kind| self.get(*kind)).sum()
+    }
+}
+
+/// A snapshot of the live tasks of every session in the process, e.g. to
+/// export as gauges.
+///
+/// With `--cfg tokio_unstable` and the `tokio-console` feature, tasks are
+/// also named after what they run, so tokio-console shows which is which.
+pub fn task_counts() -> TaskCounts {
+    let live = |kind: TaskKind| kind.live().load(Ordering::Relaxed);
+    TaskCounts {
+        serve_loops: live(TaskKind::ServeLoop),
+        transport_workers: live(TaskKind::TransportWorker),
+        handlers: live(TaskKind::Handler),
+        transport_writes: live(TaskKind::TransportWrite),
+        background: live(TaskKind::Background),
+    }
+}
+
+struct Live(TaskKind);
+
+impl Drop for Live {
+    fn drop(&mut self) {
+        self.0.live().fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts `future` as a live task of `kind` until it is dropped, for tasks
+/// spawned on a `JoinSet`.
+pub(crate) fn counted<F: Future>(kind: TaskKind, future: F) -> impl Future<Output = F::Output> {
+    kind.live().fetch_add(1, Ordering::Relaxed);
+    let live = Live(kind);
+    async move {
+        let _live = live;
+        future.await
+    }
+}
+
+/// Spawns a counted task, named `name` where the runtime supports it.
+pub(crate) fn spawn<F>(kind: TaskKind, name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let future = counted(kind, future);
+    #[cfg(all(tokio_unstable, feature = "tokio-console"))]
+    {
+        tokio::task::Builder::new()
+            .name(name)
+            .spawn(future)
+            .expect("failed to spawn task")
+    }
+    #[cfg(not(all(tokio_unstable, feature = "tokio-console")))]
+    {
+        let _ = name;
+        tokio::spawn(future)
+    }
+}
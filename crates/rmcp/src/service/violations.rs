@@ -0,0 +1,24 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static DUPLICATE_REQUEST_IDS: AtomicU64 = AtomicU64::new(0);
+
+/// Protocol violations by peers, counted over every session in the process,
+/// e.g. to export as counters next to [`task_counts`](super::task_counts).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProtocolViolations {
+    /// Requests that reused the id of a request still being handled, and
+    /// were rejected as invalid.
+    pub duplicate_request_ids: u64,
+}
+
+/// A snapshot of the protocol violations counted so far.
+pub fn protocol_violations() -> ProtocolViolations {
+    ProtocolViolations {
+        duplicate_request_ids: DUPLICATE_REQUEST_IDS.load(Ordering::Relaxed),
+    }
+}
+
+pub(crate) fn record_duplicate_request_id() {
+    DUPLICATE_REQUEST_IDS.fetch_add(1, Ordering::Relaxed);
+}
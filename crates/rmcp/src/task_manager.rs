@@ -1,18 +1,28 @@
-use std::{any::Any, collections::HashMap, pin::Pin};
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    pin::Pin,
+    sync::Arc,
+};
 
 use futures::Future;
 use tokio::{
-    sync::mpsc,
-    time::{Duration, timeout},
+    sync::{Mutex, mpsc},
+    time::{Duration, Instant, timeout},
 };
 
 use crate::{
     RoleServer,
     error::{ErrorData as McpError, RmcpError as Error},
-    model::{CallToolResult, ClientRequest},
-    service::RequestContext,
+    model::{CallToolResult, ClientRequest, ProgressNotificationParam, Task, TaskStatus},
+    service::{MemoryAccount, MemoryKind, RequestContext, TaskKind, spawn_task},
 };
 
+mod progress;
+mod store;
+pub use progress::*;
+pub use store::*;
+
 /// Boxed future that represents an asynchronous operation managed by the processor.
 pub type OperationFuture =
     Pin<Box<dyn Future<Output = Result<Box<dyn OperationResultTransport>, Error>> + Send>>;
@@ -25,6 +35,9 @@ pub struct OperationDescriptor {
     pub client_request: Option<ClientRequest>,
     pub context: Option<RequestContext<RoleServer>>,
     pub ttl: Option<u64>,
+    /// Whether the task may be resumed from the [`TaskStore`] by another run.
+    pub resumable: bool,
+    pub progress: Option<TaskProgress>,
 }
 
 impl OperationDescriptor {
@@ -35,6 +48,8 @@ impl OperationDescriptor {
             client_request: None,
             context: None,
             ttl: None,
+            resumable: false,
+            progress: None,
         }
     }
 
@@ -52,6 +67,16 @@ impl OperationDescriptor {
         self.ttl = Some(ttl);
         self
     }
+
+    pub fn with_resumable(mut self) -> Self {
+        self.resumable = true;
+        self
+    }
+
+    pub fn with_progress(mut self, progress: TaskProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
 }
 
 /// Operation message describing a unit of asynchronous work.
@@ -70,10 +95,42 @@ impl OperationMessage {
 pub trait OperationResultTransport: Send + Sync + 'static {
     fn operation_id(&self) -> &String;
     fn as_any(&self) -> &dyn std::any::Any;
+    /// Approximate size of the result in bytes, counted against
+    /// [`RetentionPolicy::max_result_bytes`].
+    fn byte_size(&self) -> usize {
+        0
+    }
+}
+
+/// How long completed results are kept before they expire.
+///
+/// The default keeps every result until it is collected or taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Drop a completed result this long after its operation finished.
+    pub result_ttl: Option<Duration>,
+    /// Cap on the total [`byte_size`](OperationResultTransport::byte_size) of
+    /// completed results; the oldest are dropped first once it is exceeded.
+    pub max_result_bytes: Option<usize>,
+}
+
+impl RetentionPolicy {
+    pub fn with_result_ttl(mut self, ttl: Duration) -> Self {
+        self.result_ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_max_result_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_result_bytes = Some(max_bytes);
+        self
+    }
 }
 
 // ===== Operation Processor =====
 pub const DEFAULT_TASK_TIMEOUT_SECS: u64 = 300; // 5 minutes
+/// How many expired task ids are remembered to tell them apart from unknown ones.
+pub const EXPIRED_TASK_HISTORY: usize = 1024;
+pub const DEFAULT_TASK_LEASE_SECS: u64 = 30;
 /// Operation processor that coordinates extractors and handlers
 pub struct OperationProcessor {
     /// Currently running tasks keyed by id
@@ -82,6 +139,15 @@ pub struct OperationProcessor {
     completed_results: Vec<TaskResult>,
     task_result_receiver: Option<mpsc::UnboundedReceiver<TaskResult>>,
     task_result_sender: mpsc::UnboundedSender<TaskResult>,
+    retention: RetentionPolicy,
+    /// Ids of results dropped by the retention policy, oldest first
+    expired_ids: VecDeque<String>,
+    store: Option<Arc<dyn TaskStore>>,
+    /// Owner of the tasks this processor runs, see [`TaskOwner`]
+    instance_id: String,
+    lease: Duration,
+    leases_renewed_at: Instant,
+    memory: Option<MemoryAccount>,
 }
 
 struct RunningTask {
@@ -94,6 +160,25 @@ struct RunningTask {
 pub struct TaskResult {
     pub descriptor: OperationDescriptor,
     pub result: Result<Box<dyn OperationResultTransport>, Error>,
+    /// When the operation finished, was cancelled or timed out.
+    pub completed_at: Instant,
+}
+
+impl TaskResult {
+    /// Completed, or failed if the operation or the tool call returned an error.
+    pub fn status(&self) -> TaskStatus {
+        match &self.result {
+            Ok(result) => match result.as_any().downcast_ref::<ToolCallTaskResult>() {
+                Some(ToolCallTaskResult { result: Err(_), .. }) => TaskStatus::Failed,
+                _ => TaskStatus::Completed,
+            },
+            Err(_) => TaskStatus::Failed,
+        }
+    }
+
+    fn byte_size(&self) -> usize {
+        self.result.as_ref().map_or(0, |result| result.byte_size())
+    }
 }
 
 /// Helper to generate an ISO 8601 timestamp for task metadata.
@@ -101,17 +186,27 @@ pub fn current_timestamp() -> String {
     chrono::Utc::now().to_rfc3339()
 }
 
+fn unix_millis() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
 /// Result transport for tool calls executed as tasks.
 pub struct ToolCallTaskResult {
     id: String,
     pub result: Result<CallToolResult, McpError>,
+    byte_size: usize,
 }
 
 impl ToolCallTaskResult {
     pub fn new(id: impl Into<String>, result: Result<CallToolResult, McpError>) -> Self {
+        let byte_size = match &result {
+            Ok(result) => serde_json::to_vec(result).map_or(0, |json| json.len()),
+            Err(error) => serde_json::to_vec(error).map_or(0, |json| json.len()),
+        };
         Self {
             id: id.into(),
             result,
+            byte_size,
         }
     }
 }
@@ -124,6 +219,10 @@ impl OperationResultTransport for ToolCallTaskResult {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn byte_size(&self) -> usize {
+        self.byte_size
+    }
 }
 
 impl Default for OperationProcessor {
@@ -140,9 +239,210 @@ impl OperationProcessor {
             completed_results: Vec::new(),
             task_result_receiver: Some(task_result_receiver),
             task_result_sender,
+            retention: RetentionPolicy::default(),
+            expired_ids: VecDeque::new(),
+            store: None,
+            instance_id: format!("{}-{}", std::process::id(), unix_millis()),
+            lease: Duration::from_secs(DEFAULT_TASK_LEASE_SECS),
+            leases_renewed_at: Instant::now(),
+            memory: None,
+        }
+    }
+
+    /// Keep tasks in `store` too, so other instances sharing it, or a later
+    /// run, can answer for them and [resume](Self::resume_tasks) them.
+    ///
+    /// The leases of running tasks are only renewed while finished tasks are
+    /// [received](Self::receive_completed_results), so also start
+    /// [`spawn_eviction`](Self::spawn_eviction); otherwise other instances
+    /// take over tasks still running here once their lease runs out.
+    pub fn with_store(mut self, store: Arc<dyn TaskStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Run tasks as `instance_id`, holding each for `lease` at a time. A task
+    /// whose lease runs out, e.g. because the instance died, may be resumed
+    /// by another instance.
+    pub fn with_ownership(mut self, instance_id: impl Into<String>, lease: Duration) -> Self {
+        self.instance_id = instance_id.into();
+        self.lease = lease;
+        self
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    fn lease_owner(&self) -> TaskOwner {
+        TaskOwner {
+            instance_id: self.instance_id.clone(),
+            lease_expires_at: unix_millis() + self.lease.as_millis() as u64,
+        }
+    }
+
+    /// The stored record of `task_id`, e.g. a task run by another instance.
+    pub fn stored_task(&self, task_id: &str) -> Option<TaskRecord> {
+        let store = self.store.as_ref()?;
+        store.load(task_id).unwrap_or_else(|error| {
+            tracing::warn!(%error, task_id, "failed to load stored task");
+            None
+        })
+    }
+
+    /// Claim the resumable tasks the store holds unfinished, left by an earlier
+    /// run or by an instance whose lease ran out, and run them again with the
+    /// future `resume` builds for each. Tasks it can't build one for are
+    /// marked failed. Returns how many tasks were resumed.
+    #[allow(clippy::result_large_err)]
+    pub fn resume_tasks(
+        &mut self,
+        mut resume: impl FnMut(&TaskRecord) -> Option<OperationFuture>,
+    ) -> Result<usize, Error> {
+        let Some(store) = self.store.clone() else {
+            return Ok(0);
+        };
+        let records = store
+            .list()
+            .map_err(|error| Error::TaskError(format!("failed to list stored tasks: {error}")))?;
+        let owner = self.lease_owner();
+        let now = unix_millis();
+        let mut resumed = 0;
+        for record in records {
+            let task_id = record.task.task_id.clone();
+            if !record.resumable
+                || self.running_tasks.contains_key(&task_id)
+                || !record.claimable_by(&owner, now)
+            {
+                continue;
+            }
+            let Some(record) = store.claim(&task_id, &owner, now).map_err(|error| {
+                Error::TaskError(format!("failed to claim task {task_id}: {error}"))
+            })?
+            else {
+                continue;
+            };
+            let Some(future) = resume(&record) else {
+                self.finish_stored(
+                    &task_id,
+                    TaskStatus::Failed,
+                    Some("Operation can't be resumed".to_string()),
+                    None,
+                );
+                continue;
+            };
+            let mut descriptor = OperationDescriptor::new(task_id, record.name).with_resumable();
+            descriptor.client_request = record.request;
+            descriptor.ttl = record.task.ttl;
+            self.spawn_async_task(OperationMessage::new(descriptor, future));
+            resumed += 1;
+        }
+        Ok(resumed)
+    }
+
+    /// Record the outcome of `task_id` in the store, if there is one.
+    fn finish_stored(
+        &self,
+        task_id: &str,
+        status: TaskStatus,
+        status_message: Option<String>,
+        result: Option<serde_json::Value>,
+    ) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let Some(mut record) = self.stored_task(task_id) else {
+            return;
+        };
+        // another instance may have finished it already
+        if record.task.transition(status, status_message).is_err() {
+            return;
+        }
+        record.owner = None;
+        record.result = result;
+        if let Err(error) = store.save(&record) {
+            tracing::warn!(%error, task_id, "failed to store task outcome");
         }
     }
 
+    fn forget_stored(&self, task_id: &str) {
+        if let Some(store) = &self.store {
+            if let Err(error) = store.remove(task_id) {
+                tracing::warn!(%error, task_id, "failed to remove stored task");
+            }
+        }
+    }
+
+    /// Extend the leases of the running tasks, stopping those another instance
+    /// took over in the meantime.
+    fn renew_leases(&mut self) {
+        let Some(store) = self.store.clone() else {
+            return;
+        };
+        if self.leases_renewed_at.elapsed() < self.lease / 3 {
+            return;
+        }
+        self.leases_renewed_at = Instant::now();
+        let owner = self.lease_owner();
+        let now = unix_millis();
+        let mut lost = Vec::new();
+        for task_id in self.running_tasks.keys() {
+            match store.claim(task_id, &owner, now) {
+                Ok(Some(_)) => {}
+                Ok(None) => lost.push(task_id.clone()),
+                Err(error) => tracing::warn!(%error, task_id, "failed to renew task lease"),
+            }
+        }
+        for task_id in lost {
+            if let Some(task) = self.running_tasks.remove(&task_id) {
+                tracing::warn!(task_id, "task lease lost to another instance");
+                task.task_handle.abort();
+            }
+        }
+    }
+
+    /// Record the bytes of the completed results held in `memory`, usually
+    /// the [`Peer::memory_account`](crate::Peer::memory_account) of the
+    /// session the processor serves.
+    pub fn with_memory_account(mut self, memory: MemoryAccount) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    fn account_results(&self) {
+        if let Some(memory) = &self.memory {
+            memory.set(MemoryKind::TaskResults, self.retained_bytes());
+        }
+    }
+
+    /// Expire completed results according to `retention`.
+    pub fn with_retention(mut self, retention: RetentionPolicy) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Evict expired results every `interval` until the processor is dropped.
+    ///
+    /// With a [store](Self::with_store) this also records finished tasks there
+    /// and renews the task leases, and is then required: the interval should
+    /// be well under the lease.
+    pub fn spawn_eviction(
+        processor: &Arc<Mutex<Self>>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let processor = Arc::downgrade(processor);
+        spawn_task(TaskKind::Background, "rmcp::task_eviction", async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(processor) = processor.upgrade() else {
+                    break;
+                };
+                processor.lock().await.receive_completed_results();
+            }
+        })
+    }
+
     /// Submit an operation for asynchronous execution.
     #[allow(clippy::result_large_err)]
     pub fn submit_operation(&mut self, message: OperationMessage) -> Result<(), Error> {
@@ -155,6 +455,31 @@ impl OperationProcessor {
                 message.descriptor.operation_id
             )));
         }
+        if let Some(store) = &self.store {
+            let descriptor = &message.descriptor;
+            let timestamp = current_timestamp();
+            let record = TaskRecord {
+                task: Task {
+                    task_id: descriptor.operation_id.clone(),
+                    status: TaskStatus::Working,
+                    created_at: timestamp.clone(),
+                    last_updated_at: Some(timestamp),
+                    ttl: descriptor.ttl,
+                    ..Default::default()
+                },
+                name: descriptor.name.clone(),
+                request: descriptor.client_request.clone(),
+                resumable: descriptor.resumable,
+                owner: Some(self.lease_owner()),
+                result: None,
+            };
+            store.save(&record).map_err(|error| {
+                Error::TaskError(format!(
+                    "failed to store task {}: {error}",
+                    descriptor.operation_id
+                ))
+            })?;
+        }
         self.spawn_async_task(message);
         Ok(())
     }
@@ -177,11 +502,12 @@ impl OperationProcessor {
             }
         };
 
-        let handle = tokio::spawn(async move {
+        let handle = spawn_task(TaskKind::Handler, "rmcp::task", async move {
             let result = timed_future.await;
             let task_result = TaskResult {
                 descriptor: descriptor_for_result,
                 result,
+                completed_at: Instant::now(),
             };
             let _ = sender.send(task_result);
         });
@@ -196,13 +522,132 @@ impl OperationProcessor {
 
     /// Collect completed results from running tasks and remove them from the running tasks map.
     pub fn collect_completed_results(&mut self) -> Vec<TaskResult> {
+        self.receive_completed_results();
+        let results = std::mem::take(&mut self.completed_results);
+        self.account_results();
+        results
+    }
+
+    /// Move finished tasks into the completed results, keeping them there for
+    /// [`peek_completed`](Self::peek_completed) and
+    /// [`take_completed_result`](Self::take_completed_result), then apply the
+    /// retention policy. With a store, their outcome is recorded there and
+    /// the leases of running tasks are renewed as needed.
+    pub fn receive_completed_results(&mut self) {
+        let mut received = Vec::new();
         if let Some(receiver) = &mut self.task_result_receiver {
             while let Ok(result) = receiver.try_recv() {
-                self.running_tasks.remove(&result.descriptor.operation_id);
-                self.completed_results.push(result);
+                received.push(result);
+            }
+        }
+        for result in received {
+            self.running_tasks.remove(&result.descriptor.operation_id);
+            let (status_message, value) = match &result.result {
+                Ok(boxed) => match boxed.as_any().downcast_ref::<ToolCallTaskResult>() {
+                    Some(ToolCallTaskResult {
+                        result: Ok(call), ..
+                    }) => (None, serde_json::to_value(call).ok()),
+                    Some(ToolCallTaskResult {
+                        result: Err(error), ..
+                    }) => (Some(error.message.to_string()), None),
+                    None => (None, None),
+                },
+                Err(error) => (Some(error.to_string()), None),
+            };
+            self.finish_stored(
+                &result.descriptor.operation_id,
+                result.status(),
+                status_message,
+                value,
+            );
+            self.completed_results.push(result);
+        }
+        self.renew_leases();
+        self.evict_expired();
+    }
+
+    /// Drop completed results that are past the retention TTL, then the oldest
+    /// ones until the byte cap is met. Returns how many were dropped.
+    pub fn evict_expired(&mut self) -> usize {
+        let before = self.completed_results.len();
+        let mut evicted = Vec::new();
+        if let Some(ttl) = self.retention.result_ttl {
+            let now = Instant::now();
+            let (expired, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.completed_results)
+                .into_iter()
+                .partition(|result| now.duration_since(result.completed_at) >= ttl);
+            self.completed_results = kept;
+            evicted = expired;
+        }
+        if let Some(max_bytes) = self.retention.max_result_bytes {
+            self.completed_results
+                .sort_by_key(|result| result.completed_at);
+            let mut total = self.retained_bytes();
+            let mut oldest = 0;
+            while total > max_bytes && oldest < self.completed_results.len() {
+                total -= self.completed_results[oldest].byte_size();
+                oldest += 1;
+            }
+            evicted.extend(self.completed_results.drain(..oldest));
+        }
+        for result in evicted {
+            self.forget_stored(&result.descriptor.operation_id);
+            if self.expired_ids.len() == EXPIRED_TASK_HISTORY {
+                self.expired_ids.pop_front();
+            }
+            self.expired_ids.push_back(result.descriptor.operation_id);
+        }
+        self.account_results();
+        before - self.completed_results.len()
+    }
+
+    /// Total [`byte_size`](OperationResultTransport::byte_size) of the completed results held.
+    pub fn retained_bytes(&self) -> usize {
+        self.completed_results
+            .iter()
+            .map(TaskResult::byte_size)
+            .sum()
+    }
+
+    /// Wait up to `timeout` for `task_id` to finish, returning its status if it did.
+    pub async fn wait_for_completion(
+        processor: &Mutex<Self>,
+        task_id: &str,
+        timeout: Duration,
+    ) -> Option<TaskStatus> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            {
+                let mut processor = processor.lock().await;
+                processor.receive_completed_results();
+                if let Some(result) = processor
+                    .completed_results
+                    .iter()
+                    .rev()
+                    .find(|result| result.descriptor.operation_id == task_id)
+                {
+                    return Some(result.status());
+                }
+                if !processor.running_tasks.contains_key(task_id) {
+                    return None;
+                }
             }
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            tokio::time::sleep((deadline - now).min(Duration::from_millis(10))).await;
         }
-        std::mem::take(&mut self.completed_results)
+    }
+
+    /// Whether the result of `task_id` was dropped by the retention policy.
+    pub fn is_expired(&self, task_id: &str) -> bool {
+        !self.running_tasks.contains_key(task_id)
+            && !self
+                .completed_results
+                .iter()
+                .any(|result| result.descriptor.operation_id == task_id)
+            && self.expired_ids.iter().any(|id| id == task_id)
     }
 
     /// Check for tasks that have exceeded their timeout and handle them appropriately.
@@ -221,13 +666,21 @@ impl OperationProcessor {
 
         for task_id in timed_out_tasks {
             if let Some(task) = self.running_tasks.remove(&task_id) {
+                self.finish_stored(
+                    &task_id,
+                    TaskStatus::Failed,
+                    Some("Operation timed out".to_string()),
+                    None,
+                );
                 let timeout_result = TaskResult {
                     descriptor: task.descriptor,
                     result: Err(Error::TaskError("Operation timed out".to_string())),
+                    completed_at: Instant::now(),
                 };
                 self.completed_results.push(timeout_result);
             }
         }
+        self.account_results();
     }
 
     /// Get the number of running tasks.
@@ -241,6 +694,7 @@ impl OperationProcessor {
             task.task_handle.abort();
         }
         self.completed_results.clear();
+        self.account_results();
     }
     /// List running task ids.
     pub fn list_running(&self) -> Vec<String> {
@@ -264,14 +718,35 @@ impl OperationProcessor {
             .map(|result| &result.descriptor)
     }
 
+    /// The progress tree of a running or recently completed task.
+    pub fn task_progress(&self, task_id: &str) -> Option<TaskProgressNode> {
+        self.task_descriptor(task_id)?
+            .progress
+            .as_ref()
+            .map(TaskProgress::snapshot)
+    }
+
+    /// Report the progress of a request nested in a running task, returning
+    /// whether its token belonged to one, see [`TaskProgress::progress_token`].
+    pub fn route_progress(&self, params: &ProgressNotificationParam) -> bool {
+        self.running_tasks.values().any(|task| {
+            task.descriptor
+                .progress
+                .as_ref()
+                .is_some_and(|progress| progress.on_progress(params))
+        })
+    }
+
     /// Attempt to cancel a running task.
     pub fn cancel_task(&mut self, task_id: &str) -> bool {
         if let Some(task) = self.running_tasks.remove(task_id) {
             task.task_handle.abort();
+            self.finish_stored(task_id, TaskStatus::Cancelled, None, None);
             // Insert a cancelled result so callers can observe the terminal state.
             let cancel_result = TaskResult {
                 descriptor: task.descriptor,
                 result: Err(Error::TaskError("Operation cancelled".to_string())),
+                completed_at: Instant::now(),
             };
             self.completed_results.push(cancel_result);
             return true;
@@ -286,7 +761,10 @@ impl OperationProcessor {
             .iter()
             .position(|result| result.descriptor.operation_id == task_id)
         {
-            Some(self.completed_results.remove(position))
+            self.forget_stored(task_id);
+            let result = self.completed_results.remove(position);
+            self.account_results();
+            Some(result)
         } else {
             None
         }
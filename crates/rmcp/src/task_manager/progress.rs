@@ -0,0 +1,328 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use futures::Future;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::{
+    Peer, RoleServer,
+    model::{ProgressNotificationParam, ProgressToken},
+    service::{RequestHandle, ServiceError, ServiceRole, TaskKind, spawn_task},
+};
+
+/// Progress reported for a whole task, as `progress` out of this total.
+pub const TASK_PROGRESS_TOTAL: f64 = 100.0;
+
+/// Progress of a task and of the nested requests it makes
+///
+/// A task's progress is a tree: the task at the root, and a child for each
+/// sampling request, proxied tool call or other piece of work it tracks. The
+/// progress of a node is the average of its own reported progress and that of
+/// its children, so the root sums up the whole task. When the task was called
+/// with a progress token, every change sends the root's progress to the
+/// caller.
+///
+/// `#[task_handler]` puts one in the request extensions of each task, so tools
+/// can take it as an `Extension<TaskProgress>`.
+#[derive(Clone)]
+pub struct TaskProgress {
+    tree: Arc<ProgressTree>,
+    node: usize,
+}
+
+impl std::fmt::Debug for TaskProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskProgress")
+            .field("node", &self.node)
+            .field("fraction", &self.fraction())
+            .finish()
+    }
+}
+
+struct ProgressTree {
+    state: Mutex<TreeState>,
+    notifier: Option<(
+        ProgressToken,
+        mpsc::UnboundedSender<ProgressNotificationParam>,
+    )>,
+}
+
+#[derive(Default)]
+struct TreeState {
+    nodes: Vec<ProgressNode>,
+    /// Progress tokens handed to nested requests, by node
+    tokens: HashMap<ProgressToken, usize>,
+    last_sent: f64,
+}
+
+struct ProgressNode {
+    name: String,
+    children: Vec<usize>,
+    progress: f64,
+    total: Option<f64>,
+    message: Option<String>,
+    completed: bool,
+}
+
+impl ProgressNode {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            children: Vec::new(),
+            progress: 0.0,
+            total: None,
+            message: None,
+            completed: false,
+        }
+    }
+}
+
+impl TreeState {
+    fn fraction(&self, node: usize) -> f64 {
+        let node = &self.nodes[node];
+        if node.completed {
+            return 1.0;
+        }
+        let own = node.total.map(|total| {
+            if total > 0.0 {
+                (node.progress / total).clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        });
+        let parts = own
+            .into_iter()
+            .chain(node.children.iter().map(|child| self.fraction(*child)))
+            .collect::<Vec<_>>();
+        if parts.is_empty() {
+            0.0
+        } else {
+            parts.iter().sum::<f64>() / parts.len() as f64
+        }
+    }
+
+    fn snapshot(&self, node: usize) -> TaskProgressNode {
+        let ProgressNode {
+            name,
+            children,
+            progress,
+            total,
+            message,
+            completed,
+        } = &self.nodes[node];
+        TaskProgressNode {
+            name: name.clone(),
+            progress: *progress,
+            total: *total,
+            message: message.clone(),
+            fraction: self.fraction(node),
+            completed: *completed,
+            children: children.iter().map(|child| self.snapshot(*child)).collect(),
+        }
+    }
+}
+
+impl TaskProgress {
+    /// Track the progress of a task without reporting it to anyone.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self::with_tree(name.into(), None)
+    }
+
+    /// Track the progress of a task, sending it to `peer` under `progress_token`.
+    ///
+    /// Must be called within a tokio runtime.
+    pub fn notifying(
+        name: impl Into<String>,
+        peer: Peer<RoleServer>,
+        progress_token: ProgressToken,
+    ) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        // a single sender keeps the notifications in order
+        spawn_task(TaskKind::Background, "rmcp::task_progress", async move {
+            while let Some(params) = receiver.recv().await {
+                if let Err(error) = peer.notify_progress(params).await {
+                    tracing::debug!(%error, "failed to send task progress");
+                    break;
+                }
+            }
+        });
+        Self::with_tree(name.into(), Some((progress_token, sender)))
+    }
+
+    fn with_tree(
+        name: String,
+        notifier: Option<(
+            ProgressToken,
+            mpsc::UnboundedSender<ProgressNotificationParam>,
+        )>,
+    ) -> Self {
+        let state = TreeState {
+            nodes: vec![ProgressNode::new(name)],
+            ..Default::default()
+        };
+        Self {
+            tree: Arc::new(ProgressTree {
+                state: Mutex::new(state),
+                notifier,
+            }),
+            node: 0,
+        }
+    }
+
+    fn update(&self, update: impl FnOnce(&mut ProgressNode)) {
+        let mut state = self.tree.state.lock().expect("task progress poisoned");
+        update(&mut state.nodes[self.node]);
+        let Some((progress_token, sender)) = &self.tree.notifier else {
+            return;
+        };
+        let progress = state.fraction(0) * TASK_PROGRESS_TOTAL;
+        // progress must keep increasing, so only send it when it does
+        if progress <= state.last_sent {
+            return;
+        }
+        state.last_sent = progress;
+        let _ = sender.send(ProgressNotificationParam {
+            progress_token: progress_token.clone(),
+            progress,
+            total: Some(TASK_PROGRESS_TOTAL),
+            message: state.nodes[self.node].message.clone(),
+        });
+    }
+
+    /// Start tracking a piece of work under this one.
+    pub fn child(&self, name: impl Into<String>) -> TaskProgress {
+        let mut state = self.tree.state.lock().expect("task progress poisoned");
+        let node = state.nodes.len();
+        state.nodes.push(ProgressNode::new(name.into()));
+        state.nodes[self.node].children.push(node);
+        TaskProgress {
+            tree: self.tree.clone(),
+            node,
+        }
+    }
+
+    /// Run `future` as a child named `name`, completed once it finishes.
+    pub async fn track<F: Future>(&self, name: impl Into<String>, future: F) -> F::Output {
+        let child = self.child(name);
+        let output = future.await;
+        child.complete();
+        output
+    }
+
+    /// Report `progress` out of `total`, if known.
+    pub fn report(&self, progress: f64, total: Option<f64>) {
+        self.update(|node| {
+            node.progress = progress;
+            node.total = total;
+        });
+    }
+
+    /// Describe what is going on, sent along with the next progress.
+    pub fn set_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        self.update(|node| node.message = Some(message));
+    }
+
+    /// Mark this piece of work as done.
+    pub fn complete(&self) {
+        self.update(|node| node.completed = true);
+    }
+
+    /// How far along this piece of work is, from 0 to 1.
+    pub fn fraction(&self) -> f64 {
+        self.tree
+            .state
+            .lock()
+            .expect("task progress poisoned")
+            .fraction(self.node)
+    }
+
+    /// Report progress notifications carrying `progress_token`, passed to
+    /// [`on_progress`](Self::on_progress), on this piece of work.
+    pub fn follow_token(&self, progress_token: ProgressToken) {
+        self.tree
+            .state
+            .lock()
+            .expect("task progress poisoned")
+            .tokens
+            .insert(progress_token, self.node);
+    }
+
+    /// Wait for a nested request as a child named `name`, following its
+    /// progress until it is answered.
+    ///
+    /// The request may go to the client, e.g. sampling, whose progress the
+    /// `on_progress` generated by `#[task_handler]` routes here, or to another
+    /// server, in which case the client handler talking to it should pass its
+    /// progress notifications to [`on_progress`](Self::on_progress).
+    pub async fn track_request<R: ServiceRole>(
+        &self,
+        name: impl Into<String>,
+        handle: RequestHandle<R>,
+    ) -> Result<R::PeerResp, ServiceError> {
+        let child = self.child(name);
+        let progress_token = handle.progress_token.clone();
+        child.follow_token(progress_token.clone());
+        let response = handle.await_response().await;
+        self.tree
+            .state
+            .lock()
+            .expect("task progress poisoned")
+            .tokens
+            .remove(&progress_token);
+        child.complete();
+        response
+    }
+
+    /// Report the progress of a nested request, returning whether its token
+    /// belongs to this tree.
+    pub fn on_progress(&self, params: &ProgressNotificationParam) -> bool {
+        let node = {
+            let state = self.tree.state.lock().expect("task progress poisoned");
+            match state.tokens.get(&params.progress_token) {
+                Some(node) => *node,
+                None => return false,
+            }
+        };
+        let target = TaskProgress {
+            tree: self.tree.clone(),
+            node,
+        };
+        target.update(|node| {
+            node.progress = params.progress;
+            node.total = params.total;
+            if let Some(message) = &params.message {
+                node.message = Some(message.clone());
+            }
+        });
+        true
+    }
+
+    /// The current state of this piece of work and everything under it.
+    pub fn snapshot(&self) -> TaskProgressNode {
+        self.tree
+            .state
+            .lock()
+            .expect("task progress poisoned")
+            .snapshot(self.node)
+    }
+}
+
+/// A node of a task's progress tree, see [`TaskProgress::snapshot`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskProgressNode {
+    pub name: String,
+    pub progress: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// How far along the work is, counting its children, from 0 to 1.
+    pub fraction: f64,
+    pub completed: bool,
+    pub children: Vec<TaskProgressNode>,
+}
@@ -0,0 +1,253 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::model::{ClientRequest, Task};
+
+/// A task as kept by a [`TaskStore`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRecord {
+    pub task: Task,
+    /// Name of the operation, e.g. the tool called.
+    pub name: String,
+    /// The request that started the task, used to resume it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<ClientRequest>,
+    /// Whether another run of the server may pick the task up once its owner
+    /// is gone.
+    #[serde(default)]
+    pub resumable: bool,
+    /// The instance running the task. Cleared once the task is over.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<TaskOwner>,
+    /// The result of the task once it completed, as JSON.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+}
+
+impl TaskRecord {
+    /// Whether `owner` may take the task at `now` (unix milliseconds): it
+    /// isn't over, and it is unowned, already `owner`'s, or its lease ran out.
+    pub fn claimable_by(&self, owner: &TaskOwner, now: u64) -> bool {
+        !self.task.status.is_terminal()
+            && self.owner.as_ref().is_none_or(|current| {
+                current.instance_id == owner.instance_id || current.lease_expires_at <= now
+            })
+    }
+}
+
+/// The server instance running a task, see [`TaskRecord::owner`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskOwner {
+    pub instance_id: String,
+    /// Unix time in milliseconds after which other instances may claim the task.
+    pub lease_expires_at: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TaskStoreError {
+    #[error("invalid task record: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("task store backend error: {0}")]
+    Backend(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Trait for keeping tasks outside of the [`OperationProcessor`](super::OperationProcessor)
+///
+/// A store shared by several server instances, or kept across restarts, lets
+/// them answer for each other's tasks and resume the resumable ones. Calls are
+/// made while the processor is locked, so they must not wait on I/O: buffer
+/// writes and persist them in the background, like [`DurableTaskStore`].
+pub trait TaskStore: Send + Sync {
+    fn save(&self, record: &TaskRecord) -> Result<(), TaskStoreError>;
+
+    fn load(&self, task_id: &str) -> Result<Option<TaskRecord>, TaskStoreError>;
+
+    fn remove(&self, task_id: &str) -> Result<(), TaskStoreError>;
+
+    fn list(&self) -> Result<Vec<TaskRecord>, TaskStoreError>;
+
+    /// Make `owner` the owner of `task_id` if the record is
+    /// [claimable](TaskRecord::claimable_by) at `now`, returning the claimed
+    /// record. Must be atomic across everything sharing the store.
+    fn claim(
+        &self,
+        task_id: &str,
+        owner: &TaskOwner,
+        now: u64,
+    ) -> Result<Option<TaskRecord>, TaskStoreError>;
+}
+
+/// In-memory task store
+///
+/// Only shared by processors in the same process and lost on restart; mostly
+/// useful for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryTaskStore {
+    records: Mutex<HashMap<String, TaskRecord>>,
+}
+
+impl InMemoryTaskStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TaskStore for InMemoryTaskStore {
+    fn save(&self, record: &TaskRecord) -> Result<(), TaskStoreError> {
+        self.records
+            .lock()
+            .expect("task records poisoned")
+            .insert(record.task.task_id.clone(), record.clone());
+        Ok(())
+    }
+
+    fn load(&self, task_id: &str) -> Result<Option<TaskRecord>, TaskStoreError> {
+        Ok(self
+            .records
+            .lock()
+            .expect("task records poisoned")
+            .get(task_id)
+            .cloned())
+    }
+
+    fn remove(&self, task_id: &str) -> Result<(), TaskStoreError> {
+        self.records
+            .lock()
+            .expect("task records poisoned")
+            .remove(task_id);
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<TaskRecord>, TaskStoreError> {
+        Ok(self
+            .records
+            .lock()
+            .expect("task records poisoned")
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    fn claim(
+        &self,
+        task_id: &str,
+        owner: &TaskOwner,
+        now: u64,
+    ) -> Result<Option<TaskRecord>, TaskStoreError> {
+        let mut records = self.records.lock().expect("task records poisoned");
+        Ok(records
+            .get_mut(task_id)
+            .filter(|record| record.claimable_by(owner, now))
+            .map(|record| {
+                record.owner = Some(owner.clone());
+                record.clone()
+            }))
+    }
+}
+
+/// Task store persisted on disk with [sled](https://docs.rs/sled)
+///
+/// Tasks survive restarts, so resumable ones can be picked up again with
+/// [`OperationProcessor::resume_tasks`](super::OperationProcessor::resume_tasks).
+///
+/// Writes go to sled's page cache and reach the disk on its background flush,
+/// every 500 ms unless the database was configured otherwise, so a crash may
+/// lose the last changes. Await [`flush`](Self::flush) where that matters.
+#[cfg(feature = "task-store-sled")]
+#[cfg_attr(docsrs, doc(cfg(feature = "task-store-sled")))]
+#[derive(Debug, Clone)]
+pub struct DurableTaskStore {
+    tree: sled::Tree,
+}
+
+#[cfg(feature = "task-store-sled")]
+impl From<sled::Error> for TaskStoreError {
+    fn from(error: sled::Error) -> Self {
+        Self::Backend(Box::new(error))
+    }
+}
+
+#[cfg(feature = "task-store-sled")]
+#[cfg_attr(docsrs, doc(cfg(feature = "task-store-sled")))]
+impl DurableTaskStore {
+    /// Name of the sled tree holding the tasks.
+    pub const TREE: &str = "rmcp-tasks";
+
+    /// Open, or create, the database at `path`.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, TaskStoreError> {
+        Self::from_db(&sled::open(path)?)
+    }
+
+    /// Keep the tasks in a tree of an already open database.
+    pub fn from_db(db: &sled::Db) -> Result<Self, TaskStoreError> {
+        Ok(Self {
+            tree: db.open_tree(Self::TREE)?,
+        })
+    }
+
+    /// Write everything stored so far to disk.
+    pub async fn flush(&self) -> Result<(), TaskStoreError> {
+        self.tree.flush_async().await?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "task-store-sled")]
+impl TaskStore for DurableTaskStore {
+    fn save(&self, record: &TaskRecord) -> Result<(), TaskStoreError> {
+        self.tree
+            .insert(record.task.task_id.as_bytes(), serde_json::to_vec(record)?)?;
+        Ok(())
+    }
+
+    fn load(&self, task_id: &str) -> Result<Option<TaskRecord>, TaskStoreError> {
+        match self.tree.get(task_id)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn remove(&self, task_id: &str) -> Result<(), TaskStoreError> {
+        self.tree.remove(task_id)?;
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<TaskRecord>, TaskStoreError> {
+        self.tree
+            .iter()
+            .values()
+            .map(|bytes| Ok(serde_json::from_slice(&bytes?)?))
+            .collect()
+    }
+
+    fn claim(
+        &self,
+        task_id: &str,
+        owner: &TaskOwner,
+        now: u64,
+    ) -> Result<Option<TaskRecord>, TaskStoreError> {
+        loop {
+            let Some(current) = self.tree.get(task_id)? else {
+                return Ok(None);
+            };
+            let mut record: TaskRecord = serde_json::from_slice(&current)?;
+            if !record.claimable_by(owner, now) {
+                return Ok(None);
+            }
+            record.owner = Some(owner.clone());
+            let claimed = serde_json::to_vec(&record)?;
+            // someone else changed the record in between, look again
+            if self
+                .tree
+                .compare_and_swap(task_id, Some(current), Some(claimed))?
+                .is_ok()
+            {
+                return Ok(Some(record));
+            }
+        }
+    }
+}
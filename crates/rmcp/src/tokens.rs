@@ -0,0 +1,279 @@
+//! Token counts of content and messages.
+//!
+//! A [`Tokenizer`] counts the tokens of a piece of text: [`Cl100k`] and
+//! [`O200k`] use the tiktoken encodings of the same name, [`ByteEstimate`]
+//! guesses from the length without any tables, and closures taking the text
+//! plug in any other tokenizer. [`CountTokens`] adds up the text of content,
+//! prompts and sampling messages, with fixed costs for what a tokenizer can't
+//! see: images, audio and the framing of each message.
+//!
+//! ```rust
+//! # use rmcp::{model::Content, tokens::{Cl100k, CountTokens}};
+//! let content = Content::text("hello world");
+//! assert_eq!(content.count_tokens(&Cl100k), 2);
+//! ```
+use crate::model::{
+    CallToolResult, Content, CreateMessageRequestParams, CreateMessageResult, GetPromptResult,
+    PromptMessage, PromptMessageContent, RawContent, ReadResourceResult, ResourceContents,
+    SamplingContent, SamplingMessage, SamplingMessageContent,
+};
+
+/// Tokens counted for an image, an audio clip or a binary resource, whose real
+/// cost depends on the model.
+pub const MEDIA_TOKENS: usize = 85;
+/// Tokens counted for the framing of each message: its role and delimiters.
+pub const MESSAGE_TOKENS: usize = 4;
+
+/// Counts the tokens of text.
+///
+/// Closures taking the text implement this trait too.
+pub trait Tokenizer: Send + Sync + 'static {
+    fn count(&self, text: &str) -> usize;
+}
+
+impl<F> Tokenizer for F
+where
+    F: Fn(&str) -> usize + Send + Sync + 'static,
+{
+    fn count(&self, text: &str) -> usize {
+        self(text)
+    }
+}
+
+/// The `cl100k_base` encoding, used by GPT-4 and GPT-3.5 and close enough
+/// for most other models. Its tables are loaded on first use.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cl100k;
+
+impl Tokenizer for Cl100k {
+    fn count(&self, text: &str) -> usize {
+        tiktoken_rs::cl100k_base_singleton()
+            .encode_with_special_tokens(text)
+            .len()
+    }
+}
+
+/// The `o200k_base` encoding, used by GPT-4o. Its tables are loaded on first
+/// use.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct O200k;
+
+impl Tokenizer for O200k {
+    fn count(&self, text: &str) -> usize {
+        tiktoken_rs::o200k_base_singleton()
+            .encode_with_special_tokens(text)
+            .len()
+    }
+}
+
+/// About four bytes per token, the average of English text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByteEstimate;
+
+impl Tokenizer for ByteEstimate {
+    fn count(&self, text: &str) -> usize {
+        text.len().div_ceil(4)
+    }
+}
+
+/// Something whose size in tokens can be estimated.
+pub trait CountTokens {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize;
+}
+
+impl CountTokens for str {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        tokenizer.count(self)
+    }
+}
+
+impl CountTokens for String {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        tokenizer.count(self)
+    }
+}
+
+impl<T: CountTokens> CountTokens for [T] {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        self.iter().map(|item| item.count_tokens(tokenizer)).sum()
+    }
+}
+
+impl<T: CountTokens> CountTokens for Vec<T> {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        self.as_slice().count_tokens(tokenizer)
+    }
+}
+
+impl CountTokens for ResourceContents {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        match self {
+            ResourceContents::TextResourceContents { text, .. } => tokenizer.count(text),
+            ResourceContents::BlobResourceContents { .. } => MEDIA_TOKENS,
+        }
+    }
+}
+
+impl CountTokens for RawContent {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        match self {
+            RawContent::Text(text) => tokenizer.count(&text.text),
+            RawContent::Image(_) | RawContent::Audio(_) => MEDIA_TOKENS,
+            RawContent::Resource(embedded) => embedded.resource.count_tokens(tokenizer),
+            RawContent::ResourceLink(link) => tokenizer.count(&link.uri),
+        }
+    }
+}
+
+impl CountTokens for Content {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        self.raw.count_tokens(tokenizer)
+    }
+}
+
+impl CountTokens for ReadResourceResult {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        self.contents.count_tokens(tokenizer)
+    }
+}
+
+/// The content blocks and the structured content, as JSON.
+impl CountTokens for CallToolResult {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        let structured = self
+            .structured_content
+            .as_ref()
+            .map_or(0, |value| tokenizer.count(&value.to_string()));
+        self.content.count_tokens(tokenizer) + structured
+    }
+}
+
+impl CountTokens for PromptMessageContent {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        match self {
+            PromptMessageContent::Text { text } => tokenizer.count(text),
+            PromptMessageContent::Image { .. } => MEDIA_TOKENS,
+            PromptMessageContent::Resource { resource } => {
+                resource.resource.count_tokens(tokenizer)
+            }
+            PromptMessageContent::ResourceLink { link } => tokenizer.count(&link.uri),
+        }
+    }
+}
+
+impl CountTokens for PromptMessage {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        MESSAGE_TOKENS + self.content.count_tokens(tokenizer)
+    }
+}
+
+impl CountTokens for GetPromptResult {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        self.messages.count_tokens(tokenizer)
+    }
+}
+
+/// Tool calls count as their name and JSON input, tool results as their
+/// content.
+impl CountTokens for SamplingContent {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        match self {
+            SamplingContent::Content(content) => content.count_tokens(tokenizer),
+            SamplingContent::ToolUse(tool_use) => {
+                let input = serde_json::Value::Object(tool_use.input.clone()).to_string();
+                tokenizer.count(&tool_use.name) + tokenizer.count(&input)
+            }
+            SamplingContent::ToolResult(tool_result) => tool_result.content.count_tokens(tokenizer),
+        }
+    }
+}
+
+impl CountTokens for SamplingMessageContent {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        self.parts().count_tokens(tokenizer)
+    }
+}
+
+impl CountTokens for SamplingMessage {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        MESSAGE_TOKENS + self.content.count_tokens(tokenizer)
+    }
+}
+
+/// The prompt sent to the model: the system prompt and the messages. The
+/// tokens the model may answer with, `maxTokens`, aren't included.
+impl CountTokens for CreateMessageRequestParams {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        let system = self
+            .system_prompt
+            .as_ref()
+            .map_or(0, |prompt| MESSAGE_TOKENS + tokenizer.count(prompt));
+        system + self.messages.count_tokens(tokenizer)
+    }
+}
+
+impl CountTokens for CreateMessageResult {
+    fn count_tokens(&self, tokenizer: &dyn Tokenizer) -> usize {
+        self.message.count_tokens(tokenizer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ResourceContents, Role, ToolUseContent};
+
+    #[test]
+    fn tokenizers_count_text() {
+        let text = "The quick brown fox jumps over the lazy dog.";
+        assert_eq!(Cl100k.count(text), 10);
+        assert_eq!(O200k.count(text), 10);
+        assert_eq!(ByteEstimate.count(text), 11);
+        assert_eq!(Cl100k.count(""), 0);
+        let words = |text: &str| text.split_whitespace().count();
+        assert_eq!(text.count_tokens(&words), 9);
+    }
+
+    #[test]
+    fn content_counts() {
+        let words = |text: &str| text.split_whitespace().count();
+        let content = vec![
+            Content::text("one two three"),
+            Content::image("aGk=", "image/png"),
+            Content::resource(ResourceContents::text("four five", "mem://a")),
+        ];
+        assert_eq!(content.count_tokens(&words), 3 + MEDIA_TOKENS + 2);
+
+        let result = CallToolResult::structured(serde_json::json!({ "a": 1 }));
+        // the text block is the JSON again
+        assert_eq!(result.count_tokens(&words), 2);
+    }
+
+    #[test]
+    fn message_counts() {
+        let words = |text: &str| text.split_whitespace().count();
+        let input = serde_json::json!({ "city": "Paris" });
+        let params = CreateMessageRequestParams {
+            meta: None,
+            task: None,
+            messages: vec![
+                SamplingMessage::user_text("what is the weather"),
+                SamplingMessage::new(
+                    Role::Assistant,
+                    ToolUseContent::new("1", "weather", input.as_object().unwrap().clone()),
+                ),
+            ],
+            model_preferences: None,
+            system_prompt: Some("be brief".to_owned()),
+            include_context: None,
+            temperature: None,
+            max_tokens: 100,
+            stop_sequences: None,
+            metadata: None,
+        };
+        assert_eq!(
+            params.count_tokens(&words),
+            (MESSAGE_TOKENS + 2) + (MESSAGE_TOKENS + 4) + (MESSAGE_TOKENS + 1 + 1)
+        );
+    }
+}
@@ -22,6 +22,12 @@
 //!
 //! This could be very helpful when you want to create a transport from a byte stream, such as a file or a tcp connection.
 //!
+//! Messages are newline-delimited JSON by default. [`AsyncRwTransport::with_framing`](`async_rw::AsyncRwTransport::with_framing`)
+//! can use LSP style `Content-Length` headers instead, which a client can ask a stdio server for with the
+//! `MCP_STDIO_FRAMING` environment variable or the `--framing` flag, see [`async_rw::Framing::detect`].
+//! With the `codec-msgpack` or `codec-cbor` feature, messages can also be MessagePack or CBOR instead of JSON,
+//! chosen the same way, see [`async_rw::Encoding`].
+//!
 //! ### [Sink/Stream Transport](`sink_stream::SinkStreamTransport`)
 //! This transport is used to create a transport from a sink and a stream.
 //!
@@ -72,13 +78,18 @@
 
 use std::{borrow::Cow, sync::Arc};
 
-use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
+use crate::service::{
+    CloseReason, ConnectionState, MemoryAccount, RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage,
+};
 
 pub mod sink_stream;
 
 #[cfg(feature = "transport-async-rw")]
 #[cfg_attr(docsrs, doc(cfg(feature = "transport-async-rw")))]
 pub mod async_rw;
+#[cfg(feature = "transport-async-rw")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-async-rw")))]
+pub use async_rw::{Encoding, Framing};
 
 #[cfg(feature = "transport-worker")]
 #[cfg_attr(docsrs, doc(cfg(feature = "transport-worker")))]
@@ -94,12 +105,26 @@ pub mod child_process;
 #[cfg_attr(docsrs, doc(cfg(feature = "transport-child-process")))]
 pub use child_process::{ConfigureCommandExt, TokioChildProcess};
 
+#[cfg(feature = "transport-container")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-container")))]
+pub mod container;
+#[cfg(feature = "transport-ssh")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-ssh")))]
+pub mod ssh;
+
+#[cfg(feature = "transport-cassette")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-cassette")))]
+pub mod cassette;
+#[cfg(feature = "transport-compose")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-compose")))]
+pub mod compose;
+
 #[cfg(feature = "transport-io")]
 #[cfg_attr(docsrs, doc(cfg(feature = "transport-io")))]
 pub mod io;
 #[cfg(feature = "transport-io")]
 #[cfg_attr(docsrs, doc(cfg(feature = "transport-io")))]
-pub use io::stdio;
+pub use io::{ParentWatch, detect_stdio, framed_stdio, stdio};
 
 #[cfg(feature = "auth")]
 #[cfg_attr(docsrs, doc(cfg(feature = "auth")))]
@@ -112,6 +137,22 @@ pub use auth::{
     StoredCredentials,
 };
 
+#[cfg(feature = "transport-grpc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-grpc")))]
+pub mod grpc;
+#[cfg(all(feature = "transport-grpc", feature = "client"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "transport-grpc", feature = "client"))))]
+pub use grpc::GrpcClientTransport;
+#[cfg(all(feature = "transport-grpc", feature = "server"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "transport-grpc", feature = "server"))))]
+pub use grpc::McpGrpcService;
+#[cfg(feature = "transport-bus")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-bus")))]
+pub mod bus;
+#[cfg(feature = "unstable-quic")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable-quic")))]
+pub mod quic;
+
 // #[cfg(feature = "transport-ws")]
 // #[cfg_attr(docsrs, doc(cfg(feature = "transport-ws")))]
 // pub mod ws;
@@ -155,6 +196,36 @@ where
 
     /// Close the transport
     fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Connection state changes the transport notices by itself, like losing
+    /// and regaining the peer, for
+    /// [`RunningService::state_changes`](crate::service::RunningService::state_changes).
+    /// `None` for transports that don't track any.
+    fn state_changes(&self) -> Option<tokio::sync::watch::Receiver<ConnectionState>> {
+        None
+    }
+
+    /// The id of the session this transport carries, like the
+    /// `Mcp-Session-Id` of streamable HTTP, recorded on the spans of the
+    /// handlers. `None` for transports without sessions.
+    fn session_id(&self) -> Option<Arc<str>> {
+        None
+    }
+
+    /// Where the transport records the bytes it buffers for its session,
+    /// e.g. events kept for resumption. The service records its own buffers
+    /// there too, see [`Peer::memory_usage`](crate::service::Peer::memory_usage).
+    /// `None` for transports that don't buffer.
+    fn memory_account(&self) -> Option<MemoryAccount> {
+        None
+    }
+
+    /// Why the transport stopped receiving, asked once
+    /// [`receive`](Self::receive) returned `None`. `None` for a plain end of
+    /// stream.
+    fn close_reason(&self) -> Option<CloseReason> {
+        None
+    }
 }
 
 pub trait IntoTransport<R, E, A>: Send + 'static
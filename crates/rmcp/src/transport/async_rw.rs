@@ -14,7 +14,10 @@ use tokio_util::{
 };
 
 use super::{IntoTransport, Transport};
-use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
+use crate::service::{CloseReason, RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
+
+mod encoding;
+pub use encoding::*;
 
 pub enum TransportAdapterAsyncRW {}
 
@@ -47,6 +50,7 @@ pub type TransportWriter<Role, W> = FramedWrite<W, JsonRpcMessageCodec<TxJsonRpc
 pub struct AsyncRwTransport<Role: ServiceRole, R: AsyncRead, W: AsyncWrite> {
     read: FramedRead<R, JsonRpcMessageCodec<RxJsonRpcMessage<Role>>>,
     write: Arc<Mutex<Option<TransportWriter<Role, W>>>>,
+    close_reason: Option<CloseReason>,
 }
 
 impl<Role: ServiceRole, R, W> AsyncRwTransport<Role, R, W>
@@ -55,15 +59,37 @@ where
     W: Send + AsyncWrite + Unpin + 'static,
 {
     pub fn new(read: R, write: W) -> Self {
+        Self::with_framing(read, write, Framing::default())
+    }
+
+    /// A transport whose messages are delimited by `framing`.
+    pub fn with_framing(read: R, write: W, framing: Framing) -> Self {
+        Self::with_codec(read, write, framing, Encoding::default())
+    }
+
+    /// A transport whose messages are serialized with `encoding`.
+    pub fn with_encoding(read: R, write: W, encoding: Encoding) -> Self {
+        Self::with_codec(read, write, Framing::default(), encoding)
+    }
+
+    pub fn with_codec(read: R, write: W, framing: Framing, encoding: Encoding) -> Self {
         let read = FramedRead::new(
             read,
-            JsonRpcMessageCodec::<RxJsonRpcMessage<Role>>::default(),
+            JsonRpcMessageCodec::<RxJsonRpcMessage<Role>>::default()
+                .with_framing(framing)
+                .with_encoding(encoding),
         );
         let write = Arc::new(Mutex::new(Some(FramedWrite::new(
             write,
-            JsonRpcMessageCodec::<TxJsonRpcMessage<Role>>::default(),
+            JsonRpcMessageCodec::<TxJsonRpcMessage<Role>>::default()
+                .with_framing(framing)
+                .with_encoding(encoding),
         ))));
-        Self { read, write }
+        Self {
+            read,
+            write,
+            close_reason: None,
+        }
     }
 }
 
@@ -116,18 +142,22 @@ where
         }
     }
 
-    fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<Role>>> {
-        let next = self.read.next();
-        async {
-            next.await.and_then(|e| {
-                e.inspect_err(|e| {
-                    tracing::error!("Error reading from stream: {}", e);
-                })
-                .ok()
-            })
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<Role>> {
+        match self.read.next().await? {
+            Ok(message) => Some(message),
+            Err(e) => {
+                tracing::error!("Error reading from stream: {}", e);
+                // the stream ends after an error
+                self.close_reason = Some(e.into());
+                None
+            }
         }
     }
 
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.close_reason.clone()
+    }
+
     async fn close(&mut self) -> Result<(), Self::Error> {
         let mut write = self.write.lock().await;
         drop(write.take());
@@ -135,12 +165,129 @@ where
     }
 }
 
+/// Environment variable a client sets to tell a stdio server which
+/// [`Framing`] to use, see [`Framing::detect`].
+pub const FRAMING_ENV: &str = "MCP_STDIO_FRAMING";
+/// Command line flag doing the same as [`FRAMING_ENV`], as
+/// `--framing content-length` or `--framing=content-length`.
+pub const FRAMING_FLAG: &str = "--framing";
+
+/// How messages are delimited in a byte stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Framing {
+    /// One JSON message per line, as the MCP specification asks for.
+    #[default]
+    NewlineDelimited,
+    /// LSP style: a `Content-Length` header, a blank line, then exactly that
+    /// many bytes of JSON. Not part of the MCP specification, but safe for
+    /// messages that contain raw newlines, e.g. from a pretty-printing or
+    /// misbehaving peer. Both sides must agree on it.
+    ContentLength,
+}
+
+impl Framing {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NewlineDelimited => "ndjson",
+            Self::ContentLength => "content-length",
+        }
+    }
+
+    /// The framing asked for by the [`FRAMING_FLAG`] in `args`, if any.
+    pub fn from_args<I>(args: I) -> Option<Result<Self, UnknownFraming>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        flag_value(args, FRAMING_FLAG).map(|value| value.parse())
+    }
+
+    /// The framing asked for by [`FRAMING_ENV`], if set.
+    pub fn from_env() -> Option<Result<Self, UnknownFraming>> {
+        std::env::var(FRAMING_ENV).ok().map(|value| value.parse())
+    }
+
+    /// The framing this process was started with: the [`FRAMING_FLAG`] on
+    /// the command line, else [`FRAMING_ENV`], else newline-delimited.
+    /// Unknown values are logged and ignored.
+    pub fn detect() -> Self {
+        let framing = Self::from_args(std::env::args().skip(1)).or_else(Self::from_env);
+        match framing {
+            Some(Ok(framing)) => framing,
+            Some(Err(error)) => {
+                tracing::warn!(%error, "falling back to newline-delimited messages");
+                Self::default()
+            }
+            None => Self::default(),
+        }
+    }
+}
+
+impl std::fmt::Display for Framing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Framing {
+    type Err = UnknownFraming;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ndjson" | "newline" | "" => Ok(Self::NewlineDelimited),
+            "content-length" | "lsp" => Ok(Self::ContentLength),
+            _ => Err(UnknownFraming(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("unknown stdio framing {0:?}, expected \"ndjson\" or \"content-length\"")]
+pub struct UnknownFraming(pub String);
+
+/// The value of `flag` in `args`, given as `flag value` or `flag=value`.
+fn flag_value<I>(args: I, flag: &str) -> Option<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let arg = arg.as_ref();
+        if arg == flag {
+            return Some(
+                args.next()
+                    .map_or_else(String::new, |value| value.as_ref().to_owned()),
+            );
+        }
+        if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_owned());
+        }
+    }
+    None
+}
+
+/// Longest header block accepted in [`Framing::ContentLength`] mode.
+const MAX_HEADER_LENGTH: usize = 8 * 1024;
+/// Longest body accepted in [`Framing::ContentLength`] mode when no maximum
+/// length was given, so a bogus header can't claim the whole address space.
+pub const DEFAULT_MAX_CONTENT_LENGTH: usize = 16 * 1024 * 1024;
+
 #[derive(Debug, Clone)]
 pub struct JsonRpcMessageCodec<T> {
     _marker: PhantomData<fn() -> T>,
     next_index: usize,
-    max_length: usize,
+    max_length: Option<usize>,
     is_discarding: bool,
+    framing: Framing,
+    encoding: Encoding,
+    /// Length of the body being read, once its headers were.
+    content_length: Option<usize>,
+    /// Bytes of an oversized body still to skip.
+    discard_length: usize,
 }
 
 impl<T> Default for JsonRpcMessageCodec<T> {
@@ -154,20 +301,131 @@ impl<T> JsonRpcMessageCodec<T> {
         Self {
             _marker: PhantomData,
             next_index: 0,
-            max_length: usize::MAX,
+            max_length: None,
             is_discarding: false,
+            framing: Framing::default(),
+            encoding: Encoding::default(),
+            content_length: None,
+            discard_length: 0,
+        }
+    }
+
+    pub fn with_framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// The framing in use, which is [`Framing::ContentLength`] for binary
+    /// encodings whatever was asked for.
+    pub fn framing(&self) -> Framing {
+        if self.encoding.is_binary() {
+            Framing::ContentLength
+        } else {
+            self.framing
         }
     }
 
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
     pub fn new_with_max_length(max_length: usize) -> Self {
         Self {
-            max_length,
+            max_length: Some(max_length),
             ..Self::new()
         }
     }
 
+    /// The longest message accepted. Unless set, lines are unlimited and
+    /// [`Framing::ContentLength`] bodies are limited to
+    /// [`DEFAULT_MAX_CONTENT_LENGTH`].
     pub fn max_length(&self) -> usize {
-        self.max_length
+        self.max_length.unwrap_or(match self.framing() {
+            Framing::NewlineDelimited => usize::MAX,
+            Framing::ContentLength => DEFAULT_MAX_CONTENT_LENGTH,
+        })
+    }
+
+    /// Parse the headers of a [`Framing::ContentLength`] message, returning
+    /// the length of the body.
+    fn parse_headers(headers: &[u8]) -> Result<usize, JsonRpcMessageCodecError> {
+        let headers = std::str::from_utf8(headers).map_err(|_| {
+            JsonRpcMessageCodecError::InvalidHeader("headers are not UTF-8".to_owned())
+        })?;
+        let mut content_length = None;
+        for header in headers.split("\r\n").filter(|header| !header.is_empty()) {
+            let Some((name, value)) = header.split_once(':') else {
+                return Err(JsonRpcMessageCodecError::InvalidHeader(header.to_owned()));
+            };
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| JsonRpcMessageCodecError::InvalidHeader(header.to_owned()))?;
+                content_length = Some(length);
+            }
+        }
+        content_length.ok_or_else(|| {
+            JsonRpcMessageCodecError::InvalidHeader("missing Content-Length".to_owned())
+        })
+    }
+}
+
+impl<T: DeserializeOwned> JsonRpcMessageCodec<T> {
+    fn decode_content_length(
+        &mut self,
+        buf: &mut BytesMut,
+    ) -> Result<Option<T>, JsonRpcMessageCodecError> {
+        loop {
+            if self.discard_length > 0 {
+                let discarded = self.discard_length.min(buf.len());
+                buf.advance(discarded);
+                self.discard_length -= discarded;
+                if self.discard_length > 0 {
+                    return Ok(None);
+                }
+            }
+            let length = match self.content_length {
+                Some(length) => length,
+                None => {
+                    let Some(end) = buf.windows(4).position(|window| window == b"\r\n\r\n") else {
+                        if buf.len() > MAX_HEADER_LENGTH {
+                            return Err(JsonRpcMessageCodecError::InvalidHeader(
+                                "headers too long".to_owned(),
+                            ));
+                        }
+                        return Ok(None);
+                    };
+                    let length = Self::parse_headers(&buf[..end]);
+                    buf.advance(end + 4);
+                    let length = length?;
+                    if length > self.max_length() {
+                        self.discard_length = length;
+                        return Err(JsonRpcMessageCodecError::MaxLineLengthExceeded);
+                    }
+                    self.content_length = Some(length);
+                    length
+                }
+            };
+            // the buffer grows as bytes arrive rather than by the announced
+            // length, which only the peer vouches for
+            if buf.len() < length {
+                return Ok(None);
+            }
+            self.content_length = None;
+            let body = buf.split_to(length);
+            if self.encoding.is_binary() {
+                return self.encoding.deserialize(&body).map(Some);
+            }
+            if let Some(item) = try_parse_with_compatibility(&body, "decode")? {
+                return Ok(Some(item));
+            }
+        }
     }
 }
 
@@ -268,7 +526,10 @@ fn try_parse_with_compatibility<T: serde::de::DeserializeOwned>(
                     line_str,
                     e
                 );
-                Err(JsonRpcMessageCodecError::Serde(e))
+                Err(JsonRpcMessageCodecError::InvalidMessage {
+                    reason: CloseReason::protocol_error(e.to_string(), line_str),
+                    source: e,
+                })
             }
         }
     } else {
@@ -282,24 +543,56 @@ fn try_parse_with_compatibility<T: serde::de::DeserializeOwned>(
 pub enum JsonRpcMessageCodecError {
     #[error("max line length exceeded")]
     MaxLineLengthExceeded,
+    /// Headers of a [`Framing::ContentLength`] message that don't make sense.
+    #[error("invalid header {0}")]
+    InvalidHeader(String),
     #[error("serde error {0}")]
     Serde(#[from] serde_json::Error),
+    /// A line that doesn't parse, kept as the [`CloseReason`] of the
+    /// transport.
+    #[error("invalid message {source}")]
+    InvalidMessage {
+        source: serde_json::Error,
+        reason: CloseReason,
+    },
     #[error("io error {0}")]
     Io(#[from] std::io::Error),
+    /// A message that a binary [`Encoding`] can't serialize or deserialize.
+    #[error("{encoding} error {message}")]
+    Encoding { encoding: Encoding, message: String },
 }
 
 impl From<JsonRpcMessageCodecError> for std::io::Error {
     fn from(value: JsonRpcMessageCodecError) -> Self {
         match value {
-            JsonRpcMessageCodecError::MaxLineLengthExceeded => {
+            JsonRpcMessageCodecError::MaxLineLengthExceeded
+            | JsonRpcMessageCodecError::InvalidHeader(_)
+            | JsonRpcMessageCodecError::Encoding { .. } => {
                 std::io::Error::new(std::io::ErrorKind::InvalidData, value)
             }
-            JsonRpcMessageCodecError::Serde(e) => e.into(),
+            JsonRpcMessageCodecError::Serde(e)
+            | JsonRpcMessageCodecError::InvalidMessage { source: e, .. } => e.into(),
             JsonRpcMessageCodecError::Io(e) => e,
         }
     }
 }
 
+impl From<JsonRpcMessageCodecError> for CloseReason {
+    fn from(value: JsonRpcMessageCodecError) -> Self {
+        match value {
+            JsonRpcMessageCodecError::InvalidMessage { reason, .. } => reason,
+            JsonRpcMessageCodecError::MaxLineLengthExceeded
+            | JsonRpcMessageCodecError::InvalidHeader(_)
+            | JsonRpcMessageCodecError::Encoding { .. }
+            | JsonRpcMessageCodecError::Serde(_) => CloseReason::ProtocolError {
+                message: value.to_string(),
+                excerpt: String::new(),
+            },
+            JsonRpcMessageCodecError::Io(e) => CloseReason::Failed(e.to_string()),
+        }
+    }
+}
+
 impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
     type Item = T;
 
@@ -309,10 +602,13 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
         &mut self,
         buf: &mut BytesMut,
     ) -> Result<Option<Self::Item>, JsonRpcMessageCodecError> {
+        if self.framing() == Framing::ContentLength {
+            return self.decode_content_length(buf);
+        }
         loop {
             // Determine how far into the buffer we'll search for a newline. If
             // there's no max_length set, we'll read to the end of the buffer.
-            let read_to = std::cmp::min(self.max_length.saturating_add(1), buf.len());
+            let read_to = std::cmp::min(self.max_length().saturating_add(1), buf.len());
 
             let newline_offset = buf[self.next_index..read_to]
                 .iter()
@@ -352,7 +648,7 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
                     };
                     return Ok(Some(item));
                 }
-                (false, None) if buf.len() > self.max_length => {
+                (false, None) if buf.len() > self.max_length() => {
                     // Reached the maximum length without finding a
                     // newline, return an error and start discarding on the
                     // next call.
@@ -370,6 +666,17 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
     }
 
     fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<T>, JsonRpcMessageCodecError> {
+        if self.framing() == Framing::ContentLength {
+            let item = self.decode_content_length(buf)?;
+            if item.is_none() && (self.content_length.is_some() || !buf.is_empty()) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "stream ended in the middle of a message",
+                )
+                .into());
+            }
+            return Ok(item);
+        }
         Ok(match self.decode(buf)? {
             Some(frame) => Some(frame),
             None => {
@@ -397,6 +704,12 @@ impl<T: Serialize> Encoder<T> for JsonRpcMessageCodec<T> {
     type Error = JsonRpcMessageCodecError;
 
     fn encode(&mut self, item: T, buf: &mut BytesMut) -> Result<(), JsonRpcMessageCodecError> {
+        if self.framing() == Framing::ContentLength {
+            let body = self.encoding.serialize(&item)?;
+            buf.put_slice(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes());
+            buf.put_slice(&body);
+            return Ok(());
+        }
         serde_json::to_writer(buf.writer(), &item)?;
         buf.put_u8(b'\n');
         Ok(())
@@ -498,6 +811,117 @@ mod test {
         assert!(lines.next().is_none());
     }
 
+    #[tokio::test]
+    async fn test_content_length_framing() {
+        let pretty = serde_json::to_string_pretty(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "ping",
+            "id": 1,
+        }))
+        .unwrap();
+        let compact = r#"{"jsonrpc":"2.0","method":"ping","id":2}"#;
+        // extra headers are ignored and the name is case-insensitive
+        let data = format!(
+            "Content-Length: {}\r\nContent-Type: application/json\r\n\r\n{pretty}\
+             content-length:{}\r\n\r\n{compact}",
+            pretty.len(),
+            compact.len(),
+        );
+        let mut codec =
+            JsonRpcMessageCodec::<serde_json::Value>::new().with_framing(Framing::ContentLength);
+        let mut buf = BytesMut::new();
+        let mut items = Vec::new();
+        // one byte at a time, the way a slow pipe might deliver it
+        for byte in data.bytes() {
+            buf.put_u8(byte);
+            items.extend(codec.decode(&mut buf).unwrap());
+        }
+        assert_eq!(codec.decode_eof(&mut buf).unwrap(), None);
+        let ids: Vec<_> = items.iter().map(|item| item["id"].clone()).collect();
+        assert_eq!(ids, [1, 2]);
+
+        let mut encoded = BytesMut::new();
+        codec.encode(items[0].clone(), &mut encoded).unwrap();
+        let body = serde_json::to_string(&items[0]).unwrap();
+        assert_eq!(
+            encoded,
+            format!("Content-Length: {}\r\n\r\n{body}", body.len()).as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_content_length_errors() {
+        let mut codec = JsonRpcMessageCodec::<serde_json::Value>::new_with_max_length(8)
+            .with_framing(Framing::ContentLength);
+        let mut buf =
+            BytesMut::from(&b"Content-Length: 10\r\n\r\n0123456789Content-Length: 2\r\n\r\n{}"[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(JsonRpcMessageCodecError::MaxLineLengthExceeded)
+        ));
+        // the oversized body is skipped
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(serde_json::json!({})));
+
+        let mut buf = BytesMut::from(&b"Content-Type: application/json\r\n\r\n{}"[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(JsonRpcMessageCodecError::InvalidHeader(_))
+        ));
+
+        let mut buf = BytesMut::from(&b"Content-Length: 5\r\n\r\n{}"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert!(codec.decode_eof(&mut buf).is_err());
+
+        // without a maximum, a huge length is refused before anything is
+        // allocated for it
+        let mut codec =
+            JsonRpcMessageCodec::<serde_json::Value>::new().with_framing(Framing::ContentLength);
+        assert_eq!(codec.max_length(), DEFAULT_MAX_CONTENT_LENGTH);
+        let mut buf =
+            BytesMut::from(format!("Content-Length: {}\r\n\r\n{{}}", usize::MAX).as_bytes());
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(JsonRpcMessageCodecError::MaxLineLengthExceeded)
+        ));
+        let mut buf = BytesMut::from(&b"Content-Length: 1000000\r\n\r\n{}"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert!(buf.capacity() < 1000000);
+    }
+
+    #[test]
+    fn test_framing_from_args() {
+        let detect = |args: &[&str]| Framing::from_args(args).map(Result::unwrap);
+        assert_eq!(detect(&["serve"]), None);
+        assert_eq!(
+            detect(&["serve", "--framing", "content-length"]),
+            Some(Framing::ContentLength)
+        );
+        assert_eq!(
+            detect(&["--framing=ndjson"]),
+            Some(Framing::NewlineDelimited)
+        );
+        assert!(Framing::from_args(["--framing", "xml"]).unwrap().is_err());
+        assert!(Framing::from_args(["--framing"]).unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_encoding_from_args() {
+        assert_eq!(Encoding::from_args(["serve"]).map(Result::unwrap), None);
+        assert_eq!(
+            Encoding::from_args(["--encoding=JSON"]).map(Result::unwrap),
+            Some(Encoding::Json)
+        );
+        #[cfg(feature = "codec-msgpack")]
+        assert_eq!(
+            Encoding::from_args(["--encoding", "msgpack"]).map(Result::unwrap),
+            Some(Encoding::MessagePack)
+        );
+        let error = Encoding::from_args(["--encoding", "xml"])
+            .unwrap()
+            .unwrap_err();
+        assert!(error.to_string().contains("json"));
+    }
+
     #[test]
     fn test_standard_notification_check() {
         // Test that all standard notifications are recognized
@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use super::{JsonRpcMessageCodecError, flag_value};
+use crate::model::ExperimentalCapability;
+
+/// Environment variable a client sets to tell a stdio server which
+/// [`Encoding`] to use, see [`Encoding::detect`].
+pub const ENCODING_ENV: &str = "MCP_STDIO_ENCODING";
+/// Command line flag doing the same as [`ENCODING_ENV`], as
+/// `--encoding msgpack` or `--encoding=msgpack`.
+pub const ENCODING_FLAG: &str = "--encoding";
+
+/// How messages are serialized in a byte stream.
+///
+/// Binary encodings are not part of the MCP specification: both peers must
+/// be set up for the same one, out of band or after finding each other's
+/// [`SupportedEncodings`]. They are always sent with
+/// [`Framing::ContentLength`](super::Framing::ContentLength), whatever
+/// framing is asked for, since their bytes may contain newlines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    #[default]
+    Json,
+    #[cfg(feature = "codec-msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "codec-msgpack")))]
+    MessagePack,
+    #[cfg(feature = "codec-cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "codec-cbor")))]
+    Cbor,
+}
+
+impl Encoding {
+    /// The encodings this build supports, JSON first.
+    pub const ALL: &[Encoding] = &[
+        Self::Json,
+        #[cfg(feature = "codec-msgpack")]
+        Self::MessagePack,
+        #[cfg(feature = "codec-cbor")]
+        Self::Cbor,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            #[cfg(feature = "codec-msgpack")]
+            Self::MessagePack => "msgpack",
+            #[cfg(feature = "codec-cbor")]
+            Self::Cbor => "cbor",
+        }
+    }
+
+    pub fn is_binary(self) -> bool {
+        self != Self::Json
+    }
+
+    /// The encoding asked for by the [`ENCODING_FLAG`] in `args`, if any.
+    pub fn from_args<I>(args: I) -> Option<Result<Self, UnknownEncoding>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        flag_value(args, ENCODING_FLAG).map(|value| value.parse())
+    }
+
+    /// The encoding asked for by [`ENCODING_ENV`], if set.
+    pub fn from_env() -> Option<Result<Self, UnknownEncoding>> {
+        std::env::var(ENCODING_ENV).ok().map(|value| value.parse())
+    }
+
+    /// The encoding this process was started with: the [`ENCODING_FLAG`] on
+    /// the command line, else [`ENCODING_ENV`], else JSON. Unknown values
+    /// are logged and ignored.
+    pub fn detect() -> Self {
+        let encoding = Self::from_args(std::env::args().skip(1)).or_else(Self::from_env);
+        match encoding {
+            Some(Ok(encoding)) => encoding,
+            Some(Err(error)) => {
+                tracing::warn!(%error, "falling back to JSON messages");
+                Self::default()
+            }
+            None => Self::default(),
+        }
+    }
+
+    pub(super) fn serialize<T: Serialize>(
+        self,
+        item: &T,
+    ) -> Result<Vec<u8>, JsonRpcMessageCodecError> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec(item)?),
+            #[cfg(feature = "codec-msgpack")]
+            // maps rather than arrays for structs, as flattened and untagged
+            // fields need their names to deserialize
+            Self::MessagePack => {
+                rmp_serde::to_vec_named(item).map_err(|e| self.error(e.to_string()))
+            }
+            #[cfg(feature = "codec-cbor")]
+            Self::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(item, &mut bytes).map_err(|e| self.error(e.to_string()))?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    pub(super) fn deserialize<T: DeserializeOwned>(
+        self,
+        bytes: &[u8],
+    ) -> Result<T, JsonRpcMessageCodecError> {
+        match self {
+            Self::Json => Ok(serde_json::from_slice(bytes)?),
+            #[cfg(feature = "codec-msgpack")]
+            Self::MessagePack => {
+                rmp_serde::from_slice(bytes).map_err(|e| self.error(e.to_string()))
+            }
+            #[cfg(feature = "codec-cbor")]
+            Self::Cbor => ciborium::from_reader(bytes).map_err(|e| self.error(e.to_string())),
+        }
+    }
+
+    #[cfg(any(feature = "codec-msgpack", feature = "codec-cbor"))]
+    fn error(self, message: String) -> JsonRpcMessageCodecError {
+        JsonRpcMessageCodecError::Encoding {
+            encoding: self,
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Encoding {
+    type Err = UnknownEncoding;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" | "" => Ok(Self::Json),
+            #[cfg(feature = "codec-msgpack")]
+            "msgpack" | "messagepack" => Ok(Self::MessagePack),
+            #[cfg(feature = "codec-cbor")]
+            "cbor" => Ok(Self::Cbor),
+            _ => Err(UnknownEncoding(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+#[error(
+    "unknown message encoding {0:?}, this build supports {supported}",
+    supported = SupportedEncodings::local().encodings.join(", ")
+)]
+pub struct UnknownEncoding(pub String);
+
+/// Experimental capability listing the [`Encoding`]s a peer can be started
+/// with, the one it prefers first.
+///
+/// A client that sees a binary encoding in the server's capabilities can
+/// start it again with [`ENCODING_ENV`] set; the encoding of a running
+/// session never changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedEncodings {
+    pub encodings: Vec<String>,
+}
+
+impl ExperimentalCapability for SupportedEncodings {
+    const KEY: &'static str = "x-rmcp/encodings";
+}
+
+impl SupportedEncodings {
+    /// The encodings this build supports, binary ones first.
+    pub fn local() -> Self {
+        Self {
+            encodings: Encoding::ALL
+                .iter()
+                .rev()
+                .map(|encoding| encoding.as_str().to_owned())
+                .collect(),
+        }
+    }
+
+    /// The first encoding in this list that this build supports too.
+    pub fn preferred(&self) -> Option<Encoding> {
+        self.encodings
+            .iter()
+            .find_map(|encoding| encoding.parse().ok())
+    }
+}
@@ -0,0 +1,374 @@
+//! MCP sessions over a message bus, for deployments where clients and
+//! servers can't reach each other directly but share a broker: NATS
+//! subjects with the `transport-nats` feature, MQTT topics with
+//! `transport-mqtt`, or any other [`MessageBus`].
+//!
+//! A server serves the sessions of every client under a topic prefix. Each
+//! session has its own id, chosen by the client, and for each side of it
+//! three topics:
+//!
+//! ```text
+//! {prefix}.{session}.server.request       requests to the server
+//! {prefix}.{session}.server.notification  notifications to the server
+//! {prefix}.{session}.server.response      responses to the server's requests
+//! {prefix}.{session}.client.…             the same, to the client
+//! ```
+//!
+//! with the bus' own separator between levels. Requests carry the topic to
+//! answer on where the bus supports it, like a NATS reply subject; else the
+//! response goes to the `response` topic of the other side. An empty message
+//! on a `notification` topic ends the session.
+//!
+//! ```rust,ignore
+//! let nats = async_nats::connect("nats://localhost:4222").await?;
+//! tokio::spawn({
+//!     let nats = nats.clone();
+//!     async move { BusServer::new(|| Ok(Counter::new())).serve(nats, "mcp.counter").await }
+//! });
+//!
+//! let transport = BusTransport::connect(nats, "mcp.counter").await?;
+//! let client = ().serve(transport).await?;
+//! ```
+#[cfg(feature = "transport-mqtt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-mqtt")))]
+mod mqtt;
+#[cfg(feature = "transport-nats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-nats")))]
+mod nats;
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use futures::{StreamExt, stream::BoxStream};
+#[cfg(feature = "transport-mqtt")]
+pub use mqtt::*;
+#[cfg(feature = "transport-nats")]
+pub use nats::*;
+use thiserror::Error;
+
+use super::Transport;
+#[cfg(feature = "client")]
+use crate::service::RoleClient;
+#[cfg(feature = "server")]
+use crate::service::{RoleServer, Service, serve_server_with_ct};
+use crate::{
+    model::{JsonRpcError, JsonRpcMessage, JsonRpcResponse, RequestId},
+    service::{CloseReason, RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage},
+};
+
+#[cfg(feature = "server")]
+const CHANNEL_SIZE: usize = 16;
+
+/// A message published on, or received from, a [`MessageBus`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BusMessage {
+    pub topic: String,
+    /// Where to answer this message, for buses supporting it.
+    pub reply_to: Option<String>,
+    pub payload: Vec<u8>,
+}
+
+/// A publish/subscribe broker carrying [`BusTransport`] sessions.
+///
+/// Messages published by one connection to the broker must reach each
+/// subscription in order, even across topics.
+pub trait MessageBus: Clone + Send + Sync + 'static {
+    type Error: std::error::Error + Send + Sync + 'static;
+    /// Separator of topic levels, `.` for NATS and `/` for MQTT.
+    const SEPARATOR: char;
+    /// Filter matching any one topic level, `*` for NATS and `+` for MQTT.
+    const WILDCARD: &'static str;
+
+    /// Publish `message`. Buses without reply topics ignore its `reply_to`.
+    fn publish(&self, message: BusMessage) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Messages published on topics matching `filter` from now on, until the
+    /// stream is dropped.
+    fn subscribe(
+        &self,
+        filter: String,
+    ) -> impl Future<Output = Result<BoxStream<'static, BusMessage>, Self::Error>> + Send;
+}
+
+#[derive(Debug, Error)]
+pub enum BusTransportError<E: std::error::Error + 'static> {
+    #[error("bus error {0}")]
+    Bus(#[source] E),
+    #[error("serde error {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+const SERVER: &str = "server";
+const CLIENT: &str = "client";
+const REQUEST: &str = "request";
+const NOTIFICATION: &str = "notification";
+const RESPONSE: &str = "response";
+
+fn topic<B: MessageBus>(levels: &[&str]) -> String {
+    levels.join(B::SEPARATOR.encode_utf8(&mut [0; 4]))
+}
+
+/// One side of a session over a [`MessageBus`].
+pub struct BusTransport<R, B: MessageBus> {
+    bus: B,
+    prefix: String,
+    session: Arc<str>,
+    inbound: BoxStream<'static, BusMessage>,
+    /// Reply topics of the requests received.
+    reply_to: HashMap<RequestId, String>,
+    ended: bool,
+    close_reason: Option<CloseReason>,
+    _role: PhantomData<fn() -> R>,
+}
+
+impl<R, B: MessageBus> std::fmt::Debug for BusTransport<R, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BusTransport")
+            .field("prefix", &self.prefix)
+            .field("session", &self.session)
+            .field("ended", &self.ended)
+            .field("close_reason", &self.close_reason)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: ServiceRole, B: MessageBus> BusTransport<R, B> {
+    fn new(
+        bus: B,
+        prefix: String,
+        session: Arc<str>,
+        inbound: BoxStream<'static, BusMessage>,
+    ) -> Self {
+        Self {
+            bus,
+            prefix,
+            session,
+            inbound,
+            reply_to: HashMap::new(),
+            ended: false,
+            close_reason: None,
+            _role: PhantomData,
+        }
+    }
+
+    /// The topic of `kind` messages to the other side.
+    fn peer_topic(&self, kind: &str) -> String {
+        let peer = if R::IS_CLIENT { SERVER } else { CLIENT };
+        topic::<B>(&[&self.prefix, &self.session, peer, kind])
+    }
+
+    /// The topic of `kind` messages to this side.
+    fn own_topic(&self, kind: &str) -> String {
+        let side = if R::IS_CLIENT { CLIENT } else { SERVER };
+        topic::<B>(&[&self.prefix, &self.session, side, kind])
+    }
+}
+
+#[cfg(feature = "client")]
+#[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+impl<B: MessageBus> BusTransport<RoleClient, B> {
+    /// Start a new session with the [`BusServer`] serving `prefix`.
+    pub async fn connect(
+        bus: B,
+        prefix: impl Into<String>,
+    ) -> Result<Self, BusTransportError<B::Error>> {
+        let prefix = prefix.into();
+        let session: Arc<str> = uuid::Uuid::new_v4().to_string().into();
+        let filter = topic::<B>(&[&prefix, &session, CLIENT, B::WILDCARD]);
+        let inbound = bus
+            .subscribe(filter)
+            .await
+            .map_err(BusTransportError::Bus)?;
+        Ok(Self::new(bus, prefix, session, inbound))
+    }
+}
+
+impl<R: ServiceRole, B: MessageBus> Transport<R> for BusTransport<R, B> {
+    type Error = BusTransportError<B::Error>;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let (topic, reply_to) = match &item {
+            JsonRpcMessage::Request(_) => {
+                (self.peer_topic(REQUEST), Some(self.own_topic(RESPONSE)))
+            }
+            JsonRpcMessage::Notification(_) => (self.peer_topic(NOTIFICATION), None),
+            JsonRpcMessage::Response(JsonRpcResponse { id, .. })
+            | JsonRpcMessage::Error(JsonRpcError { id, .. }) => {
+                let topic = self.reply_to.remove(id);
+                (topic.unwrap_or_else(|| self.peer_topic(RESPONSE)), None)
+            }
+        };
+        let bus = self.bus.clone();
+        async move {
+            let message = BusMessage {
+                topic,
+                reply_to,
+                payload: serde_json::to_vec(&item)?,
+            };
+            bus.publish(message).await.map_err(BusTransportError::Bus)
+        }
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        let message = self.inbound.next().await?;
+        if message.payload.is_empty() {
+            self.ended = true;
+            return None;
+        }
+        match serde_json::from_slice::<RxJsonRpcMessage<R>>(&message.payload) {
+            Ok(JsonRpcMessage::Request(request)) => {
+                if let Some(reply_to) = message.reply_to {
+                    self.reply_to.insert(request.id.clone(), reply_to);
+                }
+                Some(JsonRpcMessage::Request(request))
+            }
+            Ok(message) => Some(message),
+            Err(e) => {
+                tracing::error!("Error parsing message on {}: {e}", message.topic);
+                self.close_reason = Some(CloseReason::protocol_error(
+                    e.to_string(),
+                    &String::from_utf8_lossy(&message.payload),
+                ));
+                None
+            }
+        }
+    }
+
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.close_reason.clone()
+    }
+
+    fn session_id(&self) -> Option<Arc<str>> {
+        Some(self.session.clone())
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        if std::mem::replace(&mut self.ended, true) {
+            return Ok(());
+        }
+        let message = BusMessage {
+            topic: self.peer_topic(NOTIFICATION),
+            ..Default::default()
+        };
+        self.bus
+            .publish(message)
+            .await
+            .map_err(BusTransportError::Bus)
+    }
+}
+
+#[cfg(feature = "server")]
+type ServiceFactory<S> = Arc<dyn Fn() -> Result<S, std::io::Error> + Send + Sync>;
+
+/// Serves a session of a new service from `service_factory` for every
+/// client starting one under a topic prefix.
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub struct BusServer<S> {
+    service_factory: ServiceFactory<S>,
+    ct: tokio_util::sync::CancellationToken,
+}
+
+#[cfg(feature = "server")]
+impl<S> std::fmt::Debug for BusServer<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BusServer")
+            .field("ct", &self.ct)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S: Service<RoleServer> + Send + 'static> BusServer<S> {
+    pub fn new(
+        service_factory: impl Fn() -> Result<S, std::io::Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            service_factory: Arc::new(service_factory),
+            ct: tokio_util::sync::CancellationToken::new(),
+        }
+    }
+
+    /// Stop serving and end all sessions when `ct` is cancelled.
+    pub fn with_cancellation_token(mut self, ct: tokio_util::sync::CancellationToken) -> Self {
+        self.ct = ct;
+        self
+    }
+
+    /// Serve the sessions under `prefix` until the bus subscription ends or
+    /// the cancellation token is cancelled.
+    pub async fn serve<B: MessageBus>(
+        &self,
+        bus: B,
+        prefix: impl Into<String>,
+    ) -> Result<(), B::Error> {
+        let prefix = prefix.into();
+        let filter = topic::<B>(&[&prefix, B::WILDCARD, SERVER, B::WILDCARD]);
+        let mut inbound = bus.subscribe(filter).await?;
+        let mut sessions = HashMap::<Arc<str>, tokio::sync::mpsc::Sender<BusMessage>>::new();
+        loop {
+            let message = tokio::select! {
+                message = inbound.next() => message,
+                _ = self.ct.cancelled() => None,
+            };
+            let Some(message) = message else {
+                return Ok(());
+            };
+            let Some(session) = message
+                .topic
+                .strip_prefix(&prefix)
+                .and_then(|topic| topic.strip_prefix(B::SEPARATOR))
+                .and_then(|topic| topic.split(B::SEPARATOR).next())
+            else {
+                continue;
+            };
+            let sender = match sessions.get(session) {
+                Some(sender) => sender.clone(),
+                // a message to a session that already ended
+                None if message.payload.is_empty() => continue,
+                None => {
+                    sessions.retain(|_, sender| !sender.is_closed());
+                    let session: Arc<str> = session.into();
+                    let sender = self.spawn_session(bus.clone(), &prefix, session.clone());
+                    sessions.insert(session, sender.clone());
+                    sender
+                }
+            };
+            if sender.send(message).await.is_err() {
+                tracing::debug!("Dropped a message to an ended session");
+            }
+        }
+    }
+
+    fn spawn_session<B: MessageBus>(
+        &self,
+        bus: B,
+        prefix: &str,
+        session: Arc<str>,
+    ) -> tokio::sync::mpsc::Sender<BusMessage> {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(CHANNEL_SIZE);
+        let inbound = futures::stream::poll_fn(move |cx| receiver.poll_recv(cx)).boxed();
+        let transport =
+            BusTransport::<RoleServer, B>::new(bus, prefix.to_owned(), session, inbound);
+        let service = (self.service_factory)();
+        let ct = self.ct.child_token();
+        tokio::spawn(async move {
+            let service = match service {
+                Ok(service) => service,
+                Err(e) => {
+                    tracing::error!("Failed to create service: {e}");
+                    return;
+                }
+            };
+            match serve_server_with_ct(service, transport, ct).await {
+                Ok(service) => {
+                    let _ = service.waiting().await;
+                }
+                Err(e) => tracing::error!("Failed to create service: {e}"),
+            }
+        });
+        sender
+    }
+}
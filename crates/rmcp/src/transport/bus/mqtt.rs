@@ -0,0 +1,162 @@
+use std::{
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+use futures::{StreamExt, stream::BoxStream};
+use rumqttc::{AsyncClient, ClientError, Event, EventLoop, Packet, QoS};
+use tokio::sync::mpsc;
+
+use super::{BusMessage, MessageBus};
+
+/// How long to wait before polling an MQTT connection again after an error.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+struct Subscription {
+    id: u64,
+    filter: String,
+    sender: mpsc::UnboundedSender<BusMessage>,
+}
+
+#[derive(Default)]
+struct Subscriptions {
+    next_id: u64,
+    list: Vec<Subscription>,
+}
+
+/// MQTT topics through a [`rumqttc`] client. MQTT 3.1.1 has no reply
+/// topics: responses always go to the other side's `response` topic.
+#[derive(Clone)]
+pub struct MqttBus {
+    client: AsyncClient,
+    qos: QoS,
+    subscriptions: Arc<Mutex<Subscriptions>>,
+}
+
+impl std::fmt::Debug for MqttBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MqttBus")
+            .field("qos", &self.qos)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MqttBus {
+    /// Drive `event_loop` in the background, until all clones of the bus
+    /// are dropped, handing the messages it receives to the subscriptions.
+    pub fn new(client: AsyncClient, event_loop: EventLoop) -> Self {
+        let subscriptions = Arc::new(Mutex::new(Subscriptions::default()));
+        tokio::spawn(poll(event_loop, Arc::downgrade(&subscriptions)));
+        Self {
+            client,
+            qos: QoS::AtLeastOnce,
+            subscriptions,
+        }
+    }
+
+    /// The quality of service of publications and subscriptions, at least
+    /// once by default.
+    pub fn with_qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    pub fn client(&self) -> &AsyncClient {
+        &self.client
+    }
+}
+
+async fn poll(mut event_loop: EventLoop, subscriptions: Weak<Mutex<Subscriptions>>) {
+    loop {
+        let event = event_loop.poll().await;
+        let Some(subscriptions) = subscriptions.upgrade() else {
+            return;
+        };
+        match event {
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                let message = BusMessage {
+                    topic: publish.topic,
+                    reply_to: None,
+                    payload: publish.payload.into(),
+                };
+                let subscriptions = subscriptions.lock().expect("subscriptions poisoned");
+                for subscription in &subscriptions.list {
+                    if rumqttc::matches(&message.topic, &subscription.filter) {
+                        let _ = subscription.sender.send(message.clone());
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("MQTT connection error: {e}");
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Removes its subscription when dropped, and unsubscribes from the
+/// filter if no other subscription uses it.
+struct Unsubscribe {
+    id: u64,
+    bus: MqttBus,
+}
+
+impl Drop for Unsubscribe {
+    fn drop(&mut self) {
+        let mut subscriptions = self
+            .bus
+            .subscriptions
+            .lock()
+            .expect("subscriptions poisoned");
+        let Some(index) = subscriptions.list.iter().position(|s| s.id == self.id) else {
+            return;
+        };
+        let filter = subscriptions.list.remove(index).filter;
+        if subscriptions.list.iter().all(|s| s.filter != filter) {
+            if let Err(e) = self.bus.client.try_unsubscribe(filter) {
+                tracing::debug!("Failed to unsubscribe: {e}");
+            }
+        }
+    }
+}
+
+impl MessageBus for MqttBus {
+    type Error = ClientError;
+    const SEPARATOR: char = '/';
+    const WILDCARD: &'static str = "+";
+
+    async fn publish(&self, message: BusMessage) -> Result<(), Self::Error> {
+        self.client
+            .publish(message.topic, self.qos, false, message.payload)
+            .await
+    }
+
+    async fn subscribe(
+        &self,
+        filter: String,
+    ) -> Result<BoxStream<'static, BusMessage>, Self::Error> {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let id = {
+            let mut subscriptions = self.subscriptions.lock().expect("subscriptions poisoned");
+            let id = subscriptions.next_id;
+            subscriptions.next_id += 1;
+            subscriptions.list.push(Subscription {
+                id,
+                filter: filter.clone(),
+                sender,
+            });
+            id
+        };
+        let unsubscribe = Unsubscribe {
+            id,
+            bus: self.clone(),
+        };
+        self.client.subscribe(filter, self.qos).await?;
+        Ok(futures::stream::poll_fn(move |cx| {
+            let _ = &unsubscribe;
+            receiver.poll_recv(cx)
+        })
+        .boxed())
+    }
+}
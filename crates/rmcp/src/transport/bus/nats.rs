@@ -0,0 +1,46 @@
+use futures::{StreamExt, stream::BoxStream};
+use thiserror::Error;
+
+use super::{BusMessage, MessageBus};
+
+#[derive(Debug, Error)]
+pub enum NatsBusError {
+    #[error("publish error {0}")]
+    Publish(#[from] async_nats::PublishError),
+    #[error("subscribe error {0}")]
+    Subscribe(#[from] async_nats::SubscribeError),
+}
+
+/// NATS subjects, with requests answered on their reply subject.
+impl MessageBus for async_nats::Client {
+    type Error = NatsBusError;
+    const SEPARATOR: char = '.';
+    const WILDCARD: &'static str = "*";
+
+    async fn publish(&self, message: BusMessage) -> Result<(), Self::Error> {
+        match message.reply_to {
+            Some(reply_to) => {
+                self.publish_with_reply(message.topic, reply_to, message.payload.into())
+                    .await?
+            }
+            None => {
+                async_nats::Client::publish(self, message.topic, message.payload.into()).await?
+            }
+        }
+        Ok(())
+    }
+
+    async fn subscribe(
+        &self,
+        filter: String,
+    ) -> Result<BoxStream<'static, BusMessage>, Self::Error> {
+        let subscriber = async_nats::Client::subscribe(self, filter).await?;
+        Ok(subscriber
+            .map(|message| BusMessage {
+                topic: message.subject.to_string(),
+                reply_to: message.reply.map(|reply| reply.to_string()),
+                payload: message.payload.into(),
+            })
+            .boxed())
+    }
+}
@@ -0,0 +1,435 @@
+//! Record a session to a file, a "cassette", and play it back later in place
+//! of the peer, so tests against third-party servers can run offline and
+//! deterministically.
+//!
+//! ```rust,ignore
+//! // once, against the real server
+//! let transport = CassetteRecorder::create(TokioChildProcess::new(command)?, "tests/fetch.jsonl")?;
+//! let client = ().serve(transport).await?;
+//!
+//! // in the tests
+//! let client = ().serve(CassetteTransport::open("tests/fetch.jsonl")?).await?;
+//! ```
+//!
+//! A cassette has a line of JSON per message, `{"sent": message}` or
+//! `{"received": message}`. Playback answers each request sent with the
+//! messages received after the recorded request with the same method and
+//! parameters, like VCR does for HTTP: its response, with the id of the new
+//! request, and the notifications and requests the peer sent meanwhile.
+use std::{
+    io::{self, BufRead, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+use super::Transport;
+use crate::{
+    model::{ErrorCode, ErrorData, JsonRpcError, JsonRpcVersion2_0, RequestId},
+    service::{
+        CloseReason, ConnectionState, MemoryAccount, RxJsonRpcMessage, ServiceRole,
+        TxJsonRpcMessage,
+    },
+};
+
+/// A line of a cassette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CassetteEntry {
+    Sent(Value),
+    Received(Value),
+}
+
+/// A recorded request, and what was received in response.
+#[derive(Debug, Clone)]
+struct Interaction {
+    method: Value,
+    /// Without `_meta`, which holds progress tokens and the like.
+    params: Value,
+    id: Value,
+    progress_token: Option<Value>,
+    replies: Vec<Value>,
+}
+
+fn progress_token(params: &Value) -> Option<&Value> {
+    params.get("_meta")?.get("progressToken")
+}
+
+fn without_meta(params: Option<&Value>) -> Value {
+    let mut params = params.cloned().unwrap_or(Value::Null);
+    if let Some(params) = params.as_object_mut() {
+        params.remove("_meta");
+    }
+    params
+}
+
+/// A recorded session, grouped by request for playback.
+#[derive(Debug, Clone, Default)]
+pub struct Cassette {
+    /// Received before the first request.
+    leading: Vec<Value>,
+    interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn from_entries(entries: impl IntoIterator<Item = CassetteEntry>) -> Self {
+        let mut cassette = Self::default();
+        // the request the messages received belong to, unless they say
+        let mut current = None;
+        for entry in entries {
+            match entry {
+                CassetteEntry::Sent(message) => {
+                    if let (Some(method), Some(id)) = (message.get("method"), message.get("id")) {
+                        let params = message.get("params");
+                        cassette.interactions.push(Interaction {
+                            method: method.clone(),
+                            params: without_meta(params),
+                            id: id.clone(),
+                            progress_token: params.and_then(progress_token).cloned(),
+                            replies: Vec::new(),
+                        });
+                        current = Some(cassette.interactions.len() - 1);
+                    }
+                }
+                CassetteEntry::Received(message) => {
+                    let owner = if message.get("method").is_none() {
+                        // a response, to the latest request with its id
+                        let id = message.get("id");
+                        cassette
+                            .interactions
+                            .iter()
+                            .rposition(|interaction| Some(&interaction.id) == id)
+                    } else {
+                        let token = message.get("params").and_then(|p| p.get("progressToken"));
+                        cassette.interactions.iter().rposition(|interaction| {
+                            token.is_some() && interaction.progress_token.as_ref() == token
+                        })
+                    };
+                    match owner.or(current) {
+                        Some(index) => cassette.interactions[index].replies.push(message),
+                        None => cassette.leading.push(message),
+                    }
+                }
+            }
+        }
+        cassette
+    }
+
+    pub fn parse(reader: impl BufRead) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                entries.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(Self::from_entries(entries))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(io::BufReader::new(std::fs::File::open(path)?))
+    }
+
+    /// The recorded requests not played back yet.
+    pub fn remaining(&self) -> usize {
+        self.interactions.len()
+    }
+
+    /// Take the first recorded interaction matching `request`, with its
+    /// replies rewritten for the new request id and progress token.
+    fn play(&mut self, request: &Value) -> Option<Vec<Value>> {
+        let method = request.get("method")?;
+        let params = request.get("params");
+        let stripped = without_meta(params);
+        let index = self.interactions.iter().position(|interaction| {
+            &interaction.method == method && interaction.params == stripped
+        })?;
+        let interaction = self.interactions.remove(index);
+        let id = request.get("id")?;
+        let token = params.and_then(progress_token);
+        let replies = interaction
+            .replies
+            .into_iter()
+            .map(|mut reply| {
+                if reply.get("method").is_none() && reply.get("id") == Some(&interaction.id) {
+                    reply["id"] = id.clone();
+                }
+                if let (Some(recorded), Some(token)) = (&interaction.progress_token, token) {
+                    if let Some(reply_token) = reply
+                        .get_mut("params")
+                        .and_then(|params| params.get_mut("progressToken"))
+                    {
+                        if reply_token == recorded {
+                            *reply_token = token.clone();
+                        }
+                    }
+                }
+                reply
+            })
+            .collect();
+        Some(replies)
+    }
+}
+
+/// Plays a [`Cassette`] back in place of the peer.
+///
+/// Requests without a recorded match get an error response. Notifications
+/// and responses sent are ignored.
+#[derive(Debug)]
+pub struct CassetteTransport<R> {
+    cassette: Cassette,
+    sender: Option<mpsc::UnboundedSender<Value>>,
+    receiver: mpsc::UnboundedReceiver<Value>,
+    close_reason: Option<CloseReason>,
+    marker: std::marker::PhantomData<fn() -> R>,
+}
+
+impl<R: ServiceRole> CassetteTransport<R> {
+    pub fn new(mut cassette: Cassette) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        for message in std::mem::take(&mut cassette.leading) {
+            let _ = sender.send(message);
+        }
+        Self {
+            cassette,
+            sender: Some(sender),
+            receiver,
+            close_reason: None,
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Cassette::load(path).map(Self::new)
+    }
+
+    /// The recorded requests not played back yet.
+    pub fn remaining(&self) -> usize {
+        self.cassette.remaining()
+    }
+
+    fn no_match(request: &Value) -> Option<Value> {
+        let id: RequestId = serde_json::from_value(request.get("id")?.clone()).ok()?;
+        let method = request.get("method")?.as_str().unwrap_or_default();
+        tracing::warn!("No recorded interaction for {method} request {id}");
+        let error = JsonRpcError {
+            jsonrpc: JsonRpcVersion2_0,
+            id,
+            error: ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("the cassette has no recorded {method} request like this one"),
+                Some(request.get("params").cloned().unwrap_or_default()),
+            ),
+        };
+        serde_json::to_value(error).ok()
+    }
+}
+
+impl<R: ServiceRole> Transport<R> for CassetteTransport<R> {
+    type Error = serde_json::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let result = serde_json::to_value(item).map(|message| {
+            if message.get("method").is_none() || message.get("id").is_none() {
+                return;
+            }
+            let replies = self
+                .cassette
+                .play(&message)
+                .unwrap_or_else(|| Self::no_match(&message).into_iter().collect());
+            if let Some(sender) = &self.sender {
+                for reply in replies {
+                    let _ = sender.send(reply);
+                }
+            }
+        });
+        std::future::ready(result)
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        if self.close_reason.is_some() {
+            return None;
+        }
+        let message = self.receiver.recv().await?;
+        match serde_json::from_value(message.clone()) {
+            Ok(message) => Some(message),
+            Err(e) => {
+                self.close_reason = Some(CloseReason::protocol_error(
+                    e.to_string(),
+                    &message.to_string(),
+                ));
+                None
+            }
+        }
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.sender.take();
+        self.receiver.close();
+        std::future::ready(Ok(()))
+    }
+
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.close_reason.clone()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CassetteError<E: std::error::Error + 'static> {
+    #[error("transport error {0}")]
+    Transport(#[source] E),
+    #[error("io error {0}")]
+    Io(#[from] io::Error),
+    #[error("serde error {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+type Tape = Arc<Mutex<Box<dyn Write + Send>>>;
+
+fn write_entry(tape: &Tape, entry: &CassetteEntry) -> io::Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    let mut tape = tape.lock().expect("tape poisoned");
+    tape.write_all(&line)?;
+    tape.flush()
+}
+
+/// Records the messages of a transport to a [`Cassette`].
+pub struct CassetteRecorder<T> {
+    inner: T,
+    tape: Tape,
+}
+
+impl<T> std::fmt::Debug for CassetteRecorder<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CassetteRecorder").finish_non_exhaustive()
+    }
+}
+
+impl<T> CassetteRecorder<T> {
+    pub fn new(inner: T, writer: impl Write + Send + 'static) -> Self {
+        Self {
+            inner,
+            tape: Arc::new(Mutex::new(Box::new(writer))),
+        }
+    }
+
+    /// Record to the file at `path`, replacing it.
+    pub fn create(inner: T, path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(inner, std::fs::File::create(path)?))
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<R: ServiceRole, T: Transport<R>> Transport<R> for CassetteRecorder<T> {
+    type Error = CassetteError<T::Error>;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let recorded = serde_json::to_value(&item)
+            .map_err(CassetteError::from)
+            .and_then(|message| Ok(write_entry(&self.tape, &CassetteEntry::Sent(message))?));
+        let send = self.inner.send(item);
+        async move {
+            recorded?;
+            send.await.map_err(CassetteError::Transport)
+        }
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        let message = self.inner.receive().await?;
+        let recorded = serde_json::to_value(&message)
+            .map_err(io::Error::from)
+            .and_then(|message| write_entry(&self.tape, &CassetteEntry::Received(message)));
+        if let Err(e) = recorded {
+            tracing::warn!("Failed to record a message received: {e}");
+        }
+        Some(message)
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.inner.close().await.map_err(CassetteError::Transport)
+    }
+
+    fn state_changes(&self) -> Option<tokio::sync::watch::Receiver<ConnectionState>> {
+        self.inner.state_changes()
+    }
+
+    fn session_id(&self) -> Option<Arc<str>> {
+        self.inner.session_id()
+    }
+
+    fn memory_account(&self) -> Option<MemoryAccount> {
+        self.inner.memory_account()
+    }
+
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.inner.close_reason()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn replies(cassette: &mut Cassette, request: Value) -> Vec<Value> {
+        cassette.play(&request).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_playback_matching() {
+        let mut cassette = Cassette::from_entries([
+            CassetteEntry::Sent(json!({"jsonrpc": "2.0", "id": 0, "method": "tools/call",
+                "params": {"name": "slow", "_meta": {"progressToken": 7}}})),
+            CassetteEntry::Sent(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call",
+                "params": {"name": "echo"}})),
+            CassetteEntry::Received(json!({"jsonrpc": "2.0", "method": "notifications/progress",
+                "params": {"progressToken": 7, "progress": 1}})),
+            CassetteEntry::Received(json!({"jsonrpc": "2.0", "id": 1, "result": {"echo": 1}})),
+            CassetteEntry::Received(json!({"jsonrpc": "2.0", "id": 0, "result": {"slow": 1}})),
+            CassetteEntry::Sent(json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+                "params": {"name": "echo"}})),
+            CassetteEntry::Received(json!({"jsonrpc": "2.0", "id": 2, "result": {"echo": 2}})),
+        ]);
+
+        // same parameters, replayed in the recorded order
+        let echo =
+            json!({"jsonrpc": "2.0", "id": 10, "method": "tools/call", "params": {"name": "echo"}});
+        assert_eq!(
+            replies(&mut cassette, echo.clone()),
+            [json!({"jsonrpc": "2.0", "id": 10, "result": {"echo": 1}})]
+        );
+        assert_eq!(
+            replies(&mut cassette, echo.clone()),
+            [json!({"jsonrpc": "2.0", "id": 10, "result": {"echo": 2}})]
+        );
+        assert!(cassette.play(&echo).is_none());
+
+        // progress notifications follow their request, whatever `_meta` says
+        let slow = json!({"jsonrpc": "2.0", "id": 11, "method": "tools/call",
+            "params": {"name": "slow", "_meta": {"progressToken": "p"}}});
+        assert_eq!(
+            replies(&mut cassette, slow),
+            [
+                json!({"jsonrpc": "2.0", "method": "notifications/progress",
+                    "params": {"progressToken": "p", "progress": 1}}),
+                json!({"jsonrpc": "2.0", "id": 11, "result": {"slow": 1}}),
+            ]
+        );
+        assert_eq!(cassette.remaining(), 0);
+    }
+}
@@ -7,8 +7,11 @@ use tokio::{
     process::{ChildStderr, ChildStdin, ChildStdout},
 };
 
-use super::{RxJsonRpcMessage, Transport, TxJsonRpcMessage, async_rw::AsyncRwTransport};
-use crate::RoleClient;
+use super::{
+    RxJsonRpcMessage, Transport, TxJsonRpcMessage,
+    async_rw::{AsyncRwTransport, ENCODING_ENV, Encoding, FRAMING_ENV, Framing},
+};
+use crate::{RoleClient, service::CloseReason};
 
 const MAX_WAIT_ON_DROP_SECS: u64 = 3;
 /// The parts of a child process.
@@ -156,6 +159,8 @@ pub struct TokioChildProcessBuilder {
     stdin: Stdio,
     stdout: Stdio,
     stderr: Stdio,
+    framing: Framing,
+    encoding: Encoding,
 }
 
 impl TokioChildProcessBuilder {
@@ -165,6 +170,8 @@ impl TokioChildProcessBuilder {
             stdin: Stdio::piped(),
             stdout: Stdio::piped(),
             stderr: Stdio::inherit(),
+            framing: Framing::default(),
+            encoding: Encoding::default(),
         }
     }
 
@@ -184,6 +191,22 @@ impl TokioChildProcessBuilder {
         self
     }
 
+    /// Delimit messages with `framing`, telling the child through
+    /// [`FRAMING_ENV`]. Without this the child is started with that variable
+    /// unset, so it doesn't inherit the framing of this process.
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Serialize messages with `encoding`, telling the child through
+    /// [`ENCODING_ENV`]. As with [`framing`](Self::framing), the variable is
+    /// unset otherwise.
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Spawn the child process. Returns the transport plus an optional captured stderr handle.
     pub fn spawn(mut self) -> std::io::Result<(TokioChildProcess, Option<ChildStderr>)> {
         self.cmd
@@ -191,10 +214,20 @@ impl TokioChildProcessBuilder {
             .stdin(self.stdin)
             .stdout(self.stdout)
             .stderr(self.stderr);
+        match self.framing {
+            Framing::NewlineDelimited => self.cmd.command_mut().env_remove(FRAMING_ENV),
+            framing => self.cmd.command_mut().env(FRAMING_ENV, framing.as_str()),
+        };
+        if self.encoding.is_binary() {
+            let encoding = self.encoding.as_str();
+            self.cmd.command_mut().env(ENCODING_ENV, encoding);
+        } else {
+            self.cmd.command_mut().env_remove(ENCODING_ENV);
+        }
 
         let (child, stdout, stdin, stderr_opt) = child_process(self.cmd.spawn()?)?;
 
-        let transport = AsyncRwTransport::new(stdout, stdin);
+        let transport = AsyncRwTransport::with_codec(stdout, stdin, self.framing, self.encoding);
         let proc = TokioChildProcess {
             child: ChildWithCleanup { inner: Some(child) },
             transport,
@@ -217,6 +250,10 @@ impl Transport<RoleClient> for TokioChildProcess {
         self.transport.receive()
     }
 
+    fn close_reason(&self) -> Option<CloseReason> {
+        Transport::<RoleClient>::close_reason(&self.transport)
+    }
+
     fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
         self.graceful_shutdown()
     }
@@ -167,6 +167,29 @@ impl<E: std::error::Error + Send> SseAutoReconnectStream<NeverReconnect<E>> {
     }
 }
 
+/// Decode a `text/event-stream` body, delivered in `chunks`, into the server
+/// messages it carries, the way the streamable HTTP client does. Only built
+/// for the fuzz targets.
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub fn decode_sse_body(chunks: Vec<Vec<u8>>) -> Vec<ServerJsonRpcMessage> {
+    use futures::StreamExt;
+
+    let body = futures::stream::iter(
+        chunks
+            .into_iter()
+            .map(|chunk| Ok::<_, std::io::Error>(std::io::Cursor::new(chunk))),
+    );
+    let stream = sse_stream::SseStream::from_bytes_stream(body).boxed();
+    let messages =
+        SseAutoReconnectStream::never_reconnect(stream, std::io::Error::other("sse stream failed"));
+    futures::executor::block_on(
+        messages
+            .filter_map(|message| std::future::ready(message.ok()))
+            .collect(),
+    )
+}
+
 pin_project_lite::pin_project! {
     #[project = SseAutoReconnectStreamStateProj]
     pub enum SseAutoReconnectStreamState<F> {
@@ -151,6 +151,25 @@ impl StreamableHttpClient for reqwest::Client {
             }
         }
     }
+
+    fn is_transient(&self, error: &StreamableHttpError<Self::Error>) -> bool {
+        match error {
+            StreamableHttpError::Io(_) => true,
+            StreamableHttpError::Client(e) => {
+                e.is_connect()
+                    || e.is_timeout()
+                    || e.status().is_some_and(|status| {
+                        matches!(
+                            status,
+                            reqwest::StatusCode::BAD_GATEWAY
+                                | reqwest::StatusCode::SERVICE_UNAVAILABLE
+                                | reqwest::StatusCode::GATEWAY_TIMEOUT
+                        )
+                    })
+            }
+            _ => false,
+        }
+    }
 }
 
 impl StreamableHttpClientTransport<reqwest::Client> {
@@ -0,0 +1,461 @@
+//! Wrappers adding timeouts, bandwidth limits or faults to any
+//! [`Transport`], to test how clients and servers cope with slow or flaky
+//! networks without one.
+//!
+//! ```rust,ignore
+//! use rmcp::transport::compose::{Faults, TransportExt};
+//!
+//! let transport = transport
+//!     .throttled(64 * 1024)
+//!     .with_faults(Faults::default().with_drop(0.01).with_delay(0.1, Duration::from_secs(2)))
+//!     .with_timeouts(None, Some(Duration::from_secs(5)));
+//! ```
+use std::{future::Future, sync::Arc, time::Duration};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+use tokio::time::Instant;
+
+use super::Transport;
+use crate::service::{
+    CloseReason, ConnectionState, MemoryAccount, RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage,
+};
+
+/// Combinators of [`Transport`]s.
+pub trait TransportExt<R: ServiceRole>: Transport<R> + Sized {
+    /// See [`TimeoutTransport`].
+    fn with_timeouts(
+        self,
+        read: Option<Duration>,
+        write: Option<Duration>,
+    ) -> TimeoutTransport<Self> {
+        TimeoutTransport {
+            inner: self,
+            read,
+            write,
+            close_reason: None,
+        }
+    }
+
+    /// See [`ThrottledTransport`].
+    fn throttled(self, bytes_per_second: u64) -> ThrottledTransport<Self> {
+        ThrottledTransport::new(self, bytes_per_second)
+    }
+
+    /// See [`FaultyTransport`], with the same faults both ways.
+    fn with_faults(self, faults: Faults) -> FaultyTransport<Self> {
+        FaultyTransport::new(self, faults)
+    }
+}
+
+impl<R: ServiceRole, T: Transport<R>> TransportExt<R> for T {}
+
+#[derive(Debug, Error)]
+pub enum TimeoutError<E: std::error::Error + 'static> {
+    #[error("transport error {0}")]
+    Transport(#[source] E),
+    #[error("send timed out after {0:?}")]
+    Elapsed(Duration),
+}
+
+/// Fails sends taking longer than the write timeout, and ends the transport
+/// when nothing is received for the read timeout.
+///
+/// Peers may rightly stay quiet for long, so a read timeout is for sessions
+/// kept busy, e.g. with periodic pings.
+#[derive(Debug)]
+pub struct TimeoutTransport<T> {
+    inner: T,
+    read: Option<Duration>,
+    write: Option<Duration>,
+    close_reason: Option<CloseReason>,
+}
+
+impl<T> TimeoutTransport<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            read: None,
+            write: None,
+            close_reason: None,
+        }
+    }
+
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read = Some(timeout);
+        self
+    }
+
+    pub fn with_write_timeout(mut self, timeout: Duration) -> Self {
+        self.write = Some(timeout);
+        self
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<R: ServiceRole, T: Transport<R>> Transport<R> for TimeoutTransport<T> {
+    type Error = TimeoutError<T::Error>;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let send = self.inner.send(item);
+        let write = self.write;
+        async move {
+            match write {
+                Some(write) => tokio::time::timeout(write, send)
+                    .await
+                    .map_err(|_| TimeoutError::Elapsed(write))?
+                    .map_err(TimeoutError::Transport),
+                None => send.await.map_err(TimeoutError::Transport),
+            }
+        }
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        if self.close_reason.is_some() {
+            return None;
+        }
+        let Some(read) = self.read else {
+            return self.inner.receive().await;
+        };
+        match tokio::time::timeout(read, self.inner.receive()).await {
+            Ok(message) => message,
+            Err(_) => {
+                self.close_reason = Some(CloseReason::Failed(format!(
+                    "nothing received for {read:?}"
+                )));
+                None
+            }
+        }
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.inner.close().await.map_err(TimeoutError::Transport)
+    }
+
+    fn state_changes(&self) -> Option<tokio::sync::watch::Receiver<ConnectionState>> {
+        self.inner.state_changes()
+    }
+
+    fn session_id(&self) -> Option<Arc<str>> {
+        self.inner.session_id()
+    }
+
+    fn memory_account(&self) -> Option<MemoryAccount> {
+        self.inner.memory_account()
+    }
+
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.close_reason
+            .clone()
+            .or_else(|| self.inner.close_reason())
+    }
+}
+
+/// The size of `message` as JSON, what the bandwidth limits count.
+fn json_len<T: Serialize>(message: &T) -> usize {
+    serde_json::to_vec(message).map_or(0, |json| json.len())
+}
+
+/// One direction of a [`ThrottledTransport`]: when the messages passed so
+/// far are through.
+#[derive(Debug)]
+struct Pace {
+    bytes_per_second: u64,
+    free_at: Instant,
+}
+
+impl Pace {
+    fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second: bytes_per_second.max(1),
+            free_at: Instant::now(),
+        }
+    }
+
+    /// When a message of `len` bytes, queued after the previous ones, is
+    /// through.
+    fn reserve(&mut self, len: usize) -> Instant {
+        let start = self.free_at.max(Instant::now());
+        self.free_at = start + Duration::from_secs_f64(len as f64 / self.bytes_per_second as f64);
+        self.free_at
+    }
+}
+
+/// Limits the bandwidth of each direction, holding every message back
+/// for as long as its JSON takes at the given rate, queued after the
+/// previous messages.
+#[derive(Debug)]
+pub struct ThrottledTransport<T> {
+    inner: T,
+    send: Pace,
+    receive: Pace,
+}
+
+impl<T> ThrottledTransport<T> {
+    /// The same limit both ways.
+    pub fn new(inner: T, bytes_per_second: u64) -> Self {
+        Self {
+            inner,
+            send: Pace::new(bytes_per_second),
+            receive: Pace::new(bytes_per_second),
+        }
+    }
+
+    pub fn with_send_rate(mut self, bytes_per_second: u64) -> Self {
+        self.send = Pace::new(bytes_per_second);
+        self
+    }
+
+    pub fn with_receive_rate(mut self, bytes_per_second: u64) -> Self {
+        self.receive = Pace::new(bytes_per_second);
+        self
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<R: ServiceRole, T: Transport<R>> Transport<R> for ThrottledTransport<T> {
+    type Error = T::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let through = self.send.reserve(json_len(&item));
+        let send = self.inner.send(item);
+        async move {
+            tokio::time::sleep_until(through).await;
+            send.await
+        }
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        let message = self.inner.receive().await?;
+        tokio::time::sleep_until(self.receive.reserve(json_len(&message))).await;
+        Some(message)
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.inner.close()
+    }
+
+    fn state_changes(&self) -> Option<tokio::sync::watch::Receiver<ConnectionState>> {
+        self.inner.state_changes()
+    }
+
+    fn session_id(&self) -> Option<Arc<str>> {
+        self.inner.session_id()
+    }
+
+    fn memory_account(&self) -> Option<MemoryAccount> {
+        self.inner.memory_account()
+    }
+
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.inner.close_reason()
+    }
+}
+
+/// The chances of each fault of a [`FaultyTransport`], per message.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Faults {
+    /// Lose the message.
+    pub drop: f64,
+    /// Hold the message back, for up to `max_delay`.
+    pub delay: f64,
+    pub max_delay: Duration,
+    /// Change one character of its JSON. A message received that no longer
+    /// parses ends the transport with a protocol error, like the byte stream
+    /// transports; one sent is lost instead.
+    pub corrupt: f64,
+}
+
+impl Faults {
+    pub fn with_drop(mut self, chance: f64) -> Self {
+        self.drop = chance;
+        self
+    }
+
+    pub fn with_delay(mut self, chance: f64, max_delay: Duration) -> Self {
+        self.delay = chance;
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_corrupt(mut self, chance: f64) -> Self {
+        self.corrupt = chance;
+        self
+    }
+}
+
+/// What happens to one message.
+enum Fate<T> {
+    Dropped,
+    Delivered {
+        message: T,
+        delay: Option<Duration>,
+    },
+    /// Corrupted beyond parsing.
+    Garbled(CloseReason),
+}
+
+fn roll(rng: &mut StdRng, chance: f64) -> bool {
+    chance > 0.0 && rng.random_bool(chance.min(1.0))
+}
+
+fn fate<T: Serialize + DeserializeOwned>(rng: &mut StdRng, faults: &Faults, message: T) -> Fate<T> {
+    if roll(rng, faults.drop) {
+        return Fate::Dropped;
+    }
+    let delay = (roll(rng, faults.delay) && !faults.max_delay.is_zero())
+        .then(|| rng.random_range(Duration::ZERO..=faults.max_delay));
+    if !roll(rng, faults.corrupt) {
+        return Fate::Delivered { message, delay };
+    }
+    match corrupt(rng, &message) {
+        Ok(message) => Fate::Delivered { message, delay },
+        Err(reason) => Fate::Garbled(reason),
+    }
+}
+
+/// Replace one ASCII character of the JSON of `message`, keeping it UTF-8.
+fn corrupt<T: Serialize + DeserializeOwned>(
+    rng: &mut StdRng,
+    message: &T,
+) -> Result<T, CloseReason> {
+    let mut json = serde_json::to_vec(message).map_err(|e| CloseReason::Failed(e.to_string()))?;
+    let ascii: Vec<usize> = (0..json.len()).filter(|&i| json[i].is_ascii()).collect();
+    if let Some(&index) = ascii.get(rng.random_range(0..ascii.len().max(1))) {
+        let original = json[index];
+        while json[index] == original {
+            json[index] = rng.random_range(0x20..0x7f);
+        }
+    }
+    serde_json::from_slice(&json)
+        .map_err(|e| CloseReason::protocol_error(e.to_string(), &String::from_utf8_lossy(&json)))
+}
+
+/// Drops, delays and corrupts messages at random, for chaos testing.
+///
+/// The faults are drawn from a random generator seeded with
+/// [`with_seed`](Self::with_seed), to replay a failing run.
+#[derive(Debug)]
+pub struct FaultyTransport<T> {
+    inner: T,
+    send: Faults,
+    receive: Faults,
+    rng: StdRng,
+    close_reason: Option<CloseReason>,
+}
+
+impl<T> FaultyTransport<T> {
+    /// The same faults both ways.
+    pub fn new(inner: T, faults: Faults) -> Self {
+        Self {
+            inner,
+            send: faults,
+            receive: faults,
+            rng: StdRng::from_os_rng(),
+            close_reason: None,
+        }
+    }
+
+    pub fn with_send_faults(mut self, faults: Faults) -> Self {
+        self.send = faults;
+        self
+    }
+
+    pub fn with_receive_faults(mut self, faults: Faults) -> Self {
+        self.receive = faults;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<R: ServiceRole, T: Transport<R>> Transport<R> for FaultyTransport<T> {
+    type Error = T::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let (send, delay) = match fate(&mut self.rng, &self.send, item) {
+            Fate::Delivered { message, delay } => (Some(self.inner.send(message)), delay),
+            Fate::Dropped => (None, None),
+            Fate::Garbled(reason) => {
+                tracing::debug!("Dropping a message corrupted beyond parsing: {reason}");
+                (None, None)
+            }
+        };
+        async move {
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+            }
+            match send {
+                Some(send) => send.await,
+                None => Ok(()),
+            }
+        }
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        if self.close_reason.is_some() {
+            return None;
+        }
+        loop {
+            let message = self.inner.receive().await?;
+            match fate(&mut self.rng, &self.receive, message) {
+                Fate::Dropped => continue,
+                Fate::Delivered { message, delay } => {
+                    if let Some(delay) = delay {
+                        tokio::time::sleep(delay).await;
+                    }
+                    return Some(message);
+                }
+                Fate::Garbled(reason) => {
+                    self.close_reason = Some(reason);
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.inner.close()
+    }
+
+    fn state_changes(&self) -> Option<tokio::sync::watch::Receiver<ConnectionState>> {
+        self.inner.state_changes()
+    }
+
+    fn session_id(&self) -> Option<Arc<str>> {
+        self.inner.session_id()
+    }
+
+    fn memory_account(&self) -> Option<MemoryAccount> {
+        self.inner.memory_account()
+    }
+
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.close_reason
+            .clone()
+            .or_else(|| self.inner.close_reason())
+    }
+}
@@ -0,0 +1,498 @@
+//! Run a stdio server shipped as a container image, talking to it through
+//! the stdio of the container.
+//!
+//! ```rust,ignore
+//! let spec = ContainerSpec::new("mcp/fetch")
+//!     .env("LOG_LEVEL", "debug")
+//!     .volume("/srv/data", "/data");
+//! let transport = ContainerTransport::start(ContainerCli::docker(), spec).await?;
+//! let client = ().serve(transport).await?;
+//! ```
+//!
+//! The container is created, then started attached to its stdio, and removed
+//! when the transport is closed or dropped. [`ContainerCli`] does this with
+//! the `docker` or `podman` command line, other engines implement
+//! [`ContainerLauncher`].
+use std::{ffi::OsStr, future::Future, io, path::PathBuf, process::Stdio};
+
+use super::{
+    TokioChildProcess, Transport,
+    async_rw::{ENCODING_ENV, Encoding, FRAMING_ENV, Framing},
+};
+use crate::{
+    RoleClient,
+    service::{CloseReason, RxJsonRpcMessage, TxJsonRpcMessage},
+};
+
+/// When [`ContainerTransport::start`] pulls the image.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PullPolicy {
+    /// Pull the image if there is no local copy.
+    #[default]
+    Missing,
+    /// Always pull, to get the latest version of the tag.
+    Always,
+    /// Never pull: the image must be there already.
+    Never,
+}
+
+/// The container to run a server in.
+#[derive(Debug, Clone)]
+pub struct ContainerSpec {
+    pub image: String,
+    /// Arguments after the image, the command of the container or the
+    /// arguments of its entrypoint.
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    /// Host paths mounted in the container, `(host, container)`.
+    pub volumes: Vec<(String, String)>,
+    pub network: Option<String>,
+    pub name: Option<String>,
+    /// Other options of the create command of the engine, passed as is.
+    pub create_args: Vec<String>,
+    pub pull: PullPolicy,
+    pub framing: Framing,
+    pub encoding: Encoding,
+}
+
+impl ContainerSpec {
+    pub fn new(image: impl Into<String>) -> Self {
+        Self {
+            image: image.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            volumes: Vec::new(),
+            network: None,
+            name: None,
+            create_args: Vec::new(),
+            pull: PullPolicy::default(),
+            framing: Framing::default(),
+            encoding: Encoding::default(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I>(mut self, args: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn volume(mut self, host: impl Into<String>, container: impl Into<String>) -> Self {
+        self.volumes.push((host.into(), container.into()));
+        self
+    }
+
+    pub fn network(mut self, network: impl Into<String>) -> Self {
+        self.network = Some(network.into());
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn create_arg(mut self, arg: impl Into<String>) -> Self {
+        self.create_args.push(arg.into());
+        self
+    }
+
+    pub fn pull(mut self, pull: PullPolicy) -> Self {
+        self.pull = pull;
+        self
+    }
+
+    /// Like [`TokioChildProcessBuilder::framing`](super::child_process::TokioChildProcessBuilder::framing),
+    /// telling the server through the environment of the container.
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Like [`TokioChildProcessBuilder::encoding`](super::child_process::TokioChildProcessBuilder::encoding),
+    /// telling the server through the environment of the container.
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// [`env`](Self::env) with the variables telling the server the framing
+    /// and encoding.
+    pub fn container_env(&self) -> Vec<(String, String)> {
+        let mut env = self.env.clone();
+        if self.framing != Framing::default() {
+            env.push((FRAMING_ENV.to_owned(), self.framing.as_str().to_owned()));
+        }
+        if self.encoding.is_binary() {
+            env.push((ENCODING_ENV.to_owned(), self.encoding.as_str().to_owned()));
+        }
+        env
+    }
+}
+
+/// Manages the lifecycle of containers for [`ContainerTransport`].
+pub trait ContainerLauncher: Clone + Send + Sync + 'static {
+    /// Whether there is a local copy of `image`.
+    fn has_image(&self, image: &str) -> impl Future<Output = io::Result<bool>> + Send;
+
+    fn pull(&self, image: &str) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Create a container with stdin kept open, without starting it, and
+    /// return its id.
+    fn create(&self, spec: &ContainerSpec) -> impl Future<Output = io::Result<String>> + Send;
+
+    /// The command starting the container `id`, its stdin and stdout being
+    /// those of the container.
+    fn attach(&self, id: &str) -> tokio::process::Command;
+
+    /// Remove the container `id`, stopping it if it still runs.
+    fn remove(&self, id: &str) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+/// A [`ContainerLauncher`] running a docker compatible command line.
+#[derive(Debug, Clone)]
+pub struct ContainerCli {
+    program: PathBuf,
+}
+
+impl ContainerCli {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+
+    pub fn docker() -> Self {
+        Self::new("docker")
+    }
+
+    pub fn podman() -> Self {
+        Self::new("podman")
+    }
+
+    /// A command running `program` in the running container `container`,
+    /// to use with [`TokioChildProcess`] for servers of containers managed
+    /// elsewhere. The environment of the command doesn't reach `program`.
+    pub fn exec(&self, container: &str, program: &str) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(&self.program);
+        command.args(["exec", "-i", container, program]);
+        command
+    }
+
+    /// The arguments of the create command for `spec`.
+    pub fn create_args(spec: &ContainerSpec) -> Vec<String> {
+        let mut args = vec!["create".to_owned(), "--interactive".to_owned()];
+        for (key, value) in spec.container_env() {
+            args.push("--env".to_owned());
+            args.push(format!("{key}={value}"));
+        }
+        for (host, container) in &spec.volumes {
+            args.push("--volume".to_owned());
+            args.push(format!("{host}:{container}"));
+        }
+        if let Some(network) = &spec.network {
+            args.push("--network".to_owned());
+            args.push(network.clone());
+        }
+        if let Some(name) = &spec.name {
+            args.push("--name".to_owned());
+            args.push(name.clone());
+        }
+        args.extend(spec.create_args.iter().cloned());
+        args.push("--".to_owned());
+        args.push(spec.image.clone());
+        args.extend(spec.args.iter().cloned());
+        args
+    }
+
+    /// Run the command with `args`, returning its stdout, or its stderr as
+    /// the error if it fails.
+    async fn run<I>(&self, args: I) -> io::Result<String>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<OsStr>,
+    {
+        let output = tokio::process::Command::new(&self.program)
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .await?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+        } else {
+            Err(io::Error::other(format!(
+                "{} failed, {}: {}",
+                self.program.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+}
+
+impl ContainerLauncher for ContainerCli {
+    async fn has_image(&self, image: &str) -> io::Result<bool> {
+        let status = tokio::process::Command::new(&self.program)
+            .args(["image", "inspect", image])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await?;
+        Ok(status.success())
+    }
+
+    async fn pull(&self, image: &str) -> io::Result<()> {
+        self.run(["pull", "--quiet", image]).await.map(drop)
+    }
+
+    async fn create(&self, spec: &ContainerSpec) -> io::Result<String> {
+        self.run(Self::create_args(spec)).await
+    }
+
+    fn attach(&self, id: &str) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(&self.program);
+        command.args(["start", "--attach", "--interactive", id]);
+        command
+    }
+
+    async fn remove(&self, id: &str) -> io::Result<()> {
+        self.run(["rm", "--force", id]).await.map(drop)
+    }
+}
+
+/// A client transport to a server running in a container, which it removes
+/// once closed or dropped.
+pub struct ContainerTransport<L: ContainerLauncher = ContainerCli> {
+    process: TokioChildProcess,
+    launcher: L,
+    id: String,
+    removed: bool,
+}
+
+impl<L: ContainerLauncher> std::fmt::Debug for ContainerTransport<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContainerTransport")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<L: ContainerLauncher> ContainerTransport<L> {
+    /// Pull the image as `spec.pull` says, create the container and start
+    /// it attached to the transport.
+    pub async fn start(launcher: L, spec: ContainerSpec) -> io::Result<Self> {
+        let pull = match spec.pull {
+            PullPolicy::Always => true,
+            PullPolicy::Missing => !launcher.has_image(&spec.image).await?,
+            PullPolicy::Never => false,
+        };
+        if pull {
+            tracing::info!("Pulling {}", spec.image);
+            launcher.pull(&spec.image).await?;
+        }
+        let id = launcher.create(&spec).await?;
+        let spawned = TokioChildProcess::builder(launcher.attach(&id))
+            .framing(spec.framing)
+            .encoding(spec.encoding)
+            .spawn();
+        match spawned {
+            Ok((process, _stderr)) => Ok(Self {
+                process,
+                launcher,
+                id,
+                removed: false,
+            }),
+            Err(e) => {
+                if let Err(e) = launcher.remove(&id).await {
+                    tracing::warn!("Failed to remove container {id}: {e}");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// The id of the container.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Stop the server like [`TokioChildProcess::graceful_shutdown`], then
+    /// remove the container.
+    pub async fn shutdown(&mut self) -> io::Result<()> {
+        let shutdown = self.process.graceful_shutdown().await;
+        if !self.removed {
+            self.launcher.remove(&self.id).await?;
+            self.removed = true;
+        }
+        shutdown
+    }
+}
+
+impl<L: ContainerLauncher> Drop for ContainerTransport<L> {
+    fn drop(&mut self) {
+        if self.removed {
+            return;
+        }
+        let (launcher, id) = (self.launcher.clone(), std::mem::take(&mut self.id));
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    if let Err(e) = launcher.remove(&id).await {
+                        tracing::warn!("Failed to remove container {id}: {e}");
+                    }
+                });
+            }
+            Err(_) => tracing::warn!("No runtime to remove container {id}"),
+        }
+    }
+}
+
+impl<L: ContainerLauncher> Transport<RoleClient> for ContainerTransport<L> {
+    type Error = io::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<RoleClient>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        self.process.send(item)
+    }
+
+    fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<RoleClient>>> + Send {
+        self.process.receive()
+    }
+
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.process.close_reason()
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.shutdown()
+    }
+}
+
+#[cfg(unix)]
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Runs a shell instead of the container, recording the calls.
+    #[derive(Clone, Default)]
+    struct FakeLauncher {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl FakeLauncher {
+        fn record(&self, call: String) {
+            self.calls.lock().expect("calls poisoned").push(call);
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().expect("calls poisoned").clone()
+        }
+    }
+
+    impl ContainerLauncher for FakeLauncher {
+        async fn has_image(&self, image: &str) -> io::Result<bool> {
+            self.record(format!("has_image {image}"));
+            Ok(false)
+        }
+
+        async fn pull(&self, image: &str) -> io::Result<()> {
+            self.record(format!("pull {image}"));
+            Ok(())
+        }
+
+        async fn create(&self, spec: &ContainerSpec) -> io::Result<String> {
+            self.record(format!("create {}", spec.image));
+            Ok("c0ffee".to_owned())
+        }
+
+        fn attach(&self, id: &str) -> tokio::process::Command {
+            self.record(format!("attach {id}"));
+            let mut command = tokio::process::Command::new("sh");
+            command.args(["-c", "cat > /dev/null"]);
+            command
+        }
+
+        async fn remove(&self, id: &str) -> io::Result<()> {
+            self.record(format!("remove {id}"));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_container_lifecycle() -> io::Result<()> {
+        let launcher = FakeLauncher::default();
+        let mut transport =
+            ContainerTransport::start(launcher.clone(), ContainerSpec::new("mcp/fetch")).await?;
+        assert_eq!(transport.id(), "c0ffee");
+        Transport::close(&mut transport).await?;
+        drop(transport);
+        assert_eq!(
+            launcher.calls(),
+            [
+                "has_image mcp/fetch",
+                "pull mcp/fetch",
+                "create mcp/fetch",
+                "attach c0ffee",
+                "remove c0ffee",
+            ]
+        );
+
+        // dropped without closing, and the image never pulled
+        let spec = ContainerSpec::new("mcp/time").pull(PullPolicy::Never);
+        drop(ContainerTransport::start(launcher.clone(), spec).await?);
+        while launcher.calls().len() < 8 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(
+            launcher.calls()[5..],
+            ["create mcp/time", "attach c0ffee", "remove c0ffee"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_args() {
+        let spec = ContainerSpec::new("mcp/fetch")
+            .arg("--ignore-robots-txt")
+            .env("LOG_LEVEL", "debug")
+            .volume("/srv/data", "/data")
+            .network("none")
+            .framing(Framing::ContentLength);
+        assert_eq!(
+            ContainerCli::create_args(&spec),
+            [
+                "create",
+                "--interactive",
+                "--env",
+                "LOG_LEVEL=debug",
+                "--env",
+                "MCP_STDIO_FRAMING=content-length",
+                "--volume",
+                "/srv/data:/data",
+                "--network",
+                "none",
+                "--",
+                "mcp/fetch",
+                "--ignore-robots-txt",
+            ]
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! MCP over a bidirectional gRPC stream, for networks where gRPC is the only
+//! protocol allowed between services.
+//!
+//! Each call of `Connect` is one MCP session; every frame carries one
+//! JSON-RPC message as JSON text:
+//!
+//! ```proto
+//! syntax = "proto3";
+//! package rmcp.v1;
+//!
+//! service Mcp {
+//!   rpc Connect(stream Frame) returns (stream Frame);
+//! }
+//!
+//! message Frame {
+//!   string message = 1;
+//! }
+//! ```
+//!
+//! Servers add [`McpGrpcService`] to a tonic server, together with
+//! [`reflection_service`] so tools like `grpcurl` can discover it; clients
+//! [`connect`](GrpcTransport::connect) a [`GrpcClientTransport`].
+//!
+//! ```rust,ignore
+//! tonic::transport::Server::builder()
+//!     .add_service(McpGrpcService::new(|| Ok(Counter::new())))
+//!     .add_service(reflection_service())
+//!     .serve("127.0.0.1:50051".parse()?)
+//!     .await?;
+//!
+//! let transport = GrpcClientTransport::connect("http://127.0.0.1:50051").await?;
+//! let client = ().serve(transport).await?;
+//! ```
+use std::marker::PhantomData;
+#[cfg(feature = "server")]
+use std::sync::Arc;
+
+#[cfg(feature = "server")]
+use futures::StreamExt;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+#[cfg(feature = "server")]
+use tokio_util::sync::CancellationToken;
+use tonic::{Status, Streaming, codegen::http};
+#[cfg(feature = "server")]
+use tonic::{
+    codegen::{Body, BoxFuture, Context, Poll, StdError},
+    server::NamedService,
+};
+
+use super::Transport;
+#[cfg(feature = "client")]
+use crate::service::RoleClient;
+use crate::service::{CloseReason, RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
+#[cfg(feature = "server")]
+use crate::service::{RoleServer, Service, serve_server_with_ct};
+
+/// Full name of the gRPC service.
+pub const SERVICE_NAME: &str = "rmcp.v1.Mcp";
+const CONNECT_PATH: &str = "/rmcp.v1.Mcp/Connect";
+const CHANNEL_SIZE: usize = 16;
+
+/// One JSON-RPC message.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Frame {
+    #[prost(string, tag = "1")]
+    pub message: String,
+}
+
+/// The descriptor of the `rmcp.v1` protocol, for reflection.
+pub fn file_descriptor_set() -> prost_types::FileDescriptorSet {
+    use prost_types::{
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+        MethodDescriptorProto, ServiceDescriptorProto,
+        field_descriptor_proto::{Label, Type},
+    };
+    let frame = DescriptorProto {
+        name: Some("Frame".to_owned()),
+        field: vec![FieldDescriptorProto {
+            name: Some("message".to_owned()),
+            number: Some(1),
+            label: Some(Label::Optional as i32),
+            r#type: Some(Type::String as i32),
+            json_name: Some("message".to_owned()),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let service = ServiceDescriptorProto {
+        name: Some("Mcp".to_owned()),
+        method: vec![MethodDescriptorProto {
+            name: Some("Connect".to_owned()),
+            input_type: Some(".rmcp.v1.Frame".to_owned()),
+            output_type: Some(".rmcp.v1.Frame".to_owned()),
+            client_streaming: Some(true),
+            server_streaming: Some(true),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    FileDescriptorSet {
+        file: vec![FileDescriptorProto {
+            name: Some("rmcp/v1/mcp.proto".to_owned()),
+            package: Some("rmcp.v1".to_owned()),
+            message_type: vec![frame],
+            service: vec![service],
+            syntax: Some("proto3".to_owned()),
+            ..Default::default()
+        }],
+    }
+}
+
+/// A gRPC reflection service (`grpc.reflection.v1`) describing
+/// [`SERVICE_NAME`] and itself.
+pub fn reflection_service() -> tonic_reflection::server::v1::ServerReflectionServer<
+    impl tonic_reflection::server::v1::ServerReflection,
+> {
+    tonic_reflection::server::Builder::configure()
+        .register_file_descriptor_set(file_descriptor_set())
+        .build_v1()
+        .expect("the rmcp.v1 descriptor is valid")
+}
+
+#[derive(Debug, Error)]
+pub enum GrpcTransportError {
+    #[error("transport is closed")]
+    Closed,
+    #[error("serde error {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("connect error {0}")]
+    Connect(#[from] tonic::transport::Error),
+    #[error("grpc status {0}")]
+    Status(Box<Status>),
+}
+
+impl From<Status> for GrpcTransportError {
+    fn from(value: Status) -> Self {
+        Self::Status(Box::new(value))
+    }
+}
+
+/// One end of a `Connect` stream.
+pub struct GrpcTransport<R> {
+    outbound: Option<mpsc::Sender<Frame>>,
+    inbound: Streaming<Frame>,
+    close_reason: Option<CloseReason>,
+    _role: PhantomData<fn() -> R>,
+}
+
+impl<R> std::fmt::Debug for GrpcTransport<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrpcTransport")
+            .field("closed", &self.outbound.is_none())
+            .field("close_reason", &self.close_reason)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R> GrpcTransport<R> {
+    fn new(outbound: mpsc::Sender<Frame>, inbound: Streaming<Frame>) -> Self {
+        Self {
+            outbound: Some(outbound),
+            inbound,
+            close_reason: None,
+            _role: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+#[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+pub type GrpcClientTransport = GrpcTransport<RoleClient>;
+
+#[cfg(feature = "client")]
+#[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+impl GrpcTransport<RoleClient> {
+    /// Open a session with the [`McpGrpcService`] at `endpoint`, e.g.
+    /// `http://127.0.0.1:50051`.
+    pub async fn connect(endpoint: impl Into<String>) -> Result<Self, GrpcTransportError> {
+        let channel = tonic::transport::Endpoint::from_shared(endpoint.into())?
+            .connect()
+            .await?;
+        Self::with_channel(channel).await
+    }
+
+    /// Open a session over `channel`, e.g. one with TLS or timeouts set up.
+    pub async fn with_channel(
+        channel: tonic::transport::Channel,
+    ) -> Result<Self, GrpcTransportError> {
+        let mut grpc = tonic::client::Grpc::new(channel);
+        grpc.ready().await?;
+        let (outbound, requests) = mpsc::channel(CHANNEL_SIZE);
+        let response = grpc
+            .streaming(
+                tonic::Request::new(ReceiverStream::new(requests)),
+                http::uri::PathAndQuery::from_static(CONNECT_PATH),
+                tonic_prost::ProstCodec::default(),
+            )
+            .await?;
+        Ok(Self::new(outbound, response.into_inner()))
+    }
+}
+
+impl<R: ServiceRole> Transport<R> for GrpcTransport<R> {
+    type Error = GrpcTransportError;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let outbound = self.outbound.clone();
+        async move {
+            let outbound = outbound.ok_or(GrpcTransportError::Closed)?;
+            let message = serde_json::to_string(&item)?;
+            outbound
+                .send(Frame { message })
+                .await
+                .map_err(|_| GrpcTransportError::Closed)
+        }
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        match self.inbound.message().await {
+            Ok(Some(frame)) => match serde_json::from_str(&frame.message) {
+                Ok(message) => Some(message),
+                Err(e) => {
+                    tracing::error!("Error parsing gRPC frame: {e}");
+                    self.close_reason =
+                        Some(CloseReason::protocol_error(e.to_string(), &frame.message));
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(status) => {
+                tracing::error!("Error reading from gRPC stream: {status}");
+                self.close_reason = Some(CloseReason::Failed(status.to_string()));
+                None
+            }
+        }
+    }
+
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.close_reason.clone()
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.outbound.take();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "server")]
+type ServiceFactory<S> = Arc<dyn Fn() -> Result<S, std::io::Error> + Send + Sync>;
+#[cfg(feature = "server")]
+type FrameStream = std::pin::Pin<Box<dyn futures::Stream<Item = Result<Frame, Status>> + Send>>;
+
+/// The `rmcp.v1.Mcp` gRPC service, serving a session of a new service from
+/// `service_factory` on each `Connect` call.
+///
+/// Add it to a [`tonic::transport::Server`] like any generated service.
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub struct McpGrpcService<S> {
+    service_factory: ServiceFactory<S>,
+    ct: CancellationToken,
+}
+
+#[cfg(feature = "server")]
+impl<S> Clone for McpGrpcService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            service_factory: self.service_factory.clone(),
+            ct: self.ct.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S> std::fmt::Debug for McpGrpcService<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpGrpcService")
+            .field("ct", &self.ct)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S: Service<RoleServer> + Send + 'static> McpGrpcService<S> {
+    pub fn new(
+        service_factory: impl Fn() -> Result<S, std::io::Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            service_factory: Arc::new(service_factory),
+            ct: CancellationToken::new(),
+        }
+    }
+
+    /// Sessions end when `ct` is cancelled.
+    pub fn with_cancellation_token(mut self, ct: CancellationToken) -> Self {
+        self.ct = ct;
+        self
+    }
+
+    fn connect(&self, inbound: Streaming<Frame>) -> Result<FrameStream, Status> {
+        let service = (self.service_factory)()
+            .map_err(|e| Status::internal(format!("failed to create service: {e}")))?;
+        let (outbound, responses) = mpsc::channel(CHANNEL_SIZE);
+        let transport = GrpcTransport::<RoleServer>::new(outbound, inbound);
+        let ct = self.ct.child_token();
+        tokio::spawn(async move {
+            match serve_server_with_ct(service, transport, ct).await {
+                Ok(service) => {
+                    let _ = service.waiting().await;
+                }
+                Err(e) => tracing::error!("Failed to create service: {e}"),
+            }
+        });
+        Ok(Box::pin(ReceiverStream::new(responses).map(Ok)))
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S, B> tower_service::Service<http::Request<B>> for McpGrpcService<S>
+where
+    S: Service<RoleServer> + Send + 'static,
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::Body>;
+    type Error = std::convert::Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        if req.uri().path() != CONNECT_PATH {
+            return Box::pin(async move {
+                Ok(
+                    Status::unimplemented(format!("unknown method {}", req.uri().path()))
+                        .into_http(),
+                )
+            });
+        }
+
+        struct ConnectSvc<S>(McpGrpcService<S>);
+
+        impl<S: Service<RoleServer> + Send + 'static> tonic::server::StreamingService<Frame>
+            for ConnectSvc<S>
+        {
+            type Response = Frame;
+            type ResponseStream = FrameStream;
+            type Future = BoxFuture<tonic::Response<FrameStream>, Status>;
+
+            fn call(&mut self, request: tonic::Request<Streaming<Frame>>) -> Self::Future {
+                let result = self
+                    .0
+                    .connect(request.into_inner())
+                    .map(tonic::Response::new);
+                Box::pin(std::future::ready(result))
+            }
+        }
+
+        let method = ConnectSvc(self.clone());
+        Box::pin(async move {
+            let mut grpc = tonic::server::Grpc::new(tonic_prost::ProstCodec::default());
+            Ok(grpc.streaming(method, req).await)
+        })
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S> NamedService for McpGrpcService<S> {
+    const NAME: &'static str = SERVICE_NAME;
+}
@@ -1,6 +1,142 @@
+use std::time::Duration;
+
+use super::async_rw::{AsyncRwTransport, Encoding, Framing};
+use crate::service::{QuitReason, RunningService, Service, ServiceRole};
+
 /// # StdIO Transport
 ///
 /// Create a pair of [`tokio::io::Stdin`] and [`tokio::io::Stdout`].
 pub fn stdio() -> (tokio::io::Stdin, tokio::io::Stdout) {
     (tokio::io::stdin(), tokio::io::stdout())
 }
+
+/// StdIO transport whose messages are delimited by `framing` rather than by
+/// newlines.
+///
+/// Clients spawning the server choose the framing; use [`Framing::detect`] to
+/// follow them.
+///
+/// ```rust,ignore
+/// let service = Counter::new().serve(framed_stdio(Framing::detect())).await?;
+/// ```
+pub fn framed_stdio<R: ServiceRole>(
+    framing: Framing,
+) -> AsyncRwTransport<R, tokio::io::Stdin, tokio::io::Stdout> {
+    AsyncRwTransport::with_framing(tokio::io::stdin(), tokio::io::stdout(), framing)
+}
+
+/// StdIO transport with the framing and [`Encoding`] the client spawning this
+/// process asked for, see [`Framing::detect`] and [`Encoding::detect`].
+///
+/// ```rust,ignore
+/// let service = Counter::new().serve(detect_stdio()).await?;
+/// ```
+pub fn detect_stdio<R: ServiceRole>() -> AsyncRwTransport<R, tokio::io::Stdin, tokio::io::Stdout> {
+    AsyncRwTransport::with_codec(
+        tokio::io::stdin(),
+        tokio::io::stdout(),
+        Framing::detect(),
+        Encoding::detect(),
+    )
+}
+
+/// Keeps a stdio server from outliving the process that spawned it.
+///
+/// A closed stdin already ends the session, but a parent that crashes can
+/// leave stdin open (for instance when a grandchild inherited it), and the
+/// server then lingers forever. `ParentWatch` notices the parent is gone by
+/// checking whether this process got re-parented, which is only supported on
+/// Unix; elsewhere only stdin closing is detected.
+///
+/// Create it early, e.g. first thing in `main`: the parent is read then, and
+/// a parent that dies before that goes unnoticed unless this process was
+/// handed to init.
+///
+/// ```rust,ignore
+/// let watch = ParentWatch::default();
+/// let service = Counter::new().serve(stdio()).await?;
+/// let quit_reason = watch.supervise(service).await;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParentWatch {
+    /// How often the parent process is checked.
+    pub poll_interval: Duration,
+    /// How long the session gets to close cleanly once the parent is gone.
+    pub grace_period: Duration,
+    /// The parent process id read when the watch was created, `None` where
+    /// it can't be watched.
+    pub parent: Option<u32>,
+}
+
+impl ParentWatch {
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+    pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+    /// The parent of this process, if it can be watched on this platform.
+    fn current_parent() -> Option<u32> {
+        #[cfg(unix)]
+        return Some(std::os::unix::process::parent_id());
+        #[cfg(not(unix))]
+        None
+    }
+
+    /// Resolves once the parent process has exited, right away if it already
+    /// had when the watch was created. Never resolves on platforms where that
+    /// can't be detected.
+    pub async fn parent_exited(&self) {
+        let Some(parent) = self.parent else {
+            return std::future::pending().await;
+        };
+        let mut interval = tokio::time::interval(self.poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            // orphans are handed to init
+            if parent == 1 || Self::current_parent() != Some(parent) {
+                tracing::info!(parent, "parent process exited");
+                return;
+            }
+        }
+    }
+
+    /// Wait for `service` to end, closing it if the parent process exits
+    /// first.
+    ///
+    /// If the session doesn't close within the grace period, this returns
+    /// anyway with [`QuitReason::Cancelled`] so the caller can exit.
+    pub async fn supervise<R, S>(&self, service: RunningService<R, S>) -> QuitReason
+    where
+        R: ServiceRole,
+        S: Service<R>,
+    {
+        let ct = service.cancellation_token();
+        let mut waiting = std::pin::pin!(service.waiting());
+        let result = tokio::select! {
+            result = &mut waiting => result,
+            _ = self.parent_exited() => {
+                ct.cancel();
+                match tokio::time::timeout(self.grace_period, waiting).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        tracing::warn!(
+                            grace_period = ?self.grace_period,
+                            "session did not close in time after the parent exited"
+                        );
+                        return QuitReason::Cancelled;
+                    }
+                }
+            }
+        };
+        result.unwrap_or_else(QuitReason::JoinError)
+    }
+}
+
+impl Default for ParentWatch {
+    fn default() -> Self {
+        Self {
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+            grace_period: Self::DEFAULT_GRACE_PERIOD,
+            parent: Self::current_parent(),
+        }
+    }
+}
@@ -0,0 +1,527 @@
+//! Experimental MCP over QUIC, with [`quinn`], for latency sensitive
+//! deployments.
+//!
+//! One QUIC connection is one MCP session, made of
+//!
+//! - a unidirectional *control stream* each way, carrying notifications and
+//!   the initialization handshake in order, each message as its length (a
+//!   big-endian `u32`) followed by its JSON;
+//! - a bidirectional stream for every later request, carrying the request
+//!   one way and its response the other, each as the whole stream.
+//!
+//! So a slow request never holds up the others, and a lost packet only
+//! stalls the stream it belongs to. Peers don't accept request streams
+//! before the client's `notifications/initialized`, which always comes
+//! first.
+//!
+//! A client that connected to the server before, through the same
+//! [`quinn::Endpoint`], sends its `initialize` request as 0-RTT data, saving
+//! a round trip. Nothing else is sent before the handshake completes, so a
+//! replayed packet can at most start a session nobody continues.
+//!
+//! This transport is not part of the MCP specification: its wire format may
+//! change in any release.
+//!
+//! ```rust,ignore
+//! let server = quinn::Endpoint::server(server_config, "0.0.0.0:4433".parse()?)?;
+//! tokio::spawn(async move {
+//!     QuicServer::new(|| Ok(Counter::new())).serve(&server).await
+//! });
+//!
+//! let mut endpoint = quinn::Endpoint::client("0.0.0.0:0".parse()?)?;
+//! endpoint.set_default_client_config(client_config);
+//! let transport = QuicClientTransport::connect(&endpoint, address, "localhost").await?;
+//! let client = ().serve(transport).await?;
+//! ```
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use quinn::{Connection, ConnectionError, RecvStream, SendStream, WriteError};
+use thiserror::Error;
+use tokio::sync::{mpsc, watch};
+
+use super::Transport;
+#[cfg(feature = "client")]
+use crate::service::RoleClient;
+#[cfg(feature = "server")]
+use crate::service::{RoleServer, Service, serve_server_with_ct};
+use crate::{
+    model::{JsonRpcError, JsonRpcMessage, JsonRpcResponse, RequestId},
+    service::{CloseReason, RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage},
+};
+
+/// Largest message a peer sends or accepts, in bytes.
+pub const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+const CHANNEL_SIZE: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum QuicTransportError {
+    #[error("connect error {0}")]
+    Connect(#[from] quinn::ConnectError),
+    #[error("connection error {0}")]
+    Connection(#[from] ConnectionError),
+    #[error("write error {0}")]
+    Write(#[from] WriteError),
+    #[error("stream closed {0}")]
+    ClosedStream(#[from] quinn::ClosedStream),
+    #[error("serde error {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("message of {0} bytes is larger than {MAX_MESSAGE_SIZE}")]
+    TooLarge(usize),
+}
+
+/// The sending half of the control stream.
+struct Control {
+    stream: SendStream,
+    /// Frames sent as 0-RTT data, until the server accepted or rejected it.
+    early_frames: Option<Vec<Vec<u8>>>,
+}
+
+impl Control {
+    async fn write(&mut self, frame: Vec<u8>) -> Result<(), QuicTransportError> {
+        match self.stream.write_all(&frame).await {
+            // sent again once the handshake completes
+            Err(WriteError::ZeroRttRejected) if self.early_frames.is_some() => {}
+            result => result?,
+        }
+        if let Some(early_frames) = &mut self.early_frames {
+            early_frames.push(frame);
+        }
+        Ok(())
+    }
+
+    /// Send the 0-RTT frames again on a new stream if the server rejected
+    /// them.
+    async fn settle(
+        control: Arc<tokio::sync::Mutex<Self>>,
+        connection: Connection,
+        accepted: quinn::ZeroRttAccepted,
+    ) -> Result<(), QuicTransportError> {
+        let accepted = accepted.await;
+        let mut control = control.lock().await;
+        let Some(frames) = control.early_frames.take() else {
+            return Ok(());
+        };
+        if accepted {
+            return Ok(());
+        }
+        tracing::debug!("0-RTT data rejected, sending {} frames again", frames.len());
+        control.stream = connection.open_uni().await?;
+        for frame in frames {
+            control.stream.write_all(&frame).await?;
+        }
+        Ok(())
+    }
+}
+
+type Responders = Arc<Mutex<HashMap<RequestId, SendStream>>>;
+type Inbound<R> = Result<RxJsonRpcMessage<R>, CloseReason>;
+
+/// One end of an MCP session over a QUIC connection.
+pub struct QuicTransport<R: ServiceRole> {
+    connection: Connection,
+    control: Arc<tokio::sync::Mutex<Control>>,
+    /// Where to send the responses of requests received on their own stream.
+    responders: Responders,
+    inbound_tx: mpsc::Sender<Inbound<R>>,
+    inbound: mpsc::Receiver<Inbound<R>>,
+    /// Requests go on the control stream until the client has sent its
+    /// first notification.
+    handshaking: bool,
+    zero_rtt: bool,
+    close_reason: Option<CloseReason>,
+}
+
+impl<R: ServiceRole> std::fmt::Debug for QuicTransport<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuicTransport")
+            .field("remote_address", &self.connection.remote_address())
+            .field("handshaking", &self.handshaking)
+            .field("zero_rtt", &self.zero_rtt)
+            .field("close_reason", &self.close_reason)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "client")]
+#[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+pub type QuicClientTransport = QuicTransport<RoleClient>;
+
+impl<R: ServiceRole> QuicTransport<R> {
+    /// Run a session over an established `connection`.
+    pub async fn new(connection: Connection) -> Result<Self, QuicTransportError> {
+        Self::start(connection, None).await
+    }
+
+    async fn start(
+        connection: Connection,
+        zero_rtt: Option<quinn::ZeroRttAccepted>,
+    ) -> Result<Self, QuicTransportError> {
+        let control = Arc::new(tokio::sync::Mutex::new(Control {
+            stream: connection.open_uni().await?,
+            early_frames: zero_rtt.as_ref().map(|_| Vec::new()),
+        }));
+        let is_zero_rtt = zero_rtt.is_some();
+        if let Some(accepted) = zero_rtt {
+            let (control, connection) = (control.clone(), connection.clone());
+            tokio::spawn(async move {
+                if let Err(e) = Control::settle(control, connection, accepted).await {
+                    tracing::error!("Error sending 0-RTT data again: {e}");
+                }
+            });
+        }
+        let responders = Responders::default();
+        let (inbound_tx, inbound) = mpsc::channel(CHANNEL_SIZE);
+        let (initialized_tx, initialized) = watch::channel(R::IS_CLIENT);
+        tokio::spawn(read_control::<R>(
+            connection.clone(),
+            inbound_tx.clone(),
+            initialized_tx,
+        ));
+        tokio::spawn(accept_requests::<R>(
+            connection.clone(),
+            responders.clone(),
+            inbound_tx.clone(),
+            initialized,
+        ));
+        Ok(Self {
+            connection,
+            control,
+            responders,
+            inbound_tx,
+            inbound,
+            handshaking: R::IS_CLIENT,
+            zero_rtt: is_zero_rtt,
+            close_reason: None,
+        })
+    }
+
+    /// Whether the session started with 0-RTT data. The server may still
+    /// have rejected it, in which case it was sent again.
+    pub fn is_zero_rtt(&self) -> bool {
+        self.zero_rtt
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+}
+
+#[cfg(feature = "client")]
+#[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+impl QuicTransport<RoleClient> {
+    /// Connect to the server at `address` through `endpoint`, with its
+    /// default client config.
+    ///
+    /// If the endpoint connected to `server_name` before, the session starts
+    /// with 0-RTT data.
+    pub async fn connect(
+        endpoint: &quinn::Endpoint,
+        address: std::net::SocketAddr,
+        server_name: &str,
+    ) -> Result<Self, QuicTransportError> {
+        match endpoint.connect(address, server_name)?.into_0rtt() {
+            Ok((connection, accepted)) => Self::start(connection, Some(accepted)).await,
+            Err(connecting) => Self::start(connecting.await?, None).await,
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+impl QuicTransport<RoleServer> {
+    /// Accept an incoming connection, answering before its handshake
+    /// completes (0.5-RTT) so 0-RTT clients get their `initialize` response
+    /// as soon as possible.
+    pub async fn accept(incoming: quinn::Incoming) -> Result<Self, QuicTransportError> {
+        let connection = match incoming.accept()?.into_0rtt() {
+            Ok((connection, _)) => connection,
+            Err(connecting) => connecting.await?,
+        };
+        Self::start(connection, None).await
+    }
+}
+
+enum Route {
+    Control,
+    Reply(SendStream),
+    Stream,
+}
+
+impl<R: ServiceRole> Transport<R> for QuicTransport<R> {
+    type Error = QuicTransportError;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let route = match &item {
+            JsonRpcMessage::Request(_) if !self.handshaking => Route::Stream,
+            JsonRpcMessage::Request(_) => Route::Control,
+            JsonRpcMessage::Notification(_) => {
+                self.handshaking = false;
+                Route::Control
+            }
+            JsonRpcMessage::Response(JsonRpcResponse { id, .. })
+            | JsonRpcMessage::Error(JsonRpcError { id, .. }) => self
+                .responders
+                .lock()
+                .expect("responders poisoned")
+                .remove(id)
+                .map_or(Route::Control, Route::Reply),
+        };
+        let connection = self.connection.clone();
+        let control = self.control.clone();
+        let inbound = self.inbound_tx.clone();
+        async move {
+            let message = serde_json::to_vec(&item)?;
+            if message.len() > MAX_MESSAGE_SIZE {
+                return Err(QuicTransportError::TooLarge(message.len()));
+            }
+            match route {
+                Route::Control => {
+                    let mut frame = Vec::with_capacity(4 + message.len());
+                    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+                    frame.extend_from_slice(&message);
+                    control.lock().await.write(frame).await
+                }
+                Route::Reply(mut stream) => {
+                    stream.write_all(&message).await?;
+                    Ok(stream.finish()?)
+                }
+                Route::Stream => {
+                    let (mut stream, mut response) = connection.open_bi().await?;
+                    stream.write_all(&message).await?;
+                    stream.finish()?;
+                    tokio::spawn(async move {
+                        match response.read_to_end(MAX_MESSAGE_SIZE).await {
+                            Ok(message) => {
+                                let _ = inbound.send(parse::<R>(&message)).await;
+                            }
+                            Err(e) => tracing::debug!("Error reading QUIC response stream: {e}"),
+                        }
+                    });
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        let inbound = tokio::select! {
+            biased;
+            inbound = self.inbound.recv() => inbound?,
+            error = self.connection.closed() => {
+                if !matches!(
+                    error,
+                    ConnectionError::ApplicationClosed(_) | ConnectionError::LocallyClosed
+                ) {
+                    tracing::error!("QUIC connection lost: {error}");
+                    self.close_reason = Some(CloseReason::Failed(error.to_string()));
+                }
+                return None;
+            }
+        };
+        match inbound {
+            Ok(message) => Some(message),
+            Err(reason) => {
+                self.close_reason = Some(reason);
+                None
+            }
+        }
+    }
+
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.close_reason.clone()
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        let _ = self.control.lock().await.stream.finish();
+        self.connection.close(0u32.into(), b"");
+        Ok(())
+    }
+}
+
+impl<R: ServiceRole> Drop for QuicTransport<R> {
+    fn drop(&mut self) {
+        self.connection.close(0u32.into(), b"");
+    }
+}
+
+fn parse<R: ServiceRole>(message: &[u8]) -> Inbound<R> {
+    serde_json::from_slice(message).map_err(|e| {
+        tracing::error!("Error parsing QUIC message: {e}");
+        CloseReason::protocol_error(e.to_string(), &String::from_utf8_lossy(message))
+    })
+}
+
+async fn read_frame(stream: &mut RecvStream) -> Result<Option<Vec<u8>>, String> {
+    let mut length = [0; 4];
+    match stream.read_exact(&mut length).await {
+        Ok(()) => {}
+        Err(quinn::ReadExactError::FinishedEarly(0)) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    }
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_MESSAGE_SIZE {
+        return Err(format!(
+            "message of {length} bytes is larger than {MAX_MESSAGE_SIZE}"
+        ));
+    }
+    let mut message = vec![0; length];
+    stream
+        .read_exact(&mut message)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Some(message))
+}
+
+/// Read the peer's control stream, marking the session initialized at the
+/// client's first notification.
+async fn read_control<R: ServiceRole>(
+    connection: Connection,
+    inbound: mpsc::Sender<Inbound<R>>,
+    initialized: watch::Sender<bool>,
+) {
+    let mut stream = match connection.accept_uni().await {
+        Ok(stream) => stream,
+        Err(e) => {
+            tracing::debug!("No QUIC control stream: {e}");
+            return;
+        }
+    };
+    loop {
+        let message = match read_frame(&mut stream).await {
+            Ok(Some(message)) => parse::<R>(&message),
+            Ok(None) => return,
+            Err(e) => {
+                tracing::error!("Error reading QUIC control stream: {e}");
+                let reason = CloseReason::Failed(e);
+                let _ = inbound.send(Err(reason)).await;
+                return;
+            }
+        };
+        let is_notification = matches!(message, Ok(JsonRpcMessage::Notification(_)));
+        if inbound.send(message).await.is_err() {
+            return;
+        }
+        if is_notification {
+            initialized.send_replace(true);
+        }
+    }
+}
+
+/// Accept the peer's request streams, once the session is initialized.
+async fn accept_requests<R: ServiceRole>(
+    connection: Connection,
+    responders: Responders,
+    inbound: mpsc::Sender<Inbound<R>>,
+    mut initialized: watch::Receiver<bool>,
+) {
+    if initialized
+        .wait_for(|initialized| *initialized)
+        .await
+        .is_err()
+    {
+        return;
+    }
+    while let Ok((stream, mut request)) = connection.accept_bi().await {
+        let (responders, inbound) = (responders.clone(), inbound.clone());
+        tokio::spawn(async move {
+            let message = match request.read_to_end(MAX_MESSAGE_SIZE).await {
+                Ok(message) => parse::<R>(&message),
+                Err(e) => {
+                    tracing::debug!("Error reading QUIC request stream: {e}");
+                    return;
+                }
+            };
+            if let Ok(JsonRpcMessage::Request(request)) = &message {
+                responders
+                    .lock()
+                    .expect("responders poisoned")
+                    .insert(request.id.clone(), stream);
+            }
+            let _ = inbound.send(message).await;
+        });
+    }
+}
+
+#[cfg(feature = "server")]
+type ServiceFactory<S> = Arc<dyn Fn() -> Result<S, std::io::Error> + Send + Sync>;
+
+/// Serves a session of a new service from `service_factory` on every
+/// connection of a [`quinn::Endpoint`].
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub struct QuicServer<S> {
+    service_factory: ServiceFactory<S>,
+    ct: tokio_util::sync::CancellationToken,
+}
+
+#[cfg(feature = "server")]
+impl<S> std::fmt::Debug for QuicServer<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuicServer")
+            .field("ct", &self.ct)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "server")]
+impl<S: Service<RoleServer> + Send + 'static> QuicServer<S> {
+    pub fn new(
+        service_factory: impl Fn() -> Result<S, std::io::Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            service_factory: Arc::new(service_factory),
+            ct: tokio_util::sync::CancellationToken::new(),
+        }
+    }
+
+    /// Stop accepting connections and end all sessions when `ct` is
+    /// cancelled.
+    pub fn with_cancellation_token(mut self, ct: tokio_util::sync::CancellationToken) -> Self {
+        self.ct = ct;
+        self
+    }
+
+    /// Accept connections until `endpoint` is closed or the cancellation
+    /// token is cancelled.
+    pub async fn serve(&self, endpoint: &quinn::Endpoint) {
+        loop {
+            let incoming = tokio::select! {
+                incoming = endpoint.accept() => incoming,
+                _ = self.ct.cancelled() => None,
+            };
+            let Some(incoming) = incoming else {
+                return;
+            };
+            let service = match (self.service_factory)() {
+                Ok(service) => service,
+                Err(e) => {
+                    tracing::error!("Failed to create service: {e}");
+                    incoming.refuse();
+                    continue;
+                }
+            };
+            let ct = self.ct.child_token();
+            tokio::spawn(async move {
+                let transport = match QuicTransport::accept(incoming).await {
+                    Ok(transport) => transport,
+                    Err(e) => {
+                        tracing::error!("Failed to accept QUIC connection: {e}");
+                        return;
+                    }
+                };
+                match serve_server_with_ct(service, transport, ct).await {
+                    Ok(service) => {
+                        let _ = service.waiting().await;
+                    }
+                    Err(e) => tracing::error!("Failed to create service: {e}"),
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Start a stdio server on another host through the system `ssh` client,
+//! so remote servers don't need to be exposed over HTTP.
+//!
+//! ```rust,ignore
+//! let transport = SshCommand::new("deploy@build-box", "mcp-server")
+//!     .arg("--root")
+//!     .arg("/srv/builds")
+//!     .identity_file("/home/me/.ssh/mcp_ed25519")
+//!     .spawn()?;
+//! let client = ().serve(transport).await?;
+//! ```
+//!
+//! `ssh` runs without a terminal and in batch mode, since its stdin and
+//! stdout carry the messages: authentication can't prompt, so it needs a key
+//! (or an agent), and the host key must be known already unless
+//! [`HostKeyCheck::AcceptNew`] is set.
+use std::{path::PathBuf, time::Duration};
+
+use super::{
+    TokioChildProcess,
+    async_rw::{ENCODING_ENV, Encoding, FRAMING_ENV, Framing},
+    child_process::TokioChildProcessBuilder,
+};
+
+/// How `ssh` checks the key of the remote host, its `StrictHostKeyChecking`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HostKeyCheck {
+    /// Only connect to hosts whose key is in the known hosts file.
+    #[default]
+    Strict,
+    /// Trust the key of hosts seen for the first time, adding it to the
+    /// known hosts file, but refuse keys that changed.
+    AcceptNew,
+}
+
+impl HostKeyCheck {
+    fn as_ssh_option(self) -> &'static str {
+        match self {
+            Self::Strict => "yes",
+            Self::AcceptNew => "accept-new",
+        }
+    }
+}
+
+/// Keep-alive probes sent by `ssh` through the encrypted channel, so broken
+/// connections end the session instead of hanging it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepAlive {
+    /// `ServerAliveInterval`, rounded up to whole seconds.
+    pub interval: Duration,
+    /// `ServerAliveCountMax`: unanswered probes before disconnecting.
+    pub max_missed: u32,
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            max_missed: 3,
+        }
+    }
+}
+
+/// A stdio server command to run on a remote host over SSH.
+#[derive(Debug, Clone)]
+pub struct SshCommand {
+    ssh: PathBuf,
+    destination: String,
+    port: Option<u16>,
+    identity_file: Option<PathBuf>,
+    known_hosts: Option<PathBuf>,
+    host_key_check: HostKeyCheck,
+    keep_alive: Option<KeepAlive>,
+    connect_timeout: Option<Duration>,
+    options: Vec<(String, String)>,
+    program: String,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    framing: Framing,
+    encoding: Encoding,
+}
+
+impl SshCommand {
+    /// Run `program` on `destination`, `host` or `user@host` or a `Host`
+    /// of the ssh config.
+    pub fn new(destination: impl Into<String>, program: impl Into<String>) -> Self {
+        Self {
+            ssh: PathBuf::from("ssh"),
+            destination: destination.into(),
+            port: None,
+            identity_file: None,
+            known_hosts: None,
+            host_key_check: HostKeyCheck::default(),
+            keep_alive: Some(KeepAlive::default()),
+            connect_timeout: None,
+            options: Vec::new(),
+            program: program.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            framing: Framing::default(),
+            encoding: Encoding::default(),
+        }
+    }
+
+    /// The `ssh` executable to run, `ssh` from the `PATH` by default.
+    pub fn ssh_program(mut self, ssh: impl Into<PathBuf>) -> Self {
+        self.ssh = ssh.into();
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Authenticate with this private key only, rather than every key of the
+    /// agent and the ssh config.
+    pub fn identity_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.identity_file = Some(path.into());
+        self
+    }
+
+    /// Check the host key against this file instead of `~/.ssh/known_hosts`,
+    /// e.g. one holding only the keys of the MCP hosts.
+    pub fn known_hosts(mut self, path: impl Into<PathBuf>) -> Self {
+        self.known_hosts = Some(path.into());
+        self
+    }
+
+    pub fn host_key_check(mut self, check: HostKeyCheck) -> Self {
+        self.host_key_check = check;
+        self
+    }
+
+    /// Every 15 seconds, up to 3 missed, by default; `None` leaves it to the
+    /// ssh config.
+    pub fn keep_alive(mut self, keep_alive: Option<KeepAlive>) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Any other `-o key=value` option of `ssh`.
+    pub fn option(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I>(mut self, args: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set an environment variable of the remote command. Variables of this
+    /// process are not forwarded.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Like [`TokioChildProcessBuilder::framing`], telling the remote
+    /// command through its environment.
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Like [`TokioChildProcessBuilder::encoding`], telling the remote
+    /// command through its environment.
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// The command line run by the remote shell, its words quoted.
+    pub fn remote_command(&self) -> String {
+        let mut env = self.env.clone();
+        if self.framing != Framing::default() {
+            env.push((FRAMING_ENV.to_owned(), self.framing.as_str().to_owned()));
+        }
+        if self.encoding.is_binary() {
+            env.push((ENCODING_ENV.to_owned(), self.encoding.as_str().to_owned()));
+        }
+        let mut words = Vec::new();
+        if !env.is_empty() {
+            words.push("env".to_owned());
+            words.extend(
+                env.iter()
+                    .map(|(key, value)| shell_quote(&format!("{key}={value}"))),
+            );
+        }
+        words.push(shell_quote(&self.program));
+        words.extend(self.args.iter().map(|arg| shell_quote(arg)));
+        words.join(" ")
+    }
+
+    /// The local `ssh` command.
+    pub fn command(&self) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(&self.ssh);
+        // no terminal nor escape character: stdio carries the messages
+        command.args(["-T", "-e", "none", "-o", "BatchMode=yes"]);
+        command.arg("-o").arg(format!(
+            "StrictHostKeyChecking={}",
+            self.host_key_check.as_ssh_option()
+        ));
+        if let Some(known_hosts) = &self.known_hosts {
+            command
+                .arg("-o")
+                .arg(format!("UserKnownHostsFile={}", known_hosts.display()));
+        }
+        if let Some(keep_alive) = self.keep_alive {
+            let interval = keep_alive.interval.as_secs_f64().ceil().max(1.0) as u64;
+            command
+                .arg("-o")
+                .arg(format!("ServerAliveInterval={interval}"))
+                .arg("-o")
+                .arg(format!("ServerAliveCountMax={}", keep_alive.max_missed));
+        }
+        if let Some(timeout) = self.connect_timeout {
+            let timeout = timeout.as_secs_f64().ceil().max(1.0) as u64;
+            command.arg("-o").arg(format!("ConnectTimeout={timeout}"));
+        }
+        for (key, value) in &self.options {
+            command.arg("-o").arg(format!("{key}={value}"));
+        }
+        if let Some(port) = self.port {
+            command.arg("-p").arg(port.to_string());
+        }
+        if let Some(identity_file) = &self.identity_file {
+            command
+                .arg("-i")
+                .arg(identity_file)
+                .args(["-o", "IdentitiesOnly=yes"]);
+        }
+        command
+            .arg("--")
+            .arg(&self.destination)
+            .arg(self.remote_command());
+        command
+    }
+
+    /// A builder of the child process, e.g. to capture the stderr of `ssh`
+    /// and the remote command.
+    pub fn builder(self) -> TokioChildProcessBuilder {
+        TokioChildProcess::builder(self.command())
+            .framing(self.framing)
+            .encoding(self.encoding)
+    }
+
+    pub fn spawn(self) -> std::io::Result<TokioChildProcess> {
+        let (process, _stderr) = self.builder().spawn()?;
+        Ok(process)
+    }
+}
+
+/// Quote `word` for a POSIX shell, if it needs it.
+fn shell_quote(word: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "_-.,:/@%+=".contains(c);
+    if !word.is_empty() && word.chars().all(plain) {
+        word.to_owned()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ssh_args(command: &SshCommand) -> Vec<String> {
+        command
+            .command()
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_ssh_command() {
+        let command = SshCommand::new("deploy@build-box", "mcp-server")
+            .port(2222)
+            .identity_file("/keys/mcp")
+            .known_hosts("/etc/mcp/known_hosts")
+            .arg("--root")
+            .arg("/srv/my builds")
+            .env("RUST_LOG", "debug")
+            .framing(Framing::ContentLength);
+        let args = ssh_args(&command);
+        for option in [
+            "BatchMode=yes",
+            "StrictHostKeyChecking=yes",
+            "UserKnownHostsFile=/etc/mcp/known_hosts",
+            "ServerAliveInterval=15",
+            "ServerAliveCountMax=3",
+            "IdentitiesOnly=yes",
+        ] {
+            assert!(args.iter().any(|arg| arg == option), "{option} in {args:?}");
+        }
+        assert_eq!(
+            args[args.len() - 3..],
+            [
+                "--",
+                "deploy@build-box",
+                "env RUST_LOG=debug MCP_STDIO_FRAMING=content-length mcp-server --root '/srv/my builds'",
+            ]
+        );
+
+        let command = SshCommand::new("build-box", "mcp-server")
+            .host_key_check(HostKeyCheck::AcceptNew)
+            .keep_alive(None);
+        let args = ssh_args(&command);
+        assert!(
+            args.iter()
+                .any(|arg| arg == "StrictHostKeyChecking=accept-new")
+        );
+        assert!(!args.iter().any(|arg| arg.starts_with("ServerAlive")));
+        assert_eq!(args.last().map(String::as_str), Some("mcp-server"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shell_quote() {
+        let words = ["plain", "", "two words", "it's", "$HOME", "a'b\"c\\d", "*"];
+        let script = std::iter::once("printf '%s\\n'".to_owned())
+            .chain(words.iter().map(|word| shell_quote(word)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let output = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .output()
+            .await
+            .unwrap();
+        let printed = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(printed.lines().collect::<Vec<_>>(), words);
+    }
+}
@@ -1,9 +1,18 @@
-use std::{borrow::Cow, sync::Arc, time::Duration};
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
 
 use futures::{Stream, StreamExt, future::BoxFuture, stream::BoxStream};
 pub use sse_stream::Error as SseError;
 use sse_stream::Sse;
 use thiserror::Error;
+use tokio::{task::JoinSet, time::Instant};
 use tokio_util::sync::CancellationToken;
 use tracing::debug;
 
@@ -11,9 +20,10 @@ use super::common::client_side_sse::{ExponentialBackoff, SseRetryPolicy, SseStre
 use crate::{
     RoleClient,
     model::{ClientJsonRpcMessage, ServerJsonRpcMessage},
+    service::{CloseReason, ConnectionState},
     transport::{
         common::client_side_sse::SseAutoReconnectStream,
-        worker::{Worker, WorkerQuitReason, WorkerSendRequest, WorkerTransport},
+        worker::{Worker, WorkerContext, WorkerQuitReason, WorkerSendRequest, WorkerTransport},
     },
 };
 
@@ -56,6 +66,12 @@ pub enum StreamableHttpError<E: std::error::Error + Send + Sync + 'static> {
     Auth(#[from] crate::transport::auth::AuthError),
     #[error("Auth required")]
     AuthRequired(AuthRequiredError),
+    #[error("Request queue full while reconnecting")]
+    RequestQueueFull,
+    #[error("Request deadline passed while reconnecting")]
+    RequestQueueTimeout,
+    #[error("Gave up reconnecting")]
+    ReconnectFailed,
 }
 
 #[derive(Debug, Clone, Error)]
@@ -175,6 +191,12 @@ pub trait StreamableHttpClient: Clone + Send + 'static {
         >,
     > + Send
     + '_;
+    /// Whether `error`, returned by [`post_message`](Self::post_message),
+    /// means the server can't be reached for now, so the message is worth
+    /// sending again later. See [`RequestQueueConfig`].
+    fn is_transient(&self, error: &StreamableHttpError<Self::Error>) -> bool {
+        matches!(error, StreamableHttpError::Io(_))
+    }
 }
 
 pub struct RetryConfig {
@@ -266,6 +288,63 @@ impl<C: StreamableHttpClient> StreamableHttpClientWorker<C> {
         }
         Ok(())
     }
+
+    /// Hand the response to a POST to the handler, or keep reading it from
+    /// its SSE stream.
+    ///
+    /// Takes `&mut self` so the worker future stays `Send` without requiring
+    /// the client to be `Sync`.
+    async fn accept_post_response(
+        &mut self,
+        response: StreamableHttpPostResponse,
+        session_id: Option<&Arc<str>>,
+        context: &mut WorkerContext<Self>,
+        streams: &mut JoinSet<Result<(), StreamableHttpError<C::Error>>>,
+        sse_worker_tx: &tokio::sync::mpsc::Sender<ServerJsonRpcMessage>,
+        ct: &CancellationToken,
+    ) -> Result<(), WorkerQuitReason<StreamableHttpError<C::Error>>> {
+        match response {
+            StreamableHttpPostResponse::Accepted => {
+                tracing::trace!("client message accepted");
+            }
+            StreamableHttpPostResponse::Json(message, ..) => {
+                context.send_to_handler(message).await?;
+            }
+            StreamableHttpPostResponse::Sse(stream, ..) => {
+                if let Some(session_id) = session_id {
+                    let sse_stream = SseAutoReconnectStream::new(
+                        stream,
+                        StreamableHttpClientReconnect {
+                            client: self.client.clone(),
+                            session_id: session_id.clone(),
+                            uri: self.config.uri.clone(),
+                            auth_header: self.config.auth_header.clone(),
+                        },
+                        self.config.retry_config.clone(),
+                    );
+                    streams.spawn(Self::execute_sse_stream(
+                        sse_stream,
+                        sse_worker_tx.clone(),
+                        true,
+                        ct.child_token(),
+                    ));
+                } else {
+                    let sse_stream = SseAutoReconnectStream::never_reconnect(
+                        stream,
+                        StreamableHttpError::<C::Error>::UnexpectedEndOfStream,
+                    );
+                    streams.spawn(Self::execute_sse_stream(
+                        sse_stream,
+                        sse_worker_tx.clone(),
+                        true,
+                        ct.child_token(),
+                    ));
+                }
+                tracing::trace!("got new sse stream");
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<C: StreamableHttpClient> Worker for StreamableHttpClientWorker<C> {
@@ -283,8 +362,22 @@ impl<C: StreamableHttpClient> Worker for StreamableHttpClientWorker<C> {
             channel_buffer_capacity: self.config.channel_buffer_capacity,
         }
     }
+    fn close_reason(error: &Self::Error) -> Option<CloseReason> {
+        match error {
+            #[cfg(feature = "auth")]
+            StreamableHttpError::Auth(_) => Some(CloseReason::AuthRevoked(error.to_string())),
+            StreamableHttpError::AuthRequired(required) => Some(CloseReason::AuthRevoked(
+                required.www_authenticate_header.clone(),
+            )),
+            StreamableHttpError::Deserialize(e) => Some(CloseReason::ProtocolError {
+                message: e.to_string(),
+                excerpt: String::new(),
+            }),
+            _ => None,
+        }
+    }
     async fn run(
-        self,
+        mut self,
         mut context: super::worker::WorkerContext<Self>,
     ) -> Result<(), WorkerQuitReason<Self::Error>> {
         let channel_buffer_capacity = self.config.channel_buffer_capacity;
@@ -303,7 +396,7 @@ impl<C: StreamableHttpClient> Worker for StreamableHttpClientWorker<C> {
                 config.uri.clone(),
                 initialize_request,
                 None,
-                self.config.auth_header,
+                config.auth_header.clone(),
             )
             .await
         {
@@ -333,6 +426,9 @@ impl<C: StreamableHttpClient> Worker for StreamableHttpClientWorker<C> {
             }
             None
         };
+        if let Some(session_id) = &session_id {
+            context.set_session_id(session_id.clone());
+        }
         // Store session info for cleanup when run() exits (not spawned, so cleanup completes before close() returns)
         let session_cleanup_info = session_id.as_ref().map(|sid| {
             (
@@ -367,8 +463,13 @@ impl<C: StreamableHttpClient> Worker for StreamableHttpClientWorker<C> {
             ClientMessage(WorkerSendRequest<W>),
             ServerMessage(ServerJsonRpcMessage),
             StreamResult(Result<(), StreamableHttpError<E>>),
+            RetryQueued,
         }
-        let mut streams = tokio::task::JoinSet::new();
+        let mut streams = JoinSet::new();
+        let mut queue = config
+            .request_queue
+            .clone()
+            .map(|queue_config| RequestQueue::new(queue_config, config.retry_config.clone()));
         if let Some(session_id) = &session_id {
             let client = self.client.clone();
             let uri = config.uri.clone();
@@ -418,6 +519,14 @@ impl<C: StreamableHttpClient> Worker for StreamableHttpClientWorker<C> {
         }
         // Main event loop - capture exit reason so we can do cleanup before returning
         let loop_result: Result<(), WorkerQuitReason<Self::Error>> = 'main_loop: loop {
+            let wake_at = queue.as_ref().and_then(RequestQueue::wake_at);
+            if let Some(queue) = &queue {
+                context.set_connection_state(if queue.is_reconnecting() {
+                    ConnectionState::Reconnecting
+                } else {
+                    ConnectionState::Ready
+                });
+            }
             let event = tokio::select! {
                 _ = transport_task_ct.cancelled() => {
                     tracing::debug!("cancelled");
@@ -446,10 +555,18 @@ impl<C: StreamableHttpClient> Worker for StreamableHttpClientWorker<C> {
                         }
                     }
                 }
+                _ = tokio::time::sleep_until(wake_at.unwrap_or_else(Instant::now)), if wake_at.is_some() => {
+                    Event::RetryQueued
+                }
             };
             match event {
                 Event::ClientMessage(send_request) => {
+                    if let Some(queue) = queue.as_mut().filter(|queue| queue.is_reconnecting()) {
+                        queue.push(send_request);
+                        continue;
+                    }
                     let WorkerSendRequest { message, responder } = send_request;
+                    let retained = queue.is_some().then(|| message.clone());
                     let response = self
                         .client
                         .post_message(
@@ -459,52 +576,68 @@ impl<C: StreamableHttpClient> Worker for StreamableHttpClientWorker<C> {
                             config.auth_header.clone(),
                         )
                         .await;
-                    let send_result = match response {
-                        Err(e) => Err(e),
-                        Ok(StreamableHttpPostResponse::Accepted) => {
-                            tracing::trace!("client message accepted");
-                            Ok(())
-                        }
-                        Ok(StreamableHttpPostResponse::Json(message, ..)) => {
-                            context.send_to_handler(message).await?;
-                            Ok(())
-                        }
-                        Ok(StreamableHttpPostResponse::Sse(stream, ..)) => {
-                            if let Some(session_id) = &session_id {
-                                let sse_stream = SseAutoReconnectStream::new(
-                                    stream,
-                                    StreamableHttpClientReconnect {
-                                        client: self.client.clone(),
-                                        session_id: session_id.clone(),
-                                        uri: config.uri.clone(),
-                                        auth_header: config.auth_header.clone(),
-                                    },
-                                    self.config.retry_config.clone(),
-                                );
-                                streams.spawn(Self::execute_sse_stream(
-                                    sse_stream,
-                                    sse_worker_tx.clone(),
-                                    true,
-                                    transport_task_ct.child_token(),
-                                ));
-                            } else {
-                                let sse_stream = SseAutoReconnectStream::never_reconnect(
-                                    stream,
-                                    StreamableHttpError::<C::Error>::UnexpectedEndOfStream,
-                                );
-                                streams.spawn(Self::execute_sse_stream(
-                                    sse_stream,
-                                    sse_worker_tx.clone(),
-                                    true,
-                                    transport_task_ct.child_token(),
-                                ));
+                    let send_result = match (response, retained) {
+                        (Err(e), Some(message)) if self.client.is_transient(&e) => {
+                            tracing::warn!("server unreachable, queueing messages: {e}");
+                            if let Some(queue) = &mut queue {
+                                queue.start_reconnecting(WorkerSendRequest { message, responder });
                             }
-                            tracing::trace!("got new sse stream");
+                            continue;
+                        }
+                        (Err(e), _) => Err(e),
+                        (Ok(response), _) => {
+                            self.accept_post_response(
+                                response,
+                                session_id.as_ref(),
+                                &mut context,
+                                &mut streams,
+                                &sse_worker_tx,
+                                &transport_task_ct,
+                            )
+                            .await?;
                             Ok(())
                         }
                     };
                     let _ = responder.send(send_result);
                 }
+                Event::RetryQueued => {
+                    let Some(queue) = &mut queue else {
+                        continue;
+                    };
+                    // flush in order, until the server is gone again
+                    while let Some(queued) = queue.pop_due() {
+                        let response = self
+                            .client
+                            .post_message(
+                                config.uri.clone(),
+                                queued.request.message.clone(),
+                                session_id.clone(),
+                                config.auth_header.clone(),
+                            )
+                            .await;
+                        let send_result = match response {
+                            Err(e) if self.client.is_transient(&e) => {
+                                tracing::debug!("server still unreachable: {e}");
+                                queue.retry_later(queued);
+                                break;
+                            }
+                            Err(e) => Err(e),
+                            Ok(response) => {
+                                self.accept_post_response(
+                                    response,
+                                    session_id.as_ref(),
+                                    &mut context,
+                                    &mut streams,
+                                    &sse_worker_tx,
+                                    &transport_task_ct,
+                                )
+                                .await?;
+                                Ok(())
+                            }
+                        };
+                        queue.flushed(queued, send_result);
+                    }
+                }
                 Event::ServerMessage(json_rpc_message) => {
                     // send the message to the handler
                     if let Err(e) = context.send_to_handler(json_rpc_message).await {
@@ -739,6 +872,9 @@ pub struct StreamableHttpClientTransportConfig {
     pub allow_stateless: bool,
     /// The value to send in the authorization header
     pub auth_header: Option<String>,
+    /// Queue messages while the server is unreachable instead of failing
+    /// them, see [`RequestQueueConfig`]. Off by default.
+    pub request_queue: Option<RequestQueueConfig>,
 }
 
 impl StreamableHttpClientTransportConfig {
@@ -759,6 +895,13 @@ impl StreamableHttpClientTransportConfig {
         self.auth_header = Some(value.into());
         self
     }
+
+    /// Queue messages while the server is unreachable, see
+    /// [`RequestQueueConfig`].
+    pub fn request_queue(mut self, config: RequestQueueConfig) -> Self {
+        self.request_queue = Some(config);
+        self
+    }
 }
 
 impl Default for StreamableHttpClientTransportConfig {
@@ -769,6 +912,256 @@ impl Default for StreamableHttpClientTransportConfig {
             channel_buffer_capacity: 16,
             allow_stateless: true,
             auth_header: None,
+            request_queue: None,
+        }
+    }
+}
+
+/// Holds outgoing messages while the server can't be reached, so brief
+/// network blips don't fail requests.
+///
+/// Once a POST fails with an error the client deems
+/// [transient](StreamableHttpClient::is_transient), it and every message
+/// sent after it are queued, in order. The transport retries the oldest one
+/// on the schedule of
+/// [`retry_config`](StreamableHttpClientTransportConfig::retry_config) and,
+/// as soon as the server takes it, flushes the rest. Messages fail with
+/// [`StreamableHttpError::RequestQueueFull`] when the queue is full,
+/// [`StreamableHttpError::RequestQueueTimeout`] when they waited longer than
+/// `deadline`, and [`StreamableHttpError::ReconnectFailed`] once the retry
+/// policy gives up.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use rmcp::transport::streamable_http_client::{RequestQueueConfig, StreamableHttpClientTransportConfig};
+/// let queue = RequestQueueConfig::default().deadline(Duration::from_secs(10));
+/// let metrics = queue.metrics.clone();
+/// let config = StreamableHttpClientTransportConfig::with_uri("http://localhost:8000/mcp")
+///     .request_queue(queue);
+/// // later
+/// println!("{} messages waiting for the server", metrics.depth());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestQueueConfig {
+    /// Most messages held at once.
+    pub capacity: usize,
+    /// Longest a message is held.
+    pub deadline: Duration,
+    pub metrics: RequestQueueMetrics,
+}
+
+impl RequestQueueConfig {
+    pub const DEFAULT_CAPACITY: usize = 64;
+    pub const DEFAULT_DEADLINE: Duration = Duration::from_secs(30);
+
+    pub fn capacity(self, capacity: usize) -> Self {
+        Self { capacity, ..self }
+    }
+
+    pub fn deadline(self, deadline: Duration) -> Self {
+        Self { deadline, ..self }
+    }
+}
+
+impl Default for RequestQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: Self::DEFAULT_CAPACITY,
+            deadline: Self::DEFAULT_DEADLINE,
+            metrics: RequestQueueMetrics::default(),
         }
     }
 }
+
+/// Live counters of a request queue. Clones share the counters.
+#[derive(Debug, Clone, Default)]
+pub struct RequestQueueMetrics {
+    state: Arc<RequestQueueMetricsState>,
+}
+
+#[derive(Debug, Default)]
+struct RequestQueueMetricsState {
+    depth: AtomicUsize,
+    reconnecting: AtomicBool,
+    flushed: AtomicU64,
+    rejected: AtomicU64,
+    expired: AtomicU64,
+}
+
+impl RequestQueueMetrics {
+    /// Messages waiting for the server.
+    pub fn depth(&self) -> usize {
+        self.state.depth.load(Ordering::Relaxed)
+    }
+
+    /// Whether the server is currently unreachable.
+    pub fn is_reconnecting(&self) -> bool {
+        self.state.reconnecting.load(Ordering::Relaxed)
+    }
+
+    /// Queued messages the server eventually took.
+    pub fn flushed(&self) -> u64 {
+        self.state.flushed.load(Ordering::Relaxed)
+    }
+
+    /// Messages failed because the queue was full.
+    pub fn rejected(&self) -> u64 {
+        self.state.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Messages failed because they passed their deadline or the transport
+    /// gave up reconnecting.
+    pub fn expired(&self) -> u64 {
+        self.state.expired.load(Ordering::Relaxed)
+    }
+}
+
+struct QueuedRequest<C: StreamableHttpClient> {
+    request: WorkerSendRequest<StreamableHttpClientWorker<C>>,
+    deadline: Instant,
+}
+
+struct RequestQueue<C: StreamableHttpClient> {
+    config: RequestQueueConfig,
+    retry_policy: Arc<dyn SseRetryPolicy>,
+    entries: VecDeque<QueuedRequest<C>>,
+    reconnecting: bool,
+    attempts: usize,
+    next_retry: Instant,
+}
+
+impl<C: StreamableHttpClient> RequestQueue<C> {
+    fn new(config: RequestQueueConfig, retry_policy: Arc<dyn SseRetryPolicy>) -> Self {
+        Self {
+            config,
+            retry_policy,
+            entries: VecDeque::new(),
+            reconnecting: false,
+            attempts: 0,
+            next_retry: Instant::now(),
+        }
+    }
+
+    fn is_reconnecting(&self) -> bool {
+        self.reconnecting
+    }
+
+    /// When the next retry or deadline is due, if reconnecting.
+    fn wake_at(&self) -> Option<Instant> {
+        if !self.reconnecting {
+            return None;
+        }
+        let deadline = self.entries.iter().map(|queued| queued.deadline).min();
+        Some(deadline.map_or(self.next_retry, |deadline| deadline.min(self.next_retry)))
+    }
+
+    fn push(&mut self, request: WorkerSendRequest<StreamableHttpClientWorker<C>>) {
+        if self.entries.len() >= self.config.capacity {
+            self.config
+                .metrics
+                .state
+                .rejected
+                .fetch_add(1, Ordering::Relaxed);
+            let _ = request
+                .responder
+                .send(Err(StreamableHttpError::RequestQueueFull));
+            return;
+        }
+        self.entries.push_back(QueuedRequest {
+            request,
+            deadline: Instant::now() + self.config.deadline,
+        });
+        self.update_metrics();
+    }
+
+    fn start_reconnecting(&mut self, request: WorkerSendRequest<StreamableHttpClientWorker<C>>) {
+        self.reconnecting = true;
+        self.attempts = 0;
+        self.push(request);
+        self.schedule_retry();
+    }
+
+    /// The oldest message, if it is time to send it. Leaves reconnecting
+    /// once the queue has drained.
+    fn pop_due(&mut self) -> Option<QueuedRequest<C>> {
+        self.expire();
+        if !self.reconnecting || Instant::now() < self.next_retry {
+            return None;
+        }
+        let queued = self.entries.pop_front();
+        if queued.is_none() {
+            self.reconnecting = false;
+        }
+        self.update_metrics();
+        queued
+    }
+
+    /// Put back a message the server still didn't take.
+    fn retry_later(&mut self, queued: QueuedRequest<C>) {
+        self.entries.push_front(queued);
+        self.attempts += 1;
+        self.schedule_retry();
+    }
+
+    fn flushed(
+        &mut self,
+        queued: QueuedRequest<C>,
+        result: Result<(), StreamableHttpError<C::Error>>,
+    ) {
+        self.config
+            .metrics
+            .state
+            .flushed
+            .fetch_add(1, Ordering::Relaxed);
+        let _ = queued.request.responder.send(result);
+    }
+
+    fn schedule_retry(&mut self) {
+        match self.retry_policy.retry(self.attempts) {
+            Some(delay) => self.next_retry = Instant::now() + delay,
+            None => {
+                tracing::warn!("gave up reconnecting after {} attempts", self.attempts);
+                self.fail_all(|| StreamableHttpError::ReconnectFailed, |_| true);
+                self.reconnecting = false;
+            }
+        }
+        self.update_metrics();
+    }
+
+    fn expire(&mut self) {
+        let now = Instant::now();
+        self.fail_all(
+            || StreamableHttpError::RequestQueueTimeout,
+            |queued| queued.deadline <= now,
+        );
+        if self.entries.is_empty() {
+            self.reconnecting = false;
+        }
+        self.update_metrics();
+    }
+
+    fn fail_all(
+        &mut self,
+        error: impl Fn() -> StreamableHttpError<C::Error>,
+        filter: impl Fn(&QueuedRequest<C>) -> bool,
+    ) {
+        let (failed, kept) = self.entries.drain(..).partition(|queued| filter(queued));
+        self.entries = kept;
+        for queued in failed {
+            self.config
+                .metrics
+                .state
+                .expired
+                .fetch_add(1, Ordering::Relaxed);
+            let _ = queued.request.responder.send(Err(error()));
+        }
+    }
+
+    fn update_metrics(&self) {
+        let state = &self.config.metrics.state;
+        state.depth.store(self.entries.len(), Ordering::Relaxed);
+        state
+            .reconnecting
+            .store(self.reconnecting, Ordering::Relaxed);
+    }
+}
@@ -6,3 +6,21 @@ pub use session::{SessionId, SessionManager};
 #[cfg(feature = "transport-streamable-http-server")]
 #[cfg_attr(docsrs, doc(cfg(feature = "transport-streamable-http-server")))]
 pub use tower::{StreamableHttpServerConfig, StreamableHttpService};
+#[cfg(feature = "transport-streamable-http-server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-streamable-http-server")))]
+pub mod registry;
+#[cfg(feature = "transport-streamable-http-server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-streamable-http-server")))]
+pub use registry::{ActiveSession, MemoryReport, SessionMemory, SessionRegistry};
+#[cfg(feature = "transport-streamable-http-server-actix")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-streamable-http-server-actix")))]
+pub mod actix;
+#[cfg(feature = "transport-streamable-http-server-axum")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-streamable-http-server-axum")))]
+pub mod axum;
+#[cfg(feature = "transport-streamable-http-server-hyper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-streamable-http-server-hyper")))]
+pub mod hyper;
+#[cfg(feature = "transport-streamable-http-server-warp")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport-streamable-http-server-warp")))]
+pub mod warp;
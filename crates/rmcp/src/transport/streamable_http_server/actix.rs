@@ -0,0 +1,56 @@
+//! [actix-web](https://actix.rs) adapter for [`StreamableHttpService`].
+//!
+//! ```rust,ignore
+//! use actix_web::{App, HttpServer};
+//!
+//! HttpServer::new(move || App::new().service(service.clone().actix_scope("/mcp")))
+//!     .bind("127.0.0.1:8000")?
+//!     .run()
+//!     .await?;
+//! ```
+use actix_web::{HttpRequest, HttpResponse, Scope, web};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+
+use super::{session::SessionManager, tower::StreamableHttpService};
+use crate::RoleServer;
+
+impl<S, M> StreamableHttpService<S, M>
+where
+    S: crate::Service<RoleServer> + Send + 'static,
+    M: SessionManager,
+{
+    /// Expose this service as an actix-web [`Scope`] mounted at `path`.
+    ///
+    /// All methods sent to `path` are forwarded to [`StreamableHttpService::handle`].
+    pub fn actix_scope(self, path: &str) -> Scope {
+        web::scope(path).route(
+            "",
+            web::route().to(move |request: HttpRequest, body: Bytes| {
+                let service = self.clone();
+                async move { service.handle_actix(request, body).await }
+            }),
+        )
+    }
+
+    async fn handle_actix(&self, request: HttpRequest, body: Bytes) -> HttpResponse {
+        let mut builder = http::Request::builder()
+            .method(request.method().as_str())
+            .uri(request.uri().to_string());
+        for (name, value) in request.headers() {
+            builder = builder.header(name.as_str(), value.as_bytes());
+        }
+        let request = match builder.body(Full::new(body)) {
+            Ok(request) => request,
+            Err(e) => return HttpResponse::BadRequest().body(format!("Bad Request: {e}")),
+        };
+        let (parts, body) = self.handle(request).await.into_parts();
+        let status = actix_web::http::StatusCode::from_u16(parts.status.as_u16())
+            .unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = HttpResponse::build(status);
+        for (name, value) in parts.headers.iter() {
+            response.append_header((name.as_str(), value.as_bytes()));
+        }
+        response.streaming(body.into_data_stream())
+    }
+}
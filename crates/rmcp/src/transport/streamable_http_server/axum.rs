@@ -0,0 +1,64 @@
+//! [axum](https://github.com/tokio-rs/axum) integration for [`StreamableHttpService`].
+//!
+//! [`StreamableHttpService`] is a [`tower_service::Service`], so it can always be mounted with
+//! [`axum::Router::nest_service`]. The helpers here cover the common cases of mounting it inside
+//! an existing application which shares middleware layers and state.
+use super::{session::SessionManager, tower::StreamableHttpService};
+use crate::RoleServer;
+
+impl<S, M> StreamableHttpService<S, M>
+where
+    S: crate::Service<RoleServer> + Send + 'static,
+    M: SessionManager,
+{
+    /// Convert this service into an [`axum::Router`] which serves the MCP endpoint at `/`.
+    ///
+    /// The returned router can be merged or nested into an existing application, so the
+    /// MCP endpoint shares the application's middleware layers and listener.
+    ///
+    /// ```rust,ignore
+    /// let app = axum::Router::new()
+    ///     .route("/health", axum::routing::get(|| async { "ok" }))
+    ///     .nest("/mcp", service.into_router());
+    /// ```
+    pub fn into_router<St>(self) -> axum::Router<St>
+    where
+        St: Clone + Send + Sync + 'static,
+    {
+        axum::Router::new().route_service("/", self)
+    }
+
+    /// Mount this service into an existing [`axum::Router`] at `path`.
+    ///
+    /// This is a shorthand for `router.nest_service(path, self)`, any layers added to the
+    /// router afterwards will also apply to the MCP endpoint.
+    ///
+    /// ```rust,ignore
+    /// let app = service
+    ///     .nest_into(axum::Router::new(), "/mcp")
+    ///     .layer(tower_http::cors::CorsLayer::permissive());
+    /// ```
+    pub fn nest_into<St>(self, router: axum::Router<St>, path: &str) -> axum::Router<St>
+    where
+        St: Clone + Send + Sync + 'static,
+    {
+        router.nest_service(path, self)
+    }
+
+    /// A `GET` route answering with the [`memory_report`](Self::memory_report)
+    /// of this service, to mount on a debug listener or behind operator auth.
+    ///
+    /// ```rust,ignore
+    /// let debug = axum::Router::new().route("/debug/memory", service.memory_report_route());
+    /// ```
+    pub fn memory_report_route<St>(&self) -> axum::routing::MethodRouter<St>
+    where
+        St: Clone + Send + Sync + 'static,
+    {
+        let service = self.clone();
+        axum::routing::get(move || {
+            let response = service.memory_report_response();
+            async move { response }
+        })
+    }
+}
@@ -0,0 +1,81 @@
+//! A minimal HTTP/1.1 server for [`StreamableHttpService`] built directly on
+//! [hyper](https://hyper.rs), for users who don't want to pull in a web framework.
+//!
+//! ```rust,ignore
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:8000").await?;
+//! service.serve_hyper(listener).await?;
+//! ```
+use std::{convert::Infallible, time::Duration};
+
+use ::hyper::{server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+
+use super::{session::SessionManager, tower::StreamableHttpService};
+use crate::RoleServer;
+
+/// First and longest wait before accepting again after an accept error.
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(5);
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+impl<S, M> StreamableHttpService<S, M>
+where
+    S: crate::Service<RoleServer> + Send + 'static,
+    M: SessionManager,
+{
+    /// Accept connections from `listener` and serve the MCP endpoint on every request path.
+    ///
+    /// The server runs until [`StreamableHttpServerConfig::cancellation_token`] is cancelled,
+    /// after which no new connections are accepted and open connections are shut down
+    /// gracefully.
+    ///
+    /// [`StreamableHttpServerConfig::cancellation_token`]: super::StreamableHttpServerConfig::cancellation_token
+    pub async fn serve_hyper(self, listener: TcpListener) -> std::io::Result<()> {
+        let ct = self.config.cancellation_token.clone();
+        let mut backoff = ACCEPT_BACKOFF_MIN;
+        loop {
+            let stream = tokio::select! {
+                accept = listener.accept() => match accept {
+                    Ok((stream, _)) => {
+                        backoff = ACCEPT_BACKOFF_MIN;
+                        stream
+                    }
+                    Err(e) => {
+                        // errors like EMFILE persist until connections close, so
+                        // wait instead of spinning on them
+                        tracing::warn!(error = %e, ?backoff, "failed to accept connection");
+                        tokio::select! {
+                            _ = tokio::time::sleep(backoff) => {}
+                            _ = ct.cancelled() => break,
+                        }
+                        backoff = (backoff * 2).min(ACCEPT_BACKOFF_MAX);
+                        continue;
+                    }
+                },
+                _ = ct.cancelled() => break,
+            };
+            let service = self.clone();
+            let ct = ct.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |request| {
+                    let service = service.clone();
+                    async move { Ok::<_, Infallible>(service.handle(request).await) }
+                });
+                let connection =
+                    http1::Builder::new().serve_connection(TokioIo::new(stream), service);
+                let mut connection = std::pin::pin!(connection);
+                let result = tokio::select! {
+                    result = connection.as_mut() => result,
+                    _ = ct.cancelled() => {
+                        connection.as_mut().graceful_shutdown();
+                        connection.await
+                    }
+                };
+                if let Err(e) = result {
+                    tracing::debug!(error = %e, "http connection closed with error");
+                }
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use serde::Serialize;
+use tokio::sync::Notify;
+
+use super::session::SessionId;
+use crate::{
+    RoleServer,
+    model::{ClientInfo, ServerNotification},
+    service::{MemoryUsage, Peer, ServiceError, WeakPeer},
+};
+
+/// A live session known to a [`SessionRegistry`].
+#[derive(Debug, Clone)]
+pub struct ActiveSession {
+    pub id: SessionId,
+    pub peer: Peer<RoleServer>,
+}
+
+impl ActiveSession {
+    /// What the client sent in its `initialize` request.
+    pub fn client_info(&self) -> Option<&ClientInfo> {
+        self.peer.peer_info()
+    }
+
+    /// Approximate bytes buffered for this session.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.peer.memory_usage()
+    }
+}
+
+/// The bytes a session buffers, see [`SessionRegistry::memory_report`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMemory {
+    pub id: SessionId,
+    pub usage: MemoryUsage,
+    pub total: usize,
+}
+
+/// The bytes buffered by each open session, the largest first.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryReport {
+    pub sessions: Vec<SessionMemory>,
+    pub total: MemoryUsage,
+}
+
+/// Tracks the sessions served by a
+/// [`StreamableHttpService`](super::StreamableHttpService), so that the server
+/// can push notifications to all or some of its clients.
+///
+/// A session is listed once its `initialize` handshake has completed and until
+/// it closes. Stateless mode has no sessions, so the registry stays empty.
+///
+/// ```rust,no_run
+/// # use rmcp::{model::ServerNotification, transport::streamable_http_server::SessionRegistry};
+/// # async fn demo(registry: &SessionRegistry, notification: ServerNotification) {
+/// for session in registry.sessions() {
+///     if let Some(info) = session.client_info() {
+///         tracing::info!(id = %session.id, client = %info.client_info.name);
+///     }
+/// }
+/// registry.broadcast_notification(notification).await;
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct SessionRegistry {
+    sessions: RwLock<HashMap<SessionId, WeakPeer<RoleServer>>>,
+    removed: Notify,
+}
+
+impl SessionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn insert(&self, id: SessionId, peer: &Peer<RoleServer>) {
+        self.sessions
+            .write()
+            .expect("session registry poisoned")
+            .insert(id, peer.downgrade());
+    }
+
+    pub(crate) fn remove(&self, id: &SessionId) {
+        self.sessions
+            .write()
+            .expect("session registry poisoned")
+            .remove(id);
+        self.removed.notify_waiters();
+    }
+
+    /// Resolves once no session is left.
+    pub async fn drained(&self) {
+        loop {
+            let removed = self.removed.notified();
+            if self
+                .sessions
+                .read()
+                .expect("session registry poisoned")
+                .is_empty()
+            {
+                return;
+            }
+            removed.await;
+        }
+    }
+
+    /// Snapshot of the sessions that are currently open.
+    pub fn sessions(&self) -> Vec<ActiveSession> {
+        self.sessions
+            .read()
+            .expect("session registry poisoned")
+            .iter()
+            .filter_map(|(id, peer)| {
+                Some(ActiveSession {
+                    id: id.clone(),
+                    peer: peer.upgrade()?,
+                })
+            })
+            .collect()
+    }
+
+    /// What every open session buffers, to find the one that holds on to
+    /// the most.
+    pub fn memory_report(&self) -> MemoryReport {
+        let mut report = MemoryReport::default();
+        for session in self.sessions() {
+            let usage = session.memory_usage();
+            report.total.notifications += usage.notifications;
+            report.total.events += usage.events;
+            report.total.task_results += usage.task_results;
+            report.sessions.push(SessionMemory {
+                id: session.id,
+                total: usage.total(),
+                usage,
+            });
+        }
+        report
+            .sessions
+            .sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.id.cmp(&b.id)));
+        report
+    }
+
+    pub fn get(&self, id: &SessionId) -> Option<Peer<RoleServer>> {
+        self.sessions
+            .read()
+            .expect("session registry poisoned")
+            .get(id)
+            .and_then(WeakPeer::upgrade)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Send `notification` to the client of session `id`.
+    ///
+    /// Fails with [`ServiceError::TransportClosed`] if there is no such open
+    /// session.
+    pub async fn send_to(
+        &self,
+        id: &SessionId,
+        notification: ServerNotification,
+    ) -> Result<(), ServiceError> {
+        let peer = self.get(id).ok_or(ServiceError::TransportClosed)?;
+        peer.send_notification(notification).await
+    }
+
+    /// Send `notification` to every open session, returning how many of them
+    /// it was delivered to. Failures are logged and otherwise ignored.
+    pub async fn broadcast_notification(&self, notification: ServerNotification) -> usize {
+        self.broadcast_notification_where(|_| true, notification)
+            .await
+    }
+
+    /// Like [`broadcast_notification`](Self::broadcast_notification), but only
+    /// to the sessions `filter` accepts.
+    pub async fn broadcast_notification_where(
+        &self,
+        filter: impl Fn(&ActiveSession) -> bool,
+        notification: ServerNotification,
+    ) -> usize {
+        let sends = self
+            .sessions()
+            .into_iter()
+            .filter(|session| filter(session))
+            .map(|session| {
+                let notification = notification.clone();
+                async move {
+                    session
+                        .peer
+                        .send_notification(notification)
+                        .await
+                        .inspect_err(|error| {
+                            tracing::debug!(id = %session.id, %error, "failed to broadcast notification");
+                        })
+                        .is_ok()
+                }
+            });
+        futures::future::join_all(sends)
+            .await
+            .into_iter()
+            .filter(|sent| *sent)
+            .count()
+    }
+}
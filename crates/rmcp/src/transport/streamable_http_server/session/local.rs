@@ -21,6 +21,7 @@ use crate::{
         JsonRpcNotification, JsonRpcRequest, Notification, ProgressNotificationParam,
         ProgressToken, RequestId, ServerJsonRpcMessage, ServerNotification,
     },
+    service::{CloseReason, MemoryAccount, MemoryKind, SessionExpiry},
     transport::{
         WorkerTransport,
         common::server_side_http::{SessionId, session_id},
@@ -186,19 +187,32 @@ struct CachedTx {
     cache: VecDeque<ServerSseMessage>,
     http_request_id: Option<HttpRequestId>,
     capacity: usize,
+    memory: MemoryAccount,
+}
+
+fn event_size(message: &ServerSseMessage) -> usize {
+    let data = message.message.as_ref().map_or(0, |message| {
+        serde_json::to_vec(message).map_or(0, |json| json.len())
+    });
+    data + message.event_id.as_ref().map_or(0, String::len)
 }
 
 impl CachedTx {
-    fn new(tx: Sender<ServerSseMessage>, http_request_id: Option<HttpRequestId>) -> Self {
+    fn new(
+        tx: Sender<ServerSseMessage>,
+        http_request_id: Option<HttpRequestId>,
+        memory: MemoryAccount,
+    ) -> Self {
         Self {
             cache: VecDeque::with_capacity(tx.capacity()),
             capacity: tx.capacity(),
             tx,
             http_request_id,
+            memory,
         }
     }
-    fn new_common(tx: Sender<ServerSseMessage>) -> Self {
-        Self::new(tx, None)
+    fn new_common(tx: Sender<ServerSseMessage>, memory: MemoryAccount) -> Self {
+        Self::new(tx, None, memory)
     }
 
     fn next_event_id(&self) -> EventId {
@@ -239,11 +253,12 @@ impl CachedTx {
 
     async fn cache_and_send(&mut self, message: ServerSseMessage) {
         if self.cache.len() >= self.capacity {
-            self.cache.pop_front();
-            self.cache.push_back(message.clone());
-        } else {
-            self.cache.push_back(message.clone());
+            if let Some(evicted) = self.cache.pop_front() {
+                self.memory.sub(MemoryKind::Events, event_size(&evicted));
+            }
         }
+        self.memory.add(MemoryKind::Events, event_size(&message));
+        self.cache.push_back(message.clone());
         let _ = self.tx.send(message).await.inspect_err(|e| {
             let event_id = &e.0.event_id;
             tracing::trace!(?event_id, "trying to send message in a closed session")
@@ -275,6 +290,13 @@ impl CachedTx {
     }
 }
 
+impl Drop for CachedTx {
+    fn drop(&mut self) {
+        let size = self.cache.iter().map(event_size).sum();
+        self.memory.sub(MemoryKind::Events, size);
+    }
+}
+
 struct HttpRequestWise {
     resources: HashSet<ResourceKey>,
     tx: CachedTx,
@@ -295,6 +317,7 @@ pub struct LocalSessionWorker {
     common: CachedTx,
     event_rx: Receiver<SessionEvent>,
     session_config: SessionConfig,
+    memory: MemoryAccount,
 }
 
 impl LocalSessionWorker {
@@ -407,7 +430,7 @@ impl LocalSessionWorker {
             http_request_id,
             HttpRequestWise {
                 resources: Default::default(),
-                tx: CachedTx::new(tx, Some(http_request_id)),
+                tx: CachedTx::new(tx, Some(http_request_id), self.memory.clone()),
             },
         );
         tracing::debug!(http_request_id, "establish new request wise channel");
@@ -846,6 +869,14 @@ impl Worker for LocalSessionWorker {
             channel_buffer_capacity: self.session_config.channel_capacity,
         }
     }
+    fn close_reason(error: &Self::Error) -> Option<CloseReason> {
+        match error {
+            LocalSessionWorkerError::KeepAliveTimeout(keep_alive) => Some(CloseReason::Expired(
+                SessionExpiry::IdleTimeout(*keep_alive),
+            )),
+            _ => None,
+        }
+    }
     #[instrument(name = "streamable_http_session", skip_all, fields(id = self.id.as_ref()))]
     async fn run(
         mut self,
@@ -855,6 +886,10 @@ impl Worker for LocalSessionWorker {
             FromHttpService(SessionEvent),
             FromHandler(WorkerSendRequest<LocalSessionWorker>),
         }
+        context.set_session_id(self.id.clone());
+        // nothing is cached before the worker runs
+        self.memory = context.memory_account().clone();
+        self.common.memory = self.memory.clone();
         // waiting for initialize request
         let evt = self.event_rx.recv().await.ok_or_else(|| {
             WorkerQuitReason::fatal(
@@ -1024,7 +1059,8 @@ pub fn create_local_session(
     let id = id.into();
     let (event_tx, event_rx) = tokio::sync::mpsc::channel(config.channel_capacity);
     let (common_tx, _) = tokio::sync::mpsc::channel(config.channel_capacity);
-    let common = CachedTx::new_common(common_tx);
+    let memory = MemoryAccount::new();
+    let common = CachedTx::new_common(common_tx, memory.clone());
     tracing::info!(session_id = ?id, "create new session");
     let handle = LocalSessionHandle {
         event_tx,
@@ -1038,6 +1074,7 @@ pub fn create_local_session(
         common,
         event_rx,
         session_config: config.clone(),
+        memory,
     };
     (handle, session_worker)
 }
@@ -1,4 +1,12 @@
-use std::{convert::Infallible, fmt::Display, sync::Arc, time::Duration};
+use std::{
+    convert::Infallible,
+    fmt::Display,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use bytes::Bytes;
 use futures::{StreamExt, future::BoxFuture};
@@ -8,10 +16,16 @@ use http_body_util::{BodyExt, Full, combinators::BoxBody};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::CancellationToken;
 
-use super::session::SessionManager;
+use super::{
+    registry::{MemoryReport, SessionRegistry},
+    session::SessionManager,
+};
 use crate::{
     RoleServer,
-    model::{ClientJsonRpcMessage, ClientRequest, GetExtensions},
+    model::{
+        ClientJsonRpcMessage, ClientRequest, GetExtensions, LoggingLevel,
+        LoggingMessageNotification, LoggingMessageNotificationParam,
+    },
     serve_server,
     service::serve_directly,
     transport::{
@@ -42,6 +56,12 @@ pub struct StreamableHttpServerConfig {
     /// When this token is cancelled, all active sessions are terminated and
     /// the server stops accepting new requests.
     pub cancellation_token: CancellationToken,
+    /// Close a session once it buffers more than this many bytes, see
+    /// [`SessionRegistry::memory_report`].
+    pub max_session_buffered_bytes: Option<usize>,
+    /// Once all sessions together buffer more than this many bytes, close
+    /// those that buffer the most until they are back under it.
+    pub max_buffered_bytes: Option<usize>,
 }
 
 impl Default for StreamableHttpServerConfig {
@@ -51,6 +71,8 @@ impl Default for StreamableHttpServerConfig {
             sse_retry: Some(Duration::from_secs(3)),
             stateful_mode: true,
             cancellation_token: CancellationToken::new(),
+            max_session_buffered_bytes: None,
+            max_buffered_bytes: None,
         }
     }
 }
@@ -71,6 +93,8 @@ impl Default for StreamableHttpServerConfig {
 pub struct StreamableHttpService<S, M = super::session::local::LocalSessionManager> {
     pub config: StreamableHttpServerConfig,
     session_manager: Arc<M>,
+    session_registry: Arc<SessionRegistry>,
+    shutting_down: Arc<AtomicBool>,
     service_factory: Arc<dyn Fn() -> Result<S, std::io::Error> + Send + Sync>,
 }
 
@@ -79,6 +103,8 @@ impl<S, M> Clone for StreamableHttpService<S, M> {
         Self {
             config: self.config.clone(),
             session_manager: self.session_manager.clone(),
+            session_registry: self.session_registry.clone(),
+            shutting_down: self.shutting_down.clone(),
             service_factory: self.service_factory.clone(),
         }
     }
@@ -123,9 +149,97 @@ where
         Self {
             config,
             session_manager,
+            session_registry: Default::default(),
+            shutting_down: Default::default(),
             service_factory: Arc::new(service_factory),
         }
     }
+    /// The sessions this service is currently serving, for pushing
+    /// notifications to connected clients.
+    pub fn session_registry(&self) -> &Arc<SessionRegistry> {
+        &self.session_registry
+    }
+    /// Shut the server down without cutting clients off mid-request.
+    ///
+    /// New sessions are refused with `503 Service Unavailable`, every open
+    /// session gets a `notifications/message` saying the server is going away,
+    /// and clients have up to `grace_period` to finish and disconnect. Then
+    /// [`StreamableHttpServerConfig::cancellation_token`] is cancelled, which
+    /// terminates whatever is left.
+    pub async fn shutdown(&self, grace_period: Duration) {
+        if self.shutting_down.swap(true, Ordering::AcqRel) {
+            tracing::debug!("shutdown already in progress");
+        }
+        let notification = LoggingMessageNotification::new(LoggingMessageNotificationParam {
+            level: LoggingLevel::Warning,
+            logger: Some("rmcp".into()),
+            data: "server shutting down".into(),
+        });
+        let notified = self
+            .session_registry
+            .broadcast_notification(notification.into())
+            .await;
+        tracing::info!(
+            sessions = notified,
+            "shutting down, waiting for sessions to close"
+        );
+        if tokio::time::timeout(grace_period, self.session_registry.drained())
+            .await
+            .is_err()
+        {
+            tracing::info!(
+                sessions = self.session_registry.len(),
+                "grace period elapsed, closing remaining sessions"
+            );
+        }
+        self.config.cancellation_token.cancel();
+    }
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Acquire)
+    }
+    /// What each session of this service buffers.
+    pub fn memory_report(&self) -> MemoryReport {
+        self.session_registry.memory_report()
+    }
+    /// [`memory_report`](Self::memory_report) as a JSON response, to serve
+    /// from a debug endpoint that only operators can reach.
+    pub fn memory_report_response(&self) -> BoxResponse {
+        let body = serde_json::to_vec(&self.memory_report()).expect("valid json");
+        Response::builder()
+            .header(http::header::CONTENT_TYPE, JSON_MIME_TYPE)
+            .body(Full::new(Bytes::from(body)).boxed())
+            .expect("valid response")
+    }
+    /// Close the sessions over the buffered bytes caps of the config, the
+    /// largest first.
+    async fn enforce_memory_caps(&self) {
+        let (per_session, overall) = (
+            self.config.max_session_buffered_bytes,
+            self.config.max_buffered_bytes,
+        );
+        if per_session.is_none() && overall.is_none() {
+            return;
+        }
+        let report = self.memory_report();
+        let mut total = report.total.total();
+        for session in report.sessions {
+            let over_session = per_session.is_some_and(|max| session.total > max);
+            let over_total = overall.is_some_and(|max| total > max);
+            if !over_session && !over_total {
+                break;
+            }
+            tracing::warn!(
+                id = %session.id,
+                buffered = session.total,
+                total,
+                "closing session over the buffered bytes cap"
+            );
+            if let Err(error) = self.session_manager.close_session(&session.id).await {
+                tracing::warn!(id = %session.id, %error, "failed to close session");
+            }
+            total -= session.total;
+        }
+    }
     fn get_service(&self) -> Result<S, std::io::Error> {
         (self.service_factory)()
     }
@@ -135,6 +249,9 @@ where
         B::Error: Display,
     {
         let method = request.method().clone();
+        if self.config.stateful_mode {
+            self.enforce_memory_caps().await;
+        }
         let allowed_methods = match self.config.stateful_mode {
             true => "GET, POST, DELETE",
             false => "POST",
@@ -371,6 +488,9 @@ where
                     }
                 }
             } else {
+                if self.is_shutting_down() {
+                    return Ok(shutting_down_response());
+                }
                 let (session_id, transport) = self
                     .session_manager
                     .create_session()
@@ -391,6 +511,7 @@ where
                 // spawn a task to serve the session
                 tokio::spawn({
                     let session_manager = self.session_manager.clone();
+                    let session_registry = self.session_registry.clone();
                     let session_id = session_id.clone();
                     async move {
                         let service = serve_server::<S, M::Transport, _, TransportAdapterIdentity>(
@@ -400,7 +521,9 @@ where
                         match service {
                             Ok(service) => {
                                 // on service created
+                                session_registry.insert(session_id.clone(), service.peer());
                                 let _ = service.waiting().await;
+                                session_registry.remove(&session_id);
                             }
                             Err(e) => {
                                 tracing::error!("Failed to create service: {e}");
@@ -455,6 +578,9 @@ where
                 Ok(response)
             }
         } else {
+            if self.is_shutting_down() {
+                return Ok(shutting_down_response());
+            }
             let service = self
                 .get_service()
                 .map_err(internal_error_response("get service"))?;
@@ -519,3 +645,10 @@ where
         Ok(accepted_response())
     }
 }
+
+fn shutting_down_response() -> BoxResponse {
+    Response::builder()
+        .status(http::StatusCode::SERVICE_UNAVAILABLE)
+        .body(Full::new(Bytes::from("Service Unavailable: server is shutting down")).boxed())
+        .expect("valid response")
+}
@@ -0,0 +1,118 @@
+//! [warp](https://github.com/seanmonstar/warp) adapter for [`StreamableHttpService`].
+//!
+//! ```rust,ignore
+//! use warp::Filter;
+//!
+//! let routes = warp::path("mcp").and(service.warp_filter());
+//! warp::serve(routes).run(([127, 0, 0, 1], 8000)).await;
+//! ```
+use ::warp::{
+    Filter,
+    filters::BoxedFilter,
+    http::{HeaderMap, Method},
+    hyper,
+    path::FullPath,
+};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+
+use super::{session::SessionManager, tower::StreamableHttpService};
+use crate::RoleServer;
+
+/// Largest POST body the filter accepts, the same as axum's default body limit.
+const MAX_BODY_SIZE: u64 = 2 * 1024 * 1024;
+
+/// The body of POST requests, up to [`MAX_BODY_SIZE`], and an empty body for the
+/// other methods, which don't carry one.
+fn body() -> BoxedFilter<(Bytes,)> {
+    let post = ::warp::post()
+        .and(::warp::body::content_length_limit(MAX_BODY_SIZE))
+        .and(::warp::body::bytes());
+    let other = ::warp::method().and_then(|method: Method| async move {
+        if method == Method::POST {
+            Err(::warp::reject::not_found())
+        } else {
+            Ok(Bytes::new())
+        }
+    });
+    post.or(other).unify().boxed()
+}
+
+impl<S, M> StreamableHttpService<S, M>
+where
+    S: crate::Service<RoleServer> + Send + 'static,
+    M: SessionManager,
+{
+    /// Expose this service as a warp filter.
+    ///
+    /// The filter matches any method and consumes the request body, so it should be combined
+    /// with a path filter, e.g. `warp::path("mcp").and(service.warp_filter())`. POST bodies
+    /// must have a `Content-Length` of at most 2 MiB.
+    pub fn warp_filter(self) -> BoxedFilter<(::warp::reply::Response,)> {
+        let query = ::warp::query::raw()
+            .or(::warp::any().map(String::new))
+            .unify();
+        ::warp::method()
+            .and(::warp::path::full())
+            .and(query)
+            .and(::warp::header::headers_cloned())
+            .and(body())
+            .then(
+                move |method: Method,
+                      path: FullPath,
+                      query: String,
+                      headers: HeaderMap,
+                      body: Bytes| {
+                    let service = self.clone();
+                    async move {
+                        service
+                            .handle_warp(method, path, query, headers, body)
+                            .await
+                    }
+                },
+            )
+            .boxed()
+    }
+
+    async fn handle_warp(
+        &self,
+        method: Method,
+        path: FullPath,
+        query: String,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> ::warp::reply::Response {
+        let uri = if query.is_empty() {
+            path.as_str().to_owned()
+        } else {
+            format!("{}?{query}", path.as_str())
+        };
+        let mut builder = http::Request::builder().method(method.as_str()).uri(uri);
+        for (name, value) in headers.iter() {
+            builder = builder.header(name.as_str(), value.as_bytes());
+        }
+        let request = match builder.body(Full::new(body)) {
+            Ok(request) => request,
+            Err(e) => {
+                let mut response =
+                    ::warp::reply::Response::new(hyper::Body::from(format!("Bad Request: {e}")));
+                *response.status_mut() = ::warp::http::StatusCode::BAD_REQUEST;
+                return response;
+            }
+        };
+        let (parts, body) = self.handle(request).await.into_parts();
+        let mut response =
+            ::warp::reply::Response::new(hyper::Body::wrap_stream(body.into_data_stream()));
+        *response.status_mut() = ::warp::http::StatusCode::from_u16(parts.status.as_u16())
+            .unwrap_or(::warp::http::StatusCode::INTERNAL_SERVER_ERROR);
+        for (name, value) in parts.headers.iter() {
+            if let (Ok(name), Ok(value)) = (
+                ::warp::http::HeaderName::from_bytes(name.as_str().as_bytes()),
+                ::warp::http::HeaderValue::from_bytes(value.as_bytes()),
+            ) {
+                response.headers_mut().append(name, value);
+            }
+        }
+        response
+    }
+}
@@ -1,10 +1,16 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    sync::{Arc, OnceLock},
+};
 
 use tokio_util::sync::CancellationToken;
 use tracing::{Instrument, Level};
 
 use super::{IntoTransport, Transport};
-use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
+use crate::service::{
+    CloseReason, ConnectionState, MemoryAccount, RxJsonRpcMessage, ServiceRole, TaskKind,
+    TxJsonRpcMessage, spawn_task,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum WorkerQuitReason<E> {
@@ -24,6 +30,17 @@ pub enum WorkerQuitReason<E> {
 }
 
 impl<E: std::error::Error + Send + 'static> WorkerQuitReason<E> {
+    fn close_reason<W: Worker<Error = E>>(&self) -> Option<CloseReason> {
+        match self {
+            WorkerQuitReason::Cancelled => Some(CloseReason::Cancelled),
+            WorkerQuitReason::TransportClosed | WorkerQuitReason::HandlerTerminated => None,
+            WorkerQuitReason::Join(e) => Some(CloseReason::Failed(e.to_string())),
+            WorkerQuitReason::Fatal { error, context } => Some(
+                W::close_reason(error)
+                    .unwrap_or_else(|| CloseReason::Failed(format!("{error}, when {context}"))),
+            ),
+        }
+    }
     pub fn fatal(error: E, context: impl Into<Cow<'static, str>>) -> Self {
         Self::Fatal {
             error,
@@ -50,6 +67,13 @@ pub trait Worker: Sized + Send + 'static {
     fn config(&self) -> WorkerConfig {
         WorkerConfig::default()
     }
+    /// What a fatal `error` means for the service, see
+    /// [`Transport::close_reason`]. `None` reports it as
+    /// [`CloseReason::Failed`].
+    fn close_reason(error: &Self::Error) -> Option<CloseReason> {
+        let _ = error;
+        None
+    }
 }
 
 pub struct WorkerSendRequest<W: Worker> {
@@ -63,6 +87,10 @@ pub struct WorkerTransport<W: Worker> {
     join_handle: Option<tokio::task::JoinHandle<Result<(), WorkerQuitReason<W::Error>>>>,
     _drop_guard: tokio_util::sync::DropGuard,
     ct: CancellationToken,
+    state: tokio::sync::watch::Receiver<ConnectionState>,
+    session_id: Arc<OnceLock<Arc<str>>>,
+    memory: MemoryAccount,
+    close_reason: Arc<OnceLock<CloseReason>>,
 }
 
 pub struct WorkerConfig {
@@ -100,37 +128,58 @@ impl<W: Worker> WorkerTransport<W> {
             tokio::sync::mpsc::channel::<WorkerSendRequest<W>>(config.channel_buffer_capacity);
         let (to_handler_tx, from_transport_rx) =
             tokio::sync::mpsc::channel::<RxJsonRpcMessage<W::Role>>(config.channel_buffer_capacity);
+        let (state_tx, state) = tokio::sync::watch::channel(ConnectionState::Ready);
+        let session_id = Arc::new(OnceLock::new());
+        let memory = MemoryAccount::new();
+        let close_reason = Arc::new(OnceLock::new());
+        // keeps the handler's stream open until the close reason is recorded
+        let handler_tx = to_handler_tx.clone();
         let context = WorkerContext {
             to_handler_tx,
             from_handler_rx,
             cancellation_token: transport_task_ct.clone(),
+            state_tx,
+            session_id: session_id.clone(),
+            memory: memory.clone(),
         };
 
-        let join_handle = tokio::spawn(async move {
-            worker
-                .run(context)
-                .instrument(tracing::span!(
-                    Level::TRACE,
-                    "transport_worker",
-                    name = worker_name,
-                ))
-                .await
-                .inspect_err(|e| match e {
-                    WorkerQuitReason::Cancelled
-                    | WorkerQuitReason::TransportClosed
-                    | WorkerQuitReason::HandlerTerminated => {
-                        tracing::debug!("worker quit with reason: {:?}", e);
-                    }
-                    WorkerQuitReason::Join(e) => {
-                        tracing::error!("worker quit with join error: {:?}", e);
-                    }
-                    WorkerQuitReason::Fatal { error, context } => {
-                        tracing::error!("worker quit with fatal: {error}, when {context}");
-                    }
-                })
-                .inspect(|_| {
-                    tracing::debug!("worker quit");
-                })
+        let join_handle = spawn_task(TaskKind::TransportWorker, "rmcp::worker", {
+            let close_reason = close_reason.clone();
+            async move {
+                let result = worker
+                    .run(context)
+                    .instrument(tracing::span!(
+                        Level::TRACE,
+                        "transport_worker",
+                        name = worker_name,
+                    ))
+                    .await
+                    .inspect_err(|e| match e {
+                        WorkerQuitReason::Cancelled
+                        | WorkerQuitReason::TransportClosed
+                        | WorkerQuitReason::HandlerTerminated => {
+                            tracing::debug!("worker quit with reason: {:?}", e);
+                        }
+                        WorkerQuitReason::Join(e) => {
+                            tracing::error!("worker quit with join error: {:?}", e);
+                        }
+                        WorkerQuitReason::Fatal { error, context } => {
+                            tracing::error!("worker quit with fatal: {error}, when {context}");
+                        }
+                    })
+                    .inspect(|_| {
+                        tracing::debug!("worker quit");
+                    });
+                if let Some(reason) = result
+                    .as_ref()
+                    .err()
+                    .and_then(WorkerQuitReason::close_reason::<W>)
+                {
+                    let _ = close_reason.set(reason);
+                }
+                drop(handler_tx);
+                result
+            }
         });
         Self {
             rx: from_transport_rx,
@@ -138,6 +187,10 @@ impl<W: Worker> WorkerTransport<W> {
             join_handle: Some(join_handle),
             ct: transport_task_ct.clone(),
             _drop_guard: transport_task_ct.drop_guard(),
+            state,
+            session_id,
+            memory,
+            close_reason,
         }
     }
 }
@@ -151,6 +204,9 @@ pub struct WorkerContext<W: Worker> {
     pub to_handler_tx: tokio::sync::mpsc::Sender<RxJsonRpcMessage<W::Role>>,
     pub from_handler_rx: tokio::sync::mpsc::Receiver<WorkerSendRequest<W>>,
     pub cancellation_token: CancellationToken,
+    state_tx: tokio::sync::watch::Sender<ConnectionState>,
+    session_id: Arc<OnceLock<Arc<str>>>,
+    memory: MemoryAccount,
 }
 
 impl<W: Worker> WorkerContext<W> {
@@ -164,6 +220,27 @@ impl<W: Worker> WorkerContext<W> {
             .map_err(|_| WorkerQuitReason::HandlerTerminated)
     }
 
+    /// Report the connection state, see [`Transport::state_changes`].
+    pub fn set_connection_state(&self, state: ConnectionState) {
+        self.state_tx.send_if_modified(|current| {
+            let modified = *current != state;
+            *current = state;
+            modified
+        });
+    }
+
+    /// Report the id of the session, see [`Transport::session_id`]. Only the
+    /// first one counts.
+    pub fn set_session_id(&self, session_id: Arc<str>) {
+        let _ = self.session_id.set(session_id);
+    }
+
+    /// Where to record what the worker buffers, see
+    /// [`Transport::memory_account`].
+    pub fn memory_account(&self) -> &MemoryAccount {
+        &self.memory
+    }
+
     pub async fn recv_from_handler(
         &mut self,
     ) -> Result<WorkerSendRequest<W>, WorkerQuitReason<W::Error>> {
@@ -196,6 +273,19 @@ impl<W: Worker> Transport<W::Role> for WorkerTransport<W> {
     async fn receive(&mut self) -> Option<RxJsonRpcMessage<W::Role>> {
         self.rx.recv().await
     }
+    fn state_changes(&self) -> Option<tokio::sync::watch::Receiver<ConnectionState>> {
+        Some(self.state.clone())
+    }
+    fn session_id(&self) -> Option<Arc<str>> {
+        self.session_id.get().cloned()
+    }
+    fn memory_account(&self) -> Option<MemoryAccount> {
+        Some(self.memory.clone())
+    }
+    fn close_reason(&self) -> Option<CloseReason> {
+        self.close_reason.get().cloned()
+    }
+
     async fn close(&mut self) -> Result<(), Self::Error> {
         if let Some(handle) = self.join_handle.take() {
             self.ct.cancel();
@@ -0,0 +1,437 @@
+//! Helpers for the URI schemes resources use the most.
+//!
+//! - `file://`: [`file_uri`] and [`file_path`] convert between local paths
+//!   and URIs, percent-encoding what needs to be and handling Windows drive
+//!   letters and UNC shares. The `_with_style` variants take the path style
+//!   explicitly, e.g. to build URIs for a remote Windows machine on Linux.
+//! - `data:`: [`DataUri`] parses and builds inline content.
+//! - anything else, e.g. `git://`: [`encode_path`] and [`decode`] for the
+//!   path segments.
+//!
+//! ```rust
+//! # use rmcp::uri::{PathStyle, file_uri_with_style, file_path_with_style};
+//! let uri = file_uri_with_style(r"C:\Users\me\My Notes.md", PathStyle::Windows)?;
+//! assert_eq!(uri, "file:///C:/Users/me/My%20Notes.md");
+//! assert_eq!(
+//!     file_path_with_style(&uri, PathStyle::Windows)?,
+//!     r"C:\Users\me\My Notes.md"
+//! );
+//! # Ok::<_, rmcp::uri::UriError>(())
+//! ```
+use std::path::{Path, PathBuf};
+
+/// Why a URI couldn't be built or parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum UriError {
+    #[error("not a {expected} URI: {uri}")]
+    WrongScheme { expected: &'static str, uri: String },
+    #[error("path is not absolute: {0}")]
+    RelativePath(String),
+    #[error("path is not valid UTF-8")]
+    NonUtf8Path,
+    #[error("file URI with host {0} doesn't name a local path")]
+    RemoteHost(String),
+    #[error("invalid percent-encoding in {0}")]
+    InvalidPercentEncoding(String),
+    #[error("data URI has no ',' before its data")]
+    MissingData,
+    #[error("invalid base64 data: {0}")]
+    InvalidBase64(String),
+}
+
+/// How paths are written, see [`file_uri_with_style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathStyle {
+    /// `/home/me/notes.md`
+    Unix,
+    /// `C:\Users\me\notes.md` or `\\server\share\notes.md`
+    Windows,
+}
+
+impl PathStyle {
+    /// The style of the platform this runs on.
+    pub const NATIVE: PathStyle = if cfg!(windows) {
+        PathStyle::Windows
+    } else {
+        PathStyle::Unix
+    };
+}
+
+/// Percent-encode `path` for the path of a URI: everything but unreserved
+/// characters, `/` and `:` is encoded, including non-ASCII characters as
+/// their UTF-8 bytes.
+pub fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Decode the percent-encoded `text`, which must decode to UTF-8.
+pub fn decode(text: &str) -> Result<String, UriError> {
+    let invalid = || UriError::InvalidPercentEncoding(text.to_owned());
+    let bytes = decode_bytes(text).ok_or_else(invalid)?;
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+fn decode_bytes(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2)?;
+            let hex = std::str::from_utf8(hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    Some(bytes)
+}
+
+/// The `file://` URI of the absolute local `path`.
+pub fn file_uri(path: impl AsRef<Path>) -> Result<String, UriError> {
+    let path = path.as_ref().to_str().ok_or(UriError::NonUtf8Path)?;
+    file_uri_with_style(path, PathStyle::NATIVE)
+}
+
+/// The local path a `file://` URI points to.
+pub fn file_path(uri: &str) -> Result<PathBuf, UriError> {
+    file_path_with_style(uri, PathStyle::NATIVE).map(PathBuf::from)
+}
+
+/// The `file://` URI of the absolute `path`, written in `style`.
+///
+/// Windows paths may use either separator. Drive letters are kept as is,
+/// UNC shares become the host of the URI.
+pub fn file_uri_with_style(path: &str, style: PathStyle) -> Result<String, UriError> {
+    let relative = || UriError::RelativePath(path.to_owned());
+    match style {
+        PathStyle::Unix => {
+            if !path.starts_with('/') {
+                return Err(relative());
+            }
+            Ok(format!("file://{}", encode_path(path)))
+        }
+        PathStyle::Windows => {
+            let path = path.replace('\\', "/");
+            if let Some(unc) = path.strip_prefix("//") {
+                let (host, share) = unc.split_once('/').ok_or_else(relative)?;
+                if host.is_empty() || share.is_empty() {
+                    return Err(relative());
+                }
+                return Ok(format!(
+                    "file://{}/{}",
+                    encode_path(host),
+                    encode_path(share)
+                ));
+            }
+            if !has_drive(&path) || !matches!(path.as_bytes().get(2), None | Some(b'/')) {
+                return Err(relative());
+            }
+            Ok(format!("file:///{}", encode_path(&path)))
+        }
+    }
+}
+
+/// The path a `file://` URI points to, written in `style`.
+///
+/// Accepts `file:/path` and `localhost` as host too. Other hosts are only
+/// valid for Windows, as UNC shares.
+pub fn file_path_with_style(uri: &str, style: PathStyle) -> Result<String, UriError> {
+    let rest = strip_scheme(uri, "file").ok_or_else(|| UriError::WrongScheme {
+        expected: "file",
+        uri: uri.to_owned(),
+    })?;
+    // a query or fragment isn't part of the path
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (host, path) = match rest.strip_prefix("//") {
+        Some(authority) => match authority.find('/') {
+            Some(slash) => authority.split_at(slash),
+            None => (authority, "/"),
+        },
+        None => ("", rest),
+    };
+    if !path.starts_with('/') {
+        return Err(UriError::RelativePath(uri.to_owned()));
+    }
+    let host = decode(host)?;
+    let path = decode(path)?;
+    let local = host.is_empty() || host.eq_ignore_ascii_case("localhost");
+    match style {
+        PathStyle::Unix if local => Ok(path),
+        PathStyle::Unix => Err(UriError::RemoteHost(host)),
+        PathStyle::Windows if local => {
+            let path = &path[1..];
+            // some clients write the drive as `C|`
+            let path = match path.as_bytes() {
+                [letter, b'|', ..] if letter.is_ascii_alphabetic() => {
+                    format!("{}:{}", *letter as char, &path[2..])
+                }
+                _ => path.to_owned(),
+            };
+            if !has_drive(&path) {
+                return Err(UriError::RelativePath(uri.to_owned()));
+            }
+            let path = path.replace('/', "\\");
+            // `C:` alone is the current directory of drive C, not its root
+            Ok(if path.len() == 2 { path + "\\" } else { path })
+        }
+        PathStyle::Windows => Ok(format!("\\\\{host}{}", path.replace('/', "\\"))),
+    }
+}
+
+fn has_drive(path: &str) -> bool {
+    matches!(path.as_bytes(), [letter, b':', ..] if letter.is_ascii_alphabetic())
+}
+
+/// `uri` without `scheme:`, which matches case-insensitively.
+fn strip_scheme<'a>(uri: &'a str, scheme: &str) -> Option<&'a str> {
+    let (found, rest) = uri.split_once(':')?;
+    found.eq_ignore_ascii_case(scheme).then_some(rest)
+}
+
+/// Inline content as a `data:` URI, like `data:image/png;base64,iVBORw0...`.
+///
+/// ```rust
+/// # use rmcp::uri::DataUri;
+/// let data = DataUri::parse("data:text/plain,hello%20world")?;
+/// assert_eq!(data.mime_type, "text/plain");
+/// assert_eq!(data.data, b"hello world");
+/// assert_eq!(data.to_uri(), "data:text/plain;base64,aGVsbG8gd29ybGQ=");
+/// # Ok::<_, rmcp::uri::UriError>(())
+/// ```
+#[cfg(feature = "base64")]
+#[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUri {
+    /// The media type with its parameters, e.g. `text/plain;charset=utf-8`.
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+#[cfg(feature = "base64")]
+impl DataUri {
+    /// The media type of data URIs that don't name one.
+    pub const DEFAULT_MIME_TYPE: &str = "text/plain;charset=US-ASCII";
+
+    pub fn new(mime_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            mime_type: mime_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Parse a `data:` URI, base64 or percent-encoded.
+    pub fn parse(uri: &str) -> Result<Self, UriError> {
+        use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+
+        let rest = strip_scheme(uri, "data").ok_or_else(|| UriError::WrongScheme {
+            expected: "data",
+            uri: uri.to_owned(),
+        })?;
+        let (header, data) = rest.split_once(',').ok_or(UriError::MissingData)?;
+        let (mime_type, base64) = match header.strip_suffix(";base64") {
+            Some(mime_type) => (mime_type, true),
+            None => (header, false),
+        };
+        let mime_type = match mime_type {
+            "" => Self::DEFAULT_MIME_TYPE.to_owned(),
+            // parameters without a type keep the default type
+            params if params.starts_with(';') => format!("text/plain{params}"),
+            mime_type => mime_type.to_owned(),
+        };
+        let data = if base64 {
+            let encoded = decode_bytes(data)
+                .ok_or_else(|| UriError::InvalidPercentEncoding(data.to_owned()))?;
+            BASE64_STANDARD
+                .decode(encoded)
+                .map_err(|error| UriError::InvalidBase64(error.to_string()))?
+        } else {
+            decode_bytes(data).ok_or_else(|| UriError::InvalidPercentEncoding(data.to_owned()))?
+        };
+        Ok(Self { mime_type, data })
+    }
+
+    /// The data as a base64 `data:` URI.
+    pub fn to_uri(&self) -> String {
+        use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+
+        format!(
+            "data:{};base64,{}",
+            self.mime_type,
+            BASE64_STANDARD.encode(&self.data)
+        )
+    }
+
+    /// The data as the contents of the resource at `uri`: text for `text/*`
+    /// types holding UTF-8, a blob otherwise.
+    pub fn into_contents(self, uri: impl Into<String>) -> crate::model::ResourceContents {
+        use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+
+        use crate::model::ResourceContents;
+
+        let uri = uri.into();
+        let mime_type = Some(self.mime_type);
+        let is_text = mime_type
+            .as_deref()
+            .is_some_and(|mime_type| mime_type.starts_with("text/"));
+        match String::from_utf8(self.data) {
+            Ok(text) if is_text => ResourceContents::TextResourceContents {
+                uri,
+                mime_type,
+                text,
+                meta: None,
+                size: None,
+                last_modified: None,
+            },
+            Ok(text) => ResourceContents::BlobResourceContents {
+                uri,
+                mime_type,
+                blob: BASE64_STANDARD.encode(text),
+                meta: None,
+                size: None,
+                last_modified: None,
+            },
+            Err(error) => ResourceContents::BlobResourceContents {
+                uri,
+                mime_type,
+                blob: BASE64_STANDARD.encode(error.into_bytes()),
+                meta: None,
+                size: None,
+                last_modified: None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix_file_uris() {
+        let uri = file_uri_with_style("/home/me/a b#1/ü.md", PathStyle::Unix).unwrap();
+        assert_eq!(uri, "file:///home/me/a%20b%231/%C3%BC.md");
+        assert_eq!(
+            file_path_with_style(&uri, PathStyle::Unix).unwrap(),
+            "/home/me/a b#1/ü.md"
+        );
+        assert_eq!(
+            file_path_with_style("file://localhost/etc/hosts", PathStyle::Unix).unwrap(),
+            "/etc/hosts"
+        );
+        assert_eq!(
+            file_path_with_style("FILE:/etc/hosts?x#y", PathStyle::Unix).unwrap(),
+            "/etc/hosts"
+        );
+        assert!(matches!(
+            file_uri_with_style("notes.md", PathStyle::Unix),
+            Err(UriError::RelativePath(_))
+        ));
+        assert!(matches!(
+            file_path_with_style("file://server/share", PathStyle::Unix),
+            Err(UriError::RemoteHost(_))
+        ));
+        assert!(matches!(
+            file_path_with_style("https://example.com/", PathStyle::Unix),
+            Err(UriError::WrongScheme { .. })
+        ));
+        assert!(matches!(
+            file_path_with_style("file:///bad%zz", PathStyle::Unix),
+            Err(UriError::InvalidPercentEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn test_windows_file_uris() {
+        let uri = file_uri_with_style(r"C:\Program Files\x.txt", PathStyle::Windows).unwrap();
+        assert_eq!(uri, "file:///C:/Program%20Files/x.txt");
+        assert_eq!(
+            file_path_with_style(&uri, PathStyle::Windows).unwrap(),
+            r"C:\Program Files\x.txt"
+        );
+        assert_eq!(
+            file_uri_with_style("d:/", PathStyle::Windows).unwrap(),
+            "file:///d:/"
+        );
+        assert_eq!(
+            file_path_with_style("file:///C:", PathStyle::Windows).unwrap(),
+            r"C:\"
+        );
+        assert_eq!(
+            file_path_with_style("file:///c|/temp", PathStyle::Windows).unwrap(),
+            r"c:\temp"
+        );
+
+        let unc = file_uri_with_style(r"\\server\share\dir\f.txt", PathStyle::Windows).unwrap();
+        assert_eq!(unc, "file://server/share/dir/f.txt");
+        assert_eq!(
+            file_path_with_style(&unc, PathStyle::Windows).unwrap(),
+            r"\\server\share\dir\f.txt"
+        );
+
+        for relative in [r"dir\f.txt", r"C:f.txt", r"\\server"] {
+            assert!(
+                matches!(
+                    file_uri_with_style(relative, PathStyle::Windows),
+                    Err(UriError::RelativePath(_))
+                ),
+                "{relative}"
+            );
+        }
+        assert!(matches!(
+            file_path_with_style("file:///home/me", PathStyle::Windows),
+            Err(UriError::RelativePath(_))
+        ));
+    }
+
+    #[test]
+    fn test_native_file_uris() {
+        let path = std::env::temp_dir().join("rmcp uri.txt");
+        let uri = file_uri(&path).unwrap();
+        assert!(uri.starts_with("file://"));
+        assert_eq!(file_path(&uri).unwrap(), path);
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_data_uris() {
+        let data = DataUri::parse("data:,A%20brief%20note").unwrap();
+        assert_eq!(data.mime_type, DataUri::DEFAULT_MIME_TYPE);
+        assert_eq!(data.data, b"A brief note");
+
+        let data = DataUri::parse("data:;charset=utf-8,%C3%BC").unwrap();
+        assert_eq!(data.mime_type, "text/plain;charset=utf-8");
+        assert_eq!(data.data, "ü".as_bytes());
+
+        let png = DataUri::new("image/png", [0x89, b'P', b'N', b'G']);
+        assert_eq!(DataUri::parse(&png.to_uri()).unwrap(), png);
+        assert!(matches!(
+            png.clone().into_contents("memo://logo"),
+            crate::model::ResourceContents::BlobResourceContents { .. }
+        ));
+        assert!(matches!(
+            DataUri::new("text/markdown", "# hi").into_contents("memo://notes"),
+            crate::model::ResourceContents::TextResourceContents { .. }
+        ));
+
+        assert!(matches!(
+            DataUri::parse("data:text/plain"),
+            Err(UriError::MissingData)
+        ));
+        assert!(matches!(
+            DataUri::parse("data:;base64,!!"),
+            Err(UriError::InvalidBase64(_))
+        ));
+    }
+}
@@ -74,7 +74,7 @@ impl ClientHandler for TestClientHandler {
         Ok(CreateMessageResult {
             message: SamplingMessage {
                 role: Role::Assistant,
-                content: Content::text(response.to_string()),
+                content: Content::text(response.to_string()).into(),
             },
             model: "test-model".to_string(),
             stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.to_string()),
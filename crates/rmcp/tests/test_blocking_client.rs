@@ -0,0 +1,71 @@
+//cargo test --test test_blocking_client --features "server client"
+use rmcp::{
+    ServiceExt,
+    blocking::Client,
+    handler::server::{builder::Server, wrapper::Parameters},
+    model::{CallToolRequestParams, object},
+    schemars,
+};
+use serde_json::json;
+
+/// Add two numbers.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct AddArgs {
+    a: i64,
+    b: i64,
+}
+
+fn spawn_server(runtime: &tokio::runtime::Runtime) -> tokio::io::DuplexStream {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    runtime.spawn(async move {
+        let server = Server::builder()
+            .tool("add", |Parameters(args): Parameters<AddArgs>| async move {
+                (args.a + args.b).to_string()
+            })
+            .build()
+            .serve(server_transport)
+            .await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    client_transport
+}
+
+#[test]
+fn test_blocking_client_calls_tools() -> anyhow::Result<()> {
+    let server_runtime = tokio::runtime::Runtime::new()?;
+    let transport = spawn_server(&server_runtime);
+
+    let client = Client::connect(|| transport)?;
+    assert!(client.peer_info().is_some());
+    let tools = client.list_all_tools()?;
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name, "add");
+
+    let result = client.call_tool(CallToolRequestParams {
+        meta: None,
+        name: "add".into(),
+        arguments: Some(object(json!({ "a": 1, "b": 2 }))),
+        task: None,
+    })?;
+    assert_eq!(result.content[0].as_text().unwrap().text, "3");
+
+    client.close()?;
+    Ok(())
+}
+
+#[test]
+fn test_blocking_client_drop_closes_session() -> anyhow::Result<()> {
+    let server_runtime = tokio::runtime::Runtime::new()?;
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = server_runtime.spawn(async move {
+        let server = Server::builder().build().serve(server_transport).await?;
+        anyhow::Ok(server.waiting().await?)
+    });
+
+    let client = Client::connect(|| client_transport)?;
+    drop(client);
+    let reason = server_runtime.block_on(server)??;
+    assert!(matches!(reason, rmcp::service::QuitReason::Closed));
+    Ok(())
+}
@@ -0,0 +1,65 @@
+//cargo test --test test_blocking_tool --features "client server macros"
+use std::{
+    sync::{Arc, Mutex, mpsc},
+    time::Duration,
+};
+
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::router::tool::ToolRouter,
+    model::{CallToolRequestParams, Content},
+    tool, tool_handler, tool_router,
+};
+
+#[derive(Clone)]
+struct Crunch {
+    release: Arc<Mutex<mpsc::Receiver<()>>>,
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Crunch {
+    /// Blocks its thread until the test releases it.
+    #[tool(blocking)]
+    fn crunch(&self) -> Content {
+        self.release.lock().unwrap().recv().unwrap();
+        Content::text("crunched")
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Crunch {}
+
+// a single worker, which the tool would otherwise keep to itself
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_blocking_tool_keeps_session_responsive() -> anyhow::Result<()> {
+    let (release, released) = mpsc::channel();
+    let server = Crunch {
+        release: Arc::new(Mutex::new(released)),
+        tool_router: Crunch::tool_router(),
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(server.serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let _server = server.await??;
+
+    let peer = client.peer().clone();
+    let call = tokio::spawn(async move {
+        peer.call_tool(CallToolRequestParams {
+            meta: None,
+            name: "crunch".into(),
+            arguments: None,
+            task: None,
+        })
+        .await
+    });
+    // answered while the tool is still blocked
+    let tools = tokio::time::timeout(Duration::from_secs(5), client.list_all_tools()).await??;
+    assert_eq!(tools.len(), 1);
+
+    release.send(())?;
+    let result = call.await??;
+    assert_eq!(result.content[0].as_text().unwrap().text, "crunched");
+    client.cancel().await?;
+    Ok(())
+}
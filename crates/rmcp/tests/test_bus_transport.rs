@@ -0,0 +1,145 @@
+//cargo test --test test_bus_transport --features "client server transport-bus"
+use std::sync::{Arc, Mutex};
+
+use futures::{FutureExt, StreamExt, stream::BoxStream};
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult, Content, ServerRequest},
+    service::RequestContext,
+    transport::{
+        Transport,
+        bus::{BusMessage, BusServer, BusTransport, MessageBus},
+    },
+};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+type Subscriptions = Arc<Mutex<Vec<(String, mpsc::UnboundedSender<BusMessage>)>>>;
+
+/// A broker in memory, with NATS-like subjects.
+#[derive(Clone, Default)]
+struct MemoryBus {
+    subscriptions: Subscriptions,
+}
+
+fn matches(topic: &str, filter: &str) -> bool {
+    let (topic, filter) = (topic.split('.'), filter.split('.'));
+    topic.clone().count() == filter.clone().count()
+        && topic
+            .zip(filter)
+            .all(|(level, filter)| filter == "*" || level == filter)
+}
+
+impl MessageBus for MemoryBus {
+    type Error = std::io::Error;
+    const SEPARATOR: char = '.';
+    const WILDCARD: &'static str = "*";
+
+    async fn publish(&self, message: BusMessage) -> Result<(), Self::Error> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        subscriptions.retain(|(filter, sender)| {
+            !matches(&message.topic, filter) || sender.send(message.clone()).is_ok()
+        });
+        Ok(())
+    }
+
+    async fn subscribe(
+        &self,
+        filter: String,
+    ) -> Result<BoxStream<'static, BusMessage>, Self::Error> {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        self.subscriptions.lock().unwrap().push((filter, sender));
+        Ok(futures::stream::poll_fn(move |cx| receiver.poll_recv(cx)).boxed())
+    }
+}
+
+struct Server;
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        // a request the other way, answered on its reply topic
+        context
+            .peer
+            .send_request(ServerRequest::PingRequest(Default::default()))
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("ping failed: {e}"), None))?;
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "called {} after a ping",
+            request.name
+        ))]))
+    }
+}
+
+fn call(name: &'static str) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: None,
+        task: None,
+    }
+}
+
+#[tokio::test]
+async fn test_bus_sessions() -> anyhow::Result<()> {
+    let bus = MemoryBus::default();
+    let mut spy = bus.subscribe("mcp.*.*.*".to_owned()).await?;
+    let ct = CancellationToken::new();
+    tokio::spawn({
+        let (bus, ct) = (bus.clone(), ct.clone());
+        async move {
+            BusServer::new(|| Ok(Server))
+                .with_cancellation_token(ct)
+                .serve(bus, "mcp")
+                .await
+        }
+    });
+    while bus.subscriptions.lock().unwrap().len() < 2 {
+        tokio::task::yield_now().await;
+    }
+
+    let transport = BusTransport::connect(bus.clone(), "mcp").await?;
+    let session = Transport::<rmcp::RoleClient>::session_id(&transport).expect("a session id");
+    let client = ().serve(transport).await?;
+    let other = ().serve(BusTransport::connect(bus.clone(), "mcp").await?).await?;
+
+    let result = client.call_tool(call("echo")).await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("called echo after a ping")
+    );
+    let result = other.call_tool(call("other")).await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("called other after a ping")
+    );
+    client.cancel().await?;
+
+    let messages: Vec<_> = std::iter::from_fn(|| spy.next().now_or_never().flatten())
+        .filter(|message| message.topic.starts_with(&format!("mcp.{session}.")))
+        .collect();
+    let initialize = &messages[0];
+    assert_eq!(initialize.topic, format!("mcp.{session}.server.request"));
+    assert_eq!(
+        initialize.reply_to.as_deref(),
+        Some(format!("mcp.{session}.client.response").as_str())
+    );
+    assert!(
+        messages
+            .iter()
+            .any(|message| message.topic == format!("mcp.{session}.client.request"))
+    );
+    // the client ended the session
+    let end = messages
+        .iter()
+        .find(|message| message.payload.is_empty())
+        .expect("an empty message");
+    assert_eq!(end.topic, format!("mcp.{session}.server.notification"));
+
+    other.cancel().await?;
+    ct.cancel();
+    Ok(())
+}
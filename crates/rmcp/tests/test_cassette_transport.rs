@@ -0,0 +1,82 @@
+//cargo test --test test_cassette_transport --features "client server transport-cassette"
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult, Content},
+    service::RequestContext,
+    transport::{
+        async_rw::AsyncRwTransport,
+        cassette::{CassetteRecorder, CassetteTransport},
+    },
+};
+
+struct Server;
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "called {} with {:?}",
+            request.name, request.arguments
+        ))]))
+    }
+}
+
+fn call(name: &'static str, value: u32) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: serde_json::json!({ "value": value }).as_object().cloned(),
+        task: None,
+    }
+}
+
+#[tokio::test]
+async fn test_cassette_playback() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("rmcp-cassette-{}.jsonl", std::process::id()));
+
+    // record a session with the real server
+    let (client_io, server_io) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move {
+        let (read, write) = tokio::io::split(server_io);
+        Server
+            .serve(AsyncRwTransport::new_server(read, write))
+            .await?
+            .waiting()
+            .await?;
+        anyhow::Ok(())
+    });
+    let (read, write) = tokio::io::split(client_io);
+    let transport = CassetteRecorder::create(AsyncRwTransport::new_client(read, write), &path)?;
+    let client = ().serve(transport).await?;
+    let first = client.call_tool(call("echo", 1)).await?;
+    let second = client.call_tool(call("echo", 2)).await?;
+    client.cancel().await?;
+    server.await??;
+
+    // play it back, without the server, the calls in another order
+    let transport = CassetteTransport::open(&path)?;
+    let client = ().serve(transport).await?;
+    assert!(client.peer_info().is_some());
+    let replayed = client.call_tool(call("echo", 2)).await?;
+    assert_eq!(replayed.text(), second.text());
+    let replayed = client.call_tool(call("echo", 1)).await?;
+    assert_eq!(replayed.text(), first.text());
+
+    // not recorded, or recorded once only
+    let error = client
+        .call_tool(call("echo", 3))
+        .await
+        .expect_err("no match");
+    assert!(
+        error.to_string().contains("no recorded tools/call request"),
+        "{error}"
+    );
+    assert!(client.call_tool(call("echo", 1)).await.is_err());
+    client.cancel().await?;
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
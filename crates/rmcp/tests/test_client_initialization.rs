@@ -1,16 +1,18 @@
 // cargo test --features "server client" --package rmcp test_client_initialization
 mod common;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, time::Duration};
 
-use common::handlers::TestClientHandler;
+use common::handlers::{TestClientHandler, TestServer};
 use rmcp::{
     ServiceExt,
     model::{
         ErrorCode, ErrorData, JsonRpcError, JsonRpcVersion2_0, RequestId, ServerJsonRpcMessage,
     },
+    service::{ClientInitializeError, ClientOptions, serve_client_with_options},
     transport::{IntoTransport, Transport},
 };
+use tokio::io::AsyncWriteExt;
 
 #[tokio::test]
 async fn test_client_init_handles_jsonrpc_error() {
@@ -49,3 +51,127 @@ async fn test_client_init_handles_jsonrpc_error() {
         _ => panic!("Expected ClientInitializeError::JsonRpcError"),
     }
 }
+
+#[tokio::test(start_paused = true)]
+async fn test_client_init_times_out() {
+    let (server_transport, client_transport) = tokio::io::duplex(1024);
+    let mut server = IntoTransport::<rmcp::RoleServer, _, _>::into_transport(server_transport);
+    let server = tokio::spawn(async move {
+        // read the request, never answer
+        let init_request = server.receive().await;
+        std::future::pending::<()>().await;
+        drop((server, init_request));
+    });
+
+    let options = ClientOptions::default().handshake_timeout(Duration::from_secs(5));
+    let result = serve_client_with_options(
+        TestClientHandler::new(true, true),
+        client_transport,
+        options,
+    )
+    .await;
+
+    match result {
+        Err(ClientInitializeError::Timeout {
+            timeout,
+            diagnostics,
+        }) => {
+            assert_eq!(timeout, Duration::from_secs(5));
+            assert_eq!(diagnostics.sent["method"], "initialize");
+            assert!(diagnostics.received.is_empty());
+        }
+        _ => panic!("Expected ClientInitializeError::Timeout"),
+    }
+    server.abort();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_client_init_timeout_starts_after_sending() {
+    // too small for the request, so sending it waits for the server to read
+    let (server_transport, client_transport) = tokio::io::duplex(16);
+    let mut server = IntoTransport::<rmcp::RoleServer, _, _>::into_transport(server_transport);
+    let server = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let init_request = server.receive().await;
+        let error_msg = ServerJsonRpcMessage::Error(JsonRpcError {
+            jsonrpc: JsonRpcVersion2_0,
+            id: RequestId::Number(0),
+            error: ErrorData::invalid_request("slow to start", None),
+        });
+        let _: Result<(), _> = server.send(error_msg).await;
+        std::future::pending::<()>().await;
+        drop((server, init_request));
+    });
+
+    let options = ClientOptions::default().handshake_timeout(Duration::from_secs(5));
+    let result = serve_client_with_options(
+        TestClientHandler::new(true, true),
+        client_transport,
+        options,
+    )
+    .await;
+
+    match result {
+        Err(ClientInitializeError::JsonRpcError(error)) => {
+            assert_eq!(error.message, "slow to start");
+        }
+        _ => panic!("Expected ClientInitializeError::JsonRpcError"),
+    }
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_client_init_diagnoses_invalid_output() {
+    let (mut server_transport, client_transport) = tokio::io::duplex(1024);
+    tokio::spawn(async move {
+        // a server logging to stdout
+        server_transport
+            .write_all(b"Starting server v1.0\n")
+            .await
+            .unwrap();
+    });
+
+    let result = TestClientHandler::new(true, true)
+        .serve(client_transport)
+        .await;
+
+    let Err(error @ ClientInitializeError::Closed(_)) = result else {
+        panic!("Expected ClientInitializeError::Closed");
+    };
+    let diagnostics = error.diagnostics().unwrap();
+    assert_eq!(diagnostics.sent["method"], "initialize");
+    assert_eq!(
+        diagnostics.invalid_bytes(),
+        Some(&b"Starting server v1.0"[..])
+    );
+    let message = error.to_string();
+    assert!(message.contains("53 74 61 72"), "{message}");
+    assert!(message.contains("|Starting server |"), "{message}");
+}
+
+#[tokio::test]
+async fn test_client_init_compatibility_report() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let options = ClientOptions::default()
+        .want_feature("logging")
+        .want_feature("resources.subscribe")
+        .log_compatibility();
+    let (server, client) = tokio::join!(
+        TestServer::new().serve(server_transport),
+        serve_client_with_options(
+            TestClientHandler::new(true, true),
+            client_transport,
+            options
+        ),
+    );
+    let (server, client) = (server?, client?);
+
+    let report = client.peer().compatibility_report().unwrap();
+    assert!(!report.is_downgraded());
+    assert_eq!(report.missing, ["resources.subscribe"]);
+    assert!(server.peer().compatibility_report().unwrap().is_complete());
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
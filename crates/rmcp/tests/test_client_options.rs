@@ -0,0 +1,89 @@
+//cargo test --test test_client_options --features "server client"
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    ClientHandler, ErrorData, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        Implementation, InitializeRequestParams, InitializeResult, Meta, ProtocolVersion,
+        ServerInfo, object,
+    },
+    service::{ClientOptions, RequestContext, serve_client_with_options},
+};
+use serde_json::json;
+
+#[derive(Debug, Clone, Default)]
+struct Server {
+    initialize_meta: Arc<Mutex<Option<Meta>>>,
+}
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some("be nice".into()),
+            ..Default::default()
+        }
+    }
+
+    async fn initialize(
+        &self,
+        request: InitializeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, ErrorData> {
+        *self.initialize_meta.lock().unwrap() = Some(context.meta.clone());
+        context.peer.set_peer_info(request);
+        Ok(self.get_info())
+    }
+}
+
+#[tokio::test]
+async fn test_client_options_customize_initialize() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let service = Server::default();
+    let server = tokio::spawn(service.clone().serve(server_transport));
+    let mut meta = Meta::new();
+    meta.insert("traceparent".into(), json!("00-abc-def-01"));
+    let options = ClientOptions::default()
+        .protocol_version(ProtocolVersion::V_2025_03_26)
+        .client_info(Implementation {
+            name: "my-host".into(),
+            version: "1.2.0".into(),
+            ..Default::default()
+        })
+        .experimental("acme/streaming", object(json!({ "chunked": true })))
+        .meta(meta.clone());
+    let client = serve_client_with_options((), client_transport, options).await?;
+    let server = server.await??;
+
+    let sent = server.peer_info().expect("client info");
+    assert_eq!(sent.protocol_version, ProtocolVersion::V_2025_03_26);
+    assert_eq!(sent.client_info.name, "my-host");
+    assert_eq!(sent.client_info.version, "1.2.0");
+    assert_eq!(
+        sent.capabilities.experimental.as_ref().unwrap()["acme/streaming"],
+        object(json!({ "chunked": true }))
+    );
+    assert_eq!(
+        service.initialize_meta.lock().unwrap().as_ref(),
+        Some(&meta)
+    );
+
+    let received = client.initialize_result().expect("initialize result");
+    assert_eq!(received.instructions.as_deref(), Some("be nice"));
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_default_options_keep_handler_info() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server::default().serve(server_transport));
+    let client = serve_client_with_options((), client_transport, ClientOptions::default()).await?;
+    let server = server.await??;
+
+    let sent = server.peer_info().expect("client info");
+    assert_eq!(sent, &ClientHandler::get_info(&()));
+
+    client.cancel().await?;
+    Ok(())
+}
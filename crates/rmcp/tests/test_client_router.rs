@@ -0,0 +1,113 @@
+//cargo test --test test_client_router --features "server client"
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::client::router::ClientRouter,
+    model::{
+        Content, CreateMessageRequestParams, CreateMessageResult, LoggingLevel,
+        LoggingMessageNotificationParam, Role, SamplingMessage,
+    },
+};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+fn ask(text: &str) -> CreateMessageRequestParams {
+    CreateMessageRequestParams {
+        meta: None,
+        task: None,
+        messages: vec![SamplingMessage {
+            role: Role::User,
+            content: Content::text(text).into(),
+        }],
+        model_preferences: None,
+        system_prompt: None,
+        include_context: None,
+        temperature: None,
+        max_tokens: 16,
+        stop_sequences: None,
+        metadata: None,
+    }
+}
+
+#[tokio::test]
+async fn test_closures_handle_requests_and_notifications() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server.serve(server_transport));
+    let (logs_tx, mut logs) = mpsc::unbounded_channel();
+    let (tool_list_tx, mut tool_list_changes) = mpsc::unbounded_channel();
+    let router = ClientRouter::default()
+        .on_create_message(|params, _context| async move {
+            let question = params.messages[0].content.as_text().unwrap().text.clone();
+            Ok(CreateMessageResult {
+                message: SamplingMessage {
+                    role: Role::Assistant,
+                    content: Content::text(format!("you said: {question}")).into(),
+                },
+                model: "echo".into(),
+                stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.into()),
+            })
+        })
+        .on_logging_message(move |params, _context| {
+            let logs_tx = logs_tx.clone();
+            async move {
+                let _ = logs_tx.send(params.data);
+            }
+        })
+        .on_tool_list_changed(move |_context| {
+            let _ = tool_list_tx.send(());
+            async {}
+        });
+    let client = router.serve(client_transport).await?;
+    let server = server.await??;
+
+    let capabilities = server.peer().peer_capabilities().expect("client info");
+    assert!(capabilities.sampling.is_some());
+    assert!(capabilities.elicitation.is_none());
+
+    let answer = server.peer().create_message(ask("hi")).await?;
+    assert_eq!(answer.model, "echo");
+    assert_eq!(
+        answer.message.content.as_text().unwrap().text,
+        "you said: hi"
+    );
+
+    server
+        .peer()
+        .notify_logging_message(LoggingMessageNotificationParam {
+            level: LoggingLevel::Info,
+            logger: None,
+            data: json!("indexing done"),
+        })
+        .await?;
+    assert_eq!(logs.recv().await, Some(json!("indexing done")));
+
+    server.peer().notify_tool_list_changed().await?;
+    server.peer().notify_prompt_list_changed().await?;
+    server.peer().notify_tool_list_changed().await?;
+    // notifications are handled concurrently, so only count them
+    tool_list_changes.recv().await;
+    tool_list_changes.recv().await;
+    assert!(tool_list_changes.try_recv().is_err());
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unset_callbacks_fall_back_to_inner_handler() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server.serve(server_transport));
+    let client = ClientRouter::default().serve(client_transport).await?;
+    let server = server.await??;
+
+    let capabilities = server.peer().peer_capabilities().expect("client info");
+    assert!(capabilities.sampling.is_none());
+    assert!(server.peer().create_message(ask("hi")).await.is_err());
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//cargo test --test test_close_reason --features "client server"
+use rmcp::{
+    ServerHandler, ServiceExt,
+    service::{CloseReason, QuitReason},
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+#[derive(Debug, Clone, Default)]
+struct Idle;
+
+impl ServerHandler for Idle {}
+
+#[tokio::test]
+async fn test_protocol_error_keeps_an_excerpt() -> anyhow::Result<()> {
+    let (server_io, client_io) = tokio::io::duplex(4096);
+    // a server that answers the handshake, then sends garbage
+    let server = tokio::spawn(async move {
+        let (read, mut write) = tokio::io::split(server_io);
+        let mut lines = BufReader::new(read).lines();
+        let initialize: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await?.unwrap_or_default())?;
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": initialize["id"],
+            "result": {
+                "protocolVersion": "2025-06-18",
+                "capabilities": {},
+                "serverInfo": { "name": "raw", "version": "0" },
+            },
+        });
+        write.write_all(format!("{response}\n").as_bytes()).await?;
+        // notifications/initialized
+        lines.next_line().await?;
+        write.write_all(b"{\"jsonrpc\": oops}\n").await?;
+        anyhow::Ok((lines, write))
+    });
+    let client = ().serve(client_io).await?;
+    let _server = server.await??;
+
+    let reason = client.waiting().await?;
+    let QuitReason::Transport(CloseReason::ProtocolError { excerpt, .. }) = &reason else {
+        panic!("expected a protocol error, got {reason:?}");
+    };
+    assert_eq!(excerpt, "{\"jsonrpc\": oops}");
+    assert!(!reason.close_reason().should_reconnect());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_end_of_stream_is_reconnectable() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Idle.serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    server.cancel().await?;
+    let reason = client.waiting().await?;
+    assert!(matches!(reason, QuitReason::Closed));
+    assert!(reason.close_reason().should_reconnect());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancelled_is_not_reconnectable() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Idle.serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let _server = server.await??;
+
+    let reason = client.cancel().await?;
+    assert_eq!(reason.close_reason(), CloseReason::Cancelled);
+    assert!(!reason.close_reason().should_reconnect());
+    Ok(())
+}
+
+#[test]
+fn test_excerpt_is_bounded() {
+    let CloseReason::ProtocolError { excerpt, .. } =
+        CloseReason::protocol_error("bad", &"é".repeat(1000))
+    else {
+        unreachable!()
+    };
+    assert!(excerpt.len() <= 256);
+    assert!(excerpt.chars().all(|c| c == 'é'));
+}
@@ -0,0 +1,77 @@
+//cargo test --test test_connection_state --features "server client"
+use std::time::Duration;
+
+use rmcp::{
+    ServerHandler, ServiceExt,
+    service::{CloseReason, ConnectionState, ServiceConfig, SessionExpiry, SessionTimeouts},
+};
+
+#[derive(Debug, Clone, Default)]
+struct Idle;
+
+impl ServerHandler for Idle {}
+
+#[derive(Debug, Clone, Default)]
+struct ShortLived;
+
+impl ServerHandler for ShortLived {
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig {
+            session_timeouts: SessionTimeouts {
+                idle_timeout: Some(Duration::from_millis(50)),
+                max_duration: None,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_state_is_ready_then_closed() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Idle.serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    let mut state = client.state_changes();
+    assert!(state.borrow_and_update().is_ready());
+    assert!(server.state_changes().borrow().is_ready());
+
+    client.cancel().await?;
+    assert_eq!(
+        *state.borrow(),
+        ConnectionState::Closed {
+            reason: CloseReason::Cancelled
+        }
+    );
+    // the server sees the transport close
+    let mut server_state = server.state_changes();
+    server_state.wait_for(ConnectionState::is_closed).await?;
+    assert_eq!(
+        *server_state.borrow(),
+        ConnectionState::Closed {
+            reason: CloseReason::Closed
+        }
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_state_reports_session_expiry() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(ShortLived.serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    let mut state = server.state_changes();
+    state.wait_for(ConnectionState::is_closed).await?;
+    assert_eq!(
+        *state.borrow(),
+        ConnectionState::Closed {
+            reason: CloseReason::Expired(SessionExpiry::IdleTimeout(Duration::from_millis(50)))
+        }
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
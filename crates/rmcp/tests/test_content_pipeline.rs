@@ -0,0 +1,208 @@
+//cargo test --test test_content_pipeline --features "client server"
+
+use rmcp::{
+    ErrorData as McpError, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequestParams, CallToolResult, Content, ReadResourceRequestParams,
+        ReadResourceResult, ResourceContents, ServerCapabilities, ServerInfo,
+    },
+    service::{
+        ContentLimits, ContentPipeline, ContentPipelineService, RequestContext, RoleServer,
+        ScrubText, TRUNCATED_META_KEY, TruncateText,
+    },
+};
+use serde_json::json;
+
+struct Server;
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut result = CallToolResult::success(vec![
+            Content::text(format!("{}: contact alice@example.com", request.name)),
+            Content::text("é".repeat(100)),
+        ]);
+        result.structured_content = Some(json!({ "owner": { "email": "alice@example.com" } }));
+        if request.name == "screenshot" {
+            result
+                .content
+                .push(Content::image("A".repeat(4000), "image/png"));
+        }
+        Ok(result)
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        Ok(ReadResourceResult {
+            contents: vec![
+                ResourceContents::text("0123456789".repeat(3), request.uri.clone()),
+                ResourceContents::BlobResourceContents {
+                    uri: request.uri,
+                    mime_type: Some("application/pdf".into()),
+                    blob: "A".repeat(400),
+                    meta: None,
+                    size: None,
+                    last_modified: None,
+                },
+            ],
+        })
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+fn call(name: &'static str) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: None,
+        task: None,
+    }
+}
+
+fn texts(result: &CallToolResult) -> Vec<&str> {
+    result
+        .content
+        .iter()
+        .filter_map(|content| content.as_text().map(|text| text.text.as_str()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_content_pipeline_transforms_tool_results() -> anyhow::Result<()> {
+    let pipeline = ContentPipeline::new()
+        .then(ScrubText::new(|text: &str| {
+            text.replace("alice@example.com", "<email>")
+        }))
+        .then(TruncateText::new(10))
+        .for_tool(
+            "raw",
+            ContentPipeline::new()
+                .then(|_tool: &str, result: &mut CallToolResult| result.content.truncate(1)),
+        );
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (_server, client) = tokio::try_join!(
+        async {
+            anyhow::Ok(
+                ContentPipelineService::new(Server, pipeline)
+                    .serve(server_transport)
+                    .await?,
+            )
+        },
+        async { anyhow::Ok(().serve(client_transport).await?) },
+    )?;
+
+    let result = client.call_tool(call("lookup")).await?;
+    let expected_tail = format!("{}{}", "é".repeat(10), TruncateText::DEFAULT_MARKER);
+    assert_eq!(
+        texts(&result),
+        vec!["lookup: co\n[truncated]", expected_tail.as_str()]
+    );
+    let meta = result.content[1].as_text().unwrap().meta.as_ref().unwrap();
+    assert_eq!(
+        meta[TRUNCATED_META_KEY],
+        json!({ "originalLength": 100, "strategy": "head" })
+    );
+    assert_eq!(
+        result.structured_content,
+        Some(json!({ "owner": { "email": "<email>" } }))
+    );
+
+    // the per-tool pipeline replaces the default one
+    let result = client.call_tool(call("raw")).await?;
+    assert_eq!(texts(&result), vec!["raw: contact alice@example.com"]);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_content_limits() -> anyhow::Result<()> {
+    let pipeline = ContentPipeline::new()
+        .then(
+            ContentLimits::new()
+                .with_text(TruncateText::middle(10))
+                .with_max_media_bytes(1024),
+        )
+        .for_tool(
+            "logs",
+            ContentPipeline::new().then(TruncateText::tail(4).with_marker("...")),
+        )
+        .limit_resources(
+            ContentLimits::new()
+                .with_resource_text(TruncateText::new(8))
+                .with_max_media_bytes(100),
+        );
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (_server, client) = tokio::try_join!(
+        async {
+            anyhow::Ok(
+                ContentPipelineService::new(Server, pipeline)
+                    .serve(server_transport)
+                    .await?,
+            )
+        },
+        async { anyhow::Ok(().serve(client_transport).await?) },
+    )?;
+
+    // oversized images are replaced by a note
+    let result = client.call_tool(call("screenshot")).await?;
+    assert_eq!(
+        texts(&result),
+        vec![
+            "scree\n[truncated]\ne.com",
+            "ééééé\n[truncated]\nééééé",
+            "[image/png omitted: 3000 bytes]",
+        ]
+    );
+    let meta = result.content[2].as_text().unwrap().meta.as_ref().unwrap();
+    assert_eq!(
+        meta[TRUNCATED_META_KEY],
+        json!({ "originalBytes": 3000, "strategy": "omitted" })
+    );
+
+    let result = client.call_tool(call("logs")).await?;
+    assert_eq!(texts(&result), vec!["....com", "...éééé"]);
+    let meta = result.content[0].as_text().unwrap().meta.as_ref().unwrap();
+    assert_eq!(
+        meta[TRUNCATED_META_KEY],
+        json!({ "originalLength": 31, "strategy": "tail" })
+    );
+
+    let result = client
+        .read_resource(ReadResourceRequestParams::new("file:///report"))
+        .await?;
+    let [
+        ResourceContents::TextResourceContents { text, meta, .. },
+        ResourceContents::TextResourceContents {
+            uri, text: note, ..
+        },
+    ] = result.contents.as_slice()
+    else {
+        panic!("unexpected contents: {:?}", result.contents);
+    };
+    assert_eq!(text, "01234567\n[truncated]");
+    assert_eq!(
+        meta.as_ref().unwrap()[TRUNCATED_META_KEY],
+        json!({ "originalLength": 30, "strategy": "head" })
+    );
+    assert_eq!(uri, "file:///report");
+    assert_eq!(note, "[application/pdf omitted: 300 bytes]");
+
+    client.cancel().await?;
+    Ok(())
+}
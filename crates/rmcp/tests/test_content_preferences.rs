@@ -0,0 +1,55 @@
+//cargo test --test test_content_preferences --features "client server"
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    model::*,
+    service::{ClientOptions, RequestContext, serve_client_with_options},
+};
+
+struct Server;
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let contents = Representations::new()
+            .with("text/plain", "build passed".to_owned())
+            .with("text/markdown", "**build passed**".to_owned())
+            .pick(context.peer.content_preferences().as_ref());
+        Ok(CallToolResult::success(contents))
+    }
+}
+
+async fn call_with(options: ClientOptions) -> anyhow::Result<String> {
+    let (client_io, server_io) = tokio::io::duplex(4096);
+    let (server, client) = tokio::join!(
+        Server.serve(server_io),
+        serve_client_with_options((), client_io, options)
+    );
+    let (server, client) = (server?, client?);
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "status".into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(result.content[0].as_text().unwrap().text.clone())
+}
+
+#[tokio::test]
+async fn test_content_preferences() -> anyhow::Result<()> {
+    let mut options = ClientOptions::default();
+    options
+        .experimental
+        .insert_typed(&ContentPreferences::new(["text/markdown", "image/*"]))?;
+    assert_eq!(call_with(options).await?, "**build passed**");
+
+    // no preferences, the first representation
+    assert_eq!(call_with(ClientOptions::default()).await?, "build passed");
+    Ok(())
+}
@@ -0,0 +1,214 @@
+//cargo test --test test_custom_method_router --features "client server"
+
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceError, ServiceExt,
+    handler::custom::{CustomMethod, CustomMethodRouter},
+    model::{ClientInfo, CustomRequest, CustomResult, ErrorCode, ServerCapabilities, ServerInfo},
+    service::RequestContext,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RefreshParams {
+    scope: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshResult {
+    refreshed_scope: String,
+    count: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GreetParams {
+    name: String,
+}
+
+struct Server {
+    custom: CustomMethodRouter<RoleServer>,
+}
+
+impl Server {
+    fn new() -> Self {
+        let custom = CustomMethodRouter::new()
+            .route(
+                "x-test/refresh",
+                |params: RefreshParams, _context| async move {
+                    Ok(RefreshResult {
+                        refreshed_scope: params.scope,
+                        count: 3,
+                    })
+                },
+            )
+            .route("x-test/ping", |(): (), _context| async move { Ok(()) });
+        Self { custom }
+    }
+}
+
+impl ServerHandler for Server {
+    async fn on_custom_request(
+        &self,
+        request: CustomRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CustomResult, ErrorData> {
+        self.custom.handle(request, context).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_experimental_with(self.custom.experimental_capabilities())
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+struct Greet;
+
+impl CustomMethod for Greet {
+    const METHOD: &'static str = "x-test/greet";
+    type Params = GreetParams;
+    type Result = String;
+}
+
+struct Client {
+    custom: CustomMethodRouter<RoleClient>,
+    advertise: bool,
+}
+
+impl ClientHandler for Client {
+    async fn on_custom_request(
+        &self,
+        request: CustomRequest,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CustomResult, ErrorData> {
+        self.custom.handle(request, context).await
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let mut info = ClientInfo::default();
+        if self.advertise {
+            info.capabilities.experimental = Some(self.custom.experimental_capabilities());
+        }
+        info
+    }
+}
+
+fn error_code(error: ServiceError) -> ErrorCode {
+    match error {
+        ServiceError::McpError(error) => error.code,
+        other => panic!("expected an mcp error, got {other}"),
+    }
+}
+
+#[tokio::test]
+async fn test_custom_methods_both_directions() -> anyhow::Result<()> {
+    let client = Client {
+        custom: CustomMethodRouter::new()
+            .route("x-test/greet", |params: GreetParams, _context| async move {
+                Ok(format!("hello {}", params.name))
+            }),
+        advertise: false,
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        async { anyhow::Ok(Server::new().serve(server_transport).await?) },
+        async { anyhow::Ok(client.serve(client_transport).await?) },
+    )?;
+
+    let experimental = client
+        .peer_info()
+        .and_then(|info| info.capabilities.experimental.clone())
+        .expect("experimental capabilities");
+    assert!(experimental.contains_key("x-test/refresh"));
+    assert!(experimental.contains_key("x-test/ping"));
+
+    let result: RefreshResult = client
+        .call_custom(
+            "x-test/refresh",
+            RefreshParams {
+                scope: "all".into(),
+            },
+        )
+        .await?;
+    assert_eq!(
+        result,
+        RefreshResult {
+            refreshed_scope: "all".into(),
+            count: 3,
+        }
+    );
+    let () = client.call_custom("x-test/ping", ()).await?;
+
+    let error = client
+        .call_custom::<_, serde_json::Value>("x-test/unknown", ())
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(error), ErrorCode::METHOD_NOT_FOUND);
+    let error = client
+        .call_custom::<_, RefreshResult>("x-test/refresh", serde_json::json!({ "scope": 42 }))
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(error), ErrorCode::INVALID_PARAMS);
+    // params have to be an object
+    let error = client
+        .call_custom::<_, RefreshResult>("x-test/refresh", 42)
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(error), ErrorCode::INVALID_PARAMS);
+
+    let greeting: String = server
+        .call_custom(
+            "x-test/greet",
+            GreetParams {
+                name: "server".into(),
+            },
+        )
+        .await?;
+    assert_eq!(greeting, "hello server");
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_typed_method_from_server_to_client() -> anyhow::Result<()> {
+    let connect = |advertise| async move {
+        let client = Client {
+            custom: CustomMethodRouter::new().route_method::<Greet, _, _>(
+                |params: GreetParams, _context| async move { Ok(format!("hello {}", params.name)) },
+            ),
+            advertise,
+        };
+        let (server_transport, client_transport) = tokio::io::duplex(4096);
+        tokio::try_join!(
+            async { anyhow::Ok(Server::new().serve(server_transport).await?) },
+            async { anyhow::Ok(client.serve(client_transport).await?) },
+        )
+    };
+
+    let (server, client) = connect(true).await?;
+    assert!(server.peer_supports_experimental(Greet::METHOD));
+    let greeting = server
+        .call_method::<Greet>(GreetParams {
+            name: "server".into(),
+        })
+        .await?;
+    assert_eq!(greeting, "hello server");
+    client.cancel().await?;
+
+    // the client serves the method but doesn't advertise it
+    let (server, client) = connect(false).await?;
+    assert!(!server.peer_supports_experimental(Greet::METHOD));
+    let error = server
+        .call_method::<Greet>(GreetParams {
+            name: "server".into(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(error), ErrorCode::METHOD_NOT_FOUND);
+    client.cancel().await?;
+    Ok(())
+}
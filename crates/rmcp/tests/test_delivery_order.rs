@@ -0,0 +1,176 @@
+//cargo test --test test_delivery_order --features "client server"
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequestParams, CallToolResult, ProgressNotificationParam, ProtocolVersion,
+        ResourceUpdatedNotificationParam,
+    },
+    service::{DeliveryOrder, NotificationContext, RequestContext, ServiceConfig},
+};
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+const UPDATES: usize = 20;
+
+#[derive(Debug, Clone, Default)]
+struct Quiet {
+    order: DeliveryOrder,
+}
+
+impl ServerHandler for Quiet {
+    /// Reports progress, then answers right away.
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let progress_token = context.meta.get_progress_token().unwrap();
+        for progress in 0..UPDATES {
+            context
+                .peer
+                .notify_progress(ProgressNotificationParam {
+                    progress_token: progress_token.clone(),
+                    progress: progress as f64,
+                    total: None,
+                    message: None,
+                })
+                .await
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        }
+        Ok(CallToolResult::success(Vec::new()))
+    }
+
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig {
+            delivery_order: self.order,
+            ..Default::default()
+        }
+    }
+}
+
+/// Takes less time for each update than for the one before.
+#[derive(Debug, Clone, Default)]
+struct Mirror {
+    order: DeliveryOrder,
+    seen: Arc<Mutex<Vec<usize>>>,
+}
+
+impl ClientHandler for Mirror {
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let index: usize = params.uri.parse().unwrap();
+        tokio::time::sleep(Duration::from_millis((UPDATES - index) as u64)).await;
+        self.seen.lock().unwrap().push(index);
+    }
+
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig {
+            delivery_order: self.order,
+            ..Default::default()
+        }
+    }
+}
+
+async fn updates_seen(order: DeliveryOrder) -> anyhow::Result<Vec<usize>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Quiet { order }.serve(server_transport));
+    let mirror = Mirror {
+        order,
+        ..Default::default()
+    };
+    let client = mirror.clone().serve(client_transport).await?;
+    let server = server.await??;
+
+    // not awaited one by one, so the writes compete
+    futures::future::try_join_all((0..UPDATES).map(|index| {
+        server
+            .peer()
+            .notify_resource_updated(ResourceUpdatedNotificationParam {
+                uri: index.to_string(),
+            })
+    }))
+    .await?;
+    while mirror.seen.lock().unwrap().len() < UPDATES {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    client.cancel().await?;
+    let seen = mirror.seen.lock().unwrap().clone();
+    Ok(seen)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_fifo_keeps_notification_order() -> anyhow::Result<()> {
+    let seen = updates_seen(DeliveryOrder::Fifo).await?;
+    assert_eq!(seen, (0..UPDATES).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_concurrent_notifications_may_overtake() -> anyhow::Result<()> {
+    let seen = updates_seen(DeliveryOrder::Concurrent).await?;
+    assert_eq!(seen.len(), UPDATES);
+    assert_ne!(seen, (0..UPDATES).collect::<Vec<_>>());
+    Ok(())
+}
+
+/// The methods of the messages the server writes for a tool call, with
+/// `response` for its response, read straight off the wire.
+async fn written_for_tool_call(order: DeliveryOrder) -> anyhow::Result<Vec<String>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Quiet { order }.serve(server_transport));
+    let (read, mut write) = tokio::io::split(client_transport);
+    let mut lines = BufReader::new(read).lines();
+    let mut send = async |message: Value| write.write_all(format!("{message}\n").as_bytes()).await;
+
+    send(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": ProtocolVersion::LATEST,
+            "capabilities": {},
+            "clientInfo": { "name": "raw", "version": "0" },
+        },
+    }))
+    .await?;
+    lines.next_line().await?;
+    send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await?;
+    send(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": { "name": "work", "_meta": { "progressToken": "work" } },
+    }))
+    .await?;
+
+    let mut written = Vec::new();
+    while let Some(line) = lines.next_line().await? {
+        let message: Value = serde_json::from_str(&line)?;
+        match message["method"].as_str() {
+            Some(method) => written.push(method.to_owned()),
+            None => {
+                written.push("response".to_owned());
+                break;
+            }
+        }
+    }
+    server.abort();
+    Ok(written)
+}
+
+#[tokio::test]
+async fn test_fifo_writes_progress_before_response() -> anyhow::Result<()> {
+    let written = written_for_tool_call(DeliveryOrder::Fifo).await?;
+    let mut expected = vec!["notifications/progress".to_owned(); UPDATES];
+    expected.push("response".to_owned());
+    assert_eq!(written, expected);
+    Ok(())
+}
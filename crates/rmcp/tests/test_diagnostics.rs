@@ -0,0 +1,79 @@
+//cargo test --test test_diagnostics --features "client server"
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult, Content},
+    service::RequestContext,
+};
+
+#[derive(Clone, Default)]
+struct Echo {
+    calls: Arc<AtomicUsize>,
+}
+
+impl ServerHandler for Echo {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{:?}",
+            request.arguments
+        ))]))
+    }
+}
+
+#[tokio::test]
+async fn test_diagnostics() -> anyhow::Result<()> {
+    let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+    let echo = Echo::default();
+    let (server, client) = tokio::join!(echo.clone().serve(server_io), ().serve(client_io));
+    let (server, client) = (server?, client?);
+
+    let report = client
+        .diagnostics()
+        .with_samples(20)
+        .with_throughput(200, 8)
+        .measure()
+        .await?;
+    assert_eq!(report.samples, 20);
+    assert!(report.rtt.min <= report.rtt.p50 && report.rtt.p50 <= report.rtt.p99);
+    assert!(report.rtt.p99 <= report.rtt.max);
+    assert!(report.requests_per_second > 0.0);
+    assert_eq!(echo.calls.load(Ordering::Relaxed), 0);
+
+    // through the tool dispatch of the server
+    let report = client
+        .diagnostics()
+        .with_samples(5)
+        .with_throughput(20, 4)
+        .with_echo_tool(CallToolRequestParams {
+            meta: None,
+            name: "echo".into(),
+            arguments: None,
+            task: None,
+        })
+        .measure()
+        .await?;
+    assert_eq!(report.requests, 20);
+    assert_eq!(echo.calls.load(Ordering::Relaxed), 25);
+
+    // the other way
+    let report = server
+        .diagnostics()
+        .with_samples(5)
+        .with_throughput(20, 4)
+        .measure()
+        .await?;
+    assert!(report.requests_per_second > 0.0);
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//cargo test --test test_duplicate_request_id --features "server"
+use std::sync::Arc;
+
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult, Content, ErrorCode},
+    service::{RequestContext, protocol_violations},
+};
+use serde_json::{Value, json};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    sync::Semaphore,
+};
+
+/// Tool calls wait until the test lets them finish.
+#[derive(Clone)]
+struct Gated(Arc<Semaphore>);
+
+impl ServerHandler for Gated {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        let _permit = self.0.acquire().await.expect("gate open");
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+}
+
+#[tokio::test]
+async fn test_rejects_id_of_request_in_flight() -> anyhow::Result<()> {
+    let gate = Arc::new(Semaphore::new(0));
+    let (server_io, client_io) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Gated(gate.clone()).serve(server_io));
+
+    let (read, mut write) = tokio::io::split(client_io);
+    let mut lines = BufReader::new(read).lines();
+    let mut send = async |message: Value| write.write_all(format!("{message}\n").as_bytes()).await;
+    send(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2025-06-18",
+            "capabilities": {},
+            "clientInfo": { "name": "raw", "version": "0" },
+        },
+    }))
+    .await?;
+    lines.next_line().await?;
+    send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await?;
+    let _server = server.await??;
+
+    let before = protocol_violations().duplicate_request_ids;
+    let call = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": { "name": "wait" },
+    });
+    send(call.clone()).await?;
+    send(call).await?;
+    let rejected: Value = serde_json::from_str(&lines.next_line().await?.unwrap_or_default())?;
+    assert_eq!(rejected["id"], 1);
+    assert_eq!(rejected["error"]["code"], ErrorCode::INVALID_REQUEST.0);
+    assert!(protocol_violations().duplicate_request_ids > before);
+
+    // the first request is still answered
+    gate.add_permits(1);
+    let answered: Value = serde_json::from_str(&lines.next_line().await?.unwrap_or_default())?;
+    assert_eq!(answered["id"], 1);
+    assert_eq!(answered["result"]["content"][0]["text"], "done");
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//cargo test --test test_elicitation_handlers --features "elicitation client server"
+use std::sync::Arc;
+
+use rmcp::{
+    ClientHandler, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::client::elicitation::{
+        CallbackElicitationHandler, ConsoleElicitationHandler, ElicitationField, ElicitationPrompt,
+    },
+    model::*,
+    service::RunningService,
+};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+fn signup() -> CreateElicitationRequestParams {
+    CreateElicitationRequestParams::form(
+        "Sign up",
+        ElicitationSchema::builder()
+            .required_email("email")
+            .optional_integer("age", 0, 150)
+            .optional_bool("newsletter", true)
+            .build()
+            .unwrap(),
+    )
+}
+
+fn console(input: &'static str) -> ConsoleElicitationHandler<&'static [u8], Vec<u8>> {
+    ConsoleElicitationHandler::new(input.as_bytes(), Vec::new())
+}
+
+#[tokio::test]
+async fn test_console_asks_for_each_field() -> anyhow::Result<()> {
+    // fields come in property order, the newsletter keeps its default
+    let handler = console("\n200\n36\nnot an email\nada@example.com\n\n");
+    let result = handler.prompt(&signup(), None, None).await?;
+    assert_eq!(result.action, ElicitationAction::Accept);
+    assert_eq!(
+        result.content,
+        Some(json!({ "age": 36, "email": "ada@example.com", "newsletter": true }))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_console_decline_and_cancel() -> anyhow::Result<()> {
+    let result = console("n\n").prompt(&signup(), None, None).await?;
+    assert_eq!(result.action, ElicitationAction::Decline);
+    // running out of input cancels, even halfway through the form
+    let result = console("y\n36\n").prompt(&signup(), None, None).await?;
+    assert_eq!(result.action, ElicitationAction::Cancel);
+
+    let request = CreateElicitationRequestParams::url(
+        "link-1",
+        "https://example.com/link",
+        "Link your account",
+    );
+    let result = console("\n").prompt(&request, None, None).await?;
+    assert_eq!(
+        (result.action, result.content),
+        (ElicitationAction::Accept, None)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_field_parsing() {
+    let schema = ElicitationSchema::builder()
+        .required_enum_schema(
+            "colors",
+            EnumSchema::builder(vec!["red".into(), "green".into(), "blue".into()])
+                .multiselect()
+                .build(),
+        )
+        .build()
+        .unwrap();
+    let [colors] = &ElicitationField::all(&schema)[..] else {
+        panic!("expected one field");
+    };
+    assert!(colors.required);
+    let (options, multi) = colors.options().unwrap();
+    assert!(multi);
+    assert_eq!(options.len(), 3);
+    assert_eq!(colors.parse("1, blue"), Ok(json!(["red", "blue"])));
+    assert!(colors.parse("purple").is_err());
+}
+
+#[derive(Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+async fn connect<C: ClientHandler>(
+    client: C,
+) -> anyhow::Result<(
+    RunningService<RoleServer, Server>,
+    RunningService<RoleClient, C>,
+)> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server.serve(server_transport));
+    let client = client.serve(client_transport).await?;
+    Ok((server.await??, client))
+}
+
+#[tokio::test]
+async fn test_callback_is_asked_again_with_errors() -> anyhow::Result<()> {
+    let prompts = Arc::new(Mutex::new(Vec::<ElicitationPrompt>::new()));
+    let handler = CallbackElicitationHandler::new({
+        let prompts = prompts.clone();
+        move |prompt: ElicitationPrompt| {
+            let prompts = prompts.clone();
+            async move {
+                let email = if prompt.errors.is_some() {
+                    "ada@example.com"
+                } else {
+                    "not an email"
+                };
+                prompts.lock().await.push(prompt);
+                CreateElicitationResult {
+                    action: ElicitationAction::Accept,
+                    content: Some(json!({ "email": email })),
+                }
+            }
+        }
+    });
+    let (server, client) = connect(handler).await?;
+    let capabilities = server.peer_info().unwrap().capabilities.clone();
+    assert!(capabilities.elicitation.unwrap().validates_schema());
+
+    let result = server.create_elicitation(signup()).await?;
+    assert_eq!(result.content, Some(json!({ "email": "ada@example.com" })));
+
+    let prompts = prompts.lock().await;
+    let [first, second] = &prompts[..] else {
+        panic!("expected two prompts, got {prompts:?}");
+    };
+    assert_eq!(first.fields().len(), 3);
+    assert!(first.previous.is_none());
+    assert_eq!(
+        second.previous.as_ref().unwrap().content,
+        Some(json!({ "email": "not an email" }))
+    );
+    assert_eq!(second.errors.as_ref().unwrap().errors[0].field, "email");
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
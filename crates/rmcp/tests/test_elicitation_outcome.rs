@@ -0,0 +1,171 @@
+//cargo test --test test_elicitation_outcome --features "elicitation client server schemars"
+use std::{sync::Arc, time::Duration};
+
+use rmcp::{
+    ClientHandler, ErrorData as McpError, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::*,
+    service::{NotificationContext, RequestContext, RunningService},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+struct Confirmation {
+    confirmed: bool,
+}
+
+rmcp::elicit_safe!(Confirmation);
+
+#[test]
+fn test_outcome_roundtrip() {
+    let result = ElicitationOutcome::Accept(Confirmation { confirmed: true })
+        .into_result()
+        .unwrap();
+    assert_eq!(
+        result,
+        CreateElicitationResult::accept(json!({ "confirmed": true }))
+    );
+    assert_eq!(
+        result.outcome::<Confirmation>().unwrap(),
+        ElicitationOutcome::Accept(Confirmation { confirmed: true })
+    );
+
+    // URL mode accepts without content
+    let result = ElicitationOutcome::Accept(()).into_result().unwrap();
+    assert_eq!(result.content, None);
+    assert_eq!(
+        result.outcome::<()>().unwrap(),
+        ElicitationOutcome::Accept(())
+    );
+    assert!(result.outcome::<Confirmation>().is_err());
+
+    let declined: CreateElicitationResult = ElicitationOutcome::Decline.into();
+    assert_eq!(declined.action, ElicitationAction::Decline);
+    assert_eq!(
+        CreateElicitationResult::cancel()
+            .outcome::<Confirmation>()
+            .unwrap(),
+        ElicitationOutcome::Cancel
+    );
+}
+
+/// Answers with `answer`, or leaves the user thinking forever when `None`.
+#[derive(Clone, Default)]
+struct User {
+    answer: Option<ElicitationOutcome>,
+    cancelled: Arc<Mutex<Vec<CancelledNotificationParam>>>,
+}
+
+impl ClientHandler for User {
+    async fn create_elicitation(
+        &self,
+        _request: CreateElicitationRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        match &self.answer {
+            Some(answer) => Ok(answer.clone().into()),
+            None => std::future::pending().await,
+        }
+    }
+
+    async fn on_cancelled(
+        &self,
+        params: CancelledNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.cancelled.lock().await.push(params);
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            capabilities: ClientCapabilities::builder().enable_elicitation().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+async fn connect(
+    user: User,
+) -> anyhow::Result<(
+    RunningService<RoleServer, Server>,
+    RunningService<RoleClient, User>,
+)> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server.serve(server_transport));
+    let client = user.serve(client_transport).await?;
+    Ok((server.await??, client))
+}
+
+#[tokio::test]
+async fn test_elicit_outcome() -> anyhow::Result<()> {
+    let user = User {
+        answer: Some(ElicitationOutcome::Accept(json!({ "confirmed": true }))),
+        ..Default::default()
+    };
+    let (server, client) = connect(user).await?;
+    let outcome = server.elicit_outcome::<Confirmation>("Proceed?").await?;
+    assert_eq!(
+        outcome,
+        ElicitationOutcome::Accept(Confirmation { confirmed: true })
+    );
+    client.cancel().await?;
+    server.cancel().await?;
+
+    let user = User {
+        answer: Some(ElicitationOutcome::Decline),
+        ..Default::default()
+    };
+    let (server, client) = connect(user).await?;
+    let outcome = server.elicit_outcome::<Confirmation>("Proceed?").await?;
+    assert_eq!(outcome, ElicitationOutcome::Decline);
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pending_elicitation_times_out() -> anyhow::Result<()> {
+    let user = User::default();
+    let (server, client) = connect(user.clone()).await?;
+    assert_eq!(server.elicitation_timeout(), None);
+    server.set_elicitation_timeout(Some(Duration::from_millis(50)));
+    // the timeout applies to every handle on the session
+    let peer = server.peer().clone();
+    assert_eq!(peer.elicitation_timeout(), Some(Duration::from_millis(50)));
+
+    let outcome = peer.elicit_outcome::<Confirmation>("Proceed?").await?;
+    assert_eq!(outcome, ElicitationOutcome::Cancel);
+
+    let request = CreateElicitationRequestParams::form(
+        "Proceed?",
+        ElicitationSchema::builder()
+            .required_bool("confirmed")
+            .build()
+            .unwrap(),
+    );
+    let error = peer.create_elicitation(request).await.unwrap_err();
+    assert!(matches!(error, rmcp::ServiceError::Timeout { .. }));
+
+    // the client is told to stop asking
+    let mut cancelled = Vec::new();
+    for _ in 0..50 {
+        cancelled = user.cancelled.lock().await.clone();
+        if cancelled.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(cancelled.len(), 2);
+    assert_eq!(cancelled[0].reason.as_deref(), Some("request timeout"));
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
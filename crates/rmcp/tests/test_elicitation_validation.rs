@@ -0,0 +1,215 @@
+//cargo test --test test_elicitation_validation --features "elicitation client server"
+use std::{collections::VecDeque, sync::Arc};
+
+use rmcp::{
+    ClientHandler, ErrorData as McpError, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::*,
+    service::{RequestContext, RunningService, ServiceError},
+};
+use serde_json::{Value, json};
+use tokio::sync::Mutex;
+
+/// A form the user fills in once per answer, until the answers run out.
+#[derive(Clone, Default)]
+struct Form {
+    answers: Arc<Mutex<VecDeque<Value>>>,
+    revisions: Arc<Mutex<Vec<ElicitationValidationError>>>,
+    validates: bool,
+}
+
+impl Form {
+    fn new(validates: bool, answers: impl IntoIterator<Item = Value>) -> Self {
+        Self {
+            answers: Arc::new(Mutex::new(answers.into_iter().collect())),
+            revisions: Default::default(),
+            validates,
+        }
+    }
+
+    async fn answer(&self) -> CreateElicitationResult {
+        match self.answers.lock().await.pop_front() {
+            Some(content) => CreateElicitationResult {
+                action: ElicitationAction::Accept,
+                content: Some(content),
+            },
+            None => CreateElicitationResult {
+                action: ElicitationAction::Decline,
+                content: None,
+            },
+        }
+    }
+}
+
+impl ClientHandler for Form {
+    async fn create_elicitation(
+        &self,
+        _request: CreateElicitationRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        Ok(self.answer().await)
+    }
+
+    async fn revise_elicitation(
+        &self,
+        _request: CreateElicitationRequestParams,
+        _result: CreateElicitationResult,
+        errors: ElicitationValidationError,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        self.revisions.lock().await.push(errors);
+        Ok(self.answer().await)
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let builder = ClientCapabilities::builder().enable_elicitation();
+        let capabilities = if self.validates {
+            builder.enable_elicitation_form_validation().build()
+        } else {
+            builder.build()
+        };
+        ClientInfo {
+            capabilities,
+            ..Default::default()
+        }
+    }
+}
+
+/// Validates, but leaves revising to the default.
+#[derive(Clone)]
+struct StrictForm;
+
+impl ClientHandler for StrictForm {
+    async fn create_elicitation(
+        &self,
+        _request: CreateElicitationRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        Ok(CreateElicitationResult {
+            action: ElicitationAction::Accept,
+            content: Some(json!({ "email": "nope", "age": 200 })),
+        })
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            capabilities: ClientCapabilities::builder()
+                .enable_elicitation()
+                .enable_elicitation_form_validation()
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+async fn connect<C: ClientHandler>(
+    client: C,
+) -> anyhow::Result<(
+    RunningService<RoleServer, Server>,
+    RunningService<RoleClient, C>,
+)> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server.serve(server_transport));
+    let client = client.serve(client_transport).await?;
+    Ok((server.await??, client))
+}
+
+fn signup() -> CreateElicitationRequestParams {
+    CreateElicitationRequestParams::form(
+        "Sign up",
+        ElicitationSchema::builder()
+            .required_email("email")
+            .required_integer("age", 0, 150)
+            .build()
+            .unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_invalid_response_is_revised() -> anyhow::Result<()> {
+    let form = Form::new(
+        true,
+        [
+            json!({ "email": "not an email", "age": 200 }),
+            json!({ "email": "ada@example.com", "age": 36 }),
+        ],
+    );
+    let (server, client) = connect(form.clone()).await?;
+
+    let result = server.create_elicitation(signup()).await?;
+    assert_eq!(result.action, ElicitationAction::Accept);
+    assert_eq!(
+        result.content,
+        Some(json!({ "email": "ada@example.com", "age": 36 }))
+    );
+
+    let revisions = form.revisions.lock().await;
+    let [errors] = &revisions[..] else {
+        panic!("expected one revision, got {revisions:?}");
+    };
+    let fields = errors
+        .errors
+        .iter()
+        .map(|error| error.field.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(fields, ["age", "email"]);
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_revision_may_decline() -> anyhow::Result<()> {
+    let form = Form::new(true, [json!({ "age": 36 })]);
+    let (server, client) = connect(form.clone()).await?;
+
+    let result = server.create_elicitation(signup()).await?;
+    assert_eq!(result.action, ElicitationAction::Decline);
+    assert_eq!(
+        form.revisions.lock().await[0].errors,
+        [ElicitationFieldError {
+            field: "email".into(),
+            message: "is required".into(),
+        }]
+    );
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unvalidated_response_is_sent_as_is() -> anyhow::Result<()> {
+    let form = Form::new(false, [json!({ "email": "not an email" })]);
+    let (server, client) = connect(form.clone()).await?;
+
+    let result = server.create_elicitation(signup()).await?;
+    assert_eq!(result.content, Some(json!({ "email": "not an email" })));
+    assert!(form.revisions.lock().await.is_empty());
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_response_is_rejected_by_default() -> anyhow::Result<()> {
+    let (server, client) = connect(StrictForm).await?;
+
+    let Err(ServiceError::McpError(error)) = server.create_elicitation(signup()).await else {
+        panic!("expected an error");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    let errors = &error.data.unwrap()["errors"];
+    assert_eq!(errors[0]["field"], "age");
+    assert_eq!(errors[1]["field"], "email");
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
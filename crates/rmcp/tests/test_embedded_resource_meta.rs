@@ -18,6 +18,8 @@ fn serialize_embedded_text_resource_with_meta() {
             mime_type: Some("text/plain".to_string()),
             text: "hello".to_string(),
             meta: Some(resource_content_meta),
+            size: None,
+            last_modified: None,
         },
     })
     .no_annotation();
@@ -47,6 +49,8 @@ fn serialize_embedded_text_resource_without_meta_omits_fields() {
             mime_type: Some("text/plain".to_string()),
             text: "hi".to_string(),
             meta: None,
+            size: None,
+            last_modified: None,
         },
     })
     .no_annotation();
@@ -110,6 +114,8 @@ fn serialize_embedded_blob_resource_with_meta() {
             mime_type: Some("application/octet-stream".to_string()),
             blob: "Zm9v".to_string(),
             meta: Some(resource_content_meta),
+            size: None,
+            last_modified: None,
         },
     })
     .no_annotation();
@@ -0,0 +1,79 @@
+//cargo test --test test_error_chain --features "client server"
+use anyhow::Context;
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceError, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult},
+    service::RequestContext,
+};
+
+#[derive(Debug, Clone, Default)]
+struct Failing;
+
+fn read_config() -> anyhow::Result<String> {
+    let missing = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    Err(missing)
+        .context("reading config.toml")
+        .context("loading settings")
+}
+
+impl ServerHandler for Failing {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let error = read_config().unwrap_err();
+        Err(ErrorData::internal_error_with_chain(error.as_ref()))
+    }
+}
+
+#[tokio::test]
+async fn test_source_chain_reaches_the_client() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Failing.serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let _server = server.await??;
+
+    let ServiceError::McpError(error) = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "anything".into(),
+            arguments: None,
+            task: None,
+        })
+        .await
+        .unwrap_err()
+    else {
+        panic!("expected an mcp error");
+    };
+    assert_eq!(error.message, "loading settings");
+    assert_eq!(
+        error.source_chain(),
+        ["reading config.toml", "no such file"]
+    );
+
+    let report = format!("{:#}", anyhow::Error::new(error.into_error_chain()));
+    assert_eq!(
+        report,
+        "-32603: loading settings: reading config.toml: no such file"
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+#[test]
+fn test_chain_is_added_next_to_existing_data() {
+    let error = ErrorData::invalid_params("bad input", Some(serde_json::json!({ "field": "a" })))
+        .with_source_chain(&std::fmt::Error);
+    // no sources, nothing to add
+    assert_eq!(error.data, Some(serde_json::json!({ "field": "a" })));
+
+    let source = read_config().unwrap_err();
+    let error = error.with_source_chain(source.as_ref());
+    assert_eq!(error.data.as_ref().unwrap()["field"], "a");
+    assert_eq!(error.source_chain().len(), 2);
+
+    let error =
+        ErrorData::internal_error("opaque", Some("text".into())).with_source_chain(source.as_ref());
+    assert!(error.source_chain().is_empty());
+}
@@ -0,0 +1,56 @@
+//cargo test --test test_graceful_shutdown --features "client server"
+
+use std::time::Duration;
+
+use rmcp::{ServerHandler, ServiceExt, service::QuitReason};
+
+struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_signal_closes_session() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (signal_tx, signal_rx) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(
+        Server.serve_with_graceful_shutdown(server_transport, async {
+            let _ = signal_rx.await;
+        }),
+    );
+    let client = ().serve(client_transport).await?;
+    client.list_tools(None).await?;
+
+    signal_tx.send(()).unwrap();
+    let reason = tokio::time::timeout(Duration::from_secs(5), server).await???;
+    assert!(matches!(reason, QuitReason::Cancelled));
+
+    // the transport was closed, so the client notices
+    let reason = tokio::time::timeout(Duration::from_secs(5), client.waiting()).await??;
+    assert!(matches!(reason, QuitReason::Closed));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_returns_when_peer_disconnects() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server =
+        tokio::spawn(Server.serve_with_graceful_shutdown(server_transport, std::future::pending()));
+    let client = ().serve(client_transport).await?;
+    client.cancel().await?;
+
+    let reason = tokio::time::timeout(Duration::from_secs(5), server).await???;
+    assert!(matches!(reason, QuitReason::Closed));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_signal_before_initialization() -> anyhow::Result<()> {
+    let (server_transport, _client_transport) = tokio::io::duplex(4096);
+    let reason = tokio::time::timeout(
+        Duration::from_secs(5),
+        Server.serve_with_graceful_shutdown(server_transport, std::future::ready(())),
+    )
+    .await??;
+    assert!(matches!(reason, QuitReason::Cancelled));
+    Ok(())
+}
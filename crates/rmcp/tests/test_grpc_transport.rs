@@ -0,0 +1,136 @@
+//cargo test --test test_grpc_transport --features "client server transport-grpc"
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult, Content},
+    service::RequestContext,
+    transport::{
+        GrpcClientTransport, McpGrpcService,
+        grpc::{SERVICE_NAME, reflection_service},
+    },
+};
+use tokio_stream::StreamExt;
+use tokio_util::sync::CancellationToken;
+use tonic_reflection::pb::v1::{
+    ServerReflectionRequest, server_reflection_client::ServerReflectionClient,
+    server_reflection_request::MessageRequest, server_reflection_response::MessageResponse,
+};
+
+#[derive(Clone)]
+struct Server;
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "called {}\nover grpc",
+            request.name
+        ))]))
+    }
+}
+
+async fn serve(ct: CancellationToken) -> anyhow::Result<String> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let endpoint = format!("http://{}", listener.local_addr()?);
+    let router = tonic::transport::Server::builder()
+        .add_service(McpGrpcService::new(|| Ok(Server)).with_cancellation_token(ct.clone()))
+        .add_service(reflection_service());
+    tokio::spawn(async move {
+        let _ = router
+            .serve_with_incoming_shutdown(
+                tonic::transport::server::TcpIncoming::from(listener),
+                ct.cancelled_owned(),
+            )
+            .await;
+    });
+    Ok(endpoint)
+}
+
+#[tokio::test]
+async fn test_grpc_session() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let endpoint = serve(ct.clone()).await?;
+
+    let client = ().serve(GrpcClientTransport::connect(endpoint.clone()).await?).await?;
+    let other = ().serve(GrpcClientTransport::connect(endpoint).await?).await?;
+    assert!(client.peer_info().is_some());
+
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "echo".into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("called echo\nover grpc")
+    );
+    let result = other
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "other".into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("called other\nover grpc")
+    );
+
+    client.cancel().await?;
+    other.cancel().await?;
+    ct.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_grpc_reflection() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let endpoint = serve(ct.clone()).await?;
+
+    let channel = tonic::transport::Endpoint::from_shared(endpoint)?
+        .connect()
+        .await?;
+    let mut client = ServerReflectionClient::new(channel);
+    let requests = [
+        MessageRequest::ListServices(String::new()),
+        MessageRequest::FileContainingSymbol(SERVICE_NAME.to_owned()),
+    ]
+    .map(|request| ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(request),
+    });
+    let mut responses = client
+        .server_reflection_info(tokio_stream::iter(requests))
+        .await?
+        .into_inner();
+
+    let Some(MessageResponse::ListServicesResponse(list)) = responses
+        .next()
+        .await
+        .transpose()?
+        .and_then(|r| r.message_response)
+    else {
+        panic!("expected a list of services");
+    };
+    let services: Vec<_> = list.service.iter().map(|s| s.name.as_str()).collect();
+    assert!(services.contains(&SERVICE_NAME), "{services:?}");
+
+    let Some(MessageResponse::FileDescriptorResponse(files)) = responses
+        .next()
+        .await
+        .transpose()?
+        .and_then(|r| r.message_response)
+    else {
+        panic!("expected the descriptor of {SERVICE_NAME}");
+    };
+    assert_eq!(files.file_descriptor_proto.len(), 1);
+
+    ct.cancel();
+    Ok(())
+}
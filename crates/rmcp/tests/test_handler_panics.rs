@@ -0,0 +1,78 @@
+//cargo test --test test_handler_panics --features "client server"
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceError, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult, Content, ErrorCode},
+    service::{PanicPolicy, QuitReason, RequestContext, ServiceConfig},
+};
+
+#[derive(Debug, Clone, Default)]
+struct Fragile {
+    policy: PanicPolicy,
+}
+
+impl ServerHandler for Fragile {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if request.name == "boom" {
+            panic!("boom");
+        }
+        Ok(CallToolResult::success(vec![Content::text("fine")]))
+    }
+
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig {
+            panic_policy: self.policy,
+            ..Default::default()
+        }
+    }
+}
+
+fn call(name: &'static str) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: None,
+        task: None,
+    }
+}
+
+#[tokio::test]
+async fn test_panic_is_answered_and_session_survives() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Fragile::default().serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    let ServiceError::McpError(error) = client.call_tool(call("boom")).await.unwrap_err() else {
+        panic!("expected an mcp error");
+    };
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+
+    let result = client.call_tool(call("fine")).await?;
+    assert_eq!(result.content[0].as_text().unwrap().text, "fine");
+    assert!(!server.is_closed());
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_close_session_policy_ends_the_session() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(
+        Fragile {
+            policy: PanicPolicy::CloseSession,
+        }
+        .serve(server_transport),
+    );
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    assert!(client.call_tool(call("boom")).await.is_err());
+    let reason = server.waiting().await?;
+    assert!(matches!(&reason, QuitReason::Panicked(message) if message == "boom"));
+    assert!(matches!(client.waiting().await?, QuitReason::Closed));
+    Ok(())
+}
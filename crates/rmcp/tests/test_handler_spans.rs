@@ -0,0 +1,158 @@
+//cargo test --test test_handler_spans --features "client server"
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{
+        ClientJsonRpcMessage, ClientRequest, NumberOrString, ProgressNotificationParam,
+        ProgressToken, ServerJsonRpcMessage,
+    },
+    transport::{Transport, async_rw::AsyncRwTransport},
+};
+use tokio::io::{DuplexStream, ReadHalf, WriteHalf};
+use tracing::{
+    Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id},
+};
+use tracing_subscriber::{Layer, layer::Context, prelude::*};
+
+type Fields = BTreeMap<String, String>;
+
+/// Records the fields of every span created.
+#[derive(Clone, Default)]
+struct Spans(Arc<Mutex<Vec<(&'static str, Fields)>>>);
+
+struct Recorder<'a>(&'a mut Fields);
+
+impl Visit for Recorder<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_owned(), format!("{value:?}"));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_owned(), value.to_owned());
+    }
+}
+
+impl<S: Subscriber> Layer<S> for Spans {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        let mut fields = Fields::new();
+        attrs.record(&mut Recorder(&mut fields));
+        self.0
+            .lock()
+            .unwrap()
+            .push((attrs.metadata().name(), fields));
+    }
+}
+
+impl Spans {
+    fn named(&self, name: &str) -> Vec<Fields> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(span, _)| *span == name)
+            .map(|(_, fields)| fields.clone())
+            .collect()
+    }
+}
+
+/// A stdio-like transport that belongs to a session.
+struct SessionTransport {
+    inner: AsyncRwTransport<RoleServer, ReadHalf<DuplexStream>, WriteHalf<DuplexStream>>,
+}
+
+impl Transport<RoleServer> for SessionTransport {
+    type Error = std::io::Error;
+
+    fn send(
+        &mut self,
+        item: ServerJsonRpcMessage,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        self.inner.send(item)
+    }
+
+    fn receive(&mut self) -> impl Future<Output = Option<ClientJsonRpcMessage>> + Send {
+        self.inner.receive()
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.inner.close()
+    }
+
+    fn session_id(&self) -> Option<Arc<str>> {
+        Some("session-1".into())
+    }
+}
+
+#[derive(Clone)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_handler_spans_carry_correlation_ids() -> anyhow::Result<()> {
+    let spans = Spans::default();
+    let _guard = tracing_subscriber::registry()
+        .with(spans.clone())
+        .set_default();
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (read, write) = tokio::io::split(server_transport);
+    let transport = SessionTransport {
+        inner: AsyncRwTransport::new_server(read, write),
+    };
+    let server = tokio::spawn(Server.serve(transport));
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    client.list_all_tools().await?;
+    client
+        .send_request(ClientRequest::PingRequest(Default::default()))
+        .await?;
+
+    let requests = spans.named("mcp.request");
+    let server_requests = requests
+        .iter()
+        .filter(|fields| fields.contains_key("mcp.session.id"))
+        .collect::<Vec<_>>();
+    let methods = server_requests
+        .iter()
+        .map(|fields| fields["mcp.method.name"].as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(methods, ["tools/list", "ping"]);
+    for fields in &server_requests {
+        assert_eq!(fields["mcp.session.id"], "session-1");
+        assert!(fields.contains_key("jsonrpc.request.id"));
+    }
+
+    client
+        .notify_progress(ProgressNotificationParam {
+            progress_token: ProgressToken(NumberOrString::Number(1)),
+            progress: 1.0,
+            total: None,
+            message: None,
+        })
+        .await?;
+    let mut notifications = Vec::new();
+    for _ in 0..50 {
+        notifications = spans.named("mcp.notification");
+        if !notifications.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let [progress] = &notifications[..] else {
+        panic!("expected one notification span, got {notifications:?}");
+    };
+    assert_eq!(progress["mcp.method.name"], "notifications/progress");
+    assert_eq!(progress["mcp.session.id"], "session-1");
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//cargo test --test test_image_content --features "image server"
+
+use image::{DynamicImage, RgbImage, RgbaImage};
+use rmcp::{
+    model::{CallToolResult, Content, RawContent, RawImageContent},
+    service::{ContentPipeline, DownscaleImages},
+};
+
+fn image(content: &Content) -> &RawImageContent {
+    content.as_image().expect("image content")
+}
+
+#[test]
+fn test_image_from_dynamic_image() -> anyhow::Result<()> {
+    let source = DynamicImage::ImageRgba8(RgbaImage::new(40, 10));
+    let content = Content::image_from_dynamic_image(&source)?;
+    let image = image(&content);
+    assert_eq!(image.mime_type, "image/png");
+    assert_eq!(image.dimensions()?, (40, 10));
+    assert_eq!(image.byte_len(), image.decode_data()?.len());
+    Ok(())
+}
+
+#[test]
+fn test_image_from_path_sniffs_mime_type() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("rmcp-image-{}.bin", std::process::id()));
+    DynamicImage::ImageRgb8(RgbImage::new(8, 6))
+        .save_with_format(&path, image::ImageFormat::Jpeg)?;
+    let result = RawImageContent::from_path(&path);
+    std::fs::remove_file(&path)?;
+    let image = result?;
+    assert_eq!(image.mime_type, "image/jpeg");
+    assert_eq!(image.dimensions()?, (8, 6));
+
+    assert!(RawImageContent::from_bytes(b"not an image").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_downscale_keeps_aspect_ratio_and_format() -> anyhow::Result<()> {
+    let large =
+        RawImageContent::from_dynamic_image(&DynamicImage::ImageRgb8(RgbImage::new(1000, 500)))?;
+    let small = large.downscale(100)?;
+    assert_eq!(small.dimensions()?, (100, 50));
+    assert_eq!(small.mime_type, "image/png");
+    assert!(small.byte_len() < large.byte_len());
+
+    // already small enough
+    assert_eq!(small.downscale(200)?, small);
+    Ok(())
+}
+
+#[test]
+fn test_downscale_images_transformer() -> anyhow::Result<()> {
+    let pipeline = ContentPipeline::new()
+        .then(DownscaleImages::new(64))
+        .for_tool("logs", ContentPipeline::new());
+    assert!(pipeline.is_blocking_for("screenshot"));
+    assert!(!pipeline.is_blocking_for("logs"));
+    let mut result = CallToolResult::success(vec![
+        Content::image_from_dynamic_image(&DynamicImage::ImageRgb8(RgbImage::new(256, 128)))?,
+        Content::image("not base64!", "image/png"),
+        Content::text("caption"),
+    ]);
+    pipeline.apply("screenshot", &mut result);
+
+    assert_eq!(image(&result.content[0]).dimensions()?, (64, 32));
+    assert_eq!(image(&result.content[1]).data, "not base64!");
+    assert!(matches!(&result.content[2].raw, RawContent::Text(text) if text.text == "caption"));
+    Ok(())
+}
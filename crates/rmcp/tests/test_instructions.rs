@@ -0,0 +1,104 @@
+//cargo test --test test_instructions --features "server client"
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{ClientInfo, Implementation, InitializeResult, ServerInfo},
+    service::{ClientOptions, RequestContext, serve_client_with_options},
+};
+
+#[derive(Debug, Clone)]
+struct Server {
+    instructions: String,
+}
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::default().with_instructions(self.instructions.clone())
+    }
+}
+
+/// Greets each client by name.
+#[derive(Debug, Clone)]
+struct Greeter;
+
+impl ServerHandler for Greeter {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::default().with_instructions("Greet the user.")
+    }
+
+    fn instructions(&self, context: &RequestContext<RoleServer>) -> Option<String> {
+        let client = context.peer.peer_info()?;
+        Some(format!("Greet {}.", client.client_info.name))
+    }
+}
+
+fn client_named(name: &str) -> ClientOptions {
+    ClientOptions::default().client_info(Implementation {
+        name: name.to_owned(),
+        version: "1.0.0".to_owned(),
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn test_client_reads_instructions() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(
+        Server {
+            instructions: "Call `search` first.".to_owned(),
+        }
+        .serve(server_transport),
+    );
+    let client = ClientInfo::default().serve(client_transport).await?;
+    server.await??;
+
+    assert_eq!(client.peer().instructions(), Some("Call `search` first."));
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_instructions_per_session() -> anyhow::Result<()> {
+    for name in ["alice", "bob"] {
+        let (server_transport, client_transport) = tokio::io::duplex(4096);
+        let server = tokio::spawn(Greeter.serve(server_transport));
+        let client = serve_client_with_options((), client_transport, client_named(name)).await?;
+        server.await??;
+
+        assert_eq!(
+            client.peer().instructions(),
+            Some(format!("Greet {name}.").as_str())
+        );
+        client.cancel().await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_too_long_instructions_fail_initialization() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(
+        Server {
+            instructions: "x".repeat(InitializeResult::MAX_INSTRUCTIONS_LEN + 1),
+        }
+        .serve(server_transport),
+    );
+    let client = ().serve(client_transport).await;
+    assert!(client.is_err());
+    let error = server.await?.expect_err("initialization should fail");
+    assert!(
+        error.to_string().contains("Instructions too long"),
+        "{error}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_validate_instructions() {
+    let info = ServerInfo::default();
+    assert!(info.validate().is_ok());
+    let info = info.with_instructions("x".repeat(InitializeResult::MAX_INSTRUCTIONS_LEN));
+    assert!(info.validate().is_ok());
+    let info = info.with_instructions("x".repeat(InitializeResult::MAX_INSTRUCTIONS_LEN + 1));
+    assert!(info.validate().is_err());
+}
@@ -0,0 +1,143 @@
+//cargo test --test test_interceptor --features "client server"
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use rmcp::{
+    ErrorData as McpError, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequestParams, CallToolResult, ClientRequest, Content, GetMeta, RawContent,
+        ServerResult,
+    },
+    service::{Interceptor, RequestContext, RoleClient, RoleServer},
+};
+use serde_json::json;
+
+/// Echoes the tool arguments and the `locale` found in `_meta`.
+struct Server;
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let locale = context.meta.get("locale").cloned().unwrap_or_default();
+        Ok(CallToolResult::success(vec![
+            Content::text(serde_json::to_string(&request.arguments).unwrap()),
+            Content::text(format!("locale={locale} secret=hunter2")),
+        ]))
+    }
+}
+
+struct InjectLocale;
+
+impl Interceptor<RoleClient> for InjectLocale {
+    fn on_outgoing(&self, request: &mut ClientRequest) {
+        request
+            .get_meta_mut()
+            .insert("locale".to_owned(), json!("fr-CH"));
+    }
+}
+
+struct NormalizeArguments;
+
+impl Interceptor<RoleClient> for NormalizeArguments {
+    fn on_outgoing(&self, request: &mut ClientRequest) {
+        if let ClientRequest::CallToolRequest(call) = request {
+            if let Some(query) = call
+                .params
+                .arguments
+                .as_mut()
+                .and_then(|arguments| arguments.get_mut("query"))
+            {
+                *query = json!(query.as_str().unwrap_or_default().trim().to_lowercase());
+            }
+        }
+    }
+}
+
+struct ScrubSecrets;
+
+impl Interceptor<RoleClient> for ScrubSecrets {
+    fn on_incoming(&self, response: &mut ServerResult) {
+        if let ServerResult::CallToolResult(result) = response {
+            for content in &mut result.content {
+                if let RawContent::Text(text) = &mut content.raw {
+                    text.text = text.text.replace("hunter2", "***");
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Count(Arc<AtomicUsize>);
+
+impl Interceptor<RoleClient> for Count {
+    fn on_incoming(&self, _response: &mut ServerResult) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn texts(result: &CallToolResult) -> Vec<String> {
+    result
+        .content
+        .iter()
+        .filter_map(|content| content.as_text().map(|text| text.text.clone()))
+        .collect()
+}
+
+fn call() -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: "echo".into(),
+        arguments: json!({ "query": "  Hello World " }).as_object().cloned(),
+        task: None,
+    }
+}
+
+#[tokio::test]
+async fn test_interceptors_rewrite_requests_and_responses() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (_server, client) = tokio::try_join!(
+        async { anyhow::Ok(Server.serve(server_transport).await?) },
+        async { anyhow::Ok(().serve(client_transport).await?) },
+    )?;
+
+    let count = Count::default();
+    let peer = client
+        .peer()
+        .clone()
+        .with_interceptor(InjectLocale)
+        .with_interceptor(NormalizeArguments)
+        .with_interceptor(ScrubSecrets)
+        .with_interceptor(count.clone());
+
+    let result = peer.call_tool(call()).await?;
+    assert_eq!(
+        texts(&result),
+        vec![
+            r#"{"query":"hello world"}"#.to_owned(),
+            r#"locale="fr-CH" secret=***"#.to_owned(),
+        ]
+    );
+    peer.list_tools(None).await?;
+    assert_eq!(count.0.load(Ordering::SeqCst), 2);
+
+    // the handle the interceptors were added to is left untouched
+    let result = client.peer().call_tool(call()).await?;
+    assert_eq!(
+        texts(&result),
+        vec![
+            r#"{"query":"  Hello World "}"#.to_owned(),
+            "locale=null secret=hunter2".to_owned(),
+        ]
+    );
+    assert_eq!(count.0.load(Ordering::SeqCst), 2);
+
+    client.cancel().await?;
+    Ok(())
+}
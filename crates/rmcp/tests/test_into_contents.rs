@@ -0,0 +1,143 @@
+//cargo test --test test_into_contents --features "server"
+
+use rmcp::{
+    handler::server::tool::IntoCallToolResult,
+    model::{Blob, IntoContents, RawContent, ResourceContents, Table, TableFormat, fmt},
+};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Serialize)]
+struct Deployment {
+    service: &'static str,
+    replicas: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<&'static str>,
+}
+
+#[test]
+fn test_value_into_contents() {
+    let result = json!({"ok": true}).into_call_tool_result().unwrap();
+    assert_eq!(result.text().unwrap(), r#"{"ok":true}"#);
+
+    let result: Result<serde_json::Value, String> = Err("quota exceeded".to_string());
+    let result = result.into_call_tool_result().unwrap();
+    assert!(result.is_error());
+    assert_eq!(result.text().unwrap(), "quota exceeded");
+}
+
+#[test]
+fn test_blob_into_contents() {
+    let contents = Blob::new(b"\x89PNG".to_vec(), "image/png").into_contents();
+    let RawContent::Image(image) = &contents[0].raw else {
+        panic!("expected image content");
+    };
+    assert_eq!(
+        (image.data.as_str(), image.mime_type.as_str()),
+        ("iVBORw==", "image/png")
+    );
+
+    let contents = Blob::new(vec![1, 2, 3], "application/x-tar")
+        .with_uri("file:///backup.tar")
+        .into_contents();
+    let RawContent::Resource(resource) = &contents[0].raw else {
+        panic!("expected embedded resource");
+    };
+    assert_eq!(
+        resource.resource,
+        ResourceContents::BlobResourceContents {
+            uri: "file:///backup.tar".to_string(),
+            mime_type: Some("application/x-tar".to_string()),
+            blob: "AQID".to_string(),
+            meta: None,
+            size: None,
+            last_modified: None,
+        }
+    );
+
+    let contents = vec![0u8, 1, 2].into_contents();
+    let RawContent::Resource(resource) = &contents[0].raw else {
+        panic!("expected embedded resource");
+    };
+    assert_eq!(resource.resource.uri(), Blob::DEFAULT_URI);
+    assert_eq!(
+        resource.resource.mime_type(),
+        Some("application/octet-stream")
+    );
+}
+
+#[test]
+fn test_status_tuple_into_call_tool_result() {
+    let result = (true, "done".to_string()).into_call_tool_result().unwrap();
+    assert!(!result.is_error());
+
+    let result = (503u16, json!({"retryAfter": 30}))
+        .into_call_tool_result()
+        .unwrap();
+    assert!(result.is_error());
+    assert_eq!(result.text().unwrap(), r#"{"retryAfter":30}"#);
+}
+
+#[test]
+fn test_table_into_contents() {
+    let rows = vec![
+        Deployment {
+            service: "api",
+            replicas: 3,
+            note: None,
+        },
+        Deployment {
+            service: "web",
+            replicas: 2,
+            note: Some("canary | 10%\nrollback ready"),
+        },
+    ];
+    let result = Table::new(rows).into_call_tool_result().unwrap();
+    assert_eq!(
+        result.text().unwrap(),
+        "| service | replicas | note |\n\
+         | --- | --- | --- |\n\
+         | api | 3 |  |\n\
+         | web | 2 | canary \\| 10%<br>rollback ready |\n"
+    );
+
+    let result = Table::new(vec![1, 2])
+        .with_format(TableFormat::Json)
+        .into_call_tool_result()
+        .unwrap();
+    assert_eq!(result.text().unwrap(), "[1,2]");
+
+    let result = Table::new(vec!["a", "b"]).into_call_tool_result().unwrap();
+    assert_eq!(result.text().unwrap(), "| value |\n| --- |\n| a |\n| b |\n");
+}
+
+#[test]
+fn test_markdown_helpers() {
+    let block = fmt::code_block("md", "```rust\nfn main() {}\n```");
+    assert_eq!(
+        block.to_string(),
+        "````md\n```rust\nfn main() {}\n```\n````\n"
+    );
+
+    let section = fmt::details("<stderr> & more", "line 1\nline 2\n");
+    assert_eq!(
+        section.to_string(),
+        "<details>\n<summary>&lt;stderr&gt; &amp; more</summary>\n\nline 1\nline 2\n\n</details>\n"
+    );
+
+    let deployments = [("api", 3), ("web", 2)].map(|(service, replicas)| Deployment {
+        service,
+        replicas,
+        note: None,
+    });
+    let report = fmt::table(deployments.iter())
+        .then("2 services")
+        .then(section);
+    let result = report.clone().into_call_tool_result().unwrap();
+    assert_eq!(
+        result.text().unwrap(),
+        "| service | replicas |\n| --- | --- |\n| api | 3 |\n| web | 2 |\n\n2 services\n\n\
+         <details>\n<summary>&lt;stderr&gt; &amp; more</summary>\n\nline 1\nline 2\n\n</details>\n"
+    );
+    assert_eq!(String::from(report), result.text().unwrap());
+}
@@ -0,0 +1,91 @@
+//cargo test --test test_lenient_params --features "server uuid time"
+
+use chrono::{DateTime, Utc};
+use rmcp::{
+    handler::server::{
+        tool::{parse_json_object, schema_for_type},
+        wrapper::Parameters,
+    },
+    model::lenient,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+struct ScheduleRequest {
+    job: Uuid,
+    #[serde(deserialize_with = "lenient::date_time::deserialize")]
+    start: DateTime<Utc>,
+    #[serde(default, deserialize_with = "lenient::date_time::option::deserialize")]
+    end: Option<DateTime<Utc>>,
+    #[serde(with = "lenient::offset_date_time")]
+    #[schemars(with = "lenient::DateTimeSchema")]
+    created: time::OffsetDateTime,
+}
+
+fn parse(arguments: serde_json::Value) -> Result<ScheduleRequest, rmcp::ErrorData> {
+    parse_json_object::<Parameters<ScheduleRequest>>(arguments.as_object().unwrap().clone())
+        .map(|Parameters(request)| request)
+}
+
+#[test]
+fn test_schema_formats() {
+    let schema = schema_for_type::<Parameters<ScheduleRequest>>();
+    let properties = &schema["properties"];
+    assert_eq!(properties["job"]["format"], "uuid");
+    assert_eq!(properties["start"]["format"], "date-time");
+    assert_eq!(properties["created"]["format"], "date-time");
+    assert_eq!(properties["created"]["type"], "string");
+}
+
+#[test]
+fn test_lenient_date_time_inputs() {
+    let expected = "2025-07-01T08:30:00Z";
+    for start in [
+        json!("2025-07-01T08:30:00Z"),
+        json!("2025-07-01T10:30:00+02:00"),
+        json!("2025-07-01 10:30:00+02:00"),
+        json!("Tue, 1 Jul 2025 10:30:00 +0200"),
+        json!("2025-07-01 08:30"),
+        json!(" 2025-07-01T08:30:00.000 "),
+        json!(1751358600),
+    ] {
+        let request = parse(json!({
+            "job": "{67E55044-10B1-426F-9247-BB680E5FE0C8}",
+            "start": start,
+            "created": 0,
+        }))
+        .unwrap_or_else(|error| panic!("{start}: {error}"));
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["start"],
+            expected,
+            "{start}"
+        );
+    }
+
+    let request = parse(json!({
+        "job": "urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8",
+        "start": "2025-07-01",
+        "end": 1751358600.5,
+        "created": "2025-07-01T10:30:00+02:00",
+    }))
+    .unwrap();
+    assert_eq!(
+        request.job.to_string(),
+        "67e55044-10b1-426f-9247-bb680e5fe0c8"
+    );
+    let value = serde_json::to_value(&request).unwrap();
+    assert_eq!(value["start"], "2025-07-01T00:00:00Z");
+    assert_eq!(value["end"], "2025-07-01T08:30:00.500Z");
+    assert_eq!(value["created"], "2025-07-01T08:30:00Z");
+    assert_eq!(request.created.unix_timestamp(), 1751358600);
+
+    let error =
+        parse(json!({"job": Uuid::nil(), "start": "next tuesday", "created": 0})).unwrap_err();
+    assert!(error.message.contains("next tuesday"), "{}", error.message);
+    for start in ["2025", "1751358600"] {
+        assert!(parse(json!({"job": Uuid::nil(), "start": start, "created": 0})).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//cargo test --test test_locale --features "server client macros"
+use rmcp::{
+    RoleClient, ServerHandler, ServiceExt,
+    handler::server::router::tool::ToolRouter,
+    model::{CallToolRequest, CallToolRequestParams, ClientRequest, Meta, ServerResult},
+    service::{
+        ClientOptions, LocalizedToolsService, PeerRequestOptions, RequestContext, RoleServer,
+        RunningService, serve_client_with_options,
+    },
+    tool, tool_handler, tool_router,
+};
+
+#[derive(Debug, Clone)]
+pub struct Greeter {
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Greeter {
+    /// Say hello
+    #[tool]
+    async fn greet(&self, context: RequestContext<RoleServer>) -> String {
+        match context.locale() {
+            Some(locale) if locale.starts_with("fr") => "bonjour".to_owned(),
+            Some(locale) if locale.starts_with("de") => "hallo".to_owned(),
+            _ => "hello".to_owned(),
+        }
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Greeter {}
+
+async fn connect(options: ClientOptions) -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let service = LocalizedToolsService::new(Greeter {
+        tool_router: Greeter::tool_router(),
+    })
+    .with_descriptions("fr", [("greet", "Dire bonjour")]);
+    tokio::spawn(async move {
+        let server = service.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(serve_client_with_options((), client_transport, options).await?)
+}
+
+async fn call_greet(
+    client: &RunningService<RoleClient, ()>,
+    locale: Option<&str>,
+) -> anyhow::Result<String> {
+    let mut options = PeerRequestOptions::no_options();
+    if let Some(locale) = locale {
+        options
+            .meta
+            .get_or_insert_with(Meta::new)
+            .set_locale(locale);
+    }
+    let request = ClientRequest::CallToolRequest(CallToolRequest::new(CallToolRequestParams {
+        meta: None,
+        name: "greet".into(),
+        arguments: None,
+        task: None,
+    }));
+    let response = client
+        .send_request_with_option(request, options)
+        .await?
+        .await_response()
+        .await?;
+    let ServerResult::CallToolResult(result) = response else {
+        anyhow::bail!("unexpected response {response:?}");
+    };
+    Ok(result.content[0].as_text().unwrap().text.clone())
+}
+
+#[tokio::test]
+async fn test_session_locale() -> anyhow::Result<()> {
+    let client = connect(ClientOptions::default().locale("fr-CA")).await?;
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools[0].description.as_deref(), Some("Dire bonjour"));
+    assert_eq!(call_greet(&client, None).await?, "bonjour");
+    // a request can ask for another locale
+    assert_eq!(call_greet(&client, Some("de")).await?, "hallo");
+    client.cancel().await?;
+
+    let client = connect(ClientOptions::default()).await?;
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools[0].description.as_deref(), Some("Say hello"));
+    assert_eq!(call_greet(&client, None).await?, "hello");
+    client.cancel().await?;
+    Ok(())
+}
@@ -15,11 +15,11 @@ async fn test_message_roles() {
     let messages = vec![
         SamplingMessage {
             role: Role::User,
-            content: Content::text("user message"),
+            content: Content::text("user message").into(),
         },
         SamplingMessage {
             role: Role::Assistant,
-            content: Content::text("assistant message"),
+            content: Content::text("assistant message").into(),
         },
     ];
 
@@ -52,7 +52,7 @@ async fn test_context_inclusion_integration() -> anyhow::Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::User,
-                content: Content::text("test message"),
+                content: Content::text("test message").into(),
             }],
             include_context: Some(ContextInclusion::ThisServer),
             model_preferences: None,
@@ -96,7 +96,7 @@ async fn test_context_inclusion_integration() -> anyhow::Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::User,
-                content: Content::text("test message"),
+                content: Content::text("test message").into(),
             }],
             include_context: Some(ContextInclusion::AllServers),
             model_preferences: None,
@@ -140,7 +140,7 @@ async fn test_context_inclusion_integration() -> anyhow::Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::User,
-                content: Content::text("test message"),
+                content: Content::text("test message").into(),
             }],
             include_context: Some(ContextInclusion::None),
             model_preferences: None,
@@ -204,7 +204,7 @@ async fn test_context_inclusion_ignored_integration() -> anyhow::Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::User,
-                content: Content::text("test message"),
+                content: Content::text("test message").into(),
             }],
             include_context: Some(ContextInclusion::ThisServer),
             model_preferences: None,
@@ -268,11 +268,11 @@ async fn test_message_sequence_integration() -> anyhow::Result<()> {
             messages: vec![
                 SamplingMessage {
                     role: Role::User,
-                    content: Content::text("first message"),
+                    content: Content::text("first message").into(),
                 },
                 SamplingMessage {
                     role: Role::Assistant,
-                    content: Content::text("second message"),
+                    content: Content::text("second message").into(),
                 },
             ],
             include_context: Some(ContextInclusion::ThisServer),
@@ -341,15 +341,15 @@ async fn test_message_sequence_validation_integration() -> anyhow::Result<()> {
             messages: vec![
                 SamplingMessage {
                     role: Role::User,
-                    content: Content::text("first user message"),
+                    content: Content::text("first user message").into(),
                 },
                 SamplingMessage {
                     role: Role::Assistant,
-                    content: Content::text("first assistant response"),
+                    content: Content::text("first assistant response").into(),
                 },
                 SamplingMessage {
                     role: Role::User,
-                    content: Content::text("second user message"),
+                    content: Content::text("second user message").into(),
                 },
             ],
             include_context: None,
@@ -386,7 +386,7 @@ async fn test_message_sequence_validation_integration() -> anyhow::Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::Assistant,
-                content: Content::text("assistant message"),
+                content: Content::text("assistant message").into(),
             }],
             include_context: None,
             model_preferences: None,
@@ -441,7 +441,7 @@ async fn test_selective_context_handling_integration() -> anyhow::Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::User,
-                content: Content::text("test message"),
+                content: Content::text("test message").into(),
             }],
             include_context: Some(ContextInclusion::ThisServer),
             model_preferences: None,
@@ -483,7 +483,7 @@ async fn test_selective_context_handling_integration() -> anyhow::Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::User,
-                content: Content::text("test message"),
+                content: Content::text("test message").into(),
             }],
             include_context: Some(ContextInclusion::AllServers),
             model_preferences: None,
@@ -542,7 +542,7 @@ async fn test_context_inclusion() -> anyhow::Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::User,
-                content: Content::text("test"),
+                content: Content::text("test").into(),
             }],
             include_context: Some(ContextInclusion::ThisServer),
             model_preferences: None,
@@ -1,6 +1,5 @@
 mod tests {
-    use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
-    use schemars::generate::SchemaSettings;
+    use rmcp::model::json_schema::{client_message_schema, protocol_schema, server_message_schema};
 
     fn compare_schemas(name: &str, actual: &str, expected_file: &str) {
         let expected = match std::fs::read_to_string(expected_file) {
@@ -48,10 +47,7 @@ mod tests {
 
     #[test]
     fn test_client_json_rpc_message_schema() {
-        let settings = SchemaSettings::draft07();
-        let schema = settings
-            .into_generator()
-            .into_root_schema_for::<ClientJsonRpcMessage>();
+        let schema = client_message_schema();
         let schema_str = serde_json::to_string_pretty(&schema).expect("Failed to serialize schema");
 
         compare_schemas(
@@ -63,10 +59,7 @@ mod tests {
 
     #[test]
     fn test_server_json_rpc_message_schema() {
-        let settings = SchemaSettings::draft07();
-        let schema = settings
-            .into_generator()
-            .into_root_schema_for::<ServerJsonRpcMessage>();
+        let schema = server_message_schema();
         let schema_str = serde_json::to_string_pretty(&schema).expect("Failed to serialize schema");
 
         compare_schemas(
@@ -75,4 +68,16 @@ mod tests {
             "tests/test_message_schema/server_json_rpc_message_schema.json",
         );
     }
+
+    #[test]
+    fn test_protocol_schema() {
+        let schema = protocol_schema();
+        let schema_str = serde_json::to_string_pretty(&schema).expect("Failed to serialize schema");
+
+        compare_schemas(
+            "JsonRpcMessage",
+            &schema_str,
+            "tests/test_message_schema/protocol_schema.json",
+        );
+    }
 }
@@ -0,0 +1,749 @@
+//! Property-based serialize/deserialize round trips for the model types.
+//!
+//! Every generated value must
+//! - come back unchanged after a trip through JSON,
+//! - still parse when the peer adds fields we don't know about,
+//! - only use camelCase keys on the wire (`_meta` being the one exception).
+use std::{borrow::Cow, fmt::Debug, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use proptest::{collection::vec, option::of, prelude::*};
+use rmcp::model::*;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+
+// ---------------------------------------------------------------------------
+// checks
+// ---------------------------------------------------------------------------
+
+fn assert_round_trip<T>(value: &T) -> Result<(), TestCaseError>
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let json = serde_json::to_value(value).expect("serialize");
+    let parsed: T = serde_json::from_value(json.clone())
+        .map_err(|e| TestCaseError::fail(format!("failed to parse back {json}: {e}")))?;
+    prop_assert_eq!(&parsed, value);
+    assert_camel_case(&json)?;
+
+    // unknown fields from newer peers must be ignored, not rejected
+    if let Value::Object(mut object) = json {
+        object.insert("someFutureField".into(), json!({"nested": [1, "two"]}));
+        let parsed: T = serde_json::from_value(Value::Object(object.clone())).map_err(|e| {
+            TestCaseError::fail(format!("unknown field rejected in {object:?}: {e}"))
+        })?;
+        prop_assert_eq!(&parsed, value);
+    }
+    Ok(())
+}
+
+/// For the JSON-RPC envelopes, whose in-memory form carries extensions that
+/// are not part of the wire format, compare the JSON instead.
+fn assert_wire_round_trip<T>(value: &T) -> Result<(), TestCaseError>
+where
+    T: Serialize + DeserializeOwned + Debug,
+{
+    let json = serde_json::to_value(value).expect("serialize");
+    let parsed: T = serde_json::from_value(json.clone())
+        .map_err(|e| TestCaseError::fail(format!("failed to parse back {json}: {e}")))?;
+    prop_assert_eq!(
+        serde_json::to_value(&parsed).expect("serialize"),
+        json.clone()
+    );
+    assert_camel_case(&json)
+}
+
+fn assert_camel_case(value: &Value) -> Result<(), TestCaseError> {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let body = key.strip_prefix('_').unwrap_or(key);
+                prop_assert!(
+                    body.starts_with(|c: char| c.is_ascii_lowercase())
+                        && body.chars().all(|c| c.is_ascii_alphanumeric()),
+                    "key {key:?} is not camelCase"
+                );
+                assert_camel_case(value)?;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                assert_camel_case(item)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// strategies
+// ---------------------------------------------------------------------------
+
+fn ident() -> impl Strategy<Value = String> {
+    "[a-z][a-zA-Z0-9]{0,8}"
+}
+
+fn text() -> impl Strategy<Value = String> {
+    ".{0,16}"
+}
+
+fn json_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        (-1.0e9..1.0e9f64).prop_map(Value::from),
+        text().prop_map(Value::from),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..4).prop_map(Value::Array),
+            vec((ident(), inner), 0..4)
+                .prop_map(|entries| Value::Object(entries.into_iter().collect())),
+        ]
+    })
+}
+
+/// Optional values include `Some(Value::Null)`, which is sent as an explicit
+/// `null` and must not read back as `None`.
+fn optional_json_value() -> impl Strategy<Value = Option<Value>> {
+    of(json_value())
+}
+
+fn json_object() -> impl Strategy<Value = JsonObject> {
+    vec((ident(), json_value()), 0..4)
+        .prop_map(|entries| entries.into_iter().collect::<Map<_, _>>())
+}
+
+fn meta() -> impl Strategy<Value = Option<Meta>> {
+    of(json_object().prop_map(Meta))
+}
+
+fn request_id() -> impl Strategy<Value = RequestId> {
+    prop_oneof![
+        any::<i64>().prop_map(NumberOrString::Number),
+        text().prop_map(|s| NumberOrString::String(s.into())),
+    ]
+}
+
+fn role() -> impl Strategy<Value = Role> {
+    prop_oneof![Just(Role::User), Just(Role::Assistant)]
+}
+
+fn timestamp() -> impl Strategy<Value = DateTime<Utc>> {
+    (0i64..4_102_444_800, 0u32..1_000_000_000)
+        .prop_map(|(secs, nanos)| DateTime::from_timestamp(secs, nanos).expect("in range"))
+}
+
+fn annotations() -> impl Strategy<Value = Option<Annotations>> {
+    of(
+        (of(vec(role(), 0..3)), of(0.0..=1.0f32), of(timestamp())).prop_map(
+            |(audience, priority, last_modified)| Annotations {
+                audience,
+                priority,
+                last_modified,
+            },
+        ),
+    )
+}
+
+fn icon() -> impl Strategy<Value = Icon> {
+    (text(), of(text()), of(vec(text(), 0..3))).prop_map(|(src, mime_type, sizes)| Icon {
+        src,
+        mime_type,
+        sizes,
+    })
+}
+
+fn icons() -> impl Strategy<Value = Option<Vec<Icon>>> {
+    of(vec(icon(), 0..3))
+}
+
+fn implementation() -> impl Strategy<Value = Implementation> {
+    (text(), of(text()), text(), icons(), of(text())).prop_map(
+        |(name, title, version, icons, website_url)| Implementation {
+            name,
+            title,
+            version,
+            icons,
+            website_url,
+        },
+    )
+}
+
+fn tool_annotations() -> impl Strategy<Value = ToolAnnotations> {
+    (
+        of(text()),
+        of(any::<bool>()),
+        of(any::<bool>()),
+        of(any::<bool>()),
+        of(any::<bool>()),
+        of(any::<bool>()),
+    )
+        .prop_map(
+            |(
+                title,
+                read_only_hint,
+                destructive_hint,
+                idempotent_hint,
+                open_world_hint,
+                requires_confirmation,
+            )| ToolAnnotations {
+                title,
+                read_only_hint,
+                destructive_hint,
+                idempotent_hint,
+                open_world_hint,
+                requires_confirmation,
+            },
+        )
+}
+
+fn tool() -> impl Strategy<Value = Tool> {
+    (
+        text(),
+        of(text()),
+        of(text()),
+        json_object(),
+        of(json_object()),
+        of(tool_annotations()),
+        icons(),
+        of(tool_execution()),
+        meta(),
+    )
+        .prop_map(
+            |(
+                name,
+                title,
+                description,
+                input_schema,
+                output_schema,
+                annotations,
+                icons,
+                execution,
+                meta,
+            )| {
+                Tool {
+                    name: Cow::Owned(name),
+                    title,
+                    description: description.map(Cow::Owned),
+                    input_schema: Arc::new(input_schema),
+                    output_schema: output_schema.map(Arc::new),
+                    annotations,
+                    icons,
+                    execution,
+                    meta,
+                }
+            },
+        )
+}
+
+fn tool_execution() -> impl Strategy<Value = ToolExecution> {
+    of(prop_oneof![
+        Just(TaskSupport::Forbidden),
+        Just(TaskSupport::Optional),
+        Just(TaskSupport::Required),
+    ])
+    .prop_map(|task_support| ToolExecution {
+        task_support,
+        threshold: None,
+        resumable: false,
+    })
+}
+
+fn raw_resource() -> impl Strategy<Value = RawResource> {
+    (
+        text(),
+        text(),
+        of(text()),
+        of(text()),
+        of(text()),
+        of(any::<u32>()),
+        icons(),
+        meta(),
+    )
+        .prop_map(
+            |(uri, name, title, description, mime_type, size, icons, meta)| RawResource {
+                uri,
+                name,
+                title,
+                description,
+                mime_type,
+                size,
+                icons,
+                meta,
+            },
+        )
+}
+
+fn resource() -> impl Strategy<Value = Resource> {
+    (raw_resource(), annotations()).prop_map(|(raw, annotations)| Annotated { raw, annotations })
+}
+
+fn resource_template() -> impl Strategy<Value = ResourceTemplate> {
+    (
+        text(),
+        text(),
+        of(text()),
+        of(text()),
+        of(text()),
+        icons(),
+        annotations(),
+    )
+        .prop_map(
+            |(uri_template, name, title, description, mime_type, icons, annotations)| Annotated {
+                raw: RawResourceTemplate {
+                    uri_template,
+                    name,
+                    title,
+                    description,
+                    mime_type,
+                    icons,
+                },
+                annotations,
+            },
+        )
+}
+
+fn resource_contents() -> impl Strategy<Value = ResourceContents> {
+    prop_oneof![
+        (text(), of(text()), text(), meta()).prop_map(|(uri, mime_type, text, meta)| {
+            ResourceContents::TextResourceContents {
+                uri,
+                mime_type,
+                text,
+                meta,
+                size: None,
+                last_modified: None,
+            }
+        }),
+        (text(), of(text()), "[A-Za-z0-9+/]{0,16}", meta()).prop_map(
+            |(uri, mime_type, blob, meta)| ResourceContents::BlobResourceContents {
+                uri,
+                mime_type,
+                blob,
+                meta,
+                size: None,
+                last_modified: None,
+            }
+        ),
+    ]
+}
+
+fn raw_content() -> impl Strategy<Value = RawContent> {
+    prop_oneof![
+        (text(), meta()).prop_map(|(text, meta)| RawContent::Text(RawTextContent { text, meta })),
+        (text(), text(), meta()).prop_map(|(data, mime_type, meta)| {
+            RawContent::Image(RawImageContent {
+                data,
+                mime_type,
+                meta,
+            })
+        }),
+        (resource_contents(), meta()).prop_map(|(resource, meta)| RawContent::Resource(
+            RawEmbeddedResource { meta, resource }
+        )),
+        (text(), text())
+            .prop_map(|(data, mime_type)| RawContent::Audio(RawAudioContent { data, mime_type })),
+        raw_resource().prop_map(RawContent::ResourceLink),
+    ]
+}
+
+fn content() -> impl Strategy<Value = Content> {
+    (raw_content(), annotations()).prop_map(|(raw, annotations)| Annotated { raw, annotations })
+}
+
+fn call_tool_result() -> impl Strategy<Value = CallToolResult> {
+    (
+        vec(content(), 1..4),
+        optional_json_value(),
+        of(any::<bool>()),
+        meta(),
+    )
+        .prop_map(
+            |(content, structured_content, is_error, meta)| CallToolResult {
+                content,
+                structured_content,
+                is_error,
+                meta,
+            },
+        )
+}
+
+fn prompt() -> impl Strategy<Value = Prompt> {
+    let argument = (text(), of(text()), of(text()), of(any::<bool>())).prop_map(
+        |(name, title, description, required)| PromptArgument {
+            name,
+            title,
+            description,
+            required,
+        },
+    );
+    (
+        text(),
+        of(text()),
+        of(text()),
+        of(vec(argument, 0..3)),
+        icons(),
+        meta(),
+    )
+        .prop_map(
+            |(name, title, description, arguments, icons, meta)| Prompt {
+                name,
+                title,
+                description,
+                arguments,
+                icons,
+                meta,
+            },
+        )
+}
+
+fn prompt_message() -> impl Strategy<Value = PromptMessage> {
+    let role = prop_oneof![
+        Just(PromptMessageRole::User),
+        Just(PromptMessageRole::Assistant)
+    ];
+    let content = prop_oneof![
+        text().prop_map(PromptMessageContent::text),
+        (text(), text(), meta(), annotations()).prop_map(|(data, mime_type, meta, annotations)| {
+            PromptMessageContent::Image {
+                image: Annotated {
+                    raw: RawImageContent {
+                        data,
+                        mime_type,
+                        meta,
+                    },
+                    annotations,
+                },
+            }
+        }),
+        (resource_contents(), meta(), annotations()).prop_map(|(resource, meta, annotations)| {
+            PromptMessageContent::Resource {
+                resource: Annotated {
+                    raw: RawEmbeddedResource { meta, resource },
+                    annotations,
+                },
+            }
+        }),
+        resource().prop_map(PromptMessageContent::resource_link),
+    ];
+    (role, content).prop_map(|(role, content)| PromptMessage { role, content })
+}
+
+fn protocol_version() -> impl Strategy<Value = ProtocolVersion> {
+    prop_oneof![
+        Just(ProtocolVersion::V_2024_11_05),
+        Just(ProtocolVersion::V_2025_03_26),
+        Just(ProtocolVersion::V_2025_06_18),
+        Just(ProtocolVersion::V_2025_11_25),
+    ]
+}
+
+fn initialize_request_params() -> impl Strategy<Value = InitializeRequestParams> {
+    (meta(), protocol_version(), implementation(), any::<bool>()).prop_map(
+        |(meta, protocol_version, client_info, roots)| {
+            let capabilities = if roots {
+                ClientCapabilities::builder()
+                    .enable_roots()
+                    .enable_sampling()
+                    .build()
+            } else {
+                ClientCapabilities::default()
+            };
+            InitializeRequestParams {
+                meta,
+                protocol_version,
+                capabilities,
+                client_info,
+            }
+        },
+    )
+}
+
+fn initialize_result() -> impl Strategy<Value = InitializeResult> {
+    (
+        protocol_version(),
+        implementation(),
+        of(text()),
+        any::<bool>(),
+    )
+        .prop_map(|(protocol_version, server_info, instructions, tools)| {
+            let capabilities = if tools {
+                ServerCapabilities::builder()
+                    .enable_tools()
+                    .enable_tool_list_changed()
+                    .enable_resources()
+                    .enable_logging()
+                    .build()
+            } else {
+                ServerCapabilities::default()
+            };
+            InitializeResult {
+                protocol_version,
+                capabilities,
+                server_info,
+                instructions,
+            }
+        })
+}
+
+fn logging_level() -> impl Strategy<Value = LoggingLevel> {
+    prop_oneof![
+        Just(LoggingLevel::Debug),
+        Just(LoggingLevel::Info),
+        Just(LoggingLevel::Notice),
+        Just(LoggingLevel::Warning),
+        Just(LoggingLevel::Error),
+        Just(LoggingLevel::Critical),
+        Just(LoggingLevel::Alert),
+        Just(LoggingLevel::Emergency),
+    ]
+}
+
+fn logging_message() -> impl Strategy<Value = LoggingMessageNotificationParam> {
+    (logging_level(), of(text()), json_value()).prop_map(|(level, logger, data)| {
+        LoggingMessageNotificationParam {
+            level,
+            logger,
+            data,
+        }
+    })
+}
+
+fn progress() -> impl Strategy<Value = ProgressNotificationParam> {
+    (request_id(), 0.0..1.0e6f64, of(0.0..1.0e6f64), of(text())).prop_map(
+        |(token, progress, total, message)| ProgressNotificationParam {
+            progress_token: ProgressToken(token),
+            progress,
+            total,
+            message,
+        },
+    )
+}
+
+fn create_message_params() -> impl Strategy<Value = CreateMessageRequestParams> {
+    let message =
+        (role(), content()).prop_map(|(role, content)| SamplingMessage::new(role, content));
+    let hint = of(text()).prop_map(|name| ModelHint { name });
+    let preferences = (
+        of(vec(hint, 0..3)),
+        of(0.0..=1.0f32),
+        of(0.0..=1.0f32),
+        of(0.0..=1.0f32),
+    )
+        .prop_map(
+            |(hints, cost_priority, speed_priority, intelligence_priority)| ModelPreferences {
+                hints,
+                cost_priority,
+                speed_priority,
+                intelligence_priority,
+            },
+        );
+    let include_context = prop_oneof![
+        Just(ContextInclusion::AllServers),
+        Just(ContextInclusion::None),
+        Just(ContextInclusion::ThisServer),
+    ];
+    (
+        meta(),
+        vec(message, 1..3),
+        of(preferences),
+        of(text()),
+        of(include_context),
+        of(0.0..=1.0f32),
+        any::<u32>(),
+        of(vec(text(), 0..3)),
+        optional_json_value(),
+    )
+        .prop_map(
+            |(
+                meta,
+                messages,
+                model_preferences,
+                system_prompt,
+                include_context,
+                temperature,
+                max_tokens,
+                stop_sequences,
+                metadata,
+            )| CreateMessageRequestParams {
+                meta,
+                task: None,
+                messages,
+                model_preferences,
+                system_prompt,
+                include_context,
+                temperature,
+                max_tokens,
+                stop_sequences,
+                metadata,
+            },
+        )
+}
+
+fn call_tool_params() -> impl Strategy<Value = CallToolRequestParams> {
+    (meta(), text(), of(json_object())).prop_map(|(meta, name, arguments)| CallToolRequestParams {
+        meta,
+        name: Cow::Owned(name),
+        arguments,
+        task: None,
+    })
+}
+
+fn error_data() -> impl Strategy<Value = ErrorData> {
+    (any::<i32>(), text(), optional_json_value())
+        .prop_map(|(code, message, data)| ErrorData::new(ErrorCode(code), message, data))
+}
+
+fn client_message() -> impl Strategy<Value = ClientJsonRpcMessage> {
+    prop_oneof![
+        (call_tool_params(), request_id()).prop_map(|(params, id)| {
+            ClientJsonRpcMessage::request(ClientRequest::CallToolRequest(Request::new(params)), id)
+        }),
+        (initialize_request_params(), request_id()).prop_map(|(params, id)| {
+            ClientJsonRpcMessage::request(
+                ClientRequest::InitializeRequest(Request::new(params)),
+                id,
+            )
+        }),
+        progress().prop_map(|params| {
+            ClientJsonRpcMessage::notification(ClientNotification::ProgressNotification(
+                Notification::new(params),
+            ))
+        }),
+        (error_data(), request_id()).prop_map(|(error, id)| ClientJsonRpcMessage::error(error, id)),
+    ]
+}
+
+fn server_message() -> impl Strategy<Value = ServerJsonRpcMessage> {
+    prop_oneof![
+        (create_message_params(), request_id()).prop_map(|(params, id)| {
+            ServerJsonRpcMessage::request(
+                ServerRequest::CreateMessageRequest(Request::new(params)),
+                id,
+            )
+        }),
+        (call_tool_result(), request_id()).prop_map(|(result, id)| {
+            ServerJsonRpcMessage::response(ServerResult::CallToolResult(result), id)
+        }),
+        (initialize_result(), request_id()).prop_map(|(result, id)| {
+            ServerJsonRpcMessage::response(ServerResult::InitializeResult(result), id)
+        }),
+        logging_message().prop_map(|params| {
+            ServerJsonRpcMessage::notification(ServerNotification::LoggingMessageNotification(
+                Notification::new(params),
+            ))
+        }),
+        (error_data(), request_id()).prop_map(|(error, id)| ServerJsonRpcMessage::error(error, id)),
+    ]
+}
+
+// ---------------------------------------------------------------------------
+// properties
+// ---------------------------------------------------------------------------
+
+#[test]
+fn explicit_null_is_not_absent() {
+    let error = ErrorData::new(ErrorCode(0), "", Some(Value::Null));
+    assert_eq!(
+        serde_json::to_value(&error).unwrap(),
+        json!({"code": 0, "message": "", "data": null})
+    );
+    let parsed: ErrorData = serde_json::from_value(json!({"code": 0, "message": ""})).unwrap();
+    assert_eq!(parsed.data, None);
+    let parsed: ErrorData =
+        serde_json::from_value(json!({"code": 0, "message": "", "data": null})).unwrap();
+    assert_eq!(parsed.data, Some(Value::Null));
+
+    let result: CallToolResult = serde_json::from_value(
+        json!({"content": [{"type": "text", "text": ""}], "structuredContent": null}),
+    )
+    .unwrap();
+    assert_eq!(result.structured_content, Some(Value::Null));
+}
+
+proptest! {
+    #[test]
+    fn tool_round_trip(value in tool()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn resource_round_trip(value in resource()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn resource_template_round_trip(value in resource_template()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn resource_contents_round_trip(value in resource_contents()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn content_round_trip(value in content()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn call_tool_result_round_trip(value in call_tool_result()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn prompt_round_trip(value in prompt()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn prompt_message_round_trip(value in prompt_message()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn implementation_round_trip(value in implementation()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn initialize_round_trip(params in initialize_request_params(), result in initialize_result()) {
+        assert_round_trip(&params)?;
+        assert_round_trip(&result)?;
+    }
+
+    #[test]
+    fn notification_params_round_trip(logging in logging_message(), progress in progress()) {
+        assert_round_trip(&logging)?;
+        assert_round_trip(&progress)?;
+    }
+
+    #[test]
+    fn create_message_params_round_trip(value in create_message_params()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn call_tool_params_round_trip(value in call_tool_params()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn error_data_round_trip(value in error_data()) {
+        assert_round_trip(&value)?;
+    }
+
+    #[test]
+    fn client_message_round_trip(value in client_message()) {
+        assert_wire_round_trip(&value)?;
+    }
+
+    #[test]
+    fn server_message_round_trip(value in server_message()) {
+        assert_wire_round_trip(&value)?;
+    }
+}
@@ -0,0 +1,87 @@
+//cargo test --test test_notification_coalescer --features "client server"
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rmcp::{
+    ClientHandler, RoleClient, ServerHandler, ServiceExt,
+    model::ResourceUpdatedNotificationParam,
+    service::{NotificationCoalescer, NotificationContext},
+};
+
+struct Server;
+
+impl ServerHandler for Server {}
+
+#[derive(Clone, Default)]
+struct RecordingClient {
+    received: Arc<Mutex<Vec<String>>>,
+}
+
+impl ClientHandler for RecordingClient {
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.received.lock().unwrap().push(params.uri);
+    }
+
+    async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        self.received.lock().unwrap().push("tools".to_string());
+    }
+}
+
+impl RecordingClient {
+    fn count(&self, name: &str) -> usize {
+        self.received
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|n| *n == name)
+            .count()
+    }
+}
+
+#[tokio::test]
+async fn test_bursts_are_coalesced() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let client = RecordingClient::default();
+    let (server, running_client) = tokio::try_join!(
+        async { anyhow::Ok(Server.serve(server_transport).await?) },
+        async { anyhow::Ok(client.clone().serve(client_transport).await?) },
+    )?;
+
+    let window = Duration::from_millis(200);
+    let coalescer = NotificationCoalescer::new(server.peer().clone(), window);
+    for _ in 0..20 {
+        coalescer.tool_list_changed();
+        coalescer.resource_updated("file:///a");
+    }
+    coalescer.resource_updated("file:///b");
+
+    // the leading notifications go out immediately
+    tokio::time::sleep(window / 2).await;
+    assert_eq!(client.count("tools"), 1);
+    assert_eq!(client.count("file:///a"), 1);
+    assert_eq!(client.count("file:///b"), 1);
+
+    // the rest of the burst collapses into one trailing notification
+    tokio::time::sleep(window * 2).await;
+    assert_eq!(client.count("tools"), 2);
+    assert_eq!(client.count("file:///a"), 2);
+    assert_eq!(client.count("file:///b"), 1);
+
+    // a new burst inside a fresh window is flushed when the coalescer goes away
+    coalescer.tool_list_changed();
+    coalescer.tool_list_changed();
+    drop(coalescer);
+    tokio::time::sleep(window / 2).await;
+    assert_eq!(client.count("tools"), 4);
+
+    running_client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
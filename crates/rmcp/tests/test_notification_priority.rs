@@ -0,0 +1,110 @@
+//cargo test --test test_notification_priority --features "client server"
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    ClientHandler, ErrorData as McpError, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::*,
+    service::{NotificationContext, Peer, RequestContext},
+};
+
+#[derive(Clone, Default)]
+struct NoisyServer {
+    peer: Arc<std::sync::Mutex<Option<Peer<RoleServer>>>>,
+}
+
+impl ServerHandler for NoisyServer {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        // flood the sink with log messages, then answer right away; none of
+        // them waits for the write or fails when dropped
+        for n in 0..2000 {
+            context
+                .peer
+                .notify_logging_message(LoggingMessageNotificationParam {
+                    level: LoggingLevel::Info,
+                    logger: None,
+                    data: n.into(),
+                })
+                .await
+                .expect("low-priority notifications are fire-and-forget");
+        }
+        *self.peer.lock().unwrap() = Some(context.peer);
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+}
+
+#[derive(Clone, Default)]
+struct CountingClient {
+    received: Arc<AtomicUsize>,
+}
+
+impl ClientHandler for CountingClient {
+    async fn on_logging_message(
+        &self,
+        _params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.received.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn test_responses_are_not_starved_by_notifications() -> anyhow::Result<()> {
+    let server = NoisyServer::default();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    {
+        let server = server.clone();
+        tokio::spawn(async move {
+            let server = server.serve(server_transport).await?;
+            server.waiting().await?;
+            anyhow::Ok(())
+        });
+    }
+    let counter = CountingClient::default();
+    let client = counter.clone().serve(client_transport).await?;
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(5),
+        client.call_tool(CallToolRequestParams {
+            meta: None,
+            name: "noisy".into(),
+            arguments: None,
+            task: None,
+        }),
+    )
+    .await??;
+    assert_eq!(result.content[0].as_text().unwrap().text, "done");
+    client.list_tools(None).await?;
+
+    // every notification is either delivered or counted as dropped
+    let peer = server
+        .peer
+        .lock()
+        .unwrap()
+        .clone()
+        .expect("tool was called");
+    assert!(peer.dropped_notifications() > 0);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while counter.received.load(Ordering::SeqCst) + peer.dropped_notifications() < 2000 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    assert_eq!(
+        counter.received.load(Ordering::SeqCst) + peer.dropped_notifications(),
+        2000
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//cargo test --test test_number_safety --features "server"
+
+use rmcp::{
+    model::{
+        CallToolResult,
+        number::{self, MAX_SAFE_INTEGER, NumberPolicy, UnsafeNumberError},
+    },
+    service::ContentPipeline,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Ledger {
+    #[serde(with = "number::as_string")]
+    balance: i128,
+    #[serde(with = "number::as_string")]
+    account: u64,
+    #[serde(with = "number::non_finite_as_string")]
+    growth: f64,
+}
+
+#[test]
+fn test_number_policy() {
+    let document = json!({
+        "small": MAX_SAFE_INTEGER,
+        "items": [{"id": MAX_SAFE_INTEGER + 1}, {"id": -(MAX_SAFE_INTEGER as i64) - 1}],
+        "ratio": 1e300,
+    });
+
+    let mut allowed = document.clone();
+    NumberPolicy::Allow.apply(&mut allowed).unwrap();
+    assert_eq!(allowed, document);
+
+    let mut encoded = document.clone();
+    NumberPolicy::EncodeAsString.apply(&mut encoded).unwrap();
+    assert_eq!(
+        encoded,
+        json!({
+            "small": MAX_SAFE_INTEGER,
+            "items": [{"id": "9007199254740992"}, {"id": "-9007199254740992"}],
+            "ratio": 1e300,
+        })
+    );
+
+    let mut rejected = document.clone();
+    assert_eq!(
+        NumberPolicy::Reject.apply(&mut rejected),
+        Err(UnsafeNumberError {
+            path: "/items/0/id".to_string(),
+            value: "9007199254740992".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_serde_helpers_round_trip() {
+    let ledger = Ledger {
+        balance: -170141183460469231731687303715884105728,
+        account: u64::MAX,
+        growth: f64::NEG_INFINITY,
+    };
+    let value = serde_json::to_value(&ledger).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "balance": "-170141183460469231731687303715884105728",
+            "account": "18446744073709551615",
+            "growth": "-Infinity",
+        })
+    );
+    assert_eq!(serde_json::from_value::<Ledger>(value).unwrap(), ledger);
+
+    // plain numbers are accepted too
+    let ledger: Ledger =
+        serde_json::from_value(json!({"balance": 12, "account": 7, "growth": 0.5})).unwrap();
+    assert_eq!(
+        (ledger.balance, ledger.account, ledger.growth),
+        (12, 7, 0.5)
+    );
+    assert!(
+        serde_json::from_value::<Ledger>(json!({"balance": "12x", "account": 7, "growth": 0.5}))
+            .is_err()
+    );
+
+    let nan: Ledger =
+        serde_json::from_value(json!({"balance": 0, "account": 0, "growth": "NaN"})).unwrap();
+    assert!(nan.growth.is_nan());
+}
+
+#[test]
+fn test_number_policy_in_content_pipeline() {
+    let pipeline = ContentPipeline::new().then(NumberPolicy::Reject).for_tool(
+        "ids",
+        ContentPipeline::new().then(NumberPolicy::EncodeAsString),
+    );
+
+    let mut result = CallToolResult::structured(json!({"id": u64::MAX}));
+    pipeline.apply("ids", &mut result);
+    assert_eq!(
+        result.structured_content,
+        Some(json!({"id": "18446744073709551615"}))
+    );
+    assert_eq!(result.text(), Some(r#"{"id":"18446744073709551615"}"#));
+
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "integer", "default": {"type": "integer"}},
+            "scores": {"type": "array", "items": {"type": ["number", "null"]}},
+            "name": {"type": "string"},
+        },
+    });
+    let mut encoded = schema.as_object().unwrap().clone();
+    pipeline.apply_to_output_schema("ids", &mut encoded);
+    assert_eq!(
+        serde_json::Value::Object(encoded),
+        json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": ["integer", "string"], "default": {"type": "integer"}},
+                "scores": {"type": "array", "items": {"type": ["number", "null", "string"]}},
+                "name": {"type": "string"},
+            },
+        })
+    );
+    let mut rejected = schema.as_object().unwrap().clone();
+    pipeline.apply_to_output_schema("lookup", &mut rejected);
+    assert_eq!(serde_json::Value::Object(rejected), schema);
+
+    let mut result = CallToolResult::structured(json!({"id": u64::MAX}));
+    pipeline.apply("lookup", &mut result);
+    assert!(result.is_error());
+    assert!(result.structured_content.is_none());
+    assert!(result.text().unwrap().contains("/id"));
+}
@@ -0,0 +1,161 @@
+//cargo test --test test_overload --features "client server"
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rmcp::{
+    RoleServer, ServerHandler, ServiceError, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult, Content, ErrorCode, GetPromptRequestParams},
+    service::{OverloadController, OverloadService, OverloadThresholds, Pressure, RequestContext},
+};
+use tokio::sync::Semaphore;
+
+/// Tool calls wait until the test lets them finish.
+#[derive(Clone)]
+struct Gated(Arc<Semaphore>);
+
+impl ServerHandler for Gated {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        let _permit = self.0.acquire().await.expect("gate open");
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+}
+
+fn call() -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: "wait".into(),
+        arguments: None,
+        task: None,
+    }
+}
+
+#[tokio::test]
+async fn test_sheds_low_priority_requests_under_pressure() -> anyhow::Result<()> {
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let controller = Arc::new(
+        OverloadController::new(OverloadThresholds {
+            max_in_flight: Some(1),
+            ..Default::default()
+        })
+        .with_pressure_hook({
+            let changes = changes.clone();
+            move |pressure| changes.lock().unwrap().push(pressure)
+        }),
+    );
+    let gate = Arc::new(Semaphore::new(0));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (_server, client) = tokio::try_join!(
+        async {
+            anyhow::Ok(
+                OverloadService::new(Gated(gate.clone()), controller.clone())
+                    .serve(server_transport)
+                    .await?,
+            )
+        },
+        async { anyhow::Ok(().serve(client_transport).await?) },
+    )?;
+    client.list_all_tools().await?;
+
+    let peer = client.peer().clone();
+    let calls =
+        tokio::spawn(
+            async move { tokio::try_join!(peer.call_tool(call()), peer.call_tool(call())) },
+        );
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while controller.in_flight() < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    assert_eq!(controller.pressure(), Pressure::Elevated);
+
+    let ServiceError::McpError(error) = client.list_all_tools().await.unwrap_err() else {
+        panic!("expected an mcp error");
+    };
+    assert_eq!(error.code, ErrorCode::SERVER_OVERLOADED);
+    assert!(error.is_retryable());
+    assert_eq!(error.data.unwrap()["pressure"], "elevated");
+    assert_eq!(controller.shed_count(), 1);
+    // normal priority still goes through, and fails on its own merits
+    let ServiceError::McpError(error) = client
+        .get_prompt(GetPromptRequestParams {
+            meta: None,
+            name: "missing".into(),
+            arguments: None,
+        })
+        .await
+        .unwrap_err()
+    else {
+        panic!("expected an mcp error");
+    };
+    assert_ne!(error.code, ErrorCode::SERVER_OVERLOADED);
+
+    gate.add_permits(2);
+    calls.await??;
+    assert_eq!(controller.pressure(), Pressure::Normal);
+    client.list_all_tools().await?;
+    // the prompt request briefly made it three in flight
+    assert_eq!(
+        *changes.lock().unwrap(),
+        [
+            Pressure::Elevated,
+            Pressure::Severe,
+            Pressure::Elevated,
+            Pressure::Normal
+        ]
+    );
+    Ok(())
+}
+
+/// Tool calls take a while.
+#[derive(Clone)]
+struct Slow(Duration);
+
+impl ServerHandler for Slow {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        tokio::time::sleep(self.0).await;
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+}
+
+#[tokio::test]
+async fn test_latency_pressure_eases_without_traffic() -> anyhow::Result<()> {
+    let controller = Arc::new(OverloadController::new(OverloadThresholds {
+        max_latency: Some(Duration::from_millis(10)),
+        retry_after: Duration::from_millis(50),
+        ..Default::default()
+    }));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (_server, client) = tokio::try_join!(
+        async {
+            anyhow::Ok(
+                OverloadService::new(Slow(Duration::from_millis(400)), controller.clone())
+                    .serve(server_transport)
+                    .await?,
+            )
+        },
+        async { anyhow::Ok(().serve(client_transport).await?) },
+    )?;
+
+    client.call_tool(call()).await?;
+    assert_eq!(controller.pressure(), Pressure::Severe);
+    assert!(client.list_all_tools().await.is_err());
+
+    // only shed requests came in since, yet the slow call is forgotten
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    client.list_all_tools().await?;
+    assert_eq!(controller.pressure(), Pressure::Normal);
+    assert_eq!(controller.shed_count(), 1);
+    Ok(())
+}
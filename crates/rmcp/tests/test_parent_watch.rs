@@ -0,0 +1,56 @@
+//cargo test --test test_parent_watch --features "client server transport-io"
+
+use std::time::Duration;
+
+use rmcp::{ServerHandler, ServiceExt, service::QuitReason, transport::ParentWatch};
+
+struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_parent_alive() {
+    let watch = ParentWatch {
+        poll_interval: Duration::from_millis(10),
+        ..Default::default()
+    };
+    // the test harness is still running, so this must not resolve
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), watch.parent_exited())
+            .await
+            .is_err()
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_parent_gone_before_watching() {
+    // re-parented to init, or to a subreaper, before the watch was created
+    for parent in [1, u32::MAX] {
+        let watch = ParentWatch {
+            poll_interval: Duration::from_millis(10),
+            parent: Some(parent),
+            ..Default::default()
+        };
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), watch.parent_exited())
+                .await
+                .is_ok()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_supervise_returns_when_session_ends() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        async { anyhow::Ok(Server.serve(server_transport).await?) },
+        async { anyhow::Ok(().serve(client_transport).await?) },
+    )?;
+    let supervised = tokio::spawn(async move { ParentWatch::default().supervise(server).await });
+
+    client.cancel().await?;
+    let reason = tokio::time::timeout(Duration::from_secs(5), supervised).await??;
+    assert!(matches!(reason, QuitReason::Closed));
+    Ok(())
+}
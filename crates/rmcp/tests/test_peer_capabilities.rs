@@ -0,0 +1,122 @@
+//cargo test --test test_peer_capabilities --features "server client"
+use rmcp::{
+    ServerHandler, ServiceExt,
+    model::{
+        ClientCapabilities, ExperimentalCapabilities, ExperimentalCapabilitiesExt, ProtocolVersion,
+        ServerCapabilities, ServerInfo,
+    },
+    service::{ClientOptions, serve_client_with_options},
+};
+
+#[derive(Debug, Clone)]
+struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_both_peers_see_negotiated_session() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server.serve(server_transport));
+    let options = ClientOptions::default()
+        .protocol_version(ProtocolVersion::V_2025_03_26)
+        .capabilities(ClientCapabilities::builder().enable_sampling().build());
+    let client = serve_client_with_options((), client_transport, options).await?;
+    let server = server.await??;
+
+    assert_eq!(
+        client.peer().protocol_version(),
+        Some(&ProtocolVersion::V_2025_03_26)
+    );
+    assert_eq!(
+        server.peer().protocol_version(),
+        Some(&ProtocolVersion::V_2025_03_26)
+    );
+
+    let server_capabilities = client
+        .peer()
+        .peer_capabilities()
+        .expect("server capabilities");
+    assert!(server_capabilities.tools.is_some());
+    assert!(server_capabilities.prompts.is_none());
+    let client_capabilities = server
+        .peer()
+        .peer_capabilities()
+        .expect("client capabilities");
+    assert!(client_capabilities.sampling.is_some());
+    assert!(client_capabilities.elicitation.is_none());
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+struct ExperimentalServer;
+
+impl ServerHandler for ExperimentalServer {
+    fn get_info(&self) -> ServerInfo {
+        let mut experimental = ExperimentalCapabilities::new();
+        experimental.insert_versioned("x-test/feed", ["1", "2", "3"]);
+        experimental.insert_versioned("x-test/server-only", ["1"]);
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_experimental_with(experimental)
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_experimental_versions_are_negotiated() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(ExperimentalServer.serve(server_transport));
+    let mut experimental = ExperimentalCapabilities::new();
+    experimental.insert_versioned("x-test/feed", ["2", "1"]);
+    experimental.insert_versioned("x-test/client-only", ["1"]);
+    let options = ClientOptions::default().capabilities(
+        ClientCapabilities::builder()
+            .enable_experimental_with(experimental)
+            .build(),
+    );
+    let client = serve_client_with_options((), client_transport, options).await?;
+    let server = server.await??;
+
+    for peer in [
+        client.peer().negotiated_experimental("x-test/feed"),
+        server.peer().negotiated_experimental("x-test/feed"),
+    ] {
+        assert_eq!(peer, Some("2"));
+    }
+    assert_eq!(
+        client.peer().negotiated_experimental("x-test/server-only"),
+        None
+    );
+    assert_eq!(
+        server.peer().negotiated_experimental("x-test/client-only"),
+        None
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_experimental_negotiated_without_peer_support() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(ExperimentalServer.serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    assert_eq!(client.peer().negotiated_experimental("x-test/feed"), None);
+    assert_eq!(server.peer().negotiated_experimental("x-test/feed"), None);
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//cargo test --test test_pending_requests --features "client server"
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use rmcp::{
+    ErrorData as McpError, RoleServer, ServerHandler, ServiceError, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult, ClientRequest, Request},
+    service::{PeerRequestOptions, RequestContext},
+};
+
+/// A tool that runs until it is cancelled.
+#[derive(Clone, Default)]
+struct Endless {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ServerHandler for Endless {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        context.ct.cancelled().await;
+        self.cancelled.store(true, Ordering::SeqCst);
+        Err(McpError::internal_error("cancelled", None))
+    }
+}
+
+#[tokio::test]
+async fn test_pending_requests_can_be_cancelled() -> anyhow::Result<()> {
+    let server = Endless::default();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn({
+        let server = server.clone();
+        async move {
+            let server = server.serve(server_transport).await?;
+            server.waiting().await?;
+            anyhow::Ok(())
+        }
+    });
+    let client = ().serve(client_transport).await?;
+    assert!(client.pending_requests().is_empty());
+
+    let params = CallToolRequestParams {
+        meta: None,
+        name: "endless".into(),
+        arguments: None,
+        task: None,
+    };
+    let handle = client
+        .send_cancellable_request(
+            ClientRequest::CallToolRequest(Request::new(params)),
+            PeerRequestOptions::no_options(),
+        )
+        .await?;
+    // finished requests aren't pending
+    client
+        .send_request(ClientRequest::PingRequest(Default::default()))
+        .await?;
+
+    let pending = client.pending_requests();
+    let [call] = &pending[..] else {
+        panic!("expected one pending request, got {pending:?}");
+    };
+    assert_eq!(call.id, handle.id);
+    assert_eq!(call.method, "tools/call");
+    assert_eq!(call.progress_token, handle.progress_token);
+
+    // any handle on the session can cancel it
+    let peer = client.peer().clone();
+    assert!(peer.cancel_request(&call.id, Some("stop".into())).await?);
+    let Err(ServiceError::Cancelled { reason }) = handle.await_response().await else {
+        panic!("expected the request to be cancelled");
+    };
+    assert_eq!(reason.as_deref(), Some("stop"));
+    assert!(client.pending_requests().is_empty());
+    assert!(!peer.cancel_request(&call.id, None).await?);
+
+    for _ in 0..50 {
+        if server.cancelled.load(Ordering::SeqCst) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert!(server.cancelled.load(Ordering::SeqCst));
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,352 @@
+//cargo test --test test_providers --features "client server"
+use std::time::Duration;
+
+use futures::future::{BoxFuture, FutureExt};
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::server::provider::{PromptProvider, ResourceProvider, ResourceTemplateProvider},
+    model::*,
+    service::{NotificationContext, RequestContext},
+};
+use tokio::sync::mpsc;
+
+#[derive(Clone, Default)]
+struct Server {
+    prompts: PromptProvider,
+    resources: ResourceProvider,
+}
+
+impl ServerHandler for Server {
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, ErrorData> {
+        Ok(self.prompts.list(&context))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        self.prompts.get(request).await
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        Ok(self.resources.list(&context))
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, ErrorData> {
+        Ok(self.resources.list_templates(&context))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        self.resources.read(request, &context).await
+    }
+
+    async fn complete(
+        &self,
+        request: CompleteRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CompleteResult, ErrorData> {
+        self.resources.complete(request).await
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities {
+                prompts: Some(self.prompts.capability()),
+                resources: Some(self.resources.capability()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Changed {
+    Prompts,
+    Resources,
+}
+
+#[derive(Clone)]
+struct Client(mpsc::UnboundedSender<Changed>);
+
+impl ClientHandler for Client {
+    async fn on_prompt_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        let _ = self.0.send(Changed::Prompts);
+    }
+
+    async fn on_resource_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        let _ = self.0.send(Changed::Resources);
+    }
+}
+
+fn greeting(
+    text: &'static str,
+) -> impl Fn(Option<JsonObject>) -> std::future::Ready<Result<GetPromptResult, ErrorData>> {
+    move |_arguments| {
+        std::future::ready(Ok(GetPromptResult {
+            description: None,
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        }))
+    }
+}
+
+#[tokio::test]
+async fn test_mutations_notify_listing_sessions() -> anyhow::Result<()> {
+    let server = Server::default();
+    server.prompts.insert(
+        Prompt::new("hello", None::<String>, None),
+        greeting("hello"),
+    );
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (tx, mut changes) = mpsc::unbounded_channel();
+    let (running_server, client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(Client(tx).serve(client_transport).await?) },
+    )?;
+
+    // sessions that never listed aren't told
+    server.prompts.insert(
+        Prompt::new("early", None::<String>, None),
+        greeting("early"),
+    );
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), changes.recv())
+            .await
+            .is_err()
+    );
+
+    let prompts = client.list_prompts(None).await?.prompts;
+    assert_eq!(prompts.len(), 2);
+    assert!(server.prompts.remove("early"));
+    assert_eq!(changes.recv().await, Some(Changed::Prompts));
+    let prompt = client
+        .get_prompt(GetPromptRequestParams {
+            meta: None,
+            name: "hello".into(),
+            arguments: None,
+        })
+        .await?;
+    assert_eq!(prompt.messages.len(), 1);
+
+    client.list_resources(None).await?;
+    server.resources.insert(
+        RawResource::new("memo://notes", "notes").no_annotation(),
+        || async {
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text("hi", "memo://notes")],
+            })
+        },
+    );
+    assert_eq!(changes.recv().await, Some(Changed::Resources));
+    let read = client
+        .read_resource(ReadResourceRequestParams {
+            meta: None,
+            uri: "memo://notes".into(),
+        })
+        .await?;
+    assert_eq!(read.contents.len(), 1);
+    // removing nothing changes nothing
+    assert!(!server.resources.remove("memo://missing"));
+
+    client.cancel().await?;
+    running_server.cancel().await?;
+    Ok(())
+}
+
+const REFS: &str = "repo://{owner}/{repo}/refs/{ref}";
+
+/// Suggests branches of the repo already picked, and far too many owners.
+struct Refs;
+
+impl ResourceTemplateProvider for Refs {
+    fn template(&self) -> ResourceTemplate {
+        RawResourceTemplate {
+            uri_template: REFS.into(),
+            name: "refs".into(),
+            title: None,
+            description: None,
+            mime_type: None,
+            icons: None,
+        }
+        .no_annotation()
+    }
+
+    fn complete(
+        &self,
+        argument: ArgumentInfo,
+        context: Option<CompletionContext>,
+    ) -> BoxFuture<'_, Result<Vec<String>, ErrorData>> {
+        let repo = context
+            .and_then(|context| context.arguments?.get("repo").cloned())
+            .unwrap_or_default();
+        let values = match argument.name.as_str() {
+            "ref" => ["main", "release", "review"]
+                .into_iter()
+                .filter(|branch| branch.starts_with(&argument.value))
+                .map(|branch| format!("{repo}/{branch}"))
+                .collect(),
+            "owner" => (0..150).map(|i| format!("owner-{i}")).collect(),
+            _ => Vec::new(),
+        };
+        std::future::ready(Ok(values)).boxed()
+    }
+}
+
+#[tokio::test]
+async fn test_template_completions() -> anyhow::Result<()> {
+    let server = Server::default();
+    server.resources.insert_template(Refs);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (tx, _changes) = mpsc::unbounded_channel();
+    let (running_server, client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(Client(tx).serve(client_transport).await?) },
+    )?;
+
+    let templates = client.list_resource_templates(None).await?;
+    assert_eq!(templates.resource_templates[0].uri_template, REFS);
+
+    let context = CompletionContext::with_arguments(
+        [("repo".to_owned(), "sdk".to_owned())]
+            .into_iter()
+            .collect(),
+    );
+    let refs = client
+        .complete_resource_argument(REFS, "ref", "re", Some(context))
+        .await?;
+    assert_eq!(refs.values, ["sdk/release", "sdk/review"]);
+    assert!(!refs.has_more_results());
+
+    let owners = client
+        .complete_resource_argument(REFS, "owner", "", None)
+        .await?;
+    assert_eq!(owners.values.len(), CompletionInfo::MAX_VALUES);
+    assert_eq!(owners.total_available(), Some(150));
+    assert!(owners.has_more_results());
+
+    // unknown templates complete to nothing
+    let none = client
+        .complete_resource_simple("repo://{owner}", "owner", "")
+        .await?;
+    assert!(none.is_empty());
+
+    client.cancel().await?;
+    running_server.cancel().await?;
+    Ok(())
+}
+
+#[cfg(feature = "mime-sniffing")]
+#[tokio::test]
+async fn test_file_resource_metadata() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("rmcp-provider-{}.txt", std::process::id()));
+    std::fs::write(&path, "hello")?;
+
+    let server = Server::default();
+    let listed = server.resources.insert_file(&path)?;
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (tx, _changes) = mpsc::unbounded_channel();
+    let (running_server, client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(Client(tx).serve(client_transport).await?) },
+    )?;
+
+    let resources = client.list_resources(None).await?.resources;
+    assert_eq!(resources.len(), 1);
+    assert_eq!(resources[0], listed);
+    assert_eq!(resources[0].size, Some(5));
+    assert_eq!(resources[0].mime_type.as_deref(), Some("text/plain"));
+    let downloaded_at = resources[0].last_modified().expect("listed with mtime");
+    assert!(!resources[0].is_modified_since(downloaded_at));
+
+    std::fs::write(&path, "hello, world")?;
+    let read = client
+        .read_resource(ReadResourceRequestParams {
+            meta: None,
+            uri: listed.uri.clone(),
+        })
+        .await?;
+    let contents = &read.contents[0];
+    assert_eq!(contents.as_text(), Some("hello, world"));
+    assert_eq!(contents.size(), Some(12));
+    assert!(contents.last_modified().is_some());
+
+    std::fs::remove_file(&path)?;
+    let gone = client
+        .read_resource(ReadResourceRequestParams {
+            meta: None,
+            uri: listed.uri.clone(),
+        })
+        .await;
+    assert!(gone.is_err());
+
+    client.cancel().await?;
+    running_server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conditional_reads() -> anyhow::Result<()> {
+    const LOG: &str = "mem://log";
+    let server = Server::default();
+    let log_resource = || RawResource::new(LOG, "log").no_annotation();
+    let log = |text: &'static str| {
+        move || {
+            std::future::ready(Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text(text, LOG)],
+            }))
+        }
+    };
+    server.resources.insert(log_resource(), log("line 1"));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (tx, _changes) = mpsc::unbounded_channel();
+    let (running_server, client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(Client(tx).serve(client_transport).await?) },
+    )?;
+
+    let first = client
+        .read_resource_if_modified(LOG, None)
+        .await?
+        .expect("no etag yet");
+    assert_eq!(first.text()?, "line 1");
+    let etag = first.etag().expect("tagged by the provider").to_owned();
+
+    assert!(
+        client
+            .read_resource_if_modified(LOG, Some(&etag))
+            .await?
+            .is_none()
+    );
+
+    server
+        .resources
+        .insert(log_resource(), log("line 1\nline 2"));
+    let changed = client
+        .read_resource_if_modified(LOG, Some(&etag))
+        .await?
+        .expect("contents changed");
+    assert_eq!(changed.text()?, "line 1\nline 2");
+    assert_ne!(changed.etag(), Some(etag.as_str()));
+
+    client.cancel().await?;
+    running_server.cancel().await?;
+    Ok(())
+}
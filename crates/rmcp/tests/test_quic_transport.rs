@@ -0,0 +1,112 @@
+//cargo test --test test_quic_transport --features "client server unstable-quic"
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use quinn::rustls::{RootCertStore, pki_types::PrivatePkcs8KeyDer};
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult, Content},
+    service::RequestContext,
+    transport::quic::{QuicClientTransport, QuicServer},
+};
+use tokio_util::sync::CancellationToken;
+
+struct Server;
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if request.name == "slow" {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "called {}",
+            request.name
+        ))]))
+    }
+}
+
+fn call(name: &'static str) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: None,
+        task: None,
+    }
+}
+
+/// A server on localhost, and a client endpoint trusting it.
+fn endpoints(ct: CancellationToken) -> anyhow::Result<(quinn::Endpoint, SocketAddr)> {
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()])?;
+    let certificate = certified.cert.der().clone();
+    let key = PrivatePkcs8KeyDer::from(certified.signing_key.serialize_der());
+    let server_config =
+        quinn::ServerConfig::with_single_cert(vec![certificate.clone()], key.into())?;
+    let server = quinn::Endpoint::server(server_config, "127.0.0.1:0".parse()?)?;
+    let address = server.local_addr()?;
+    tokio::spawn(async move {
+        QuicServer::new(|| Ok(Server))
+            .with_cancellation_token(ct)
+            .serve(&server)
+            .await
+    });
+
+    let mut roots = RootCertStore::empty();
+    roots.add(certificate)?;
+    let mut client = quinn::Endpoint::client("127.0.0.1:0".parse()?)?;
+    client.set_default_client_config(quinn::ClientConfig::with_root_certificates(Arc::new(
+        roots,
+    ))?);
+    Ok((client, address))
+}
+
+#[tokio::test]
+async fn test_quic_session() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let (endpoint, address) = endpoints(ct.clone())?;
+
+    let transport = QuicClientTransport::connect(&endpoint, address, "localhost").await?;
+    assert!(!transport.is_zero_rtt());
+    let client = ().serve(transport).await?;
+    assert!(client.peer_info().is_some());
+
+    // each request has its own stream, the fast one doesn't wait
+    let (slow, fast) = tokio::join!(client.call_tool(call("slow")), async {
+        let result = client.call_tool(call("fast")).await;
+        (result, std::time::Instant::now())
+    });
+    let finished = std::time::Instant::now();
+    assert_eq!(slow?.text(), Some("called slow"));
+    let (fast, fast_finished) = fast;
+    assert_eq!(fast?.text(), Some("called fast"));
+    assert!(finished - fast_finished >= Duration::from_millis(100));
+
+    client.cancel().await?;
+    ct.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_quic_zero_rtt() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let (endpoint, address) = endpoints(ct.clone())?;
+
+    let client =
+        ().serve(QuicClientTransport::connect(&endpoint, address, "localhost").await?)
+            .await?;
+    client.call_tool(call("first")).await?;
+    client.cancel().await?;
+
+    // resumes the TLS session of the first connection
+    let transport = QuicClientTransport::connect(&endpoint, address, "localhost").await?;
+    assert!(transport.is_zero_rtt());
+    let client = ().serve(transport).await?;
+    let result = client.call_tool(call("second")).await?;
+    assert_eq!(result.text(), Some("called second"));
+
+    client.cancel().await?;
+    ct.cancel();
+    Ok(())
+}
@@ -0,0 +1,157 @@
+//cargo test --test test_request_budget --features "client server"
+
+use std::time::Duration;
+
+use rmcp::{
+    ClientHandler, ErrorData as McpError, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::*,
+    service::{PeerRequestOptions, RequestContext},
+};
+
+fn millis(duration: Option<Duration>) -> serde_json::Value {
+    duration.map(|d| d.as_millis() as u64).into()
+}
+
+/// Reports the budget it saw, and how much was left for its nested sampling
+/// request.
+struct BudgetServer;
+
+impl ServerHandler for BudgetServer {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let remaining = context.remaining_time();
+        let nested = if request.name == "nested" {
+            let result = context
+                .peer
+                .create_message(CreateMessageRequestParams {
+                    meta: None,
+                    task: None,
+                    messages: vec![SamplingMessage {
+                        role: Role::User,
+                        content: Content::text("hi").into(),
+                    }],
+                    model_preferences: None,
+                    system_prompt: None,
+                    include_context: None,
+                    temperature: None,
+                    max_tokens: 1,
+                    stop_sequences: None,
+                    metadata: None,
+                })
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            let text = result.message.content.as_text().unwrap().text.clone();
+            serde_json::from_str(&text).unwrap()
+        } else {
+            serde_json::Value::Null
+        };
+        Ok(CallToolResult::structured(serde_json::json!({
+            "remaining": millis(remaining),
+            "nested": nested,
+        })))
+    }
+}
+
+struct BudgetClient;
+
+impl ClientHandler for BudgetClient {
+    async fn create_message(
+        &self,
+        _params: CreateMessageRequestParams,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, McpError> {
+        Ok(CreateMessageResult {
+            message: SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text(millis(context.remaining_time()).to_string()).into(),
+            },
+            model: "budget".to_string(),
+            stop_reason: None,
+        })
+    }
+}
+
+async fn call(
+    client: &rmcp::service::RunningService<RoleClient, BudgetClient>,
+    name: &'static str,
+    timeout: Option<Duration>,
+) -> anyhow::Result<serde_json::Value> {
+    let request = ClientRequest::CallToolRequest(CallToolRequest::new(CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: None,
+        task: None,
+    }));
+    let response = client
+        .send_request_with_option(
+            request,
+            PeerRequestOptions {
+                timeout,
+                meta: None,
+            },
+        )
+        .await?
+        .await_response()
+        .await?;
+    let ServerResult::CallToolResult(result) = response else {
+        anyhow::bail!("unexpected response {response:?}");
+    };
+    Ok(result.structured_content.unwrap())
+}
+
+async fn connect() -> anyhow::Result<rmcp::service::RunningService<RoleClient, BudgetClient>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = BudgetServer.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(BudgetClient.serve(client_transport).await?)
+}
+
+#[tokio::test]
+async fn test_timeout_is_sent_as_budget() -> anyhow::Result<()> {
+    let client = connect().await?;
+
+    let result = call(&client, "plain", Some(Duration::from_secs(10))).await?;
+    let remaining = result["remaining"].as_u64().unwrap();
+    assert!(remaining > 5_000 && remaining <= 10_000, "{remaining}");
+
+    let result = call(&client, "plain", None).await?;
+    assert!(result["remaining"].is_null());
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nested_requests_inherit_budget() -> anyhow::Result<()> {
+    let client = connect().await?;
+
+    let result = call(&client, "nested", Some(Duration::from_secs(10))).await?;
+    let remaining = result["remaining"].as_u64().unwrap();
+    let nested = result["nested"].as_u64().unwrap();
+    assert!(nested > 0 && nested <= remaining, "{nested} > {remaining}");
+
+    // without a budget, nested requests get none either
+    let result = call(&client, "nested", None).await?;
+    assert!(result["nested"].is_null());
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[test]
+fn test_budget_meta_field() {
+    let mut meta = Meta::new();
+    assert_eq!(meta.get_budget(), None);
+    meta.set_budget(Duration::from_millis(1500));
+    assert_eq!(
+        serde_json::to_value(&meta).unwrap(),
+        serde_json::json!({ "rmcp/budgetMs": 1500 })
+    );
+    assert_eq!(meta.get_budget(), Some(Duration::from_millis(1500)));
+}
@@ -0,0 +1,101 @@
+//cargo test --test test_request_dedup --features "server client"
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    ErrorData, ServerHandler, ServiceExt,
+    model::{
+        ReadResourceRequestParams, ReadResourceResult, ResourceContents, ServerCapabilities,
+        ServerInfo,
+    },
+    service::{RequestContext, RequestDedupService, RoleServer},
+};
+
+#[derive(Debug, Clone, Default)]
+struct SlowFiles {
+    reads: Arc<AtomicUsize>,
+}
+
+impl ServerHandler for SlowFiles {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let read = self.reads.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(format!("read #{read}"), request.uri)],
+        })
+    }
+}
+
+fn read(uri: &str) -> ReadResourceRequestParams {
+    ReadResourceRequestParams {
+        meta: None,
+        uri: uri.to_owned(),
+    }
+}
+
+#[tokio::test]
+async fn test_identical_requests_run_once() -> anyhow::Result<()> {
+    let files = SlowFiles::default();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(RequestDedupService::new(files.clone()).serve(server_transport));
+    let client = ().serve(client_transport).await?;
+
+    let (first, second, third, other) = tokio::try_join!(
+        client.read_resource(read("file:///notes.md")),
+        client.read_resource(read("file:///notes.md")),
+        client.read_resource(read("file:///notes.md")),
+        client.read_resource(read("file:///todo.md")),
+    )?;
+    assert_eq!(files.reads.load(Ordering::SeqCst), 2);
+    assert_eq!(first, second);
+    assert_eq!(first, third);
+    assert_ne!(first.text()?, other.text()?);
+
+    // once the first read is done, the next one runs again
+    let again = client.read_resource(read("file:///notes.md")).await?;
+    assert_eq!(files.reads.load(Ordering::SeqCst), 3);
+    assert_eq!(again.text()?, "read #3");
+
+    client.cancel().await?;
+    server.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_methods_can_be_excluded() -> anyhow::Result<()> {
+    let files = SlowFiles::default();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(
+        RequestDedupService::new(files.clone())
+            .without_method("resources/read")
+            .serve(server_transport),
+    );
+    let client = ().serve(client_transport).await?;
+
+    let (first, second) = tokio::try_join!(
+        client.read_resource(read("file:///notes.md")),
+        client.read_resource(read("file:///notes.md")),
+    )?;
+    assert_eq!(files.reads.load(Ordering::SeqCst), 2);
+    assert_ne!(first.text()?, second.text()?);
+
+    client.cancel().await?;
+    server.await??;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//cargo test --test test_request_ids --features "client server"
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    ClientHandler, RoleServer, ServerHandler, ServiceExt,
+    model::{ClientRequest, ListToolsResult, PaginatedRequestParams, RequestId},
+    service::{
+        AtomicU32RequestIdProvider, PeerRequestOptions, PrefixedRequestIdProvider, RequestContext,
+        ServiceConfig,
+    },
+};
+
+/// Remembers the ids of the requests it handles.
+#[derive(Debug, Clone, Default)]
+struct Recorder {
+    ids: Arc<Mutex<Vec<RequestId>>>,
+}
+
+impl ServerHandler for Recorder {
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, rmcp::ErrorData> {
+        self.ids.lock().unwrap().push(context.id);
+        Ok(ListToolsResult::default())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Client;
+
+impl ClientHandler for Client {
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig {
+            request_id_provider: Arc::new(PrefixedRequestIdProvider::new(
+                "client-",
+                Arc::new(AtomicU32RequestIdProvider::default()),
+            )),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_request_ids_come_from_the_provider() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let recorder = Recorder::default();
+    let server = tokio::spawn(recorder.clone().serve(server_transport));
+    let client = Client.serve(client_transport).await?;
+    let server = server.await??;
+
+    client.list_tools(None).await?;
+    client.with_id_prefix("indexer-").list_tools(None).await?;
+    let handle = client
+        .send_request_with_option(
+            ClientRequest::ListToolsRequest(Default::default()),
+            PeerRequestOptions::no_options(),
+        )
+        .await?;
+    let sent = handle.id.clone();
+    handle.await_response().await?;
+
+    let ids: Vec<String> = recorder
+        .ids
+        .lock()
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    // the initialize request took "client-0"
+    assert_eq!(ids, ["client-1", "indexer-client-2", "client-3"]);
+    assert_eq!(sent.to_string(), "client-3");
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,106 @@
+//cargo test --test test_request_timings --features "client server"
+use std::sync::{Arc, Mutex};
+
+use rmcp::{ServerHandler, ServiceExt, model::ClientRequest, service::ServiceConfig};
+use tracing::{
+    Subscriber,
+    span::{Attributes, Id},
+};
+use tracing_subscriber::{Layer, layer::Context, prelude::*, registry::LookupSpan};
+
+/// A span's name and its parent's.
+type Named = (&'static str, Option<&'static str>);
+
+/// Records every span created.
+#[derive(Clone, Default)]
+struct Spans(Arc<Mutex<Vec<Named>>>);
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Spans {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, ctx: Context<'_, S>) {
+        let parent = if attrs.is_contextual() {
+            ctx.lookup_current().map(|span| span.name())
+        } else {
+            attrs
+                .parent()
+                .and_then(|id| ctx.span(id))
+                .map(|span| span.name())
+        };
+        self.0
+            .lock()
+            .unwrap()
+            .push((attrs.metadata().name(), parent));
+    }
+}
+
+impl Spans {
+    fn children_of_requests(&self) -> Vec<&'static str> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, parent)| *parent == Some("mcp.request"))
+            .map(|(name, _)| *name)
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+struct Server {
+    request_timings: bool,
+}
+
+impl ServerHandler for Server {
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig {
+            request_timings: self.request_timings,
+            ..Default::default()
+        }
+    }
+}
+
+async fn ping(server: Server) -> anyhow::Result<Spans> {
+    let spans = Spans::default();
+    let _guard = tracing_subscriber::registry()
+        .with(spans.clone())
+        .set_default();
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(server.serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+    client
+        .send_request(ClientRequest::PingRequest(Default::default()))
+        .await?;
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(spans)
+}
+
+#[tokio::test]
+async fn test_request_timings_break_down_each_request() -> anyhow::Result<()> {
+    let spans = ping(Server {
+        request_timings: true,
+    })
+    .await?;
+    assert_eq!(
+        spans.children_of_requests(),
+        [
+            "mcp.request.queue",
+            "mcp.handler",
+            "mcp.response.queue",
+            "mcp.transport.write",
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_timings_are_off_by_default() -> anyhow::Result<()> {
+    let spans = ping(Server {
+        request_timings: false,
+    })
+    .await?;
+    assert!(spans.children_of_requests().is_empty());
+    Ok(())
+}
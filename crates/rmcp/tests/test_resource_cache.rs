@@ -0,0 +1,162 @@
+//cargo test --test test_resource_cache --features "client server"
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::client::cache::ResourceCache,
+    model::*,
+    service::{NotificationContext, RequestContext},
+};
+use tokio::sync::mpsc;
+
+/// Serves `mem://<name>` resources whose text is the name repeated, and
+/// answers conditional reads.
+#[derive(Clone, Default)]
+struct Server {
+    texts: Arc<Mutex<std::collections::HashMap<String, String>>>,
+    full_reads: Arc<AtomicUsize>,
+    read_delay: Duration,
+}
+
+impl Server {
+    fn set(&self, uri: &str, text: &str) {
+        self.texts
+            .lock()
+            .unwrap()
+            .insert(uri.to_owned(), text.to_owned());
+    }
+}
+
+impl ServerHandler for Server {
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let text = self.texts.lock().unwrap().get(&request.uri).cloned();
+        tokio::time::sleep(self.read_delay).await;
+        let text = text.ok_or_else(|| ErrorData::resource_not_found("no such resource", None))?;
+        let result = ReadResourceResult {
+            contents: vec![ResourceContents::text(text, request.uri.clone())],
+        }
+        .conditional(request.uri, context.meta.if_none_match());
+        if !result.is_not_modified() {
+            self.full_reads.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Clone)]
+struct Client {
+    cache: ResourceCache,
+    updated: mpsc::UnboundedSender<()>,
+}
+
+impl ClientHandler for Client {
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.cache.on_resource_updated(&params);
+        let _ = self.updated.send(());
+    }
+}
+
+#[tokio::test]
+async fn test_resource_cache() -> anyhow::Result<()> {
+    const A: &str = "mem://a";
+    const B: &str = "mem://b";
+    const C: &str = "mem://c";
+    let server = Server::default();
+    server.set(A, "aaaa");
+    server.set(B, "bbbb");
+    server.set(C, "cccc");
+    let cache = ResourceCache::new(8).with_ttl(Duration::from_millis(200));
+    let (tx, mut updated) = mpsc::unbounded_channel();
+    let client = Client {
+        cache: cache.clone(),
+        updated: tx,
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (running_server, client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(client.serve(client_transport).await?) },
+    )?;
+    let full_reads = || server.full_reads.load(Ordering::SeqCst);
+
+    assert!(cache.get_cached(A).is_none());
+    assert_eq!(cache.read(client.peer(), A).await?.text()?, "aaaa");
+    assert_eq!(cache.read(client.peer(), A).await?.text()?, "aaaa");
+    assert_eq!(full_reads(), 1);
+    assert_eq!(cache.get_cached(A).unwrap().text()?, "aaaa");
+
+    // updates invalidate the entry
+    server.set(A, "AAAA");
+    running_server
+        .notify_resource_updated(ResourceUpdatedNotificationParam { uri: A.to_owned() })
+        .await?;
+    updated.recv().await.unwrap();
+    assert!(cache.get_cached(A).is_none());
+    assert_eq!(cache.read(client.peer(), A).await?.text()?, "AAAA");
+    assert_eq!(full_reads(), 2);
+
+    // expired entries are revalidated without downloading them again
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(cache.get_cached(A).is_none());
+    assert_eq!(cache.read(client.peer(), A).await?.text()?, "AAAA");
+    assert_eq!(full_reads(), 2);
+    assert!(cache.get_cached(A).is_some());
+
+    // 8 bytes hold two entries, the least recently used one goes first
+    cache.read(client.peer(), B).await?;
+    assert!(cache.get_cached(A).is_some());
+    cache.read(client.peer(), C).await?;
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.size_bytes(), 8);
+    assert!(cache.get_cached(B).is_none());
+    assert!(cache.get_cached(A).is_some());
+    assert!(cache.get_cached(C).is_some());
+
+    client.cancel().await?;
+    running_server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalidate_during_read() -> anyhow::Result<()> {
+    const A: &str = "mem://a";
+    let server = Server {
+        read_delay: Duration::from_millis(100),
+        ..Default::default()
+    };
+    server.set(A, "aaaa");
+    let cache = ResourceCache::default();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (running_server, client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(().serve(client_transport).await?) },
+    )?;
+
+    // the update lands while the old contents are on their way
+    let (read, _) = tokio::join!(cache.read(client.peer(), A), async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        server.set(A, "AAAA");
+        cache.invalidate(A);
+    });
+    assert_eq!(read?.text()?, "aaaa");
+    assert!(cache.get_cached(A).is_none());
+    assert_eq!(cache.read(client.peer(), A).await?.text()?, "AAAA");
+    assert_eq!(cache.get_cached(A).unwrap().text()?, "AAAA");
+
+    client.cancel().await?;
+    running_server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,163 @@
+//cargo test --test test_resource_mirror --features "client server"
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::client::mirror::ResourceMirror,
+    model::*,
+    service::{NotificationContext, RequestContext},
+};
+use tokio::sync::mpsc;
+
+const LOG: &str = "mem://log";
+
+/// Serves one text resource, tagged with its version number.
+#[derive(Clone, Default)]
+struct Server {
+    log: Arc<Mutex<(u32, String)>>,
+    reads: Arc<AtomicUsize>,
+}
+
+impl Server {
+    /// Append `line` and return the delta between the two versions.
+    fn append(&self, line: &str) -> ResourceDelta {
+        let mut log = self.log.lock().unwrap();
+        let old = log.1.clone();
+        log.0 += 1;
+        log.1.push_str(line);
+        ResourceDelta::between((log.0 - 1).to_string(), &old, log.0.to_string(), &log.1)
+    }
+}
+
+impl ServerHandler for Server {
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        let (version, text) = self.log.lock().unwrap().clone();
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(text, LOG).with_etag(version.to_string())],
+        }
+        .conditional(request.uri, context.meta.if_none_match()))
+    }
+
+    async fn subscribe(
+        &self,
+        _request: SubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        Ok(())
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities {
+                resources: Some(ResourcesCapability {
+                    subscribe: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Client {
+    mirror: ResourceMirror,
+    deltas: bool,
+    updated: mpsc::UnboundedSender<()>,
+}
+
+impl ClientHandler for Client {
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) {
+        self.mirror
+            .on_resource_updated(&params, &context)
+            .await
+            .unwrap();
+        let _ = self.updated.send(());
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let mut experimental = ExperimentalCapabilities::new();
+        if self.deltas {
+            experimental.insert_typed(&ResourceDeltas {}).unwrap();
+        }
+        ClientInfo {
+            capabilities: ClientCapabilities {
+                experimental: Some(experimental),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+async fn mirror_log(deltas: bool) -> anyhow::Result<usize> {
+    let server = Server::default();
+    let mirror = ResourceMirror::new();
+    let (tx, mut updated) = mpsc::unbounded_channel();
+    let client = Client {
+        mirror: mirror.clone(),
+        deltas,
+        updated: tx,
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (running_server, client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(client.serve(client_transport).await?) },
+    )?;
+
+    server.append("line 1\n");
+    mirror.track(client.peer(), LOG).await?;
+    assert_eq!(mirror.text(LOG).as_deref(), Some("line 1\n"));
+
+    for line in ["line 2\n", "line 3\n"] {
+        let delta = server.append(line);
+        running_server.notify_resource_delta(LOG, &delta).await?;
+        updated.recv().await.unwrap();
+    }
+    assert_eq!(
+        mirror.text(LOG).as_deref(),
+        Some("line 1\nline 2\nline 3\n")
+    );
+    assert_eq!(mirror.get(LOG).unwrap().etag(), Some("3"));
+
+    // a delta from a version the mirror never saw falls back to a read
+    server.append("line 4\n");
+    let delta = server.append("line 5\n");
+    running_server.notify_resource_delta(LOG, &delta).await?;
+    updated.recv().await.unwrap();
+    assert_eq!(
+        mirror.text(LOG).as_deref(),
+        Some("line 1\nline 2\nline 3\nline 4\nline 5\n")
+    );
+
+    client.cancel().await?;
+    running_server.cancel().await?;
+    Ok(server.reads.load(Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn test_mirror_applies_deltas() -> anyhow::Result<()> {
+    // the initial read and the one after the missed version
+    assert_eq!(mirror_log(true).await?, 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mirror_reads_without_deltas() -> anyhow::Result<()> {
+    // clients that didn't ask for deltas read again on every update
+    assert_eq!(mirror_log(false).await?, 4);
+    Ok(())
+}
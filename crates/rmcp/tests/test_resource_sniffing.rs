@@ -0,0 +1,88 @@
+//cargo test --test test_resource_sniffing --features "mime-sniffing"
+
+use rmcp::model::{
+    ResourceContents,
+    sniff::{decode_text, mime_from_extension, mime_from_magic, sniff_mime_type},
+};
+
+#[test]
+fn test_mime_type_detection() {
+    assert_eq!(mime_from_magic(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+    assert_eq!(mime_from_magic(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+    assert_eq!(mime_from_magic(b"hello"), None);
+
+    assert_eq!(mime_from_extension("notes.md"), Some("text/markdown"));
+    assert_eq!(
+        mime_from_extension("file:///srv/data.json?rev=2#top"),
+        Some("application/json")
+    );
+
+    // content beats a misleading extension
+    assert_eq!(sniff_mime_type("photo.txt", b"%PDF-1.7"), "application/pdf");
+    assert_eq!(sniff_mime_type("README", b"plain words"), "text/plain");
+    assert_eq!(
+        sniff_mime_type("dump", b"\0\x01\x02"),
+        "application/octet-stream"
+    );
+}
+
+#[test]
+fn test_decode_text_charsets() {
+    let utf8 = decode_text("grüße".as_bytes(), None);
+    assert_eq!((utf8.text.as_str(), utf8.encoding), ("grüße", "UTF-8"));
+
+    // UTF-16LE with a byte order mark
+    let utf16: Vec<u8> = [0xFF, 0xFE]
+        .into_iter()
+        .chain("hé".encode_utf16().flat_map(u16::to_le_bytes))
+        .collect();
+    let decoded = decode_text(&utf16, None);
+    assert_eq!(
+        (decoded.text.as_str(), decoded.encoding),
+        ("hé", "UTF-16LE")
+    );
+
+    // declared charset
+    let latin1 = b"caf\xE9";
+    let decoded = decode_text(latin1, Some("text/plain; charset=\"ISO-8859-1\""));
+    assert_eq!(decoded.text, "café");
+    assert!(!decoded.had_errors);
+
+    // detected charset
+    let decoded = decode_text(
+        b"Le caf\xE9 \xE9tait tr\xE8s bon, merci \xE0 vous et \xE0 bient\xF4t.",
+        None,
+    );
+    assert_eq!(decoded.encoding, "windows-1252");
+    assert_eq!(
+        decoded.text,
+        "Le café était très bon, merci à vous et à bientôt."
+    );
+}
+
+#[test]
+fn test_resource_contents_from_bytes() {
+    let contents = ResourceContents::from_bytes("file:///notes.csv", b"a;b\n\xE9;\xE8\n");
+    let ResourceContents::TextResourceContents {
+        mime_type, text, ..
+    } = contents
+    else {
+        panic!("expected text contents");
+    };
+    assert_eq!(mime_type.as_deref(), Some("text/csv"));
+    assert_eq!(text, "a;b\né;è\n");
+
+    let contents = ResourceContents::from_bytes("file:///logo", b"GIF89a\x01\0\x01\0");
+    let ResourceContents::BlobResourceContents {
+        uri,
+        mime_type,
+        blob,
+        ..
+    } = contents
+    else {
+        panic!("expected blob contents");
+    };
+    assert_eq!(uri, "file:///logo");
+    assert_eq!(mime_type.as_deref(), Some("image/gif"));
+    assert_eq!(blob, "R0lGODlhAQABAA==");
+}
@@ -0,0 +1,198 @@
+//cargo test --test test_retry --features "client server"
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    ErrorData as McpError, ServerHandler, ServiceError, ServiceExt,
+    model::{
+        CallToolRequest, CallToolRequestParams, CallToolResult, ClientRequest, Content, ErrorCode,
+        ListToolsResult, PaginatedRequestParams, ServerResult,
+    },
+    service::{ClientOptions, RequestContext, RetryPolicy, RoleServer, serve_client_with_options},
+};
+
+/// Fails the first `failures` requests with `code`, then succeeds.
+#[derive(Clone)]
+struct FlakyServer {
+    code: ErrorCode,
+    failures: Arc<AtomicUsize>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl FlakyServer {
+    fn new(code: ErrorCode, failures: usize) -> Self {
+        Self {
+            code,
+            failures: Arc::new(AtomicUsize::new(failures)),
+            calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn attempt(&self) -> Result<(), McpError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let failed = self
+            .failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if failed {
+            Err(McpError::new(self.code, "flaky", None))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl ServerHandler for FlakyServer {
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        self.attempt()?;
+        Ok(ListToolsResult::default())
+    }
+
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.attempt()?;
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+}
+
+fn fast_policy() -> RetryPolicy {
+    RetryPolicy {
+        base_backoff: Duration::from_millis(1),
+        ..Default::default()
+    }
+}
+
+fn call_tool_request() -> ClientRequest {
+    ClientRequest::CallToolRequest(CallToolRequest::new(CallToolRequestParams {
+        meta: None,
+        name: "do_it".into(),
+        arguments: None,
+        task: None,
+    }))
+}
+
+async fn serve(
+    server: FlakyServer,
+) -> anyhow::Result<rmcp::service::RunningService<rmcp::RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+#[tokio::test]
+async fn test_idempotent_request_is_retried() -> anyhow::Result<()> {
+    let server = FlakyServer::new(ErrorCode::CONNECTION_CLOSED, 2);
+    let client = serve(server.clone()).await?;
+    let peer = client.peer().clone().with_retry_policy(fast_policy());
+
+    peer.list_tools(None).await?;
+    assert_eq!(server.calls.load(Ordering::SeqCst), 3);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retries_are_bounded() -> anyhow::Result<()> {
+    let server = FlakyServer::new(ErrorCode::REQUEST_TIMEOUT, 10);
+    let client = serve(server.clone()).await?;
+    let peer = client.peer().clone().with_retry_policy(RetryPolicy {
+        max_retries: 2,
+        ..fast_policy()
+    });
+
+    let error = peer.list_tools(None).await.unwrap_err();
+    assert!(matches!(error, ServiceError::McpError(e) if e.code == ErrorCode::REQUEST_TIMEOUT));
+    assert_eq!(server.calls.load(Ordering::SeqCst), 3);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_permanent_errors_are_not_retried() -> anyhow::Result<()> {
+    let server = FlakyServer::new(ErrorCode::INVALID_PARAMS, 1);
+    let client = serve(server.clone()).await?;
+    let peer = client.peer().clone().with_retry_policy(fast_policy());
+
+    assert!(peer.list_tools(None).await.is_err());
+    assert_eq!(server.calls.load(Ordering::SeqCst), 1);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_extra_error_codes_are_retried() -> anyhow::Result<()> {
+    let server = FlakyServer::new(ErrorCode::INTERNAL_ERROR, 1);
+    let client = serve(server.clone()).await?;
+    let peer = client.peer().clone().with_retry_policy(RetryPolicy {
+        retry_on: vec![ErrorCode::INTERNAL_ERROR],
+        ..fast_policy()
+    });
+
+    peer.list_tools(None).await?;
+    assert_eq!(server.calls.load(Ordering::SeqCst), 2);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_non_idempotent_request_needs_opt_in() -> anyhow::Result<()> {
+    let server = FlakyServer::new(ErrorCode::CONNECTION_CLOSED, 1);
+    let client = serve(server.clone()).await?;
+    let peer = client.peer().clone().with_retry_policy(fast_policy());
+
+    assert!(peer.send_request(call_tool_request()).await.is_err());
+    assert_eq!(server.calls.load(Ordering::SeqCst), 1);
+
+    server.failures.store(1, Ordering::SeqCst);
+    let result = peer.send_request_with_retry(call_tool_request()).await?;
+    assert!(matches!(result, ServerResult::CallToolResult(_)));
+    assert_eq!(server.calls.load(Ordering::SeqCst), 3);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_client_options_retry_policy() -> anyhow::Result<()> {
+    let server = FlakyServer::new(ErrorCode::CONNECTION_CLOSED, 2);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let flaky = server.clone();
+    tokio::spawn(async move {
+        let server = flaky.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = serve_client_with_options(
+        (),
+        client_transport,
+        ClientOptions::default().retry_policy(fast_policy()),
+    )
+    .await?;
+
+    client.list_tools(None).await?;
+    assert_eq!(server.calls.load(Ordering::SeqCst), 3);
+
+    client.cancel().await?;
+    Ok(())
+}
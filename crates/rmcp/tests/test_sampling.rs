@@ -16,7 +16,7 @@ async fn test_basic_sampling_message_creation() -> Result<()> {
     // Test basic sampling message structure
     let message = SamplingMessage {
         role: Role::User,
-        content: Content::text("What is the capital of France?"),
+        content: Content::text("What is the capital of France?").into(),
     };
 
     // Verify serialization/deserialization
@@ -36,7 +36,7 @@ async fn test_sampling_request_params() -> Result<()> {
         task: None,
         messages: vec![SamplingMessage {
             role: Role::User,
-            content: Content::text("Hello, world!"),
+            content: Content::text("Hello, world!").into(),
         }],
         model_preferences: Some(ModelPreferences {
             hints: Some(vec![ModelHint {
@@ -73,7 +73,7 @@ async fn test_sampling_result_structure() -> Result<()> {
     let result = CreateMessageResult {
         message: SamplingMessage {
             role: Role::Assistant,
-            content: Content::text("The capital of France is Paris."),
+            content: Content::text("The capital of France is Paris.").into(),
         },
         model: "test-model".to_string(),
         stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.to_string()),
@@ -141,7 +141,7 @@ async fn test_sampling_integration_with_test_handlers() -> Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::User,
-                content: Content::text("What is the capital of France?"),
+                content: Content::text("What is the capital of France?").into(),
             }],
             include_context: Some(ContextInclusion::ThisServer),
             model_preferences: Some(ModelPreferences {
@@ -223,7 +223,7 @@ async fn test_sampling_no_context_inclusion() -> Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::User,
-                content: Content::text("Hello"),
+                content: Content::text("Hello").into(),
             }],
             include_context: Some(ContextInclusion::None),
             model_preferences: None,
@@ -294,7 +294,7 @@ async fn test_sampling_error_invalid_message_sequence() -> Result<()> {
             task: None,
             messages: vec![SamplingMessage {
                 role: Role::Assistant,
-                content: Content::text("I'm an assistant message without a user message"),
+                content: Content::text("I'm an assistant message without a user message").into(),
             }],
             include_context: Some(ContextInclusion::None),
             model_preferences: None,
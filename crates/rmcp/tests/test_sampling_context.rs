@@ -0,0 +1,194 @@
+//cargo test --test test_sampling_context --features "client server"
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::client::sampling::{ContextPolicy, ContextServers},
+    model::*,
+    service::{RequestContext, RunningService},
+};
+
+/// Serves notes as text resources, the first one with the highest priority.
+#[derive(Clone)]
+struct Notes(&'static [(&'static str, &'static str)]);
+
+impl ServerHandler for Notes {
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        let mut resources = Vec::new();
+        for (i, (uri, _)) in self.0.iter().enumerate() {
+            let resource = RawResource::new(*uri, *uri).no_annotation();
+            resources.push(resource.with_priority(1.0 - i as f32 / 10.0));
+        }
+        resources.push(
+            RawResource::new("note://private", "private")
+                .no_annotation()
+                .with_audience(vec![Role::User]),
+        );
+        Ok(ListResourcesResult::with_all_items(resources))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let (_, text) = self
+            .0
+            .iter()
+            .find(|(uri, _)| *uri == request.uri)
+            .ok_or_else(|| ErrorData::resource_not_found(request.uri.clone(), None))?;
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(*text, request.uri)],
+        })
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Answers with the system prompt the context policy made.
+#[derive(Clone)]
+struct Client {
+    policy: ContextPolicy,
+}
+
+impl ClientHandler for Client {
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParams,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        let params = self.policy.apply(params, &context.peer).await;
+        Ok(CreateMessageResult {
+            message: SamplingMessage::assistant_text(params.system_prompt.unwrap_or_default()),
+            model: "echo".to_owned(),
+            stop_reason: None,
+        })
+    }
+}
+
+async fn connect(
+    client: &Client,
+    server: Notes,
+) -> anyhow::Result<(
+    RunningService<RoleServer, Notes>,
+    RunningService<RoleClient, Client>,
+)> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    Ok(tokio::try_join!(
+        async { anyhow::Ok(server.serve(server_transport).await?) },
+        async { anyhow::Ok(client.clone().serve(client_transport).await?) },
+    )?)
+}
+
+async fn sample(
+    server: &RunningService<RoleServer, Notes>,
+    include_context: Option<ContextInclusion>,
+) -> anyhow::Result<String> {
+    let result = server
+        .create_message(CreateMessageRequestParams {
+            meta: None,
+            task: None,
+            messages: vec![SamplingMessage::user_text("summarize")],
+            model_preferences: None,
+            system_prompt: Some("Be brief.".to_owned()),
+            include_context,
+            temperature: None,
+            max_tokens: 100,
+            stop_sequences: None,
+            metadata: None,
+        })
+        .await?;
+    Ok(result.message.content.as_text().unwrap().text.clone())
+}
+
+#[tokio::test]
+async fn test_include_context() -> anyhow::Result<()> {
+    let servers = ContextServers::new();
+    let client = Client {
+        policy: ContextPolicy::new(servers.clone()),
+    };
+    let (todo, todo_client) = connect(
+        &client,
+        Notes(&[("note://todo", "buy milk"), ("note://done", "call mom")]),
+    )
+    .await?;
+    let (diary, diary_client) = connect(&client, Notes(&[("note://diary", "sunny day")])).await?;
+    servers.insert("todo", todo_client.peer().clone());
+    servers.insert("diary", diary_client.peer().clone());
+
+    assert_eq!(sample(&todo, None).await?, "Be brief.");
+    assert_eq!(
+        sample(&todo, Some(ContextInclusion::None)).await?,
+        "Be brief."
+    );
+    assert_eq!(
+        sample(&todo, Some(ContextInclusion::ThisServer)).await?,
+        "Be brief.\n\nContext from todo:\n\n[note://todo]\nbuy milk\n\n[note://done]\ncall mom"
+    );
+    // the requesting server comes first
+    assert_eq!(
+        sample(&diary, Some(ContextInclusion::AllServers)).await?,
+        "Be brief.\n\nContext from diary:\n\n[note://diary]\nsunny day\n\n\
+         Context from todo:\n\n[note://todo]\nbuy milk\n\n[note://done]\ncall mom"
+    );
+
+    for service in [todo_client, diary_client] {
+        service.cancel().await?;
+    }
+    todo.cancel().await?;
+    diary.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_context_token_budget() -> anyhow::Result<()> {
+    let servers = ContextServers::new();
+    // "note://todo" and "buy milk" are 3 and 2 tokens; unregistered servers go
+    // by the name they gave
+    let client = Client {
+        policy: ContextPolicy::new(servers.clone()).with_max_tokens(6),
+    };
+    let (todo, todo_client) = connect(
+        &client,
+        Notes(&[("note://todo", "buy milk"), ("note://done", "call mom")]),
+    )
+    .await?;
+    assert_eq!(
+        sample(&todo, Some(ContextInclusion::ThisServer)).await?,
+        "Be brief.\n\nContext from rmcp:\n\n[note://todo]\nbuy milk"
+    );
+    todo_client.cancel().await?;
+    todo.cancel().await?;
+    Ok(())
+}
+
+#[cfg(feature = "tokens")]
+#[tokio::test]
+async fn test_context_tokenizer() -> anyhow::Result<()> {
+    // one token per word: the first note and its URI take 3 tokens
+    let words = |text: &str| text.split_whitespace().count();
+    let client = Client {
+        policy: ContextPolicy::new(ContextServers::new())
+            .with_max_tokens(3)
+            .with_tokenizer(words),
+    };
+    let (todo, todo_client) = connect(
+        &client,
+        Notes(&[("note://todo", "buy milk"), ("note://done", "call mom")]),
+    )
+    .await?;
+    assert_eq!(
+        sample(&todo, Some(ContextInclusion::ThisServer)).await?,
+        "Be brief.\n\nContext from rmcp:\n\n[note://todo]\nbuy milk"
+    );
+    todo_client.cancel().await?;
+    todo.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//cargo test --test test_sampling_stream --features "client server"
+use std::sync::Arc;
+
+use futures::StreamExt;
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::{client::sampling::SamplingChunkSender, server::sampling::SamplingStreams},
+    model::*,
+    service::{NotificationContext, RequestContext},
+};
+use tokio::sync::Notify;
+
+const ANSWER: [&str; 3] = ["The capital ", "of France ", "is Paris."];
+
+#[derive(Clone, Default)]
+struct Server {
+    streams: SamplingStreams,
+}
+
+impl ServerHandler for Server {
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        context: NotificationContext<RoleServer>,
+    ) {
+        assert!(self.streams.on_progress(&params, &context).await);
+    }
+}
+
+#[derive(Clone)]
+struct Client {
+    streaming: bool,
+    /// Holds the final result back until the server has seen the chunks.
+    release: Arc<Notify>,
+}
+
+impl ClientHandler for Client {
+    async fn create_message(
+        &self,
+        _params: CreateMessageRequestParams,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        if let Some(mut chunks) = SamplingChunkSender::from_context(&context) {
+            for text in ANSWER {
+                chunks.send(text).await.unwrap();
+            }
+            assert_eq!(chunks.sent(), 3);
+            self.release.notified().await;
+        }
+        Ok(CreateMessageResult {
+            message: SamplingMessage::assistant_text(ANSWER.concat()),
+            model: "test-model".to_owned(),
+            stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.to_owned()),
+        })
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let mut experimental = ExperimentalCapabilities::new();
+        if self.streaming {
+            experimental.insert_typed(&SamplingStreaming {}).unwrap();
+        }
+        ClientInfo {
+            capabilities: ClientCapabilities {
+                experimental: Some(experimental),
+                sampling: Some(Default::default()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+fn params() -> CreateMessageRequestParams {
+    CreateMessageRequestParams {
+        meta: None,
+        task: None,
+        messages: vec![SamplingMessage::user_text("What is the capital of France?")],
+        model_preferences: None,
+        system_prompt: None,
+        include_context: None,
+        temperature: None,
+        max_tokens: 100,
+        stop_sequences: None,
+        metadata: None,
+    }
+}
+
+async fn stream_answer(streaming: bool) -> anyhow::Result<(Vec<SamplingChunk>, String)> {
+    let server = Server::default();
+    let release = Arc::new(Notify::new());
+    let client = Client {
+        streaming,
+        release: release.clone(),
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (running_server, client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(client.serve(client_transport).await?) },
+    )?;
+
+    let mut stream = server
+        .streams
+        .create_message(running_server.peer(), params())
+        .await?;
+    let mut chunks = Vec::new();
+    if streaming {
+        while chunks.len() < ANSWER.len() {
+            chunks.push(stream.next().await.expect("chunk before the result"));
+        }
+        release.notify_one();
+    }
+    assert_eq!(stream.next().await, None);
+    let result = stream.result().await?;
+
+    client.cancel().await?;
+    running_server.cancel().await?;
+    Ok((
+        chunks,
+        result.message.content.as_text().unwrap().text.clone(),
+    ))
+}
+
+#[tokio::test]
+async fn test_streamed_sampling() -> anyhow::Result<()> {
+    let (chunks, text) = stream_answer(true).await?;
+    assert_eq!(
+        chunks.iter().map(|chunk| chunk.index).collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+    let streamed: String = chunks.into_iter().map(|chunk| chunk.text).collect();
+    assert_eq!(streamed, text);
+    assert_eq!(text, ANSWER.concat());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sampling_without_streaming() -> anyhow::Result<()> {
+    // clients that don't stream just answer
+    let (chunks, text) = stream_answer(false).await?;
+    assert!(chunks.is_empty());
+    assert_eq!(text, ANSWER.concat());
+    Ok(())
+}
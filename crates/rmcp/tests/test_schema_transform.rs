@@ -0,0 +1,117 @@
+//cargo test --test test_schema_transform --features "server macros"
+use rmcp::{
+    Json,
+    handler::server::{
+        schema_transform::{cap_enum, inline_refs, strip_formats},
+        wrapper::Parameters,
+    },
+    model::{JsonObject, Tool},
+    tool, tool_router,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Event {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub place: Place,
+    pub weekday: Weekday,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Place {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+fn for_strict_providers(schema: &mut JsonObject) {
+    inline_refs(schema);
+    strip_formats(schema);
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Calendar;
+
+#[tool_router]
+impl Calendar {
+    #[tool(schema_transform = for_strict_providers)]
+    async fn add(&self, Parameters(event): Parameters<Event>) -> Json<Event> {
+        Json(event)
+    }
+
+    #[tool(schema_transform = cap_enum(5))]
+    async fn remove(&self, Parameters(event): Parameters<Event>) -> String {
+        event.place.name
+    }
+
+    #[tool]
+    async fn show(&self, Parameters(event): Parameters<Event>) -> String {
+        event.place.name
+    }
+}
+
+#[tool_router(router = strict_router, schema_transform = for_strict_providers)]
+impl Calendar {
+    #[tool(name = "add")]
+    async fn strict_add(&self, Parameters(event): Parameters<Event>) -> Json<Event> {
+        Json(event)
+    }
+}
+
+fn find(tools: &[Tool], name: &str) -> Tool {
+    tools.iter().find(|tool| tool.name == name).unwrap().clone()
+}
+
+fn assert_strict(schema: &JsonObject) {
+    assert!(!schema.contains_key("$defs"), "{schema:?}");
+    assert_eq!(schema["properties"]["place"]["type"], "object");
+    assert_eq!(schema["properties"]["weekday"]["enum"][0], "Mon");
+    assert!(schema["properties"]["at"].get("format").is_none());
+}
+
+#[test]
+fn test_per_tool_schema_transform() {
+    let tools = Calendar::tool_router().list_all();
+
+    let add = find(&tools, "add");
+    assert_strict(&add.input_schema);
+    assert_strict(add.output_schema.as_ref().unwrap());
+
+    let remove = find(&tools, "remove");
+    let weekday = &remove.input_schema["$defs"]["Weekday"];
+    assert!(weekday.get("enum").is_none(), "{weekday}");
+    assert_eq!(weekday["type"], "string");
+    assert_eq!(
+        remove.input_schema["properties"]["at"]["format"],
+        "date-time"
+    );
+
+    // other tools keep the generated schema
+    let show = find(&tools, "show");
+    assert!(show.input_schema.contains_key("$defs"));
+    assert_eq!(show.input_schema["$defs"]["Weekday"]["enum"][6], "Sun");
+}
+
+#[test]
+fn test_router_schema_transform() {
+    let tools = Calendar::strict_router().list_all();
+    let add = find(&tools, "add");
+    assert_strict(&add.input_schema);
+    assert_strict(add.output_schema.as_ref().unwrap());
+
+    let tools = Calendar::tool_router()
+        .transform_schemas(cap_enum(5))
+        .list_all();
+    let show = find(&tools, "show");
+    assert!(show.input_schema["$defs"]["Weekday"].get("enum").is_none());
+}
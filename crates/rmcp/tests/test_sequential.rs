@@ -0,0 +1,218 @@
+//cargo test --test test_sequential --features "client server"
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequestParams, CallToolResult, Content, LoggingLevel,
+        LoggingMessageNotificationParam, ProgressNotificationParam,
+    },
+    service::{
+        NotificationContext, QuitReason, RequestContext, ServiceConfig, SessionExpiry,
+        SessionTimeouts,
+    },
+};
+
+/// Logs its name after a delay taken from its name.
+#[derive(Debug, Clone, Default)]
+struct Delayed {
+    sequential: bool,
+}
+
+impl ServerHandler for Delayed {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let delay = if request.name == "slow" { 100 } else { 0 };
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        context
+            .peer
+            .notify_logging_message(LoggingMessageNotificationParam {
+                level: LoggingLevel::Info,
+                logger: None,
+                data: request.name.as_ref().into(),
+            })
+            .await
+            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(request.name)]))
+    }
+
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig {
+            session_timeouts: SessionTimeouts {
+                idle_timeout: Some(Duration::from_secs(30)),
+                max_duration: None,
+            },
+            sequential: self.sequential,
+            ..Default::default()
+        }
+    }
+}
+
+/// More log and progress messages per call than the low-priority queue holds.
+const CHATTER: usize = 300;
+
+/// Sends a log and a progress message per step of a call.
+#[derive(Debug, Clone, Default)]
+struct Chatty;
+
+impl ServerHandler for Chatty {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let progress_token = context.meta.get_progress_token().unwrap();
+        for step in 0..CHATTER {
+            context
+                .peer
+                .notify_logging_message(LoggingMessageNotificationParam {
+                    level: LoggingLevel::Info,
+                    logger: None,
+                    data: format!("log {step}").into(),
+                })
+                .await
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            context
+                .peer
+                .notify_progress(ProgressNotificationParam {
+                    progress_token: progress_token.clone(),
+                    progress: step as f64,
+                    total: None,
+                    message: None,
+                })
+                .await
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        }
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig {
+            sequential: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl ClientHandler for Recorder {
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(params.data.as_str().unwrap().to_owned());
+    }
+
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("progress {}", params.progress));
+    }
+
+    fn config(&self) -> ServiceConfig {
+        ServiceConfig {
+            sequential: true,
+            ..Default::default()
+        }
+    }
+}
+
+fn call(name: &'static str) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: None,
+        task: None,
+    }
+}
+
+async fn logs_of(server: Delayed) -> anyhow::Result<Vec<String>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(server.serve(server_transport));
+    let recorder = Recorder::default();
+    let client = recorder.clone().serve(client_transport).await?;
+    let _server = server.await??;
+
+    let (slow, fast) = tokio::join!(
+        client.call_tool(call("slow")),
+        client.call_tool(call("fast"))
+    );
+    slow?;
+    fast?;
+    client.cancel().await?;
+    Ok(recorder.0.lock().unwrap().clone())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_sequential_handlers_keep_arrival_order() -> anyhow::Result<()> {
+    assert_eq!(logs_of(Delayed::default()).await?, ["fast", "slow"]);
+    assert_eq!(
+        logs_of(Delayed { sequential: true }).await?,
+        ["slow", "fast"]
+    );
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_timeouts_follow_the_paused_clock() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Delayed { sequential: true }.serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    let started_at = tokio::time::Instant::now();
+    let reason = server.waiting().await?;
+    assert!(matches!(
+        reason,
+        QuitReason::Expired(SessionExpiry::IdleTimeout(_))
+    ));
+    assert!(started_at.elapsed() >= Duration::from_secs(30));
+    client.waiting().await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_sequential_keeps_every_log_and_progress_message() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Chatty.serve(server_transport));
+    let recorder = Recorder::default();
+    let client = recorder.clone().serve(client_transport).await?;
+    let server = server.await??;
+
+    // the client sends a progress token with every request
+    client.call_tool(call("chatty")).await?;
+    let all_seen = async {
+        while recorder.0.lock().unwrap().len() < 2 * CHATTER {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    let timed_out = tokio::time::timeout(Duration::from_secs(5), all_seen)
+        .await
+        .is_err();
+
+    let expected: Vec<_> = (0..CHATTER)
+        .flat_map(|step| [format!("log {step}"), format!("progress {step}")])
+        .collect();
+    assert!(!timed_out, "messages are missing");
+    assert_eq!(*recorder.0.lock().unwrap(), expected);
+    assert_eq!(server.peer().dropped_notifications(), 0);
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+//cargo test --test test_server_builder --features "server client"
+use rmcp::{
+    ServiceExt,
+    handler::server::{builder::Server, router::tool::CallToolHandlerExt, wrapper::Parameters},
+    model::{CallToolRequestParams, object},
+    schemars,
+};
+use serde_json::json;
+
+/// Add two numbers.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct AddArgs {
+    a: i64,
+    b: i64,
+}
+
+fn call(name: &'static str, arguments: serde_json::Value) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: Some(object(arguments)),
+        task: None,
+    }
+}
+
+#[tokio::test]
+async fn test_closure_tools() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(
+        Server::builder()
+            .name("calculator", "1.0.0")
+            .instructions("do the maths")
+            .tool("add", |Parameters(args): Parameters<AddArgs>| async move {
+                (args.a + args.b).to_string()
+            })
+            .route(
+                (|| async { "pong".to_owned() })
+                    .name("ping")
+                    .description("Answer pong."),
+            )
+            .serve(server_transport),
+    );
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    let info = client.peer_info().expect("server info");
+    assert_eq!(info.server_info.name, "calculator");
+    assert_eq!(info.instructions.as_deref(), Some("do the maths"));
+    assert!(info.capabilities.tools.is_some());
+
+    let mut tools = client.list_all_tools().await?;
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(tools.len(), 2);
+    assert_eq!(tools[0].name, "add");
+    assert_eq!(tools[0].description.as_deref(), Some("Add two numbers."));
+    assert_eq!(
+        tools[0].input_schema["required"],
+        json!(["a", "b"]),
+        "schema generated from AddArgs"
+    );
+    assert_eq!(tools[1].description.as_deref(), Some("Answer pong."));
+
+    let sum = client
+        .call_tool(call("add", json!({ "a": 2, "b": 3 })))
+        .await?;
+    assert_eq!(sum.content[0].as_text().unwrap().text, "5");
+    let pong = client.call_tool(call("ping", json!({}))).await?;
+    assert_eq!(pong.content[0].as_text().unwrap().text, "pong");
+    assert!(
+        client
+            .call_tool(call("add", json!({ "a": "two" })))
+            .await
+            .is_err()
+    );
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//cargo test --test test_session_memory --features "client server transport-streamable-http-server-axum transport-streamable-http-client-reqwest"
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    ServiceExt,
+    model::{
+        LoggingLevel, LoggingMessageNotification, LoggingMessageNotificationParam,
+        ServerNotification,
+    },
+    transport::{
+        StreamableHttpClientTransport,
+        streamable_http_server::{
+            SessionRegistry, StreamableHttpServerConfig, StreamableHttpService,
+            session::local::LocalSessionManager,
+        },
+    },
+};
+use tokio_util::sync::CancellationToken;
+
+mod common;
+use common::calculator::Calculator;
+
+const CAP: usize = 4096;
+
+fn large_log() -> ServerNotification {
+    LoggingMessageNotification::new(LoggingMessageNotificationParam {
+        level: LoggingLevel::Info,
+        logger: None,
+        data: "x".repeat(1024).into(),
+    })
+    .into()
+}
+
+async fn wait_for_len(len: usize, registry: &SessionRegistry) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while registry.len() != len {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("registry reaches the expected size");
+}
+
+#[tokio::test]
+async fn test_memory_report_and_session_cap() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let service = StreamableHttpService::new(
+        || Ok(Calculator::new()),
+        LocalSessionManager::default().into(),
+        StreamableHttpServerConfig {
+            sse_keep_alive: None,
+            cancellation_token: ct.child_token(),
+            max_session_buffered_bytes: Some(CAP),
+            ..Default::default()
+        },
+    );
+    let registry = Arc::clone(service.session_registry());
+    // every request enforces the caps, count the standalone streams so none
+    // of them comes in after the noisy session is over its cap
+    let streams = Arc::new(AtomicUsize::new(0));
+    let app = axum::Router::new()
+        .route("/debug/memory", service.memory_report_route())
+        .nest_service("/mcp", service)
+        .layer(axum::middleware::from_fn({
+            let streams = streams.clone();
+            move |request: axum::extract::Request, next: axum::middleware::Next| {
+                let streams = streams.clone();
+                async move {
+                    let is_stream = request.method() == http::Method::GET;
+                    let response = next.run(request).await;
+                    if is_stream {
+                        streams.fetch_add(1, Ordering::SeqCst);
+                    }
+                    response
+                }
+            }
+        }));
+    let tcp_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = tcp_listener.local_addr()?;
+    let handle = tokio::spawn({
+        let ct = ct.clone();
+        async move {
+            let _ = axum::serve(tcp_listener, app)
+                .with_graceful_shutdown(async move { ct.cancelled_owned().await })
+                .await;
+        }
+    });
+
+    let noisy = ()
+        .serve(StreamableHttpClientTransport::from_uri(format!(
+            "http://{addr}/mcp"
+        )))
+        .await?;
+    wait_for_len(1, &registry).await;
+    let noisy_id = registry.sessions()[0].id.clone();
+    let quiet = ()
+        .serve(StreamableHttpClientTransport::from_uri(format!(
+            "http://{addr}/mcp"
+        )))
+        .await?;
+    wait_for_len(2, &registry).await;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while streams.load(Ordering::SeqCst) < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+
+    // kept for resumption whether or not the client is listening
+    for _ in 0..8 {
+        registry.send_to(&noisy_id, large_log()).await?;
+    }
+    // queued or cached, the bytes count for the session either way
+    let report = registry.memory_report();
+    assert_eq!(report.sessions.len(), 2);
+    assert_eq!(report.sessions[0].id, noisy_id);
+    assert!(report.sessions[0].total > CAP);
+    assert!(report.sessions[1].total < CAP);
+    assert!(rmcp::service::buffered_bytes().total() >= report.total.total());
+
+    let body: serde_json::Value = reqwest::get(format!("http://{addr}/debug/memory"))
+        .await?
+        .json()
+        .await?;
+    assert_eq!(body["sessions"][0]["id"], noisy_id.as_ref());
+    assert!(body["sessions"][0]["total"].as_u64().unwrap() > CAP as u64);
+
+    // the next request closes the session over the cap
+    quiet.list_all_tools().await?;
+    wait_for_len(1, &registry).await;
+    assert_ne!(registry.sessions()[0].id, noisy_id);
+
+    drop(noisy);
+    quiet.cancel().await?;
+    wait_for_len(0, &registry).await;
+    ct.cancel();
+    handle.await?;
+    Ok(())
+}
@@ -0,0 +1,215 @@
+//cargo test --test test_session_quota --features "client server"
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rmcp::{
+    RoleServer, ServerHandler, ServiceError, ServiceExt,
+    model::{ErrorCode, ErrorData, ListToolsResult, PaginatedRequestParams},
+    service::{
+        FairScheduler, QuotaService, QuotaUsage, RequestContext, RunningService, SessionQuota,
+    },
+};
+
+#[derive(Default)]
+struct SlowServer {
+    name: &'static str,
+    finished: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl ServerHandler for SlowServer {
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, rmcp::ErrorData> {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        self.finished.lock().unwrap().push(self.name);
+        Ok(ListToolsResult::default())
+    }
+}
+
+async fn start(
+    quota: SessionQuota,
+) -> anyhow::Result<(
+    RunningService<RoleServer, QuotaService<SlowServer>>,
+    RunningService<rmcp::RoleClient, ()>,
+)> {
+    serve(QuotaService::new(SlowServer::default(), quota)).await
+}
+
+async fn serve(
+    service: QuotaService<SlowServer>,
+) -> anyhow::Result<(
+    RunningService<RoleServer, QuotaService<SlowServer>>,
+    RunningService<rmcp::RoleClient, ()>,
+)> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    Ok(tokio::try_join!(
+        async { anyhow::Ok(service.serve(server_transport).await?) },
+        async { anyhow::Ok(().serve(client_transport).await?) },
+    )?)
+}
+
+fn error_of(error: ServiceError) -> ErrorData {
+    let ServiceError::McpError(error) = error else {
+        panic!("expected an mcp error, got {error}");
+    };
+    error
+}
+
+fn quota_of(error: &ErrorData) -> &str {
+    error.data.as_ref().expect("error data")["quota"]
+        .as_str()
+        .expect("quota name")
+}
+
+fn retry_after_of(error: &ErrorData) -> u64 {
+    assert_eq!(error.code, ErrorCode::SERVER_OVERLOADED);
+    error.data.as_ref().expect("error data")["retryAfterMs"]
+        .as_u64()
+        .expect("retry hint")
+}
+
+#[tokio::test]
+async fn test_requests_per_minute() -> anyhow::Result<()> {
+    let (server, client) = start(SessionQuota {
+        max_requests_per_minute: Some(2),
+        ..Default::default()
+    })
+    .await?;
+
+    client.list_tools(None).await?;
+    client.list_tools(None).await?;
+    let error = error_of(client.list_tools(None).await.unwrap_err());
+    assert_eq!(quota_of(&error), "requestsPerMinute");
+    // the oldest request leaves the window in a little under a minute
+    let retry_after = retry_after_of(&error);
+    assert!(retry_after > 55_000 && retry_after <= 60_000);
+
+    assert_eq!(
+        server.service().usage(),
+        QuotaUsage {
+            requests_last_minute: 2,
+            in_flight: 0,
+            content_bytes: 0,
+            rejected: 1,
+        }
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_concurrent_requests() -> anyhow::Result<()> {
+    let (server, client) = start(SessionQuota {
+        max_concurrent_requests: Some(1),
+        ..Default::default()
+    })
+    .await?;
+
+    let (first, second) = tokio::join!(client.list_tools(None), async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.list_tools(None).await
+    });
+    first?;
+    let error = error_of(second.unwrap_err());
+    assert_eq!(quota_of(&error), "concurrentRequests");
+    assert!(retry_after_of(&error) > 0);
+
+    // the slot is released once the first request is done
+    client.list_tools(None).await?;
+    assert_eq!(server.service().usage().in_flight, 0);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_content_bytes() -> anyhow::Result<()> {
+    let (server, client) = start(SessionQuota {
+        max_content_bytes: Some(64),
+        ..Default::default()
+    })
+    .await?;
+
+    client.list_tools(None).await?;
+    let used = server.service().usage().content_bytes;
+    assert!(used > 0);
+    let mut rejected = None;
+    for _ in 0..64 {
+        if let Err(error) = client.list_tools(None).await {
+            rejected = Some(error);
+            break;
+        }
+    }
+    // the budget never comes back, so this one is not worth a retry
+    let error = error_of(rejected.expect("quota kicks in"));
+    assert_eq!(quota_of(&error), "contentBytes");
+    assert_eq!(error.code, ErrorCode::INVALID_REQUEST);
+    assert!(server.service().usage().content_bytes <= 64);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_oversized_request_leaves_the_rate_alone() -> anyhow::Result<()> {
+    let (server, client) = start(SessionQuota {
+        max_requests_per_minute: Some(2),
+        max_content_bytes: Some(512),
+        ..Default::default()
+    })
+    .await?;
+
+    let oversized = PaginatedRequestParams {
+        meta: None,
+        cursor: Some("x".repeat(1024)),
+    };
+    let error = error_of(client.list_tools(Some(oversized)).await.unwrap_err());
+    assert_eq!(quota_of(&error), "contentBytes");
+    // the rejected request didn't take one of the two slots of the minute
+    client.list_tools(None).await?;
+    client.list_tools(None).await?;
+    assert_eq!(server.service().usage().requests_last_minute, 2);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fair_scheduler_takes_turns() -> anyhow::Result<()> {
+    let scheduler = FairScheduler::new(1);
+    let finished = Arc::new(Mutex::new(Vec::new()));
+    let session = |name| {
+        QuotaService::new(
+            SlowServer {
+                name,
+                finished: finished.clone(),
+            },
+            SessionQuota::default(),
+        )
+        .with_scheduler(scheduler.clone())
+    };
+    let (_busy_server, busy) = serve(session("busy")).await?;
+    let (_quiet_server, quiet) = serve(session("quiet")).await?;
+
+    // the busy session queues up three requests before the quiet one asks
+    let (results, quiet_result) = tokio::join!(
+        futures::future::join_all((0..3).map(|_| busy.list_tools(None))),
+        async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert_eq!(scheduler.waiting(), 2);
+            quiet.list_tools(None).await
+        }
+    );
+    for result in results {
+        result?;
+    }
+    quiet_result?;
+    assert_eq!(*finished.lock().unwrap(), ["busy", "busy", "quiet", "busy"]);
+    assert_eq!(scheduler.in_flight(), 0);
+
+    busy.cancel().await?;
+    quiet.cancel().await?;
+    Ok(())
+}
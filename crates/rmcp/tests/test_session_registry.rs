@@ -0,0 +1,153 @@
+//cargo test --test test_session_registry --features "client server transport-streamable-http-server-axum transport-streamable-http-client-reqwest"
+
+use std::{sync::Arc, time::Duration};
+
+use rmcp::{
+    ClientHandler, RoleClient, ServiceExt,
+    model::{ClientInfo, Implementation, ResourceListChangedNotification, ServerNotification},
+    service::NotificationContext,
+    transport::{
+        StreamableHttpClientTransport,
+        streamable_http_server::{
+            StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
+        },
+    },
+};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+mod common;
+use common::calculator::Calculator;
+
+#[derive(Clone)]
+struct NamedClient {
+    name: &'static str,
+    received: mpsc::UnboundedSender<&'static str>,
+}
+
+impl ClientHandler for NamedClient {
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            client_info: Implementation {
+                name: self.name.to_string(),
+                ..Implementation::from_build_env()
+            },
+            ..Default::default()
+        }
+    }
+
+    async fn on_resource_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        let _ = self.received.send(self.name);
+    }
+}
+
+fn resource_list_changed() -> ServerNotification {
+    ResourceListChangedNotification::default().into()
+}
+
+async fn wait_for_len(
+    len: usize,
+    registry: &rmcp::transport::streamable_http_server::SessionRegistry,
+) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while registry.len() != len {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("registry reaches the expected size");
+}
+
+#[tokio::test]
+async fn test_broadcast_and_send_to() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let service = StreamableHttpService::new(
+        || Ok(Calculator::new()),
+        LocalSessionManager::default().into(),
+        StreamableHttpServerConfig {
+            sse_keep_alive: None,
+            cancellation_token: ct.child_token(),
+            ..Default::default()
+        },
+    );
+    let registry = Arc::clone(service.session_registry());
+    let app = service.into_router();
+    let tcp_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = tcp_listener.local_addr()?;
+    let handle = tokio::spawn({
+        let ct = ct.clone();
+        async move {
+            let _ = axum::serve(tcp_listener, app)
+                .with_graceful_shutdown(async move { ct.cancelled_owned().await })
+                .await;
+        }
+    });
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut clients = Vec::new();
+    for name in ["alpha", "beta"] {
+        let transport = StreamableHttpClientTransport::from_uri(format!("http://{addr}/"));
+        let client = NamedClient {
+            name,
+            received: tx.clone(),
+        };
+        clients.push(client.serve(transport).await?);
+    }
+    wait_for_len(2, &registry).await;
+
+    let mut names = registry
+        .sessions()
+        .iter()
+        .filter_map(|session| Some(session.client_info()?.client_info.name.clone()))
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["alpha", "beta"]);
+
+    // the standalone SSE stream may still be connecting, so retry until both
+    // clients have seen the broadcast
+    let mut seen = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while seen.len() < 2 {
+            registry
+                .broadcast_notification(resource_list_changed())
+                .await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            while let Ok(name) = rx.try_recv() {
+                if !seen.contains(&name) {
+                    seen.push(name);
+                }
+            }
+        }
+    })
+    .await?;
+    while rx.try_recv().is_ok() {}
+
+    let beta = registry
+        .sessions()
+        .into_iter()
+        .find(|session| {
+            session
+                .client_info()
+                .is_some_and(|info| info.client_info.name == "beta")
+        })
+        .expect("beta session");
+    registry.send_to(&beta.id, resource_list_changed()).await?;
+    let received = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await?;
+    assert_eq!(received, Some("beta"));
+
+    let sent = registry
+        .broadcast_notification_where(|session| session.id == beta.id, resource_list_changed())
+        .await;
+    assert_eq!(sent, 1);
+    drop(beta);
+
+    for client in clients {
+        client.cancel().await?;
+    }
+    wait_for_len(0, &registry).await;
+    assert!(registry.is_empty());
+
+    ct.cancel();
+    handle.await?;
+    Ok(())
+}
@@ -0,0 +1,168 @@
+//cargo test --test test_session_timeouts --features "client server"
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rmcp::{
+    ClientHandler, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::{ListToolsResult, LoggingMessageNotificationParam, PaginatedRequestParams},
+    service::{
+        NotificationContext, QuitReason, RequestContext, ServiceConfig, SessionExpiry,
+        SessionTimeouts,
+    },
+};
+
+#[derive(Clone, Default)]
+struct Server {
+    timeouts: SessionTimeouts,
+    ended: Arc<Mutex<Option<String>>>,
+}
+
+impl ServerHandler for Server {
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, rmcp::ErrorData> {
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        Ok(ListToolsResult::default())
+    }
+
+    fn config(&self) -> ServiceConfig {
+        let ended = self.ended.clone();
+        ServiceConfig {
+            session_timeouts: self.timeouts,
+            ..Default::default()
+        }
+        .on_session_end(move |reason: &QuitReason| {
+            *ended.lock().unwrap() = Some(format!("{reason:?}"));
+        })
+    }
+}
+
+#[derive(Clone, Default)]
+struct Client {
+    logs: Arc<Mutex<Vec<String>>>,
+}
+
+impl ClientHandler for Client {
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        if let Some(message) = params.data.as_str() {
+            self.logs.lock().unwrap().push(message.to_owned());
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_idle_timeout_closes_session() -> anyhow::Result<()> {
+    let server = Server {
+        timeouts: SessionTimeouts {
+            idle_timeout: Some(Duration::from_millis(200)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let client = Client::default();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (running_server, running_client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(client.clone().serve(client_transport).await?) },
+    )?;
+
+    // a request that outlives the idle timeout doesn't count as idle
+    running_client.list_tools(None).await?;
+
+    let reason = tokio::time::timeout(Duration::from_secs(5), running_server.waiting()).await??;
+    assert!(matches!(
+        reason,
+        QuitReason::Expired(SessionExpiry::IdleTimeout(idle)) if idle == Duration::from_millis(200)
+    ));
+    assert!(
+        server
+            .ended
+            .lock()
+            .unwrap()
+            .as_deref()
+            .is_some_and(|reason| reason.contains("IdleTimeout"))
+    );
+
+    tokio::time::timeout(Duration::from_secs(5), running_client.waiting()).await??;
+    let logs = client.logs.lock().unwrap().clone();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].starts_with("session closed: idle"));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_idle_timeout_counts_from_the_last_peer_message() -> anyhow::Result<()> {
+    let server = Server {
+        timeouts: SessionTimeouts {
+            idle_timeout: Some(Duration::from_millis(200)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (running_server, running_client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(Client::default().serve(client_transport).await?) },
+    )?;
+
+    // the client went quiet when it sent the request, answering it is no
+    // activity of the client
+    running_client.list_tools(None).await?;
+    let answered_at = tokio::time::Instant::now();
+    let reason = tokio::time::timeout(Duration::from_secs(5), running_server.waiting()).await??;
+    assert!(matches!(
+        reason,
+        QuitReason::Expired(SessionExpiry::IdleTimeout(_))
+    ));
+    assert!(answered_at.elapsed() < Duration::from_millis(200));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_duration_closes_active_session() -> anyhow::Result<()> {
+    let server = Server {
+        timeouts: SessionTimeouts {
+            idle_timeout: Some(Duration::from_secs(60)),
+            max_duration: Some(Duration::from_millis(300)),
+        },
+        ..Default::default()
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (running_server, running_client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(Client::default().serve(client_transport).await?) },
+    )?;
+
+    let reason = tokio::time::timeout(Duration::from_secs(5), running_server.waiting()).await??;
+    assert!(matches!(
+        reason,
+        QuitReason::Expired(SessionExpiry::MaxDuration(_))
+    ));
+    tokio::time::timeout(Duration::from_secs(5), running_client.waiting()).await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_on_session_end_without_timeouts() -> anyhow::Result<()> {
+    let server = Server::default();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (running_server, running_client) = tokio::try_join!(
+        async { anyhow::Ok(server.clone().serve(server_transport).await?) },
+        async { anyhow::Ok(Client::default().serve(client_transport).await?) },
+    )?;
+
+    running_client.cancel().await?;
+    let reason = tokio::time::timeout(Duration::from_secs(5), running_server.waiting()).await??;
+    assert!(matches!(reason, QuitReason::Closed));
+    assert_eq!(server.ended.lock().unwrap().as_deref(), Some("Closed"));
+    Ok(())
+}
@@ -0,0 +1,159 @@
+//cargo test --test test_stdio_framing --features "client server"
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    model::{CallToolRequestParams, CallToolResult, Content},
+    service::RequestContext,
+    transport::{
+        Encoding, Framing,
+        async_rw::{AsyncRwTransport, SupportedEncodings},
+    },
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const LOG: &str = "line one\nline two\r\n\r\nContent-Length: 0\r\n\r\n";
+
+struct Server;
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut result = CallToolResult::success(vec![
+            Content::text(LOG),
+            Content::image("iVBORw0KGgo=", "image/png"),
+        ]);
+        result.structured_content = Some(serde_json::json!({
+            "lines": 2,
+            "ratio": 0.5,
+            "offset": -1,
+            "tags": ["log", null],
+        }));
+        Ok(result)
+    }
+}
+
+#[tokio::test]
+async fn test_content_length_session() -> anyhow::Result<()> {
+    let (server_io, client_io) = tokio::io::duplex(4096);
+    let (server_read, server_write) = tokio::io::split(server_io);
+    let (client_read, client_write) = tokio::io::split(client_io);
+    let server = AsyncRwTransport::with_framing(server_read, server_write, Framing::ContentLength);
+    let client = AsyncRwTransport::with_framing(client_read, client_write, Framing::ContentLength);
+    let (server, client) =
+        tokio::try_join!(async { anyhow::Ok(Server.serve(server).await?) }, async {
+            anyhow::Ok(().serve(client).await?)
+        },)?;
+
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "read_log".into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+    // text that looks like framing survives
+    assert_eq!(result.content[0].as_text().unwrap().text, LOG);
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_content_length_on_the_wire() -> anyhow::Result<()> {
+    let (server_io, mut client_io) = tokio::io::duplex(4096);
+    let (server_read, server_write) = tokio::io::split(server_io);
+    let server = AsyncRwTransport::with_framing(server_read, server_write, Framing::ContentLength);
+    let serving = tokio::spawn(Server.serve(server));
+
+    // a pretty-printed request, which newline-delimited framing would split
+    let request = serde_json::to_string_pretty(&serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2025-06-18",
+            "capabilities": {},
+            "clientInfo": { "name": "raw", "version": "0.0.0" },
+        },
+    }))?;
+    client_io
+        .write_all(format!("Content-Length: {}\r\n\r\n{request}", request.len()).as_bytes())
+        .await?;
+
+    let mut response = Vec::new();
+    while !response.ends_with(b"}") {
+        let mut chunk = [0; 1024];
+        let read = client_io.read(&mut chunk).await?;
+        anyhow::ensure!(read > 0, "server closed the stream");
+        response.extend_from_slice(&chunk[..read]);
+    }
+    let response = String::from_utf8(response)?;
+    let (headers, body) = response.split_once("\r\n\r\n").unwrap();
+    assert_eq!(headers, format!("Content-Length: {}", body.len()));
+    let body: serde_json::Value = serde_json::from_str(body)?;
+    assert_eq!(body["id"], 1);
+    assert!(body["result"]["serverInfo"].is_object());
+
+    drop(client_io);
+    let _ = serving.await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_encoded_sessions() -> anyhow::Result<()> {
+    for &encoding in Encoding::ALL {
+        let (server_io, client_io) = tokio::io::duplex(4096);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        let (client_read, client_write) = tokio::io::split(client_io);
+        let server = AsyncRwTransport::with_encoding(server_read, server_write, encoding);
+        let client = AsyncRwTransport::with_encoding(client_read, client_write, encoding);
+        let (server, client) =
+            tokio::try_join!(async { anyhow::Ok(Server.serve(server).await?) }, async {
+                anyhow::Ok(().serve(client).await?)
+            },)?;
+
+        let result = client
+            .call_tool(CallToolRequestParams {
+                meta: None,
+                name: "read_log".into(),
+                arguments: serde_json::json!({ "limit": 10 }).as_object().cloned(),
+                task: None,
+            })
+            .await?;
+        assert_eq!(result.content[0].as_text().unwrap().text, LOG, "{encoding}");
+        assert_eq!(
+            result.content[1].as_image().unwrap().mime_type,
+            "image/png",
+            "{encoding}"
+        );
+        assert_eq!(
+            result.structured_content,
+            Some(serde_json::json!({
+                "lines": 2,
+                "ratio": 0.5,
+                "offset": -1,
+                "tags": ["log", null],
+            })),
+            "{encoding}"
+        );
+
+        client.cancel().await?;
+        server.cancel().await?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_supported_encodings() {
+    let local = SupportedEncodings::local();
+    assert_eq!(local.encodings.last().map(String::as_str), Some("json"));
+    let peer = SupportedEncodings {
+        encodings: vec!["protobuf".into(), "json".into()],
+    };
+    assert_eq!(peer.preferred(), Some(Encoding::Json));
+    assert_eq!(local.preferred(), Encoding::ALL.last().copied());
+}
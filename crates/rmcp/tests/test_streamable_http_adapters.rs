@@ -0,0 +1,91 @@
+use rmcp::transport::streamable_http_server::{
+    StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
+};
+use tokio_util::sync::CancellationToken;
+
+mod common;
+use common::calculator::Calculator;
+
+const INITIALIZE_REQUEST: &str = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-11-25","capabilities":{},"clientInfo":{"name":"test","version":"1.0"}}}"#;
+
+fn calculator_service(ct: &CancellationToken) -> StreamableHttpService<Calculator> {
+    StreamableHttpService::new(
+        || Ok(Calculator::new()),
+        LocalSessionManager::default().into(),
+        StreamableHttpServerConfig {
+            sse_keep_alive: None,
+            cancellation_token: ct.child_token(),
+            ..Default::default()
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_actix_scope_initialize() -> anyhow::Result<()> {
+    use actix_web::{App, test};
+
+    let ct = CancellationToken::new();
+    let app =
+        test::init_service(App::new().service(calculator_service(&ct).actix_scope("/mcp"))).await;
+    let request = test::TestRequest::post()
+        .uri("/mcp")
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Accept", "application/json, text/event-stream"))
+        .set_payload(INITIALIZE_REQUEST)
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers().contains_key("mcp-session-id"));
+    let body = test::read_body(response).await;
+    let body = String::from_utf8(body.to_vec())?;
+    assert!(body.contains(r#""id":1"#));
+    assert!(body.contains("A simple calculator"));
+
+    let request = test::TestRequest::put().uri("/mcp").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status().as_u16(), 405);
+
+    ct.cancel();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_warp_filter_initialize() -> anyhow::Result<()> {
+    use warp::Filter;
+
+    let ct = CancellationToken::new();
+    let filter = warp::path("mcp").and(calculator_service(&ct).warp_filter());
+    let response = warp::test::request()
+        .method("POST")
+        .path("/mcp")
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json, text/event-stream")
+        .body(INITIALIZE_REQUEST)
+        .reply(&filter)
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers().contains_key("mcp-session-id"));
+    let body = String::from_utf8(response.body().to_vec())?;
+    assert!(body.contains(r#""id":1"#));
+    assert!(body.contains("A simple calculator"));
+
+    let response = warp::test::request()
+        .method("PUT")
+        .path("/mcp")
+        .reply(&filter)
+        .await;
+    assert_eq!(response.status().as_u16(), 405);
+
+    let response = warp::test::request()
+        .method("POST")
+        .path("/mcp")
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json, text/event-stream")
+        .body(vec![b' '; 3 * 1024 * 1024])
+        .reply(&filter)
+        .await;
+    assert_eq!(response.status().as_u16(), 413);
+
+    ct.cancel();
+    Ok(())
+}
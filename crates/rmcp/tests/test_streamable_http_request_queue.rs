@@ -0,0 +1,200 @@
+//cargo test --test test_streamable_http_request_queue --features "client transport-streamable-http-client"
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use futures::stream::BoxStream;
+use rmcp::{
+    ServiceExt,
+    model::{ClientJsonRpcMessage, ClientRequest, ServerInfo, ServerJsonRpcMessage, ServerResult},
+    service::{CloseReason, ConnectionState},
+    transport::{
+        common::client_side_sse::FixedInterval,
+        streamable_http_client::{
+            RequestQueueConfig, SseError, StreamableHttpClient, StreamableHttpClientTransport,
+            StreamableHttpClientTransportConfig, StreamableHttpError, StreamableHttpPostResponse,
+        },
+    },
+};
+use sse_stream::Sse;
+
+/// Answers like a server with no tools, unless `down` is set.
+#[derive(Clone, Default)]
+struct FlakyServer {
+    down: Arc<AtomicBool>,
+}
+
+impl StreamableHttpClient for FlakyServer {
+    type Error = std::io::Error;
+
+    async fn post_message(
+        &self,
+        _uri: Arc<str>,
+        message: ClientJsonRpcMessage,
+        _session_id: Option<Arc<str>>,
+        _auth_header: Option<String>,
+    ) -> Result<StreamableHttpPostResponse, StreamableHttpError<Self::Error>> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err(StreamableHttpError::Io(
+                std::io::ErrorKind::ConnectionRefused.into(),
+            ));
+        }
+        let ClientJsonRpcMessage::Request(request) = message else {
+            return Ok(StreamableHttpPostResponse::Accepted);
+        };
+        let result = match request.request {
+            ClientRequest::InitializeRequest(_) => {
+                ServerResult::InitializeResult(ServerInfo::default())
+            }
+            ClientRequest::ListToolsRequest(_) => ServerResult::ListToolsResult(Default::default()),
+            _ => ServerResult::empty(()),
+        };
+        Ok(StreamableHttpPostResponse::Json(
+            ServerJsonRpcMessage::response(result, request.id),
+            Some("session".to_owned()),
+        ))
+    }
+
+    async fn delete_session(
+        &self,
+        _uri: Arc<str>,
+        _session_id: Arc<str>,
+        _auth_header: Option<String>,
+    ) -> Result<(), StreamableHttpError<Self::Error>> {
+        Ok(())
+    }
+
+    async fn get_stream(
+        &self,
+        _uri: Arc<str>,
+        _session_id: Arc<str>,
+        _last_event_id: Option<String>,
+        _auth_header: Option<String>,
+    ) -> Result<BoxStream<'static, Result<Sse, SseError>>, StreamableHttpError<Self::Error>> {
+        Err(StreamableHttpError::ServerDoesNotSupportSse)
+    }
+}
+
+fn config(queue: RequestQueueConfig) -> StreamableHttpClientTransportConfig {
+    StreamableHttpClientTransportConfig {
+        retry_config: Arc::new(FixedInterval {
+            max_times: None,
+            duration: Duration::from_millis(20),
+        }),
+        ..StreamableHttpClientTransportConfig::with_uri("http://localhost/mcp")
+    }
+    .request_queue(queue)
+}
+
+#[tokio::test]
+async fn test_requests_are_flushed_on_reconnect() -> anyhow::Result<()> {
+    let server = FlakyServer::default();
+    let queue = RequestQueueConfig::default();
+    let metrics = queue.metrics.clone();
+    let transport = StreamableHttpClientTransport::with_client(server.clone(), config(queue));
+    let client = ().serve(transport).await?;
+
+    server.down.store(true, Ordering::SeqCst);
+    let first = tokio::spawn({
+        let peer = client.peer().clone();
+        async move { peer.list_tools(None).await }
+    });
+    let second = tokio::spawn({
+        let peer = client.peer().clone();
+        async move { peer.list_tools(None).await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(metrics.is_reconnecting());
+    assert_eq!(metrics.depth(), 2);
+
+    server.down.store(false, Ordering::SeqCst);
+    first.await??;
+    second.await??;
+    assert!(!metrics.is_reconnecting());
+    assert_eq!(metrics.depth(), 0);
+    assert_eq!(metrics.flushed(), 2);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_queued_requests_expire() -> anyhow::Result<()> {
+    let server = FlakyServer::default();
+    let queue = RequestQueueConfig::default()
+        .capacity(1)
+        .deadline(Duration::from_millis(100));
+    let metrics = queue.metrics.clone();
+    let transport = StreamableHttpClientTransport::with_client(server.clone(), config(queue));
+    let client = ().serve(transport).await?;
+
+    server.down.store(true, Ordering::SeqCst);
+    let (first, second) = tokio::join!(client.list_tools(None), client.list_tools(None));
+    assert!(first.is_err());
+    assert!(second.is_err());
+    assert_eq!(metrics.rejected(), 1);
+    assert_eq!(metrics.expired(), 1);
+    assert_eq!(metrics.depth(), 0);
+
+    // once the server is back, requests go through again
+    server.down.store(false, Ordering::SeqCst);
+    client.list_tools(None).await?;
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_without_queue_requests_fail_right_away() -> anyhow::Result<()> {
+    let server = FlakyServer::default();
+    let transport = StreamableHttpClientTransport::with_client(
+        server.clone(),
+        StreamableHttpClientTransportConfig::with_uri("http://localhost/mcp"),
+    );
+    let client = ().serve(transport).await?;
+
+    server.down.store(true, Ordering::SeqCst);
+    let result = tokio::time::timeout(Duration::from_secs(1), client.list_tools(None)).await?;
+    assert!(result.is_err());
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_state_changes_while_reconnecting() -> anyhow::Result<()> {
+    let server = FlakyServer::default();
+    let transport = StreamableHttpClientTransport::with_client(
+        server.clone(),
+        config(RequestQueueConfig::default()),
+    );
+    let client = ().serve(transport).await?;
+    let mut state = client.state_changes();
+    assert_eq!(*state.borrow_and_update(), ConnectionState::Ready);
+
+    server.down.store(true, Ordering::SeqCst);
+    let request = tokio::spawn({
+        let peer = client.peer().clone();
+        async move { peer.list_tools(None).await }
+    });
+    state.changed().await?;
+    assert_eq!(*state.borrow_and_update(), ConnectionState::Reconnecting);
+
+    server.down.store(false, Ordering::SeqCst);
+    state.changed().await?;
+    assert_eq!(*state.borrow_and_update(), ConnectionState::Ready);
+    request.await??;
+
+    client.cancel().await?;
+    assert_eq!(
+        *state.borrow(),
+        ConnectionState::Closed {
+            reason: CloseReason::Cancelled
+        }
+    );
+    Ok(())
+}
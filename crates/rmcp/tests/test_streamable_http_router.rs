@@ -0,0 +1,107 @@
+use rmcp::{
+    ServiceExt,
+    transport::{
+        StreamableHttpClientTransport,
+        streamable_http_server::{
+            StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
+        },
+    },
+};
+use tokio_util::sync::CancellationToken;
+
+mod common;
+use common::calculator::Calculator;
+
+fn calculator_service(ct: &CancellationToken) -> StreamableHttpService<Calculator> {
+    StreamableHttpService::new(
+        || Ok(Calculator::new()),
+        LocalSessionManager::default().into(),
+        StreamableHttpServerConfig {
+            sse_keep_alive: None,
+            cancellation_token: ct.child_token(),
+            ..Default::default()
+        },
+    )
+}
+
+async fn spawn_app(
+    app: axum::Router,
+    ct: CancellationToken,
+) -> anyhow::Result<(std::net::SocketAddr, tokio::task::JoinHandle<()>)> {
+    let tcp_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = tcp_listener.local_addr()?;
+    let handle = tokio::spawn(async move {
+        let _ = axum::serve(tcp_listener, app)
+            .with_graceful_shutdown(async move { ct.cancelled_owned().await })
+            .await;
+    });
+    Ok((addr, handle))
+}
+
+#[tokio::test]
+async fn test_nest_into_existing_router() -> anyhow::Result<()> {
+    let ct = CancellationToken::new();
+    let app = axum::Router::new().route("/health", axum::routing::get(|| async { "ok" }));
+    let app = calculator_service(&ct).nest_into(app, "/mcp");
+    let (addr, handle) = spawn_app(app, ct.clone()).await?;
+
+    let health = reqwest::get(format!("http://{addr}/health"))
+        .await?
+        .text()
+        .await?;
+    assert_eq!(health, "ok");
+
+    let transport = StreamableHttpClientTransport::from_uri(format!("http://{addr}/mcp"));
+    let client = ().serve(transport).await?;
+    let server_info = client.peer_info().expect("server info");
+    assert_eq!(
+        server_info.instructions.as_deref(),
+        Some("A simple calculator")
+    );
+    client.cancel().await?;
+
+    ct.cancel();
+    handle.await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_into_router_nested_with_shared_state() -> anyhow::Result<()> {
+    #[derive(Clone)]
+    struct AppState {
+        greeting: &'static str,
+    }
+
+    let ct = CancellationToken::new();
+    let app = axum::Router::new()
+        .route(
+            "/greeting",
+            axum::routing::get(
+                |axum::extract::State(state): axum::extract::State<AppState>| async move {
+                    state.greeting
+                },
+            ),
+        )
+        .nest("/api/mcp", calculator_service(&ct).into_router())
+        .with_state(AppState { greeting: "hello" });
+    let (addr, handle) = spawn_app(app, ct.clone()).await?;
+
+    let greeting = reqwest::get(format!("http://{addr}/greeting"))
+        .await?
+        .text()
+        .await?;
+    assert_eq!(greeting, "hello");
+
+    let transport = StreamableHttpClientTransport::from_uri(format!("http://{addr}/api/mcp"));
+    let client = ().serve(transport).await?;
+    let server_info = client.peer_info().expect("server info");
+    assert_eq!(
+        server_info.instructions.as_deref(),
+        Some("A simple calculator")
+    );
+    client.cancel().await?;
+
+    ct.cancel();
+    handle.await?;
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//cargo test --test test_streamable_http_shutdown --features "client server transport-streamable-http-server-axum transport-streamable-http-client-reqwest"
+
+use std::time::{Duration, Instant};
+
+use rmcp::{
+    ClientHandler, RoleClient, ServiceExt,
+    model::LoggingMessageNotificationParam,
+    service::NotificationContext,
+    transport::{
+        StreamableHttpClientTransport,
+        streamable_http_server::{
+            StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
+        },
+    },
+};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+mod common;
+use common::calculator::Calculator;
+
+#[derive(Clone)]
+struct LoggingClient {
+    logs: mpsc::UnboundedSender<String>,
+}
+
+impl ClientHandler for LoggingClient {
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let _ = self.logs.send(params.data.to_string());
+    }
+}
+
+async fn start() -> anyhow::Result<(
+    StreamableHttpService<Calculator>,
+    std::net::SocketAddr,
+    tokio::task::JoinHandle<()>,
+)> {
+    let ct = CancellationToken::new();
+    let service = StreamableHttpService::new(
+        || Ok(Calculator::new()),
+        LocalSessionManager::default().into(),
+        StreamableHttpServerConfig {
+            sse_keep_alive: None,
+            cancellation_token: ct.clone(),
+            ..Default::default()
+        },
+    );
+    let app = service.clone().into_router();
+    let tcp_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = tcp_listener.local_addr()?;
+    let handle = tokio::spawn(async move {
+        let _ = axum::serve(tcp_listener, app)
+            .with_graceful_shutdown(async move { ct.cancelled_owned().await })
+            .await;
+    });
+    Ok((service, addr, handle))
+}
+
+async fn wait_for_sessions(service: &StreamableHttpService<Calculator>, len: usize) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while service.session_registry().len() != len {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("sessions registered");
+}
+
+#[tokio::test]
+async fn test_shutdown_waits_for_clients_to_leave() -> anyhow::Result<()> {
+    let (service, addr, handle) = start().await?;
+    let (logs_tx, mut logs) = mpsc::unbounded_channel();
+    let client = LoggingClient { logs: logs_tx }
+        .serve(StreamableHttpClientTransport::from_uri(format!(
+            "http://{addr}/"
+        )))
+        .await?;
+    wait_for_sessions(&service, 1).await;
+    // give the standalone SSE stream time to connect
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let started = Instant::now();
+    let shutdown = tokio::spawn({
+        let service = service.clone();
+        async move { service.shutdown(Duration::from_secs(30)).await }
+    });
+
+    let log = tokio::time::timeout(Duration::from_secs(5), logs.recv()).await?;
+    assert!(log.is_some_and(|log| log.contains("shutting down")));
+    assert!(service.is_shutting_down());
+
+    // new sessions are refused from now on
+    let refused = reqwest::Client::new()
+        .post(format!("http://{addr}/"))
+        .header("accept", "application/json, text/event-stream")
+        .header("content-type", "application/json")
+        .body(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"late","version":"0"}}}"#)
+        .send()
+        .await?;
+    assert_eq!(refused.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+
+    client.cancel().await?;
+    tokio::time::timeout(Duration::from_secs(5), shutdown).await??;
+    assert!(started.elapsed() < Duration::from_secs(30));
+    assert!(service.session_registry().is_empty());
+    tokio::time::timeout(Duration::from_secs(5), handle).await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shutdown_closes_lingering_sessions() -> anyhow::Result<()> {
+    let (service, addr, handle) = start().await?;
+    let (logs_tx, _logs) = mpsc::unbounded_channel();
+    let client = LoggingClient { logs: logs_tx }
+        .serve(StreamableHttpClientTransport::from_uri(format!(
+            "http://{addr}/"
+        )))
+        .await?;
+    wait_for_sessions(&service, 1).await;
+
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        service.shutdown(Duration::from_millis(200)),
+    )
+    .await?;
+    tokio::time::timeout(Duration::from_secs(5), handle).await??;
+    drop(client);
+    Ok(())
+}
@@ -2,7 +2,7 @@
 use rmcp::{
     Json, ServerHandler,
     handler::server::{router::tool::ToolRouter, tool::IntoCallToolResult, wrapper::Parameters},
-    model::{CallToolResult, Content, Tool},
+    model::{CallToolResult, Content, Tool, ToolResultError},
     tool, tool_handler, tool_router,
 };
 use schemars::JsonSchema;
@@ -280,3 +280,36 @@ async fn test_output_schema_requires_structured_content() {
     assert!(call_result.structured_content.is_some());
     assert!(!call_result.content.is_empty());
 }
+
+#[tokio::test]
+async fn test_call_tool_result_accessors() {
+    let structured = CallToolResult::structured(json!({"sum": 8, "product": 15}));
+    assert!(!structured.is_error());
+    assert_eq!(structured.text(), Some(r#"{"product":15,"sum":8}"#));
+    let result: CalculationResult = structured.json().unwrap();
+    assert_eq!((result.sum, result.product), (8, 15));
+
+    let Json(result) = Json::<CalculationResult>::try_from(structured).unwrap();
+    assert_eq!(result.sum, 8);
+
+    // unstructured content is parsed from the first text block
+    let text_only = CallToolResult::success(vec![
+        Content::image("aGk=", "image/png"),
+        Content::text(r#"{"name": "alice", "age": 30}"#),
+    ]);
+    let user: UserInfo = text_only.json().unwrap();
+    assert_eq!((user.name.as_str(), user.age), ("alice", 30));
+    assert!(matches!(
+        text_only.json::<CalculationResult>(),
+        Err(ToolResultError::Json(_))
+    ));
+
+    let failed = CallToolResult::error(vec![Content::text("division by zero")]);
+    assert!(failed.is_error());
+    let error = failed.json::<Value>().unwrap_err();
+    assert!(matches!(error, ToolResultError::ToolError(_)));
+    assert_eq!(
+        error.to_string(),
+        "tool returned an error: division by zero"
+    );
+}
@@ -1,8 +1,13 @@
-use std::{any::Any, time::Duration};
+use std::{any::Any, sync::Arc, time::Duration};
 
-use rmcp::task_manager::{
-    OperationDescriptor, OperationMessage, OperationProcessor, OperationResultTransport,
+use rmcp::{
+    model::CallToolResult,
+    task_manager::{
+        OperationDescriptor, OperationMessage, OperationProcessor, OperationResultTransport,
+        RetentionPolicy, ToolCallTaskResult,
+    },
 };
+use tokio::sync::Mutex;
 
 struct DummyTransport {
     id: String,
@@ -75,3 +80,89 @@ async fn rejects_duplicate_operation_ids() {
         .expect_err("duplicate should fail");
     assert!(format!("{err}").contains("already running"));
 }
+
+fn tool_call(id: &str, text: &str) -> OperationMessage {
+    let result = ToolCallTaskResult::new(id, Ok(CallToolResult::structured(text.into())));
+    OperationMessage::new(
+        OperationDescriptor::new(id, "tool"),
+        Box::pin(async move { Ok(Box::new(result) as Box<dyn OperationResultTransport>) }),
+    )
+}
+
+#[tokio::test]
+async fn expires_results_after_ttl() {
+    let mut processor = OperationProcessor::new()
+        .with_retention(RetentionPolicy::default().with_result_ttl(Duration::from_millis(50)));
+    processor
+        .submit_operation(tool_call("op1", "done"))
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    processor.receive_completed_results();
+    assert_eq!(processor.peek_completed().len(), 1);
+    assert!(processor.retained_bytes() > 0);
+    assert!(!processor.is_expired("op1"));
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(processor.evict_expired(), 1);
+    assert!(processor.peek_completed().is_empty());
+    assert_eq!(processor.retained_bytes(), 0);
+    assert!(processor.is_expired("op1"));
+    assert!(!processor.is_expired("unknown"));
+}
+
+#[tokio::test]
+async fn evicts_oldest_results_over_byte_cap() {
+    let size =
+        ToolCallTaskResult::new("op1", Ok(CallToolResult::structured("done".into()))).byte_size();
+    let mut processor = OperationProcessor::new()
+        .with_retention(RetentionPolicy::default().with_max_result_bytes(size));
+
+    processor
+        .submit_operation(tool_call("op1", "done"))
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    processor.receive_completed_results();
+    processor
+        .submit_operation(tool_call("op2", "done"))
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    processor.receive_completed_results();
+
+    let retained = processor
+        .peek_completed()
+        .iter()
+        .map(|result| result.descriptor.operation_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(retained, ["op2"]);
+    assert_eq!(processor.retained_bytes(), size);
+    assert!(processor.is_expired("op1"));
+    assert!(processor.take_completed_result("op2").is_some());
+    assert!(!processor.is_expired("op2"));
+}
+
+#[tokio::test]
+async fn evicts_in_background_until_dropped() {
+    let processor = Arc::new(Mutex::new(OperationProcessor::new().with_retention(
+        RetentionPolicy::default().with_result_ttl(Duration::from_millis(10)),
+    )));
+    let eviction = OperationProcessor::spawn_eviction(&processor, Duration::from_millis(5));
+    processor
+        .lock()
+        .await
+        .submit_operation(tool_call("op1", "done"))
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    {
+        let processor = processor.lock().await;
+        assert!(processor.peek_completed().is_empty());
+        assert!(processor.is_expired("op1"));
+    }
+
+    drop(processor);
+    tokio::time::timeout(Duration::from_secs(1), eviction)
+        .await
+        .expect("eviction stops once the processor is dropped")
+        .unwrap();
+}
@@ -0,0 +1,39 @@
+//cargo test --test test_task_counts --features "client server"
+use std::time::Duration;
+
+use rmcp::{
+    ServerHandler, ServiceExt,
+    model::ClientRequest,
+    service::{TaskKind, task_counts},
+};
+
+#[derive(Clone, Default)]
+struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_task_counts_follow_sessions() -> anyhow::Result<()> {
+    assert_eq!(task_counts().total(), 0);
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::join!(Server.serve(server_transport), ().serve(client_transport));
+    let (server, client) = (server?, client?);
+    assert_eq!(task_counts().get(TaskKind::ServeLoop), 2);
+
+    client
+        .send_request(ClientRequest::PingRequest(Default::default()))
+        .await?;
+    client.cancel().await?;
+    server.waiting().await?;
+
+    // the last writes and handlers may still be winding down
+    for _ in 0..100 {
+        if task_counts().total() == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(task_counts(), Default::default());
+    Ok(())
+}
@@ -0,0 +1,251 @@
+//cargo test --test test_task_progress --features "server client macros"
+use std::{sync::Arc, time::Duration};
+
+use rmcp::{
+    ClientHandler, ErrorData as McpError, Peer, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    handler::server::{router::tool::ToolRouter, tool::Extension},
+    model::{
+        CallToolRequestParams, ClientRequest, ClientResult, Content, CreateMessageRequestParams,
+        CreateMessageResult, GetTaskResultParams, ProgressNotificationParam, ProgressToken,
+        Request, Role, SamplingMessage, ServerRequest, ServerResult, TaskStatus,
+    },
+    service::{NotificationContext, PeerRequestOptions, RequestContext},
+    task_handler,
+    task_manager::{OperationProcessor, TaskProgress},
+    tool, tool_handler, tool_router,
+};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+#[tokio::test]
+async fn test_progress_aggregates_children() {
+    let progress = TaskProgress::new("deploy");
+    let build = progress.child("build");
+    let upload = progress.child("upload");
+    assert_eq!(progress.fraction(), 0.0);
+
+    build.report(1.0, Some(4.0));
+    assert_eq!(progress.fraction(), 0.125);
+    progress.track("test", async {}).await;
+    assert_eq!(progress.fraction(), (0.25 + 0.0 + 1.0) / 3.0);
+
+    let token = ProgressToken(rmcp::model::NumberOrString::Number(7));
+    upload.follow_token(token.clone());
+    assert!(progress.on_progress(&ProgressNotificationParam {
+        progress_token: token,
+        progress: 3.0,
+        total: Some(4.0),
+        message: Some("uploading".into()),
+    }));
+    assert!(!progress.on_progress(&ProgressNotificationParam {
+        progress_token: ProgressToken(rmcp::model::NumberOrString::Number(8)),
+        progress: 1.0,
+        total: None,
+        message: None,
+    }));
+
+    let tree = progress.snapshot();
+    assert_eq!(tree.name, "deploy");
+    assert!(!tree.completed);
+    let names = tree
+        .children
+        .iter()
+        .map(|child| child.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["build", "upload", "test"]);
+    assert_eq!(tree.children[1].fraction, 0.75);
+    assert_eq!(tree.children[1].message.as_deref(), Some("uploading"));
+    assert!(tree.children[2].completed);
+
+    progress.complete();
+    assert_eq!(progress.fraction(), 1.0);
+}
+
+#[derive(Clone)]
+pub struct Writer {
+    tool_router: ToolRouter<Self>,
+    processor: Arc<Mutex<OperationProcessor>>,
+}
+
+#[tool_router]
+impl Writer {
+    /// Draft a document, asking the client's model for help
+    #[tool(task)]
+    async fn draft(
+        &self,
+        Extension(progress): Extension<TaskProgress>,
+        peer: Peer<RoleServer>,
+    ) -> Result<String, McpError> {
+        let outline = progress.child("outline");
+        outline.report(1.0, Some(2.0));
+
+        let request =
+            ServerRequest::CreateMessageRequest(Request::new(CreateMessageRequestParams {
+                meta: None,
+                task: None,
+                messages: vec![SamplingMessage {
+                    role: Role::User,
+                    content: Content::text("Write the introduction").into(),
+                }],
+                model_preferences: None,
+                system_prompt: None,
+                include_context: None,
+                temperature: None,
+                max_tokens: 100,
+                stop_sequences: None,
+                metadata: None,
+            }));
+        let handle = peer
+            .send_cancellable_request(request, PeerRequestOptions::no_options())
+            .await
+            .map_err(|error| McpError::internal_error(error.to_string(), None))?;
+        let response = progress
+            .track_request("sampling", handle)
+            .await
+            .map_err(|error| McpError::internal_error(error.to_string(), None))?;
+        let ClientResult::CreateMessageResult(result) = response else {
+            return Err(McpError::internal_error("unexpected response", None));
+        };
+
+        outline.complete();
+        Ok(result.message.content.as_text().unwrap().text.clone())
+    }
+}
+
+#[tool_handler]
+#[task_handler]
+impl ServerHandler for Writer {}
+
+#[derive(Clone, Default)]
+pub struct Author {
+    progress: Arc<Mutex<Vec<ProgressNotificationParam>>>,
+}
+
+impl ClientHandler for Author {
+    async fn create_message(
+        &self,
+        _params: CreateMessageRequestParams,
+        context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, McpError> {
+        let progress_token = context.meta.get_progress_token().unwrap();
+        context
+            .peer
+            .notify_progress(ProgressNotificationParam {
+                progress_token,
+                progress: 1.0,
+                total: Some(2.0),
+                message: Some("thinking".into()),
+            })
+            .await
+            .unwrap();
+        // let the progress reach the server before the answer
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(CreateMessageResult {
+            model: "test-model".into(),
+            stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.into()),
+            message: SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text("Once upon a time").into(),
+            },
+        })
+    }
+
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.progress.lock().await.push(params);
+    }
+}
+
+#[tokio::test]
+async fn test_task_progress_follows_nested_requests() -> anyhow::Result<()> {
+    let processor = Arc::new(Mutex::new(OperationProcessor::new()));
+    let server = Writer {
+        tool_router: Writer::tool_router(),
+        processor: processor.clone(),
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let author = Author::default();
+    let client = author.clone().serve(client_transport).await?;
+
+    let params = CallToolRequestParams {
+        meta: None,
+        name: "draft".into(),
+        arguments: None,
+        task: Some(json!({}).as_object().unwrap().clone()),
+    };
+    let handle = client
+        .send_cancellable_request(
+            ClientRequest::CallToolRequest(Request::new(params)),
+            PeerRequestOptions::no_options(),
+        )
+        .await?;
+    let progress_token = handle.progress_token.clone();
+    let ServerResult::CreateTaskResult(created) = handle.await_response().await? else {
+        panic!("expected a task");
+    };
+    let task_id = created.task.task_id;
+
+    let status =
+        OperationProcessor::wait_for_completion(&processor, &task_id, Duration::from_secs(5)).await;
+    assert_eq!(status, Some(TaskStatus::Completed));
+    let tree = processor.lock().await.task_progress(&task_id).unwrap();
+    assert_eq!(tree.name, "draft");
+    assert!(tree.completed);
+    let [outline, sampling] = &tree.children[..] else {
+        panic!("expected two children, got {tree:?}");
+    };
+    assert_eq!((outline.name.as_str(), outline.progress), ("outline", 1.0));
+    assert_eq!(sampling.name, "sampling");
+    assert!(sampling.completed);
+    assert_eq!((sampling.progress, sampling.total), (1.0, Some(2.0)));
+    assert_eq!(sampling.message.as_deref(), Some("thinking"));
+
+    let result = client
+        .send_request(ClientRequest::GetTaskResultRequest(Request::new(
+            GetTaskResultParams {
+                meta: None,
+                task_id,
+            },
+        )))
+        .await?;
+    let ServerResult::TaskResult(result) = result else {
+        panic!("expected a task result, got {result:?}");
+    };
+    assert_eq!(result.value["content"][0]["text"], "Once upon a time");
+
+    // the caller only sees the progress of the whole task, always increasing
+    let mut sent = Vec::new();
+    for _ in 0..50 {
+        sent = author
+            .progress
+            .lock()
+            .await
+            .iter()
+            .filter(|params| params.progress_token == progress_token)
+            .map(|params| (params.progress, params.total))
+            .collect::<Vec<_>>();
+        if sent.len() == 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(
+        sent,
+        [
+            (50.0, Some(100.0)),
+            (75.0, Some(100.0)),
+            (100.0, Some(100.0))
+        ]
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
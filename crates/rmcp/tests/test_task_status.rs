@@ -0,0 +1,131 @@
+//cargo test --test test_task_status --features "server client"
+use rmcp::{
+    ErrorData, RoleServer, ServerHandler, ServiceExt,
+    handler::client::router::ClientRouter,
+    model::{
+        CallToolRequestParams, CallToolResult, ServerCapabilities, ServerInfo, ServerNotification,
+        Task, TaskStatus,
+    },
+    service::RequestContext,
+};
+
+/// Reports each step of its only tool as a task status change.
+#[derive(Debug, Clone)]
+struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut task = Task {
+            task_id: "task-1".into(),
+            created_at: "2025-11-25T00:00:00Z".into(),
+            ..Default::default()
+        };
+        for (status, message) in [
+            (TaskStatus::InputRequired, Some("waiting for approval")),
+            (TaskStatus::Working, None),
+            (TaskStatus::Completed, Some("done")),
+        ] {
+            task.transition(status, message.map(Into::into))
+                .map_err(|error| ErrorData::internal_error(error.to_string(), None))?;
+            context
+                .peer
+                .notify_task_status(task.clone())
+                .await
+                .map_err(|error| ErrorData::internal_error(error.to_string(), None))?;
+        }
+        let error = task
+            .transition(TaskStatus::Working, None)
+            .expect_err("completed is final");
+        Ok(CallToolResult::structured(serde_json::json!({
+            "error": error.to_string()
+        })))
+    }
+}
+
+#[tokio::test]
+async fn test_client_receives_task_status() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Server.serve(server_transport));
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let client = ClientRouter::default()
+        .on_task_status(move |task, _context| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(task);
+            }
+        })
+        .serve(client_transport)
+        .await?;
+    let _server = server.await??;
+
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "work".into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+    assert_eq!(
+        result.structured_content.unwrap()["error"],
+        "task can't move from Completed to Working"
+    );
+
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        received.push(rx.recv().await.expect("task status"));
+    }
+    // notifications are handled concurrently, so they may arrive in any order
+    received.sort_by_key(|task| match task.status {
+        TaskStatus::InputRequired => 0,
+        TaskStatus::Working => 1,
+        _ => 2,
+    });
+    assert_eq!(
+        received
+            .iter()
+            .map(|task| (task.status.clone(), task.status_message.as_deref()))
+            .collect::<Vec<_>>(),
+        [
+            (TaskStatus::InputRequired, Some("waiting for approval")),
+            (TaskStatus::Working, None),
+            (TaskStatus::Completed, Some("done")),
+        ]
+    );
+    assert!(received.iter().all(|task| task.task_id == "task-1"));
+    assert!(received.iter().all(|task| task.last_updated_at.is_some()));
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[test]
+fn test_task_status_notification_serde() {
+    let notification: ServerNotification = serde_json::from_value(serde_json::json!({
+        "method": "notifications/tasks/status",
+        "params": {
+            "taskId": "task-1",
+            "status": "input_required",
+            "statusMessage": "waiting for approval",
+            "createdAt": "2025-11-25T00:00:00Z",
+            "pollInterval": 500
+        }
+    }))
+    .unwrap();
+    let ServerNotification::TaskStatusNotification(notification) = &notification else {
+        panic!("expected a task status notification, got {notification:?}");
+    };
+    assert_eq!(notification.params.status, TaskStatus::InputRequired);
+    assert_eq!(notification.params.poll_interval, Some(500));
+}
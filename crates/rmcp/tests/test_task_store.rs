@@ -0,0 +1,266 @@
+//cargo test --test test_task_store --features "server client macros task-store-sled"
+use std::{sync::Arc, time::Duration};
+
+use rmcp::{
+    ErrorData as McpError, ServerHandler, ServiceExt,
+    handler::server::router::tool::ToolRouter,
+    model::{
+        CallToolRequestParams, CallToolResult, ClientRequest, Content, GetTaskResultParams,
+        Request, ServerResult, TaskStatus,
+    },
+    service::{RoleClient, RunningService},
+    task_handler,
+    task_manager::{
+        DurableTaskStore, InMemoryTaskStore, OperationDescriptor, OperationFuture,
+        OperationMessage, OperationProcessor, OperationResultTransport, TaskOwner, TaskStore,
+        ToolCallTaskResult,
+    },
+    tool, tool_handler, tool_router,
+};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+fn store_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("rmcp-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+fn finishing(task_id: &str, text: &str) -> OperationFuture {
+    let result = ToolCallTaskResult::new(
+        task_id,
+        Ok(CallToolResult::success(vec![Content::text(text)])),
+    );
+    Box::pin(async move { Ok(Box::new(result) as Box<dyn OperationResultTransport>) })
+}
+
+fn never_finishing() -> OperationFuture {
+    Box::pin(std::future::pending())
+}
+
+#[test]
+fn test_claim_respects_leases() -> anyhow::Result<()> {
+    let path = store_path("claim");
+    let store = DurableTaskStore::open(&path)?;
+    let mut processor = OperationProcessor::new()
+        .with_store(Arc::new(store.clone()))
+        .with_ownership("a", Duration::from_secs(60));
+    let runtime = tokio::runtime::Runtime::new()?;
+    let _guard = runtime.enter();
+    processor.submit_operation(OperationMessage::new(
+        OperationDescriptor::new("task-1", "build").with_resumable(),
+        never_finishing(),
+    ))?;
+
+    let record = store.load("task-1")?.expect("task is stored");
+    assert_eq!(record.task.status, TaskStatus::Working);
+    assert!(record.resumable);
+    let lease = record.owner.expect("task is owned").lease_expires_at;
+
+    let other = TaskOwner {
+        instance_id: "b".into(),
+        lease_expires_at: unix_millis() + 60_000,
+    };
+    assert!(store.claim("task-1", &other, unix_millis())?.is_none());
+    let claimed = store
+        .claim("task-1", &other, lease)?
+        .expect("lease ran out");
+    assert_eq!(claimed.owner, Some(other));
+    assert!(
+        store
+            .claim("unknown", &claimed.owner.unwrap(), lease)?
+            .is_none()
+    );
+
+    drop(processor);
+    drop(store);
+    std::fs::remove_dir_all(path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resumable_tasks_survive_restart() -> anyhow::Result<()> {
+    let path = store_path("restart");
+    {
+        let mut processor = OperationProcessor::new()
+            .with_store(Arc::new(DurableTaskStore::open(&path)?))
+            .with_ownership("crashed", Duration::from_millis(10));
+        processor.submit_operation(OperationMessage::new(
+            OperationDescriptor::new("resumable", "build").with_resumable(),
+            never_finishing(),
+        ))?;
+        processor.submit_operation(OperationMessage::new(
+            OperationDescriptor::new("one-shot", "lint"),
+            never_finishing(),
+        ))?;
+        processor.submit_operation(OperationMessage::new(
+            OperationDescriptor::new("unknown", "gone").with_resumable(),
+            never_finishing(),
+        ))?;
+        // the instance dies without finishing its tasks
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let store = Arc::new(DurableTaskStore::open(&path)?);
+    let processor = Arc::new(Mutex::new(
+        OperationProcessor::new()
+            .with_store(store.clone())
+            .with_ownership("restarted", Duration::from_secs(60)),
+    ));
+    let resumed = processor.lock().await.resume_tasks(|record| {
+        assert_eq!(record.owner.as_ref().unwrap().instance_id, "restarted");
+        (record.name == "build").then(|| finishing(&record.task.task_id, "built"))
+    })?;
+    assert_eq!(resumed, 1);
+
+    let status =
+        OperationProcessor::wait_for_completion(&processor, "resumable", Duration::from_secs(5))
+            .await;
+    assert_eq!(status, Some(TaskStatus::Completed));
+    let record = store.load("resumable")?.unwrap();
+    assert_eq!(record.task.status, TaskStatus::Completed);
+    assert_eq!(record.owner, None);
+    assert_eq!(record.result.unwrap()["content"][0]["text"], "built");
+
+    // not resumable, left alone
+    assert_eq!(
+        store.load("one-shot")?.unwrap().task.status,
+        TaskStatus::Working
+    );
+    // resumable, but nothing knows how to run it anymore
+    let record = store.load("unknown")?.unwrap();
+    assert_eq!(record.task.status, TaskStatus::Failed);
+    assert!(record.task.status_message.is_some());
+
+    // taking the result forgets the task
+    assert!(
+        processor
+            .lock()
+            .await
+            .take_completed_result("resumable")
+            .is_some()
+    );
+    assert!(store.load("resumable")?.is_none());
+
+    drop(processor);
+    drop(store);
+    std::fs::remove_dir_all(path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_lost_lease_stops_task() -> anyhow::Result<()> {
+    let store = Arc::new(InMemoryTaskStore::new());
+    let mut processor = OperationProcessor::new()
+        .with_store(store.clone())
+        .with_ownership("a", Duration::from_millis(30));
+    processor.submit_operation(OperationMessage::new(
+        OperationDescriptor::new("task-1", "build"),
+        never_finishing(),
+    ))?;
+
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    let other = TaskOwner {
+        instance_id: "b".into(),
+        lease_expires_at: unix_millis() + 60_000,
+    };
+    assert!(store.claim("task-1", &other, unix_millis())?.is_some());
+
+    processor.receive_completed_results();
+    assert!(processor.list_running().is_empty());
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct Builder {
+    tool_router: ToolRouter<Self>,
+    processor: Arc<Mutex<OperationProcessor>>,
+}
+
+impl Builder {
+    fn new(processor: OperationProcessor) -> Self {
+        let processor = Arc::new(Mutex::new(processor));
+        OperationProcessor::spawn_eviction(&processor, Duration::from_millis(10));
+        Self {
+            tool_router: Self::tool_router(),
+            processor,
+        }
+    }
+}
+
+#[tool_router]
+impl Builder {
+    /// Build the project
+    #[tool(task(resumable))]
+    async fn build(&self) -> String {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        "built".into()
+    }
+}
+
+#[tool_handler]
+#[task_handler]
+impl ServerHandler for Builder {}
+
+async fn connect(server: Builder) -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+#[tokio::test]
+async fn test_instances_share_task_results() -> anyhow::Result<()> {
+    let store: Arc<dyn TaskStore> = Arc::new(InMemoryTaskStore::new());
+    let a = connect(Builder::new(
+        OperationProcessor::new().with_store(store.clone()),
+    ))
+    .await?;
+    let b = connect(Builder::new(
+        OperationProcessor::new().with_store(store.clone()),
+    ))
+    .await?;
+
+    let params = CallToolRequestParams {
+        meta: None,
+        name: "build".into(),
+        arguments: None,
+        task: Some(json!({}).as_object().unwrap().clone()),
+    };
+    let ServerResult::CreateTaskResult(created) = a
+        .send_request(ClientRequest::CallToolRequest(Request::new(params)))
+        .await?
+    else {
+        panic!("expected a task");
+    };
+    let record = store.load(&created.task.task_id)?.unwrap();
+    assert!(record.resumable);
+
+    // the other instance waits for the task to finish, then answers for it
+    let result = b
+        .send_request(ClientRequest::GetTaskResultRequest(Request::new(
+            GetTaskResultParams {
+                meta: None,
+                task_id: created.task.task_id,
+            },
+        )))
+        .await?;
+    let ServerResult::TaskResult(result) = result else {
+        panic!("expected a task result, got {result:?}");
+    };
+    assert_eq!(result.value["content"][0]["text"], "built");
+
+    a.cancel().await?;
+    b.cancel().await?;
+    Ok(())
+}
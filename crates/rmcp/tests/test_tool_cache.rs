@@ -0,0 +1,221 @@
+//cargo test --test test_tool_cache --features "server client macros"
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rmcp::{
+    ErrorData, RoleClient, ServerHandler, ServiceExt,
+    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    model::{CallToolRequestParams, CallToolResult, Content, object},
+    service::{CacheBackend, CacheLayer, MemoryCache, RunningService, canonical_json},
+    tool, tool_handler, tool_router,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Lookup {
+    pub city: String,
+    #[serde(default)]
+    pub units: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Weather {
+    calls: Arc<AtomicUsize>,
+    tool_router: ToolRouter<Self>,
+}
+
+impl Weather {
+    fn new(calls: Arc<AtomicUsize>) -> Self {
+        Self {
+            calls,
+            tool_router: Self::tool_router(),
+        }
+    }
+}
+
+#[tool_router]
+impl Weather {
+    #[tool(annotations(read_only_hint = true))]
+    async fn forecast(&self, Parameters(lookup): Parameters<Lookup>) -> String {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        format!("{} {:?} #{call}", lookup.city, lookup.units)
+    }
+
+    #[tool]
+    async fn report(&self, Parameters(lookup): Parameters<Lookup>) -> String {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        format!("{} #{call}", lookup.city)
+    }
+
+    #[tool(annotations(idempotent_hint = true))]
+    async fn radar(&self) -> Result<CallToolResult, ErrorData> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(CallToolResult::error(vec![Content::text("radar offline")]))
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Weather {}
+
+async fn connect<S: rmcp::Service<rmcp::RoleServer>>(
+    service: S,
+) -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        service.serve(server_transport).await?.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+async fn call(
+    client: &RunningService<RoleClient, ()>,
+    tool: &'static str,
+    arguments: serde_json::Value,
+) -> anyhow::Result<String> {
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: tool.into(),
+            arguments: Some(object(arguments)),
+            task: None,
+        })
+        .await?;
+    Ok(result.text().unwrap().to_owned())
+}
+
+#[tokio::test]
+async fn test_caches_annotated_tools() -> anyhow::Result<()> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let cache = MemoryCache::default();
+    let weather = Weather::new(calls.clone());
+    let tools = weather.tool_router.list_all();
+    let client = connect(
+        CacheLayer::new(weather, cache.clone(), Duration::from_secs(60)).with_tools(&tools),
+    )
+    .await?;
+
+    let first = call(
+        &client,
+        "forecast",
+        json!({"city": "Oslo", "units": "metric"}),
+    )
+    .await?;
+    let again = call(
+        &client,
+        "forecast",
+        json!({"units": "metric", "city": "Oslo"}),
+    )
+    .await?;
+    assert_eq!(first, again);
+    let other = call(&client, "forecast", json!({"city": "Bergen"})).await?;
+    assert_ne!(first, other);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(cache.len(), 2);
+
+    // not annotated
+    call(&client, "report", json!({"city": "Oslo"})).await?;
+    call(&client, "report", json!({"city": "Oslo"})).await?;
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+    // errors aren't cached
+    call(&client, "radar", json!({})).await?;
+    call(&client, "radar", json!({})).await?;
+    assert_eq!(calls.load(Ordering::SeqCst), 6);
+    assert_eq!(cache.len(), 2);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_learns_from_listing_and_expires() -> anyhow::Result<()> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let client = connect(
+        CacheLayer::new(
+            Weather::new(calls.clone()),
+            MemoryCache::default(),
+            Duration::from_millis(100),
+        )
+        .with_tool("report", Duration::from_secs(60)),
+    )
+    .await?;
+
+    // not known to be cacheable before the listing
+    call(&client, "forecast", json!({"city": "Oslo"})).await?;
+    call(&client, "forecast", json!({"city": "Oslo"})).await?;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    client.list_all_tools().await?;
+    call(&client, "forecast", json!({"city": "Oslo"})).await?;
+    call(&client, "forecast", json!({"city": "Oslo"})).await?;
+    call(&client, "report", json!({"city": "Oslo"})).await?;
+    call(&client, "report", json!({"city": "Oslo"})).await?;
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    call(&client, "forecast", json!({"city": "Oslo"})).await?;
+    call(&client, "report", json!({"city": "Oslo"})).await?;
+    assert_eq!(calls.load(Ordering::SeqCst), 5);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scope() -> anyhow::Result<()> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let cache = MemoryCache::default();
+    let scoped = |scope: Option<&'static str>| {
+        CacheLayer::new(
+            Weather::new(calls.clone()),
+            cache.clone(),
+            Duration::from_secs(60),
+        )
+        .with_tool("forecast", Duration::from_secs(60))
+        .with_scope(move |_| scope.map(str::to_owned))
+    };
+
+    let alice = connect(scoped(Some("alice"))).await?;
+    let bob = connect(scoped(Some("bob"))).await?;
+    let anonymous = connect(scoped(None)).await?;
+    for client in [&alice, &alice, &bob, &bob, &anonymous, &anonymous] {
+        call(client, "forecast", json!({"city": "Oslo"})).await?;
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+    assert_eq!(cache.len(), 2);
+
+    for client in [alice, bob, anonymous] {
+        client.cancel().await?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_memory_cache_evicts_least_recently_used() {
+    let cache = MemoryCache::new(2);
+    let ttl = Duration::from_secs(60);
+    let result = |text: &str| CallToolResult::success(vec![Content::text(text)]);
+    cache.set("a".into(), result("a"), ttl).await;
+    cache.set("b".into(), result("b"), ttl).await;
+    assert!(cache.get("a").await.is_some());
+    cache.set("c".into(), result("c"), ttl).await;
+    assert!(cache.get("a").await.is_some());
+    assert!(cache.get("b").await.is_none());
+    assert!(cache.get("c").await.is_some());
+}
+
+#[test]
+fn test_canonical_json() {
+    assert_eq!(
+        canonical_json(&json!({"b": [{"d": 1, "c": 2}], "a": null})),
+        r#"{"a":null,"b":[{"c":2,"d":1}]}"#
+    );
+}
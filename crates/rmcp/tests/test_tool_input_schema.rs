@@ -0,0 +1,164 @@
+//cargo test --test test_tool_input_schema --features "server macros"
+use rmcp::{
+    ServerHandler,
+    handler::server::{
+        router::tool::ToolRouter,
+        tool::{check_input_schema, schema_for_type},
+        wrapper::Parameters,
+    },
+    model::{JsonObject, object},
+    tool, tool_handler, tool_router,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+/// No `JsonSchema` here, the schema is written by hand.
+#[derive(Debug, Deserialize)]
+pub struct SearchRequest {
+    pub query: String,
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub filters: Vec<Filter>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Filter {
+    Language { code: String },
+    Since { date: String },
+}
+
+fn search_schema() -> JsonObject {
+    object(json!({
+        "type": "object",
+        "properties": {
+            "query": { "type": "string", "minLength": 1 },
+            "limit": { "type": "integer", "minimum": 1, "maximum": 100 },
+            "filters": {
+                "type": "array",
+                "items": { "$ref": "#/$defs/filter" }
+            }
+        },
+        "required": ["query"],
+        "$defs": {
+            "filter": {
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": {
+                            "kind": { "const": "language" },
+                            "code": { "type": "string", "examples": ["en"] }
+                        },
+                        "required": ["kind", "code"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "kind": { "const": "since" },
+                            "date": { "type": "string", "format": "date" }
+                        },
+                        "required": ["kind", "date"]
+                    }
+                ]
+            }
+        }
+    }))
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchServer {
+    tool_router: ToolRouter<Self>,
+}
+
+impl Default for SearchServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[tool_router]
+impl SearchServer {
+    pub fn new() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    /// Search the index
+    #[tool(input_schema = search_schema)]
+    async fn search(&self, Parameters(request): Parameters<SearchRequest>) -> String {
+        format!(
+            "{} {:?} {:?}",
+            request.query, request.limit, request.filters
+        )
+    }
+
+    /// Search with an inline schema expression
+    #[tool(input_schema = std::sync::Arc::new(search_schema()))]
+    async fn search_inline(&self, Parameters(request): Parameters<SearchRequest>) -> String {
+        request.query
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for SearchServer {}
+
+#[derive(Debug, Deserialize)]
+pub struct Renamed {
+    #[serde(rename = "searchQuery")]
+    pub query: String,
+}
+
+#[test]
+fn test_handwritten_schema_is_used() {
+    let tools = SearchServer::new().tool_router.list_all();
+    let tool = tools.iter().find(|tool| tool.name == "search").unwrap();
+    assert_eq!(*tool.input_schema, search_schema());
+    assert_eq!(tool.description.as_deref(), Some("Search the index"));
+    let tool = tools
+        .iter()
+        .find(|tool| tool.name == "search_inline")
+        .unwrap();
+    assert_eq!(*tool.input_schema, search_schema());
+}
+
+#[test]
+fn test_check_input_schema() {
+    check_input_schema::<SearchRequest>(&search_schema()).unwrap();
+    check_input_schema::<Parameters<SearchRequest>>(&search_schema()).unwrap();
+
+    // the schema names a property the type doesn't know by that name
+    let error = check_input_schema::<Renamed>(&search_schema()).unwrap_err();
+    assert!(error.error.contains("searchQuery"), "{error}");
+    assert_eq!(error.instance, json!({ "query": "a" }));
+
+    // generated schemas pass trivially
+    check_input_schema::<Vec<u32>>(&schema_for_type::<Vec<u32>>()).unwrap();
+}
+
+fn mismatched_schema() -> JsonObject {
+    object(json!({
+        "type": "object",
+        "properties": { "query": { "type": "integer" } },
+        "required": ["query"]
+    }))
+}
+
+#[derive(Debug, Clone)]
+pub struct MismatchedServer;
+
+#[tool_router]
+impl MismatchedServer {
+    #[tool(input_schema = mismatched_schema)]
+    async fn search(&self, Parameters(request): Parameters<SearchRequest>) -> String {
+        request.query
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "invalid input schema for tool `search`")]
+fn test_mismatched_schema_panics_in_debug() {
+    let _ = MismatchedServer::tool_router();
+}
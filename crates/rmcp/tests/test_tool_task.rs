@@ -0,0 +1,188 @@
+//cargo test --test test_tool_task --features "server client macros"
+use std::{sync::Arc, time::Duration};
+
+use rmcp::{
+    ErrorData as McpError, ServerHandler, ServiceExt,
+    handler::server::router::tool::ToolRouter,
+    model::{
+        CallToolRequestParams, ClientRequest, ErrorCode, GetTaskResultParams, Request,
+        ServerResult, TaskStatus, TaskSupport,
+    },
+    service::{RoleClient, RunningService, ServiceError},
+    task_handler,
+    task_manager::OperationProcessor,
+    tool, tool_handler, tool_router,
+};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+#[derive(Clone)]
+pub struct Builder {
+    tool_router: ToolRouter<Self>,
+    processor: Arc<Mutex<OperationProcessor>>,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+            processor: Arc::new(Mutex::new(OperationProcessor::new())),
+        }
+    }
+}
+
+#[tool_router]
+impl Builder {
+    /// Build the project
+    #[tool(task)]
+    async fn build(&self) -> String {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        "built".into()
+    }
+
+    /// Lint the project, usually quick
+    #[tool(task(threshold_ms = 2000))]
+    async fn lint(&self) -> String {
+        "clean".into()
+    }
+
+    /// Deploy the project
+    #[tool(task(required))]
+    async fn deploy(&self) -> String {
+        "deployed".into()
+    }
+
+    /// Print the version
+    #[tool]
+    async fn version(&self) -> String {
+        "1.0.0".into()
+    }
+}
+
+#[tool_handler]
+#[task_handler]
+impl ServerHandler for Builder {}
+
+async fn call(
+    client: &RunningService<RoleClient, ()>,
+    name: &'static str,
+    as_task: bool,
+) -> Result<ServerResult, ServiceError> {
+    let params = CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments: None,
+        task: as_task.then(|| json!({}).as_object().unwrap().clone()),
+    };
+    client
+        .send_request(ClientRequest::CallToolRequest(Request::new(params)))
+        .await
+}
+
+async fn connect() -> anyhow::Result<RunningService<RoleClient, ()>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Builder::default().serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    Ok(().serve(client_transport).await?)
+}
+
+#[test]
+fn test_task_support_metadata() {
+    let tools = Builder::tool_router();
+    let task_support = |name| tools.get(name).unwrap().task_support();
+    assert_eq!(task_support("build"), TaskSupport::Optional);
+    assert_eq!(task_support("deploy"), TaskSupport::Required);
+    assert_eq!(task_support("version"), TaskSupport::Forbidden);
+
+    let lint = tools.get("lint").unwrap();
+    let execution = lint.execution.as_ref().unwrap();
+    assert_eq!(execution.threshold, Some(Duration::from_millis(2000)));
+    // the threshold stays on the server
+    assert_eq!(
+        serde_json::to_value(lint).unwrap()["execution"],
+        json!({ "taskSupport": "optional" })
+    );
+    assert!(
+        serde_json::to_value(tools.get("version").unwrap())
+            .unwrap()
+            .get("execution")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_task_tool_runs_as_task_when_asked() -> anyhow::Result<()> {
+    let client = connect().await?;
+
+    let ServerResult::CreateTaskResult(created) = call(&client, "build", true).await? else {
+        panic!("expected a task");
+    };
+    assert_eq!(created.task.status, TaskStatus::Working);
+    let result = client
+        .send_request(ClientRequest::GetTaskResultRequest(Request::new(
+            GetTaskResultParams {
+                meta: None,
+                task_id: created.task.task_id,
+            },
+        )))
+        .await?;
+    let ServerResult::TaskResult(result) = result else {
+        panic!("expected a task result, got {result:?}");
+    };
+    assert_eq!(result.value["content"][0]["text"], "built");
+
+    let ServerResult::CallToolResult(result) = call(&client, "build", false).await? else {
+        panic!("expected a direct result");
+    };
+    assert_eq!(result.content[0].as_text().unwrap().text, "built");
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_task_tool_within_threshold_is_reported_done() -> anyhow::Result<()> {
+    let client = connect().await?;
+
+    let response = call(&client, "lint", true).await?;
+    let ServerResult::CreateTaskResult(created) = response else {
+        panic!("expected a task, got {response:?}");
+    };
+    assert_eq!(created.task.status, TaskStatus::Completed);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_plain_tool_ignores_task_request() -> anyhow::Result<()> {
+    let client = connect().await?;
+
+    let ServerResult::CallToolResult(result) = call(&client, "version", true).await? else {
+        panic!("expected a direct result");
+    };
+    assert_eq!(result.content[0].as_text().unwrap().text, "1.0.0");
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_required_task_tool_rejects_direct_call() -> anyhow::Result<()> {
+    let client = connect().await?;
+
+    let Err(ServiceError::McpError(error)) = call(&client, "deploy", false).await else {
+        panic!("expected an error");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_REQUEST);
+    assert!(matches!(
+        call(&client, "deploy", true).await?,
+        ServerResult::CreateTaskResult(_)
+    ));
+
+    client.cancel().await?;
+    Ok(())
+}
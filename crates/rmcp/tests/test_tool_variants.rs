@@ -0,0 +1,229 @@
+//cargo test --test test_tool_variants --features "server client macros"
+use rmcp::{
+    ErrorData, RoleClient, ServerHandler, ServiceExt,
+    handler::server::{
+        router::tool::ToolRouter,
+        tool::{Extension, ToolCallContext},
+    },
+    model::{
+        CallToolRequestParams, CallToolResult, ClientInfo, Implementation, ListToolsResult,
+        PaginatedRequestParams,
+    },
+    service::{
+        RequestContext, RoleServer, RunningService, SelectedToolVariant, SessionHashSelector,
+        ToolVariant, ToolVariantSelector, ToolVariantService,
+    },
+    tool, tool_handler, tool_router,
+};
+
+#[derive(Debug, Clone)]
+pub struct Search {
+    tool_router: ToolRouter<Self>,
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+}
+
+#[tool_router]
+impl Search {
+    /// Search by keyword
+    #[tool]
+    async fn search(&self, Extension(variant): Extension<SelectedToolVariant>) -> String {
+        format!("keyword:{}", variant.label)
+    }
+
+    /// Search by meaning
+    #[tool]
+    async fn semantic_search(&self, Extension(variant): Extension<SelectedToolVariant>) -> String {
+        format!("semantic:{}", variant.label)
+    }
+
+    /// Current time
+    #[tool]
+    async fn now(&self) -> String {
+        "noon".to_owned()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Search {}
+
+/// Lists the tools of [`Search`] one per page.
+#[derive(Debug, Clone, Default)]
+pub struct PagedSearch(Search);
+
+impl ServerHandler for PagedSearch {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tcc = ToolCallContext::new(&self.0, request, context);
+        self.0.tool_router.call(tcc).await
+    }
+
+    async fn list_tools(
+        &self,
+        request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        let tools = self.0.tool_router.list_all();
+        let page: usize = request
+            .and_then(|request| request.cursor)
+            .map_or(0, |cursor| cursor.parse().unwrap());
+        Ok(ListToolsResult {
+            meta: None,
+            next_cursor: (page + 1 < tools.len()).then(|| (page + 1).to_string()),
+            tools: vec![tools[page].clone()],
+        })
+    }
+}
+
+fn client_name(context: &RequestContext<RoleServer>) -> Option<String> {
+    Some(context.peer.peer_info()?.client_info.name.clone())
+}
+
+fn variants() -> [ToolVariant; 3] {
+    [
+        ToolVariant::new("control"),
+        ToolVariant::new("terse").description("Search."),
+        ToolVariant::new("semantic").implementation("semantic_search"),
+    ]
+}
+
+async fn connect(
+    selector: impl ToolVariantSelector,
+    name: &str,
+) -> anyhow::Result<RunningService<RoleClient, ClientInfo>> {
+    connect_to(Search::default(), selector, name).await
+}
+
+async fn connect_to(
+    server: impl ServerHandler,
+    selector: impl ToolVariantSelector,
+    name: &str,
+) -> anyhow::Result<RunningService<RoleClient, ClientInfo>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let service = ToolVariantService::new(server, selector).with_variants("search", variants());
+    tokio::spawn(async move {
+        let server = service.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ClientInfo {
+        client_info: Implementation {
+            name: name.to_owned(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    Ok(client.serve(client_transport).await?)
+}
+
+async fn call_search(client: &RunningService<RoleClient, ClientInfo>) -> anyhow::Result<String> {
+    let result = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "search".into(),
+            arguments: None,
+            task: None,
+        })
+        .await?;
+    Ok(result.text().unwrap().to_owned())
+}
+
+#[tokio::test]
+async fn test_variants_by_client() -> anyhow::Result<()> {
+    let by_name =
+        |_: &str, _: &[ToolVariant], context: &RequestContext<RoleServer>| match client_name(
+            context,
+        )
+        .as_deref()
+        {
+            Some("terse") => 1,
+            Some("semantic") => 2,
+            _ => 0,
+        };
+
+    let client = connect(by_name, "control").await?;
+    let tools = client.list_all_tools().await?;
+    let mut names: Vec<_> = tools.iter().map(|tool| tool.name.as_ref()).collect();
+    names.sort();
+    assert_eq!(names, ["now", "search"]);
+    let search = tools.iter().find(|tool| tool.name == "search").unwrap();
+    assert_eq!(search.description.as_deref(), Some("Search by keyword"));
+    assert_eq!(call_search(&client).await?, "keyword:control");
+    client.cancel().await?;
+
+    let client = connect(by_name, "terse").await?;
+    let tools = client.list_all_tools().await?;
+    let search = tools.iter().find(|tool| tool.name == "search").unwrap();
+    assert_eq!(search.description.as_deref(), Some("Search."));
+    assert_eq!(call_search(&client).await?, "keyword:terse");
+    client.cancel().await?;
+
+    let client = connect(by_name, "semantic").await?;
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools.len(), 2);
+    let search = tools.iter().find(|tool| tool.name == "search").unwrap();
+    assert_eq!(search.description.as_deref(), Some("Search by meaning"));
+    assert_eq!(call_search(&client).await?, "semantic:semantic");
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_implementation_tools_stay_hidden() -> anyhow::Result<()> {
+    let semantic = |_: &str, _: &[ToolVariant], _: &RequestContext<RoleServer>| 2;
+
+    let client = connect(semantic, "direct").await?;
+    let error = client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "semantic_search".into(),
+            arguments: None,
+            task: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("tool not found"), "{error}");
+    client.cancel().await?;
+
+    // the implementation tool is listed on another page than the experiment
+    let client = connect_to(PagedSearch::default(), semantic, "paged").await?;
+    let tools = client.list_all_tools().await?;
+    let mut names: Vec<_> = tools.iter().map(|tool| tool.name.as_ref()).collect();
+    names.sort();
+    assert_eq!(names, ["now", "search"]);
+    let search = tools.iter().find(|tool| tool.name == "search").unwrap();
+    assert_eq!(search.description.as_deref(), Some("Search by meaning"));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_hash_selector() -> anyhow::Result<()> {
+    let mut seen = Vec::new();
+    for name in ["alice", "bob", "carol", "dave", "erin", "frank"] {
+        let client = connect(SessionHashSelector::new(client_name), name).await?;
+        let first = call_search(&client).await?;
+        // stable within a session
+        assert_eq!(call_search(&client).await?, first);
+        client.cancel().await?;
+
+        // and across sessions with the same key
+        let client = connect(SessionHashSelector::new(client_name), name).await?;
+        assert_eq!(call_search(&client).await?, first);
+        client.cancel().await?;
+        seen.push(first);
+    }
+    seen.sort();
+    seen.dedup();
+    assert!(seen.len() > 1, "{seen:?}");
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//cargo test --test test_tool_versioning --features "server client macros"
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::router::tool::ToolRouter,
+    model::{TOOL_DEPRECATION_META_KEY, Tool, ToolDeprecation},
+    tool, tool_handler, tool_router,
+};
+use serde_json::json;
+
+#[derive(Debug, Clone)]
+pub struct Search {
+    tool_router: ToolRouter<Self>,
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+}
+
+#[tool_router]
+impl Search {
+    /// Search by keyword
+    #[tool(
+        version = "1.4.0",
+        deprecated(message = "keyword matching only", replacement = "search_v2")
+    )]
+    async fn search(&self) -> String {
+        String::new()
+    }
+
+    /// Semantic search
+    #[tool(version = "2.0.0")]
+    async fn search_v2(&self) -> String {
+        String::new()
+    }
+
+    #[tool(deprecated)]
+    async fn legacy_lookup(&self) -> String {
+        String::new()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Search {}
+
+fn find(tools: &[Tool], name: &str) -> Tool {
+    tools.iter().find(|tool| tool.name == name).unwrap().clone()
+}
+
+#[test]
+fn test_tool_metadata() {
+    let tools = Search::tool_router().list_all();
+
+    let search = find(&tools, "search");
+    assert_eq!(search.version(), Some("1.4.0"));
+    assert_eq!(
+        search.deprecation(),
+        Some(
+            ToolDeprecation::new()
+                .message("keyword matching only")
+                .replacement("search_v2")
+        )
+    );
+    assert_eq!(
+        serde_json::to_value(&search).unwrap()["_meta"],
+        json!({
+            "rmcp/version": "1.4.0",
+            "rmcp/deprecated": {
+                "message": "keyword matching only",
+                "replacement": "search_v2",
+            },
+        })
+    );
+    assert_eq!(
+        search.deprecation_warning().unwrap(),
+        "tool `search` is deprecated: keyword matching only, use `search_v2` instead"
+    );
+
+    let search_v2 = find(&tools, "search_v2");
+    assert_eq!(search_v2.version(), Some("2.0.0"));
+    assert!(!search_v2.is_deprecated());
+    assert!(search_v2.deprecation_warning().is_none());
+
+    let legacy = find(&tools, "legacy_lookup");
+    assert_eq!(legacy.version(), None);
+    assert_eq!(legacy.deprecation(), Some(ToolDeprecation::default()));
+}
+
+#[test]
+fn test_bare_deprecation_flag() {
+    let mut tool = Tool::new("old", "", rmcp::model::object(json!({"type": "object"})));
+    assert!(!tool.is_deprecated());
+    tool.meta
+        .get_or_insert_with(Default::default)
+        .insert(TOOL_DEPRECATION_META_KEY.to_owned(), json!(true));
+    assert!(tool.is_deprecated());
+    assert_eq!(
+        tool.deprecation_warning().as_deref(),
+        Some("tool `old` is deprecated")
+    );
+}
+
+#[tokio::test]
+async fn test_list_current_tools() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(Search::default().serve(server_transport));
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    assert_eq!(client.list_all_tools().await?.len(), 3);
+    let current = client.list_current_tools().await?;
+    let names: Vec<_> = current.iter().map(|tool| tool.name.as_ref()).collect();
+    assert_eq!(names, ["search_v2"]);
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
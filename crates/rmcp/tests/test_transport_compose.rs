@@ -0,0 +1,151 @@
+//cargo test --test test_transport_compose --features "client transport-compose"
+use std::time::Duration;
+
+use rmcp::{
+    RoleClient,
+    model::{ClientJsonRpcMessage, ServerJsonRpcMessage},
+    service::CloseReason,
+    transport::{
+        Transport,
+        compose::{Faults, FaultyTransport, TransportExt},
+    },
+};
+use tokio::{sync::mpsc, time::Instant};
+
+/// A client transport over channels, the other ends held by the test.
+struct Pipe {
+    sender: mpsc::Sender<ClientJsonRpcMessage>,
+    receiver: mpsc::Receiver<ServerJsonRpcMessage>,
+}
+
+struct PipeEnds {
+    sent: mpsc::Receiver<ClientJsonRpcMessage>,
+    incoming: mpsc::Sender<ServerJsonRpcMessage>,
+}
+
+fn pipe(capacity: usize) -> (Pipe, PipeEnds) {
+    let (sender, sent) = mpsc::channel(capacity);
+    let (incoming, receiver) = mpsc::channel(capacity);
+    (Pipe { sender, receiver }, PipeEnds { sent, incoming })
+}
+
+impl Transport<RoleClient> for Pipe {
+    type Error = mpsc::error::SendError<ClientJsonRpcMessage>;
+
+    fn send(
+        &mut self,
+        item: ClientJsonRpcMessage,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let sender = self.sender.clone();
+        async move { sender.send(item).await }
+    }
+
+    fn receive(&mut self) -> impl Future<Output = Option<ServerJsonRpcMessage>> + Send {
+        self.receiver.recv()
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.receiver.close();
+        Ok(())
+    }
+}
+
+fn notification<M: serde::de::DeserializeOwned>(n: u32) -> M {
+    serde_json::from_value(serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/custom",
+        "params": { "n": n },
+    }))
+    .expect("a valid notification")
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_timeouts() {
+    let (inner, _ends) = pipe(1);
+    let mut transport =
+        inner.with_timeouts(Some(Duration::from_secs(5)), Some(Duration::from_secs(1)));
+
+    transport.send(notification(1)).await.expect("room for one");
+    let error = transport
+        .send(notification(2))
+        .await
+        .expect_err("a full pipe");
+    assert_eq!(error.to_string(), "send timed out after 1s");
+
+    let start = Instant::now();
+    assert!(transport.receive().await.is_none());
+    assert_eq!(start.elapsed(), Duration::from_secs(5));
+    assert!(matches!(
+        Transport::<RoleClient>::close_reason(&transport),
+        Some(CloseReason::Failed(_))
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttle() {
+    let (inner, mut ends) = pipe(8);
+    let mut transport = inner.throttled(100);
+    let len = serde_json::to_vec(&notification::<ClientJsonRpcMessage>(1))
+        .unwrap()
+        .len();
+    let through = |messages: usize| Duration::from_secs_f64((messages * len) as f64 / 100.0);
+
+    let start = Instant::now();
+    let first = transport.send(notification(1));
+    let second = transport.send(notification(2));
+    second.await.unwrap();
+    // queued behind the first one
+    assert!(start.elapsed() >= through(2));
+    first.await.unwrap();
+    assert!(ends.sent.recv().await.is_some());
+
+    let start = Instant::now();
+    ends.incoming.send(notification(1)).await.unwrap();
+    assert!(transport.receive().await.is_some());
+    assert!(start.elapsed() >= through(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_faults() {
+    // everything dropped
+    let (inner, mut ends) = pipe(8);
+    let mut transport = inner.with_faults(Faults::default().with_drop(1.0));
+    transport.send(notification(1)).await.unwrap();
+    for n in 0..3 {
+        ends.incoming.send(notification(n)).await.unwrap();
+    }
+    drop(ends.incoming);
+    assert!(transport.receive().await.is_none());
+    assert!(Transport::<RoleClient>::close_reason(&transport).is_none());
+    drop(transport);
+    assert!(ends.sent.recv().await.is_none());
+
+    // everything delayed, by up to 10s
+    let (inner, ends) = pipe(8);
+    let mut transport = FaultyTransport::new(inner, Faults::default())
+        .with_receive_faults(Faults::default().with_delay(1.0, Duration::from_secs(10)))
+        .with_seed(7);
+    let start = Instant::now();
+    ends.incoming.send(notification(1)).await.unwrap();
+    assert!(transport.receive().await.is_some());
+    assert!(start.elapsed() <= Duration::from_secs(10));
+
+    // everything corrupted: changed, or no longer a message
+    for seed in 0..32 {
+        let (inner, ends) = pipe(8);
+        let mut transport =
+            FaultyTransport::new(inner, Faults::default().with_corrupt(1.0)).with_seed(seed);
+        let original: ServerJsonRpcMessage = notification(1);
+        ends.incoming.send(original.clone()).await.unwrap();
+        match transport.receive().await {
+            Some(message) => assert_ne!(
+                serde_json::to_value(message).unwrap(),
+                serde_json::to_value(&original).unwrap()
+            ),
+            None => assert!(matches!(
+                Transport::<RoleClient>::close_reason(&transport),
+                Some(CloseReason::ProtocolError { .. })
+            )),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//cargo test --test test_usage_recorder --features "server client macros"
+use rmcp::{
+    ErrorData, ServerHandler, ServiceExt,
+    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    model::{CallToolRequestParams, CallToolResult, Content, object},
+    service::{ToolUsage, UsageRecorder, UsageService},
+    tool, tool_handler, tool_router,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DivideRequest {
+    pub a: i64,
+    pub b: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Calculator {
+    tool_router: ToolRouter<Self>,
+}
+
+impl Default for Calculator {
+    fn default() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+        }
+    }
+}
+
+#[tool_router]
+impl Calculator {
+    #[tool]
+    async fn divide(
+        &self,
+        Parameters(request): Parameters<DivideRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        Ok(match request.a.checked_div(request.b) {
+            Some(quotient) => CallToolResult::success(vec![Content::text(quotient.to_string())]),
+            None => CallToolResult::error(vec![Content::text("division by zero")]),
+        })
+    }
+
+    #[tool]
+    async fn pi(&self) -> String {
+        "3.14".to_owned()
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Calculator {}
+
+fn divide(arguments: serde_json::Value) -> CallToolRequestParams {
+    CallToolRequestParams {
+        meta: None,
+        name: "divide".into(),
+        arguments: Some(object(arguments)),
+        task: None,
+    }
+}
+
+#[tokio::test]
+async fn test_usage_recorder() -> anyhow::Result<()> {
+    let recorder = UsageRecorder::new();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(
+        UsageService::new(Calculator::default(), recorder.clone()).serve(server_transport),
+    );
+    let client = ().serve(client_transport).await?;
+    let server = server.await??;
+
+    client.list_all_tools().await?;
+    client.list_all_tools().await?;
+    client.call_tool(divide(json!({"a": 6, "b": 3}))).await?;
+    client.call_tool(divide(json!({"a": 6, "b": 0}))).await?;
+    client
+        .call_tool(divide(json!({"dividend": 6, "divisor": 3})))
+        .await
+        .unwrap_err();
+    client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: "sqrt".into(),
+            arguments: None,
+            task: None,
+        })
+        .await
+        .unwrap_err();
+
+    let summary = recorder.summary();
+    assert_eq!(summary.list_requests, 2);
+    assert_eq!(
+        summary.tools["divide"],
+        ToolUsage {
+            listed: 2,
+            called: 3,
+            invalid_arguments: 1,
+            failed: 0,
+            tool_errors: 1,
+        }
+    );
+    assert_eq!(recorder.tool("pi").listed, 2);
+    assert_eq!(recorder.tool("pi").call_rate(), 0.0);
+    assert_eq!(recorder.tool("sqrt").called, 1);
+
+    let divide = recorder.tool("divide");
+    assert_eq!(divide.call_rate(), 1.5);
+    assert_eq!(divide.invalid_argument_rate(), 1.0 / 3.0);
+    assert_eq!(divide.error_rate(), 2.0 / 3.0);
+
+    recorder.reset();
+    assert_eq!(recorder.summary(), Default::default());
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
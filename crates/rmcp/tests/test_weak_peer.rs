@@ -0,0 +1,68 @@
+//cargo test --test test_weak_peer --features "client server"
+
+use std::time::Duration;
+
+use rmcp::{ServerHandler, ServiceExt};
+
+struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_weak_peer_stops_upgrading_after_close() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        async { anyhow::Ok(Server.serve(server_transport).await?) },
+        async { anyhow::Ok(().serve(client_transport).await?) },
+    )?;
+
+    let weak = server.peer().downgrade();
+    let peer = weak.upgrade().expect("session is running");
+    assert!(!weak.is_closed());
+    peer.notify_tool_list_changed().await?;
+    drop(peer);
+
+    // a background task that lives as long as the session
+    let watcher = tokio::spawn({
+        let weak = weak.clone();
+        async move {
+            let mut sent = 0usize;
+            loop {
+                let Some(peer) = weak.upgrade() else {
+                    break sent;
+                };
+                if peer.notify_resource_list_changed().await.is_ok() {
+                    sent += 1;
+                }
+                drop(peer);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+    });
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    client.cancel().await?;
+    let server_peer = server.peer().clone();
+    tokio::time::timeout(Duration::from_secs(5), server_peer.closed()).await?;
+
+    let sent = tokio::time::timeout(Duration::from_secs(5), watcher).await??;
+    assert!(sent > 0);
+    assert!(weak.upgrade().is_none());
+    assert!(weak.is_closed());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_weak_peer_does_not_keep_session_alive() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        async { anyhow::Ok(Server.serve(server_transport).await?) },
+        async { anyhow::Ok(().serve(client_transport).await?) },
+    )?;
+
+    let weak = client.peer().downgrade();
+    server.cancel().await?;
+    tokio::time::timeout(Duration::from_secs(5), client.waiting()).await??;
+    assert!(weak.upgrade().is_none());
+    Ok(())
+}
@@ -43,7 +43,7 @@ impl ClientHandler for SamplingDemoClient {
         Ok(CreateMessageResult {
             message: SamplingMessage {
                 role: Role::Assistant,
-                content: Content::text(response_text),
+                content: Content::text(response_text).into(),
             },
             model: "mock_llm".to_string(),
             stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.to_string()),
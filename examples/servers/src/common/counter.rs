@@ -107,7 +107,7 @@ impl Counter {
         )]))
     }
 
-    #[tool(description = "Long running task example")]
+    #[tool(description = "Long running task example", task)]
     async fn long_task(&self) -> Result<CallToolResult, McpError> {
         tokio::time::sleep(std::time::Duration::from_secs(10)).await;
         Ok(CallToolResult::success(vec![Content::text(
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use rmcp::transport::streamable_http_server::{
     StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
 };
@@ -10,6 +12,7 @@ mod common;
 use common::counter::Counter;
 
 const BIND_ADDRESS: &str = "127.0.0.1:8000";
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(10);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -31,13 +34,36 @@ async fn main() -> anyhow::Result<()> {
         },
     );
 
-    let router = axum::Router::new().nest_service("/mcp", service);
+    let router = axum::Router::new().nest_service("/mcp", service.clone());
     let tcp_listener = tokio::net::TcpListener::bind(BIND_ADDRESS).await?;
     let _ = axum::serve(tcp_listener, router)
         .with_graceful_shutdown(async move {
-            tokio::signal::ctrl_c().await.unwrap();
+            shutdown_signal().await;
+            // tell connected clients we're going away and give them a moment
+            // to finish before the remaining sessions are closed
+            service.shutdown(SHUTDOWN_GRACE_PERIOD).await;
             ct.cancel();
         })
         .await;
     Ok(())
 }
+
+/// Resolves on Ctrl-C, or on SIGTERM where available.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.unwrap();
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .unwrap()
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
@@ -53,7 +53,7 @@ impl ServerHandler for SamplingDemoServer {
                         task: None,
                         messages: vec![SamplingMessage {
                             role: Role::User,
-                            content: Content::text(question),
+                            content: Content::text(question).into(),
                         }],
                         model_preferences: Some(ModelPreferences {
                             hints: Some(vec![ModelHint {
@@ -125,6 +125,7 @@ impl ServerHandler for SamplingDemoServer {
                 output_schema: None,
                 annotations: None,
                 icons: None,
+                execution: None,
                 meta: None,
             }],
             meta: None,